@@ -6,7 +6,8 @@
  */
 
 use std::env;
-use std::fs::File;
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -30,11 +31,40 @@ fn get_output_path() -> Result<PathBuf, std::env::VarError> {
         None => {
             // When building with Cargo, this env var is the containing directory of the artifact
             let out_dir = env::var("OUT_DIR")?;
-            Ok(Path::new(&out_dir).join("typeshed.tar.zst"))
+            Ok(Path::new(&out_dir).join("typeshed.bundle"))
         }
     }
 }
 
+/// Stubs are compressed one at a time, which compresses less well than the archive as a whole,
+/// so use a higher level than the default to make up for it.
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// The `.pyi` files under `dir`, with their paths relative to `root` and separated by `/`, in a
+/// stable order.
+fn collect_stubs(root: &Path, dir: &Path, res: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|x| x.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_stubs(root, &path, res)?;
+        } else if path.extension().is_some_and(|x| x == "pyi") {
+            let relative = path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .map(|x| x.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            res.push((relative, path));
+        }
+    }
+    Ok(())
+}
+
+/// Write the archive read by `lib.rs`: an index of the stubs, then each stub compressed on its
+/// own. See `BUNDLED_TYPESHED_BYTES` for the layout.
 fn main() -> Result<(), std::io::Error> {
     // Only watch for metadata changes to avoid having Cargo repeatedly crawling for
     // changes in the entire typeshed dir.
@@ -42,11 +72,19 @@ fn main() -> Result<(), std::io::Error> {
 
     let input_path = get_input_path();
     let output_path = get_output_path().unwrap();
-    let output_file = File::create(output_path)?;
-    let encoder = zstd::stream::write::Encoder::new(output_file, 0)?;
-    let mut tar = tar::Builder::new(encoder);
-    tar.append_dir_all("", input_path)?;
-    let encoder = tar.into_inner()?;
-    encoder.finish()?;
-    Ok(())
+    let mut stubs = Vec::new();
+    collect_stubs(&input_path, &input_path, &mut stubs)?;
+    let mut index = Vec::new();
+    let mut contents = Vec::new();
+    index.extend((stubs.len() as u32).to_le_bytes());
+    for (relative, path) in stubs {
+        let stub = fs::read_to_string(&path)?;
+        let compressed = zstd::bulk::compress(stub.as_bytes(), COMPRESSION_LEVEL)?;
+        index.extend((relative.len() as u32).to_le_bytes());
+        index.extend(relative.as_bytes());
+        index.extend((compressed.len() as u32).to_le_bytes());
+        contents.extend(compressed);
+    }
+    index.extend(contents);
+    fs::write(output_path, index)
 }
@@ -6,15 +6,21 @@
  */
 
 use std::env;
-use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
 
 use anyhow::Context as _;
 use starlark_map::small_map::SmallMap;
-use tar::Archive;
-use zstd::stream::read::Decoder;
 
-const BUNDLED_TYPESHED_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/typeshed.tar.zst"));
+/// The bundled stubs, written by `build.rs`. It starts with an index: a little-endian `u32`
+/// count of stubs, then for each stub the `u32` length of its path, the path (relative to the
+/// root of typeshed, separated by `/`), and the `u32` length of its compressed contents. The
+/// contents of each stub follow, compressed on their own and in the order of the index, so that
+/// loading a module only decompresses its stub.
+const BUNDLED_TYPESHED_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/typeshed.bundle"));
 
 #[derive(PartialEq)]
 enum PathFilter {
@@ -41,27 +47,88 @@ impl PathFilter {
     }
 }
 
-fn extract_pyi_files_from_archive(filter: PathFilter) -> anyhow::Result<SmallMap<PathBuf, String>> {
-    let decoder = Decoder::new(BUNDLED_TYPESHED_BYTES)?;
-    let mut archive = Archive::new(decoder);
-    let entries = archive
-        .entries()
-        .context("Cannot query all entries in typeshed archive")?;
+/// The stubs of the bundled archive. Each stub is only decompressed the first time it is loaded,
+/// so that we don't pay for the thousands of stubs a project never imports.
+#[derive(Debug, Clone)]
+pub struct BundledStubs {
+    /// Where the compressed contents of each stub are in the archive, and the contents once
+    /// loaded.
+    stubs: SmallMap<PathBuf, (Range<usize>, OnceLock<Arc<String>>)>,
+}
 
-    let mut items = SmallMap::new();
+impl BundledStubs {
+    pub fn len(&self) -> usize {
+        self.stubs.len()
+    }
 
-    for maybe_entry in entries {
-        let mut entry = maybe_entry.context("Cannot read individual entry in typeshed archive")?;
+    pub fn is_empty(&self) -> bool {
+        self.stubs.is_empty()
+    }
 
-        if entry.header().entry_type().is_dir() {
-            continue;
-        }
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.stubs.keys()
+    }
+
+    /// The contents of the stub at `path`, relative to the root of the stubs.
+    pub fn get(&self, path: &Path) -> Option<&Arc<String>> {
+        let (range, contents) = self.stubs.get(path)?;
+        Some(contents.get_or_init(|| {
+            // The build script checked that every stub is UTF-8 and compressed it, so this can
+            // only fail if the binary itself is corrupt.
+            let contents = zstd::stream::decode_all(&BUNDLED_TYPESHED_BYTES[range.clone()])
+                .and_then(|x| String::from_utf8(x).map_err(std::io::Error::other))
+                .unwrap_or_else(|e| {
+                    panic!("Cannot decompress bundled stub `{}`: {e}", path.display())
+                });
+            Arc::new(contents)
+        }))
+    }
+
+    /// All the stubs, loading any that haven't been loaded yet.
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Arc<String>)> {
+        self.stubs
+            .keys()
+            .map(|path| (path, self.get(path).unwrap()))
+    }
+}
+
+/// Read a little-endian `u32` from the index of the archive at `pos`, and move past it.
+fn read_u32(pos: &mut usize) -> anyhow::Result<u32> {
+    let bytes = BUNDLED_TYPESHED_BYTES
+        .get(*pos..*pos + 4)
+        .context("Truncated typeshed archive index")?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn extract_pyi_files_from_archive(filter: PathFilter) -> anyhow::Result<BundledStubs> {
+    let mut pos = 0;
+    let count = read_u32(&mut pos)?;
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let path_len = read_u32(&mut pos)? as usize;
+        let path = BUNDLED_TYPESHED_BYTES
+            .get(pos..pos + path_len)
+            .context("Truncated typeshed archive index")?;
+        let path = std::str::from_utf8(path).context("Cannot read path of archive entry")?;
+        pos += path_len;
+        let size = read_u32(&mut pos)? as usize;
+        entries.push((path, size));
+    }
 
-        let relative_path_context = entry
-            .path()
-            .context("Cannot extract path from archive entry")?;
+    let mut stubs = SmallMap::new();
+    // The contents start right after the index.
+    let mut start = pos;
+    for (path, size) in entries {
+        let range = start..start + size;
+        start += size;
+        if range.end > BUNDLED_TYPESHED_BYTES.len() {
+            return Err(anyhow::anyhow!(
+                "Cannot read content of archive entry `{path}`"
+            ));
+        }
 
-        let mut relative_path_components = relative_path_context.components();
+        let mut relative_path_components = Path::new(path).components();
 
         let first_component = relative_path_components.next();
         if first_component
@@ -83,27 +150,23 @@ fn extract_pyi_files_from_archive(filter: PathFilter) -> anyhow::Result<SmallMap
         };
 
         if relative_path.extension().is_none_or(|ext| ext != "pyi") {
-            // typeshed/stdlib/ contains non-.pyi files like VERSIONS that we don't care about.
             continue;
         }
 
-        let size = entry.size();
-        let mut contents = String::with_capacity(size as usize);
-        entry
-            .read_to_string(&mut contents)
-            .context("Cannot read content of archive entry")?;
-        items.entry(relative_path).or_insert(contents);
+        stubs
+            .entry(relative_path)
+            .or_insert((range, OnceLock::new()));
     }
 
-    Ok(items)
+    Ok(BundledStubs { stubs })
 }
 
-pub fn bundled_typeshed() -> anyhow::Result<SmallMap<PathBuf, String>> {
+pub fn bundled_typeshed() -> anyhow::Result<BundledStubs> {
     extract_pyi_files_from_archive(PathFilter::Stdlib)
 }
 
 #[allow(dead_code)]
-pub fn bundled_third_party_stubs() -> anyhow::Result<SmallMap<PathBuf, String>> {
+pub fn bundled_third_party_stubs() -> anyhow::Result<BundledStubs> {
     extract_pyi_files_from_archive(PathFilter::ThirdPartyStubs)
 }
 
@@ -149,6 +212,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bundled_typeshed_loads_each_stub_once() {
+        let files = bundled_typeshed().unwrap();
+        let path = Path::new("builtins.pyi");
+        let contents = files.get(path).unwrap();
+        assert!(contents.contains("class object"));
+        assert!(Arc::ptr_eq(contents, files.get(path).unwrap()));
+        assert!(files.get(Path::new("not_a_module.pyi")).is_none());
+    }
+
     #[test]
     fn test_bundled_typeshed_paths_are_relative() {
         let result = bundled_typeshed().unwrap();
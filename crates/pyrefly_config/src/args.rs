@@ -16,6 +16,8 @@ use pyrefly_util::absolutize::Absolutize as _;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::display;
 
+use crate::base::GeneratedCodePolicy;
+use crate::base::ReexportMode;
 use crate::base::UntypedDefBehavior;
 use crate::config::ConfigFile;
 use crate::config::validate_path;
@@ -94,6 +96,17 @@ pub struct ConfigOverrideArgs {
     #[arg(long)]
     typeshed_path: Option<PathBuf>,
 
+    /// A JSON manifest of the third-party modules that may be imported and the directories they
+    /// are found in. Resolves imports hermetically, without querying an interpreter or scanning
+    /// site packages.
+    #[arg(long, value_name = "MANIFEST_PATH")]
+    dependency_manifest: Option<PathBuf>,
+
+    /// A JSON file giving deprecated symbols the date or version they are removed in, after which
+    /// using them is an error.
+    #[arg(long, value_name = "POLICY_PATH")]
+    deprecation_policy: Option<PathBuf>,
+
     /// Always replace specified imports with typing.Any, suppressing related import errors even if the module is found.
     #[arg(long)]
     replace_imports_with_any: Option<Vec<String>>,
@@ -117,6 +130,10 @@ pub struct ConfigOverrideArgs {
         num_args = 0..=1
     )]
     ignore_errors_in_generated_code: Option<bool>,
+    /// How to treat files marked as generated code: check them as normal, report their errors as
+    /// warnings, or ignore their errors entirely.
+    #[arg(long)]
+    generated_code_policy: Option<GeneratedCodePolicy>,
     /// If this is true, infer type variables not determined by a call or constructor based on their first usage.
     /// For example, the type of an empty container would be determined by the first thing you put into it.
     /// If this is false, any unsolved type variables at the end of a call or constructor will be replaced with `Any`.
@@ -128,6 +145,31 @@ pub struct ConfigOverrideArgs {
         num_args = 0..=1
     )]
     infer_with_first_use: Option<bool>,
+    /// If this is true, use the literal values of module-level constants imported from other modules,
+    /// such as `if settings.DEBUG:`, to decide which branches are reachable.
+    /// Defaults to false.
+    #[arg(
+        long,
+        default_missing_value = "true",
+        require_equals = true,
+        num_args = 0..=1
+    )]
+    propagate_constants: Option<bool>,
+    /// If this is true, give the unannotated parameters of functions in installed packages without
+    /// stubs or a `py.typed` marker the type of their default value, rather than that type unioned
+    /// with `Any`. Defaults to false.
+    #[arg(
+        long,
+        default_missing_value = "true",
+        require_equals = true,
+        num_args = 0..=1
+    )]
+    infer_untyped_package_signatures: Option<bool>,
+    /// Which names imported into a module can be imported from it: any of them (`implicit`), or
+    /// only those re-exported with `import x as x` or listed in `__all__` (`explicit`).
+    /// Defaults to implicit.
+    #[arg(long)]
+    reexport: Option<ReexportMode>,
     /// Whether to respect ignore files (.gitignore, .ignore, .git/exclude).
     #[arg(
         long,
@@ -147,6 +189,11 @@ pub struct ConfigOverrideArgs {
         num_args = 0..=1
     )]
     permissive_ignores: Option<bool>,
+    /// Check the public API of a library: report public functions that are missing annotations or
+    /// mention private types, and packages without a `py.typed` marker. Severities set in the
+    /// configuration file, or by `--error`, `--warn` and `--ignore`, take precedence.
+    #[arg(long)]
+    library_mode: bool,
     /// Force this rule to emit an error. Can be used multiple times.
     #[arg(long, hide_possible_values = true)]
     error: Vec<ErrorKind>,
@@ -240,6 +287,12 @@ impl ConfigOverrideArgs {
         if let Some(x) = &self.typeshed_path {
             config.typeshed_path = Some(x.clone());
         }
+        if let Some(x) = &self.dependency_manifest {
+            config.dependency_manifest = Some(x.clone());
+        }
+        if let Some(x) = &self.deprecation_policy {
+            config.deprecation_policy = Some(x.clone());
+        }
         if let Some(x) = &self.use_ignore_files {
             config.use_ignore_files = *x;
         }
@@ -271,10 +324,27 @@ impl ConfigOverrideArgs {
         if let Some(x) = &self.ignore_errors_in_generated_code {
             config.root.ignore_errors_in_generated_code = Some(*x);
         }
+        if let Some(x) = &self.generated_code_policy {
+            config.root.generated_code_policy = Some(*x);
+        }
         if let Some(x) = &self.infer_with_first_use {
             config.root.infer_with_first_use = Some(*x);
         }
+        if let Some(x) = &self.propagate_constants {
+            config.root.propagate_constants = Some(*x);
+        }
+        if let Some(x) = &self.infer_untyped_package_signatures {
+            config.root.infer_untyped_package_signatures = Some(*x);
+        }
+        if let Some(x) = &self.reexport {
+            config.root.reexport = Some(*x);
+        }
         let apply_error_settings = |error_config: &mut ErrorDisplayConfig| {
+            if self.library_mode {
+                for error_kind in ErrorKind::LIBRARY_MODE {
+                    error_config.set_default_error_severity(error_kind, Severity::Error);
+                }
+            }
             let mut missing_source_severity = None;
             let mut apply_severity = |error_kind: &ErrorKind, severity| {
                 error_config.set_error_severity(*error_kind, severity);
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
+
 use clap::ValueEnum;
 use serde::Deserialize;
 use serde::Serialize;
@@ -23,6 +25,57 @@ pub enum UntypedDefBehavior {
     SkipAndInferReturnAny,
 }
 
+/// What to do with errors in generated code.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Default)]
+#[derive(ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeneratedCodePolicy {
+    /// Check generated code like any other code.
+    #[default]
+    Check,
+    /// Report errors in generated code as warnings, and never add suppressions for them.
+    Warn,
+    /// Don't report errors in generated code at all.
+    Ignore,
+}
+
+/// Which names imported into a module can be imported from it by other modules.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Default)]
+#[derive(ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReexportMode {
+    /// Any name imported into a module can be imported from it.
+    #[default]
+    Implicit,
+    /// Only names re-exported with a redundant alias, as in `from m import x as x`, or listed in
+    /// `__all__` can be imported from a module.
+    Explicit,
+}
+
+/// Python code embedded in other code, e.g. in a string literal, that can be extracted and
+/// type checked.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbeddedCodeKind {
+    /// An expression in a string literal passed to `eval`, e.g. `eval("x + 1")`.
+    Eval,
+}
+
+/// A framework that calls functions itself, passing values to their parameters that can be
+/// worked out from the code.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParameterProviderKind {
+    /// Tests and fixtures in pytest test files, which are given fixtures and the values of
+    /// `@pytest.mark.parametrize`.
+    Pytest,
+    /// Parameters whose default is `Depends(f)` or `Security(f)`, which are given the value
+    /// returned by `f`.
+    Fastapi,
+    /// Parameters declared with `@click.option` or `@click.argument`.
+    Click,
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct ConfigBase {
@@ -75,11 +128,59 @@ pub struct ConfigBase {
     )]
     pub ignore_errors_in_generated_code: Option<bool>,
 
+    /// What to do with errors in generated code. Takes precedence over
+    /// `ignore-errors-in-generated-code`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_code_policy: Option<GeneratedCodePolicy>,
+
+    /// Strings that mark a file as generated code. By default this is `@` immediately followed by
+    /// `generated`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_code_markers: Option<Vec<String>>,
+
+    /// Only look for generated code markers in this many lines at the start of a file.
+    /// By default the whole file is searched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_code_marker_lines: Option<usize>,
+
     /// Whether to infer empty container types as Any instead of creating type variables.
     /// By default this is enabled.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub infer_with_first_use: Option<bool>,
 
+    /// Whether to use the literal values of module-level constants in other modules, such as
+    /// `DEBUG = False` in a settings module, when deciding which branches of an `if` are reachable.
+    /// By default this is disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagate_constants: Option<bool>,
+
+    /// Which names imported into a module other modules can import from it. By default any of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reexport: Option<ReexportMode>,
+
+    /// Which kinds of Python code embedded in other code to extract and type check.
+    /// By default none are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded_code: Option<Vec<EmbeddedCodeKind>>,
+
+    /// Which frameworks to work out the values of parameters for, when they call functions
+    /// themselves. By default only pytest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter_providers: Option<Vec<ParameterProviderKind>>,
+
+    /// Functions that create base classes, as in `class C(make_base(config))`, mapped to the
+    /// class that the classes they create inherit from. Both are given by their fully qualified
+    /// names, such as `mylib.make_base`. By default none are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_class_factories: Option<BTreeMap<String, String>>,
+
+    /// Whether to give the unannotated parameters of functions in installed packages without
+    /// stubs or a `py.typed` marker the type of their default value, rather than that type
+    /// unioned with `Any`. This is more precise but may reject calls that work at runtime.
+    /// By default this is disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infer_untyped_package_signatures: Option<bool>,
+
     /// Any unknown config items
     #[serde(default, flatten)]
     pub(crate) extras: ExtraConfigs,
@@ -130,7 +231,43 @@ impl ConfigBase {
         base.ignore_errors_in_generated_code
     }
 
+    pub fn get_generated_code_policy(base: &Self) -> Option<GeneratedCodePolicy> {
+        base.generated_code_policy
+    }
+
+    pub fn get_generated_code_markers(base: &Self) -> Option<&[String]> {
+        base.generated_code_markers.as_deref()
+    }
+
+    pub fn get_generated_code_marker_lines(base: &Self) -> Option<usize> {
+        base.generated_code_marker_lines
+    }
+
     pub fn get_infer_with_first_use(base: &Self) -> Option<bool> {
         base.infer_with_first_use
     }
+
+    pub fn get_propagate_constants(base: &Self) -> Option<bool> {
+        base.propagate_constants
+    }
+
+    pub fn get_reexport(base: &Self) -> Option<ReexportMode> {
+        base.reexport
+    }
+
+    pub fn get_embedded_code(base: &Self) -> Option<&[EmbeddedCodeKind]> {
+        base.embedded_code.as_deref()
+    }
+
+    pub fn get_parameter_providers(base: &Self) -> Option<&[ParameterProviderKind]> {
+        base.parameter_providers.as_deref()
+    }
+
+    pub fn get_base_class_factories(base: &Self) -> Option<&BTreeMap<String, String>> {
+        base.base_class_factories.as_ref()
+    }
+
+    pub fn get_infer_untyped_package_signatures(base: &Self) -> Option<bool> {
+        base.infer_untyped_package_signatures
+    }
 }
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fmt;
 use std::fmt::Display;
@@ -25,8 +26,10 @@ use pyrefly_build::source_db::SourceDatabase;
 use pyrefly_build::source_db::Target;
 use pyrefly_python::COMPILED_FILE_SUFFIXES;
 use pyrefly_python::PYTHON_EXTENSIONS;
+use pyrefly_python::module::GENERATED_TOKEN;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModulePathDetails;
 use pyrefly_python::sys_info::PythonPlatform;
 use pyrefly_python::sys_info::PythonVersion;
 use pyrefly_python::sys_info::SysInfo;
@@ -43,13 +46,22 @@ use starlark_map::small_set::SmallSet;
 use tracing::debug;
 
 use crate::base::ConfigBase;
+use crate::base::EmbeddedCodeKind;
+use crate::base::GeneratedCodePolicy;
+use crate::base::ParameterProviderKind;
+use crate::base::ReexportMode;
 use crate::base::UntypedDefBehavior;
+use crate::dependency_manifest::DependencyManifest;
+use crate::deprecation_policy::DeprecationPolicy;
 use crate::environment::environment::PythonEnvironment;
 use crate::environment::interpreters::Interpreters;
 use crate::error::ErrorConfig;
 use crate::error::ErrorDisplayConfig;
+use crate::error::GeneratedCode;
 use crate::finder::ConfigError;
+use crate::import_boundaries::ImportBoundaries;
 use crate::module_wildcard::Match;
+use crate::module_wildcard::ModuleWildcard;
 use crate::pyproject::PyProject;
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
@@ -194,6 +206,7 @@ pub enum ImportLookupPathPart<'a> {
     SearchPathFromFile(&'a [PathBuf]),
     ImportRoot(Option<&'a PathBuf>),
     FallbackSearchPath(&'a [PathBuf]),
+    DependencyManifest(Option<&'a PathBuf>),
     SitePackagePath(&'a [PathBuf]),
     InterpreterSitePackagePath(&'a [PathBuf]),
     BuildSystem(Option<Target>),
@@ -216,6 +229,13 @@ impl Display for ImportLookupPathPart<'_> {
                 f,
                 "Fallback search path (guessed from project_includes): {fallback:?}"
             ),
+            Self::DependencyManifest(Some(path)) => {
+                write!(
+                    f,
+                    "Third-party modules listed in dependency manifest: {path:?}"
+                )
+            }
+            Self::DependencyManifest(None) => write!(f, "Dependency manifest: None"),
             Self::SitePackagePath(paths) => {
                 write!(f, "Site package path from user: {paths:?}")
             }
@@ -242,6 +262,7 @@ impl ImportLookupPathPart<'_> {
             | Self::InterpreterSitePackagePath(paths) => paths.is_empty(),
             Self::ImportRoot(root) => root.is_none(),
             Self::FallbackSearchPath(inner) => inner.is_empty(),
+            Self::DependencyManifest(path) => path.is_none(),
             Self::BuildSystem(_) => false,
         }
     }
@@ -323,6 +344,25 @@ pub struct ConfigFile {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub typeshed_path: Option<PathBuf>,
 
+    /// A JSON manifest of the third-party modules that may be imported and where to find them.
+    /// When set, imports are resolved hermetically: the interpreter is not queried, no site
+    /// packages are scanned, and only the listed modules can be imported from outside the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependency_manifest: Option<PathBuf>,
+
+    /// The contents of `dependency_manifest`, loaded in `ConfigFile::configure()`.
+    #[serde(skip, default)]
+    pub loaded_dependency_manifest: Option<Arc<DependencyManifest>>,
+
+    /// A JSON file giving deprecated symbols the date or version they are removed in. Once a
+    /// symbol is removed, using it is always an error rather than a `deprecated` warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation_policy: Option<PathBuf>,
+
+    /// The contents of `deprecation_policy`, loaded in `ConfigFile::configure()`.
+    #[serde(skip, default)]
+    pub loaded_deprecation_policy: Option<Arc<DeprecationPolicy>>,
+
     /// Pyrefly's configurations around interpreter querying/finding.
     #[serde(flatten)]
     pub interpreters: Interpreters,
@@ -383,6 +423,39 @@ pub struct ConfigFile {
     /// may speed up LSP operations on large projects.
     #[serde(default, skip_serializing_if = "crate::util::skip_default_false")]
     pub skip_lsp_config_indexing: bool,
+
+    /// Modules that are part of the project wherever they are found, e.g. packages installed in
+    /// site packages by an editable install.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) first_party_modules: Vec<ModuleWildcard>,
+
+    /// Modules that aren't part of the project wherever they are found, e.g. vendored packages.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) third_party_modules: Vec<ModuleWildcard>,
+
+    /// Files generated by tools rather than written by hand. Their errors are handled according to
+    /// `generated-code-policy`, as if they were marked as generated code.
+    #[serde(default, skip_serializing_if = "Globs::is_empty")]
+    pub generated_files: Globs,
+
+    /// Which modules of the project may import which: higher layers may import lower ones but not
+    /// the other way around, and private packages may only be imported from within their parent.
+    #[serde(default, skip_serializing_if = "ImportBoundaries::is_empty")]
+    pub import_boundaries: ImportBoundaries,
+}
+
+/// Where a module comes from, which decides how features treat it, e.g. which block of imports an
+/// import of it goes in. Ordered from the most to the least relevant to the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModuleOrigin {
+    /// The code of the project.
+    FirstParty,
+    /// The standard library.
+    Stdlib,
+    /// Installed packages and stubs.
+    ThirdParty,
+    /// Code of the project generated by tools, as configured by `generated-files`.
+    Generated,
 }
 
 impl Default for ConfigFile {
@@ -411,7 +484,15 @@ impl Default for ConfigFile {
             use_ignore_files: true,
             ignore_missing_source: true,
             typeshed_path: None,
+            dependency_manifest: None,
+            loaded_dependency_manifest: None,
+            deprecation_policy: None,
+            loaded_deprecation_policy: None,
             skip_lsp_config_indexing: false,
+            first_party_modules: Vec::new(),
+            third_party_modules: Vec::new(),
+            generated_files: Default::default(),
+            import_boundaries: Default::default(),
         }
     }
 }
@@ -574,6 +655,9 @@ impl ConfigFile {
                 &self.fallback_search_path,
             ));
         }
+        result.push(ImportLookupPathPart::DependencyManifest(
+            self.dependency_manifest.as_ref(),
+        ));
         result.push(ImportLookupPathPart::SitePackagePath(
             self.python_environment.site_package_path.as_ref().unwrap(),
         ));
@@ -659,6 +743,72 @@ impl ConfigFile {
                  self.root.ignore_errors_in_generated_code.unwrap())
     }
 
+    /// Where the module `module`, found at `path`, comes from. Files matching `generated-files` are
+    /// generated, and `first-party-modules` and `third-party-modules` take precedence over where
+    /// the module was found.
+    pub fn module_origin(&self, module: ModuleName, path: &ModulePath) -> ModuleOrigin {
+        let matches = |wildcards: &[ModuleWildcard]| {
+            wildcards.iter().find_map(|w| match w.matches(module) {
+                Match::Positive => Some(true),
+                Match::Negative => Some(false),
+                Match::NoMatch => None,
+            }) == Some(true)
+        };
+        if self.generated_files.covers(path.as_path()) {
+            return ModuleOrigin::Generated;
+        }
+        if matches(&self.first_party_modules) {
+            return ModuleOrigin::FirstParty;
+        }
+        if matches(&self.third_party_modules) {
+            return ModuleOrigin::ThirdParty;
+        }
+        match path.details() {
+            ModulePathDetails::BundledTypeshed(_) => ModuleOrigin::Stdlib,
+            ModulePathDetails::BundledTypeshedThirdParty(_) => ModuleOrigin::ThirdParty,
+            _ if self
+                .typeshed_path
+                .as_ref()
+                .is_some_and(|x| path.as_path().starts_with(x.join("stdlib"))) =>
+            {
+                ModuleOrigin::Stdlib
+            }
+            _ if self
+                .site_package_path()
+                .any(|x| path.as_path().starts_with(x)) =>
+            {
+                ModuleOrigin::ThirdParty
+            }
+            _ => ModuleOrigin::FirstParty,
+        }
+    }
+
+    fn generated_code(&self, path: &Path) -> GeneratedCode<'_> {
+        let policy = self
+            .get_from_sub_configs(ConfigBase::get_generated_code_policy, path)
+            .or(self.root.generated_code_policy)
+            .unwrap_or_else(|| {
+                if self.ignore_errors_in_generated_code(path) {
+                    GeneratedCodePolicy::Ignore
+                } else {
+                    GeneratedCodePolicy::Check
+                }
+            });
+        GeneratedCode {
+            policy,
+            markers: self
+                .get_from_sub_configs(ConfigBase::get_generated_code_markers, path)
+                .unwrap_or_else(||
+                     // we can use unwrap here, because the value in the root config must
+                     // be set in `ConfigFile::configure()`.
+                     self.root.generated_code_markers.as_deref().unwrap()),
+            marker_lines: self
+                .get_from_sub_configs(ConfigBase::get_generated_code_marker_lines, path)
+                .or(self.root.generated_code_marker_lines),
+            generated_file: self.generated_files.covers(path),
+        }
+    }
+
     pub fn infer_with_first_use(&self, path: &Path) -> bool {
         self.get_from_sub_configs(ConfigBase::get_infer_with_first_use, path)
             .unwrap_or_else(||
@@ -667,6 +817,54 @@ impl ConfigFile {
                  self.root.infer_with_first_use.unwrap())
     }
 
+    pub fn propagate_constants(&self, path: &Path) -> bool {
+        self.get_from_sub_configs(ConfigBase::get_propagate_constants, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.propagate_constants.unwrap())
+    }
+
+    pub fn reexport(&self, path: &Path) -> ReexportMode {
+        self.get_from_sub_configs(ConfigBase::get_reexport, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.reexport.unwrap())
+    }
+
+    pub fn embedded_code(&self, path: &Path) -> &[EmbeddedCodeKind] {
+        self.get_from_sub_configs(ConfigBase::get_embedded_code, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.embedded_code.as_deref().unwrap())
+    }
+
+    pub fn parameter_providers(&self, path: &Path) -> &[ParameterProviderKind] {
+        self.get_from_sub_configs(ConfigBase::get_parameter_providers, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.parameter_providers.as_deref().unwrap())
+    }
+
+    pub fn base_class_factories(&self, path: &Path) -> &BTreeMap<String, String> {
+        self.get_from_sub_configs(ConfigBase::get_base_class_factories, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.base_class_factories.as_ref().unwrap())
+    }
+
+    pub fn infer_untyped_package_signatures(&self, path: &Path) -> bool {
+        self.get_from_sub_configs(ConfigBase::get_infer_untyped_package_signatures, path)
+            .unwrap_or_else(||
+                 // we can use unwrap here, because the value in the root config must
+                 // be set in `ConfigFile::configure()`.
+                 self.root.infer_untyped_package_signatures.unwrap())
+    }
+
     pub fn permissive_ignores(&self, path: &Path) -> bool {
         self.get_from_sub_configs(|x| x.permissive_ignores, path)
             .unwrap_or_else(||
@@ -678,10 +876,11 @@ impl ConfigFile {
     pub fn get_error_config(&self, path: &Path) -> ErrorConfig<'_> {
         ErrorConfig::new(
             self.errors(path),
-            self.ignore_errors_in_generated_code(path),
+            self.generated_code(path),
             self.permissive_ignores(path),
             self.ignore_missing_source,
         )
+        .with_deprecation_policy(self.loaded_deprecation_policy.as_deref())
     }
 
     /// Filter to sub configs whose matches succeed for the given `path`,
@@ -765,7 +964,35 @@ impl ConfigFile {
     pub fn configure(&mut self) -> Vec<ConfigError> {
         let mut configure_errors = Vec::new();
 
-        if self.interpreters.skip_interpreter_query {
+        if let Some(dependency_manifest) = &self.dependency_manifest {
+            match DependencyManifest::from_file(dependency_manifest) {
+                Ok(manifest) => self.loaded_dependency_manifest = Some(Arc::new(manifest)),
+                Err(error) => configure_errors.push(error),
+            }
+            // Third-party modules only come from the manifest, so never look for site packages,
+            // not even the default `./typings`.
+            if self
+                .python_environment
+                .site_package_path
+                .as_ref()
+                .is_some_and(|x| !x.is_empty())
+            {
+                configure_errors.push(anyhow::anyhow!(
+                    "`site-package-path` is ignored because `dependency-manifest` is set"
+                ));
+            }
+            self.python_environment.site_package_path = Some(Vec::new());
+            self.python_environment.interpreter_site_package_path = Vec::new();
+        }
+
+        if let Some(deprecation_policy) = &self.deprecation_policy {
+            match DeprecationPolicy::from_file(deprecation_policy) {
+                Ok(policy) => self.loaded_deprecation_policy = Some(Arc::new(policy)),
+                Err(error) => configure_errors.push(error),
+            }
+        }
+
+        if self.interpreters.skip_interpreter_query || self.dependency_manifest.is_some() {
             self.python_environment.set_empty_to_default();
         } else {
             if self.interpreters.python_interpreter_path.is_some()
@@ -816,10 +1043,38 @@ impl ConfigFile {
             self.root.ignore_errors_in_generated_code = Some(Default::default());
         }
 
+        if self.root.generated_code_markers.is_none() {
+            self.root.generated_code_markers = Some(vec![GENERATED_TOKEN.to_owned()]);
+        }
+
         if self.root.infer_with_first_use.is_none() {
             self.root.infer_with_first_use = Some(true);
         }
 
+        if self.root.propagate_constants.is_none() {
+            self.root.propagate_constants = Some(false);
+        }
+
+        if self.root.reexport.is_none() {
+            self.root.reexport = Some(Default::default());
+        }
+
+        if self.root.embedded_code.is_none() {
+            self.root.embedded_code = Some(Vec::new());
+        }
+
+        if self.root.parameter_providers.is_none() {
+            self.root.parameter_providers = Some(vec![ParameterProviderKind::Pytest]);
+        }
+
+        if self.root.base_class_factories.is_none() {
+            self.root.base_class_factories = Some(BTreeMap::new());
+        }
+
+        if self.root.infer_untyped_package_signatures.is_none() {
+            self.root.infer_untyped_package_signatures = Some(false);
+        }
+
         if self.root.permissive_ignores.is_none() {
             self.root.permissive_ignores = Some(false);
         }
@@ -876,6 +1131,7 @@ impl ConfigFile {
     pub fn rewrite_with_path_to_config(&mut self, config_root: &Path) {
         self.project_includes = self.project_includes.clone().from_root(config_root);
         self.project_excludes = self.project_excludes.clone().from_root(config_root);
+        self.generated_files = self.generated_files.clone().from_root(config_root);
         self.search_path_from_file
             .iter_mut()
             .for_each(|search_root| {
@@ -884,6 +1140,12 @@ impl ConfigFile {
         if let Some(import_root) = &self.import_root {
             self.import_root = Some(import_root.absolutize_from(config_root));
         }
+        if let Some(dependency_manifest) = &self.dependency_manifest {
+            self.dependency_manifest = Some(dependency_manifest.absolutize_from(config_root));
+        }
+        if let Some(deprecation_policy) = &self.deprecation_policy {
+            self.deprecation_policy = Some(deprecation_policy.absolutize_from(config_root));
+        }
         self.python_environment
             .site_package_path
             .iter_mut()
@@ -1120,7 +1382,16 @@ mod tests {
                     ]))),
                     disable_type_errors_in_ide: None,
                     ignore_errors_in_generated_code: Some(true),
+                    generated_code_policy: None,
+                    generated_code_markers: None,
+                    generated_code_marker_lines: None,
                     infer_with_first_use: None,
+                    propagate_constants: None,
+                    reexport: None,
+                    embedded_code: None,
+                    parameter_providers: None,
+                    base_class_factories: None,
+                    infer_untyped_package_signatures: None,
                     replace_imports_with_any: Some(vec![ModuleWildcard::new("fibonacci").unwrap()]),
                     ignore_missing_imports: Some(vec![ModuleWildcard::new("sprout").unwrap()]),
                     untyped_def_behavior: Some(UntypedDefBehavior::CheckAndInferReturnType),
@@ -1137,7 +1408,16 @@ mod tests {
                         ]))),
                         disable_type_errors_in_ide: None,
                         ignore_errors_in_generated_code: Some(false),
+                        generated_code_policy: None,
+                        generated_code_markers: None,
+                        generated_code_marker_lines: None,
                         infer_with_first_use: Some(false),
+                        propagate_constants: None,
+                        reexport: None,
+                        embedded_code: None,
+                        parameter_providers: None,
+                        base_class_factories: None,
+                        infer_untyped_package_signatures: None,
                         replace_imports_with_any: Some(Vec::new()),
                         ignore_missing_imports: Some(Vec::new()),
                         untyped_def_behavior: Some(UntypedDefBehavior::CheckAndInferReturnAny),
@@ -1146,7 +1426,15 @@ mod tests {
                 }],
                 ignore_missing_source: true,
                 typeshed_path: None,
+                dependency_manifest: None,
+                loaded_dependency_manifest: None,
+                deprecation_policy: None,
+                loaded_deprecation_policy: None,
                 skip_lsp_config_indexing: false,
+                first_party_modules: Vec::new(),
+                third_party_modules: Vec::new(),
+                generated_files: Default::default(),
+                import_boundaries: Default::default(),
             }
         );
     }
@@ -1378,7 +1666,15 @@ mod tests {
             }],
             ignore_missing_source: false,
             typeshed_path: None,
+            dependency_manifest: None,
+            loaded_dependency_manifest: None,
+            deprecation_policy: None,
+            loaded_deprecation_policy: None,
             skip_lsp_config_indexing: false,
+            first_party_modules: Vec::new(),
+            third_party_modules: Vec::new(),
+            generated_files: Default::default(),
+            import_boundaries: Default::default(),
         };
 
         let current_dir = std::env::current_dir().unwrap();
@@ -1435,7 +1731,15 @@ mod tests {
             }],
             ignore_missing_source: false,
             typeshed_path: None,
+            dependency_manifest: None,
+            loaded_dependency_manifest: None,
+            deprecation_policy: None,
+            loaded_deprecation_policy: None,
             skip_lsp_config_indexing: false,
+            first_party_modules: Vec::new(),
+            third_party_modules: Vec::new(),
+            generated_files: Default::default(),
+            import_boundaries: Default::default(),
         };
         assert_eq!(config, expected_config);
     }
@@ -1508,7 +1812,16 @@ mod tests {
                 untyped_def_behavior: Some(UntypedDefBehavior::CheckAndInferReturnType),
                 disable_type_errors_in_ide: Some(true),
                 ignore_errors_in_generated_code: Some(false),
+                generated_code_policy: None,
+                generated_code_markers: Some(vec![GENERATED_TOKEN.to_owned()]),
+                generated_code_marker_lines: None,
                 infer_with_first_use: Some(true),
+                propagate_constants: Some(false),
+                reexport: Some(ReexportMode::Implicit),
+                embedded_code: None,
+                parameter_providers: None,
+                base_class_factories: None,
+                infer_untyped_package_signatures: Some(false),
                 extras: Default::default(),
                 permissive_ignores: Some(false),
             },
@@ -1552,6 +1865,20 @@ mod tests {
         // test empty value falls back to next
         assert!(config.ignore_errors_in_generated_code(Path::new("this/is/highest/priority")));
 
+        // test the legacy option picks the policy when no policy is set
+        assert_eq!(
+            config
+                .generated_code(Path::new("this/is/highest/priority"))
+                .policy,
+            GeneratedCodePolicy::Ignore
+        );
+        assert_eq!(
+            config
+                .generated_code(Path::new("this/does/not/match/any"))
+                .policy,
+            GeneratedCodePolicy::Check
+        );
+
         // test no pattern match
         assert!(config.replace_imports_with_any(
             Some(Path::new("this/does/not/match/any")),
@@ -1807,7 +2134,16 @@ mod tests {
                 untyped_def_behavior: Some(UntypedDefBehavior::CheckAndInferReturnType),
                 disable_type_errors_in_ide: Some(true),
                 ignore_errors_in_generated_code: Some(false),
+                generated_code_policy: None,
+                generated_code_markers: None,
+                generated_code_marker_lines: None,
                 infer_with_first_use: Some(true),
+                propagate_constants: Some(false),
+                reexport: Some(ReexportMode::Implicit),
+                embedded_code: None,
+                parameter_providers: None,
+                base_class_factories: None,
+                infer_untyped_package_signatures: Some(false),
                 extras: Default::default(),
                 permissive_ignores: Some(false),
             },
@@ -1838,7 +2174,16 @@ mod tests {
                 untyped_def_behavior: Some(UntypedDefBehavior::CheckAndInferReturnType),
                 disable_type_errors_in_ide: Some(true),
                 ignore_errors_in_generated_code: Some(false),
+                generated_code_policy: None,
+                generated_code_markers: None,
+                generated_code_marker_lines: None,
                 infer_with_first_use: Some(true),
+                propagate_constants: Some(false),
+                reexport: Some(ReexportMode::Implicit),
+                embedded_code: None,
+                parameter_providers: None,
+                base_class_factories: None,
+                infer_untyped_package_signatures: Some(false),
                 extras: Default::default(),
                 permissive_ignores: Some(false),
             },
@@ -1855,4 +2200,47 @@ mod tests {
             ModuleName::from_str("example.path.b")
         ));
     }
+
+    #[test]
+    fn test_module_origin() {
+        let config = ConfigFile {
+            first_party_modules: vec![ModuleWildcard::new("mylib.*").unwrap()],
+            third_party_modules: vec![ModuleWildcard::new("app.vendored.*").unwrap()],
+            generated_files: Globs::new(vec!["/project/app/proto/**".to_owned()]).unwrap(),
+            python_environment: PythonEnvironment {
+                site_package_path: Some(vec![PathBuf::from("/venv/site-packages")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let origin = |module: &str, path: ModulePath| {
+            config.module_origin(ModuleName::from_str(module), &path)
+        };
+        let file = |path: &str| ModulePath::filesystem(PathBuf::from(path));
+        assert_eq!(
+            origin("os", ModulePath::bundled_typeshed(PathBuf::from("os.pyi"))),
+            ModuleOrigin::Stdlib
+        );
+        assert_eq!(
+            origin("requests", file("/venv/site-packages/requests/__init__.py")),
+            ModuleOrigin::ThirdParty
+        );
+        assert_eq!(
+            origin("app.main", file("/project/app/main.py")),
+            ModuleOrigin::FirstParty
+        );
+        assert_eq!(
+            origin("app.proto.user_pb2", file("/project/app/proto/user_pb2.py")),
+            ModuleOrigin::Generated
+        );
+        // The configured modules take precedence over where they are found.
+        assert_eq!(
+            origin("mylib.util", file("/venv/site-packages/mylib/util.py")),
+            ModuleOrigin::FirstParty
+        );
+        assert_eq!(
+            origin("app.vendored.six", file("/project/app/vendored/six.py")),
+            ModuleOrigin::ThirdParty
+        );
+    }
 }
@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A manifest listing the third-party modules a project may import, and the directories they are
+//! found in. When a config uses a manifest, third-party imports are resolved hermetically: the
+//! Python interpreter is never queried and no site packages are scanned, so checks give the same
+//! results in a build sandbox as anywhere else.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::absolutize::Absolutize as _;
+use pyrefly_util::fs_anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyManifest {
+    /// For each top-level module that may be imported, the directories to search for it, in
+    /// priority order. These are laid out like site packages, so a directory may contain the
+    /// package itself, its `-stubs` package, or both. Relative paths are relative to the manifest.
+    pub modules: HashMap<String, Vec<PathBuf>>,
+}
+
+impl DependencyManifest {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let path = path.absolutize();
+        let path = path.as_path();
+        let contents = fs_anyhow::read_to_string(path)?;
+        let mut manifest: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid dependency manifest `{}`", path.display()))?;
+        if let Some(root) = path.parent() {
+            for roots in manifest.modules.values_mut() {
+                for x in roots {
+                    *x = x.absolutize_from(root);
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// The directories to search for `module`, or `None` if its top-level package is not allowed.
+    pub fn roots(&self, module: ModuleName) -> Option<&[PathBuf]> {
+        self.modules
+            .get(module.first_component().as_str())
+            .map(|x| x.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("deps.json");
+        fs_anyhow::write(
+            &path,
+            r#"{"modules": {"requests": ["third-party/requests", "/abs/types-requests"]}}"#,
+        )
+        .unwrap();
+        let manifest = DependencyManifest::from_file(&path).unwrap();
+        assert_eq!(
+            manifest.roots(ModuleName::from_str("requests.adapters")),
+            Some(
+                [
+                    tempdir.path().join("third-party/requests"),
+                    PathBuf::from("/abs/types-requests"),
+                ]
+                .as_slice()
+            )
+        );
+        assert_eq!(manifest.roots(ModuleName::from_str("yaml")), None);
+    }
+}
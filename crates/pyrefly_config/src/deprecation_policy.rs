@@ -0,0 +1,219 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A policy giving deprecated symbols a date or version from which they are removed. Until then,
+//! using a deprecated symbol is a `deprecated` error at its configured severity (a warning by
+//! default); from then on it is always an error, so the last usages can't be forgotten.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context as _;
+use pyrefly_util::fs_anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// When a deprecated symbol is removed. If both are given, whichever comes first applies.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Sunset {
+    /// The date, as `YYYY-MM-DD`, from which the symbol may no longer be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removal_date: Option<String>,
+    /// The version, compared against `current-version`, in which the symbol is removed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removal_version: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DeprecationPolicy {
+    /// The version of the project, which each `removal-version` is compared against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_version: Option<String>,
+    /// For each deprecated symbol, by fully qualified name, when it is removed. The entry for a
+    /// module or class also covers everything in it.
+    #[serde(default)]
+    pub symbols: HashMap<String, Sunset>,
+}
+
+impl DeprecationPolicy {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs_anyhow::read_to_string(path)?;
+        let policy: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid deprecation policy `{}`", path.display()))?;
+        for (symbol, sunset) in &policy.symbols {
+            if let Some(date) = &sunset.removal_date
+                && !is_date(date)
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid deprecation policy `{}`: removal date `{date}` of `{symbol}` is not of the form `YYYY-MM-DD`",
+                    path.display()
+                ));
+            }
+        }
+        Ok(policy)
+    }
+
+    /// The sunset of `symbol`, from its own entry or that of the closest module or class
+    /// containing it.
+    pub fn sunset(&self, symbol: &str) -> Option<&Sunset> {
+        let mut prefix = symbol;
+        loop {
+            if let Some(sunset) = self.symbols.get(prefix) {
+                return Some(sunset);
+            }
+            prefix = prefix.rsplit_once('.')?.0;
+        }
+    }
+
+    /// Whether `symbol` has been removed, so that using it must be an error.
+    pub fn is_removed(&self, symbol: &str) -> bool {
+        self.is_removed_on(symbol, &today())
+    }
+
+    fn is_removed_on(&self, symbol: &str, today: &str) -> bool {
+        let Some(sunset) = self.sunset(symbol) else {
+            return false;
+        };
+        // Dates are validated when loading, so compare as strings.
+        let past_date = sunset
+            .removal_date
+            .as_ref()
+            .is_some_and(|x| x.as_str() <= today);
+        let past_version = match (&sunset.removal_version, &self.current_version) {
+            (Some(removal), Some(current)) => version_key(removal) <= version_key(current),
+            _ => false,
+        };
+        past_date || past_version
+    }
+}
+
+fn is_date(x: &str) -> bool {
+    x.len() == 10
+        && x.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// The numeric components of a version such as `2.10.1`, compared component by component, with
+/// missing components being zero.
+fn version_key(version: &str) -> Vec<u64> {
+    let mut res = version
+        .split('.')
+        .map(|x| {
+            let digits = x.find(|c: char| !c.is_ascii_digit()).unwrap_or(x.len());
+            x[..digits].parse().unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    while res.last() == Some(&0) {
+        res.pop();
+    }
+    res
+}
+
+/// Today's date in UTC, as `YYYY-MM-DD`.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs() / 86400) as i64;
+    civil_date(days)
+}
+
+/// The date `days` days after 1970-01-01, as `YYYY-MM-DD`, using the algorithm from
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_date(days: i64) -> String {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("deprecations.json");
+        fs_anyhow::write(
+            &path,
+            r#"{"current-version": "2.4", "symbols": {"mylib.old": {"removal-version": "3.0"}}}"#,
+        )
+        .unwrap();
+        let policy = DeprecationPolicy::from_file(&path).unwrap();
+        assert_eq!(policy.current_version.as_deref(), Some("2.4"));
+        assert_eq!(
+            policy.sunset("mylib.old"),
+            Some(&Sunset {
+                removal_date: None,
+                removal_version: Some("3.0".to_owned()),
+            })
+        );
+
+        fs_anyhow::write(
+            &path,
+            r#"{"symbols": {"mylib.old": {"removal-date": "next year"}}}"#,
+        )
+        .unwrap();
+        assert!(DeprecationPolicy::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_is_removed() {
+        let sunset = |date: Option<&str>, version: Option<&str>| Sunset {
+            removal_date: date.map(|x| x.to_owned()),
+            removal_version: version.map(|x| x.to_owned()),
+        };
+        let policy = DeprecationPolicy {
+            current_version: Some("2.10".to_owned()),
+            symbols: HashMap::from([
+                ("mylib.legacy".to_owned(), sunset(Some("2025-01-01"), None)),
+                ("mylib.Client.get".to_owned(), sunset(None, Some("2.9"))),
+                ("mylib.Client.put".to_owned(), sunset(None, Some("2.10.0"))),
+                ("mylib.Client.post".to_owned(), sunset(None, Some("2.11"))),
+                (
+                    "mylib.later".to_owned(),
+                    sunset(Some("2030-06-30"), Some("4")),
+                ),
+            ]),
+        };
+        let today = "2026-10-15";
+        assert!(policy.is_removed_on("mylib.legacy", today));
+        assert!(policy.is_removed_on("mylib.legacy.Widget.draw", today));
+        assert!(!policy.is_removed_on("mylib.legacy_tools", today));
+        assert!(policy.is_removed_on("mylib.Client.get", today));
+        assert!(policy.is_removed_on("mylib.Client.put", today));
+        assert!(!policy.is_removed_on("mylib.Client.post", today));
+        assert!(!policy.is_removed_on("mylib.later", today));
+        assert!(policy.is_removed_on("mylib.later", "2030-06-30"));
+        assert!(!policy.is_removed_on("mylib.unlisted", today));
+    }
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(59), "1970-03-01");
+        assert_eq!(civil_date(11016), "2000-02-29");
+        assert_eq!(civil_date(20741), "2026-10-15");
+    }
+}
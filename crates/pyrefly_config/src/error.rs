@@ -13,6 +13,8 @@ use serde::Serialize;
 use serde::de::MapAccess;
 use serde::de::Visitor;
 
+use crate::base::GeneratedCodePolicy;
+use crate::deprecation_policy::DeprecationPolicy;
 use crate::error_kind::ErrorKind;
 use crate::error_kind::Severity;
 
@@ -39,6 +41,11 @@ impl ErrorDisplayConfig {
     pub fn set_error_severity(&mut self, kind: ErrorKind, severity: Severity) {
         self.0.insert(kind, severity);
     }
+
+    /// Sets the severity for the given `ErrorKind`, unless it has already been configured.
+    pub fn set_default_error_severity(&mut self, kind: ErrorKind, severity: Severity) {
+        self.0.entry(kind).or_insert(severity);
+    }
 }
 
 impl<'de> Deserialize<'de> for ErrorDisplayConfig {
@@ -86,26 +93,66 @@ impl<'de> Deserialize<'de> for ErrorDisplayConfig {
     }
 }
 
+/// How to recognize generated code, and what to do with its errors.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct GeneratedCode<'a> {
+    pub policy: GeneratedCodePolicy,
+    /// Strings that mark a file as generated.
+    pub markers: &'a [String],
+    /// Only look for the markers in this many lines at the start of the file.
+    pub marker_lines: Option<usize>,
+    /// Whether the file is configured as generated, whatever its contents.
+    pub generated_file: bool,
+}
+
+impl<'a> GeneratedCode<'a> {
+    pub fn is_generated(&self, contents: &str) -> bool {
+        if self.generated_file {
+            return true;
+        }
+        match self.marker_lines {
+            None => self.markers.iter().any(|x| contents.contains(x.as_str())),
+            Some(n) => contents
+                .lines()
+                .take(n)
+                .any(|line| self.markers.iter().any(|x| line.contains(x.as_str()))),
+        }
+    }
+
+    /// Whether errors in `contents` should be handled differently from other code.
+    pub fn applies_to(&self, contents: &str) -> bool {
+        self.policy != GeneratedCodePolicy::Check && self.is_generated(contents)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ErrorConfig<'a> {
     pub display_config: &'a ErrorDisplayConfig,
-    pub ignore_errors_in_generated_code: bool,
+    pub generated_code: GeneratedCode<'a>,
     pub permissive_ignores: bool,
     pub ignore_missing_source: bool,
+    /// When deprecated symbols are removed, after which using them is an error.
+    pub deprecation_policy: Option<&'a DeprecationPolicy>,
 }
 
 impl<'a> ErrorConfig<'a> {
     pub fn new(
         display_config: &'a ErrorDisplayConfig,
-        ignore_errors_in_generated_code: bool,
+        generated_code: GeneratedCode<'a>,
         permissive_ignores: bool,
         ignore_missing_source: bool,
     ) -> Self {
         Self {
             display_config,
-            ignore_errors_in_generated_code,
+            generated_code,
             permissive_ignores,
             ignore_missing_source,
+            deprecation_policy: None,
         }
     }
+
+    pub fn with_deprecation_policy(mut self, policy: Option<&'a DeprecationPolicy>) -> Self {
+        self.deprecation_policy = policy;
+        self
+    }
 }
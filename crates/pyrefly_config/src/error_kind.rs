@@ -134,6 +134,9 @@ pub enum ErrorKind {
     /// An error caused by unpacking.
     /// e.g. attempting to unpack an iterable into the wrong number of variables.
     BadUnpacking,
+    /// A function or class is redefined before it is used, where one of the definitions is
+    /// conditional, or the two definitions are of different kinds in different branches.
+    ConditionalRedefinition,
     /// Calling a function marked with `@deprecated`
     Deprecated,
     /// Raised when a class implicitly becomes abstract by defining abstract members without
@@ -143,14 +146,24 @@ pub enum ErrorKind {
     ImplicitAny,
     /// Usage of a module that was not actually imported, but does exist.
     ImplicitImport,
+    /// A name was imported from a module that imports it without re-exporting it, when
+    /// `reexport` is `explicit`.
+    ImplicitReexport,
     /// An attribute was implicitly defined by assignment to `self` in a method that we
     /// do not recognize as always executing (we recognize constructors and some test setup
     /// methods).
     ImplicitlyDefinedAttribute,
+    /// An import crossing the import boundaries of the project, e.g. a lower layer importing a
+    /// higher one, as configured by `import-boundaries`.
+    ImportBoundary,
     /// An inconsistency between inherited fields or methods from multiple base classes.
     InconsistentInheritance,
     /// An inconsistency between the signature of a function overload and the implementation.
     InconsistentOverload,
+    /// A function mixes `return value` with bare `return` statements.
+    InconsistentReturn,
+    /// A first-party `.py` file is inconsistent with the `.pyi` stub next to it.
+    InconsistentStub,
     /// Internal Pyrefly error.
     InternalError,
     /// Attempting to write an annotation that is invalid for some reason.
@@ -197,12 +210,18 @@ pub enum ErrorKind {
     MissingImport,
     /// Accessing an attribute that does not exist on a module.
     MissingModuleAttribute,
+    /// A function in the public API of a library is missing a parameter or return annotation.
+    MissingPublicAnnotation,
+    /// A top-level package has no `py.typed` marker, so its annotations are ignored once installed.
+    MissingPyTyped,
     /// The source code for an imported package is missing.
     MissingSource,
     /// The attribute exists but does not support this access pattern.
     NoAccess,
     /// Attempting to call an overloaded function, but none of the signatures match.
     NoMatchingOverload,
+    /// A dict literal keyed by an enum that doesn't have an entry for every member of the enum.
+    NonExhaustiveEnumDict,
     /// Attempting to use something that isn't a type where a type is expected.
     /// This is a very general error and should be used sparingly.
     NotAType,
@@ -216,16 +235,26 @@ pub enum ErrorKind {
     OpenUnpacking,
     /// An error related to parsing or syntax.
     ParseError,
+    /// An attribute that `__init__` only assigns on some paths, so it may be missing on an instance.
+    PossiblyMissingAttribute,
+    /// A signature in the public API of a library mentions a private class, or a class from a
+    /// private module that isn't re-exported.
+    PrivateTypeInPublicApi,
     /// A protocol attribute was first defined inside a method instead of the class body.
     ProtocolImplicitlyDefinedAttribute,
     /// The attribute exists but cannot be modified.
     ReadOnly,
+    /// A function or class is redefined in the same scope before it is used.
+    Redefinition,
     /// Warning when casting a value to a type it is already compatible with.
     RedundantCast,
     /// Attempting to use value that is equivalent to True or always False in boolean context.
     RedundantCondition,
     /// Raised by a call to reveal_type().
     RevealType,
+    /// A local variable shadows a builtin or a name from an enclosing scope, or an assignment
+    /// shadows an import of the same name.
+    ShadowedName,
     /// Attempting to use a name that may be unbound or uninitialized
     UnboundName,
     /// An error caused by a keyword argument used in the wrong place.
@@ -234,6 +263,8 @@ pub enum ErrorKind {
     UnexpectedPositionalArgument,
     /// Attempting to use a name that is not defined.
     UnknownName,
+    /// A file or lock is created outside a `with` statement and only used for a method call.
+    UnmanagedResource,
     /// Attempting to use a feature that is not yet supported.
     Unsupported,
     /// Attempting to `del` something that cannot be deleted
@@ -244,6 +275,8 @@ pub enum ErrorKind {
     UntypedImport,
     /// Result of async function call is never used or awaited
     UnusedCoroutine,
+    /// A function parameter is never used in the function body.
+    UnusedParameter,
 }
 
 impl std::str::FromStr for ErrorKind {
@@ -290,10 +323,29 @@ impl ErrorKind {
             ErrorKind::ImplicitAny => Severity::Ignore,
             ErrorKind::MissingSource => Severity::Ignore,
             ErrorKind::OpenUnpacking => Severity::Ignore,
+            ErrorKind::InconsistentReturn => Severity::Ignore,
+            ErrorKind::InconsistentStub => Severity::Ignore,
+            ErrorKind::ShadowedName => Severity::Ignore,
+            ErrorKind::UnusedParameter => Severity::Ignore,
+            ErrorKind::UnmanagedResource => Severity::Ignore,
+            ErrorKind::PossiblyMissingAttribute => Severity::Ignore,
+            ErrorKind::NonExhaustiveEnumDict => Severity::Ignore,
+            ErrorKind::Redefinition => Severity::Ignore,
+            ErrorKind::ConditionalRedefinition => Severity::Ignore,
+            ErrorKind::MissingPublicAnnotation => Severity::Ignore,
+            ErrorKind::MissingPyTyped => Severity::Ignore,
+            ErrorKind::PrivateTypeInPublicApi => Severity::Ignore,
             _ => Severity::Error,
         }
     }
 
+    /// The checks for the public API of a library, enabled by `--library-mode`.
+    pub const LIBRARY_MODE: [ErrorKind; 3] = [
+        ErrorKind::MissingPublicAnnotation,
+        ErrorKind::MissingPyTyped,
+        ErrorKind::PrivateTypeInPublicApi,
+    ];
+
     /// Returns the public documentation URL for this error kind.
     /// Example: https://pyrefly.org/en/docs/error-kinds/#bad-context-manager
     pub fn docs_url(self) -> String {
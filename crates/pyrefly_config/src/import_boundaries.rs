@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Rules restricting which modules of a project may import which, to keep its architecture
+//! layered: higher layers may import lower ones but never the other way around, and private
+//! packages may only be imported from within the package containing them.
+
+use pyrefly_python::module_name::ModuleName;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::module_wildcard::Match;
+use crate::module_wildcard::ModuleWildcard;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ImportBoundaries {
+    /// Packages from the highest layer to the lowest. A module in one of them may import modules
+    /// in its own layer and lower ones, but not in higher ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    layers: Vec<String>,
+    /// Packages, such as `*.internal`, that may only be imported from within their parent package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    private_packages: Vec<ModuleWildcard>,
+}
+
+impl ImportBoundaries {
+    pub fn new(layers: Vec<String>, private_packages: &[&str]) -> anyhow::Result<Self> {
+        Ok(Self {
+            layers,
+            private_packages: private_packages
+                .iter()
+                .map(|x| ModuleWildcard::new(x))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty() && self.private_packages.is_empty()
+    }
+
+    fn layer(&self, module: ModuleName) -> Option<usize> {
+        self.layers.iter().position(|x| is_within(module, x))
+    }
+
+    /// Why `importer` may not import `imported`, or `None` if it may.
+    pub fn violation(&self, importer: ModuleName, imported: ModuleName) -> Option<String> {
+        if let Some(importer_layer) = self.layer(importer)
+            && let Some(imported_layer) = self.layer(imported)
+            && imported_layer < importer_layer
+        {
+            return Some(format!(
+                "`{importer}` may not import `{imported}`, because layer `{}` is below layer `{}`",
+                self.layers[importer_layer], self.layers[imported_layer]
+            ));
+        }
+        let components = imported.components();
+        // A top-level package has no parent to be private to.
+        for len in 2..=components.len() {
+            let package = ModuleName::from_parts(&components[..len]);
+            if self
+                .private_packages
+                .iter()
+                .any(|x| x.matches(package) == Match::Positive)
+            {
+                let parent = ModuleName::from_parts(&components[..len - 1]);
+                if !is_within(importer, parent.as_str()) {
+                    return Some(format!(
+                        "`{importer}` may not import `{imported}`, because `{package}` is private to `{parent}`"
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Whether `module` is the package `package` or inside it.
+fn is_within(module: ModuleName, package: &str) -> bool {
+    module
+        .as_str()
+        .strip_prefix(package)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violation() {
+        let boundaries = ImportBoundaries::new(
+            vec!["app".to_owned(), "services".to_owned(), "lib".to_owned()],
+            &["*.internal"],
+        )
+        .unwrap();
+        let violation = |importer: &str, imported: &str| {
+            boundaries.violation(
+                ModuleName::from_str(importer),
+                ModuleName::from_str(imported),
+            )
+        };
+        assert_eq!(violation("app.main", "lib.utils"), None);
+        assert_eq!(violation("app.main", "app.views"), None);
+        assert_eq!(violation("services.billing", "lib"), None);
+        assert_eq!(
+            violation("lib.utils", "app.main").as_deref(),
+            Some("`lib.utils` may not import `app.main`, because layer `lib` is below layer `app`")
+        );
+        assert_eq!(violation("libs.utils", "app.main"), None);
+        assert_eq!(violation("scripts.run", "app.main"), None);
+
+        assert_eq!(violation("lib.db.models", "lib.db.internal.pool"), None);
+        assert_eq!(violation("lib.db", "lib.db.internal"), None);
+        assert_eq!(
+            violation("lib.cache", "lib.db.internal.pool").as_deref(),
+            Some(
+                "`lib.cache` may not import `lib.db.internal.pool`, because `lib.db.internal` is private to `lib.db`"
+            )
+        );
+        assert_eq!(violation("lib.cache", "internal"), None);
+    }
+}
@@ -8,11 +8,14 @@
 pub mod args;
 pub mod base;
 pub mod config;
+pub mod dependency_manifest;
+pub mod deprecation_policy;
 pub mod environment;
 pub mod error;
 pub mod error_kind;
 pub mod file_kind;
 pub mod finder;
+pub mod import_boundaries;
 pub mod migration;
 pub(crate) mod module_wildcard;
 pub mod pyproject;
@@ -157,6 +157,20 @@ impl Ast {
         )
     }
 
+    /// Whether an annotation is `Final`, such as `Final`, `Final[int]` or `typing.Final[int]`.
+    /// This is only syntactic, so doesn't check where `Final` comes from.
+    pub fn is_final_annotation(x: &Expr) -> bool {
+        let x = match x {
+            Expr::Subscript(x) => &*x.value,
+            _ => x,
+        };
+        match x {
+            Expr::Name(x) => x.id == "Final",
+            Expr::Attribute(x) => x.attr.id == "Final",
+            _ => false,
+        }
+    }
+
     /// Iterates over the branches of an if statement, returning the test and body.
     /// A test on `None` is an `else` branch that is always taken.
     pub fn if_branches(x: &StmtIf) -> impl Iterator<Item = (Option<&Expr>, &[Stmt])> {
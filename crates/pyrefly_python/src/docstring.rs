@@ -6,7 +6,9 @@
  */
 
 use std::cmp::min;
+use std::sync::LazyLock;
 
+use regex::Regex;
 use ruff_python_ast::Stmt;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
@@ -16,6 +18,317 @@ use crate::module::Module;
 #[derive(Debug, Clone)]
 pub struct Docstring(pub TextRange, pub Module);
 
+/// An entry of a Google style section, e.g. `name (int): Description`.
+static GOOGLE_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\*{0,2}[A-Za-z_][\w.]*(?:\[.*\])?)\s*(?:\(([^)]*)\))?\s*:(?:\s+(.*))?$").unwrap()
+});
+
+/// A reST field, e.g. `:param int name: Description`.
+static REST_FIELD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:(\w+)([^:]*):\s*(.*)$").unwrap());
+
+/// The kinds of sections of Google and NumPy style docstrings.
+#[derive(Clone, Copy)]
+enum Section {
+    /// Each entry describes a name, such as a parameter or an exception.
+    Fields,
+    /// The contents are free text.
+    Text,
+}
+
+impl Section {
+    fn from_header(header: &str) -> Option<Self> {
+        match header.to_lowercase().as_str() {
+            "args" | "arguments" | "attributes" | "exceptions" | "keyword args"
+            | "keyword arguments" | "other parameters" | "parameters" | "params" | "raises"
+            | "receives" | "return" | "returns" | "warns" | "yield" | "yields" => {
+                Some(Self::Fields)
+            }
+            "example" | "examples" | "methods" | "note" | "notes" | "references" | "see also"
+            | "todo" | "warning" | "warnings" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// A name described in a section, rendered as an item of a Markdown list.
+struct Entry {
+    name: String,
+    type_: Option<String>,
+    description: String,
+}
+
+impl Entry {
+    fn new(name: &str, type_: Option<&str>, description: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            type_: type_.map(|x| x.trim().to_owned()).filter(|x| !x.is_empty()),
+            description: description.trim().to_owned(),
+        }
+    }
+
+    fn google(line: &str) -> Option<Self> {
+        let captures = GOOGLE_ENTRY.captures(line)?;
+        Some(Self::new(
+            &captures[1],
+            captures.get(2).map(|x| x.as_str()),
+            captures.get(3).map_or("", |x| x.as_str()),
+        ))
+    }
+
+    fn numpy(line: &str) -> Option<Self> {
+        Some(match line.split_once(" : ") {
+            Some((name, type_)) => Self::new(name.trim(), Some(type_), ""),
+            None => Self::new(line.trim_end_matches(" :"), None, ""),
+        })
+    }
+
+    fn extend_description(&mut self, text: &str) {
+        if !self.description.is_empty() {
+            self.description.push(' ');
+        }
+        self.description.push_str(text.trim());
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut res = format!("- `{}`", self.name);
+        if let Some(type_) = &self.type_ {
+            res.push_str(&format!(" ({type_})"));
+        }
+        if !self.description.is_empty() {
+            res.push_str(": ");
+            res.push_str(&self.description);
+        }
+        res
+    }
+}
+
+/// The number of spaces at the start of `line`.
+fn indent(line: &str) -> usize {
+    line.bytes().take_while(|&c| c == b' ').count()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// The section of a NumPy style header at line `i`, which is underlined with dashes.
+fn numpy_header(lines: &[&str], i: usize) -> Option<Section> {
+    let underline = lines.get(i + 1)?.trim();
+    if underline.len() >= 3 && underline.bytes().all(|c| c == b'-') {
+        Section::from_header(lines[i].trim())
+    } else {
+        None
+    }
+}
+
+/// The end of a section running from `start` to `end`, leaving out its trailing blank lines,
+/// which separate it from what follows.
+fn section_end(lines: &[&str], start: usize, mut end: usize) -> usize {
+    while end > start && is_blank(lines[end - 1]) {
+        end -= 1;
+    }
+    end
+}
+
+/// Start a new section in the Markdown, separated from the text before it.
+fn push_header(res: &mut Vec<String>, name: &str) {
+    if res.last().is_some_and(|x| !is_blank(x)) {
+        res.push(String::new());
+    }
+    res.push(format!("**{name}**"));
+}
+
+/// Render the lines in the body of a Google or NumPy style section, where `entry` parses the
+/// first line of an entry.
+fn push_section(
+    res: &mut Vec<String>,
+    section: Section,
+    body: &[&str],
+    entry: fn(&str) -> Option<Entry>,
+) {
+    let base = body
+        .iter()
+        .filter(|x| !is_blank(x))
+        .map(|x| indent(x))
+        .min()
+        .unwrap_or(0);
+    match section {
+        Section::Text => {
+            res.extend(
+                body.iter()
+                    .map(|x| x.get(base..).unwrap_or_default().to_owned()),
+            );
+        }
+        Section::Fields => {
+            let mut current: Option<Entry> = None;
+            for line in body {
+                if is_blank(line) {
+                    continue;
+                }
+                if indent(line) == base
+                    && let Some(next) = entry(line.trim())
+                {
+                    res.extend(current.replace(next).map(|x| x.to_markdown()));
+                } else if let Some(current) = &mut current {
+                    current.extend_description(line);
+                } else {
+                    res.push(line[base..].to_owned());
+                }
+            }
+            res.extend(current.map(|x| x.to_markdown()));
+        }
+    }
+}
+
+/// Render a block of reST fields, gathering them into sections like the other styles.
+fn push_rest_fields(res: &mut Vec<String>, fields: &[(String, String, String, Vec<&str>)]) {
+    fn named<'a>(entries: &'a mut Vec<Entry>, name: &str) -> &'a mut Entry {
+        match entries.iter().position(|x| x.name == name) {
+            Some(i) => &mut entries[i],
+            None => {
+                entries.push(Entry::new(name, None, ""));
+                entries.last_mut().unwrap()
+            }
+        }
+    }
+
+    let mut parameters = Vec::new();
+    let mut attributes = Vec::new();
+    let mut raises = Vec::new();
+    let mut returns = None;
+    let mut return_type = None;
+    for (kind, args, description, lines) in fields {
+        let mut words = args.split_whitespace().collect::<Vec<_>>();
+        let name = words.pop();
+        match (kind.as_str(), name) {
+            (
+                "param" | "parameter" | "arg" | "argument" | "key" | "keyword" | "var" | "ivar"
+                | "cvar",
+                Some(name),
+            ) => {
+                let entries = if matches!(kind.as_str(), "var" | "ivar" | "cvar") {
+                    &mut attributes
+                } else {
+                    &mut parameters
+                };
+                let entry = named(entries, name);
+                if !words.is_empty() {
+                    entry.type_ = Some(words.join(" "));
+                }
+                entry.extend_description(description);
+            }
+            ("type" | "vartype", Some(name)) => {
+                let entries = if kind == "type" {
+                    &mut parameters
+                } else {
+                    &mut attributes
+                };
+                named(entries, name).type_ = Some(description.clone());
+            }
+            ("raises" | "raise" | "except" | "exception", Some(name)) => {
+                raises.push(Entry::new(name, None, description));
+            }
+            ("returns" | "return", None) => returns = Some(description.clone()),
+            ("rtype", None) => return_type = Some(description.clone()),
+            _ => res.extend(lines.iter().map(|x| (*x).to_owned())),
+        }
+    }
+    for (name, entries) in [("Parameters", parameters), ("Attributes", attributes)] {
+        if !entries.is_empty() {
+            push_header(res, name);
+            res.extend(entries.iter().map(|x| x.to_markdown()));
+        }
+    }
+    if returns.is_some() || return_type.is_some() {
+        push_header(res, "Returns");
+        match return_type {
+            Some(type_) => res.push(
+                Entry::new(&type_, None, returns.as_deref().unwrap_or_default()).to_markdown(),
+            ),
+            None => res.extend(returns),
+        }
+    }
+    if !raises.is_empty() {
+        push_header(res, "Raises");
+        res.extend(raises.iter().map(|x| x.to_markdown()));
+    }
+}
+
+/// Convert the sections of Google, NumPy and reST style docstrings to Markdown, given the lines
+/// of a docstring without their common indentation. Other lines are left alone.
+fn sections_to_markdown(lines: &[&str]) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let header = line.trim();
+        let header_indent = indent(line);
+        if let Some(section) = numpy_header(lines, i) {
+            // NumPy style: the entries are at the same indentation as the header.
+            let start = i + 2;
+            i = start;
+            while i < lines.len()
+                && numpy_header(lines, i).is_none()
+                && (is_blank(lines[i]) || indent(lines[i]) >= header_indent)
+            {
+                i += 1;
+            }
+            i = section_end(lines, start, i);
+            push_header(&mut res, header);
+            push_section(&mut res, section, &lines[start..i], Entry::numpy);
+        } else if let Some(name) = header.strip_suffix(':')
+            && let Some(section) = Section::from_header(name)
+            && lines
+                .get(i + 1)
+                .is_some_and(|x| !is_blank(x) && indent(x) > header_indent)
+        {
+            // Google style: the entries are indented below the header.
+            let start = i + 1;
+            i = start;
+            while i < lines.len() && (is_blank(lines[i]) || indent(lines[i]) > header_indent) {
+                i += 1;
+            }
+            i = section_end(lines, start, i);
+            push_header(&mut res, name);
+            push_section(&mut res, section, &lines[start..i], Entry::google);
+        } else if REST_FIELD.is_match(header) {
+            // reST style: each field is on its own line, continued by more indented lines.
+            let mut fields: Vec<(String, String, String, Vec<&str>)> = Vec::new();
+            while i < lines.len() {
+                let line = lines[i];
+                if indent(line) == header_indent
+                    && let Some(captures) = REST_FIELD.captures(line.trim())
+                {
+                    fields.push((
+                        captures[1].to_owned(),
+                        captures[2].to_owned(),
+                        captures[3].trim().to_owned(),
+                        vec![line],
+                    ));
+                } else if !is_blank(line)
+                    && indent(line) > header_indent
+                    && let Some((_, _, description, lines)) = fields.last_mut()
+                {
+                    if !description.is_empty() {
+                        description.push(' ');
+                    }
+                    description.push_str(line.trim());
+                    lines.push(line);
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+            push_rest_fields(&mut res, &fields);
+        } else {
+            res.push(line.to_owned());
+            i += 1;
+        }
+    }
+    res
+}
+
 impl Docstring {
     pub fn range_from_stmts(xs: &[Stmt]) -> Option<TextRange> {
         if let Some(stmt) = xs.first()
@@ -68,15 +381,26 @@ impl Docstring {
             .min()
             .unwrap_or(0);
 
-        result
+        let lines = result
             .lines()
             .enumerate()
             .map(|(i, line)| {
                 if i == 0 {
-                    line.to_owned()
+                    line
                 } else {
-                    let trimmed = &line[min(min_indent, line.len())..];
-                    let mut without_blockquote = trimmed;
+                    &line[min(min_indent, line.len())..]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        sections_to_markdown(&lines)
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 {
+                    line
+                } else {
+                    let mut without_blockquote = line.as_str();
                     while let Some(rest) = without_blockquote.strip_prefix('>') {
                         without_blockquote = rest.strip_prefix(' ').unwrap_or(rest);
                     }
@@ -188,4 +512,46 @@ mod tests {
             "hello  \nworld  \ntest"
         );
     }
+
+    #[test]
+    fn test_clean_google_style() {
+        assert_eq!(
+            Docstring::clean(
+                "\"\"\"Add two numbers.\n\n    Args:\n        a (int): The first\n            number.\n        b: The second number.\n\n    Returns:\n        int: The sum.\n    \"\"\""
+            )
+            .as_str(),
+            "Add two numbers.  \n  \n**Args**  \n- `a` (int): The first number.  \n- `b`: The second number.  \n  \n**Returns**  \n- `int`: The sum.  \n"
+        );
+    }
+
+    #[test]
+    fn test_clean_numpy_style() {
+        assert_eq!(
+            Docstring::clean(
+                "\"\"\"\n    Add two numbers.\n\n    Parameters\n    ----------\n    a : int\n        The first number.\n    b : int\n        The second number.\n\n    Returns\n    -------\n    int\n        The sum.\n    \"\"\""
+            )
+            .as_str(),
+            "  \nAdd two numbers.  \n  \n**Parameters**  \n- `a` (int): The first number.  \n- `b` (int): The second number.  \n  \n**Returns**  \n- `int`: The sum.  \n"
+        );
+    }
+
+    #[test]
+    fn test_clean_rest_style() {
+        assert_eq!(
+            Docstring::clean(
+                "\"\"\"Add two numbers.\n\n:param a: The first number.\n:type a: int\n:param int b: The second\n    number.\n:returns: The sum.\n:rtype: int\n:raises ValueError: If a number is negative.\n\"\"\""
+            )
+            .as_str(),
+            "Add two numbers.  \n  \n**Parameters**  \n- `a` (int): The first number.  \n- `b` (int): The second number.  \n  \n**Returns**  \n- `int`: The sum.  \n  \n**Raises**  \n- `ValueError`: If a number is negative."
+        );
+    }
+
+    #[test]
+    fn test_clean_leaves_other_text_alone() {
+        assert_eq!(
+            Docstring::clean("\"\"\"Note: not a section.\n\n:class:`Foo` isn't a field.\"\"\"")
+                .as_str(),
+            "Note: not a section.  \n  \n:class:`Foo` isn't a field."
+        );
+    }
 }
@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A stable name for a symbol, which external tools can use to join what we know about a symbol
+//! with their own data about it, such as coverage, profiles or ownership.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use dupe::Dupe;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_text_size::TextRange;
+use serde::Serialize;
+use serde::Serializer;
+use static_interner::Intern;
+use static_interner::Interner;
+
+use crate::ast::Ast;
+use crate::module::Module;
+use crate::module_name::ModuleName;
+
+static FULLY_QUALIFIED_NAME_INTERNER: Interner<String> = Interner::new();
+
+/// The module a symbol is defined in and its path from the top level of the module, separated
+/// by a colon, as in `pkg.mod:Class.method`. As in `__qualname__`, the path goes through
+/// `<locals>` for symbols defined in a function, as in `pkg.mod:function.<locals>.helper`.
+#[derive(Clone, Dupe, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FullyQualifiedName(Intern<String>);
+
+impl Display for FullyQualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Debug for FullyQualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FullyQualifiedName")
+            .field(&self.as_str())
+            .finish()
+    }
+}
+
+impl Serialize for FullyQualifiedName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl FullyQualifiedName {
+    /// The name of the symbol at `path`, such as `Class.method`, in `module`.
+    pub fn new(module: ModuleName, path: &str) -> Self {
+        Self(FULLY_QUALIFIED_NAME_INTERNER.intern(format!("{module}:{path}")))
+    }
+
+    /// The name of the symbol whose name is defined at `range` of `module`, with AST `ast`.
+    pub fn of_definition(module: &Module, ast: &ModModule, range: TextRange) -> Self {
+        let mut path = vec![module.code_at(range)];
+        for node in Ast::locate_node(ast, range.start()) {
+            match node {
+                AnyNodeRef::StmtClassDef(x) if x.name.range != range => path.push(x.name.as_str()),
+                AnyNodeRef::StmtFunctionDef(x) if x.name.range != range => {
+                    path.push("<locals>");
+                    path.push(x.name.as_str());
+                }
+                _ => {}
+            }
+        }
+        path.reverse();
+        Self::new(module.name(), &path.join("."))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn module(&self) -> ModuleName {
+        ModuleName::from_str(self.split().0)
+    }
+
+    /// The path of the symbol within its module, such as `Class.method`.
+    pub fn path(&self) -> &str {
+        self.split().1
+    }
+
+    fn split(&self) -> (&str, &str) {
+        // Module names don't contain colons, so the first one ends the module.
+        self.as_str().split_once(':').unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use ruff_text_size::TextSize;
+
+    use super::*;
+    use crate::module_path::ModulePath;
+
+    #[test]
+    fn test_of_definition() {
+        let contents = r#"
+class Outer:
+    class Inner:
+        def method(self):
+            def helper(): ...
+x = 1
+"#;
+        let module = Module::new(
+            ModuleName::from_str("pkg.mod"),
+            ModulePath::filesystem(PathBuf::from("pkg/mod.py")),
+            Arc::new(contents.to_owned()),
+        );
+        let ast = Ast::parse(contents).0;
+        let name = |x: &str| {
+            let range = TextRange::at(
+                TextSize::new(contents.find(x).unwrap() as u32),
+                TextSize::new(x.len() as u32),
+            );
+            FullyQualifiedName::of_definition(&module, &ast, range)
+        };
+        assert_eq!(name("Outer").as_str(), "pkg.mod:Outer");
+        assert_eq!(name("Inner").as_str(), "pkg.mod:Outer.Inner");
+        assert_eq!(name("method").as_str(), "pkg.mod:Outer.Inner.method");
+        assert_eq!(
+            name("helper").as_str(),
+            "pkg.mod:Outer.Inner.method.<locals>.helper"
+        );
+        assert_eq!(name("x").as_str(), "pkg.mod:x");
+        assert_eq!(name("helper").module(), ModuleName::from_str("pkg.mod"));
+        assert_eq!(name("method").path(), "Outer.Inner.method");
+        assert_eq!(name("x"), FullyQualifiedName::new(module.name(), "x"));
+    }
+}
@@ -144,7 +144,7 @@ impl Module {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TextRangeWithModule {
     pub module: Module,
     pub range: TextRange,
@@ -18,16 +18,22 @@ use pyrefly_util::prelude::SliceExt;
 use pyrefly_util::with_hash::WithHash;
 use regex::Match;
 use regex::Regex;
+use ruff_python_ast::AtomicNodeIndex;
 use ruff_python_ast::BoolOp;
 use ruff_python_ast::CmpOp;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ExprAttribute;
 use ruff_python_ast::ExprBooleanLiteral;
 use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprNoneLiteral;
 use ruff_python_ast::ExprNumberLiteral;
+use ruff_python_ast::ExprUnaryOp;
+use ruff_python_ast::Int;
+use ruff_python_ast::Number;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::StmtIf;
 use ruff_python_ast::UnaryOp;
+use ruff_text_size::TextRange;
 use serde::Deserialize;
 use serde::Serialize;
 use serde::de;
@@ -283,6 +289,70 @@ impl<'de> Deserialize<'de> for SysInfo {
     }
 }
 
+/// The value of a constant defined by a literal, e.g. `DEBUG = False`, which can be used when
+/// statically evaluating conditions in other modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantValue {
+    None,
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl ConstantValue {
+    pub fn from_expr(x: &Expr) -> Option<Self> {
+        match x {
+            Expr::NoneLiteral(_) => Some(Self::None),
+            Expr::BooleanLiteral(x) => Some(Self::Bool(x.value)),
+            Expr::NumberLiteral(x) => Some(Self::Int(x.value.as_int()?.as_i64()?)),
+            Expr::StringLiteral(x) => Some(Self::String(x.value.to_str().to_owned())),
+            _ => None,
+        }
+    }
+
+    /// A literal expression with this value, located at `range`.
+    pub fn to_expr(&self, range: TextRange) -> Expr {
+        match self {
+            Self::None => Expr::NoneLiteral(ExprNoneLiteral {
+                node_index: AtomicNodeIndex::dummy(),
+                range,
+            }),
+            Self::Bool(x) => Expr::BooleanLiteral(ExprBooleanLiteral {
+                node_index: AtomicNodeIndex::dummy(),
+                range,
+                value: *x,
+            }),
+            Self::Int(x) => {
+                let literal = Expr::NumberLiteral(ExprNumberLiteral {
+                    node_index: AtomicNodeIndex::dummy(),
+                    range,
+                    value: Number::Int(Int::from(x.unsigned_abs())),
+                });
+                if *x < 0 {
+                    Expr::UnaryOp(ExprUnaryOp {
+                        node_index: AtomicNodeIndex::dummy(),
+                        range,
+                        op: UnaryOp::USub,
+                        operand: Box::new(literal),
+                    })
+                } else {
+                    literal
+                }
+            }
+            Self::String(x) => Ast::str_expr(x, range),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Self::None => Value::Truthiness(false),
+            Self::Bool(x) => Value::Bool(*x),
+            Self::Int(x) => Value::Int(*x),
+            Self::String(x) => Value::String(x.clone()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, PartialOrd)]
 enum Value {
     Tuple(Vec<Value>),
@@ -335,18 +405,32 @@ impl Value {
 impl SysInfo {
     /// Return true/false if we can statically evaluate it, and None if we can't.
     pub fn evaluate_bool(&self, x: &Expr) -> Option<bool> {
-        Some(self.evaluate(x)?.to_bool())
+        self.evaluate_bool_with_constants(x, &|_| None)
+    }
+
+    /// Like `evaluate_bool`, but `constants` gives the values of names and attributes that refer
+    /// to constants, e.g. `settings.DEBUG`.
+    pub fn evaluate_bool_with_constants(
+        &self,
+        x: &Expr,
+        constants: &dyn Fn(&Expr) -> Option<ConstantValue>,
+    ) -> Option<bool> {
+        Some(self.evaluate(x, constants)?.to_bool())
     }
 
     fn is_type_checking_constant_name(x: &str) -> bool {
         x == "TYPE_CHECKING" || x == "TYPE_CHECKING_WITH_PYREFLY"
     }
 
-    fn evaluate(&self, x: &Expr) -> Option<Value> {
+    fn evaluate(
+        &self,
+        x: &Expr,
+        constants: &dyn Fn(&Expr) -> Option<ConstantValue>,
+    ) -> Option<Value> {
         match x {
             Expr::Compare(x) if x.ops.len() == 1 && x.comparators.len() == 1 => Some(Value::Bool(
-                self.evaluate(&x.left)?
-                    .compare(x.ops[0], &self.evaluate(&x.comparators[0])?)?,
+                self.evaluate(&x.left, constants)?
+                    .compare(x.ops[0], &self.evaluate(&x.comparators[0], constants)?)?,
             )),
             Expr::Attribute(ExprAttribute { value, attr, .. })
                 if let Expr::Name(name) = &**value
@@ -378,13 +462,15 @@ impl SysInfo {
                 && attr.as_str() == "startswith"
                 && arguments.keywords.is_empty()
                 && let [arg] = &*arguments.args
-                && let Some(Value::String(x)) = self.evaluate(value)
-                && let Some(Value::String(y)) = self.evaluate(arg) =>
+                && let Some(Value::String(x)) = self.evaluate(value, constants)
+                && let Some(Value::String(y)) = self.evaluate(arg, constants) =>
             {
                 Some(Value::Bool(x.starts_with(&y)))
             }
             Expr::Tuple(x) => Some(Value::Tuple(
-                x.elts.try_map(|x| self.evaluate(x).ok_or(())).ok()?,
+                x.elts
+                    .try_map(|x| self.evaluate(x, constants).ok_or(()))
+                    .ok()?,
             )),
             Expr::NumberLiteral(ExprNumberLiteral { value: i, .. }) => {
                 Some(Value::Int(i.as_int()?.as_i64()?))
@@ -396,7 +482,7 @@ impl SysInfo {
                 BoolOp::And => {
                     let mut res = Some(Value::Bool(true));
                     for x in &x.values {
-                        match self.evaluate(x) {
+                        match self.evaluate(x, constants) {
                             None => res = None,
                             Some(x) => match (x.to_bool(), res.is_none()) {
                                 (false, false) => return Some(x),
@@ -411,7 +497,7 @@ impl SysInfo {
                 BoolOp::Or => {
                     let mut res = Some(Value::Bool(false));
                     for x in &x.values {
-                        match self.evaluate(x) {
+                        match self.evaluate(x, constants) {
                             None => res = None,
                             Some(x) => match (x.to_bool(), res.is_none()) {
                                 (false, false) => res = Some(x),
@@ -426,12 +512,12 @@ impl SysInfo {
             },
             Expr::UnaryOp(x) => match x.op {
                 UnaryOp::Not => {
-                    let v = self.evaluate(&x.operand)?;
+                    let v = self.evaluate(&x.operand, constants)?;
                     Some(Value::Bool(!v.to_bool()))
                 }
                 _ => None,
             },
-            _ => None,
+            _ => constants(x).map(|x| x.to_value()),
         }
     }
 
@@ -799,6 +799,19 @@ impl FunctionKind {
             current_module,
         )
     }
+
+    /// The fully qualified name of the function, e.g. `module.Class.method`.
+    pub fn qualified_name(&self) -> String {
+        match self.class() {
+            Some(cls) => format!(
+                "{}.{}.{}",
+                self.module_name(),
+                cls.name(),
+                self.function_name()
+            ),
+            None => format!("{}.{}", self.module_name(), self.function_name()),
+        }
+    }
 }
 
 pub fn unexpected_keyword(error: &dyn Fn(String), func: &str, keyword: &Keyword) {
@@ -7,11 +7,13 @@
 
 //! Display a type. The complexity comes from if we have two classes with the same name,
 //! we want to display disambiguating information (e.g. module name or location).
+use std::cell::Cell;
 use std::fmt;
 use std::fmt::Display;
 
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::qname::QName;
+use pyrefly_python::sys_info::PythonVersion;
 use pyrefly_util::display::Fmt;
 use pyrefly_util::display::append;
 use pyrefly_util::display::commas_iter;
@@ -87,6 +89,15 @@ pub struct TypeDisplayContext<'a> {
     /// Should we display for IDE Hover? This makes type names more readable but less precise.
     hover: bool,
     always_display_module_name: bool,
+    /// Sort union members by their display, rather than the order the union was built in,
+    /// keeping `Literal`s first and `None` last.
+    normalize_unions: bool,
+    /// Render unions as `Optional[X]` and `Union[X, Y]`, for targets that predate PEP 604.
+    legacy_union_syntax: bool,
+    /// Types nested deeper than this are displayed as `...`.
+    max_depth: Option<usize>,
+    /// How deeply nested the type currently being displayed is.
+    depth: Cell<usize>,
 }
 
 impl<'a> TypeDisplayContext<'a> {
@@ -147,6 +158,22 @@ impl<'a> TypeDisplayContext<'a> {
     /// Set the context to display for hover. This makes type names more readable but less precise.
     pub fn set_display_mode_to_hover(&mut self) {
         self.hover = true;
+        self.normalize_unions = true;
+    }
+
+    /// Display unions in a stable order that doesn't depend on how they were constructed.
+    pub fn normalize_unions(&mut self) {
+        self.normalize_unions = true;
+    }
+
+    /// Use the union syntax supported by the given Python version.
+    pub fn set_target_python_version(&mut self, version: PythonVersion) {
+        self.legacy_union_syntax = !version.at_least(3, 10);
+    }
+
+    /// Elide types nested more than `depth` levels deep.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = Some(depth);
     }
 
     pub fn display(&'a self, t: &'a Type) -> impl Display + 'a {
@@ -155,7 +182,18 @@ impl<'a> TypeDisplayContext<'a> {
 
     // Private method for internal use
     fn display_internal(&'a self, t: &'a Type) -> impl Display + 'a {
-        Fmt(|f| self.fmt_helper(t, f, false))
+        Fmt(|f| {
+            let depth = self.depth.get();
+            if let Some(max_depth) = self.max_depth
+                && depth >= max_depth
+            {
+                return write!(f, "...");
+            }
+            self.depth.set(depth + 1);
+            let res = self.fmt_helper(t, f, false);
+            self.depth.set(depth);
+            res
+        })
     }
 
     fn fmt_targ(&self, param: &TParam, arg: &Type, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -421,13 +459,25 @@ impl<'a> TypeDisplayContext<'a> {
                         _ => display_types.push(format!("{}", self.display_internal(t))),
                     }
                 }
-                if let Some(i) = literal_idx {
-                    if self.always_display_module_name {
-                        display_types
-                            .insert(i, format!("typing.Literal[{}]", commas_iter(|| &literals)));
+                let literal = literal_idx.map(|i| {
+                    let literal = if self.always_display_module_name {
+                        format!("typing.Literal[{}]", commas_iter(|| &literals))
                     } else {
-                        display_types.insert(i, format!("Literal[{}]", commas_iter(|| &literals)));
+                        format!("Literal[{}]", commas_iter(|| &literals))
+                    };
+                    (i, literal)
+                });
+                if self.normalize_unions {
+                    display_types.sort();
+                    if let Some(i) = display_types.iter().position(|t| t == "None") {
+                        let none = display_types.remove(i);
+                        display_types.push(none);
                     }
+                    if let Some((_, literal)) = literal {
+                        display_types.insert(0, literal);
+                    }
+                } else if let Some((i, literal)) = literal {
+                    display_types.insert(i, literal);
                 }
                 // This is mainly to prettify types for functions with different names but the same signature
                 let display_types_deduped = display_types
@@ -435,7 +485,22 @@ impl<'a> TypeDisplayContext<'a> {
                     .collect::<SmallSet<_>>()
                     .into_iter()
                     .collect::<Vec<_>>();
-                write!(f, "{}", display_types_deduped.join(" | "))
+                if self.legacy_union_syntax {
+                    let module = if self.always_display_module_name {
+                        "typing."
+                    } else {
+                        ""
+                    };
+                    match display_types_deduped.as_slice() {
+                        [t] => write!(f, "{t}"),
+                        [t, none] | [none, t] if none == "None" => {
+                            write!(f, "{module}Optional[{t}]")
+                        }
+                        ts => write!(f, "{module}Union[{}]", ts.join(", ")),
+                    }
+                } else {
+                    write!(f, "{}", display_types_deduped.join(" | "))
+                }
             }
             Type::Intersect(types) => {
                 write!(
@@ -563,6 +628,8 @@ impl<'a> TypeDisplayContext<'a> {
     }
 }
 
+/// Error messages use this display. Unlike the IDE displays below, it doesn't normalize unions or
+/// elide nested types, so a message always spells out the full types involved.
 impl Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         TypeDisplayContext::new(&[self]).fmt(self, f)
@@ -575,8 +642,29 @@ impl Type {
         c.set_display_mode_to_hover();
         c.display(self).to_string()
     }
+
+    /// Display for inlay hints. These can be inserted into the code, so use the union syntax the
+    /// target Python version supports.
+    pub fn as_inlay_hint_string(&self, version: PythonVersion) -> String {
+        let mut c = TypeDisplayContext::new(&[self]);
+        c.normalize_unions();
+        c.set_target_python_version(version);
+        c.display(self).to_string()
+    }
+
+    /// Display for the detail of a completion item, which is shown inline in the completion list,
+    /// so deeply nested types are elided. The documentation of the item then shows the full type.
+    pub fn as_completion_detail_string(&self) -> String {
+        let mut c = TypeDisplayContext::new(&[self]);
+        c.set_display_mode_to_hover();
+        c.set_max_depth(COMPLETION_DETAIL_MAX_DEPTH);
+        c.display(self).to_string()
+    }
 }
 
+/// How many levels of nested type arguments to show in a completion item detail.
+const COMPLETION_DETAIL_MAX_DEPTH: usize = 3;
+
 pub struct ClassDisplayContext<'a>(TypeDisplayContext<'a>);
 
 impl<'a> ClassDisplayContext<'a> {
@@ -1001,6 +1089,64 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_display_union_normalized() {
+        let foo = Type::ClassType(ClassType::new(
+            fake_class("foo", "mod.ule", 5),
+            TArgs::default(),
+        ));
+        let bar = Type::ClassType(ClassType::new(
+            fake_class("Bar", "mod.ule", 8),
+            TArgs::default(),
+        ));
+        let union = Type::Union(vec![
+            Type::None,
+            foo.clone(),
+            Type::Literal(Lit::Bool(true)),
+            bar,
+        ]);
+        assert_eq!(union.to_string(), "None | foo | Literal[True] | Bar");
+
+        let mut ctx = TypeDisplayContext::new(&[&union]);
+        ctx.normalize_unions();
+        assert_eq!(
+            ctx.display(&union).to_string(),
+            "Literal[True] | Bar | foo | None"
+        );
+
+        let mut legacy_ctx = TypeDisplayContext::new(&[&union]);
+        legacy_ctx.normalize_unions();
+        legacy_ctx.set_target_python_version(PythonVersion::new(3, 9, 0));
+        assert_eq!(
+            legacy_ctx.display(&union).to_string(),
+            "Union[Literal[True], Bar, foo, None]"
+        );
+
+        let optional = Type::Union(vec![Type::None, foo]);
+        let mut optional_ctx = TypeDisplayContext::new(&[&optional]);
+        optional_ctx.set_target_python_version(PythonVersion::new(3, 9, 0));
+        assert_eq!(optional_ctx.display(&optional).to_string(), "Optional[foo]");
+    }
+
+    #[test]
+    fn test_display_max_depth() {
+        let uniques = UniqueFactory::new();
+        let list = fake_class("list", "builtins", 0);
+        let list_tparams = fake_tparams(vec![fake_tparam(&uniques, "T", QuantifiedKind::TypeVar)]);
+        let list_of = |t: Type| {
+            Type::ClassType(ClassType::new(
+                list.dupe(),
+                TArgs::new(list_tparams.dupe(), vec![t]),
+            ))
+        };
+        let nested = list_of(list_of(list_of(Type::None)));
+        assert_eq!(nested.to_string(), "list[list[list[None]]]");
+
+        let mut ctx = TypeDisplayContext::new(&[&nested]);
+        ctx.set_max_depth(2);
+        assert_eq!(ctx.display(&nested).to_string(), "list[list[list[...]]]");
+    }
+
     #[test]
     fn test_display_single_param_callable() {
         let param1 = Param::Pos(Name::new_static("hello"), Type::None, Required::Required);
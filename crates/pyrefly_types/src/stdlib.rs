@@ -94,6 +94,8 @@ pub struct Stdlib {
     object: StdlibResult<ClassType>,
     /// Introduced in Python 3.10.
     union_type: Option<StdlibResult<ClassType>>,
+    /// The type of t-strings, introduced in Python 3.14.
+    template: Option<StdlibResult<ClassType>>,
 }
 
 impl Stdlib {
@@ -203,6 +205,9 @@ impl Stdlib {
             union_type: version
                 .at_least(3, 10)
                 .then(|| lookup_concrete(types, "UnionType")),
+            template: version
+                .at_least(3, 14)
+                .then(|| lookup_concrete(ModuleName::from_str("string.templatelib"), "Template")),
         }
     }
 
@@ -274,6 +279,10 @@ impl Stdlib {
         Some(Self::primitive(self.ellipsis_type.as_ref()?))
     }
 
+    pub fn template(&self) -> Option<&ClassType> {
+        Some(Self::primitive(self.template.as_ref()?))
+    }
+
     pub fn none_type(&self) -> &ClassType {
         Self::primitive(&self.none_type)
     }
@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A mode that removes the sources of nondeterminism from a run, so that bugs which only show up
+//! on some runs can be reproduced and bisected.
+
+use std::hash::BuildHasher;
+use std::hash::DefaultHasher;
+use std::hash::RandomState;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Make everything that runs afterwards deterministic: work runs on a single thread, one item at
+/// a time, and hash tables use a fixed seed, so they are iterated in the same order every time.
+pub fn enable_deterministic() {
+    DETERMINISTIC.store(true, Ordering::Relaxed);
+}
+
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// The number of items to put in each batch of parallel work: `size` normally, but one at a time
+/// in deterministic mode.
+pub fn batch_size(size: usize) -> usize {
+    if is_deterministic() { 1 } else { size }
+}
+
+/// A hasher builder that is randomly seeded, like the standard library's `RandomState`, except in
+/// deterministic mode, where it always uses the same seed.
+#[derive(Debug, Clone)]
+pub struct SeededState(Option<RandomState>);
+
+impl Default for SeededState {
+    fn default() -> Self {
+        Self((!is_deterministic()).then(RandomState::new))
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        match &self.0 {
+            Some(state) => state.build_hasher(),
+            // `DefaultHasher::new` always uses the same keys.
+            None => DefaultHasher::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_seed() {
+        let a = SeededState(None);
+        let b = SeededState(None);
+        assert_eq!(a.hash_one("pyrefly"), b.hash_one("pyrefly"));
+    }
+}
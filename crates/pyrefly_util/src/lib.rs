@@ -32,6 +32,7 @@ pub mod absolutize;
 pub mod arc_id;
 pub mod args;
 pub mod assert_size;
+pub mod deterministic;
 pub mod display;
 pub mod events;
 pub mod exclusive_lock;
@@ -45,6 +46,8 @@ pub mod lock;
 pub mod locked_map;
 pub mod memory;
 pub mod no_hash;
+#[cfg(all(feature = "otlp", not(target_arch = "wasm32")))]
+pub mod otlp;
 pub mod owner;
 pub mod panic;
 pub mod prelude;
@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Export `tracing` spans to an OpenTelemetry collector, such as Jaeger, over OTLP/HTTP, so that
+//! slow requests can be broken down into the modules and steps they waited on.
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::error;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::filter::Targets;
+
+use crate::trace::TracingLayer;
+
+/// The provider exporting spans, kept so that we can flush it before exiting.
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Create a layer exporting the spans of Pyrefly, at every level, to the OTLP/HTTP `endpoint`,
+/// e.g. `http://localhost:4318/v1/traces`.
+pub fn otlp_layer(endpoint: &str) -> anyhow::Result<TracingLayer> {
+    // The blocking HTTP client can't be created on a thread running an async runtime, as our main
+    // thread is, so create it on its own thread. Spans are then sent from a background thread.
+    let exporter = std::thread::scope(|s| {
+        s.spawn(|| {
+            SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .build()
+        })
+        .join()
+        .unwrap()
+    })
+    .with_context(|| format!("Failed to export traces to `{endpoint}`"))?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("pyrefly").build())
+        .build();
+    let tracer = provider.tracer("pyrefly");
+    if PROVIDER.set(provider).is_err() {
+        return Err(anyhow::anyhow!("Traces are already being exported"));
+    }
+    Ok(tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(Targets::new().with_target("pyrefly", LevelFilter::TRACE))
+        .boxed())
+}
+
+/// Export any spans that haven't been sent yet. Call before exiting, or they are lost.
+pub fn flush_otlp() {
+    if let Some(provider) = PROVIDER.get()
+        && let Err(e) = provider.shutdown()
+    {
+        error!("Failed to export traces: {e}");
+    }
+}
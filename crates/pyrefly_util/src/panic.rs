@@ -5,7 +5,10 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::any::Any;
 use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic::AssertUnwindSafe;
 use std::panic::PanicHookInfo;
 use std::sync::Once;
 use std::sync::atomic::AtomicBool;
@@ -24,7 +27,76 @@ pub fn has_panicked() -> bool {
 /// The code that Rust uses for panics.
 pub const PANIC_EXIT_CODE: u8 = 101;
 
+/// Something a thread is working on, which a panic on that thread can be blamed on.
+pub trait CrashContext {
+    /// Report that working on this caused a panic. Called from the panic hook, so should avoid
+    /// taking locks or doing anything else that might panic again.
+    fn report_panic(&self, message: &str, backtrace: &Backtrace);
+}
+
+thread_local! {
+    /// The innermost thing this thread is working on, if any.
+    static CRASH_CONTEXT: RefCell<Option<Box<dyn CrashContext>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, blaming any panic it causes on `context`. The panic is reported, but rather than
+/// exiting, we unwind back here and return the panic message, so the caller can recover.
+pub fn with_crash_context<R>(
+    context: Box<dyn CrashContext>,
+    f: impl FnOnce() -> R,
+) -> Result<R, String> {
+    /// Restores the outer context when dropped, even if `f` unwinds.
+    struct Restore(Option<Box<dyn CrashContext>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CRASH_CONTEXT.with(|x| *x.borrow_mut() = previous);
+        }
+    }
+
+    let restore = Restore(CRASH_CONTEXT.with(|x| x.replace(Some(context))));
+    let res = std::panic::catch_unwind(AssertUnwindSafe(f));
+    drop(restore);
+    res.map_err(|payload| payload_message(&*payload).to_owned())
+}
+
+/// Whether a panic on this thread will be caught by `with_crash_context`.
+fn in_crash_context() -> bool {
+    CRASH_CONTEXT
+        .try_with(|x| x.try_borrow().is_ok_and(|x| x.is_some()))
+        .unwrap_or(false)
+}
+
+fn payload_message(payload: &dyn Any) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(|x| x.as_str()))
+        .unwrap_or("Box<dyn Any>")
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let message = payload_message(info.payload());
+    match info.location() {
+        Some(location) => format!("{message} at {location}"),
+        None => message.to_owned(),
+    }
+}
+
 pub fn print_panic(info: &PanicHookInfo<'_>) {
+    if in_crash_context() {
+        // We'll recover, so let the context report it, and don't claim we are shutting down.
+        let backtrace = Backtrace::force_capture();
+        error!("Thread panicked: {info}\nBacktrace:\n{backtrace}");
+        CRASH_CONTEXT.with(|x| {
+            if let Some(context) = &*x.borrow() {
+                context.report_panic(&panic_message(info), &backtrace);
+            }
+        });
+        return;
+    }
+
     HAS_PANICKED.store(true, Ordering::Relaxed);
 
     // Sometimes we get two simultaneous panics, and there output gets co-mingled.
@@ -32,10 +104,8 @@ pub fn print_panic(info: &PanicHookInfo<'_>) {
     static PANIC_LOCK: Once = Once::new();
 
     PANIC_LOCK.call_once(|| {
-        error!(
-            "Thread panicked, shutting down: {info}\nBacktrace:\n{}",
-            Backtrace::force_capture()
-        );
+        let backtrace = Backtrace::force_capture();
+        error!("Thread panicked, shutting down: {info}\nBacktrace:\n{backtrace}");
 
         let out = |x: &str| anstream::eprintln!("{} {x}", Paint::magenta("PANIC"));
 
@@ -52,10 +122,32 @@ pub fn print_panic(info: &PanicHookInfo<'_>) {
 ///
 /// We want to exit immediately because otherwise we'd have to ensure all our thread/lock code
 /// properly bubbled up the panic, without producing a deadlock, or another panic, which is
-/// a) a lot of work, and b) almost impossible to test.
+/// a) a lot of work, and b) almost impossible to test. The exception is a panic inside
+/// `with_crash_context`, which is caught and recovered from.
 pub fn exit_on_panic() {
     std::panic::set_hook(Box::new(move |info| {
         print_panic(info);
-        std::process::exit(PANIC_EXIT_CODE as i32);
+        if !in_crash_context() {
+            std::process::exit(PANIC_EXIT_CODE as i32);
+        }
     }));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Ignore;
+
+    impl CrashContext for Ignore {
+        fn report_panic(&self, _message: &str, _backtrace: &Backtrace) {}
+    }
+
+    #[test]
+    fn test_with_crash_context() {
+        assert_eq!(with_crash_context(Box::new(Ignore), || 42), Ok(42));
+        let res: Result<(), String> = with_crash_context(Box::new(Ignore), || panic!("oops"));
+        assert_eq!(res, Err("oops".to_owned()));
+        assert!(!in_crash_context());
+    }
+}
@@ -16,6 +16,7 @@ use human_bytes::human_bytes;
 use tracing::debug;
 use tracing::info;
 
+use crate::deterministic::is_deterministic;
 use crate::display::number_thousands;
 use crate::lock::Mutex;
 
@@ -99,6 +100,10 @@ impl ThreadPool {
     }
 
     pub fn new() -> Self {
+        if is_deterministic() {
+            // With a single thread, work is always done in the same order.
+            return Self::with_thread_count(ThreadCount::NumThreads(NonZeroUsize::MIN));
+        }
         Self::with_thread_count(*THREADS.lock())
     }
 
@@ -50,13 +50,19 @@ pub fn tracing_layer(verbose: bool, testing: bool) -> TracingLayer {
 /// Set up tracing so it prints to stderr, and can be used for output.
 /// Most things should use `info` and `debug` level for showing messages.
 pub fn init_tracing(verbose: bool, testing: bool) {
+    init_tracing_with_export(verbose, testing, None)
+}
+
+/// Like `init_tracing`, but also sends events and spans to the `export` layer, e.g. one exporting
+/// spans to a trace collector.
+pub fn init_tracing_with_export(verbose: bool, testing: bool, export: Option<TracingLayer>) {
     // If we create tracing twice, the library panics. Avoid that.
     // Mostly happens when we run tests.
     static INIT_TRACING_ONCE: Once = Once::new();
 
     INIT_TRACING_ONCE.call_once(|| {
-        tracing_subscriber::registry()
-            .with(tracing_layer(verbose, testing))
-            .init();
+        let mut layers = vec![tracing_layer(verbose, testing)];
+        layers.extend(export);
+        tracing_subscriber::registry().with(layers).init();
     })
 }
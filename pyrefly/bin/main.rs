@@ -14,6 +14,8 @@ use library::Command;
 use library::util::CommonGlobalArgs;
 use pyrefly::library::library::library::library;
 use pyrefly_util::args::get_args_expanded;
+#[cfg(feature = "otlp")]
+use pyrefly_util::otlp::flush_otlp;
 use pyrefly_util::panic::exit_on_panic;
 
 // fbcode likes to set its own allocator in fbcode.default_allocator
@@ -54,6 +56,8 @@ async fn run() -> anyhow::Result<ExitCode> {
 async fn main() -> ExitCode {
     exit_on_panic();
     let res = run().await;
+    #[cfg(feature = "otlp")]
+    flush_otlp();
     match res {
         Ok(code) => code,
         Err(e) => {
@@ -64,8 +64,10 @@ pub enum CallStyle<'a> {
 pub enum ConstructorKind {
     // `MyClass`
     BareClassName,
-    // `type[MyClass]` or `type[Self]`
+    // `type[MyClass]`
     TypeOfClass,
+    // `type[Self]`
+    TypeOfSelf,
 }
 
 /// A thing that can be called (see as_call_target and call_infer).
@@ -161,9 +163,12 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 Type::TypedDict(typed_dict) => Some(CallTarget::TypedDict(typed_dict)),
                 _ => unreachable!(),
             },
-            Type::Type(box Type::ClassType(cls)) | Type::Type(box Type::SelfType(cls)) => {
+            Type::Type(box Type::ClassType(cls)) => {
                 Some(CallTarget::Class(cls, ConstructorKind::TypeOfClass))
             }
+            Type::Type(box Type::SelfType(cls)) => {
+                Some(CallTarget::Class(cls, ConstructorKind::TypeOfSelf))
+            }
             Type::Type(box Type::Tuple(tuple)) => Some(CallTarget::Class(
                 self.erase_tuple_type(tuple),
                 ConstructorKind::TypeOfClass,
@@ -299,7 +304,12 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     } else {
                         vec1![msg]
                     };
-                    errors.add(range, ErrorInfo::Kind(ErrorKind::Deprecated), full_msg);
+                    errors.add_deprecated(
+                        range,
+                        ErrorInfo::Kind(ErrorKind::Deprecated),
+                        full_msg,
+                        m.kind.qualified_name(),
+                    );
                 }
                 target
             }
@@ -448,9 +458,13 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         Some(ret)
     }
 
+    /// Construct an instance of `cls`. If `is_self`, we are calling `cls()` in a classmethod, so
+    /// type parameters the arguments don't determine are left as those of `Self`, rather than
+    /// defaulted.
     fn construct_class(
         &self,
         mut cls: ClassType,
+        is_self: bool,
         args: &[CallArg],
         keywords: &[CallKeyword],
         range: TextRange,
@@ -549,8 +563,10 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             // Not quite an overload, but close enough
             self.record_overload_trace_from_type(range, init_method);
         }
-        self.solver()
-            .finish_class_targs(cls.targs_mut(), self.uniques);
+        if !is_self {
+            self.solver()
+                .finish_class_targs(cls.targs_mut(), self.uniques);
+        }
         if let Some(mut ret) = dunder_new_ret {
             ret.subst_mut(&cls.targs().substitution_map());
             ret
@@ -686,7 +702,26 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         Some(ty) => self.check_dunder_bool_is_callable(&ty, range, errors),
                     }
                 };
-                self.construct_class(cls, args, keywords, range, errors, context, hint)
+                let self_cls =
+                    (constructor_kind == ConstructorKind::TypeOfSelf).then(|| cls.clone());
+                let ret = self.construct_class(
+                    cls,
+                    self_cls.is_some(),
+                    args,
+                    keywords,
+                    range,
+                    errors,
+                    context,
+                    hint,
+                );
+                match self_cls {
+                    // `cls()` in a classmethod constructs `Self`, unless the arguments force a
+                    // different specialization of a generic class.
+                    Some(self_cls) if ret == Type::ClassType(self_cls.clone()) => {
+                        Type::SelfType(self_cls)
+                    }
+                    _ => ret,
+                }
             }
             CallTarget::TypedDict(td) => {
                 self.construct_typed_dict(td, args, keywords, range, errors, context, hint)
@@ -1113,6 +1148,20 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 //     f = staticmethod(f)
                 // Check if this call applies a decorator with known typing effects to a function.
                 _ if let Some(ret) = self.maybe_apply_function_decorator(ty, &args, &kws, errors) => ret,
+                // Calls like `d.pop("key")` on a `TypedDict` get better errors than the
+                // synthesized overloads give.
+                _ if let Type::BoundMethod(method_ty) = ty
+                    && let Type::TypedDict(typed_dict) = &method_ty.obj
+                    && let Expr::Attribute(method) = &*x.func
+                    && let Some(ret) = self.typed_dict_method_call(
+                        typed_dict,
+                        &method.attr.id,
+                        &x.arguments,
+                        errors,
+                    ) =>
+                {
+                    ret
+                }
                 _ => {
                     let callable = self.as_call_target_or_error(
                         ty.clone(),
@@ -1136,6 +1185,52 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// Whether `ty` is a resource that must be closed or released, i.e. a file returned by `open`
+    /// or one of the `threading` lock types.
+    fn is_resource(&self, ty: &Type) -> bool {
+        let Type::ClassType(cls) = ty else {
+            return false;
+        };
+        [
+            ("_io", "TextIOWrapper"),
+            ("_io", "BufferedReader"),
+            ("_io", "BufferedWriter"),
+            ("_io", "BufferedRandom"),
+            ("_io", "FileIO"),
+            ("_thread", "lock"),
+            ("_thread", "LockType"),
+            ("_thread", "RLock"),
+            ("threading", "_RLock"),
+            ("threading", "Condition"),
+            ("threading", "Semaphore"),
+            ("threading", "BoundedSemaphore"),
+        ]
+        .iter()
+        .any(|(module, name)| cls.has_qname(module, name))
+    }
+
+    /// Report calls like `open(path).read()`, where a file or lock is created and then only used
+    /// for a single method call, so nothing closes or releases it. `callee` is the already
+    /// inferred type of the method, whose bound object is the resource.
+    pub fn check_unmanaged_resource(&self, x: &ExprCall, callee: &Type, errors: &ErrorCollector) {
+        if self.bindings().check_unmanaged_resources()
+            && let Expr::Attribute(method) = &*x.func
+            && let Expr::Call(resource) = &*method.value
+            && let Type::BoundMethod(bound) = callee
+            && self.is_resource(&bound.obj)
+        {
+            self.error(
+                errors,
+                resource.range,
+                ErrorInfo::Kind(ErrorKind::UnmanagedResource),
+                format!(
+                    "Result of `{}()` is never closed or released, use it in a `with` statement",
+                    self.module().code_at(resource.func.range())
+                ),
+            );
+        }
+    }
+
     fn has_exactly_two_posargs(&self, arguments: &Arguments) -> bool {
         arguments.keywords.is_empty()
             && arguments.args.len() == 2
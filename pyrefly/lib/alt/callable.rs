@@ -5,8 +5,10 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use dupe::Dupe;
 use itertools::Itertools;
 use pyrefly_python::dunder;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_types::callable::FunctionKind;
 use pyrefly_types::typed_dict::ExtraItems;
 use pyrefly_types::types::TArgs;
@@ -22,9 +24,11 @@ use ruff_python_ast::Keyword;
 use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
 use starlark_map::ordered_map::OrderedMap;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
+use vec1::vec1;
 
 use crate::alt::answers::LookupAnswer;
 use crate::alt::answers_solver::AnswersSolver;
@@ -38,6 +42,7 @@ use crate::error::context::ErrorInfo;
 use crate::error::context::TypeCheckContext;
 use crate::error::context::TypeCheckKind;
 use crate::error::display::function_suffix;
+use crate::export::exports::ExportLocation;
 use crate::solver::solver::QuantifiedHandle;
 use crate::types::callable::Callable;
 use crate::types::callable::Param;
@@ -424,6 +429,36 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         matches!(ty, Type::Kwargs(q2) if &*q2 == q)
     }
 
+    /// Where the function being called is defined, if it is a `def` or a callback protocol.
+    fn callee_definition(
+        &self,
+        callable_name: Option<&FunctionKind>,
+    ) -> Option<TextRangeWithModule> {
+        match callable_name? {
+            FunctionKind::Def(func) => match &func.cls {
+                Some(cls) => Some(TextRangeWithModule::new(
+                    cls.module().dupe(),
+                    cls.field_decl_range(&func.name)?,
+                )),
+                None => {
+                    let exports = self.exports.get(func.module.name()).finding()?;
+                    match exports.exports(self.exports).get(&func.name)? {
+                        ExportLocation::ThisModule(export) => Some(TextRangeWithModule::new(
+                            func.module.dupe(),
+                            export.location,
+                        )),
+                        ExportLocation::OtherModule(..) => None,
+                    }
+                }
+            },
+            FunctionKind::CallbackProtocol(cls) => Some(TextRangeWithModule::new(
+                cls.class_object().module().dupe(),
+                cls.class_object().field_decl_range(&dunder::CALL)?,
+            )),
+            _ => None,
+        }
+    }
+
     // See comment on `callable_infer` about `arg_errors` and `call_errors`.
     fn callable_infer_params(
         &self,
@@ -432,6 +467,8 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         // A ParamSpec Var (if any) that comes at the end of the parameter list.
         // See test::paramspec::test_paramspec_twice for an example of this.
         mut paramspec: Option<Var>,
+        // The return type of the callable, used only to describe its signature in errors.
+        ret: &Type,
         self_arg: Option<CallArg>,
         args: &[CallArg],
         keywords: &[CallKeyword],
@@ -458,6 +495,38 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 ),
             )
         };
+        // Errors about which arguments were passed, as opposed to their types, also point at the
+        // definition of the callee, so that it's easy to see what was expected.
+        let arity_error = |errors: &ErrorCollector, range, kind, msg: String| {
+            let related = self
+                .callee_definition(callable_name)
+                .map(|definition| {
+                    let signature = self.for_display(Type::Callable(Box::new(Callable::list(
+                        params.clone(),
+                        ret.clone(),
+                    ))));
+                    let msg = match callable_name {
+                        Some(name) => format!(
+                            "`{}` is defined here with signature `{signature}`",
+                            name.format(self.module().name())
+                        ),
+                        None => format!("Defined here with signature `{signature}`"),
+                    };
+                    (definition, msg)
+                })
+                .into_iter()
+                .collect();
+            errors.add_with_related(
+                range,
+                ErrorInfo::new(kind, context),
+                vec1![format!(
+                    "{}{}",
+                    msg,
+                    function_suffix(callable_name, self.module().name())
+                )],
+                related,
+            );
+        };
         let iargs = self_arg.iter().chain(args.iter());
         // Creates a reversed copy of the parameters that we iterate through from back to front,
         // so that we can easily peek at and pop from the end.
@@ -693,14 +762,14 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
         let mut unexpected_keyword_error = |name: &Name, range| {
             if missing_named_posonly.shift_remove(name) {
-                error(
+                arity_error(
                     call_errors,
                     range,
                     ErrorKind::UnexpectedKeyword,
                     format!("Expected argument `{name}` to be positional"),
                 );
             } else {
-                error(
+                arity_error(
                     call_errors,
                     range,
                     ErrorKind::UnexpectedKeyword,
@@ -848,8 +917,15 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 }
             }
         }
+        // Missing arguments have no range of their own, so we point at the closing parenthesis
+        // of the call, which is where they would need to be added.
+        let missing_range = if self.module().code_at(range).ends_with(')') {
+            TextRange::new(range.end() - TextSize::from(1), range.end())
+        } else {
+            range
+        };
         if missing_unnamed_posonly > 0 || !missing_named_posonly.is_empty() {
-            let range = keywords.first().map_or(range, |kw| kw.range);
+            let range = keywords.first().map_or(missing_range, |kw| kw.range);
             let msg = if missing_unnamed_posonly == 0 {
                 format!(
                     "Missing positional argument{} {}",
@@ -872,7 +948,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     ),
                 )
             };
-            error(call_errors, range, ErrorKind::BadArgumentCount, msg);
+            arity_error(call_errors, range, ErrorKind::BadArgumentCount, msg);
         }
         let missing_self_param = self_arg.is_some() && num_positional_params == 0;
         // We'll attempt to match extra positional arguments to kw-only parameters for better error messages.
@@ -886,7 +962,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             if !seen_names.contains_key(name) {
                 if splat_kwargs.is_empty() && *required {
                     if let Some(arg_range) = extra_posargs_iter.next() {
-                        error(
+                        arity_error(
                             call_errors,
                             *arg_range,
                             ErrorKind::UnexpectedPositionalArgument,
@@ -894,9 +970,9 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         );
                         extra_posargs_matched += 1;
                     } else {
-                        error(
+                        arity_error(
                             call_errors,
-                            range,
+                            missing_range,
                             ErrorKind::MissingArgument,
                             format!("Missing argument `{name}`"),
                         );
@@ -914,7 +990,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             }
         }
         let num_extra_positional_args = extra_positional_args.len();
-        if let Some(arg_range) = extra_arg_pos
+        if let Some(first_arg_range) = extra_arg_pos
             // This error is redundant if we've already reported an error for every individual arg.
             && extra_posargs_matched < num_extra_positional_args
         {
@@ -930,7 +1006,11 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     (num_positional_params + num_extra_positional_args).to_string(),
                 )
             };
-            error(
+            // Cover all the extra arguments, not just the first one.
+            let arg_range = extra_positional_args
+                .last()
+                .map_or(first_arg_range, |last| first_arg_range.cover(*last));
+            arity_error(
                 call_errors,
                 arg_range,
                 ErrorKind::BadArgumentCount,
@@ -980,6 +1060,17 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         } else {
             (QuantifiedHandle::empty(), callable)
         };
+        // A classmethod of a generic class accessed through the bare class, e.g. `A.m()`, infers
+        // the class's type arguments from the call rather than defaulting them to `Any`.
+        if let Some(Type::ClassDef(cls)) = &self_obj
+            && let Params::List(params) = &callable.params
+            && let Some(first) = params.items().first()
+            && let Type::Type(box Type::ClassType(first_cls)) = first.as_type()
+            && first_cls.class_object() == cls
+            && !first_cls.targs().is_empty()
+        {
+            self_obj = Some(first.as_type().clone());
+        }
         if let Some(targs) = ctor_targs.as_mut() {
             self.solver().freshen_class_targs(targs, self.uniques);
             let mp = targs.substitution_map();
@@ -1003,6 +1094,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     callable_name,
                     &params,
                     None,
+                    &callable.ret,
                     self_arg,
                     args,
                     keywords,
@@ -1025,6 +1117,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         callable_name,
                         &params.prepend_types(&concatenate),
                         None,
+                        &callable.ret,
                         self_arg,
                         args,
                         keywords,
@@ -1039,6 +1132,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         callable_name,
                         &ParamList::new_types(concatenate.into_vec()),
                         Some(var),
+                        &callable.ret,
                         self_arg,
                         args,
                         keywords,
@@ -1070,6 +1164,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                                 callable_name,
                                 &ParamList::new_types(concatenate.into_vec()),
                                 None,
+                                &callable.ret,
                                 self_arg,
                                 &args[0..args.len() - 1],
                                 &keywords[0..keywords.len() - 1],
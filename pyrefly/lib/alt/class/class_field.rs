@@ -14,6 +14,7 @@ use dupe::Dupe;
 use pyrefly_derive::TypeEq;
 use pyrefly_derive::VisitMut;
 use pyrefly_python::dunder;
+use pyrefly_python::module_name::ModuleName;
 use pyrefly_types::callable::FunctionKind;
 use pyrefly_types::callable::Params;
 use pyrefly_types::simplify::unions;
@@ -27,6 +28,7 @@ use pyrefly_util::visit::Visit;
 use pyrefly_util::visit::VisitMut;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ExprCall;
+use ruff_python_ast::Identifier;
 use ruff_python_ast::name::Name;
 use ruff_text_size::TextRange;
 use starlark_map::small_map::SmallMap;
@@ -39,6 +41,7 @@ use crate::alt::attr::AttrSubsetError;
 use crate::alt::attr::ClassBase;
 use crate::alt::attr::NoAccessReason;
 use crate::alt::callable::CallArg;
+use crate::alt::callable::CallKeyword;
 use crate::alt::expr::TypeOrExpr;
 use crate::alt::types::class_bases::ClassBases;
 use crate::alt::types::class_metadata::ClassMetadata;
@@ -68,6 +71,7 @@ use crate::types::literal::Lit;
 use crate::types::quantified::Quantified;
 use crate::types::read_only::ReadOnlyReason;
 use crate::types::stdlib::Stdlib;
+use crate::types::tuple::Tuple;
 use crate::types::typed_dict::TypedDict;
 use crate::types::typed_dict::TypedDictField;
 use crate::types::types::BoundMethod;
@@ -1940,6 +1944,172 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         name.starts_with("__") && !name.ends_with("__")
     }
 
+    /// Check a class whose `__slots__` contains `slots`: slots can't also have a class-level
+    /// value, instance attributes must be declared as slots, and `__dict__` or `__weakref__` can't
+    /// be requested when a base class already provides them.
+    pub fn check_slots(&self, cls: &Class, slots: &[Name], errors: &ErrorCollector) {
+        let mro = self.get_mro_for_class(cls);
+        let ancestors = mro.ancestors_no_object();
+        let slots_range = cls
+            .field_decl_range(&dunder::SLOTS)
+            .unwrap_or_else(|| cls.range());
+        // Instances of a class without `__slots__` have a `__dict__`. Builtin classes often omit
+        // `__slots__` in their stubs, so we can't tell whether they do.
+        let base_without_slots = ancestors
+            .iter()
+            .find(|base| !base.class_object().contains(&dunder::SLOTS));
+        if let Some(base) = base_without_slots
+            && base.class_object().module_name() != ModuleName::builtins()
+        {
+            for special in [dunder::DICT, dunder::WEAKREF] {
+                if slots.contains(&special) {
+                    self.error(
+                        errors,
+                        slots_range,
+                        ErrorInfo::Kind(ErrorKind::InvalidInheritance),
+                        format!(
+                            "`{special}` in `__slots__` of `{}` conflicts with base class `{}`, which has no `__slots__` and already provides it",
+                            cls.name(),
+                            base.name(),
+                        ),
+                    );
+                }
+            }
+        }
+        for slot in slots {
+            if slot != &dunder::SLOTS
+                && cls.is_field_initialized_on_class(slot)
+                && let Some(range) = cls.field_decl_range(slot)
+            {
+                self.error(
+                    errors,
+                    range,
+                    ErrorInfo::Kind(ErrorKind::BadClassDefinition),
+                    format!(
+                        "`{slot}` in `__slots__` conflicts with a class variable of the same name"
+                    ),
+                );
+            }
+        }
+        // We don't know which names a base class's `__slots__` contains, so only check classes
+        // whose bases all have empty `__slots__`.
+        if base_without_slots.is_some()
+            || slots.contains(&dunder::DICT)
+            || ancestors.iter().any(|base| {
+                !self
+                    .get_field_from_current_class_only(base.class_object(), &dunder::SLOTS)
+                    .is_some_and(|field| {
+                        matches!(field.ty(), Type::Tuple(Tuple::Concrete(elts)) if elts.is_empty())
+                    })
+            })
+        {
+            return;
+        }
+        for name in cls.fields() {
+            // Annotations without a value are usually declarations for a dataclass-like transform,
+            // which will generate the slot itself.
+            if !cls.is_field_initialized_on_class(name)
+                && !cls.is_field_annotated(name)
+                && !slots.contains(name)
+                && let Some(range) = cls.field_decl_range(name)
+            {
+                self.error(
+                    errors,
+                    range,
+                    ErrorInfo::Kind(ErrorKind::MissingAttribute),
+                    format!(
+                        "Attribute `{name}` is not declared in `__slots__` of `{}`",
+                        cls.name()
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Check the keywords in the header of `cls` against the `__init_subclass__` of its base
+    /// classes, which they are passed to. The keywords are also passed to the metaclass, so
+    /// classes with a custom metaclass aren't checked, and neither are typed dictionaries and
+    /// `dataclass_transform`-ed classes, whose keywords are interpreted specially.
+    pub fn check_init_subclass_keywords(
+        &self,
+        cls: &Class,
+        keywords: &[(Identifier, TextRange)],
+        errors: &ErrorCollector,
+    ) {
+        let metadata = self.get_metadata_for_class(cls);
+        if metadata.custom_metaclass().is_some()
+            || metadata.is_typed_dict()
+            || metadata.dataclass_transform_metadata().is_some()
+            || metadata.has_base_any()
+        {
+            return;
+        }
+        let Some(base) = self
+            .get_mro_for_class(cls)
+            .ancestors(self.stdlib)
+            .next()
+            .cloned()
+        else {
+            return;
+        };
+        let keyword_types = metadata.keywords();
+        let keywords = keywords
+            .iter()
+            .filter_map(|(name, range)| {
+                let (_, ty) = keyword_types.iter().find(|(x, _)| *x == name.id)?;
+                Some(CallKeyword {
+                    range: name.range.cover(*range),
+                    arg: Some(name),
+                    value: TypeOrExpr::Type(ty, *range),
+                })
+            })
+            .collect::<Vec<_>>();
+        // `__init_subclass__` is looked up as `super().__init_subclass__` would be in the body of
+        // `cls`, so an `__init_subclass__` defined by `cls` itself only applies to its subclasses.
+        let super_ty = Type::SuperInstance(Box::new((
+            base,
+            SuperObj::Class(self.as_class_type_unchecked(cls)),
+        )));
+        self.call_method_or_error(
+            &super_ty,
+            &dunder::INIT_SUBCLASS,
+            cls.range(),
+            &[],
+            &keywords,
+            errors,
+            None,
+        );
+    }
+
+    /// Check that the descriptors assigned in the body of `cls` accept the owner class and
+    /// attribute name their `__set_name__` is called with.
+    pub fn check_set_name(&self, cls: &Class, errors: &ErrorCollector) {
+        let owner = Type::ClassDef(cls.dupe());
+        for name in cls.fields() {
+            if !cls.is_field_initialized_on_class(name) {
+                continue;
+            }
+            let Some(field) = self.get_field_from_current_class_only(cls, name) else {
+                continue;
+            };
+            let ty = field.ty();
+            if !matches!(ty, Type::ClassType(_)) || !self.has_attr(&ty, &dunder::SET_NAME) {
+                continue;
+            }
+            let range = cls.field_decl_range(name).unwrap_or_else(|| cls.range());
+            let name_ty = Type::Literal(Lit::Str(name.as_str().into()));
+            self.call_method_or_error(
+                &ty,
+                &dunder::SET_NAME,
+                range,
+                &[CallArg::ty(&owner, range), CallArg::ty(&name_ty, range)],
+                &[],
+                errors,
+                None,
+            );
+        }
+    }
+
     pub fn check_consistent_override_for_field(
         &self,
         cls: &Class,
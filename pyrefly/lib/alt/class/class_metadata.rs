@@ -44,10 +44,12 @@ use crate::alt::types::class_metadata::TotalOrderingMetadata;
 use crate::alt::types::class_metadata::TypedDictMetadata;
 use crate::alt::types::pydantic::PydanticConfig;
 use crate::binding::base_class::BaseClass;
+use crate::binding::base_class::BaseClassCall;
 use crate::binding::base_class::BaseClassExpr;
 use crate::binding::base_class::BaseClassGeneric;
 use crate::binding::base_class::BaseClassGenericKind;
 use crate::binding::binding::Key;
+use crate::binding::binding::KeyExport;
 use crate::binding::pydantic::PydanticConfigDict;
 use crate::binding::pydantic::VALIDATION_ALIAS;
 use crate::config::error_kind::ErrorKind;
@@ -65,6 +67,8 @@ use crate::types::keywords::DataclassTransformKeywords;
 use crate::types::keywords::TypeMap;
 use crate::types::literal::Lit;
 use crate::types::types::CalleeKind;
+use crate::types::types::Forall;
+use crate::types::types::Forallable;
 use crate::types::types::Type;
 
 #[derive(Debug, Clone)]
@@ -832,6 +836,70 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// The instance type of the class inherited from by a call in a base class list, such as
+    /// `make_base(config)`: the class the config declares for the function in
+    /// `base-class-factories`, or else `X` when the function returns `type[X]`.
+    pub fn base_class_call_infer(
+        &self,
+        call: &BaseClassCall,
+        errors: &ErrorCollector,
+    ) -> Option<Type> {
+        let func = match self.base_class_expr_infer_for_metadata(&call.func, errors) {
+            Type::Function(func) => *func,
+            Type::Forall(box Forall {
+                body: Forallable::Function(func),
+                ..
+            }) => func,
+            _ => return None,
+        };
+        let kind = &func.metadata.kind;
+        let factory = match kind.class() {
+            Some(cls) => format!(
+                "{}.{}.{}",
+                kind.module_name(),
+                cls.name(),
+                kind.function_name()
+            ),
+            None => format!("{}.{}", kind.module_name(), kind.function_name()),
+        };
+        if let Some(base) = self.bindings().base_class_factory(&factory) {
+            let (module, name) = base.rsplit_once('.')?;
+            let module = ModuleName::from_str(module);
+            let name = Name::new(name);
+            if !self
+                .exports
+                .get(module)
+                .finding()?
+                .exports(self.exports)
+                .contains_key(&name)
+            {
+                return None;
+            }
+            let ty = self
+                .get_from_export(module, None, &KeyExport(name))
+                .arc_clone();
+            return self.untype_opt(ty, call.range(), errors);
+        }
+        match func.signature.ret {
+            // A class that depends on the type parameters of the function can't be worked out
+            // without solving the call.
+            Type::Type(box ty @ Type::ClassType(_))
+                if !ty.any(|x| {
+                    matches!(
+                        x,
+                        Type::Quantified(_)
+                            | Type::TypeVar(_)
+                            | Type::TypeVarTuple(_)
+                            | Type::ParamSpec(_)
+                    )
+                }) =>
+            {
+                Some(ty)
+            }
+            _ => None,
+        }
+    }
+
     fn parse_base_class(&self, base: &BaseClass, is_new_type: bool) -> BaseClassParseResult {
         let range = base.range();
         let parse_base_class_type = |ty| match ty {
@@ -883,6 +951,14 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
 
         match base {
             BaseClass::InvalidExpr(x) => BaseClassParseResult::InvalidExpr(x.clone()),
+            BaseClass::Call(x) => {
+                // Ignore all type errors here since they'll be reported when checking the call
+                let errors = ErrorCollector::new(self.module().dupe(), ErrorStyle::Never);
+                match self.base_class_call_infer(x, &errors) {
+                    Some(ty) => parse_base_class_type(ty),
+                    None => BaseClassParseResult::InvalidExpr(x.call.clone()),
+                }
+            }
             BaseClass::BaseClassExpr(x) => {
                 // Ignore all type errors here since they'll be reported in `class_bases_of` anyway
                 let errors = ErrorCollector::new(self.module().dupe(), ErrorStyle::Never);
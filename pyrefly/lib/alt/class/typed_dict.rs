@@ -11,10 +11,12 @@ use dupe::Dupe;
 use pyrefly_python::dunder;
 use pyrefly_types::simplify::unions_with_literals;
 use pyrefly_types::typed_dict::ExtraItems;
+use ruff_python_ast::Arguments;
 use ruff_python_ast::DictItem;
 use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
 use starlark_map::smallmap;
@@ -174,11 +176,18 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         });
         // You can update a TypedDict with a subset of its items. Otherwise, all required fields must be present.
         if !has_expansion && !is_update {
+            // Missing keys have no range of their own, so we point at the closing brace of the
+            // literal, which is where they would need to be added.
+            let missing_range = if self.module().code_at(range).ends_with('}') {
+                TextRange::new(range.end() - TextSize::from(1), range.end())
+            } else {
+                range
+            };
             for (key, field) in &fields {
                 if field.required && !keys.contains(key) {
                     self.error(
                         check_errors,
-                        range,
+                        missing_range,
                         ErrorInfo::Kind(ErrorKind::BadTypedDictKey),
                         format!(
                             "Missing required key `{}` for TypedDict `{}`",
@@ -808,6 +817,78 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             })
             .collect()
     }
+
+    /// Check a call of `get`, `pop` or `setdefault` on a `TypedDict` with a literal key, reporting
+    /// keys the `TypedDict` doesn't have and required or read-only keys that can't be removed or
+    /// set, which the synthesized overloads would otherwise hide behind `object` or `Any`. Returns
+    /// the result of the call if there is nothing left for the overloads to check.
+    pub fn typed_dict_method_call(
+        &self,
+        typed_dict: &TypedDict,
+        method: &Name,
+        arguments: &Arguments,
+        errors: &ErrorCollector,
+    ) -> Option<Type> {
+        let [key, rest @ ..] = &*arguments.args else {
+            return None;
+        };
+        if !arguments.keywords.is_empty() || arguments.args.iter().any(|x| x.is_starred_expr()) {
+            return None;
+        }
+        // The key is inferred again when the method is called, so its errors would be duplicates.
+        let Type::Literal(Lit::Str(key_name)) = self.expr_infer(key, &self.error_swallower())
+        else {
+            return None;
+        };
+        let key_name = Name::new(key_name);
+        let field = self.typed_dict_field(typed_dict, &key_name);
+        let error = |kind, msg| {
+            self.error(errors, key.range(), ErrorInfo::Kind(kind), msg);
+            for x in rest {
+                self.expr_infer(x, errors);
+            }
+        };
+        match field {
+            None if *method == GET_METHOD
+                && !matches!(
+                    self.typed_dict_extra_items(typed_dict.class_object()),
+                    ExtraItems::Extra(_)
+                ) =>
+            {
+                self.error(
+                    errors,
+                    key.range(),
+                    ErrorInfo::Kind(ErrorKind::BadTypedDictKey),
+                    format!(
+                        "TypedDict `{}` does not have key `{key_name}`",
+                        typed_dict.name()
+                    ),
+                );
+                None
+            }
+            Some(field) if *method == POP_METHOD && (field.required || field.is_read_only()) => {
+                error(
+                    ErrorKind::UnsupportedDelete,
+                    format!(
+                        "Key `{key_name}` in TypedDict `{}` may not be deleted",
+                        typed_dict.name()
+                    ),
+                );
+                Some(field.ty)
+            }
+            Some(field) if *method == SETDEFAULT_METHOD && field.is_read_only() => {
+                error(
+                    ErrorKind::ReadOnly,
+                    format!(
+                        "Key `{key_name}` in TypedDict `{}` is read-only",
+                        typed_dict.name()
+                    ),
+                );
+                Some(field.ty)
+            }
+            _ => None,
+        }
+    }
 }
 
 fn name_to_literal_type(name: &Name) -> Type {
@@ -42,6 +42,7 @@ use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use starlark_map::Hashed;
+use starlark_map::small_set::SmallSet;
 use vec1::Vec1;
 use vec1::vec1;
 
@@ -65,6 +66,7 @@ use crate::types::callable::Param;
 use crate::types::callable::ParamList;
 use crate::types::callable::Params;
 use crate::types::callable::Required;
+use crate::types::class::ClassType;
 use crate::types::facet::FacetKind;
 use crate::types::lit_int::LitInt;
 use crate::types::literal::Lit;
@@ -237,15 +239,15 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         if !ty.is_deprecated_function() {
             return;
         }
-        let deprecated_function = ty
-            .to_func_kind()
-            .map(|func_kind| func_kind.format(self.module().name()));
-        if let Some(deprecated_function) = deprecated_function {
-            self.error(
-                errors,
+        if let Some(func_kind) = ty.to_func_kind() {
+            errors.add_deprecated(
                 range,
                 ErrorInfo::Kind(ErrorKind::Deprecated),
-                format!("`{deprecated_function}` is deprecated"),
+                vec1![format!(
+                    "`{}` is deprecated",
+                    func_kind.format(self.module().name())
+                )],
+                func_kind.qualified_name(),
             );
         }
     }
@@ -481,7 +483,6 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             Expr::SetComp(x) => {
                 let elem_hint = hint.and_then(|ty| self.decompose_set(ty));
                 self.ifs_infer(&x.generators, errors);
-                self.ifs_infer(&x.generators, errors);
                 let elem_ty = self.expr_infer_with_hint_promote(
                     &x.elt,
                     elem_hint.as_ref().map(|hint| hint.as_ref()),
@@ -540,6 +541,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             Expr::Compare(x) => self.compare_infer(x, errors),
             Expr::Call(x) => {
                 let callee_ty = self.expr_infer(&x.func, errors);
+                self.check_unmanaged_resource(x, &callee_ty, errors);
                 if let Some(d) = self.call_to_dict(&callee_ty, &x.arguments) {
                     self.dict_infer(&d, hint, x.range, errors)
                 } else {
@@ -561,12 +563,15 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     _ => self.stdlib.str().clone().to_type(),
                 }
             }
-            Expr::TString(x) => self.error(
-                errors,
-                x.range,
-                ErrorInfo::Kind(ErrorKind::Unsupported),
-                "t-strings are not yet supported".to_owned(),
-            ),
+            Expr::TString(_) => {
+                x.recurse(&mut |x| {
+                    self.expr_infer(x, errors);
+                });
+                // Before 3.14, the parser has already reported the t-string as unsupported syntax.
+                self.stdlib
+                    .template()
+                    .map_or_else(Type::any_error, |cls| cls.clone().to_type())
+            }
             Expr::StringLiteral(x) => Lit::from_string_literal(x).to_type(),
             Expr::BytesLiteral(x) => Lit::from_bytes_literal(x).to_type(),
             Expr::NumberLiteral(x) => match &x.value {
@@ -605,6 +610,10 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         errors: &ErrorCollector,
     ) -> Type {
         let ty = self.expr_infer_with_hint(x, hint, errors);
+        self.promote_with_hint(ty, hint)
+    }
+
+    fn promote_with_hint(&self, ty: Type, hint: Option<HintRef>) -> Type {
         if let Some(want) = hint
             && self.is_subset_eq(&ty, want.ty())
         {
@@ -810,6 +819,9 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 .collect(),
             _ => vec![*hint],
         });
+        // A literal can't be `None`, so for `TD | None` we report errors against `TD` directly,
+        // pointing at the offending keys rather than rejecting the whole literal.
+        let candidates = hints.iter().filter(|hint| !hint.ty().is_none()).count();
         for hint in hints.iter() {
             let (typed_dict, is_update) = match hint.ty() {
                 Type::TypedDict(td) => (td, false),
@@ -827,10 +839,11 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 &item_errors,
             );
 
-            // We use the TypedDict hint if it successfully matched or if there is only one hint, unless
-            // this is a "soft" type hint, in which case we don't want to raise any check errors.
+            // We use the TypedDict hint if it successfully matched or if there is only one candidate
+            // hint, unless this is a "soft" type hint, in which case we don't want to raise any
+            // check errors.
             if check_errors.is_empty()
-                || hints.len() == 1
+                || candidates == 1
                     && hint
                         .errors()
                         .inspect(|errors| errors.extend(check_errors))
@@ -887,13 +900,27 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         } else {
             let mut key_tys = Vec::new();
             let mut value_tys = Vec::new();
+            // With an enum key type, track which members have an entry, so that dispatch tables
+            // can be checked for exhaustiveness. We give up as soon as a key isn't a member.
+            let mut enum_keys = key_hint.as_ref().and_then(|hint| match hint.ty() {
+                Type::ClassType(cls) if self.get_enum_from_class(cls.class_object()).is_some() => {
+                    Some((cls.clone(), SmallSet::new()))
+                }
+                _ => None,
+            });
             items.iter().for_each(|x| match &x.key {
                 Some(key) => {
-                    let key_t = self.expr_infer_with_hint_promote(
-                        key,
-                        key_hint.as_ref().map(|hint| hint.as_ref()),
-                        errors,
-                    );
+                    let key_hint = key_hint.as_ref().map(|hint| hint.as_ref());
+                    let key_t = self.expr_infer_with_hint(key, key_hint, errors);
+                    if let Some((cls, members)) = &mut enum_keys
+                        && let Type::Literal(Lit::Enum(lit)) = &key_t
+                        && lit.class == *cls
+                    {
+                        members.insert(lit.member.clone());
+                    } else {
+                        enum_keys = None;
+                    }
+                    let key_t = self.promote_with_hint(key_t, key_hint);
                     let value_t = self.expr_infer_with_hint_promote(
                         &x.value,
                         value_hint.as_ref().map(|hint| hint.as_ref()),
@@ -907,6 +934,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     }
                 }
                 None => {
+                    enum_keys = None;
                     let ty = self.expr_infer(&x.value, errors);
                     if let Some((key_t, value_t)) = self.unwrap_mapping(&ty) {
                         if !key_t.is_error() {
@@ -937,6 +965,9 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     }
                 }
             });
+            if let Some((cls, members)) = enum_keys {
+                self.check_enum_dict_exhaustive(&cls, &members, range, errors);
+            }
             if key_tys.is_empty() {
                 key_tys.push(Type::any_error())
             }
@@ -949,6 +980,37 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    fn check_enum_dict_exhaustive(
+        &self,
+        cls: &ClassType,
+        members: &SmallSet<Name>,
+        range: TextRange,
+        errors: &ErrorCollector,
+    ) {
+        let missing = self
+            .get_enum_members(cls.class_object())
+            .into_iter()
+            .filter_map(|lit| match lit {
+                Lit::Enum(lit) if !members.contains(&lit.member) => {
+                    Some(format!("`{}.{}`", cls.name(), lit.member))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            self.error(
+                errors,
+                range,
+                ErrorInfo::Kind(ErrorKind::NonExhaustiveEnumDict),
+                format!(
+                    "Dict literal keyed by enum `{}` is missing entries for {}",
+                    cls.name(),
+                    missing.join(", ")
+                ),
+            );
+        }
+    }
+
     /// If this is a `dict` call that can be converted to an equivalent dict literal (e.g., `dict(x=1)` => `{'x': 1}`),
     /// return the items in the converted dict.
     fn call_to_dict(&self, callee_ty: &Type, args: &Arguments) -> Option<Vec<DictItem>> {
@@ -1875,13 +1937,13 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         // TODO(stroxler): Add a new API, similar to `type_of_attr_get` but returning a
                         // LookupResult or an Optional type, that we could use here to avoid the double lookup.
                         if self.has_attr(&class_ty, &dunder::CLASS_GETITEM) {
-                            let cls_value = self.promote_silently(&cls);
-                            let call_args = [CallArg::ty(&cls_value, range), CallArg::expr(slice)];
+                            // `__class_getitem__` is an implicit classmethod, so is bound to the
+                            // class.
                             Some(self.call_method_or_error(
                                 &class_ty,
                                 &dunder::CLASS_GETITEM,
                                 range,
-                                &call_args,
+                                &[CallArg::expr(slice)],
                                 &[],
                                 errors,
                                 Some(&|| ErrorContext::Index(self.for_display(class_ty.clone()))),
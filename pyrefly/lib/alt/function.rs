@@ -10,7 +10,10 @@ use std::sync::Arc;
 
 use dupe::Dupe;
 use pyrefly_python::dunder;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePathDetails;
 use pyrefly_python::module_path::ModuleStyle;
+use pyrefly_python::nesting_context::NestingContext;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_types::callable::Params;
 use pyrefly_types::class::Class;
@@ -42,12 +45,15 @@ use crate::binding::binding::FunctionStubOrImpl;
 use crate::binding::binding::Key;
 use crate::binding::binding::KeyClass;
 use crate::binding::binding::KeyClassMetadata;
+use crate::binding::binding::KeyExport;
 use crate::binding::binding::KeyLegacyTypeParam;
+use crate::binding::parameter_provider::ProvidedParameter;
 use crate::config::error_kind::ErrorKind;
 use crate::error::collector::ErrorCollector;
 use crate::error::context::ErrorInfo;
 use crate::error::context::TypeCheckContext;
 use crate::error::context::TypeCheckKind;
+use crate::error::style::ErrorStyle;
 use crate::graph::index::Idx;
 use crate::types::callable::Callable;
 use crate::types::callable::FuncFlags;
@@ -59,6 +65,7 @@ use crate::types::callable::ParamList;
 use crate::types::callable::Required;
 use crate::types::class::ClassKind;
 use crate::types::keywords::DataclassTransformKeywords;
+use crate::types::tuple::Tuple;
 use crate::types::types::CalleeKind;
 use crate::types::types::Forall;
 use crate::types::types::Forallable;
@@ -226,15 +233,16 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             .as_ref()
             .map(|cls| Type::SelfType(self.as_class_type_unchecked(cls)));
 
-        // __new__ is an implicit staticmethod, __init_subclass__ is an implicit classmethod
-        // __new__, unlike decorated staticmethods, uses Self
+        // __new__ is an implicit staticmethod, __init_subclass__ and __class_getitem__ are implicit
+        // classmethods. __new__, unlike decorated staticmethods, uses Self
         let is_dunder_new = defining_cls.is_some() && def.name.as_str() == dunder::NEW;
-        let is_dunder_init_subclass =
-            defining_cls.is_some() && def.name.as_str() == dunder::INIT_SUBCLASS;
+        let is_implicit_classmethod = defining_cls.is_some()
+            && (def.name.as_str() == dunder::INIT_SUBCLASS
+                || def.name.as_str() == dunder::CLASS_GETITEM);
 
         let mut flags = FuncFlags {
             is_staticmethod: is_dunder_new,
-            is_classmethod: is_dunder_init_subclass,
+            is_classmethod: is_implicit_classmethod,
             ..Default::default()
         };
         let mut found_class_property = false;
@@ -296,10 +304,290 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         })
     }
 
+    /// Report the parameters that are never used in the function body. Parameters are exempt
+    /// when their names are dictated by something other than the body: the receiver of a method,
+    /// and every parameter of stubs, overloads, abstract methods, dunder methods and overrides.
+    pub fn check_unused_parameters(
+        &self,
+        def: &StmtFunctionDef,
+        function: &UndecoratedFunction,
+        unused_parameters: &[Identifier],
+        errors: &ErrorCollector,
+    ) {
+        let flags = &function.metadata.flags;
+        let name = &def.name.id;
+        if unused_parameters.is_empty()
+            || function.stub_or_impl == FunctionStubOrImpl::Stub
+            || flags.is_overload
+            || flags.is_abstract_method
+            || flags.is_override
+            || (name.starts_with("__") && name.ends_with("__"))
+        {
+            return;
+        }
+        let receiver = match &function.defining_cls {
+            Some(cls) => {
+                if self
+                    .get_mro_for_class(cls)
+                    .ancestors(self.stdlib)
+                    .any(|base| base.class_object().contains(name))
+                {
+                    return;
+                }
+                if flags.is_staticmethod {
+                    None
+                } else {
+                    def.parameters
+                        .iter_non_variadic_params()
+                        .next()
+                        .map(|x| &x.parameter.name.id)
+                }
+            }
+            None => None,
+        };
+        for param in unused_parameters {
+            if Some(&param.id) != receiver {
+                self.error(
+                    errors,
+                    param.range,
+                    ErrorInfo::Kind(ErrorKind::UnusedParameter),
+                    format!("Parameter `{}` is never used", param.id),
+                );
+            }
+        }
+    }
+
+    /// Whether a function is part of the public API of a library: a function at the top level of a
+    /// public module, or a method of a class at the top level of one, with a public name.
+    fn is_public_api(
+        &self,
+        name: &Name,
+        function: &UndecoratedFunction,
+        parent: &NestingContext,
+    ) -> bool {
+        let is_private = |x: &str| x.starts_with('_');
+        if self
+            .module()
+            .name()
+            .components()
+            .iter()
+            .any(|x| is_private(x))
+        {
+            return false;
+        }
+        if parent.is_toplevel() {
+            !is_private(name)
+        } else if let Some(cls) = &function.defining_cls
+            && parent.is_class()
+            && parent.parent().is_some_and(|x| x.is_toplevel())
+        {
+            !is_private(cls.name())
+                && (!is_private(name) || (name.starts_with("__") && name.ends_with("__")))
+        } else {
+            false
+        }
+    }
+
+    /// The way that `cls` can't be referred to by users of a library, if any: either its name is
+    /// private, or it is defined in a private module and the nearest public package doesn't
+    /// re-export it. Classes from the standard library are exempt, since they are implementation
+    /// details of Python itself.
+    fn private_class_reason(&self, cls: &Class, check_name: bool) -> Option<String> {
+        if matches!(
+            cls.module().path().details(),
+            ModulePathDetails::BundledTypeshed(_)
+        ) {
+            return None;
+        }
+        if check_name && cls.name().starts_with('_') {
+            return Some(format!("internal class `{}`", cls.name()));
+        }
+        let module = cls.module_name();
+        let components = module.components();
+        let public = components
+            .iter()
+            .take_while(|x| !x.starts_with('_'))
+            .collect::<Vec<_>>();
+        if public.len() == components.len() {
+            return None;
+        }
+        let reexported = !public.is_empty()
+            && self
+                .exports
+                .get(ModuleName::from_parts(public))
+                .finding()
+                .is_some_and(|x| x.exports(self.exports).contains_key(cls.name()));
+        if reexported {
+            None
+        } else {
+            Some(format!(
+                "`{}` from private module `{module}`, which isn't re-exported",
+                cls.name()
+            ))
+        }
+    }
+
+    /// For `--library-mode`, check that a function in the public API of a library is fully
+    /// annotated, and that its signature only mentions types that users of the library can refer
+    /// to.
+    pub fn check_public_api(
+        &self,
+        def: &StmtFunctionDef,
+        function: &UndecoratedFunction,
+        parent: &NestingContext,
+        errors: &ErrorCollector,
+    ) {
+        let name = &def.name.id;
+        if !self.is_public_api(name, function, parent) {
+            return;
+        }
+        let receiver =
+            if function.defining_cls.is_some() && !function.metadata.flags.is_staticmethod {
+                def.parameters
+                    .iter_non_variadic_params()
+                    .next()
+                    .map(|x| &x.parameter.name.id)
+            } else {
+                None
+            };
+        for p in def.parameters.iter() {
+            if p.annotation().is_none() && Some(&p.name().id) != receiver {
+                self.error(
+                    errors,
+                    p.name().range(),
+                    ErrorInfo::Kind(ErrorKind::MissingPublicAnnotation),
+                    format!(
+                        "Parameter `{}` of public function `{name}` is missing an annotation",
+                        p.name()
+                    ),
+                );
+            }
+        }
+        let has_return_annotation = self.bindings().function_has_return_annotation(&def.name);
+        if !has_return_annotation && *name != dunder::INIT {
+            self.error(
+                errors,
+                def.name.range(),
+                ErrorInfo::Kind(ErrorKind::MissingPublicAnnotation),
+                format!("Public function `{name}` is missing a return annotation"),
+            );
+        }
+
+        let mut reasons = SmallSet::new();
+        let mut check = |ty: &Type, check_name: bool| {
+            ty.universe(&mut |ty| {
+                let cls = match ty {
+                    Type::ClassType(x) => x.class_object(),
+                    Type::ClassDef(x) => x,
+                    _ => return,
+                };
+                if let Some(reason) = self.private_class_reason(cls, check_name) {
+                    reasons.insert(reason);
+                }
+            })
+        };
+        for param in &function.params {
+            check(param.as_type(), false);
+        }
+        if has_return_annotation {
+            let ret = self.get(&Key::ReturnType(ShortIdentifier::new(&def.name)));
+            check(ret.ty(), true);
+        }
+        for reason in reasons {
+            self.error(
+                errors,
+                def.name.range(),
+                ErrorInfo::Kind(ErrorKind::PrivateTypeInPublicApi),
+                format!("Public function `{name}` mentions {reason}"),
+            );
+        }
+    }
+
+    /// Check that `__enter__`, `__exit__` and their async counterparts can be called the way that a
+    /// `with` or `async with` statement calls them.
+    fn check_context_manager_method(
+        &self,
+        def: &UndecoratedFunction,
+        stmt: &StmtFunctionDef,
+        ret: &Type,
+        errors: &ErrorCollector,
+    ) {
+        let name = &stmt.name.id;
+        let (is_async_method, num_args, counterpart) = match name.as_str() {
+            "__enter__" => (false, 0, dunder::AENTER),
+            "__exit__" => (false, 3, dunder::AEXIT),
+            "__aenter__" => (true, 0, dunder::ENTER),
+            "__aexit__" => (true, 3, dunder::EXIT),
+            _ => return,
+        };
+        if def.defining_cls.is_none() || def.metadata.flags.is_overload {
+            return;
+        }
+        let params = if def.metadata.flags.is_staticmethod {
+            &def.params[..]
+        } else {
+            def.params.get(1..).unwrap_or_default()
+        };
+        let positional = params
+            .iter()
+            .filter(|p| matches!(p, Param::PosOnly(..) | Param::Pos(..)))
+            .count();
+        let required_positional = params
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p,
+                    Param::PosOnly(_, _, Required::Required) | Param::Pos(_, _, Required::Required)
+                )
+            })
+            .count();
+        let accepts_args =
+            positional >= num_args || params.iter().any(|p| matches!(p, Param::VarArg(..)));
+        let requires_more_args = required_positional > num_args
+            || params
+                .iter()
+                .any(|p| matches!(p, Param::KwOnly(_, _, Required::Required)));
+        if !accepts_args || requires_more_args {
+            let expected = if num_args == 0 {
+                "no arguments besides `self`"
+            } else {
+                "three positional arguments besides `self`: the exception type, value and traceback"
+            };
+            self.error(
+                errors,
+                stmt.name.range,
+                ErrorInfo::Kind(ErrorKind::BadFunctionDefinition),
+                format!("`{name}` should accept {expected}"),
+            );
+        }
+        if stmt.is_async && !is_async_method {
+            self.error(
+                errors,
+                stmt.name.range,
+                ErrorInfo::Kind(ErrorKind::BadFunctionDefinition),
+                format!(
+                    "`{name}` should not be async, since `with` does not await it, did you mean `{counterpart}`?"
+                ),
+            );
+        } else if is_async_method
+            && !stmt.is_async
+            && !ret.is_any()
+            && self.unwrap_awaitable(ret).is_none()
+        {
+            self.error(
+                errors,
+                stmt.name.range,
+                ErrorInfo::Kind(ErrorKind::BadFunctionDefinition),
+                format!("`{name}` should return an awaitable, since `async with` awaits it"),
+            );
+        }
+    }
+
     pub fn decorated_function_type(
         &self,
         def: &UndecoratedFunction,
         stmt: &StmtFunctionDef,
+        called_by_framework: bool,
         errors: &ErrorCollector,
     ) -> Arc<Type> {
         let mut ret = self
@@ -325,7 +613,10 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             );
             ret = coroutine_ret;
         }
-        if !has_return_annotation_or_infers_return {
+        // Nothing reads the return value of a function called by a framework except the
+        // framework, like pytest, which takes the value of a fixture from its body rather than its
+        // annotation.
+        if !has_return_annotation_or_infers_return && !called_by_framework {
             self.error(
                 errors,
                 stmt.name.range(),
@@ -335,7 +626,14 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
         for p in stmt.parameters.iter() {
             let name = p.name().as_str();
-            if p.annotation().is_none() && name != "cls" && name != "self" {
+            if p.annotation().is_none()
+                && name != "cls"
+                && name != "self"
+                && !matches!(
+                    self.bindings().get_function_param(p.name()),
+                    FunctionParameter::Unannotated(_, _, Some(_))
+                )
+            {
                 self.error(
                     errors,
                     p.name().range(),
@@ -347,6 +645,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 );
             }
         }
+        self.check_context_manager_method(def, stmt, &ret, errors);
         if matches!(&ret, Type::TypeGuard(_) | Type::TypeIs(_)) {
             self.validate_type_guard_positional_argument_count(
                 &def.params,
@@ -523,6 +822,82 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// The value a fixture or dependency provides, given the return type of its function: what
+    /// it yields if it is a generator, and what it returns once awaited if it is async.
+    fn provided_value(&self, ret: Type, is_async: bool, is_generator: bool) -> Type {
+        if ret.is_any() {
+            return ret;
+        }
+        let value = match (is_async, is_generator) {
+            (false, false) => Some(ret),
+            (true, false) => self.unwrap_coroutine(&ret).map(|(_, _, x)| x),
+            (false, true) => self.unwrap_iterable(&ret),
+            (true, true) => self.unwrap_async_iterable(&ret),
+        };
+        value.unwrap_or_else(Type::any_implicit)
+    }
+
+    /// The value provided by a function we can't see the definition of, going by its type.
+    fn provided_value_of_callable(&self, func: &Type) -> Type {
+        let Some(ret) = func.callable_return_type() else {
+            return Type::any_implicit();
+        };
+        let is_one_of = |names: &[&str]| match &ret {
+            Type::ClassType(cls) => names.iter().any(|x| cls.has_qname("typing", x)),
+            _ => false,
+        };
+        let is_async = is_one_of(&["Coroutine", "AsyncGenerator", "AsyncIterator"]);
+        let is_generator = is_one_of(&["Generator", "Iterator", "AsyncGenerator", "AsyncIterator"]);
+        self.provided_value(ret, is_async, is_generator)
+    }
+
+    /// The type of the values a framework passes to a parameter.
+    fn provided_parameter_type(&self, param: &ProvidedParameter) -> Type {
+        // The expressions are checked where they are written, so don't report errors again.
+        let errors = ErrorCollector::new(self.module().dupe(), ErrorStyle::Never);
+        match param {
+            ProvidedParameter::Fixture(idx) => {
+                let ret = self.get_idx(*idx).arc_clone_ty();
+                match self.bindings().get(*idx) {
+                    Binding::ReturnType(x) => {
+                        self.provided_value(ret, x.is_async, x.kind.is_generator())
+                    }
+                    // The fixture isn't checked, so returns `Any`.
+                    _ => ret,
+                }
+            }
+            ProvidedParameter::ExternalFixture(module, name) => self.provided_value_of_callable(
+                &self.get_from_export(*module, None, &KeyExport(name.clone())),
+            ),
+            ProvidedParameter::Dependency(x) => match self.expr_infer(x, &errors) {
+                // A class is instantiated to provide the value.
+                ty @ Type::ClassDef(_) => self
+                    .untype_opt(ty, x.range(), &errors)
+                    .unwrap_or_else(Type::any_implicit),
+                ty => self.provided_value_of_callable(&ty),
+            },
+            ProvidedParameter::Values(values) => {
+                if values.is_empty() {
+                    return Type::any_implicit();
+                }
+                self.unions(
+                    values.map(|x| self.expr_infer(x, &errors).promote_literals(self.stdlib)),
+                )
+            }
+            ProvidedParameter::Instance(x) => {
+                let ty = self.expr_infer(x, &errors);
+                self.untype_opt(ty, x.range(), &errors)
+                    .unwrap_or_else(Type::any_implicit)
+            }
+            ProvidedParameter::Multiple(x) => {
+                Type::Tuple(Tuple::unbounded(self.provided_parameter_type(x)))
+            }
+            ProvidedParameter::Optional(x) => {
+                self.union(self.provided_parameter_type(x), Type::None)
+            }
+        }
+    }
+
     /// Determine the type and required-ness of a parameter.
     fn get_param_type_and_requiredness(
         &self,
@@ -550,22 +925,29 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 );
                 (param_ty, required)
             }
-            FunctionParameter::Unannotated(var, _) => {
+            FunctionParameter::Unannotated(var, _, provided) => {
                 let required = self.get_requiredness(default, None, stub_or_impl, errors);
                 // If this is the first parameter and there is a self type, solve to `Self`.
                 // We only try to solve the first param for now. Other unannotated params
-                // are also Var. If a default value of type T is provided, it will resolve to Any | T.
-                // Otherwise, it will be forced to Any
+                // are also Var. If a framework passes the parameter a value, it will resolve to
+                // the type of that value. If a default value of type T is provided, it will resolve
+                // to Any | T, or just T in untyped packages whose signatures we infer, unless T is
+                // None. Otherwise, it will be forced to Any
                 if let Some(ty) = self_type {
                     self.solver().solve_parameter(*var, ty);
+                } else if let Some(provided) = provided {
+                    self.solver()
+                        .solve_parameter(*var, self.provided_parameter_type(provided));
                 } else if let Required::Optional(Some(default_ty)) = &required {
-                    self.solver().solve_parameter(
-                        *var,
-                        self.union(
-                            Type::any_implicit(),
-                            default_ty.clone().promote_literals(self.stdlib),
-                        ),
-                    );
+                    let default_ty = default_ty.clone().promote_literals(self.stdlib);
+                    let ty = if self.bindings().infer_parameters_from_defaults()
+                        && !default_ty.is_none()
+                    {
+                        default_ty
+                    } else {
+                        self.union(Type::any_implicit(), default_ty)
+                    };
+                    self.solver().solve_parameter(*var, ty);
                 }
                 (self.solver().force_var(*var), required)
             }
@@ -675,7 +1057,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     let annot = self.get_idx(*idx);
                     annot.annotation.get_type().clone()
                 }
-                FunctionParameter::Unannotated(var, _) => self.solver().force_var(*var),
+                FunctionParameter::Unannotated(var, _, _) => self.solver().force_var(*var),
             };
             if let Type::Kwargs(q) = &ty {
                 paramspec_kwargs = Some(q.clone());
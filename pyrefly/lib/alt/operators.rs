@@ -33,6 +33,7 @@ use crate::error::context::TypeCheckKind;
 use crate::graph::index::Idx;
 use crate::types::literal::Lit;
 use crate::types::tuple::Tuple;
+use crate::types::typed_dict::TypedDict;
 use crate::types::types::Type;
 
 impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
@@ -211,6 +212,27 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// Merging a dict literal into a TypedDict with `|` or `|=` only replaces some of its items, so
+    /// the literal, including any `**` spreads in it, is checked against a partial version of the
+    /// TypedDict, and the result has the TypedDict's type.
+    fn merge_dict_literal_into_typed_dict(
+        &self,
+        typed_dict: &TypedDict,
+        literal: &Expr,
+        errors: &ErrorCollector,
+    ) -> Type {
+        let partial = Type::PartialTypedDict(typed_dict.clone());
+        self.expr_infer_with_hint(literal, Some(HintRef::new(&partial, Some(errors))), errors);
+        Type::TypedDict(typed_dict.clone())
+    }
+
+    /// Whether merging `other` into `typed_dict` with `|` or `|=` keeps it a `typed_dict`, which is
+    /// the case when `other` is a TypedDict with only some of its items, e.g. a `total=False` one.
+    fn is_typed_dict_update(&self, typed_dict: &TypedDict, other: &Type) -> bool {
+        matches!(other, Type::TypedDict(_) | Type::PartialTypedDict(_))
+            && self.is_subset_eq(other, &Type::PartialTypedDict(typed_dict.clone()))
+    }
+
     pub fn binop_infer(
         &self,
         x: &ExprBinOp,
@@ -245,6 +267,12 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             } else {
                 lhs = self.expr_infer(&x.left, errors);
             }
+        } else if x.op == Operator::BitOr && matches!(&*x.right, Expr::Dict(_)) {
+            lhs = self.expr_infer(&x.left, errors);
+            if let Type::TypedDict(typed_dict) = &lhs {
+                return self.merge_dict_literal_into_typed_dict(typed_dict, &x.right, errors);
+            }
+            rhs = self.expr_infer(&x.right, errors);
         } else {
             lhs = self.expr_infer(&x.left, errors);
             rhs = self.expr_infer(&x.right, errors);
@@ -284,6 +312,11 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     && let Type::Tuple(r) = rhs
                 {
                     self.tuple_concat(l, r)
+                } else if x.op == Operator::BitOr
+                    && let Type::TypedDict(typed_dict) = lhs
+                    && self.is_typed_dict_update(typed_dict, rhs)
+                {
+                    lhs.clone()
                 } else {
                     binop_call(x.op, lhs, rhs, x.range)
                 }
@@ -313,28 +346,40 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             self.try_binop_calls(&calls_to_try, range, errors, &context)
         };
         let base = self.expr_infer(&x.target, errors);
-        let rhs = self.expr_infer(&x.value, errors);
         let tcc: &dyn Fn() -> TypeCheckContext =
             &|| TypeCheckContext::of_kind(TypeCheckKind::AugmentedAssignment);
-        let result = self.distribute_over_union(&base, |lhs| {
-            self.distribute_over_union(&rhs, |rhs| {
-                if let Type::Any(style) = &base {
-                    style.propagate()
-                } else if x.op == Operator::Add
-                    && base.is_literal_string()
-                    && rhs.is_literal_string()
-                {
-                    Type::LiteralString
-                } else if x.op == Operator::Add
-                    && let Type::Tuple(ref l) = base
-                    && let Type::Tuple(r) = rhs
-                {
-                    self.tuple_concat(l, r)
-                } else {
-                    binop_call(x.op, lhs, rhs, x.range)
-                }
+        let result = if x.op == Operator::BitOr
+            && matches!(&*x.value, Expr::Dict(_))
+            && let Type::TypedDict(typed_dict) = &base
+        {
+            self.merge_dict_literal_into_typed_dict(typed_dict, &x.value, errors)
+        } else {
+            let rhs = self.expr_infer(&x.value, errors);
+            self.distribute_over_union(&base, |lhs| {
+                self.distribute_over_union(&rhs, |rhs| {
+                    if let Type::Any(style) = &base {
+                        style.propagate()
+                    } else if x.op == Operator::Add
+                        && base.is_literal_string()
+                        && rhs.is_literal_string()
+                    {
+                        Type::LiteralString
+                    } else if x.op == Operator::Add
+                        && let Type::Tuple(ref l) = base
+                        && let Type::Tuple(r) = rhs
+                    {
+                        self.tuple_concat(l, r)
+                    } else if x.op == Operator::BitOr
+                        && let Type::TypedDict(typed_dict) = lhs
+                        && self.is_typed_dict_update(typed_dict, rhs)
+                    {
+                        lhs.clone()
+                    } else {
+                        binop_call(x.op, lhs, rhs, x.range)
+                    }
+                })
             })
-        });
+        };
         // If we're assigning to something with an annotation, make sure the produced value is assignable to it
         if let Some(ann) = ann.map(|k| self.get_idx(k)) {
             if ann.annotation.is_final() {
@@ -18,6 +18,7 @@ use pyrefly_util::gas::Gas;
 use pyrefly_util::owner::Owner;
 use pyrefly_util::prelude::SliceExt;
 use pyrefly_util::prelude::VecExt;
+use ruff_python_ast::Expr;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use vec1::Vec1;
@@ -192,6 +193,18 @@ impl<'a> ArgsExpander<'a> {
     }
 }
 
+fn is_lambda(x: &TypeOrExpr) -> bool {
+    matches!(x, TypeOrExpr::Expr(Expr::Lambda(_)))
+}
+
+fn replace_lambda<'a>(x: TypeOrExpr<'a>, placeholder: &'a Type) -> TypeOrExpr<'a> {
+    if is_lambda(&x) {
+        TypeOrExpr::Type(placeholder, x.range())
+    } else {
+        x
+    }
+}
+
 impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
     /// Calls an overloaded function, returning the return type and the closest matching overload signature.
     pub fn call_overloads(
@@ -211,10 +224,20 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         // There may be Expr values in args and keywords.
         // If we infer them for each overload, we may end up inferring them multiple times.
         // If those overloads contain nested overloads, then we can easily end up with O(2^n) perf.
-        // Therefore, flatten all TypeOrExpr's into Type before we start
+        // Therefore, flatten all TypeOrExpr's into Type before we start, except for lambdas,
+        // which `overload_for_lambdas` deals with.
         let call = CallWithTypes::new();
-        let args = call.vec_call_arg(args, self, errors);
-        let keywords = call.vec_call_keyword(keywords, self, errors);
+        let args = args.map(|arg| match arg {
+            CallArg::Arg(x) | CallArg::Star(x, _) if is_lambda(x) => arg.clone(),
+            _ => call.call_arg(arg, self, errors),
+        });
+        let keywords = keywords.map(|kw| {
+            if is_lambda(&kw.value) {
+                kw.clone()
+            } else {
+                call.call_keyword(kw, self, errors)
+            }
+        });
 
         // Evaluate the call following https://typing.python.org/en/latest/spec/overload.html#overload-call-evaluation.
 
@@ -235,6 +258,22 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                 mismatch_size == 0
             })
             .collect::<Vec<_>>();
+        let (arity_compatible_overloads, args, keywords) = match self.overload_for_lambdas(
+            &arity_compatible_overloads,
+            &metadata,
+            self_obj.as_ref(),
+            &args,
+            &keywords,
+            range,
+            &ctor_targs,
+        ) {
+            Some(overload) => (vec![overload], args, keywords),
+            None => (
+                arity_compatible_overloads,
+                call.vec_call_arg(&args, self, errors),
+                call.vec_call_keyword(&keywords, self, errors),
+            ),
+        };
         let (closest_overload, matched) = match Vec1::try_from_vec(arity_compatible_overloads) {
             Err(_) => (
                 CalledOverload {
@@ -319,20 +358,16 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         );
         if matched {
             // If the selected overload is deprecated, we log a deprecation error.
-            if closest_overload.func.1.metadata.flags.is_deprecated {
-                self.error(
-                    errors,
+            let metadata = &closest_overload.func.1.metadata;
+            if metadata.flags.is_deprecated {
+                errors.add_deprecated(
                     range,
                     ErrorInfo::new(ErrorKind::Deprecated, context),
-                    format!(
+                    vec1![format!(
                         "Call to deprecated overload `{}`",
-                        closest_overload
-                            .func
-                            .1
-                            .metadata
-                            .kind
-                            .format(self.module().name())
-                    ),
+                        metadata.kind.format(self.module().name())
+                    )],
+                    metadata.kind.qualified_name(),
                 );
             }
             (closest_overload.res, closest_overload.func.1.signature)
@@ -398,6 +433,55 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// A lambda's parameters are typed from the parameter it is passed to, but they can only be
+    /// inferred once. If there are lambda arguments, find the one overload that accepts the other
+    /// arguments along with an arbitrary callable in place of each lambda, so that the lambdas can
+    /// be typed against it. Returns `None` if there are no lambdas, or no single such overload.
+    fn overload_for_lambdas<'c>(
+        &self,
+        overloads: &[&'c TargetWithTParams<Function>],
+        metadata: &FuncMetadata,
+        self_obj: Option<&Type>,
+        args: &[CallArg],
+        keywords: &[CallKeyword],
+        range: TextRange,
+        ctor_targs: &Option<&mut TArgs>,
+    ) -> Option<&'c TargetWithTParams<Function>> {
+        if !args.iter().any(|arg| match arg {
+            CallArg::Arg(x) | CallArg::Star(x, _) => is_lambda(x),
+        }) && !keywords.iter().any(|kw| is_lambda(&kw.value))
+        {
+            return None;
+        }
+        if let [overload] = overloads {
+            return Some(*overload);
+        }
+        let placeholder = Type::callable_ellipsis(Type::any_implicit());
+        let args = args.map(|arg| match arg {
+            CallArg::Arg(x) => CallArg::Arg(replace_lambda(*x, &placeholder)),
+            CallArg::Star(x, r) => CallArg::Star(replace_lambda(*x, &placeholder), *r),
+        });
+        let keywords = keywords.map(|kw| CallKeyword {
+            range: kw.range,
+            arg: kw.arg,
+            value: replace_lambda(kw.value, &placeholder),
+        });
+        let errors = self.error_swallower();
+        let mut matched = overloads.iter().filter(|overload| {
+            self.try_call_overload(
+                overload, metadata, self_obj, &args, &keywords, range, &errors, None, ctor_targs,
+            )
+            .call_errors
+            .is_empty()
+        });
+        let overload = matched.next()?;
+        if matched.next().is_some() {
+            None
+        } else {
+            Some(*overload)
+        }
+    }
+
     fn arity_mismatch_size(
         &self,
         expected_arg_counts: &ArgCounts,
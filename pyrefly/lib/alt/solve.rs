@@ -12,6 +12,7 @@ use std::sync::Arc;
 use dupe::Dupe;
 use pyrefly_python::ast::Ast;
 use pyrefly_python::dunder;
+use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_types::facet::FacetKind;
 use pyrefly_types::type_info::JoinStyle;
@@ -27,6 +28,7 @@ use ruff_python_ast::TypeParams;
 use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
+use starlark_map::Hashed;
 use starlark_map::ordered_set::OrderedSet;
 use starlark_map::small_map::Entry;
 use starlark_map::small_map::SmallMap;
@@ -97,11 +99,13 @@ use crate::error::context::ErrorInfo;
 use crate::error::context::TypeCheckContext;
 use crate::error::context::TypeCheckKind;
 use crate::error::style::ErrorStyle;
+use crate::export::exports::ExportLocation;
 use crate::export::special::SpecialExport;
 use crate::graph::index::Idx;
 use crate::solver::solver::SubsetError;
 use crate::types::annotation::Annotation;
 use crate::types::annotation::Qualifier;
+use crate::types::callable::Callable;
 use crate::types::callable::Function;
 use crate::types::callable::FunctionKind;
 use crate::types::callable::Param;
@@ -125,6 +129,7 @@ use crate::types::type_var_tuple::TypeVarTuple;
 use crate::types::types::AnyStyle;
 use crate::types::types::CalleeKind;
 use crate::types::types::Forallable;
+use crate::types::types::OverloadType;
 use crate::types::types::SuperObj;
 use crate::types::types::TParam;
 use crate::types::types::TParams;
@@ -1099,6 +1104,24 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         if !self.has_valid_annotation_syntax(expr, errors) {
             return Type::any_error();
         }
+        // As in an annotation, a variable holding a class object is a value rather than a type.
+        // An implicit alias is only an alias if its value is a type, so such a variable just
+        // makes it an ordinary variable.
+        if style != TypeAliasStyle::LegacyImplicit
+            && let Expr::Name(x) = expr
+            && let Type::Type(_) = &ty
+        {
+            return self.error(
+                errors,
+                range,
+                ErrorInfo::Kind(ErrorKind::InvalidTypeAlias),
+                format!(
+                    "Variable `{}` of type `{}` is not a type alias and cannot be aliased",
+                    x.id,
+                    self.for_display(ty),
+                ),
+            );
+        }
         let untyped = self.untype_opt(ty.clone(), range, errors);
         let mut ty = if let Some(untyped) = untyped {
             let validated =
@@ -1653,10 +1676,121 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     );
                 }
             }
+            BindingExpect::ConsistentWithStub {
+                implementation,
+                stub,
+                name,
+                range,
+            } => {
+                self.check_consistent_with_stub(*implementation, stub, name, *range, errors);
+            }
         }
         Arc::new(EmptyAnswer)
     }
 
+    /// Check the type this `.py` implementation gives `name` against the one the `.pyi` stub at
+    /// `stub` declares. The classes defined here are distinct from the ones the stub declares, so
+    /// they are replaced by the stub's before comparing, and the classes themselves aren't compared.
+    fn check_consistent_with_stub(
+        &self,
+        implementation: Idx<KeyExport>,
+        stub: &ModulePath,
+        name: &Name,
+        range: TextRange,
+        errors: &ErrorCollector,
+    ) {
+        let module = self.module().name();
+        let want = self.get_from_export(module, Some(stub), &KeyExport(name.clone()));
+        let got = self.get_idx(implementation);
+        if matches!((&*got, &*want), (Type::ClassDef(_), Type::ClassDef(_))) {
+            return;
+        }
+        let stub_exports = self.exports.get(module).finding();
+        let stub_class = |cls: &Class| {
+            if cls.module_path() != self.module().path()
+                || !stub_exports.as_ref().is_some_and(|x| {
+                    matches!(
+                        x.exports(self.exports).get(cls.name()),
+                        Some(ExportLocation::ThisModule(_))
+                    )
+                })
+            {
+                return None;
+            }
+            match &*self.get_from_export(module, Some(stub), &KeyExport(cls.name().clone())) {
+                Type::ClassDef(x) => Some(x.dupe()),
+                _ => None,
+            }
+        };
+        let got = (*got).clone().transform(&mut |x| {
+            let replacement = match x {
+                Type::ClassType(c) => stub_class(c.class_object())
+                    .map(|cls| Type::ClassType(ClassType::new(cls, c.targs().clone()))),
+                Type::ClassDef(c) => stub_class(c).map(Type::ClassDef),
+                _ => None,
+            };
+            if let Some(replacement) = replacement {
+                *x = replacement;
+            }
+        });
+        if let Type::Overload(overload) = &*want
+            && !matches!(got, Type::Overload(_))
+        {
+            // The implementation of an overloaded function doesn't repeat the overloads, so it
+            // must satisfy each of them, like the implementation of `@overload`s in one file.
+            if let Some(unsatisfied) = overload
+                .signatures
+                .iter()
+                .find(|x| !self.satisfies_stub_overload(&got, x))
+            {
+                let got = self.for_display(got);
+                let want = self.for_display(unsatisfied.as_type());
+                let ctx = TypeDisplayContext::new(&[&got, &want]);
+                self.error(
+                    errors,
+                    range,
+                    ErrorInfo::Kind(ErrorKind::InconsistentStub),
+                    format!(
+                        "`{name}` has type `{}` in the implementation, which doesn't satisfy the overload `{}` in the stub",
+                        ctx.display(&got),
+                        ctx.display(&want),
+                    ),
+                );
+            }
+        } else if !self.is_subset_eq(&got, &want) {
+            let got = self.for_display(got);
+            let want = self.for_display((*want).clone());
+            let ctx = TypeDisplayContext::new(&[&got, &want]);
+            self.error(
+                errors,
+                range,
+                ErrorInfo::Kind(ErrorKind::InconsistentStub),
+                format!(
+                    "`{name}` has type `{}` in the implementation, which is inconsistent with `{}` in the stub",
+                    ctx.display(&got),
+                    ctx.display(&want),
+                ),
+            );
+        }
+    }
+
+    /// Whether the implementation `got` of a function satisfies one `overload` the stub declares:
+    /// it must accept the arguments of the overload, and return something the overload's return
+    /// type is assignable to.
+    fn satisfies_stub_overload(&self, got: &Type, overload: &OverloadType) -> bool {
+        let sigs = got.callable_signatures();
+        let (OverloadType::Function(overload), [&sig]) = (overload, sigs.as_slice()) else {
+            return self.is_subset_eq(got, &overload.as_type());
+        };
+        let inputs = |sig: &Callable| {
+            let mut sig = sig.clone();
+            sig.ret = Type::any_implicit();
+            Type::Callable(Box::new(sig))
+        };
+        self.is_subset_eq(&inputs(sig), &inputs(&overload.signature))
+            && self.is_subset_eq(&overload.signature.ret, &sig.ret)
+    }
+
     pub fn solve_consistent_override_check(
         &self,
         binding: &BindingConsistentOverrideCheck,
@@ -1679,6 +1813,13 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
             if class_bases.as_ref().base_type_count() > 1 {
                 self.check_consistent_multiple_inheritance(cls, errors);
             }
+
+            if let Some(slots) = &binding.slots {
+                self.check_slots(cls, slots, errors);
+            }
+
+            self.check_init_subclass_keywords(cls, &binding.keywords, errors);
+            self.check_set_name(cls, errors);
         }
         Arc::new(EmptyAnswer)
     }
@@ -2344,6 +2485,22 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         })
     }
 
+    /// Describe where each of `ranges` ends, for pointing at specific paths through a function.
+    fn path_notes(&self, ranges: &[TextRange], describe: impl Fn(u32) -> String) -> Vec<String> {
+        ranges
+            .iter()
+            .map(|range| {
+                describe(
+                    self.module()
+                        .display_range(*range)
+                        .end
+                        .line_within_cell()
+                        .get(),
+                )
+            })
+            .collect()
+    }
+
     fn check_implicit_return_against_annotation(
         &self,
         implicit_return: Arc<TypeInfo>,
@@ -2351,17 +2508,21 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         is_async: bool,
         is_generator: bool,
         has_explicit_returns: bool,
+        yield_ranges: &[TextRange],
+        fall_through: &[TextRange],
         range: TextRange,
         errors: &ErrorCollector,
     ) {
+        let generator_error = |msg: &str| {
+            let mut msg = vec1![msg.to_owned()];
+            msg.extend(self.path_notes(yield_ranges, |line| {
+                format!("The function is a generator because of the `yield` on line {line}")
+            }));
+            errors.add(range, ErrorInfo::Kind(ErrorKind::BadReturn), msg);
+        };
         if is_async && is_generator {
             if self.decompose_async_generator(annotation).is_none() {
-                self.error(
-                    errors,
-                    range,
-                    ErrorInfo::Kind(ErrorKind::BadReturn),
-                    "Async generator function should return `AsyncGenerator`".to_owned(),
-                );
+                generator_error("Async generator function should return `AsyncGenerator`");
             }
         } else if is_generator {
             if let Some((_, _, return_ty)) = self.decompose_generator(annotation) {
@@ -2371,19 +2532,32 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     ))
                 });
             } else {
-                self.error(
-                    errors,
-                    range,
-                    ErrorInfo::Kind(ErrorKind::BadReturn),
-                    "Generator function should return `Generator`".to_owned(),
-                );
+                generator_error("Generator function should return `Generator`");
             }
-        } else {
-            self.check_type(implicit_return.ty(), annotation, range, errors, &|| {
-                TypeCheckContext::of_kind(TypeCheckKind::ImplicitFunctionReturn(
-                    has_explicit_returns,
-                ))
-            });
+        } else if !implicit_return.ty().is_error()
+            && let Err(error) = self.is_subset_eq_with_reason(implicit_return.ty(), annotation)
+        {
+            // Like `check_type`, but also point at every path that falls off the end. Expression
+            // statements that never return (e.g. `sys.exit()`) don't count.
+            let fall_through = fall_through
+                .iter()
+                .filter(|range| {
+                    self.get_hashed_opt(Hashed::new(&Key::StmtExpr(**range)))
+                        .is_none_or(|x| !x.ty().is_never())
+                })
+                .copied()
+                .collect::<Vec<_>>();
+            let kind = TypeCheckKind::ImplicitFunctionReturn(has_explicit_returns);
+            let mut msg = vec1![kind.format_error(
+                &self.for_display(implicit_return.ty().clone()),
+                &self.for_display(annotation.clone()),
+                self.module().name(),
+            )];
+            msg.extend(error.to_error_msg());
+            msg.extend(self.path_notes(&fall_through, |line| {
+                format!("Execution can reach the end of the function after line {line}")
+            }));
+            errors.add(range, ErrorInfo::Kind(kind.as_error_kind()), msg);
         }
     }
 
@@ -2793,6 +2967,8 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                         implicit_return,
                         is_generator,
                         has_explicit_return,
+                        yield_ranges,
+                        fall_through,
                     } => {
                         // TODO: A return type annotation like `Final` is invalid in this context.
                         // It will result in an implicit Any type, which is reasonable, but we should
@@ -2818,6 +2994,8 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                                 x.is_async,
                                 *is_generator,
                                 *has_explicit_return,
+                                yield_ranges,
+                                fall_through,
                                 *range,
                                 errors,
                             );
@@ -3317,7 +3495,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                             Type::any_implicit()
                         })
                     }
-                    FunctionParameter::Unannotated(var, function_idx) => {
+                    FunctionParameter::Unannotated(var, function_idx, _) => {
                         // It's important that we force the undecorated function binding before reading
                         // from this var. Solving the undecorated function binding pins the type of the var,
                         // either to a concrete type or to any. Without this we can have non-determinism
@@ -3357,7 +3535,11 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
     ) -> Arc<Type> {
         let b = self.bindings().get(x.undecorated_idx);
         let def = self.get_idx(x.undecorated_idx);
-        self.decorated_function_type(&def, &b.def, errors)
+        // Functions called by a framework, like pytest tests and fixtures, aren't part of the API.
+        if !b.called_by_framework {
+            self.check_public_api(&b.def, &def, &b.parent, errors);
+        }
+        self.decorated_function_type(&def, &b.def, b.called_by_framework, errors)
     }
 
     pub fn solve_undecorated_function(
@@ -3365,14 +3547,16 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         x: &BindingUndecoratedFunction,
         errors: &ErrorCollector,
     ) -> Arc<UndecoratedFunction> {
-        self.undecorated_function(
+        let function = self.undecorated_function(
             &x.def,
             x.stub_or_impl,
             x.class_key.as_ref(),
             &x.decorators,
             &x.legacy_tparams,
             errors,
-        )
+        );
+        self.check_unused_parameters(&x.def, &function, &x.unused_parameters, errors);
+        function
     }
 
     pub fn solve_yield(&self, x: &BindingYield, errors: &ErrorCollector) -> Arc<YieldResult> {
@@ -3906,6 +4090,26 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                                 ),
                             );
                 }
+                // A variable holding a class object is a value, not a type alias, even though
+                // its type is `type[...]`. Dynamic base classes and arguments to functions like
+                // `cast` are still allowed to be variables.
+                if !matches!(
+                    type_form_context,
+                    TypeFormContext::BaseClassList | TypeFormContext::FunctionArgument
+                ) && let Expr::Name(name) = x
+                    && let Type::Type(_) = &inferred_ty
+                {
+                    return self.error(
+                        errors,
+                        x.range(),
+                        ErrorInfo::Kind(ErrorKind::InvalidAnnotation),
+                        format!(
+                            "Variable `{}` of type `{}` is not a type alias and cannot be used as a type",
+                            name.id,
+                            self.for_display(inferred_ty),
+                        ),
+                    );
+                }
                 self.untype(inferred_ty, x.range(), errors)
             }
         };
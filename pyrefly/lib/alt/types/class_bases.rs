@@ -236,6 +236,9 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
                     }
                     Some((ty, x.range()))
                 }
+                BaseClass::Call(x) => self
+                    .base_class_call_infer(x, &fake_error_collector)
+                    .map(|ty| (ty, x.range())),
                 BaseClass::NamedTuple(..) => Some((
                     self.stdlib.named_tuple_fallback().clone().to_type(),
                     x.range(),
@@ -85,6 +85,21 @@ impl Ranged for BaseClassGeneric {
     }
 }
 
+/// A call to a function that creates a base class, as in `class C(make_base(config))`.
+#[derive(Debug, Clone)]
+pub struct BaseClassCall {
+    /// The function being called.
+    pub func: BaseClassExpr,
+    /// The whole call.
+    pub call: Expr,
+}
+
+impl Ranged for BaseClassCall {
+    fn range(&self) -> TextRange {
+        self.call.range()
+    }
+}
+
 /// Helper type used to share part of the logic needed for the
 /// binding-level work of finding legacy type parameters versus the type-level
 /// work of computing inheritance information and the MRO.
@@ -93,6 +108,7 @@ pub enum BaseClass {
     TypedDict(TextRange),
     Generic(BaseClassGeneric),
     BaseClassExpr(BaseClassExpr),
+    Call(BaseClassCall),
     InvalidExpr(Expr),
     NamedTuple(TextRange),
 }
@@ -119,6 +135,7 @@ impl Ranged for BaseClass {
             BaseClass::TypedDict(range) => *range,
             BaseClass::Generic(x) => x.range(),
             BaseClass::BaseClassExpr(base_expr) => base_expr.range(),
+            BaseClass::Call(call) => call.range(),
             BaseClass::InvalidExpr(expr) => expr.range(),
             BaseClass::NamedTuple(range) => *range,
         }
@@ -164,6 +181,13 @@ impl<'a> BindingsBuilder<'a> {
                 }
                 if let Some(valid_expr) = BaseClassExpr::from_expr(&base_expr) {
                     BaseClass::BaseClassExpr(valid_expr)
+                } else if let Expr::Call(call) = &base_expr
+                    && let Some(func) = BaseClassExpr::from_expr(&call.func)
+                {
+                    BaseClass::Call(BaseClassCall {
+                        func,
+                        call: base_expr,
+                    })
                 } else {
                     BaseClass::InvalidExpr(base_expr)
                 }
@@ -15,6 +15,7 @@ use pyrefly_derive::TypeEq;
 use pyrefly_derive::VisitMut;
 use pyrefly_python::dunder;
 use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::nesting_context::NestingContext;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_python::symbol_kind::SymbolKind;
@@ -59,6 +60,7 @@ use crate::binding::base_class::BaseClass;
 use crate::binding::base_class::BaseClassGeneric;
 use crate::binding::bindings::Bindings;
 use crate::binding::narrow::NarrowOp;
+use crate::binding::parameter_provider::ProvidedParameter;
 use crate::binding::pydantic::PydanticConfigDict;
 use crate::export::special::SpecialExport;
 use crate::graph::index::Idx;
@@ -111,7 +113,7 @@ assert_bytes!(BindingLegacyTypeParam, 16);
 assert_words!(BindingYield, 4);
 assert_words!(BindingYieldFrom, 4);
 assert_bytes!(BindingDecoratedFunction, 20);
-assert_words!(BindingUndecoratedFunction, 21);
+assert_words!(BindingUndecoratedFunction, 23);
 
 #[derive(Clone, Dupe, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AnyIdx {
@@ -535,6 +537,14 @@ pub enum BindingExpect {
     },
     /// Expression used in a boolean context (`bool()`, `if`, or `while`)
     Bool(Expr),
+    /// A name defined by a `.py` implementation, at the range given, that its `.pyi` stub, at the
+    /// path given, also declares, so the implementation must be consistent with the stub.
+    ConsistentWithStub {
+        implementation: Idx<KeyExport>,
+        stub: ModulePath,
+        name: Name,
+        range: TextRange,
+    },
 }
 
 impl DisplayWith<Bindings> for BindingExpect {
@@ -585,6 +595,18 @@ impl DisplayWith<Bindings> for BindingExpect {
                 ctx.display(*existing),
                 name
             ),
+            Self::ConsistentWithStub {
+                implementation,
+                stub,
+                name,
+                range: _,
+            } => write!(
+                f,
+                "ConsistentWithStub({} with {} in {})",
+                ctx.display(*implementation),
+                name,
+                stub
+            ),
         }
     }
 }
@@ -975,7 +997,13 @@ impl IsAsync {
 #[derive(Clone, Debug)]
 pub enum FunctionParameter {
     Annotated(Idx<KeyAnnotation>),
-    Unannotated(Var, Idx<KeyUndecoratedFunction>),
+    /// An unannotated parameter, and where a framework that calls the function gets its value
+    /// from, if we understand it.
+    Unannotated(
+        Var,
+        Idx<KeyUndecoratedFunction>,
+        Option<Box<ProvidedParameter>>,
+    ),
 }
 
 /// Is the body of this function stubbed out (contains nothing but `...`)?
@@ -1009,6 +1037,13 @@ pub struct BindingUndecoratedFunction {
     pub class_key: Option<Idx<KeyClass>>,
     pub legacy_tparams: Box<[Idx<KeyLegacyTypeParam>]>,
     pub decorators: Box<[(Idx<Key>, TextRange)]>,
+    /// Parameters that are never read in the body, other than `_`-prefixed ones.
+    pub unused_parameters: Box<[Identifier]>,
+    /// The class or function the definition is nested in, if any.
+    pub parent: NestingContext,
+    /// Whether a framework calls this function itself, like a test or fixture in a pytest test
+    /// file, so its return type doesn't need annotating.
+    pub called_by_framework: bool,
 }
 
 impl DisplayWith<Bindings> for BindingUndecoratedFunction {
@@ -1063,6 +1098,12 @@ pub enum ReturnTypeKind {
         implicit_return: Idx<Key>,
         is_generator: bool,
         has_explicit_return: bool,
+        /// The `yield` and `yield from` expressions, to point at when a generator has a
+        /// non-generator annotation.
+        yield_ranges: Box<[TextRange]>,
+        /// The last statement of each path that may reach the end of the function body. For
+        /// expression statements, this is the range of the expression.
+        fall_through: Box<[TextRange]>,
     },
     /// We have an explicit return annotation, and we should blindly trust it without any validation
     ShouldTrustAnnotation {
@@ -1102,6 +1143,19 @@ impl ReturnTypeKind {
             Self::ShouldInferType { .. } => true,
         }
     }
+
+    pub fn is_generator(&self) -> bool {
+        match self {
+            Self::ShouldValidateAnnotation { is_generator, .. } => *is_generator,
+            Self::ShouldTrustAnnotation { is_generator, .. } => *is_generator,
+            Self::ShouldReturnAny { is_generator } => *is_generator,
+            Self::ShouldInferType {
+                yields,
+                yield_froms,
+                ..
+            } => !(yields.is_empty() && yield_froms.is_empty()),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1543,7 +1597,7 @@ impl DisplayWith<Bindings> for Binding {
                 "FunctionParameter({})",
                 match x {
                     FunctionParameter::Annotated(k) => ctx.display(*k).to_string(),
-                    FunctionParameter::Unannotated(x, k) => format!("{x}, {}", ctx.display(*k)),
+                    FunctionParameter::Unannotated(x, k, _) => format!("{x}, {}", ctx.display(*k)),
                 }
             ),
             Self::SuperInstance(SuperStyle::ExplicitArgs(cls, obj), _range) => {
@@ -2007,6 +2061,11 @@ impl DisplayWith<Bindings> for BindingVariance {
 #[derive(Clone, Debug)]
 pub struct BindingConsistentOverrideCheck {
     pub class_key: Idx<KeyClass>,
+    /// The names in the class's `__slots__`, if it has one whose contents we can determine.
+    pub slots: Option<Box<[Name]>>,
+    /// The keywords in the class header other than `metaclass`, with the ranges of their values,
+    /// which are passed to `__init_subclass__`.
+    pub keywords: Box<[(Identifier, TextRange)]>,
 }
 
 impl DisplayWith<Bindings> for BindingConsistentOverrideCheck {
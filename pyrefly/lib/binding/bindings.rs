@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -12,9 +13,11 @@ use std::sync::Arc;
 
 use dupe::Dupe;
 use pyrefly_python::ast::Ast;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::nesting_context::NestingContext;
 use pyrefly_python::short_identifier::ShortIdentifier;
+use pyrefly_python::sys_info::ConstantValue;
 use pyrefly_python::sys_info::SysInfo;
 use pyrefly_types::type_info::JoinStyle;
 use pyrefly_types::types::Type;
@@ -56,16 +59,26 @@ use crate::binding::binding::KeyUndecoratedFunction;
 use crate::binding::binding::Keyed;
 use crate::binding::binding::LastStmt;
 use crate::binding::binding::TypeParameter;
+use crate::binding::embedded_code::EmbeddedCodeExtractor;
+use crate::binding::embedded_code::extractor;
 use crate::binding::expr::Usage;
 use crate::binding::narrow::NarrowOps;
+use crate::binding::parameter_provider::ParameterProvider;
+use crate::binding::parameter_provider::ProvidedParameter;
+use crate::binding::parameter_provider::provider;
+use crate::binding::parameter_provider::pytest;
 use crate::binding::scope::Exportable;
 use crate::binding::scope::FlowStyle;
 use crate::binding::scope::NameReadInfo;
 use crate::binding::scope::ScopeTrace;
 use crate::binding::scope::Scopes;
 use crate::binding::table::TableKeyed;
+use crate::config::base::EmbeddedCodeKind;
+use crate::config::base::ParameterProviderKind;
+use crate::config::base::ReexportMode;
 use crate::config::base::UntypedDefBehavior;
 use crate::config::error_kind::ErrorKind;
+use crate::config::import_boundaries::ImportBoundaries;
 use crate::error::collector::ErrorCollector;
 use crate::error::context::ErrorInfo;
 use crate::export::definitions::MutableCaptureKind;
@@ -150,6 +163,13 @@ struct BindingsInner {
     module_info: ModuleInfo,
     table: BindingTable,
     scope_trace: Option<ScopeTrace>,
+    /// Functions that create base classes, mapped to the class those classes inherit from,
+    /// both by fully qualified name.
+    base_class_factories: BTreeMap<String, String>,
+    /// Whether unannotated parameters take the type of their default value.
+    infer_parameters_from_defaults: bool,
+    /// Whether `unmanaged-resource` errors are enabled for this module.
+    check_unmanaged_resources: bool,
 }
 
 impl Display for Bindings {
@@ -177,6 +197,8 @@ impl Display for Bindings {
 pub struct BindingsBuilder<'a> {
     pub module_info: ModuleInfo,
     pub lookup: &'a dyn LookupExport,
+    /// The exports of this module.
+    exports: Exports,
     pub sys_info: &'a SysInfo,
     pub class_count: u32,
     errors: &'a ErrorCollector,
@@ -186,6 +208,16 @@ pub struct BindingsBuilder<'a> {
     pub scopes: Scopes,
     table: BindingTable,
     pub untyped_def_behavior: UntypedDefBehavior,
+    propagate_constants: bool,
+    pub reexport: ReexportMode,
+    pub embedded_code_extractors: Vec<&'static dyn EmbeddedCodeExtractor>,
+    pub parameter_providers: Vec<&'static dyn ParameterProvider>,
+    /// The fixtures defined in this module, if it is a pytest test file and pytest is one of the
+    /// parameter providers.
+    pub pytest_fixtures: Option<SmallMap<Name, Identifier>>,
+    /// The import boundaries imports of this module are checked against, if it is part of the
+    /// project.
+    import_boundaries: Option<&'a ImportBoundaries>,
 }
 
 impl Bindings {
@@ -207,6 +239,23 @@ impl Bindings {
         &self.0.module_info
     }
 
+    /// The fully qualified name of the class that the classes created by the function
+    /// `factory` inherit from, if the config declares one.
+    pub fn base_class_factory(&self, factory: &str) -> Option<&str> {
+        self.0.base_class_factories.get(factory).map(|x| x.as_str())
+    }
+
+    /// Whether unannotated parameters with a default take its type, rather than its type unioned
+    /// with `Any`, as in untyped installed packages with `infer-untyped-package-signatures`.
+    pub fn infer_parameters_from_defaults(&self) -> bool {
+        self.0.infer_parameters_from_defaults
+    }
+
+    /// Whether `unmanaged-resource` errors would be shown, so the check needs to run.
+    pub fn check_unmanaged_resources(&self) -> bool {
+        self.0.check_unmanaged_resources
+    }
+
     pub fn available_definitions(&self, position: TextSize) -> SmallSet<Idx<Key>> {
         if let Some(trace) = &self.0.scope_trace {
             trace.available_definitions(&self.0.table, position)
@@ -335,6 +384,11 @@ impl Bindings {
         }
     }
 
+    pub fn function_has_return_annotation(&self, name: &Identifier) -> bool {
+        let b = self.get(self.key_to_idx(&Key::ReturnType(ShortIdentifier::new(name))));
+        matches!(b, Binding::ReturnType(box r) if r.kind.has_return_annotation())
+    }
+
     pub fn new(
         x: ModModule,
         module_info: ModuleInfo,
@@ -346,10 +400,20 @@ impl Bindings {
         uniques: &UniqueFactory,
         enable_trace: bool,
         untyped_def_behavior: UntypedDefBehavior,
+        propagate_constants: bool,
+        reexport: ReexportMode,
+        embedded_code: &[EmbeddedCodeKind],
+        parameter_providers: &[ParameterProviderKind],
+        base_class_factories: &BTreeMap<String, String>,
+        stub: Option<&ModuleInfo>,
+        infer_parameters_from_defaults: bool,
+        check_unmanaged_resources: bool,
+        import_boundaries: Option<&ImportBoundaries>,
     ) -> Self {
         let mut builder = BindingsBuilder {
             module_info: module_info.dupe(),
             lookup,
+            exports: exports.dupe(),
             sys_info,
             errors,
             solver,
@@ -359,8 +423,21 @@ impl Bindings {
             scopes: Scopes::module(x.range, enable_trace),
             table: Default::default(),
             untyped_def_behavior,
+            propagate_constants,
+            reexport,
+            embedded_code_extractors: embedded_code.iter().map(|x| extractor(*x)).collect(),
+            parameter_providers: parameter_providers.iter().map(|x| provider(*x)).collect(),
+            pytest_fixtures: if parameter_providers.contains(&ParameterProviderKind::Pytest) {
+                pytest::fixtures(module_info.path(), &x.body)
+            } else {
+                None
+            },
+            import_boundaries,
         };
         builder.init_static_scope(&x.body, true);
+        builder.check_shadowed_imports(&x.body);
+        builder.check_redeclared_type_aliases(&x.body);
+        builder.check_redefinitions(&x.body);
         if module_info.name() != ModuleName::builtins() {
             builder.inject_builtins(ModuleName::builtins(), false);
             if module_info.name() != ModuleName::extra_builtins() {
@@ -372,6 +449,7 @@ impl Bindings {
         assert_eq!(builder.scopes.loop_depth(), 0);
         let scope_trace = builder.scopes.finish();
         let exported = exports.exports(lookup);
+        let mut export_idxs = SmallMap::new();
         for (name, exportable) in scope_trace.exportables().into_iter_hashed() {
             let binding = match exportable {
                 Exportable::Initialized(key, Some(ann)) => {
@@ -383,11 +461,16 @@ impl Bindings {
                 }
             };
             if exported.contains_key_hashed(name.as_ref()) {
-                builder
+                let name = name.into_key();
+                let idx = builder
                     .table
-                    .insert(KeyExport(name.into_key()), BindingExport(binding));
+                    .insert(KeyExport(name.clone()), BindingExport(binding));
+                export_idxs.insert(name, idx);
             }
         }
+        if let Some(stub) = stub {
+            builder.check_consistent_with_stub(stub, &exported, &export_idxs);
+        }
         Self(Arc::new(BindingsInner {
             module_info,
             table: builder.table,
@@ -396,6 +479,9 @@ impl Bindings {
             } else {
                 None
             },
+            base_class_factories: base_class_factories.clone(),
+            infer_parameters_from_defaults,
+            check_unmanaged_resources,
         }))
     }
 }
@@ -648,6 +734,88 @@ impl<'a> BindingsBuilder<'a> {
         }
     }
 
+    /// The binding a name currently refers to, following any `Forward`s.
+    fn binding_of_name(&self, name: &Name) -> Option<&Binding> {
+        let NameReadInfo::Flow { idx, .. } = self.scopes.look_up_name_for_read(Hashed::new(name))
+        else {
+            return None;
+        };
+        let mut binding = self.table.types.1.get(idx);
+        let mut gas = Gas::new(100);
+        while let Some(Binding::Forward(idx)) = binding {
+            if gas.stop() {
+                return None;
+            }
+            binding = self.table.types.1.get(*idx);
+        }
+        binding
+    }
+
+    /// The literal value of a module-level constant in another module, for `X` where
+    /// `from module import X`, or `module.X` where `import module`.
+    fn imported_constant(&self, x: &Expr) -> Option<ConstantValue> {
+        let (module, name) = match x {
+            Expr::Name(x) => match self.binding_of_name(&x.id)? {
+                Binding::Import(module, name, _) => (*module, name.clone()),
+                _ => return None,
+            },
+            Expr::Attribute(x) => {
+                // Collect the module path, e.g. `a.b` in `a.b.X`.
+                let mut path = Vec::new();
+                let mut base = &*x.value;
+                while let Expr::Attribute(x) = base {
+                    path.push(x.attr.id.clone());
+                    base = &x.value;
+                }
+                let Expr::Name(base) = base else {
+                    return None;
+                };
+                let Binding::Module(_, prefix, _) = self.binding_of_name(&base.id)? else {
+                    return None;
+                };
+                path.extend(prefix.iter().rev().cloned());
+                path.reverse();
+                (ModuleName::from_parts(path), x.attr.id.clone())
+            }
+            _ => return None,
+        };
+        self.lookup.get(module).finding()?.constant(&name).cloned()
+    }
+
+    /// The keys of the lookup table `name` refers to, if it is a `Final` table defined by a
+    /// literal, either in this module or imported with `from module import name`.
+    pub fn literal_table(&self, name: &Name) -> Option<Vec<ConstantValue>> {
+        let NameReadInfo::Flow { idx, .. } = self.scopes.look_up_name_for_read(Hashed::new(name))
+        else {
+            return None;
+        };
+        match self.binding_of_name(name)? {
+            Binding::Import(module, name, _) => {
+                let (_, keys) = self.lookup.get(*module).finding()?.literal_table(name)?;
+                Some(keys.to_vec())
+            }
+            _ => {
+                // Only the module-level definition, and not a local variable shadowing it.
+                let (range, keys) = self.exports.literal_table(name)?;
+                match self.table.types.0.idx_to_key(idx) {
+                    Key::Definition(x) if x.range() == range => Some(keys.to_vec()),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Statically evaluate a condition, using the values of constants imported from other
+    /// modules if `propagate-constants` is enabled. Returns `None` if the condition can't be
+    /// evaluated that way, or the option is disabled.
+    pub fn evaluate_constant_bool(&self, x: &Expr) -> Option<bool> {
+        if !self.propagate_constants {
+            return None;
+        }
+        self.sys_info
+            .evaluate_bool_with_constants(x, &|x| self.imported_constant(x))
+    }
+
     // Only works for things with `Foo`, or `source.Foo`, or `F` where `from module import Foo as F`.
     // Does not work for things with nested modules - but no SpecialExport's have that.
     pub fn as_special_export(&self, e: &Expr) -> Option<SpecialExport> {
@@ -672,10 +840,40 @@ impl<'a> BindingsBuilder<'a> {
         self.errors.add(range, info, vec1![msg]);
     }
 
+    pub fn error_with_related(
+        &self,
+        range: TextRange,
+        info: ErrorInfo,
+        msg: String,
+        related: Vec<(TextRangeWithModule, String)>,
+    ) {
+        self.errors
+            .add_with_related(range, info, vec1![msg], related);
+    }
+
     pub fn error_multiline(&self, range: TextRange, info: ErrorInfo, msg: Vec1<String>) {
         self.errors.add(range, info, msg);
     }
 
+    /// Report an import of `imported` at `range` if it crosses the import boundaries.
+    pub fn check_import_boundary(&self, imported: ModuleName, range: TextRange) {
+        if let Some(boundaries) = self.import_boundaries
+            && let Some(msg) = boundaries.violation(self.module_info.name(), imported)
+        {
+            self.error(range, ErrorInfo::Kind(ErrorKind::ImportBoundary), msg);
+        }
+    }
+
+    /// Report a use of a deprecated symbol, given by its fully qualified name.
+    pub fn error_deprecated(&self, range: TextRange, msg: String, symbol: String) {
+        self.errors.add_deprecated(
+            range,
+            ErrorInfo::Kind(ErrorKind::Deprecated),
+            vec1![msg],
+            symbol,
+        );
+    }
+
     pub fn declare_mutable_capture(&mut self, name: &Identifier, kind: MutableCaptureKind) {
         // Record any errors finding the identity of the mutable capture, and get a binding
         // that provides the type coming from the parent scope.
@@ -953,6 +1151,7 @@ impl<'a> BindingsBuilder<'a> {
         x: AnyParameterRef,
         undecorated_idx: Idx<KeyUndecoratedFunction>,
         class_key: Option<Idx<KeyClass>>,
+        provided: Option<ProvidedParameter>,
     ) {
         let name = x.name();
         let annot = x.annotation().map(|x| {
@@ -968,6 +1167,7 @@ impl<'a> BindingsBuilder<'a> {
                 None => FunctionParameter::Unannotated(
                     self.solver.fresh_parameter(self.uniques),
                     undecorated_idx,
+                    provided.map(Box::new),
                 ),
             }),
         );
@@ -6,6 +6,7 @@
  */
 
 use std::mem;
+use std::slice;
 use std::sync::LazyLock;
 
 use dupe::Dupe as _;
@@ -63,6 +64,7 @@ use crate::binding::binding::KeyVariance;
 use crate::binding::bindings::BindingsBuilder;
 use crate::binding::bindings::CurrentIdx;
 use crate::binding::bindings::LegacyTParamCollector;
+use crate::binding::instance_attributes::slot_names;
 use crate::binding::pydantic::PydanticConfigDict;
 use crate::binding::scope::ClassIndices;
 use crate::binding::scope::FlowStyle;
@@ -87,6 +89,50 @@ enum SynthesizedClassKind {
     NewType,
 }
 
+/// Whether `x` is `name`, or the attribute `name` of a module, as in `typing.Annotated`.
+fn is_named(x: &Expr, name: &str) -> bool {
+    match x {
+        Expr::Name(x) => x.id == name,
+        Expr::Attribute(x) => x.attr.id == name,
+        _ => false,
+    }
+}
+
+/// The string literal documenting a field in its `Annotated` annotation, with `Doc("...")` from
+/// PEP 727, or `Field(description="...")`.
+fn annotated_docstring(x: &Expr) -> Option<TextRange> {
+    let Expr::Subscript(x) = x else {
+        return None;
+    };
+    if !is_named(&x.value, "Annotated") {
+        return None;
+    }
+    let Expr::Tuple(metadata) = &*x.slice else {
+        return None;
+    };
+    metadata.elts.iter().skip(1).find_map(|x| match x {
+        Expr::Call(call) if is_named(&call.func, "Doc") => match call.arguments.args.first() {
+            Some(Expr::StringLiteral(x)) => Some(x.range),
+            _ => None,
+        },
+        _ => field_description(x),
+    })
+}
+
+/// The `description` of a field defined with `Field(description="...")`, as in Pydantic.
+fn field_description(x: &Expr) -> Option<TextRange> {
+    let Expr::Call(call) = x else {
+        return None;
+    };
+    if !is_named(&call.func, "Field") {
+        return None;
+    }
+    match &call.arguments.find_keyword("description")?.value {
+        Expr::StringLiteral(x) => Some(x.range),
+        _ => None,
+    }
+}
+
 impl<'a> BindingsBuilder<'a> {
     fn def_index(&mut self) -> ClassDefIndex {
         let res = ClassDefIndex(self.class_count);
@@ -158,7 +204,12 @@ impl<'a> BindingsBuilder<'a> {
             } else {
                 &mut legacy
             };
-            self.ensure_type(&mut base, legacy);
+            // A call is evaluated as a value, whose result is the base class.
+            if matches!(base, Expr::Call(_)) {
+                self.ensure_expr(&mut base, class_object.usage());
+            } else {
+                self.ensure_type(&mut base, legacy);
+            }
 
             let base_class = self.base_class_of(base.clone());
             // NOTE(grievejia): If any of the class base is a specialized generic class (e.g. `Foo[Bar]`), and if the tparam of the
@@ -171,21 +222,37 @@ impl<'a> BindingsBuilder<'a> {
             //
             // Also note that there's no risk of first-usage tracking issues here because `ensure_type` does not participate in first
             // usage tracking.
-            if matches!(base_class, BaseClass::BaseClassExpr(..)) {
-                self.insert_binding(
-                    KeyExpect(base.range()),
-                    BindingExpect::TypeCheckBaseClassExpr(base),
-                );
+            match base_class {
+                BaseClass::BaseClassExpr(..) => {
+                    self.insert_binding(
+                        KeyExpect(base.range()),
+                        BindingExpect::TypeCheckBaseClassExpr(base),
+                    );
+                }
+                BaseClass::Call(..) => {
+                    self.insert_binding(
+                        KeyExpect(base.range()),
+                        BindingExpect::TypeCheckExpr(base),
+                    );
+                }
+                _ => {}
             }
             base_class
         });
 
         let mut keywords = Vec::new();
+        let mut init_subclass_keywords: Vec<(Identifier, TextRange)> = Vec::new();
         if let Some(args) = &mut x.arguments {
             args.keywords.iter_mut().for_each(|keyword| {
                 if let Some(name) = &keyword.arg {
                     self.ensure_expr(&mut keyword.value, class_object.usage());
                     keywords.push((name.id.clone(), keyword.value.clone()));
+                    // A duplicate keyword is already a parse error.
+                    if name.id != "metaclass"
+                        && !init_subclass_keywords.iter().any(|(x, _)| x.id == name.id)
+                    {
+                        init_subclass_keywords.push((name.clone(), keyword.value.range()));
+                    }
                 } else {
                     self.error(
                         keyword.range(),
@@ -227,6 +294,10 @@ impl<'a> BindingsBuilder<'a> {
             x.name.clone(),
         ));
         self.init_static_scope(&body, false);
+        self.check_possibly_missing_attributes(&body);
+        self.check_redeclared_type_aliases(&body);
+        self.check_redefinitions(&body);
+        let slots = slot_names(&body);
         self.stmts(
             body,
             &NestingContext::class(ShortIdentifier::new(&x.name), parent.dupe()),
@@ -336,6 +407,8 @@ impl<'a> BindingsBuilder<'a> {
             class_indices.consistent_override_check_idx,
             BindingConsistentOverrideCheck {
                 class_key: class_indices.class_idx,
+                slots,
+                keywords: init_subclass_keywords.into_boxed_slice(),
             },
         );
         self.insert_binding_idx(
@@ -358,37 +431,48 @@ impl<'a> BindingsBuilder<'a> {
         );
     }
 
-    /// Extracts docstrings for each field, mapping the field's range to the docstring's range.
+    /// Extracts docstrings for each field, mapping the range of the field's name to the range of
+    /// its documentation: the docstring of a method, or for an attribute, the string literal that
+    /// follows it (PEP 224), else the `Doc(...)` or `Field(description=...)` documenting it.
     fn extract_field_docstrings(
         &self,
         body: &[ruff_python_ast::Stmt],
     ) -> SmallMap<TextRange, TextRange> {
-        use ruff_python_ast::Expr;
         use ruff_python_ast::Stmt;
 
         let mut field_docstrings = SmallMap::new();
-        let mut i = 0;
-
-        while i < body.len() {
-            let stmt = &body[i];
-
-            let is_field = matches!(stmt, Stmt::AnnAssign(_) | Stmt::Assign(_));
-
-            if let Stmt::FunctionDef(func_def) = stmt {
-                if let Some(docstring_range) = Docstring::range_from_stmts(&func_def.body) {
-                    field_docstrings.insert(func_def.name.range, docstring_range);
+        for (i, stmt) in body.iter().enumerate() {
+            let (targets, annotation, value) = match stmt {
+                Stmt::FunctionDef(func_def) => {
+                    if let Some(docstring_range) = Docstring::range_from_stmts(&func_def.body) {
+                        field_docstrings.insert(func_def.name.range, docstring_range);
+                    }
+                    continue;
+                }
+                Stmt::AnnAssign(x) => (
+                    slice::from_ref(&*x.target),
+                    Some(&*x.annotation),
+                    x.value.as_deref(),
+                ),
+                Stmt::Assign(x) => (x.targets.as_slice(), None, Some(&*x.value)),
+                _ => continue,
+            };
+            let docstring_range = match body.get(i + 1) {
+                Some(Stmt::Expr(x)) if matches!(&*x.value, Expr::StringLiteral(_)) => {
+                    Some(x.range())
+                }
+                _ => annotation
+                    .and_then(annotated_docstring)
+                    .or_else(|| value.and_then(field_description)),
+            };
+            if let Some(docstring_range) = docstring_range {
+                for target in targets {
+                    if let Expr::Name(x) = target {
+                        field_docstrings.insert(x.range, docstring_range);
+                    }
                 }
-            } else if is_field
-                && let Some(next_stmt) = body.get(i + 1)
-                && let Stmt::Expr(expr_stmt) = next_stmt
-                && matches!(&*expr_stmt.value, Expr::StringLiteral(_))
-            {
-                field_docstrings.insert(stmt.range(), next_stmt.range());
             }
-
-            i += 1;
         }
-
         field_docstrings
     }
 
@@ -622,6 +706,8 @@ impl<'a> BindingsBuilder<'a> {
             class_indices.consistent_override_check_idx,
             BindingConsistentOverrideCheck {
                 class_key: class_indices.class_idx,
+                slots: None,
+                keywords: Box::new([]),
             },
         );
         self.insert_binding_idx(
@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Python code embedded in other code, e.g. in a string literal, which we extract and type check
+//! as though it had been written in place. Each kind of embedded code has an extractor, enabled
+//! with the `embedded-code` config option. To support a new kind, implement
+//! `EmbeddedCodeExtractor`, add a variant to `EmbeddedCodeKind` and return it from `extractor`.
+
+use pyrefly_python::ast::Ast;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::StringLiteral;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::binding::binding::Binding;
+use crate::binding::binding::Key;
+use crate::binding::bindings::BindingsBuilder;
+use crate::binding::expr::Usage;
+use crate::config::base::EmbeddedCodeKind;
+use crate::config::error_kind::ErrorKind;
+use crate::error::context::ErrorInfo;
+
+/// A Python expression found inside other code.
+pub struct EmbeddedCode {
+    /// The source of the expression.
+    pub code: String,
+    /// The offset of the start of `code` in the module. Each byte of `code` must correspond to
+    /// the byte at the same offset from `start` in the module, so that errors are reported in the
+    /// right place.
+    pub start: TextSize,
+}
+
+/// Finds embedded code of one kind.
+pub trait EmbeddedCodeExtractor: Sync {
+    /// The embedded code in the arguments of `call`, if any.
+    fn extract(&self, call: &ExprCall) -> Vec<EmbeddedCode>;
+}
+
+pub fn extractor(kind: EmbeddedCodeKind) -> &'static dyn EmbeddedCodeExtractor {
+    match kind {
+        EmbeddedCodeKind::Eval => &Eval,
+    }
+}
+
+/// The code in a string literal, if it can be mapped back to the source byte for byte, i.e. it is
+/// on a single line and has no escapes.
+fn string_literal_code(x: &StringLiteral) -> Option<EmbeddedCode> {
+    if x.flags.is_triple_quoted() {
+        return None;
+    }
+    let start = x.range.start() + x.flags.prefix().text_len() + TextSize::new(1);
+    let end = x.range.end() - TextSize::new(1);
+    if end < start || (end - start).to_usize() != x.value.len() {
+        // The source contains escapes.
+        return None;
+    }
+    Some(EmbeddedCode {
+        code: x.value.to_string(),
+        start,
+    })
+}
+
+/// `eval("...")`, where the string is evaluated with the globals and locals of the caller, so can
+/// be checked in the scope of the call.
+struct Eval;
+
+impl EmbeddedCodeExtractor for Eval {
+    fn extract(&self, call: &ExprCall) -> Vec<EmbeddedCode> {
+        if let Expr::Name(func) = &*call.func
+            && func.id == "eval"
+            && call.arguments.keywords.is_empty()
+            && let [Expr::StringLiteral(arg)] = &*call.arguments.args
+            && let Some(literal) = arg.as_single_part_string()
+        {
+            string_literal_code(literal).into_iter().collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl<'a> BindingsBuilder<'a> {
+    /// Check the embedded code in the arguments of `call`, in the current scope.
+    pub fn ensure_embedded_code(&mut self, call: &ExprCall, usage: &mut Usage) {
+        for extractor in self.embedded_code_extractors.clone() {
+            for code in extractor.extract(call) {
+                match Ast::parse_expr(&code.code, code.start) {
+                    Ok(mut x) => {
+                        self.ensure_expr(&mut x, usage);
+                        self.insert_binding(Key::Anon(x.range()), Binding::Expr(None, x));
+                    }
+                    Err(e) => self.error(
+                        TextRange::at(code.start, TextSize::of(code.code.as_str())),
+                        ErrorInfo::Kind(ErrorKind::ParseError),
+                        format!("Could not parse embedded code: {e}"),
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::slice;
+
 use pyrefly_python::ast::Ast;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_util::visit::VisitMut;
@@ -17,10 +19,12 @@ use ruff_python_ast::Expr;
 use ruff_python_ast::ExprAttribute;
 use ruff_python_ast::ExprBoolOp;
 use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprContext;
 use ruff_python_ast::ExprLambda;
 use ruff_python_ast::ExprName;
 use ruff_python_ast::ExprNoneLiteral;
 use ruff_python_ast::ExprSubscript;
+use ruff_python_ast::ExprTuple;
 use ruff_python_ast::ExprYield;
 use ruff_python_ast::ExprYieldFrom;
 use ruff_python_ast::Identifier;
@@ -711,6 +715,12 @@ impl<'a> BindingsBuilder<'a> {
                 // Control flow doesn't proceed after sys.exit(), exit(), quit(), or os._exit().
                 self.scopes.mark_flow_termination();
             }
+            Expr::Subscript(ExprSubscript { value, slice, .. })
+                if self.as_special_export(value) == Some(SpecialExport::Literal) =>
+            {
+                self.expand_literal_tables(slice, usage);
+                x.recurse_mut(&mut |x| self.ensure_expr(x, usage));
+            }
             Expr::Name(x) => {
                 let name = Ast::expr_name_identifier(x.clone());
                 self.ensure_name(&name, usage, &mut None);
@@ -733,6 +743,9 @@ impl<'a> BindingsBuilder<'a> {
             }
             _ => {
                 x.recurse_mut(&mut |x| self.ensure_expr(x, usage));
+                if let Expr::Call(x) = x {
+                    self.ensure_embedded_code(x, usage);
+                }
             }
         }
     }
@@ -828,6 +841,61 @@ impl<'a> BindingsBuilder<'a> {
         }
     }
 
+    /// The keys of the table, located at `x`, if `x` is `tuple(NAME)` where `NAME` is a `Final`
+    /// lookup table defined by a literal.
+    fn literal_table_keys(&self, x: &Expr) -> Option<Vec<Expr>> {
+        let Expr::Call(ExprCall {
+            func, arguments, ..
+        }) = x
+        else {
+            return None;
+        };
+        if self.as_special_export(func) != Some(SpecialExport::BuiltinsTuple)
+            || !arguments.keywords.is_empty()
+        {
+            return None;
+        }
+        let [Expr::Name(name)] = &*arguments.args else {
+            return None;
+        };
+        let keys = self.literal_table(&name.id)?;
+        Some(keys.iter().map(|key| key.to_expr(x.range())).collect())
+    }
+
+    /// Replace the arguments to `Literal` of the form `tuple(NAME)`, where `NAME` is a lookup
+    /// table like `CODES: Final = {"ok": 200, "missing": 404}`, with the keys of the table, just as
+    /// `Literal` unpacks the tuple at runtime. That lets the keys of a table be checked precisely
+    /// without repeating them in a `Literal`.
+    fn expand_literal_tables(&mut self, slice: &mut Expr, usage: &mut Usage) {
+        let range = slice.range();
+        let args = match &mut *slice {
+            Expr::Tuple(x) => x.elts.as_mut_slice(),
+            x => slice::from_mut(x),
+        };
+        let mut elts = Vec::new();
+        let mut expanded = false;
+        for arg in args {
+            match self.literal_table_keys(arg) {
+                Some(keys) => {
+                    // The table is still used, even though it is replaced by its keys.
+                    self.ensure_expr(arg, usage);
+                    elts.extend(keys);
+                    expanded = true;
+                }
+                None => elts.push(arg.clone()),
+            }
+        }
+        if expanded {
+            *slice = Expr::Tuple(ExprTuple {
+                node_index: AtomicNodeIndex::dummy(),
+                range,
+                elts,
+                ctx: ExprContext::Load,
+                parenthesized: false,
+            });
+        }
+    }
+
     /// Whenever we see a use of `typing.Self` and we are inside a class body,
     /// create a special binding that can be used to remap the special form to a proper
     /// self type during answers solving.
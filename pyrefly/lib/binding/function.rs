@@ -19,6 +19,7 @@ use ruff_python_ast::AnyParameterRef;
 use ruff_python_ast::Decorator;
 use ruff_python_ast::ExceptHandler;
 use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
 use ruff_python_ast::Identifier;
 use ruff_python_ast::Parameters;
 use ruff_python_ast::Stmt;
@@ -28,6 +29,7 @@ use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
 
 use crate::binding::binding::AnnotationTarget;
 use crate::binding::binding::Binding;
@@ -51,11 +53,14 @@ use crate::binding::binding::ReturnTypeKind;
 use crate::binding::bindings::BindingsBuilder;
 use crate::binding::bindings::LegacyTParamCollector;
 use crate::binding::expr::Usage;
+use crate::binding::parameter_provider::ProvidedParameter;
 use crate::binding::scope::FlowStyle;
 use crate::binding::scope::InstanceAttribute;
 use crate::binding::scope::Scope;
 use crate::binding::scope::YieldsAndReturns;
 use crate::config::base::UntypedDefBehavior;
+use crate::config::error_kind::ErrorKind;
+use crate::error::context::ErrorInfo;
 use crate::export::special::SpecialExport;
 use crate::graph::index::Idx;
 use crate::types::types::Type;
@@ -178,6 +183,7 @@ impl<'a> BindingsBuilder<'a> {
         x: &mut Parameters,
         undecorated_idx: Idx<KeyUndecoratedFunction>,
         class_key: Option<Idx<KeyClass>>,
+        provided: &SmallMap<Name, ProvidedParameter>,
     ) {
         let mut self_name = None;
         for x in x.iter_non_variadic_params() {
@@ -189,6 +195,7 @@ impl<'a> BindingsBuilder<'a> {
                 AnyParameterRef::NonVariadic(x),
                 undecorated_idx,
                 class_key,
+                provided.get(&x.parameter.name.id).cloned(),
             );
         }
         if let Some(args) = &x.vararg {
@@ -197,6 +204,7 @@ impl<'a> BindingsBuilder<'a> {
                 AnyParameterRef::Variadic(args),
                 undecorated_idx,
                 class_key,
+                None,
             );
         }
         if let Some(kwargs) = &x.kwarg {
@@ -205,6 +213,7 @@ impl<'a> BindingsBuilder<'a> {
                 AnyParameterRef::Variadic(kwargs),
                 undecorated_idx,
                 class_key,
+                None,
             );
         }
         self.scopes.set_self_name_if_applicable(self_name);
@@ -280,11 +289,15 @@ impl<'a> BindingsBuilder<'a> {
         undecorated_idx: Idx<KeyUndecoratedFunction>,
         class_key: Option<Idx<KeyClass>>,
         is_async: bool,
+        provided: &SmallMap<Name, ProvidedParameter>,
     ) -> (YieldsAndReturns, Option<SelfAssignments>) {
         self.scopes
             .push_function_scope(range, func_name, class_key.is_some(), is_async);
-        self.parameters(parameters, undecorated_idx, class_key);
+        self.parameters(parameters, undecorated_idx, class_key, provided);
         self.init_static_scope(&body, false);
+        self.check_shadowed_locals(parameters);
+        self.check_shadowed_imports(&body);
+        self.check_redefinitions(&body);
         self.stmts(
             body,
             &NestingContext::function(ShortIdentifier::new(func_name), parent.dupe()),
@@ -301,11 +314,12 @@ impl<'a> BindingsBuilder<'a> {
         undecorated_idx: Idx<KeyUndecoratedFunction>,
         class_key: Option<Idx<KeyClass>>,
         is_async: bool,
+        provided: &SmallMap<Name, ProvidedParameter>,
     ) -> Option<SelfAssignments> {
         // Push a scope to create the parameter keys (but do nothing else with it).
         self.scopes
             .push_function_scope(range, func_name, class_key.is_some(), is_async);
-        self.parameters(parameters, undecorated_idx, class_key);
+        self.parameters(parameters, undecorated_idx, class_key, provided);
         self.scopes.pop();
         // If we are in a class, use a simple visitor to find `self.<attr>` assignments.
         if class_key.is_some() {
@@ -315,19 +329,28 @@ impl<'a> BindingsBuilder<'a> {
         }
     }
 
-    /// Compute a `Key::ReturnImplicit` / `Binding::ReturnImplicit` for the given function body.
+    /// Compute a `Key::ReturnImplicit` / `Binding::ReturnImplicit` for the given function body,
+    /// along with the paths that may fall off the end of it.
     ///
     /// This function must not be called unless the function body statements will be bound;
     /// it relies on that binding to ensure we don't have a dangling `Idx<Key>` (which could lead
     /// to a panic).
-    fn implicit_return(&mut self, body: &[Stmt], func_name: &Identifier) -> Idx<Key> {
+    fn implicit_return(
+        &mut self,
+        body: &[Stmt],
+        func_name: &Identifier,
+    ) -> (Idx<Key>, Box<[TextRange]>) {
         let last_exprs = function_last_expressions(body, self.sys_info).map(|x| {
             x.into_map(|(last, x)| (last, self.last_statement_idx_for_implicit_return(last, x)))
                 .into_boxed_slice()
         });
-        self.insert_binding(
+        let idx = self.insert_binding(
             Key::ReturnImplicit(ShortIdentifier::new(func_name)),
             Binding::ReturnImplicit(ReturnImplicit { last_exprs }),
+        );
+        (
+            idx,
+            function_fall_through(body, self.sys_info).into_boxed_slice(),
         )
     }
 
@@ -338,7 +361,7 @@ impl<'a> BindingsBuilder<'a> {
         is_async: bool,
         yields_and_returns: YieldsAndReturns,
         return_ann_with_range: Option<(TextRange, Idx<KeyAnnotation>)>,
-        implicit_return: Option<Idx<Key>>,
+        implicit_return: Option<(Idx<Key>, Box<[TextRange]>)>,
         should_infer_return_type: bool,
         stub_or_impl: FunctionStubOrImpl,
         decorators: Box<[(Idx<Key>, TextRange)]>,
@@ -346,6 +369,31 @@ impl<'a> BindingsBuilder<'a> {
         let is_generator =
             !(yields_and_returns.yields.is_empty() && yields_and_returns.yield_froms.is_empty());
         let return_ann = return_ann_with_range.as_ref().map(|(_, key)| *key);
+        let yield_ranges = yields_and_returns
+            .yields
+            .iter()
+            .map(|(_, x)| x.range)
+            .chain(yields_and_returns.yield_froms.iter().map(|(_, x)| x.range))
+            .collect::<Box<[_]>>();
+
+        // A bare `return` is easy to miss next to `return value`, so flag the mix.
+        if !is_generator
+            && yields_and_returns
+                .returns
+                .iter()
+                .any(|(_, x)| x.value.is_some())
+        {
+            for (_, x) in &yields_and_returns.returns {
+                if x.value.is_none() {
+                    self.error(
+                        x.range,
+                        ErrorInfo::Kind(ErrorKind::InconsistentReturn),
+                        "Bare `return` in a function that returns a value elsewhere, use `return None`"
+                            .to_owned(),
+                    );
+                }
+            }
+        }
 
         // Collect the keys of explicit returns.
         let return_keys = yields_and_returns
@@ -380,7 +428,7 @@ impl<'a> BindingsBuilder<'a> {
 
         let return_type_binding = {
             let kind = match (return_ann_with_range, implicit_return) {
-                (Some((range, annotation)), Some(implicit_return)) => {
+                (Some((range, annotation)), Some((implicit_return, fall_through))) => {
                     // We have an explicit return annotation and we want to validate it.
                     ReturnTypeKind::ShouldValidateAnnotation {
                         range,
@@ -390,6 +438,8 @@ impl<'a> BindingsBuilder<'a> {
                         implicit_return,
                         is_generator: !(yield_keys.is_empty() && yield_from_keys.is_empty()),
                         has_explicit_return: !return_keys.is_empty(),
+                        yield_ranges,
+                        fall_through,
                     }
                 }
                 (Some((_, annotation)), None) => {
@@ -399,7 +449,7 @@ impl<'a> BindingsBuilder<'a> {
                         is_generator: !(yield_keys.is_empty() && yield_from_keys.is_empty()),
                     }
                 }
-                (None, Some(implicit_return)) if should_infer_return_type => {
+                (None, Some((implicit_return, _))) if should_infer_return_type => {
                     // We don't have an explicit return annotation, but we want to infer it.
                     ReturnTypeKind::ShouldInferType {
                         returns: return_keys,
@@ -468,6 +518,7 @@ impl<'a> BindingsBuilder<'a> {
         parent: &NestingContext,
         undecorated_idx: Idx<KeyUndecoratedFunction>,
         class_key: Option<Idx<KeyClass>>,
+        provided: &SmallMap<Name, ProvidedParameter>,
     ) -> (FunctionStubOrImpl, Option<SelfAssignments>) {
         let stub_or_impl = if (body.first().is_some_and(is_docstring)
             && decorators.is_abstract_method)
@@ -492,6 +543,7 @@ impl<'a> BindingsBuilder<'a> {
                 undecorated_idx,
                 class_key,
                 is_async,
+                provided,
             )
         } else {
             match self.untyped_def_behavior {
@@ -505,6 +557,7 @@ impl<'a> BindingsBuilder<'a> {
                         undecorated_idx,
                         class_key,
                         is_async,
+                        provided,
                     );
                     self.analyze_return_type(
                         func_name,
@@ -529,6 +582,7 @@ impl<'a> BindingsBuilder<'a> {
                         undecorated_idx,
                         class_key,
                         is_async,
+                        provided,
                     );
                     self.analyze_return_type(
                         func_name,
@@ -553,6 +607,7 @@ impl<'a> BindingsBuilder<'a> {
                         undecorated_idx,
                         class_key,
                         is_async,
+                        provided,
                     );
                     self.analyze_return_type(
                         func_name,
@@ -592,9 +647,18 @@ impl<'a> BindingsBuilder<'a> {
         let (return_ann_with_range, legacy_tparams) =
             self.function_header(&mut x, &func_name, class_key, def_idx.usage());
 
+        let called_by_framework = self.is_called_by_framework(&x, parent);
+        let provided = self.provided_parameters(&x, parent, class_key.is_some());
+
         let decorators = self.decorators(mem::take(&mut x.decorator_list), def_idx.usage());
 
         let docstring_range = Docstring::range_from_stmts(x.body.as_slice());
+        // A test can request a fixture just for its side effects.
+        let unused_parameters = unused_parameters(&x.parameters, &x.body)
+            .iter()
+            .filter(|x| !provided.get(&x.id).is_some_and(|x| x.may_be_unused()))
+            .cloned()
+            .collect();
         let (stub_or_impl, self_assignments) = self.function_body(
             &mut x.parameters,
             mem::take(&mut x.body),
@@ -606,6 +670,7 @@ impl<'a> BindingsBuilder<'a> {
             parent,
             undecorated_idx,
             class_key,
+            &provided,
         );
 
         // Pop the annotation scope to get back to the parent scope, and handle this
@@ -622,6 +687,9 @@ impl<'a> BindingsBuilder<'a> {
                 class_key,
                 decorators: decorators.decorators,
                 legacy_tparams: legacy_tparams.into_boxed_slice(),
+                unused_parameters,
+                parent: parent.dupe(),
+                called_by_framework,
             },
         );
 
@@ -643,6 +711,131 @@ impl<'a> BindingsBuilder<'a> {
     }
 }
 
+/// The parameters that are never read in a function body, including from nested scopes.
+/// Parameters whose name starts with `_` are unused by convention, and a body that calls `locals()`
+/// might read any of them.
+fn unused_parameters(parameters: &Parameters, body: &[Stmt]) -> Box<[Identifier]> {
+    fn f(x: &Expr, used: &mut SmallSet<Name>, calls_locals: &mut bool) {
+        match x {
+            Expr::Name(x) if x.ctx != ExprContext::Store => {
+                used.insert(x.id.clone());
+            }
+            Expr::Call(call) if matches!(&*call.func, Expr::Name(x) if x.id == "locals") => {
+                *calls_locals = true;
+            }
+            _ => {}
+        }
+        x.recurse(&mut |x| f(x, used, calls_locals));
+    }
+    let mut used = SmallSet::new();
+    let mut calls_locals = false;
+    for stmt in body {
+        stmt.visit(&mut |x| f(x, &mut used, &mut calls_locals));
+    }
+    if calls_locals {
+        return Box::new([]);
+    }
+    parameters
+        .iter()
+        .filter_map(|x| {
+            let name = x.name();
+            (!name.id.starts_with('_') && !used.contains(&name.id)).then(|| name.clone())
+        })
+        .collect()
+}
+
+/// The last statement of each path through a function body that may reach the end of the body,
+/// following the same rules as `function_last_expressions`. For expression statements we record
+/// the range of the expression, so that calls which never return can be discarded once solved.
+fn function_fall_through(x: &[Stmt], sys_info: &SysInfo) -> Vec<TextRange> {
+    fn has_break(body: &[Stmt]) -> bool {
+        fn f(stmt: &Stmt, res: &mut bool) {
+            match stmt {
+                Stmt::Break(_) => {
+                    *res = true;
+                }
+                Stmt::While(_) | Stmt::For(_) => {}
+                _ => stmt.recurse(&mut |stmt| f(stmt, res)),
+            }
+        }
+        let mut res = false;
+        for stmt in body {
+            f(stmt, &mut res);
+        }
+        res
+    }
+
+    fn f(sys_info: &SysInfo, x: &[Stmt], res: &mut Vec<TextRange>) {
+        let Some(last) = x.last() else {
+            return;
+        };
+        match last {
+            Stmt::Expr(x) => res.push(x.value.range()),
+            Stmt::Return(_) | Stmt::Raise(_) => {}
+            Stmt::Assert(x) if sys_info.evaluate_bool(&x.test) == Some(false) => {}
+            Stmt::With(x) => f(sys_info, &x.body, res),
+            Stmt::While(x) => {
+                if sys_info.evaluate_bool(&x.test) != Some(true) || has_break(&x.body) {
+                    res.push(x.range);
+                }
+            }
+            Stmt::For(x) => {
+                if has_break(&x.body) {
+                    res.push(x.range);
+                }
+            }
+            Stmt::If(x) => {
+                let mut last_test = None;
+                for (test, body) in sys_info.pruned_if_branches(x) {
+                    last_test = test;
+                    f(sys_info, body, res);
+                }
+                if last_test.is_some() {
+                    res.push(x.range);
+                }
+            }
+            Stmt::Try(x) => {
+                if !x.finalbody.is_empty()
+                    && x.finalbody
+                        .iter()
+                        .any(|stmt| matches!(stmt, Stmt::Return(_)))
+                {
+                    f(sys_info, &x.finalbody, res);
+                } else {
+                    if x.orelse.is_empty() {
+                        f(sys_info, &x.body, res);
+                    } else {
+                        f(sys_info, &x.orelse, res);
+                    }
+                    for handler in &x.handlers {
+                        match handler {
+                            ExceptHandler::ExceptHandler(x) => f(sys_info, &x.body, res),
+                        }
+                    }
+                }
+            }
+            Stmt::Match(x) => {
+                let mut exhaustive = false;
+                for case in x.cases.iter() {
+                    f(sys_info, &case.body, res);
+                    if case.pattern.is_wildcard() || case.pattern.is_irrefutable() {
+                        exhaustive = true;
+                        break;
+                    }
+                }
+                if !exhaustive {
+                    res.push(x.range);
+                }
+            }
+            _ => res.push(last.range()),
+        }
+    }
+
+    let mut res = Vec::new();
+    f(sys_info, x, &mut res);
+    res
+}
+
 /// Given the body of a function, what are the potential expressions that
 /// could be the last ones to be executed, where the function then falls off the end.
 ///
@@ -0,0 +1,279 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Syntactic checks on how a class body and its methods define instance attributes.
+
+use pyrefly_python::dunder;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+
+use crate::binding::bindings::BindingsBuilder;
+use crate::config::error_kind::ErrorKind;
+use crate::error::context::ErrorInfo;
+
+/// The names declared by `__slots__` in a class body, if it is assigned a string, or a tuple,
+/// list or set of strings. Returns `None` if there is no `__slots__`, or if we can't tell what
+/// it contains.
+pub fn slot_names(body: &[Stmt]) -> Option<Box<[Name]>> {
+    let value = body.iter().rev().find_map(|stmt| match stmt {
+        Stmt::Assign(x)
+            if x.targets
+                .iter()
+                .any(|t| t.as_name_expr().is_some_and(|t| t.id == dunder::SLOTS)) =>
+        {
+            Some(&*x.value)
+        }
+        Stmt::AnnAssign(x)
+            if x.target
+                .as_name_expr()
+                .is_some_and(|t| t.id == dunder::SLOTS) =>
+        {
+            x.value.as_deref()
+        }
+        _ => None,
+    })?;
+    let elts = match value {
+        Expr::StringLiteral(x) => return Some(Box::new([Name::new(x.value.to_str())])),
+        Expr::Tuple(x) => &x.elts,
+        Expr::List(x) => &x.elts,
+        Expr::Set(x) => &x.elts,
+        _ => return None,
+    };
+    elts.iter()
+        .map(|x| match x {
+            Expr::StringLiteral(x) => Some(Name::new(x.value.to_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The receiver of a method, i.e. its first positional parameter.
+fn method_receiver(def: &StmtFunctionDef) -> Option<&Name> {
+    def.parameters
+        .posonlyargs
+        .iter()
+        .chain(&def.parameters.args)
+        .next()
+        .map(|x| &x.parameter.name.id)
+}
+
+/// Record every attribute of `receiver` that `target` assigns to.
+fn assigned_attributes(receiver: &Name, target: &Expr, res: &mut SmallMap<Name, TextRange>) {
+    match target {
+        Expr::Attribute(x) if x.value.as_name_expr().is_some_and(|x| &x.id == receiver) => {
+            if !res.contains_key(&x.attr.id) {
+                res.insert(x.attr.id.clone(), x.range);
+            }
+        }
+        Expr::Tuple(_) | Expr::List(_) | Expr::Starred(_) => {
+            target.recurse(&mut |x| assigned_attributes(receiver, x, res))
+        }
+        _ => {}
+    }
+}
+
+/// Every attribute of `receiver` assigned anywhere in `body`, with the range of the first assignment.
+fn all_assigned_attributes(receiver: &Name, body: &[Stmt]) -> SmallMap<Name, TextRange> {
+    fn f(receiver: &Name, stmt: &Stmt, res: &mut SmallMap<Name, TextRange>) {
+        match stmt {
+            Stmt::FunctionDef(_) | Stmt::ClassDef(_) => return,
+            Stmt::Assign(x) => {
+                for target in &x.targets {
+                    assigned_attributes(receiver, target, res);
+                }
+            }
+            Stmt::AnnAssign(x) if x.value.is_some() => {
+                assigned_attributes(receiver, &x.target, res)
+            }
+            _ => {}
+        }
+        stmt.recurse(&mut |x| f(receiver, x, res));
+    }
+    let mut res = SmallMap::new();
+    for stmt in body {
+        f(receiver, stmt, &mut res);
+    }
+    res
+}
+
+fn intersect(x: SmallSet<Name>, y: &SmallSet<Name>) -> SmallSet<Name> {
+    x.into_iter().filter(|name| y.contains(name)).collect()
+}
+
+/// Merge the attributes assigned on another path that reaches the same point.
+fn merge(x: Option<SmallSet<Name>>, y: SmallSet<Name>) -> Option<SmallSet<Name>> {
+    Some(match x {
+        None => y,
+        Some(x) => intersect(x, &y),
+    })
+}
+
+/// The attributes of `receiver` that are assigned on every path through `body`, starting from
+/// those in `assigned`. Returns `None` if no path falls off the end of `body`. The attributes
+/// assigned on paths that leave the function with `return` are added to `returns`.
+fn definitely_assigned(
+    receiver: &Name,
+    body: &[Stmt],
+    mut assigned: SmallSet<Name>,
+    returns: &mut Vec<SmallSet<Name>>,
+) -> Option<SmallSet<Name>> {
+    for stmt in body {
+        match stmt {
+            Stmt::Assign(_) | Stmt::AnnAssign(_) => {
+                for (name, _) in all_assigned_attributes(receiver, std::slice::from_ref(stmt)) {
+                    assigned.insert(name);
+                }
+            }
+            Stmt::Return(_) => {
+                returns.push(assigned);
+                return None;
+            }
+            Stmt::Raise(_) => return None,
+            Stmt::If(x) => {
+                let mut out = None;
+                if let Some(res) = definitely_assigned(receiver, &x.body, assigned.clone(), returns)
+                {
+                    out = merge(out, res);
+                }
+                let mut has_else = false;
+                for clause in &x.elif_else_clauses {
+                    has_else |= clause.test.is_none();
+                    if let Some(res) =
+                        definitely_assigned(receiver, &clause.body, assigned.clone(), returns)
+                    {
+                        out = merge(out, res);
+                    }
+                }
+                if !has_else {
+                    out = merge(out, assigned);
+                }
+                assigned = out?;
+            }
+            Stmt::Match(x) => {
+                let mut out = None;
+                let mut exhaustive = false;
+                for case in &x.cases {
+                    if let Some(res) =
+                        definitely_assigned(receiver, &case.body, assigned.clone(), returns)
+                    {
+                        out = merge(out, res);
+                    }
+                    if case.pattern.is_wildcard() || case.pattern.is_irrefutable() {
+                        exhaustive = true;
+                        break;
+                    }
+                }
+                if !exhaustive {
+                    out = merge(out, assigned);
+                }
+                assigned = out?;
+            }
+            Stmt::With(x) => {
+                assigned = definitely_assigned(receiver, &x.body, assigned, returns)?;
+            }
+            Stmt::Try(x) => {
+                // Any statement in the body may raise, so a handler can only rely on what was
+                // assigned before the `try`.
+                let mut out = definitely_assigned(receiver, &x.body, assigned.clone(), returns)
+                    .and_then(|res| definitely_assigned(receiver, &x.orelse, res, returns));
+                for handler in &x.handlers {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    if let Some(res) =
+                        definitely_assigned(receiver, &handler.body, assigned.clone(), returns)
+                    {
+                        out = merge(out, res);
+                    }
+                }
+                assigned = definitely_assigned(receiver, &x.finalbody, out?, returns)?;
+            }
+            Stmt::For(x) => {
+                // The loop body might never run, but any `return` inside it still leaves the function.
+                definitely_assigned(receiver, &x.body, assigned.clone(), returns);
+                assigned = definitely_assigned(receiver, &x.orelse, assigned, returns)?;
+            }
+            Stmt::While(x) => {
+                definitely_assigned(receiver, &x.body, assigned.clone(), returns);
+                assigned = definitely_assigned(receiver, &x.orelse, assigned, returns)?;
+            }
+            _ => {}
+        }
+    }
+    Some(assigned)
+}
+
+impl<'a> BindingsBuilder<'a> {
+    /// Report attributes that `__init__` assigns on some paths but not others, and which aren't
+    /// defined anywhere else in the class, since accessing them later may fail.
+    pub fn check_possibly_missing_attributes(&mut self, body: &[Stmt]) {
+        let Some(init) = body.iter().find_map(|stmt| match stmt {
+            Stmt::FunctionDef(x) if x.name.id == dunder::INIT => Some(x),
+            _ => None,
+        }) else {
+            return;
+        };
+        let Some(receiver) = method_receiver(init) else {
+            return;
+        };
+        let assigned = all_assigned_attributes(receiver, &init.body);
+        if assigned.is_empty() {
+            return;
+        }
+        let mut returns = Vec::new();
+        let mut definite = definitely_assigned(receiver, &init.body, SmallSet::new(), &mut returns);
+        for res in returns {
+            definite = merge(definite, res);
+        }
+        // If `__init__` always raises, nothing is ever missing.
+        let Some(definite) = definite else {
+            return;
+        };
+        let mut defined_elsewhere = SmallSet::new();
+        for stmt in body {
+            match stmt {
+                Stmt::FunctionDef(x) if x.name.id != dunder::INIT => {
+                    if let Some(receiver) = method_receiver(x) {
+                        for (name, _) in all_assigned_attributes(receiver, &x.body) {
+                            defined_elsewhere.insert(name);
+                        }
+                    }
+                }
+                Stmt::Assign(x) => {
+                    for target in &x.targets {
+                        if let Some(name) = target.as_name_expr() {
+                            defined_elsewhere.insert(name.id.clone());
+                        }
+                    }
+                }
+                Stmt::AnnAssign(x) if x.value.is_some() => {
+                    if let Some(name) = x.target.as_name_expr() {
+                        defined_elsewhere.insert(name.id.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (name, range) in assigned {
+            if !definite.contains(&name) && !defined_elsewhere.contains(&name) {
+                self.error(
+                    range,
+                    ErrorInfo::Kind(ErrorKind::PossiblyMissingAttribute),
+                    format!(
+                        "Attribute `{name}` is only assigned on some paths through `__init__`, so it may be missing"
+                    ),
+                );
+            }
+        }
+    }
+}
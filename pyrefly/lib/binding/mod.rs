@@ -10,12 +10,17 @@ pub mod binding;
 pub mod bindings;
 pub mod class;
 pub mod django;
+pub mod embedded_code;
 pub mod expr;
 pub mod function;
+pub mod instance_attributes;
 pub mod narrow;
+pub mod parameter_provider;
 pub mod pattern;
 pub mod pydantic;
+pub mod redefinition;
 pub mod scope;
 pub mod stmt;
+pub mod stub_consistency;
 pub mod table;
 pub mod target;
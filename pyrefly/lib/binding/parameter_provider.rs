@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Frameworks that call functions themselves, passing values to their parameters that we can work
+//! out from the code, like pytest passing fixtures to tests. We give the unannotated parameters
+//! the types of those values. Each framework has a provider, enabled with the
+//! `parameter-providers` config option. To support a new framework, implement
+//! `ParameterProvider`, add a variant to `ParameterProviderKind` and return it from `provider`.
+
+pub mod click;
+pub mod fastapi;
+pub mod pytest;
+
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::nesting_context::NestingContext;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ParameterWithDefault;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use starlark_map::small_map::SmallMap;
+
+use crate::binding::binding::Key;
+use crate::binding::bindings::BindingsBuilder;
+use crate::binding::parameter_provider::click::Click;
+use crate::binding::parameter_provider::fastapi::FastApi;
+use crate::binding::parameter_provider::pytest::Pytest;
+use crate::config::base::ParameterProviderKind;
+use crate::graph::index::Idx;
+
+/// Where a framework gets the value of a parameter from, which determines its type.
+#[derive(Clone, Debug)]
+pub enum ProvidedParameter {
+    /// A fixture defined in the same module, given by the key of its return type.
+    Fixture(Idx<Key>),
+    /// A fixture exported from another module.
+    ExternalFixture(ModuleName, Name),
+    /// The value returned by calling this expression, awaiting it if it is a coroutine, or the
+    /// value it yields if it is a generator.
+    Dependency(Expr),
+    /// Each of these values in turn.
+    Values(Box<[Expr]>),
+    /// An instance of the class this expression evaluates to.
+    Instance(Expr),
+    /// A tuple of any number of values.
+    Multiple(Box<ProvidedParameter>),
+    /// A value, or `None`.
+    Optional(Box<ProvidedParameter>),
+}
+
+impl ProvidedParameter {
+    /// Whether the parameter may be requested just for the side effects of computing its value,
+    /// so shouldn't be reported if it is never used.
+    pub fn may_be_unused(&self) -> bool {
+        matches!(
+            self,
+            Self::Fixture(_) | Self::ExternalFixture(..) | Self::Dependency(_)
+        )
+    }
+}
+
+/// A framework that provides the values of the parameters of some functions.
+pub trait ParameterProvider: Sync {
+    /// Whether the framework calls `def` itself, so nothing else uses what it returns.
+    fn calls_function(
+        &self,
+        builder: &BindingsBuilder,
+        def: &StmtFunctionDef,
+        parent: &NestingContext,
+    ) -> bool;
+
+    /// Where the framework gets the value of `param` of `def` from, if it provides it. `param`
+    /// is unannotated, and isn't the receiver of a method.
+    fn parameter(
+        &self,
+        builder: &mut BindingsBuilder,
+        def: &StmtFunctionDef,
+        parent: &NestingContext,
+        param: &ParameterWithDefault,
+    ) -> Option<ProvidedParameter>;
+}
+
+pub fn provider(kind: ParameterProviderKind) -> &'static dyn ParameterProvider {
+    match kind {
+        ParameterProviderKind::Pytest => &Pytest,
+        ParameterProviderKind::Fastapi => &FastApi,
+        ParameterProviderKind::Click => &Click,
+    }
+}
+
+impl<'a> BindingsBuilder<'a> {
+    /// Whether any of the parameter providers calls `def` itself.
+    pub fn is_called_by_framework(&self, def: &StmtFunctionDef, parent: &NestingContext) -> bool {
+        self.parameter_providers
+            .iter()
+            .any(|x| x.calls_function(self, def, parent))
+    }
+
+    /// Where the frameworks get the values of the unannotated parameters of `def` from, for the
+    /// ones we understand. The first provider to give a parameter a value wins.
+    pub fn provided_parameters(
+        &mut self,
+        def: &StmtFunctionDef,
+        parent: &NestingContext,
+        is_method: bool,
+    ) -> SmallMap<Name, ProvidedParameter> {
+        let mut res = SmallMap::new();
+        if self.parameter_providers.is_empty() {
+            return res;
+        }
+        let positional = def
+            .parameters
+            .posonlyargs
+            .iter()
+            .chain(&def.parameters.args)
+            // Frameworks pass the receiver of a method themselves.
+            .skip(if is_method { 1 } else { 0 });
+        for param in positional.chain(&def.parameters.kwonlyargs) {
+            if param.parameter.annotation.is_some() {
+                continue;
+            }
+            for provider in self.parameter_providers.clone() {
+                if let Some(x) = provider.parameter(self, def, parent, param) {
+                    res.insert(param.parameter.name.id.clone(), x);
+                    break;
+                }
+            }
+        }
+        res
+    }
+}
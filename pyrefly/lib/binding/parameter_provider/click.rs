@@ -0,0 +1,216 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Support for Click commands. Click calls a command with the values of its
+//! `@click.option(...)` and `@click.argument(...)` decorators, converted to the `type` of the
+//! option, or else to the type of its `default`, or else to `str`.
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::nesting_context::NestingContext;
+use ruff_python_ast::AtomicNodeIndex;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprBooleanLiteral;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprNumberLiteral;
+use ruff_python_ast::ExprUnaryOp;
+use ruff_python_ast::Int;
+use ruff_python_ast::Number;
+use ruff_python_ast::ParameterWithDefault;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::UnaryOp;
+use ruff_text_size::Ranged;
+
+use crate::binding::bindings::BindingsBuilder;
+use crate::binding::parameter_provider::ParameterProvider;
+use crate::binding::parameter_provider::ProvidedParameter;
+
+/// The name of the Click decorator this is, like `option` for `@click.option(...)`, possibly with
+/// the decorator imported directly.
+fn click_decorator(x: &Expr) -> Option<&str> {
+    let func = match x {
+        Expr::Call(x) => &*x.func,
+        x => x,
+    };
+    let name = match func {
+        Expr::Name(x) => x.id.as_str(),
+        Expr::Attribute(x) if matches!(&*x.value, Expr::Name(base) if base.id == "click") => {
+            x.attr.id.as_str()
+        }
+        _ => return None,
+    };
+    matches!(name, "command" | "group" | "option" | "argument").then_some(name)
+}
+
+fn keyword<'a>(call: &'a ExprCall, name: &str) -> Option<&'a Expr> {
+    call.arguments
+        .keywords
+        .iter()
+        .find(|x| x.arg.as_ref().is_some_and(|x| x.as_str() == name))
+        .map(|x| &x.value)
+}
+
+fn is_true(x: Option<&Expr>) -> bool {
+    matches!(x, Some(Expr::BooleanLiteral(x)) if x.value)
+}
+
+/// The number of values an option or argument takes, where `-1` means any number.
+fn nargs(call: &ExprCall) -> Option<i64> {
+    match keyword(call, "nargs") {
+        None => Some(1),
+        Some(Expr::NumberLiteral(ExprNumberLiteral {
+            value: Number::Int(x),
+            ..
+        })) => x.as_i64(),
+        Some(Expr::UnaryOp(ExprUnaryOp {
+            op: UnaryOp::USub,
+            operand,
+            ..
+        })) => match &**operand {
+            Expr::NumberLiteral(ExprNumberLiteral {
+                value: Number::Int(x),
+                ..
+            }) => x.as_i64().map(|x| -x),
+            _ => None,
+        },
+        Some(_) => None,
+    }
+}
+
+/// The name of the parameter an option or argument is passed to, following Click: a declaration
+/// without dashes, or else the first declaration with the most leading dashes, converted to an
+/// identifier. Also returns whether the option is a `--flag/--no-flag` pair.
+fn parameter_name(call: &ExprCall) -> Option<(String, bool)> {
+    let decls = call
+        .arguments
+        .args
+        .iter()
+        .map(|x| Some(x.as_string_literal_expr()?.value.to_str()))
+        .collect::<Option<Vec<_>>>()?;
+    let is_slash = decls.iter().any(|x| x.contains('/'));
+    let decl = match decls.iter().find(|x| !x.starts_with('-')) {
+        Some(x) => x,
+        None => {
+            let dashes = |x: &&str| x.len() - x.trim_start_matches('-').len();
+            let most = decls.iter().map(dashes).max()?;
+            decls.iter().find(|x| dashes(x) == most)?
+        }
+    };
+    let name = decl
+        .split('/')
+        .next()?
+        .trim()
+        .trim_start_matches('-')
+        .replace('-', "_")
+        .to_lowercase();
+    Some((name, is_slash))
+}
+
+/// Where Click gets the value of the parameter of an option or argument from.
+fn provided(call: &ExprCall, is_option: bool, is_slash: bool) -> Option<ProvidedParameter> {
+    if is_true(keyword(call, "is_flag")) || is_slash {
+        return Some(ProvidedParameter::Values(Box::new([Expr::BooleanLiteral(
+            ExprBooleanLiteral {
+                node_index: AtomicNodeIndex::dummy(),
+                range: call.range(),
+                value: true,
+            },
+        )])));
+    }
+    if is_true(keyword(call, "count")) {
+        return Some(ProvidedParameter::Values(Box::new([Expr::NumberLiteral(
+            ExprNumberLiteral {
+                node_index: AtomicNodeIndex::dummy(),
+                range: call.range(),
+                value: Number::Int(Int::ZERO),
+            },
+        )])));
+    }
+    // `nargs=-1` takes any number of values, and a fixed number of values is a tuple, which we
+    // don't model.
+    let multiple = match nargs(call)? {
+        1 => is_true(keyword(call, "multiple")),
+        -1 => true,
+        _ => return None,
+    };
+    let default = keyword(call, "default").filter(|x| !x.is_none_literal_expr());
+    let value = if let Some(ty) = keyword(call, "type") {
+        // A tuple of types gives each of a fixed number of values a type.
+        if ty.is_tuple_expr() {
+            return None;
+        }
+        ProvidedParameter::Instance(ty.clone())
+    } else if let Some(default) = default {
+        let values = match default {
+            // The default of an option that takes many values lists them.
+            Expr::List(x) if multiple => &x.elts[..],
+            Expr::Tuple(x) if multiple => &x.elts[..],
+            x => std::slice::from_ref(x),
+        };
+        if values.is_empty() {
+            ProvidedParameter::Values(Box::new([Ast::str_expr("", call.range())]))
+        } else {
+            ProvidedParameter::Values(values.into())
+        }
+    } else {
+        ProvidedParameter::Values(Box::new([Ast::str_expr("", call.range())]))
+    };
+    if multiple {
+        return Some(ProvidedParameter::Multiple(Box::new(value)));
+    }
+    // Options are optional by default, and arguments are required by default.
+    let required = match keyword(call, "required") {
+        Some(x) => is_true(Some(x)),
+        None => !is_option,
+    };
+    if !required && default.is_none() {
+        Some(ProvidedParameter::Optional(Box::new(value)))
+    } else {
+        Some(value)
+    }
+}
+
+pub struct Click;
+
+impl ParameterProvider for Click {
+    /// A function decorated as a command is turned into a `Command` object that calls it, and its
+    /// options and arguments are only ever passed by Click.
+    fn calls_function(
+        &self,
+        _builder: &BindingsBuilder,
+        def: &StmtFunctionDef,
+        _parent: &NestingContext,
+    ) -> bool {
+        def.decorator_list
+            .iter()
+            .any(|x| click_decorator(&x.expression).is_some())
+    }
+
+    fn parameter(
+        &self,
+        _builder: &mut BindingsBuilder,
+        def: &StmtFunctionDef,
+        _parent: &NestingContext,
+        param: &ParameterWithDefault,
+    ) -> Option<ProvidedParameter> {
+        let name = param.parameter.name.as_str();
+        def.decorator_list.iter().find_map(|x| {
+            let Expr::Call(call) = &x.expression else {
+                return None;
+            };
+            let is_option = match click_decorator(&x.expression)? {
+                "option" => true,
+                "argument" => false,
+                _ => return None,
+            };
+            let (decl, is_slash) = parameter_name(call)?;
+            if decl != name {
+                return None;
+            }
+            provided(call, is_option, is_slash)
+        })
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Support for FastAPI dependencies. A parameter whose default is `Depends(f)` or `Security(f)`
+//! is passed the value of the dependency `f`: what it returns, what it yields if it is a
+//! generator, and an instance if it is a class.
+
+use pyrefly_python::nesting_context::NestingContext;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ParameterWithDefault;
+use ruff_python_ast::StmtFunctionDef;
+
+use crate::binding::bindings::BindingsBuilder;
+use crate::binding::parameter_provider::ParameterProvider;
+use crate::binding::parameter_provider::ProvidedParameter;
+
+/// Is this `Depends` or `Security`, possibly as an attribute of the `fastapi` module?
+fn is_dependency_marker(x: &Expr) -> bool {
+    let name = match x {
+        Expr::Name(x) => &x.id,
+        Expr::Attribute(x) if matches!(&*x.value, Expr::Name(base) if base.id == "fastapi") => {
+            &x.attr.id
+        }
+        _ => return false,
+    };
+    name == "Depends" || name == "Security"
+}
+
+/// The dependency of `Depends(f)`, `Depends(dependency=f)` or `Security(f)`.
+fn dependency(x: &Expr) -> Option<&Expr> {
+    let Expr::Call(call) = x else {
+        return None;
+    };
+    if !is_dependency_marker(&call.func) {
+        return None;
+    }
+    call.arguments.args.first().or_else(|| {
+        call.arguments
+            .keywords
+            .iter()
+            .find(|x| x.arg.as_ref().is_some_and(|x| x.as_str() == "dependency"))
+            .map(|x| &x.value)
+    })
+}
+
+pub struct FastApi;
+
+impl ParameterProvider for FastApi {
+    /// FastAPI calls route handlers and dependencies, but they are ordinary functions that may be
+    /// called directly too.
+    fn calls_function(
+        &self,
+        _builder: &BindingsBuilder,
+        _def: &StmtFunctionDef,
+        _parent: &NestingContext,
+    ) -> bool {
+        false
+    }
+
+    fn parameter(
+        &self,
+        _builder: &mut BindingsBuilder,
+        _def: &StmtFunctionDef,
+        _parent: &NestingContext,
+        param: &ParameterWithDefault,
+    ) -> Option<ProvidedParameter> {
+        // Without a dependency, `Depends()` uses the annotation, which we don't have.
+        let dependency = dependency(param.default.as_deref()?)?;
+        Some(ProvidedParameter::Dependency(dependency.clone()))
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Support for test files run by pytest, i.e. `test_*.py`, `*_test.py` and `conftest.py`.
+//! pytest calls the tests and fixtures in these files itself, passing each parameter either the
+//! value of the fixture with the same name, or the values of a `@pytest.mark.parametrize`
+//! argument, so we give the unannotated parameters those types.
+
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::nesting_context::NestingContext;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use ruff_python_ast::Decorator;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::Identifier;
+use ruff_python_ast::ParameterWithDefault;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use starlark_map::small_map::SmallMap;
+
+use crate::binding::binding::Key;
+use crate::binding::bindings::BindingsBuilder;
+use crate::binding::parameter_provider::ParameterProvider;
+use crate::binding::parameter_provider::ProvidedParameter;
+
+const CONFTEST: Name = Name::new_static("conftest");
+
+/// Is this a file pytest collects tests or fixtures from, with its default settings?
+pub fn is_test_file(path: &ModulePath) -> bool {
+    let Some(stem) = path
+        .as_path()
+        .file_name()
+        .and_then(|x| x.to_str())
+        .and_then(|x| x.strip_suffix(".py"))
+    else {
+        return false;
+    };
+    stem == "conftest" || stem.starts_with("test_") || stem.ends_with("_test")
+}
+
+/// Is this `@pytest.fixture` or `@pytest.fixture(...)`, possibly with `fixture` imported directly?
+fn is_fixture_decorator(x: &Expr) -> bool {
+    let func = match x {
+        Expr::Call(x) => &*x.func,
+        x => x,
+    };
+    match func {
+        Expr::Name(x) => x.id == "fixture",
+        Expr::Attribute(x) => {
+            x.attr.id == "fixture" && matches!(&*x.value, Expr::Name(base) if base.id == "pytest")
+        }
+        _ => false,
+    }
+}
+
+fn is_parametrize(x: &Expr) -> bool {
+    let Expr::Attribute(x) = x else {
+        return false;
+    };
+    x.attr.id == "parametrize"
+        && match &*x.value {
+            Expr::Name(mark) => mark.id == "mark",
+            Expr::Attribute(mark) => {
+                mark.attr.id == "mark"
+                    && matches!(&*mark.value, Expr::Name(base) if base.id == "pytest")
+            }
+            _ => false,
+        }
+}
+
+/// The name a fixture is requested by, if `def` is a fixture: the `name` passed to
+/// `@pytest.fixture`, or else the name of the function.
+fn fixture_name(def: &StmtFunctionDef) -> Option<Name> {
+    let decorator = def
+        .decorator_list
+        .iter()
+        .find(|x| is_fixture_decorator(&x.expression))?;
+    if let Expr::Call(call) = &decorator.expression
+        && let Some(name) = call
+            .arguments
+            .keywords
+            .iter()
+            .find(|x| x.arg.as_ref().is_some_and(|x| x.as_str() == "name"))
+        && let Expr::StringLiteral(name) = &name.value
+    {
+        Some(Name::new(name.value.to_str()))
+    } else {
+        Some(def.name.id.clone())
+    }
+}
+
+/// The fixtures defined at the top level of a test file, by the name they are requested with,
+/// or `None` if this isn't a test file.
+pub fn fixtures(path: &ModulePath, body: &[Stmt]) -> Option<SmallMap<Name, Identifier>> {
+    if !is_test_file(path) {
+        return None;
+    }
+    let mut res = SmallMap::new();
+    for x in body {
+        if let Stmt::FunctionDef(def) = x
+            && let Some(name) = fixture_name(def)
+        {
+            // pytest uses the last definition of a fixture.
+            res.insert(name, def.name.clone());
+        }
+    }
+    Some(res)
+}
+
+/// The values each argument of a `@pytest.mark.parametrize(...)` call takes, if we can work them
+/// out syntactically.
+fn parametrize_values(call: &ExprCall) -> Option<Vec<(Name, Box<[Expr]>)>> {
+    let mut argnames = call.arguments.args.first();
+    let mut argvalues = call.arguments.args.get(1);
+    for keyword in &call.arguments.keywords {
+        match keyword.arg.as_ref().map(|x| x.as_str()) {
+            Some("argnames") => argnames = Some(&keyword.value),
+            Some("argvalues") => argvalues = Some(&keyword.value),
+            // The values are passed to fixtures, rather than to the test.
+            Some("indirect") => return None,
+            _ => {}
+        }
+    }
+    let names: Vec<Name> = match argnames? {
+        Expr::StringLiteral(x) => x
+            .value
+            .to_str()
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(Name::new)
+            .collect(),
+        Expr::Tuple(x) => x
+            .elts
+            .iter()
+            .map(|x| Some(Name::new(x.as_string_literal_expr()?.value.to_str())))
+            .collect::<Option<_>>()?,
+        Expr::List(x) => x
+            .elts
+            .iter()
+            .map(|x| Some(Name::new(x.as_string_literal_expr()?.value.to_str())))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let rows = match argvalues? {
+        Expr::List(x) => &x.elts,
+        Expr::Tuple(x) => &x.elts,
+        _ => return None,
+    };
+    let mut columns = vec![Vec::with_capacity(rows.len()); names.len()];
+    for row in rows {
+        // `pytest.param(...)` wraps a row to give it marks or an id.
+        let row = match row {
+            Expr::Call(x)
+                if matches!(&*x.func, Expr::Attribute(f) if f.attr.id == "param"
+                    && matches!(&*f.value, Expr::Name(base) if base.id == "pytest")) =>
+            {
+                &*x.arguments.args
+            }
+            row if names.len() == 1 => std::slice::from_ref(row),
+            Expr::Tuple(x) => &*x.elts,
+            Expr::List(x) => &*x.elts,
+            _ => return None,
+        };
+        if row.len() != names.len() {
+            return None;
+        }
+        for (column, x) in columns.iter_mut().zip(row) {
+            column.push(x.clone());
+        }
+    }
+    Some(
+        names
+            .into_iter()
+            .zip(columns)
+            .map(|(name, values)| (name, values.into_boxed_slice()))
+            .collect(),
+    )
+}
+
+/// The arguments of all the `@pytest.mark.parametrize` decorators on a function.
+fn parametrize_arguments(decorators: &[Decorator]) -> SmallMap<Name, Box<[Expr]>> {
+    let mut res = SmallMap::new();
+    for x in decorators {
+        if let Expr::Call(call) = &x.expression
+            && is_parametrize(&call.func)
+            && let Some(values) = parametrize_values(call)
+        {
+            res.extend(values);
+        }
+    }
+    res
+}
+
+/// Find a fixture in the `conftest.py` files pytest would load for this module: the one in the
+/// same package, then those in each parent package.
+fn conftest_fixture(builder: &BindingsBuilder, name: &Name) -> Option<ProvidedParameter> {
+    let this = builder.module_info.name();
+    let mut package = this.parent();
+    loop {
+        let module = match package {
+            Some(package) => package.append(&CONFTEST),
+            None => ModuleName::from_name(&CONFTEST),
+        };
+        if module != this
+            && let Some(exports) = builder.lookup.get(module).finding()
+            && exports.exports(builder.lookup).contains_key(name)
+        {
+            return Some(ProvidedParameter::ExternalFixture(module, name.clone()));
+        }
+        package = package?.parent();
+    }
+}
+
+pub struct Pytest;
+
+impl ParameterProvider for Pytest {
+    /// Is this a function pytest calls, i.e. a fixture, or a test collected with pytest's default
+    /// settings: a top-level `test*` function, or a `test*` method of a top-level `Test*` class.
+    fn calls_function(
+        &self,
+        builder: &BindingsBuilder,
+        def: &StmtFunctionDef,
+        parent: &NestingContext,
+    ) -> bool {
+        if builder.pytest_fixtures.is_none() {
+            return false;
+        }
+        if def
+            .decorator_list
+            .iter()
+            .any(|x| is_fixture_decorator(&x.expression))
+        {
+            return true;
+        }
+        def.name.starts_with("test")
+            && (parent.is_toplevel()
+                || (parent.is_class()
+                    && parent.parent().is_some_and(|x| x.is_toplevel())
+                    && parent.identifier().is_some_and(|x| {
+                        builder.module_info.code_at(x.range()).starts_with("Test")
+                    })))
+    }
+
+    /// pytest passes the values of a `@pytest.mark.parametrize` argument, or else the fixture
+    /// with the same name as the parameter.
+    fn parameter(
+        &self,
+        builder: &mut BindingsBuilder,
+        def: &StmtFunctionDef,
+        parent: &NestingContext,
+        param: &ParameterWithDefault,
+    ) -> Option<ProvidedParameter> {
+        if param.default.is_some() || !self.calls_function(builder, def, parent) {
+            return None;
+        }
+        let name = &param.parameter.name.id;
+        if let Some(values) = parametrize_arguments(&def.decorator_list).shift_remove(name) {
+            Some(ProvidedParameter::Values(values))
+        } else if let Some(fixture) = builder
+            .pytest_fixtures
+            .as_ref()
+            .and_then(|x| x.get(name))
+            .cloned()
+        {
+            Some(ProvidedParameter::Fixture(builder.idx_for_promise(
+                Key::ReturnType(ShortIdentifier::new(&fixture)),
+            )))
+        } else {
+            conftest_fixture(builder, name)
+        }
+    }
+}
@@ -0,0 +1,273 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Syntactic checks for names that are redefined in the same scope before the earlier definition
+//! is ever used.
+
+use pyrefly_python::ast::Ast;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Decorator;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
+use vec1::vec1;
+
+use crate::binding::bindings::BindingsBuilder;
+use crate::config::error_kind::ErrorKind;
+use crate::error::context::ErrorInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefinitionKind {
+    Function,
+    Class,
+    Variable,
+}
+
+impl DefinitionKind {
+    fn describe(self) -> &'static str {
+        match self {
+            DefinitionKind::Function => "function",
+            DefinitionKind::Class => "class",
+            DefinitionKind::Variable => "variable",
+        }
+    }
+}
+
+/// A definition of a name directly in a scope, i.e. not inside a nested function or class.
+struct Definition {
+    kind: DefinitionKind,
+    /// The range of the defined name.
+    range: TextRange,
+    /// The range of the whole defining statement, which may itself use the earlier definition,
+    /// e.g. `f = decorate(f)`.
+    stmt_range: TextRange,
+    /// The branches of the enclosing compound statements that the definition is in, as the range
+    /// of the statement and the index of the branch. Empty if the definition is unconditional.
+    branches: Vec<(TextRange, usize)>,
+    /// Whether this is an overload, or a function that is decorated by an attribute of the name it
+    /// redefines, e.g. `@x.setter`. Either way, redefining the name is intended.
+    intentional: bool,
+}
+
+impl Definition {
+    fn is_conditional(&self) -> bool {
+        !self.branches.is_empty()
+    }
+
+    /// Whether at most one of `self` and `other` can run, because they are in different branches
+    /// of the same `if`, `try` or `match` statement.
+    fn is_exclusive(&self, other: &Self) -> bool {
+        self.branches
+            .iter()
+            .zip(&other.branches)
+            .find(|(x, y)| x != y)
+            .is_some_and(|((x, _), (y, _))| x == y)
+    }
+}
+
+fn is_overload(decorator: &Decorator) -> bool {
+    match &decorator.expression {
+        Expr::Name(x) => x.id == "overload",
+        Expr::Attribute(x) => x.attr.id == "overload",
+        _ => false,
+    }
+}
+
+/// A decorator like `@name.setter` or `@name.register`, which needs the earlier definition.
+fn is_decorated_by(decorator: &Decorator, name: &Name) -> bool {
+    let mut x = &decorator.expression;
+    if let Expr::Call(call) = x {
+        x = &call.func;
+    }
+    matches!(x, Expr::Attribute(x) if x.value.as_name_expr().is_some_and(|x| &x.id == name))
+}
+
+struct Collector<'a> {
+    definitions: SmallMap<&'a Name, Vec<Definition>>,
+    branches: Vec<(TextRange, usize)>,
+    /// The range of the statement being visited.
+    stmt_range: TextRange,
+}
+
+impl<'a> Collector<'a> {
+    fn add(&mut self, name: &'a Name, kind: DefinitionKind, range: TextRange, intentional: bool) {
+        self.definitions.entry(name).or_default().push(Definition {
+            kind,
+            range,
+            stmt_range: self.stmt_range,
+            branches: self.branches.clone(),
+            intentional,
+        });
+    }
+
+    fn branch(&mut self, owner: TextRange, index: usize, body: &'a [Stmt]) {
+        self.branches.push((owner, index));
+        self.stmts(body);
+        self.branches.pop();
+    }
+
+    fn stmts(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        self.stmt_range = stmt.range();
+        match stmt {
+            Stmt::FunctionDef(x) => {
+                let intentional = x
+                    .decorator_list
+                    .iter()
+                    .any(|d| is_overload(d) || is_decorated_by(d, &x.name.id));
+                self.add(
+                    &x.name.id,
+                    DefinitionKind::Function,
+                    x.name.range,
+                    intentional,
+                );
+            }
+            Stmt::ClassDef(x) => {
+                self.add(&x.name.id, DefinitionKind::Class, x.name.range, false);
+            }
+            Stmt::Assign(x) => {
+                for target in &x.targets {
+                    Ast::expr_lvalue(target, &mut |name| {
+                        self.add(&name.id, DefinitionKind::Variable, name.range, false)
+                    });
+                }
+            }
+            Stmt::AnnAssign(x) if x.value.is_some() => {
+                if let Expr::Name(name) = &*x.target {
+                    self.add(&name.id, DefinitionKind::Variable, name.range, false);
+                }
+            }
+            Stmt::If(x) => {
+                self.branch(x.range, 0, &x.body);
+                for (i, clause) in x.elif_else_clauses.iter().enumerate() {
+                    self.branch(x.range, i + 1, &clause.body);
+                }
+            }
+            Stmt::Try(x) => {
+                // The `else` clause only runs after the body, so it shares the body's branch.
+                self.branch(x.range, 0, &x.body);
+                for (i, handler) in x.handlers.iter().enumerate() {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    self.branch(x.range, i + 1, &handler.body);
+                }
+                self.branch(x.range, 0, &x.orelse);
+                self.stmts(&x.finalbody);
+            }
+            Stmt::Match(x) => {
+                for (i, case) in x.cases.iter().enumerate() {
+                    self.branch(x.range, i, &case.body);
+                }
+            }
+            Stmt::For(x) => {
+                self.branch(x.range, 0, &x.body);
+                self.branch(x.range, 0, &x.orelse);
+            }
+            Stmt::While(x) => {
+                self.branch(x.range, 0, &x.body);
+                self.branch(x.range, 0, &x.orelse);
+            }
+            Stmt::With(x) => self.stmts(&x.body),
+            _ => {}
+        }
+    }
+}
+
+/// The positions of every read of `name` in `body`, including from nested scopes.
+fn uses(body: &[Stmt]) -> SmallMap<Name, Vec<TextSize>> {
+    fn f(x: &Expr, res: &mut SmallMap<Name, Vec<TextSize>>) {
+        if let Expr::Name(x) = x
+            && x.ctx != ExprContext::Store
+        {
+            res.entry(x.id.clone()).or_default().push(x.range.start());
+        }
+        x.recurse(&mut |x| f(x, res));
+    }
+    let mut res = SmallMap::new();
+    for stmt in body {
+        stmt.visit(&mut |x| f(x, &mut res));
+    }
+    res
+}
+
+impl<'a> BindingsBuilder<'a> {
+    /// Report functions and classes that are redefined in the same block, with an incompatible kind
+    /// of definition or as a second function or class, before they are ever used. Redefinitions
+    /// where either definition is conditional are reported separately, so they can be allowed.
+    pub fn check_redefinitions(&self, body: &[Stmt]) {
+        let mut collector = Collector {
+            definitions: SmallMap::new(),
+            branches: Vec::new(),
+            stmt_range: TextRange::default(),
+        };
+        collector.stmts(body);
+        let uses = uses(body);
+        for (name, definitions) in collector.definitions {
+            for (previous, definition) in definitions.iter().zip(definitions.iter().skip(1)) {
+                if previous.kind == DefinitionKind::Variable
+                    || previous.intentional
+                    || definition.intentional
+                {
+                    continue;
+                }
+                let msg = if previous.is_exclusive(definition) {
+                    if previous.kind == definition.kind {
+                        continue;
+                    }
+                    format!(
+                        "`{name}` is defined as a {} here, but as a {} in another branch",
+                        definition.kind.describe(),
+                        previous.kind.describe()
+                    )
+                } else {
+                    let used = uses.get(name).is_some_and(|uses| {
+                        uses.iter().any(|pos| {
+                            *pos > previous.stmt_range.end() && *pos < definition.stmt_range.end()
+                        })
+                    });
+                    if used {
+                        continue;
+                    }
+                    format!(
+                        "`{name}` is redefined as a {} before the {} it replaces is used",
+                        definition.kind.describe(),
+                        previous.kind.describe()
+                    )
+                };
+                let kind = if previous.is_conditional() || definition.is_conditional() {
+                    ErrorKind::ConditionalRedefinition
+                } else {
+                    ErrorKind::Redefinition
+                };
+                let line = self
+                    .module_info
+                    .display_range(previous.range)
+                    .start
+                    .line_within_cell()
+                    .get();
+                self.error_multiline(
+                    definition.range,
+                    ErrorInfo::Kind(kind),
+                    vec1![
+                        msg,
+                        format!("The previous definition of `{name}` is on line {line}"),
+                    ],
+                );
+            }
+        }
+    }
+}
@@ -25,6 +25,7 @@ use ruff_python_ast::ExprName;
 use ruff_python_ast::ExprYield;
 use ruff_python_ast::ExprYieldFrom;
 use ruff_python_ast::Identifier;
+use ruff_python_ast::Parameters;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::StmtReturn;
 use ruff_python_ast::name::Name;
@@ -1000,6 +1001,13 @@ impl ScopeTreeNode {
     }
 }
 
+/// What a local variable hides from the code in its scope.
+#[derive(Debug, Clone, Copy)]
+pub enum Shadowed {
+    Builtin,
+    Enclosing,
+}
+
 /// Scopes keep track of the current stack of the scopes we are in.
 #[derive(Clone, Debug)]
 pub struct Scopes {
@@ -1393,6 +1401,52 @@ impl Scopes {
         )
     }
 
+    /// The local variables of the current scope that hide a builtin or a name defined in an
+    /// enclosing scope. Parameters are skipped, as are names declared `global` or `nonlocal`,
+    /// which refer to the enclosing definition rather than hiding it.
+    pub fn shadowed_locals(
+        &self,
+        parameters: &Parameters,
+        is_builtin: &dyn Fn(&Name) -> bool,
+    ) -> Vec<(&Name, TextRange, Shadowed)> {
+        let mut res = Vec::new();
+        for (name, info) in &self.current().stat.0 {
+            if !matches!(
+                info.style,
+                StaticStyle::SingleDef(_) | StaticStyle::Anywhere(_)
+            ) || parameters.iter().any(|x| &x.name().id == name)
+            {
+                continue;
+            }
+            // Class bodies and annotation scopes are not visible from a nested function.
+            let outer = self
+                .iter_rev()
+                .skip(1)
+                .filter(|scope| {
+                    matches!(
+                        scope.kind,
+                        ScopeKind::Function(_) | ScopeKind::Method(_) | ScopeKind::Module
+                    )
+                })
+                .find_map(|scope| Some((scope, scope.stat.0.get(name)?)));
+            let Some((scope, outer)) = outer else {
+                continue;
+            };
+            match outer.style {
+                StaticStyle::ImplicitGlobal | StaticStyle::PossibleLegacyTParam => {}
+                // Builtins are injected into the module scope by an implicit wildcard import. A
+                // name the module also binds itself is no longer a `MergeableImport`.
+                StaticStyle::MergeableImport
+                    if matches!(scope.kind, ScopeKind::Module) && is_builtin(name) =>
+                {
+                    res.push((name, info.range, Shadowed::Builtin));
+                }
+                _ => res.push((name, info.range, Shadowed::Enclosing)),
+            }
+        }
+        res
+    }
+
     /// Add an intercepted possible legacy TParam - this is a name that's part
     /// of the scope, but only for static type lookups, and might potentially
     /// intercept the raw runtime value of a pre-PEP-695 legacy type variable
@@ -2243,6 +2297,84 @@ impl<'a> BindingsBuilder<'a> {
         }
     }
 
+    /// Report the local variables of the current function that hide a builtin or a name from an
+    /// enclosing scope.
+    pub fn check_shadowed_locals(&self, parameters: &Parameters) {
+        let builtins = [ModuleName::builtins(), ModuleName::extra_builtins()]
+            .into_iter()
+            .filter_map(|module| Some(self.lookup.get(module).finding()?.wildcard(self.lookup)))
+            .collect::<Vec<_>>();
+        let is_builtin = |name: &Name| builtins.iter().any(|x| x.contains(name));
+        for (name, range, shadowed) in self.scopes.shadowed_locals(parameters, &is_builtin) {
+            let msg = match shadowed {
+                Shadowed::Builtin => format!("`{name}` shadows a builtin"),
+                Shadowed::Enclosing => format!("`{name}` shadows a name from an enclosing scope"),
+            };
+            self.error(range, ErrorInfo::Kind(ErrorKind::ShadowedName), msg);
+        }
+    }
+
+    /// Report assignments that replace a name imported earlier in the same block. Only the
+    /// statements directly in `body` are considered, so that fallbacks such as assigning `None`
+    /// in an `except ImportError:` handler are allowed.
+    pub fn check_shadowed_imports(&self, body: &[Stmt]) {
+        let mut imported = HashSet::new();
+        for stmt in body {
+            let target = match stmt {
+                Stmt::Import(x) => {
+                    imported.extend(x.names.iter().map(|alias| match &alias.asname {
+                        Some(asname) => asname.id.clone(),
+                        None => ModuleName::from_name(&alias.name.id).first_component(),
+                    }));
+                    continue;
+                }
+                Stmt::ImportFrom(x) => {
+                    imported.extend(
+                        x.names
+                            .iter()
+                            .filter(|alias| alias.name.id != "*")
+                            .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name).id.clone()),
+                    );
+                    continue;
+                }
+                Stmt::Assign(x) => Either::Left(x.targets.iter()),
+                Stmt::AnnAssign(x) if x.value.is_some() => {
+                    Either::Right(std::iter::once(&*x.target))
+                }
+                _ => continue,
+            };
+            for target in target {
+                Ast::expr_lvalue(target, &mut |name| {
+                    if imported.remove(&name.id) {
+                        self.error(
+                            name.range,
+                            ErrorInfo::Kind(ErrorKind::ShadowedName),
+                            format!("`{}` shadows an import of the same name", name.id),
+                        );
+                    }
+                });
+            }
+        }
+    }
+
+    /// Report `type` statements that redeclare an alias defined by an earlier `type` statement in
+    /// the same block.
+    pub fn check_redeclared_type_aliases(&self, body: &[Stmt]) {
+        let mut declared = HashSet::new();
+        for stmt in body {
+            if let Stmt::TypeAlias(x) = stmt
+                && let Expr::Name(name) = &*x.name
+                && !declared.insert(&name.id)
+            {
+                self.error(
+                    name.range,
+                    ErrorInfo::Kind(ErrorKind::InvalidTypeAlias),
+                    format!("Type alias `{}` is already declared", name.id),
+                );
+            }
+        }
+    }
+
     pub fn add_loop_exitpoint(&mut self, exit: LoopExit, range: TextRange) {
         let in_loop = self.scopes.add_loop_exit(exit);
         if !in_loop {
@@ -40,6 +40,7 @@ use crate::binding::expr::Usage;
 use crate::binding::narrow::NarrowOps;
 use crate::binding::scope::FlowStyle;
 use crate::binding::scope::LoopExit;
+use crate::config::base::ReexportMode;
 use crate::config::error_kind::ErrorKind;
 use crate::error::context::ErrorInfo;
 use crate::export::definitions::MutableCaptureKind;
@@ -673,6 +674,9 @@ impl<'a> BindingsBuilder<'a> {
                 // x is bound to Narrow(x, Is(None)) in the if branch, and the negation, Narrow(x, IsNot(None)),
                 // is carried over to the else branch.
                 let mut negated_prev_ops = NarrowOps::new();
+                // Set once a branch is known to be taken because of a constant imported from
+                // another module, after which the remaining branches are unreachable.
+                let mut constant_branch_chosen = false;
                 for (range, mut test, body) in Ast::if_branches_owned(x) {
                     self.start_branch();
                     self.bind_narrow_ops(&negated_prev_ops, range, &Usage::Narrowing(None));
@@ -681,6 +685,10 @@ impl<'a> BindingsBuilder<'a> {
                         None => Some(true),
                         Some(x) => self.sys_info.evaluate_bool(x),
                     };
+                    let constant_chosen = match &test {
+                        Some(x) if this_branch_chosen.is_none() => self.evaluate_constant_bool(x),
+                        _ => None,
+                    };
                     self.ensure_expr_opt(test.as_mut(), &mut Usage::Narrowing(None));
                     let new_narrow_ops = if this_branch_chosen == Some(false) {
                         // Skip the body in this case - it typically means a check (e.g. a sys version,
@@ -699,12 +707,22 @@ impl<'a> BindingsBuilder<'a> {
                     }
                     self.bind_narrow_ops(&new_narrow_ops, range, &Usage::Narrowing(None));
                     negated_prev_ops.and_all(new_narrow_ops.negate());
+                    if constant_branch_chosen || constant_chosen == Some(false) {
+                        // Unlike the checks above, we can't skip the body: the definitions of this
+                        // module were collected without knowing the values of imported constants.
+                        // Instead we analyze it as unreachable, so it doesn't flow into the code below.
+                        self.scopes.mark_flow_termination();
+                    }
                     self.stmts(body, parent);
                     self.finish_branch();
                     if this_branch_chosen == Some(true) {
                         exhaustive = true;
                         break; // We definitely picked this branch if we got here, nothing below is reachable.
                     }
+                    if constant_chosen == Some(true) {
+                        exhaustive = true;
+                        constant_branch_chosen = true;
+                    }
                 }
                 if exhaustive {
                     self.finish_exhaustive_fork();
@@ -844,6 +862,7 @@ impl<'a> BindingsBuilder<'a> {
                     if let Some(error) = self.lookup.get(m).error() {
                         self.find_error(&error, x.range);
                     }
+                    self.check_import_boundary(m, x.name.range);
                     match x.asname {
                         Some(asname) => {
                             self.bind_definition(
@@ -870,6 +889,7 @@ impl<'a> BindingsBuilder<'a> {
                     x.level,
                     x.module.as_ref().map(|x| &x.id),
                 ) {
+                    self.check_import_boundary(m, x.module.as_ref().map_or(x.range, |x| x.range));
                     match self.lookup.get(m) {
                         FindingOrError::Finding(module_exports) => {
                             if let Some(error) = module_exports.error {
@@ -969,10 +989,10 @@ impl<'a> BindingsBuilder<'a> {
                         exported.get_hashed(name)
                         && *is_deprecated
                     {
-                        self.error(
+                        self.error_deprecated(
                             x.range,
-                            ErrorInfo::Kind(ErrorKind::Deprecated),
                             format!("`{name}` is deprecated"),
+                            format!("{m}.{name}"),
                         );
                     }
                     let val = if exported.contains_key_hashed(name) {
@@ -1012,10 +1032,19 @@ impl<'a> BindingsBuilder<'a> {
                         exported.get(&x.name.id)
                         && *is_deprecated
                     {
-                        self.error(
+                        self.error_deprecated(
                             x.range,
-                            ErrorInfo::Kind(ErrorKind::Deprecated),
                             format!("`{}` is deprecated", x.name),
+                            format!("{m}.{}", x.name),
+                        );
+                    }
+                    if self.reexport == ReexportMode::Explicit
+                        && module_exports.is_implicit_reexport(m, &x.name.id, self.lookup)
+                    {
+                        self.error(
+                            x.range,
+                            ErrorInfo::Kind(ErrorKind::ImplicitReexport),
+                            format!("`{}` is imported into `{m}` but not re-exported", x.name),
                         );
                     }
                     Binding::Import(m, x.name.id.clone(), original_name_range)
@@ -1034,6 +1063,7 @@ impl<'a> BindingsBuilder<'a> {
                         );
                     }
                     if finding {
+                        self.check_import_boundary(x_as_module_name, x.range);
                         Binding::Module(x_as_module_name, x_as_module_name.components(), None)
                     } else if error {
                         Binding::Type(Type::any_error())
@@ -0,0 +1,75 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Checks that a first-party `.py` implementation hasn't drifted from the `.pyi` stub next to it,
+//! which is what every other module sees instead of the implementation.
+
+use dupe::Dupe;
+use pyrefly_python::module::TextRangeWithModule;
+use ruff_python_ast::name::Name;
+use ruff_text_size::TextRange;
+use starlark_map::small_map::SmallMap;
+
+use crate::binding::binding::BindingExpect;
+use crate::binding::binding::KeyExpect;
+use crate::binding::binding::KeyExport;
+use crate::binding::bindings::BindingsBuilder;
+use crate::config::error_kind::ErrorKind;
+use crate::error::context::ErrorInfo;
+use crate::export::exports::ExportLocation;
+use crate::graph::index::Idx;
+use crate::module::module_info::ModuleInfo;
+
+impl<'a> BindingsBuilder<'a> {
+    /// Compare the names this module defines, `exported`, with those the stub declares.
+    /// A name the stub declares must be defined here, with a type consistent with the stub, which
+    /// is checked once the types of both are solved. Names the stub re-exports from other modules
+    /// are left alone.
+    pub fn check_consistent_with_stub(
+        &mut self,
+        stub: &ModuleInfo,
+        exported: &SmallMap<Name, ExportLocation>,
+        export_idxs: &SmallMap<Name, Idx<KeyExport>>,
+    ) {
+        let Some(stub_exports) = self.lookup.get(self.module_info.name()).finding() else {
+            return;
+        };
+        for (name, location) in stub_exports.exports(self.lookup).iter() {
+            let ExportLocation::ThisModule(declared) = location else {
+                continue;
+            };
+            match exported.get(name) {
+                Some(ExportLocation::ThisModule(export))
+                    if let Some(&implementation) = export_idxs.get(name) =>
+                {
+                    self.insert_binding(
+                        KeyExpect(export.location),
+                        BindingExpect::ConsistentWithStub {
+                            implementation,
+                            stub: stub.path().dupe(),
+                            name: name.clone(),
+                            range: export.location,
+                        },
+                    );
+                }
+                Some(_) => {}
+                // There is nowhere in this module to put the error, so point at the stub.
+                None => self.error_with_related(
+                    TextRange::default(),
+                    ErrorInfo::Kind(ErrorKind::InconsistentStub),
+                    format!(
+                        "`{name}` is declared in the stub but not defined in the implementation"
+                    ),
+                    vec![(
+                        TextRangeWithModule::new(stub.dupe(), declared.location),
+                        format!("`{name}` is declared here"),
+                    )],
+                ),
+            }
+        }
+    }
+}
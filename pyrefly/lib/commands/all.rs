@@ -11,11 +11,15 @@ use crate::commands::buck_check::BuckCheckArgs;
 use crate::commands::check::FullCheckArgs;
 use crate::commands::check::SnippetCheckArgs;
 use crate::commands::dump_config::DumpConfigArgs;
+use crate::commands::gate::GateArgs;
+use crate::commands::impacted_tests::ImpactedTestsArgs;
 use crate::commands::infer::InferArgs;
 use crate::commands::init::InitArgs;
 use crate::commands::lsp::LspArgs;
+use crate::commands::report::ReportArgs;
 use crate::commands::tsp::TspArgs;
 use crate::commands::util::CommandExitStatus;
+use crate::commands::why::WhyArgs;
 
 /// Subcommands to run Pyrefly with.
 #[deny(clippy::missing_docs_in_private_items)]
@@ -44,6 +48,19 @@ pub enum Command {
     Tsp(TspArgs),
     /// Automatically add type annotations to a file or directory.
     Infer(InferArgs),
+
+    /// Analyze a project and print a report about it
+    Report(ReportArgs),
+
+    /// Check a project against a budget of weighted errors per package, failing if any package
+    /// is over its budget
+    Gate(GateArgs),
+
+    /// Explain how the type at a position was computed
+    Why(WhyArgs),
+
+    /// List the test files affected by the changes since a git revision, following imports
+    ImpactedTests(ImpactedTestsArgs),
 }
 
 impl Command {
@@ -57,6 +74,10 @@ impl Command {
             Command::Init(args) => args.run(),
             Command::Infer(args) => args.run(),
             Command::DumpConfig(args) => args.run(),
+            Command::Report(args) => args.run(),
+            Command::Gate(args) => args.run(),
+            Command::Why(args) => args.run(),
+            Command::ImpactedTests(args) => args.run(),
         }
     }
 }
@@ -44,6 +44,7 @@ use ruff_text_size::Ranged;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
 use tracing::debug;
+use tracing::error;
 use tracing::info;
 
 use crate::commands::files::FilesArgs;
@@ -55,6 +56,7 @@ use crate::error::error::print_error_counts;
 use crate::error::legacy::LegacyErrors;
 use crate::error::summarize::print_error_summary;
 use crate::error::suppress;
+use crate::error::suppression_budget::SuppressionBudget;
 use crate::module::typeshed::stdlib_search_path;
 use crate::report;
 use crate::state::require::Require;
@@ -257,6 +259,15 @@ struct OutputArgs {
     /// When specified, emit a sorted/formatted JSON of the errors to the baseline file
     #[arg(long, requires("baseline"))]
     update_baseline: bool,
+
+    /// Path to a file recording the number of suppression comments allowed in each module.
+    /// Fails if any module has more suppressions than its budget.
+    #[arg(long, value_name = "BUDGET_FILE")]
+    suppression_budget: Option<PathBuf>,
+
+    /// When specified, write the current number of suppression comments in each module to the budget file
+    #[arg(long, requires("suppression_budget"))]
+    update_suppression_budget: bool,
 }
 
 #[derive(Clone, Debug, ValueEnum, Default, PartialEq, Eq)]
@@ -737,6 +748,28 @@ impl CheckArgs {
             )?;
         }
 
+        let mut budget_overruns = 0;
+        if let Some(budget_path) = &self.output.suppression_budget {
+            let current = SuppressionBudget::from_errors(&loads, relative_to.as_path());
+            if self.output.update_suppression_budget {
+                current.write_to_file(budget_path)?;
+            } else {
+                let budget = SuppressionBudget::from_file(budget_path)?;
+                for overrun in budget.overruns(&current) {
+                    error!(
+                        "{} has {} suppression comments, but its budget is {}",
+                        overrun.path, overrun.count, overrun.budget
+                    );
+                    budget_overruns += 1;
+                }
+                if budget.can_tighten(&current) {
+                    info!(
+                        "Some modules are under their suppression budget, pass `--update-suppression-budget` to lower it"
+                    );
+                }
+            }
+        }
+
         if let Some(path) = &self.output.output {
             self.output.output_format.write_errors_to_file(
                 path,
@@ -826,7 +859,16 @@ impl CheckArgs {
             fs_anyhow::write(path, report::trace::trace(transaction))?;
         }
         if self.behavior.suppress_errors {
-            suppress::suppress_errors(errors.shown.clone(), self.behavior.same_line);
+            let generated = loads.generated_code_paths();
+            suppress::suppress_errors(
+                errors
+                    .shown
+                    .iter()
+                    .filter(|e| !generated.contains(e.path()))
+                    .cloned()
+                    .collect(),
+                self.behavior.same_line,
+            );
         }
         if self.behavior.remove_unused_ignores {
             suppress::remove_unused_ignores(&loads, self.behavior.all);
@@ -834,7 +876,7 @@ impl CheckArgs {
         if self.behavior.expectations {
             loads.check_against_expectations()?;
             Ok((CommandExitStatus::Success, errors.shown))
-        } else if shown_errors_count > 0 {
+        } else if shown_errors_count > 0 || budget_overruns > 0 {
             Ok((CommandExitStatus::UserError, errors.shown))
         } else {
             Ok((CommandExitStatus::Success, errors.shown))
@@ -10,6 +10,7 @@ use std::str::FromStr;
 
 use clap::Parser;
 use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::arc_id::ArcId;
 use starlark_map::small_map::SmallMap;
@@ -20,6 +21,7 @@ use crate::commands::files::FilesArgs;
 use crate::commands::util::CommandExitStatus;
 use crate::config::config::ConfigFile;
 use crate::config::config::ConfigSource;
+use crate::module::import_trace::ImportTrace;
 
 #[derive(Debug, Clone)]
 enum MaxFiles {
@@ -74,6 +76,11 @@ pub struct DumpConfigArgs {
     /// config(s) to print. Pass "all" to output all files.
     #[arg(long, default_value_t = MaxFiles::Count(10))]
     max_files: MaxFiles,
+    /// Print a step-by-step report of how this module is resolved by each found config: every
+    /// search location and candidate file, and why each was used or rejected.
+    /// Can be used multiple times.
+    #[arg(long, value_name = "MODULE")]
+    trace_imports: Vec<String>,
     #[command(flatten)]
     args: FullCheckArgs,
 }
@@ -81,7 +88,12 @@ pub struct DumpConfigArgs {
 impl DumpConfigArgs {
     pub fn run(self) -> anyhow::Result<CommandExitStatus> {
         // Pass on just the subset of args we use, the rest are irrelevant
-        dump_config(self.args.files, self.args.config_override, self.max_files)
+        dump_config(
+            self.args.files,
+            self.args.config_override,
+            self.max_files,
+            &self.trace_imports,
+        )
     }
 }
 
@@ -89,6 +101,7 @@ fn dump_config(
     files: FilesArgs,
     config_override: ConfigOverrideArgs,
     max_files: MaxFiles,
+    trace_imports: &[String],
 ) -> anyhow::Result<CommandExitStatus> {
     config_override.validate()?;
     let (files_to_check, config_finder) = files.resolve(config_override)?;
@@ -138,15 +151,21 @@ fn dump_config(
         }
         println!("  Resolving imports from:");
         let origin = if files.len() == 1 {
-            files.first().map(|p| p.as_path())
+            files.first()
         } else {
             None
         };
-        for path_part in config.structured_import_lookup_path(origin) {
+        for path_part in config.structured_import_lookup_path(origin.map(|p| p.as_path())) {
             if !path_part.is_empty() {
                 println!("    {path_part}");
             }
         }
+        for module in trace_imports {
+            let trace = ImportTrace::new(&config, ModuleName::from_str(module), origin);
+            for line in trace.to_string().lines() {
+                println!("  {line}");
+            }
+        }
     }
     Ok(CommandExitStatus::Success)
 }
@@ -39,6 +39,8 @@ pub struct FilesArgs {
     /// check are determined from the closest configuration file.
     /// When supplied, `project_excludes` in any config files loaded for these files to check
     /// are ignored, and we use the default excludes unless overridden with the `--project-excludes` flag.
+    /// The exception is when several directories are supplied and each is the root of a project:
+    /// then every project is checked according to its own configuration, all in a single run.
     files: Vec<String>,
     /// Files to exclude when type checking.
     #[arg(long)]
@@ -213,6 +215,77 @@ fn get_globs_and_config_for_files(
     ))
 }
 
+/// The files of several projects checked together, each with its own includes and excludes.
+struct MultiProjectIncludes(Vec<FilteredGlobs>);
+
+impl Includes for MultiProjectIncludes {
+    fn roots(&self) -> Vec<PathBuf> {
+        self.0.iter().flat_map(|x| x.roots()).collect()
+    }
+
+    fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for x in &self.0 {
+            files.extend(x.files()?);
+        }
+        // Projects may be nested, in which case the outer one finds the files of the inner one too.
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    fn covers(&self, path: &Path) -> bool {
+        self.0.iter().any(|x| x.covers(path))
+    }
+
+    fn errors(&mut self) -> Vec<anyhow::Error> {
+        self.0.iter_mut().flat_map(|x| x.errors()).collect()
+    }
+}
+
+/// Get inputs for checking several projects at once, when every input is a directory at the root
+/// of its own project. Each project is checked with the includes and excludes from its own config,
+/// as if `pyrefly check` had been run in that directory, but they share a single run, so common
+/// dependencies are only checked once. Returns `None` if any input isn't a project root.
+fn get_globs_and_config_for_projects(
+    files: &[String],
+    project_excludes: Option<Globs>,
+    args: ConfigOverrideArgs,
+) -> anyhow::Result<Option<(Box<dyn Includes>, ConfigFinder)>> {
+    let roots = files
+        .iter()
+        .map(|x| PathBuf::from(x).absolutize())
+        .collect::<Vec<_>>();
+    if !roots.iter().all(|x| x.is_dir()) {
+        return Ok(None);
+    }
+    let config_finder = default_config_finder_with_overrides(args, false);
+    let mut configs = Vec::new();
+    for root in &roots {
+        match config_finder.directory(root) {
+            Some(config) if config.source.root() == Some(root.as_path()) => configs.push(config),
+            _ => return Ok(None),
+        }
+    }
+    let mut globs = Vec::new();
+    let mut errors = config_finder.errors();
+    for config in configs {
+        if let ConfigSource::File(path) = &config.source {
+            info!("Checking project configured at `{}`", path.display());
+        }
+        if config.build_system.is_some() {
+            return Err(anyhow::anyhow!(
+                "Cannot run build system in project mode, you must provide files to check"
+            ));
+        }
+        let mut filtered_globs = config.get_filtered_globs(project_excludes.clone());
+        errors.extend(filtered_globs.errors().into_iter().map(ConfigError::warn));
+        globs.push(filtered_globs);
+    }
+    add_config_errors(&config_finder, errors)?;
+    Ok(Some((Box::new(MultiProjectIncludes(globs)), config_finder)))
+}
+
 impl FilesArgs {
     pub fn resolve(
         self,
@@ -225,6 +298,15 @@ impl FilesArgs {
         };
         if self.files.is_empty() {
             get_globs_and_config_for_project(self.config, project_excludes, config_override)
+        } else if self.files.len() > 1
+            && self.config.is_none()
+            && let Some(res) = get_globs_and_config_for_projects(
+                &self.files,
+                project_excludes.clone(),
+                config_override.clone(),
+            )?
+        {
+            Ok(res)
         } else {
             get_globs_and_config_for_files(
                 self.config,
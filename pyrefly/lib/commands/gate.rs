@@ -0,0 +1,69 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use pyrefly_config::args::ConfigOverrideArgs;
+use tracing::error;
+
+use crate::commands::check::CheckArgs;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::config::error_kind::Severity;
+use crate::error::error_budget::ErrorBudget;
+
+/// Arguments for the gate command, which checks a project against a budget of weighted errors
+/// per package.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct GateArgs {
+    /// Which files to check.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Path to a TOML file giving the weight of each error kind and the budget of each package.
+    #[arg(long, value_name = "BUDGET_FILE")]
+    budget: PathBuf,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    args: CheckArgs,
+
+    /// Configuration override options
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+}
+
+impl GateArgs {
+    pub fn run(self) -> anyhow::Result<CommandExitStatus> {
+        self.config_override.validate()?;
+        // Read the budget first, so a bad budget file fails before checking anything.
+        let budget = ErrorBudget::from_file(&self.budget)?;
+        let (files_to_check, config_finder) = self.files.resolve(self.config_override)?;
+        let (_, errors) = self.args.run_once(files_to_check, config_finder)?;
+        let tally = budget.tally(
+            errors
+                .iter()
+                .filter(|e| e.severity() >= Severity::Error)
+                .map(|e| (e.module().name(), e.error_kind())),
+        );
+        for package in &tally {
+            println!("{package}");
+        }
+        let over_budget = tally.iter().filter(|x| x.is_over_budget()).count();
+        if over_budget > 0 {
+            error!(
+                "{over_budget} of {} packages are over their error budget",
+                tally.len()
+            );
+            Ok(CommandExitStatus::UserError)
+        } else {
+            Ok(CommandExitStatus::Success)
+        }
+    }
+}
@@ -0,0 +1,359 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context as _;
+use clap::Parser;
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_util::absolutize::Absolutize as _;
+use pyrefly_util::forgetter::Forgetter;
+use pyrefly_util::globs::Globs;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+
+use crate::commands::check::Handles;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::state::require::Require;
+use crate::state::state::State;
+use crate::state::state::Transaction;
+
+/// Arguments for the impacted-tests command, which lists the test files that import, directly or
+/// not, a file changed since a git revision.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct ImpactedTestsArgs {
+    /// Which files make up the project whose imports are followed.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// The git revision to compare against, such as `origin/main`. Every file that differs
+    /// between it and the working tree is part of the change.
+    #[arg(long, value_name = "REV")]
+    diff: String,
+
+    /// Glob patterns, relative to the current directory, matching the test files to list.
+    #[arg(long, value_name = "GLOB", required = true)]
+    test_glob: Vec<String>,
+
+    /// Configuration override options
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+}
+
+/// Run `git` with `args`, returning what it prints.
+fn git(args: &[&str]) -> anyhow::Result<String> {
+    let command = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run `{command}`"))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Failed to run `{command}`, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// The files that differ from a revision, split into those that exist in the working tree and
+/// those that were deleted or renamed away, all as absolute paths.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Changes {
+    changed: HashSet<PathBuf>,
+    removed: HashSet<PathBuf>,
+}
+
+impl Changes {
+    /// Parse the output of `git diff --name-status`, whose paths are relative to the root of the
+    /// repository. A rename or copy lists the old path before the new one.
+    fn from_name_status(root: &Path, output: &str) -> Self {
+        let mut res = Self::default();
+        for line in output.lines() {
+            let mut parts = line.split('\t');
+            let (Some(status), Some(path)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let path = root.join(path);
+            match (status.chars().next(), parts.next()) {
+                (Some('D'), _) => {
+                    res.removed.insert(path);
+                }
+                (Some('R'), Some(new)) => {
+                    res.removed.insert(path);
+                    res.changed.insert(root.join(new));
+                }
+                (Some('C'), Some(new)) => {
+                    res.changed.insert(root.join(new));
+                }
+                _ => {
+                    res.changed.insert(path);
+                }
+            }
+        }
+        res
+    }
+
+    /// Add the files listed by `git ls-files --others --full-name`, which are relative to the
+    /// root of the repository.
+    fn add_untracked(&mut self, root: &Path, output: &str) {
+        self.changed.extend(
+            output
+                .lines()
+                .filter(|x| !x.is_empty())
+                .map(|x| root.join(x)),
+        );
+    }
+}
+
+/// Whether `name`, which an import statement refers to, is the module `removed` or inside it.
+fn refers_to(name: ModuleName, removed: ModuleName) -> bool {
+    name == removed
+        || name
+            .as_str()
+            .strip_prefix(removed.as_str())
+            .is_some_and(|x| x.starts_with('.'))
+}
+
+/// Whether the module of `handle`, whose code is `ast`, imports one of the `removed` modules.
+fn imports_removed(handle: &Handle, ast: &ModModule, removed: &[ModuleName]) -> bool {
+    fn go(x: &Stmt, handle: &Handle, removed: &[ModuleName], found: &mut bool) {
+        let names = match x {
+            Stmt::Import(x) => x
+                .names
+                .iter()
+                .map(|x| ModuleName::from_name(&x.name.id))
+                .collect(),
+            Stmt::ImportFrom(x) => {
+                let Some(base) = handle.module().new_maybe_relative(
+                    handle.path().is_init(),
+                    x.level,
+                    x.module.as_ref().map(|x| &x.id),
+                ) else {
+                    return;
+                };
+                // `from a import b` may import the module `a.b`.
+                let mut names = x
+                    .names
+                    .iter()
+                    .map(|x| base.append(&x.name.id))
+                    .collect::<Vec<_>>();
+                names.push(base);
+                names
+            }
+            _ => Vec::new(),
+        };
+        if names
+            .into_iter()
+            .any(|name| removed.iter().any(|removed| refers_to(name, *removed)))
+        {
+            *found = true;
+        }
+        x.recurse(&mut |x| go(x, handle, removed, found));
+    }
+    let mut found = false;
+    for x in &ast.body {
+        go(x, handle, removed, &mut found);
+    }
+    found
+}
+
+/// The modules among `handles` affected by a change: the `changed` modules, the importers of the
+/// `removed` modules, which no longer exist, and everything depending on those, directly or not.
+fn impacted(
+    transaction: &Transaction,
+    handles: &[Handle],
+    changed: &[Handle],
+    removed: &[ModuleName],
+) -> HashSet<Handle> {
+    let mut roots = changed.to_vec();
+    if !removed.is_empty() {
+        roots.extend(
+            handles
+                .iter()
+                .filter(|x| {
+                    transaction.get_module_info(x).is_some_and(|info| {
+                        imports_removed(x, &Ast::parse(info.contents()).0, removed)
+                    })
+                })
+                .cloned(),
+        );
+    }
+    roots
+        .into_iter()
+        .flat_map(|x| transaction.get_transitive_rdeps(x))
+        .collect()
+}
+
+impl ImpactedTestsArgs {
+    pub fn run(self) -> anyhow::Result<CommandExitStatus> {
+        self.config_override.validate()?;
+        let test_globs = Globs::new_with_root(&PathBuf::new().absolutize(), self.test_glob)?;
+        let root = git(&["rev-parse", "--show-toplevel"])?;
+        let root = Path::new(root.trim_end());
+        let mut changes = Changes::from_name_status(
+            root,
+            &git(&["diff", "--name-status", "-M", &self.diff, "--"])?,
+        );
+        changes.add_untracked(
+            root,
+            &git(&[
+                "ls-files",
+                "--others",
+                "--exclude-standard",
+                "--full-name",
+                ":/",
+            ])?,
+        );
+
+        let (files_to_check, config_finder) = self.files.resolve(self.config_override)?;
+        let expanded_file_list = config_finder.checkpoint(files_to_check.files())?;
+        let state = State::new(config_finder);
+        let holder = Forgetter::new(state, false);
+        let mut forgetter =
+            Forgetter::new(holder.as_ref().new_transaction(Require::Errors, None), true);
+        let transaction = forgetter.as_mut();
+        let (handles, _, sourcedb_errors) =
+            Handles::new(expanded_file_list).all(holder.as_ref().config_finder());
+        if !sourcedb_errors.is_empty() {
+            for error in sourcedb_errors {
+                error.print();
+            }
+            return Err(anyhow::anyhow!("Failed to query sourcedb."));
+        }
+        // The reverse dependencies of a module are only known once the modules importing it
+        // have been solved, so we solve the whole project.
+        transaction.run(&handles, Require::Errors);
+
+        let changed = handles
+            .iter()
+            .filter(|x| changes.changed.contains(&x.path().as_path().absolutize()))
+            .cloned()
+            .collect::<Vec<_>>();
+        // The modules that were removed, named as they would be if they still existed.
+        let removed = changes
+            .removed
+            .iter()
+            .map(|path| {
+                let path = ModulePath::filesystem(path.clone());
+                holder
+                    .as_ref()
+                    .config_finder()
+                    .python_file(ModuleName::unknown(), &path)
+                    .handle_from_module_path(path)
+                    .module()
+            })
+            .filter(|x| *x != ModuleName::unknown())
+            .collect::<Vec<_>>();
+        let impacted = impacted(transaction, &handles, &changed, &removed)
+            .into_iter()
+            .map(|x| x.path().as_path().absolutize())
+            .filter(|x| test_globs.covers(x))
+            .collect::<BTreeSet<_>>();
+        for path in impacted {
+            println!("{}", path.display());
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::util::mk_multi_file_state;
+
+    #[test]
+    fn test_changes() {
+        let changes = Changes::from_name_status(
+            Path::new("/repo"),
+            "M\tapp/main.py\nA\ttests/test_main.py\nD\tapp/gone.py\nR087\tapp/old.py\tapp/new.py\n",
+        );
+        assert_eq!(
+            changes,
+            Changes {
+                changed: HashSet::from([
+                    PathBuf::from("/repo/app/main.py"),
+                    PathBuf::from("/repo/tests/test_main.py"),
+                    PathBuf::from("/repo/app/new.py"),
+                ]),
+                removed: HashSet::from([
+                    PathBuf::from("/repo/app/gone.py"),
+                    PathBuf::from("/repo/app/old.py"),
+                ]),
+            }
+        );
+        let mut changes = Changes::from_name_status(Path::new("/repo"), "");
+        assert_eq!(changes, Changes::default());
+        changes.add_untracked(Path::new("/repo"), "tests/test_new.py\n");
+        assert_eq!(
+            changes.changed,
+            HashSet::from([PathBuf::from("/repo/tests/test_new.py")])
+        );
+    }
+
+    #[test]
+    fn test_impacted() {
+        let (handles, state) = mk_multi_file_state(
+            &[
+                ("lib", "def f() -> None: ..."),
+                ("app", "from lib import f"),
+                ("test_app", "import app"),
+                ("test_lib", "import lib"),
+                ("test_other", "x = 1"),
+                ("test_gone", "def test() -> None:\n    import pkg.gone"),
+                ("test_from_gone", "from pkg import gone"),
+            ],
+            Require::Errors,
+            false,
+        );
+        let all = handles.values().cloned().collect::<Vec<_>>();
+        let transaction = state.transaction();
+        let impacted_by = |changed: &[&str], removed: &[&str]| {
+            let changed = changed
+                .iter()
+                .map(|x| handles[*x].dupe())
+                .collect::<Vec<_>>();
+            let removed = removed
+                .iter()
+                .map(|x| ModuleName::from_str(x))
+                .collect::<Vec<_>>();
+            impacted(&transaction, &all, &changed, &removed)
+                .into_iter()
+                .map(|x| x.module().to_string())
+                .collect::<BTreeSet<_>>()
+        };
+        assert_eq!(
+            impacted_by(&["lib"], &[]),
+            BTreeSet::from(["app", "lib", "test_app", "test_lib"].map(|x| x.to_owned()))
+        );
+        assert_eq!(
+            impacted_by(&["app"], &[]),
+            BTreeSet::from(["app", "test_app"].map(|x| x.to_owned()))
+        );
+        assert_eq!(
+            impacted_by(&[], &["pkg.gone"]),
+            BTreeSet::from(["test_from_gone", "test_gone"].map(|x| x.to_owned()))
+        );
+        assert_eq!(
+            impacted_by(&[], &["pkg"]),
+            BTreeSet::from(["test_from_gone", "test_gone"].map(|x| x.to_owned()))
+        );
+        assert!(impacted_by(&[], &["elsewhere"]).is_empty());
+    }
+}
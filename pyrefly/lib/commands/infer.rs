@@ -25,6 +25,7 @@ use crate::commands::files::get_project_config_for_current_dir;
 use crate::commands::util::CommandExitStatus;
 use crate::config::error_kind::ErrorKind;
 use crate::state::ide::insert_import_edit_with_forced_import_format;
+use crate::state::ide::top_of_module_position;
 use crate::state::lsp::AnnotationKind;
 use crate::state::lsp::ParameterAnnotation;
 use crate::state::require::Require;
@@ -293,12 +294,14 @@ impl InferArgs {
                                     .search_exports_exact(unknown_name)
                                     .into_iter()
                                     .map(|handle_to_import_from| {
-                                        insert_import_edit_with_forced_import_format(
-                                            &ast,
-                                            handle.dupe(),
-                                            handle_to_import_from.dupe(),
-                                            unknown_name,
-                                            true,
+                                        (
+                                            top_of_module_position(&ast),
+                                            insert_import_edit_with_forced_import_format(
+                                                handle.dupe(),
+                                                handle_to_import_from.dupe(),
+                                                unknown_name,
+                                                true,
+                                            ),
                                         )
                                     })
                                     .collect();
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use clap::Parser;
@@ -14,8 +16,12 @@ use lsp_server::ProtocolError;
 use lsp_types::InitializeParams;
 
 use crate::commands::util::CommandExitStatus;
+use crate::lsp::non_wasm::multiplex;
+use crate::lsp::non_wasm::pipe;
 use crate::lsp::non_wasm::server::capabilities;
 use crate::lsp::non_wasm::server::lsp_loop;
+use crate::state::lsp::AllOffPartial;
+use crate::state::lsp::InlayHintConfig;
 
 /// Pyrefly's indexing strategy for open projects when performing go-to-definition
 /// requests.
@@ -45,6 +51,56 @@ pub struct LspArgs {
     /// Note that indexing files is a performance-intensive task.
     #[arg(long, default_value_t = if cfg!(fbcode_build) {0} else {2000})]
     pub(crate) workspace_indexing_limit: usize,
+    /// Communicate with the client over a TCP socket on this port, instead of stdin and stdout.
+    /// By default, the server listens for the client to connect.
+    #[arg(long)]
+    pub(crate) port: Option<u16>,
+    /// The host to listen on, or to connect to with `--connect`.
+    #[arg(long, default_value = "127.0.0.1", requires = "port")]
+    pub(crate) host: String,
+    /// Connect to a client that is already listening on `--host` and `--port`, rather than
+    /// waiting for the client to connect.
+    #[arg(long, requires = "port")]
+    pub(crate) connect: bool,
+    /// Accept any number of clients on `--port`, rather than just one, which share the analysis
+    /// of the modules while each having their own open documents. The server's capabilities and
+    /// settings are negotiated with the first client to connect, and later clients are given the
+    /// same, so clients should be configured alike.
+    #[arg(long, requires = "port", conflicts_with = "connect")]
+    pub(crate) multi_client: bool,
+    /// Communicate with the client over the Unix domain socket or Windows named pipe at this path,
+    /// which the client must already be listening on. This is the `pipe` transport of VS Code.
+    #[arg(long, conflicts_with = "port")]
+    pub(crate) pipe: Option<PathBuf>,
+    /// Whether to show inlay hints for the inferred types of variables, in workspaces whose
+    /// settings don't configure inlay hints.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub(crate) inlay_hint_variable_types: bool,
+    /// Whether to show inlay hints for the inferred return types of functions, in workspaces
+    /// whose settings don't configure inlay hints.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub(crate) inlay_hint_function_return_types: bool,
+    /// Which call arguments get an inlay hint naming their parameter, in workspaces whose
+    /// settings don't configure inlay hints.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) inlay_hint_call_argument_names: AllOffPartial,
+    /// Truncate inlay hints longer than this many characters, in workspaces whose settings
+    /// don't configure inlay hints.
+    #[arg(long)]
+    pub(crate) inlay_hint_max_length: Option<usize>,
+}
+
+impl LspArgs {
+    /// The inlay hints to show in workspaces whose settings don't configure them.
+    fn inlay_hint_config(&self) -> InlayHintConfig {
+        InlayHintConfig {
+            call_argument_names: self.inlay_hint_call_argument_names,
+            function_return_types: self.inlay_hint_function_return_types,
+            variable_types: self.inlay_hint_variable_types,
+            max_length: self.inlay_hint_max_length,
+            ..Default::default()
+        }
+    }
 }
 
 pub fn run_lsp(
@@ -67,6 +123,7 @@ pub fn run_lsp(
         initialization_params,
         args.indexing_mode,
         args.workspace_indexing_limit,
+        args.inlay_hint_config(),
     )?;
     Ok(())
 }
@@ -98,12 +155,38 @@ impl LspArgs {
         // Note that  we must have our logging only write out to stderr.
         eprintln!("starting generic LSP server");
 
-        // Create the transport. Includes the stdio (stdin and stdout) versions but this could
-        // also be implemented to use sockets or HTTP.
-        let (connection, io_threads) = Connection::stdio();
+        // Create the transport: stdio (stdin and stdout), a Unix domain socket or Windows named
+        // pipe, or a TCP socket.
+        let (connection, join_io_threads): (Connection, Box<dyn FnOnce() -> io::Result<()>>) =
+            match (&self.pipe, self.port) {
+                (Some(pipe), _) => {
+                    eprintln!("connecting to client over pipe {}", pipe.display());
+                    let (connection, io_threads) = pipe::connect(pipe)?;
+                    (connection, Box::new(|| io_threads.join()))
+                }
+                (None, None) => {
+                    let (connection, io_threads) = Connection::stdio();
+                    (connection, Box::new(|| io_threads.join()))
+                }
+                (None, Some(port)) if self.connect => {
+                    eprintln!("connecting to client at {}:{port}", self.host);
+                    let (connection, io_threads) = Connection::connect((self.host.as_str(), port))?;
+                    (connection, Box::new(|| io_threads.join()))
+                }
+                (None, Some(port)) if self.multi_client => {
+                    eprintln!("listening for clients on {}:{port}", self.host);
+                    let (connection, threads) = multiplex::listen((self.host.as_str(), port))?;
+                    (connection, Box::new(|| threads.join()))
+                }
+                (None, Some(port)) => {
+                    eprintln!("listening for client on {}:{port}", self.host);
+                    let (connection, io_threads) = Connection::listen((self.host.as_str(), port))?;
+                    (connection, Box::new(|| io_threads.join()))
+                }
+            };
 
         run_lsp(Arc::new(connection), self, version_string)?;
-        io_threads.join()?;
+        join_io_threads()?;
         // We have shut down gracefully.
         eprintln!("shutting down server");
         Ok(CommandExitStatus::Success)
@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use pyrefly_build::handle::Handle;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_util::forgetter::Forgetter;
+
+use crate::commands::check::Handles;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::config::error_kind::ErrorKind;
+use crate::config::error_kind::Severity;
+use crate::report::any_flows::AnyFlows;
+use crate::report::deprecations::DeprecationUsage;
+use crate::report::deprecations::DeprecationsReport;
+use crate::report::metrics::Metrics;
+use crate::report::owners::CodeOwners;
+use crate::report::owners::OwnersReport;
+use crate::state::require::Require;
+use crate::state::state::State;
+use crate::state::state::Transaction;
+
+/// Arguments for the report command, which analyzes a project without reporting errors.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct ReportArgs {
+    /// The report to produce.
+    #[command(subcommand)]
+    report: Report,
+}
+
+/// The reports Pyrefly can produce.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Subcommand)]
+enum Report {
+    /// Find where `Any` enters a project (untyped imports, missing annotations and casts), and which
+    /// public APIs it reaches, ranked by how far it spreads.
+    AnyFlows(AnyFlowsArgs),
+    /// Attribute the errors in a project to the owners of the files they are in, as given by a
+    /// `CODEOWNERS` file.
+    Owners(OwnersArgs),
+    /// List the remaining usages of deprecated symbols by the owners of the files they are in, as
+    /// given by a `CODEOWNERS` file.
+    Deprecations(DeprecationsArgs),
+    /// Measure the complexity and typing debt of each function: its parameters, how many of them
+    /// are untyped, and how many `Any` expressions and error suppressions its body has.
+    Metrics(MetricsArgs),
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+enum OutputFormat {
+    /// Human readable text output
+    #[default]
+    Text,
+    /// JSON output
+    Json,
+    /// CSV output, which only the `owners` report supports
+    Csv,
+}
+
+/// Arguments for the `any-flows` report.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+struct AnyFlowsArgs {
+    /// Which files to analyze.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    config_override: ConfigOverrideArgs,
+
+    /// The maximum number of sources of `Any` to show, most widespread first.
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+
+    /// The format to print the report in.
+    #[arg(long, value_enum, default_value_t)]
+    output_format: OutputFormat,
+}
+
+/// Arguments for the `owners` report.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+struct OwnersArgs {
+    /// Which files to analyze.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    config_override: ConfigOverrideArgs,
+
+    /// Path to the `CODEOWNERS` file giving the owners of each file.
+    #[arg(long, value_name = "CODEOWNERS_FILE")]
+    codeowners: PathBuf,
+
+    /// The format to print the report in.
+    #[arg(long, value_enum, default_value_t)]
+    output_format: OutputFormat,
+}
+
+/// Arguments for the `deprecations` report.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+struct DeprecationsArgs {
+    /// Which files to analyze.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    config_override: ConfigOverrideArgs,
+
+    /// Path to the `CODEOWNERS` file giving the owners of each file.
+    #[arg(long, value_name = "CODEOWNERS_FILE")]
+    codeowners: PathBuf,
+
+    /// The format to print the report in.
+    #[arg(long, value_enum, default_value_t)]
+    output_format: OutputFormat,
+}
+
+/// Arguments for the `metrics` report.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+struct MetricsArgs {
+    /// Which files to analyze.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    config_override: ConfigOverrideArgs,
+
+    /// The format to print the report in.
+    #[arg(long, value_enum, default_value_t)]
+    output_format: OutputFormat,
+}
+
+impl ReportArgs {
+    pub fn run(self) -> anyhow::Result<CommandExitStatus> {
+        match self.report {
+            Report::AnyFlows(args) => args.run(),
+            Report::Owners(args) => args.run(),
+            Report::Deprecations(args) => args.run(),
+            Report::Metrics(args) => args.run(),
+        }
+    }
+}
+
+/// Check the files given by `files` and `config_override`, and call `f` with the transaction
+/// and the handles of the files.
+fn with_checked_files<R>(
+    files: FilesArgs,
+    config_override: ConfigOverrideArgs,
+    f: impl FnOnce(&Transaction, &[Handle]) -> anyhow::Result<R>,
+) -> anyhow::Result<R> {
+    config_override.validate()?;
+    let (files_to_check, config_finder) = files.resolve(config_override)?;
+    let expanded_file_list = config_finder.checkpoint(files_to_check.files())?;
+    let state = State::new(config_finder);
+    let holder = Forgetter::new(state, false);
+    let mut forgetter = Forgetter::new(
+        holder.as_ref().new_transaction(Require::Everything, None),
+        true,
+    );
+    let transaction = forgetter.as_mut();
+
+    let (handles, _, sourcedb_errors) =
+        Handles::new(expanded_file_list).all(holder.as_ref().config_finder());
+    if !sourcedb_errors.is_empty() {
+        for error in sourcedb_errors {
+            error.print();
+        }
+        return Err(anyhow::anyhow!("Failed to query sourcedb."));
+    }
+    transaction.run(&handles, Require::Everything);
+    f(transaction, &handles)
+}
+
+impl AnyFlowsArgs {
+    fn run(self) -> anyhow::Result<CommandExitStatus> {
+        if matches!(self.output_format, OutputFormat::Csv) {
+            return Err(anyhow::anyhow!(
+                "The `any-flows` report can't be printed as CSV"
+            ));
+        }
+        let mut flows =
+            with_checked_files(self.files, self.config_override, |transaction, handles| {
+                Ok(AnyFlows::new(transaction, handles))
+            })?;
+        flows.sources.truncate(self.limit);
+        match self.output_format {
+            OutputFormat::Text => print!("{flows}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&flows)?),
+            OutputFormat::Csv => unreachable!("rejected above"),
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+impl OwnersArgs {
+    fn run(self) -> anyhow::Result<CommandExitStatus> {
+        // Read the owners first, so a bad file fails before checking anything.
+        let code_owners = CodeOwners::from_file(&self.codeowners)?;
+        let report =
+            with_checked_files(self.files, self.config_override, |transaction, handles| {
+                let errors = transaction.get_errors(handles).collect_errors().shown;
+                Ok(OwnersReport::new(
+                    &code_owners,
+                    errors.iter().map(|e| (e.path().as_path(), e.severity())),
+                ))
+            })?;
+        match self.output_format {
+            OutputFormat::Text => print!("{report}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Csv => print!("{}", report.to_csv()),
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+impl DeprecationsArgs {
+    fn run(self) -> anyhow::Result<CommandExitStatus> {
+        if matches!(self.output_format, OutputFormat::Csv) {
+            return Err(anyhow::anyhow!(
+                "The `deprecations` report can't be printed as CSV"
+            ));
+        }
+        // Read the owners first, so a bad file fails before checking anything.
+        let code_owners = CodeOwners::from_file(&self.codeowners)?;
+        let report =
+            with_checked_files(self.files, self.config_override, |transaction, handles| {
+                let errors = transaction.get_errors(handles).collect_errors().shown;
+                Ok(DeprecationsReport::new(
+                    &code_owners,
+                    errors
+                        .iter()
+                        .filter(|e| e.error_kind() == ErrorKind::Deprecated)
+                        .map(|e| DeprecationUsage {
+                            path: e.path().as_path().to_path_buf(),
+                            line: e.display_range().start.line_within_file().get() as usize,
+                            symbol: e
+                                .deprecated_symbol()
+                                .unwrap_or_else(|| e.msg_header())
+                                .to_owned(),
+                            error: e.severity() == Severity::Error,
+                        }),
+                ))
+            })?;
+        match self.output_format {
+            OutputFormat::Text => print!("{report}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Csv => unreachable!("rejected above"),
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+impl MetricsArgs {
+    fn run(self) -> anyhow::Result<CommandExitStatus> {
+        if matches!(self.output_format, OutputFormat::Csv) {
+            return Err(anyhow::anyhow!(
+                "The `metrics` report can't be printed as CSV"
+            ));
+        }
+        let metrics =
+            with_checked_files(self.files, self.config_override, |transaction, handles| {
+                Ok(Metrics::new(transaction, handles))
+            })?;
+        match self.output_format {
+            OutputFormat::Text => print!("{metrics}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&metrics)?),
+            OutputFormat::Csv => unreachable!("rejected above"),
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
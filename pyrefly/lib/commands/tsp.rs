@@ -16,6 +16,7 @@ use lsp_types::InitializeParams;
 use crate::commands::lsp::IndexingMode;
 use crate::commands::util::CommandExitStatus;
 use crate::lsp::non_wasm::queue::LspQueue;
+use crate::state::lsp::InlayHintConfig;
 use crate::tsp::server::tsp_capabilities;
 use crate::tsp::server::tsp_loop;
 
@@ -48,6 +49,7 @@ pub fn run_tsp(connection: Arc<Connection>, args: TspArgs) -> anyhow::Result<()>
         initialization_params.clone(),
         args.indexing_mode,
         args.workspace_indexing_limit,
+        InlayHintConfig::default(),
     ));
 
     // Reuse the existing lsp_loop but with TSP initialization
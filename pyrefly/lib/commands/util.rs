@@ -5,14 +5,23 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 use clap::ColorChoice;
 use clap::Parser;
 use pyrefly_util::args::clap_env;
+use pyrefly_util::deterministic::enable_deterministic;
+#[cfg(feature = "otlp")]
+use pyrefly_util::otlp::otlp_layer;
 use pyrefly_util::thread_pool::ThreadCount;
 use pyrefly_util::thread_pool::init_thread_pool;
-use pyrefly_util::trace::init_tracing;
+#[cfg(not(feature = "otlp"))]
+use pyrefly_util::trace::TracingLayer;
+use pyrefly_util::trace::init_tracing_with_export;
+use tracing::warn;
+
+use crate::state::crash_report::enable_crash_reports;
 
 /// Arguments shared between all commands.
 #[deny(clippy::missing_docs_in_private_items)]
@@ -31,6 +40,36 @@ pub struct CommonGlobalArgs {
     /// Enable verbose logging.
     #[arg(long = "verbose", short = 'v', global = true, env = clap_env("VERBOSE"))]
     verbose: bool,
+
+    /// If the checker crashes, write a crash report into this directory, containing the file being
+    /// checked, the configuration and version information, to attach to a bug report.
+    #[arg(long, global = true, env = clap_env("CRASH_REPORT"))]
+    crash_report: Option<PathBuf>,
+
+    /// Remove comments, the contents of strings and the names defined by the file being checked
+    /// from crash reports.
+    #[arg(long, global = true, requires = "crash_report", env = clap_env("CRASH_REPORT_ANONYMIZE"))]
+    crash_report_anonymize: bool,
+
+    /// Run deterministically, to reproduce bugs that only show up on some runs: use a single
+    /// thread, process work one item at a time, and use fixed hash seeds so that everything is
+    /// iterated in the same order on every run. This is much slower.
+    #[arg(long, global = true, env = clap_env("DETERMINISTIC"))]
+    deterministic: bool,
+
+    /// Export spans of LSP requests and of the steps of checking each module to this OTLP/HTTP
+    /// endpoint, e.g. `http://localhost:4318/v1/traces` for a local Jaeger, to find out what a
+    /// slow request was waiting on. Requires Pyrefly to be built with the `otlp` feature.
+    #[arg(long, global = true, env = clap_env("TRACE_ENDPOINT"))]
+    trace_endpoint: Option<String>,
+}
+
+/// Without the `otlp` feature, Pyrefly can't export traces.
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer(endpoint: &str) -> anyhow::Result<TracingLayer> {
+    Err(anyhow::anyhow!(
+        "Cannot export traces to `{endpoint}`: Pyrefly was built without the `otlp` feature"
+    ))
 }
 
 fn init_color(color: ColorChoice) {
@@ -50,10 +89,25 @@ fn init_color(color: ColorChoice) {
 impl CommonGlobalArgs {
     pub fn init(&self, skip_tracing: bool) {
         if !skip_tracing {
-            init_tracing(self.verbose, false);
+            let export = self.trace_endpoint.as_deref().map(otlp_layer);
+            let (export, export_error) = match export {
+                Some(Ok(layer)) => (Some(layer), None),
+                Some(Err(e)) => (None, Some(e)),
+                None => (None, None),
+            };
+            init_tracing_with_export(self.verbose, false, export);
+            if let Some(e) = export_error {
+                warn!("{e:#}");
+            }
+        }
+        if self.deterministic {
+            enable_deterministic();
         }
         init_thread_pool(self.threads);
         init_color(self.color);
+        if let Some(dir) = &self.crash_report {
+            enable_crash_reports(dir.clone(), self.crash_report_anonymize);
+        }
     }
 }
 
@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::num::NonZeroU32;
+
+use anyhow::Context as _;
+use clap::Parser;
+use dupe::Dupe;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_util::forgetter::Forgetter;
+use pyrefly_util::lined_buffer::DisplayPos;
+use pyrefly_util::lined_buffer::LineNumber;
+
+use crate::commands::check::Handles;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::report::why::Why;
+use crate::state::require::Require;
+use crate::state::state::State;
+
+/// Arguments for the why command, which explains how the type at a position was computed.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct WhyArgs {
+    /// The position to explain, as `FILE:LINE:COLUMN`, with the line and column starting at 1.
+    location: String,
+
+    /// Type checking arguments and configuration
+    #[command(flatten)]
+    config_override: ConfigOverrideArgs,
+
+    /// Print the explanation as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Split `FILE:LINE:COLUMN` into its parts. The file may itself contain colons, e.g. a drive letter.
+fn parse_location(location: &str) -> anyhow::Result<(String, LineNumber, NonZeroU32)> {
+    let mut parts = location.rsplitn(3, ':');
+    let (Some(column), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(anyhow::anyhow!(
+            "Expected a location of the form `FILE:LINE:COLUMN`, got `{location}`"
+        ));
+    };
+    let line = line
+        .parse()
+        .ok()
+        .and_then(LineNumber::new)
+        .with_context(|| format!("Invalid line number `{line}`, lines start at 1"))?;
+    let column = column
+        .parse()
+        .with_context(|| format!("Invalid column `{column}`, columns start at 1"))?;
+    Ok((file.to_owned(), line, column))
+}
+
+impl WhyArgs {
+    pub fn run(self) -> anyhow::Result<CommandExitStatus> {
+        self.config_override.validate()?;
+        let (file, line, column) = parse_location(&self.location)?;
+        let (files_to_check, config_finder) =
+            FilesArgs::get(vec![file.clone()], None, self.config_override)?;
+        let expanded_file_list = config_finder.checkpoint(files_to_check.files())?;
+        let state = State::new(config_finder);
+        let holder = Forgetter::new(state, false);
+        let mut forgetter = Forgetter::new(
+            holder.as_ref().new_transaction(Require::Everything, None),
+            true,
+        );
+        let transaction = forgetter.as_mut();
+
+        let (handles, _, sourcedb_errors) =
+            Handles::new(expanded_file_list).all(holder.as_ref().config_finder());
+        if !sourcedb_errors.is_empty() {
+            for error in sourcedb_errors {
+                error.print();
+            }
+            return Err(anyhow::anyhow!("Failed to query sourcedb."));
+        }
+        let Some(handle) = handles.into_iter().next() else {
+            return Err(anyhow::anyhow!("No Python file found at `{file}`"));
+        };
+        transaction.run(&[handle.dupe()], Require::Everything);
+
+        let info = transaction
+            .get_module_info(&handle)
+            .with_context(|| format!("Failed to load `{file}`"))?;
+        let position = info
+            .lined_buffer()
+            .from_display_pos(DisplayPos::Source { line, column });
+        let Some(why) = Why::new(transaction, &handle, position) else {
+            eprintln!("No name with a type found at {}", self.location);
+            return Ok(CommandExitStatus::UserError);
+        };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&why)?);
+        } else {
+            print!("{why}");
+        }
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_location() {
+        let (file, line, column) = parse_location("C:\\src\\main.py:3:7").unwrap();
+        assert_eq!(file, "C:\\src\\main.py");
+        assert_eq!(line.get(), 3);
+        assert_eq!(column.get(), 7);
+        assert!(parse_location("main.py:3").is_err());
+        assert!(parse_location("main.py:0:1").is_err());
+        assert!(parse_location("main.py:1:x").is_err());
+    }
+}
@@ -10,11 +10,13 @@ use std::mem;
 
 use dupe::Dupe;
 use pyrefly_config::error_kind::ErrorKind;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_util::lock::Mutex;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use vec1::Vec1;
 
+use crate::config::base::GeneratedCodePolicy;
 use crate::config::error::ErrorConfig;
 use crate::config::error_kind::Severity;
 use crate::error::context::ErrorInfo;
@@ -121,9 +123,40 @@ impl ErrorCollector {
         }
     }
 
-    pub fn add(&self, range: TextRange, info: ErrorInfo, mut msg: Vec1<String>) {
+    pub fn add(&self, range: TextRange, info: ErrorInfo, msg: Vec1<String>) {
+        self.add_with_related(range, info, msg, Vec::new())
+    }
+
+    /// Like `add`, but also attaches other locations that help explain the error.
+    pub fn add_with_related(
+        &self,
+        range: TextRange,
+        info: ErrorInfo,
+        msg: Vec1<String>,
+        related: Vec<(TextRangeWithModule, String)>,
+    ) {
+        if let Some(err) = self.new_error(range, info, msg) {
+            self.errors.lock().push(err.with_related(related));
+        }
+    }
+
+    /// Add an error about using a deprecated symbol, given by its fully qualified name, so that
+    /// the error can be escalated once the symbol is removed according to the deprecation policy.
+    pub fn add_deprecated(
+        &self,
+        range: TextRange,
+        info: ErrorInfo,
+        msg: Vec1<String>,
+        symbol: String,
+    ) {
+        if let Some(err) = self.new_error(range, info, msg) {
+            self.errors.lock().push(err.with_deprecated_symbol(symbol));
+        }
+    }
+
+    fn new_error(&self, range: TextRange, info: ErrorInfo, mut msg: Vec1<String>) -> Option<Error> {
         if self.style == ErrorStyle::Never {
-            return;
+            return None;
         }
         let (kind, ctx) = match info {
             ErrorInfo::Context(ctx) => {
@@ -135,8 +168,7 @@ impl ErrorCollector {
         if let Some(ctx) = ctx {
             msg.insert(0, ctx.format());
         }
-        let err = Error::new(self.module_info.dupe(), range, msg, kind);
-        self.errors.lock().push(err);
+        Some(Error::new(self.module_info.dupe(), range, msg, kind))
     }
 
     pub fn module(&self) -> &ModuleInfo {
@@ -157,7 +189,10 @@ impl ErrorCollector {
 
     pub fn collect_into(&self, error_config: &ErrorConfig, result: &mut CollectedErrors) {
         let mut errors = self.errors.lock();
-        if !(self.module_info.is_generated() && error_config.ignore_errors_in_generated_code) {
+        let generated = error_config
+            .generated_code
+            .applies_to(self.module_info.contents());
+        if !(generated && error_config.generated_code.policy == GeneratedCodePolicy::Ignore) {
             for err in errors.iter() {
                 if err.is_ignored(error_config.permissive_ignores) {
                     result.suppressed.push(err.clone());
@@ -173,6 +208,21 @@ impl ErrorCollector {
                         (ErrorKind::MissingSource, Severity::Ignore, false) => Severity::Error,
                         _ => raw_severity,
                     };
+                    // Using a symbol after its removal is always an error.
+                    let severity = if let Some(policy) = error_config.deprecation_policy
+                        && let Some(symbol) = err.deprecated_symbol()
+                        && policy.is_removed(symbol)
+                    {
+                        Severity::Error
+                    } else {
+                        severity
+                    };
+                    // Errors in generated code are never more than warnings under the warn policy.
+                    let severity = if generated && severity == Severity::Error {
+                        Severity::Warn
+                    } else {
+                        severity
+                    };
                     match severity {
                         Severity::Error => result.shown.push(err.with_severity(Severity::Error)),
                         Severity::Warn => result.shown.push(err.with_severity(Severity::Warn)),
@@ -206,7 +256,11 @@ mod tests {
     use vec1::vec1;
 
     use super::*;
+    use crate::config::base::GeneratedCodePolicy;
+    use crate::config::deprecation_policy::DeprecationPolicy;
+    use crate::config::deprecation_policy::Sunset;
     use crate::config::error::ErrorDisplayConfig;
+    use crate::config::error::GeneratedCode;
     use crate::config::error_kind::ErrorKind;
     use crate::config::error_kind::Severity;
 
@@ -256,7 +310,7 @@ mod tests {
             errors
                 .collect(&ErrorConfig::new(
                     &ErrorDisplayConfig::default(),
-                    false,
+                    GeneratedCode::default(),
                     false,
                     true,
                 ))
@@ -310,7 +364,7 @@ mod tests {
             (ErrorKind::BadAssignment, Severity::Ignore),
             (ErrorKind::NotIterable, Severity::Ignore),
         ]));
-        let config = ErrorConfig::new(&display_config, false, false, true);
+        let config = ErrorConfig::new(&display_config, GeneratedCode::default(), false, true);
 
         assert_eq!(
             errors.collect(&config).shown.map(|x| x.msg()),
@@ -334,11 +388,118 @@ mod tests {
         );
 
         let display_config = ErrorDisplayConfig::default();
-        let config0 = ErrorConfig::new(&display_config, false, false, true);
-        assert_eq!(errors.collect(&config0).shown.map(|x| x.msg()), vec!["a"]);
+        let markers = [format!("{}{}", "@", "generated")];
+        let generated_code = |policy, marker_lines| GeneratedCode {
+            policy,
+            markers: &markers,
+            marker_lines,
+            generated_file: false,
+        };
+        let collect = |generated_code| {
+            errors
+                .collect(&ErrorConfig::new(
+                    &display_config,
+                    generated_code,
+                    false,
+                    true,
+                ))
+                .shown
+                .map(|x| (x.msg(), x.severity()))
+        };
 
-        let config1 = ErrorConfig::new(&display_config, true, false, true);
-        assert!(errors.collect(&config1).shown.map(|x| x.msg()).is_empty());
+        assert_eq!(
+            collect(generated_code(GeneratedCodePolicy::Check, None)),
+            vec![("a".to_owned(), Severity::Error)]
+        );
+        assert!(collect(generated_code(GeneratedCodePolicy::Ignore, None)).is_empty());
+        assert_eq!(
+            collect(generated_code(GeneratedCodePolicy::Warn, Some(1))),
+            vec![("a".to_owned(), Severity::Warn)]
+        );
+
+        let mi = ModuleInfo::new(
+            ModuleName::from_name(&Name::new_static("main")),
+            ModulePath::filesystem(Path::new("main.py").to_owned()),
+            Arc::new(format!("contents\n# {}{}", "@", "generated")),
+        );
+        let errors = ErrorCollector::new(mi.dupe(), ErrorStyle::Delayed);
+        add(
+            &errors,
+            TextRange::new(TextSize::new(1), TextSize::new(3)),
+            ErrorKind::InternalError,
+            "a".to_owned(),
+        );
+        let config = ErrorConfig::new(
+            &display_config,
+            generated_code(GeneratedCodePolicy::Ignore, Some(1)),
+            false,
+            true,
+        );
+        // The marker is past the first line, so the file isn't treated as generated.
+        assert_eq!(errors.collect(&config).shown.map(|x| x.msg()), vec!["a"]);
+    }
+
+    #[test]
+    fn test_error_collector_deprecation_policy() {
+        let mi = ModuleInfo::new(
+            ModuleName::from_name(&Name::new_static("main")),
+            ModulePath::filesystem(Path::new("main.py").to_owned()),
+            Arc::new("contents".to_owned()),
+        );
+        let errors = ErrorCollector::new(mi.dupe(), ErrorStyle::Delayed);
+        for (start, symbol) in [(1, "lib.removed"), (2, "lib.kept")] {
+            errors.add_deprecated(
+                TextRange::new(TextSize::new(start), TextSize::new(3)),
+                ErrorInfo::Kind(ErrorKind::Deprecated),
+                vec1![format!("`{symbol}` is deprecated")],
+                symbol.to_owned(),
+            );
+        }
+
+        let display_config = ErrorDisplayConfig::default();
+        let policy = DeprecationPolicy {
+            current_version: Some("2.0".to_owned()),
+            symbols: HashMap::from([
+                (
+                    "lib.removed".to_owned(),
+                    Sunset {
+                        removal_date: None,
+                        removal_version: Some("2.0".to_owned()),
+                    },
+                ),
+                (
+                    "lib.kept".to_owned(),
+                    Sunset {
+                        removal_date: None,
+                        removal_version: Some("3.0".to_owned()),
+                    },
+                ),
+            ]),
+        };
+        let collect = |policy| {
+            errors
+                .collect(
+                    &ErrorConfig::new(&display_config, GeneratedCode::default(), false, true)
+                        .with_deprecation_policy(policy),
+                )
+                .shown
+                .map(|x| (x.msg(), x.severity()))
+        };
+
+        assert_eq!(
+            collect(None),
+            vec![
+                ("`lib.removed` is deprecated".to_owned(), Severity::Warn),
+                ("`lib.kept` is deprecated".to_owned(), Severity::Warn),
+            ]
+        );
+        assert_eq!(
+            collect(Some(&policy)),
+            vec![
+                ("`lib.removed` is deprecated".to_owned(), Severity::Error),
+                ("`lib.kept` is deprecated".to_owned(), Severity::Warn),
+            ]
+        );
     }
 
     #[test]
@@ -365,7 +526,7 @@ mod tests {
             errors
                 .collect(&ErrorConfig::new(
                     &ErrorDisplayConfig::default(),
-                    false,
+                    GeneratedCode::default(),
                     false,
                     true,
                 ))
@@ -16,6 +16,7 @@ use lsp_types::CodeDescription;
 use lsp_types::Diagnostic;
 use lsp_types::Url;
 use pyrefly_python::module::Module;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::display::number_thousands;
 use pyrefly_util::lined_buffer::DisplayRange;
@@ -46,6 +47,11 @@ pub struct Error {
     /// The rest of the error message after the first line.
     /// Note that this is formatted for pretty-printing, with two spaces at the beginning and after every newline.
     msg_details: Option<Box<str>>,
+    /// Other locations that help explain the error, each with a short message,
+    /// e.g. the definition of a function that was called with the wrong arguments.
+    related: Vec<(TextRangeWithModule, String)>,
+    /// For a `deprecated` error, the fully qualified name of the deprecated symbol.
+    deprecated_symbol: Option<Box<str>>,
 }
 
 impl Ranged for Error {
@@ -255,9 +261,21 @@ impl Error {
             severity: error_kind.default_severity(),
             msg_header,
             msg_details,
+            related: Vec::new(),
+            deprecated_symbol: None,
         }
     }
 
+    pub fn with_related(mut self, related: Vec<(TextRangeWithModule, String)>) -> Self {
+        self.related = related;
+        self
+    }
+
+    pub fn with_deprecated_symbol(mut self, symbol: String) -> Self {
+        self.deprecated_symbol = Some(symbol.into_boxed_str());
+        self
+    }
+
     pub fn display_range(&self) -> &DisplayRange {
         &self.display_range
     }
@@ -274,6 +292,14 @@ impl Error {
         &self.msg_header
     }
 
+    pub fn related(&self) -> &[(TextRangeWithModule, String)] {
+        &self.related
+    }
+
+    pub fn deprecated_symbol(&self) -> Option<&str> {
+        self.deprecated_symbol.as_deref()
+    }
+
     pub fn msg_details(&self) -> Option<&str> {
         self.msg_details.as_deref()
     }
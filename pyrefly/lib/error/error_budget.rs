@@ -0,0 +1,189 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A committed budget of weighted errors per package, which the `gate` command checks a project
+//! against.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+
+use anyhow::Context as _;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::fs_anyhow;
+use serde::Deserialize;
+
+use crate::config::error_kind::ErrorKind;
+
+/// How much each kind of error counts, and how much the errors in each package may count in
+/// total, read from a TOML file such as:
+///
+/// ```toml
+/// [weights]
+/// bad-return = 3
+/// unknown-name = 0
+///
+/// [budgets]
+/// app = 120
+/// "app.legacy" = 400
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorBudget {
+    /// The weight of each error kind. Kinds that aren't listed have a weight of one.
+    #[serde(default)]
+    weights: BTreeMap<ErrorKind, u64>,
+    /// The budget of each package, keyed by module name.
+    #[serde(default)]
+    budgets: BTreeMap<String, u64>,
+}
+
+/// The weighted count of the errors in a package, against its budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageTally {
+    pub package: String,
+    pub budget: u64,
+    pub count: u64,
+}
+
+impl PackageTally {
+    pub fn is_over_budget(&self) -> bool {
+        self.count > self.budget
+    }
+}
+
+impl Display for PackageTally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let delta = self.count as i128 - self.budget as i128;
+        write!(
+            f,
+            "{}: {} (budget {}, {delta:+})",
+            self.package, self.count, self.budget
+        )
+    }
+}
+
+impl ErrorBudget {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs_anyhow::read_to_string(path)?;
+        toml::from_str(&content)
+            .with_context(|| format!("while parsing error budget `{}`", path.display()))
+    }
+
+    fn weight(&self, kind: ErrorKind) -> u64 {
+        self.weights.get(&kind).copied().unwrap_or(1)
+    }
+
+    /// The package an error in `module` counts towards: the most specific package containing it,
+    /// so an error is only counted once when packages are nested.
+    fn package_of(&self, module: ModuleName) -> Option<&str> {
+        let module = module.as_str();
+        self.budgets
+            .keys()
+            .filter(|package| {
+                module == package.as_str()
+                    || module
+                        .strip_prefix(package.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .max_by_key(|package| package.len())
+            .map(|package| package.as_str())
+    }
+
+    /// The weighted count of the errors, given by module and kind, in every package of the
+    /// budget. Errors outside of all the packages aren't counted.
+    pub fn tally(
+        &self,
+        errors: impl IntoIterator<Item = (ModuleName, ErrorKind)>,
+    ) -> Vec<PackageTally> {
+        let mut counts: BTreeMap<&str, u64> = BTreeMap::new();
+        for (module, kind) in errors {
+            if let Some(package) = self.package_of(module) {
+                *counts.entry(package).or_default() += self.weight(kind);
+            }
+        }
+        self.budgets
+            .iter()
+            .map(|(package, &budget)| PackageTally {
+                package: package.clone(),
+                budget,
+                count: counts.get(package.as_str()).copied().unwrap_or(0),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(content: &str) -> ErrorBudget {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_tally() {
+        let budget = budget(
+            r#"
+[weights]
+bad-return = 3
+unknown-name = 0
+
+[budgets]
+app = 5
+"app.legacy" = 10
+"#,
+        );
+        let errors = [
+            ("app.main", ErrorKind::BadReturn),
+            ("app.main", ErrorKind::UnknownName),
+            ("app", ErrorKind::BadAssignment),
+            ("app.legacy.db", ErrorKind::BadReturn),
+            ("application", ErrorKind::BadReturn),
+        ];
+        let tally = budget.tally(
+            errors
+                .into_iter()
+                .map(|(module, kind)| (ModuleName::from_str(module), kind)),
+        );
+        assert_eq!(
+            tally,
+            vec![
+                PackageTally {
+                    package: "app".to_owned(),
+                    budget: 5,
+                    count: 4,
+                },
+                PackageTally {
+                    package: "app.legacy".to_owned(),
+                    budget: 10,
+                    count: 3,
+                },
+            ]
+        );
+        assert!(tally.iter().all(|x| !x.is_over_budget()));
+    }
+
+    #[test]
+    fn test_display() {
+        let over = PackageTally {
+            package: "app".to_owned(),
+            budget: 5,
+            count: 7,
+        };
+        assert!(over.is_over_budget());
+        assert_eq!(over.to_string(), "app: 7 (budget 5, +2)");
+        let under = PackageTally { count: 3, ..over };
+        assert_eq!(under.to_string(), "app: 3 (budget 5, -2)");
+    }
+
+    #[test]
+    fn test_unknown_error_kind() {
+        assert!(toml::from_str::<ErrorBudget>("[weights]\nnot-an-error = 1\n").is_err());
+    }
+}
@@ -10,8 +10,10 @@ pub mod collector;
 pub mod context;
 pub mod display;
 pub mod error;
+pub mod error_budget;
 pub mod expectation;
 pub mod legacy;
 pub mod style;
 pub mod summarize;
+pub mod suppression_budget;
 pub mod suppress;
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A committed budget of suppression comments per module, which is only allowed to go down.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context as _;
+use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_util::fs_anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::state::errors::Errors;
+
+/// The number of suppression comments in each module, keyed by path.
+/// Modules without any suppressions are omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuppressionBudget {
+    modules: BTreeMap<String, usize>,
+}
+
+/// A module that has more suppressions than its budget allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetOverrun {
+    pub path: String,
+    pub budget: usize,
+    pub count: usize,
+}
+
+impl SuppressionBudget {
+    /// Count the suppression comments in every module that was checked.
+    pub fn from_errors(loads: &Errors, relative_to: &Path) -> Self {
+        let mut modules = BTreeMap::new();
+        for (module_path, ignore) in loads.collect_ignores() {
+            let ModulePathDetails::FileSystem(path) = module_path.details() else {
+                continue;
+            };
+            let count = ignore.iter().map(|(_, xs)| xs.len()).sum::<usize>();
+            if count > 0 {
+                let path = path.strip_prefix(relative_to).unwrap_or(path);
+                modules.insert(path.to_string_lossy().into_owned(), count);
+            }
+        }
+        Self { modules }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs_anyhow::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("while parsing suppression budget `{}`", path.display()))
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> anyhow::Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        fs_anyhow::write(path, content)
+    }
+
+    /// The modules in `current` with more suppressions than this budget allows. A module missing
+    /// from the budget has a budget of zero.
+    pub fn overruns(&self, current: &Self) -> Vec<BudgetOverrun> {
+        current
+            .modules
+            .iter()
+            .filter_map(|(path, &count)| {
+                let budget = self.modules.get(path).copied().unwrap_or(0);
+                (count > budget).then(|| BudgetOverrun {
+                    path: path.clone(),
+                    budget,
+                    count,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether `current` has fewer suppressions than this budget in some module, so the budget
+    /// could be tightened.
+    pub fn can_tighten(&self, current: &Self) -> bool {
+        self.modules
+            .iter()
+            .any(|(path, &budget)| current.modules.get(path).copied().unwrap_or(0) < budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(modules: &[(&str, usize)]) -> SuppressionBudget {
+        SuppressionBudget {
+            modules: modules
+                .iter()
+                .map(|(path, count)| ((*path).to_owned(), *count))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_overruns() {
+        let old = budget(&[("a.py", 3), ("b.py", 1)]);
+        let new = budget(&[("a.py", 2), ("b.py", 2), ("c.py", 1)]);
+        assert_eq!(
+            old.overruns(&new),
+            vec![
+                BudgetOverrun {
+                    path: "b.py".to_owned(),
+                    budget: 1,
+                    count: 2,
+                },
+                BudgetOverrun {
+                    path: "c.py".to_owned(),
+                    budget: 0,
+                    count: 1,
+                },
+            ]
+        );
+        assert!(old.can_tighten(&new));
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let old = budget(&[("a.py", 3)]);
+        assert!(old.overruns(&old).is_empty());
+        assert!(!old.can_tighten(&old));
+        assert!(old.can_tighten(&budget(&[])));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let old = budget(&[("a.py", 3), ("dir/b.py", 1)]);
+        let json = serde_json::to_string(&old).unwrap();
+        assert_eq!(json, r#"{"modules":{"a.py":3,"dir/b.py":1}}"#);
+        assert_eq!(
+            serde_json::from_str::<SuppressionBudget>(&json).unwrap(),
+            old
+        );
+    }
+}
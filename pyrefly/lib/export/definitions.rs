@@ -12,6 +12,7 @@ use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModuleStyle;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_python::symbol_kind::SymbolKind;
+use pyrefly_python::sys_info::ConstantValue;
 use pyrefly_python::sys_info::SysInfo;
 use pyrefly_util::visit::Visit;
 use ruff_python_ast::Decorator;
@@ -139,6 +140,11 @@ pub struct Definitions {
     pub deprecated: SmallSet<Name>,
     /// Special exports defined in this module
     pub special_exports: SmallMap<Name, SpecialExport>,
+    /// Names that are defined exactly once, by assigning a literal, e.g. `DEBUG = False`.
+    pub constants: SmallMap<Name, ConstantValue>,
+    /// Names that are defined exactly once, by a `Final` assignment of a literal lookup table, e.g.
+    /// `CODES: Final = {"ok": 200}`, with the range of the name and the keys of the table.
+    pub literal_tables: SmallMap<Name, (TextRange, Vec<ConstantValue>)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -213,6 +219,22 @@ fn is_deprecated_decorator(decorator: &Decorator) -> bool {
     })
 }
 
+/// The keys of a dict display, or the elements of a tuple, list or set display, if they are all
+/// literals.
+fn literal_table(x: &Expr) -> Option<Vec<ConstantValue>> {
+    match x {
+        Expr::Dict(x) => x
+            .items
+            .iter()
+            .map(|x| ConstantValue::from_expr(x.key.as_ref()?))
+            .collect(),
+        Expr::Tuple(x) => x.elts.iter().map(ConstantValue::from_expr).collect(),
+        Expr::List(x) => x.elts.iter().map(ConstantValue::from_expr).collect(),
+        Expr::Set(x) => x.elts.iter().map(ConstantValue::from_expr).collect(),
+        _ => None,
+    }
+}
+
 fn is_overload_decorator(decorator: &Decorator) -> bool {
     decorator
         .expression
@@ -229,6 +251,24 @@ impl Definitions {
             inner: Definitions::default(),
         };
         builder.stmts(x);
+        let Definitions {
+            definitions,
+            constants,
+            literal_tables,
+            ..
+        } = &mut builder.inner;
+        let defined_once = |name: &Name| {
+            definitions.get(name).is_some_and(|x| {
+                !x.needs_anywhere
+                    && matches!(
+                        x.style,
+                        DefinitionStyle::Annotated(..) | DefinitionStyle::Unannotated(..)
+                    )
+            })
+        };
+        // Anything defined more than once, or by more than just an assignment, isn't a constant.
+        constants.retain(|name, _| defined_once(name));
+        literal_tables.retain(|name, _| defined_once(name));
         builder.inner
     }
 
@@ -475,6 +515,11 @@ impl<'a> DefinitionsBuilder<'a> {
             }
             Stmt::Assign(x) => {
                 self.named_in_expr(&x.value);
+                if let [Expr::Name(name)] = x.targets.as_slice()
+                    && let Some(value) = ConstantValue::from_expr(&x.value)
+                {
+                    self.inner.constants.insert(name.id.clone(), value);
+                }
                 for t in &x.targets {
                     self.expr_lvalue(t);
                     if DunderAllEntry::is_all(t) {
@@ -492,13 +537,27 @@ impl<'a> DefinitionsBuilder<'a> {
                     self.inner.dunder_all = DunderAllEntry::as_list(v.as_ref());
                 }
                 match &*x.target {
-                    Expr::Name(x) => {
+                    Expr::Name(name) => {
+                        if let Some(value) = x.value.as_deref().and_then(ConstantValue::from_expr) {
+                            self.inner.constants.insert(name.id.clone(), value);
+                        }
+                        if Ast::is_final_annotation(&x.annotation)
+                            && let Some(keys) = x.value.as_deref().and_then(literal_table)
+                        {
+                            self.inner
+                                .literal_tables
+                                .insert(name.id.clone(), (name.range, keys));
+                        }
                         self.add_name(
-                            &x.id,
-                            x.range,
+                            &name.id,
+                            name.range,
                             DefinitionStyle::Annotated(
-                                SymbolKind::Variable,
-                                ShortIdentifier::expr_name(x),
+                                if Ast::is_final_annotation(&x.annotation) {
+                                    SymbolKind::Constant
+                                } else {
+                                    SymbolKind::Variable
+                                },
+                                ShortIdentifier::expr_name(name),
                             ),
                         );
                     }
@@ -1026,4 +1085,74 @@ del x
         let x = defs.definitions.get(&Name::new_static("x")).unwrap();
         assert!(!x.needs_anywhere);
     }
+
+    #[test]
+    fn test_constants() {
+        let defs = calculate_unranged_definitions_with_defaults(
+            r#"
+DEBUG = False
+NAME: str = "app"
+LIMIT = 10
+NOTHING = None
+NOT_LITERAL = 1 + 2
+REASSIGNED = True
+REASSIGNED = False
+AUGMENTED = 1
+AUGMENTED += 1
+A = B = True
+def f():
+    INNER = True
+"#,
+        );
+        assert_eq!(
+            defs.constants.into_iter().collect::<Vec<_>>(),
+            vec![
+                (Name::new_static("DEBUG"), ConstantValue::Bool(false)),
+                (
+                    Name::new_static("NAME"),
+                    ConstantValue::String("app".to_owned())
+                ),
+                (Name::new_static("LIMIT"), ConstantValue::Int(10)),
+                (Name::new_static("NOTHING"), ConstantValue::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_literal_tables() {
+        let defs = calculate_unranged_definitions_with_defaults(
+            r#"
+from typing import Final
+CODES: Final = {"ok": 200, "missing": 404}
+SIZES: Final = (1, 2, 3)
+NOT_FINAL: dict[str, int] = {"ok": 200}
+NOT_LITERAL: Final = {"ok": 200, str(1): 500}
+REASSIGNED: Final = [True]
+REASSIGNED: Final = [False]
+"#,
+        );
+        assert_eq!(
+            defs.literal_tables
+                .into_iter()
+                .map(|(name, (_, keys))| (name, keys))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Name::new_static("CODES"),
+                    vec![
+                        ConstantValue::String("ok".to_owned()),
+                        ConstantValue::String("missing".to_owned())
+                    ]
+                ),
+                (
+                    Name::new_static("SIZES"),
+                    vec![
+                        ConstantValue::Int(1),
+                        ConstantValue::Int(2),
+                        ConstantValue::Int(3)
+                    ]
+                ),
+            ]
+        );
+    }
 }
@@ -12,8 +12,10 @@ use std::sync::Arc;
 
 use dupe::Dupe;
 use pyrefly_python::docstring::Docstring;
+use pyrefly_python::dunder;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::symbol_kind::SymbolKind;
+use pyrefly_python::sys_info::ConstantValue;
 use pyrefly_python::sys_info::SysInfo;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::name::Name;
@@ -149,6 +151,18 @@ impl Exports {
         self.0.docstring_range
     }
 
+    /// The value of `name`, if it is a constant defined by a literal.
+    pub fn constant(&self, name: &Name) -> Option<&ConstantValue> {
+        self.0.definitions.constants.get(name)
+    }
+
+    /// The range of `name` and the keys of the table, if it is a `Final` lookup table defined by a
+    /// literal, e.g. `CODES: Final = {"ok": 200}`.
+    pub fn literal_table(&self, name: &Name) -> Option<(TextRange, &[ConstantValue])> {
+        let (range, keys) = self.0.definitions.literal_tables.get(name)?;
+        Some((*range, keys))
+    }
+
     pub fn is_submodule_imported_implicitly(&self, name: &Name) -> bool {
         self.0
             .definitions
@@ -156,6 +170,30 @@ impl Exports {
             .contains(name)
     }
 
+    /// Whether `name` is imported into this module, named `module`, without being re-exported
+    /// explicitly: with a redundant alias, as in `from m import x as x`, or by being listed in an
+    /// explicit `__all__`. A package re-exports the submodules it imports with `from . import x`.
+    pub fn is_implicit_reexport(
+        &self,
+        module: ModuleName,
+        name: &Name,
+        lookup: &dyn LookupExport,
+    ) -> bool {
+        let definitions = &self.0.definitions;
+        let Some(definition) = definitions.definitions.get(name) else {
+            return false;
+        };
+        let is_import = match &definition.style {
+            DefinitionStyle::Import(from) => *from != module,
+            DefinitionStyle::ImportAs(..) | DefinitionStyle::ImportModule(_) => true,
+            _ => false,
+        };
+        // Without an explicit `__all__`, one listing every public name is synthesized.
+        is_import
+            && !(definitions.definitions.contains_key(&dunder::ALL)
+                && self.wildcard(lookup).contains(name))
+    }
+
     pub fn exports(&self, lookup: &dyn LookupExport) -> Arc<SmallMap<Name, ExportLocation>> {
         let f = || {
             let mut result: SmallMap<Name, ExportLocation> = SmallMap::new();
@@ -315,6 +353,36 @@ _x = 2
         eq_wildcards(&b, &imports, &[]);
     }
 
+    #[test]
+    fn test_implicit_reexport() {
+        let a = mk_exports("x = 1\ny = 2", ModuleStyle::Executable);
+        let b = mk_exports(
+            "import a\nfrom a import x\nfrom a import y as y\nfrom a import x as z\nw = 1",
+            ModuleStyle::Executable,
+        );
+        let c = mk_exports(
+            "from a import x, y\n__all__ = ['x']",
+            ModuleStyle::Executable,
+        );
+        let imports = smallmap! {ModuleName::from_str("a") => a};
+        let foo = ModuleName::from_str("foo");
+        for (name, expected) in [
+            ("a", true),
+            ("x", true),
+            ("y", false),
+            ("z", true),
+            ("w", false),
+        ] {
+            assert_eq!(
+                b.is_implicit_reexport(foo, &Name::new(name), &imports),
+                expected,
+                "{name}"
+            );
+        }
+        assert!(!c.is_implicit_reexport(foo, &Name::new_static("x"), &imports));
+        assert!(c.is_implicit_reexport(foo, &Name::new_static("y"), &imports));
+    }
+
     #[test]
     fn test_cyclic() {
         let a = mk_exports("from b import *", ModuleStyle::Interface);
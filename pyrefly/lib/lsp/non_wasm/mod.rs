@@ -8,7 +8,12 @@
 mod build_system;
 pub mod lsp;
 pub mod module_helpers;
+pub mod multiplex;
+pub mod pipe;
+pub mod plaintext;
+pub mod query_cache;
 pub mod queue;
 pub mod server;
+pub mod status;
 pub mod transaction_manager;
 pub mod workspace;
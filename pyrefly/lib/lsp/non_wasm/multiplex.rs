@@ -0,0 +1,944 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Share one server between several clients connecting over TCP, e.g. a VS Code window and vim
+//! on the same checkout. Each client has its own open documents, but modules are only analyzed,
+//! and kept in memory, once.
+//!
+//! The server talks to a single `Connection`, which the clients are multiplexed onto:
+//! - The first client initializes the server. Later clients are sent the same `initialize`
+//!   result, and their workspace folders are added to the server's. Capabilities aren't
+//!   negotiated per client: the server only knows the first client's, so may use features a later
+//!   client doesn't support, and later clients get the server capabilities chosen for the first.
+//! - Requests from clients are renumbered, so their ids are unique, and the responses are sent
+//!   back to the client with the original id.
+//! - Each client has its own contents and version of a document it has open. The server has the
+//!   contents of one of them at a time, and is sent another client's contents before that
+//!   client's changes and requests about the document. Diagnostics go to the clients whose
+//!   contents the server checked, with their own version, and other notifications to every
+//!   client. Requests from the server, such as applying an edit, go to the client that most
+//!   recently sent a request or notification, which is usually the one the server is acting for.
+//!   Capability registrations go to every client, and the first response is used. Capabilities
+//!   the server registered are registered again with clients that connect later.
+//! - A document is closed once every client that opened it has closed it, or gone.
+//! - Once the last client has gone, the server is shut down.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::thread;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use lsp_server::Connection;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::RequestId;
+use lsp_server::Response;
+use lsp_types::CancelParams;
+use lsp_types::DidChangeTextDocumentParams;
+use lsp_types::DidChangeWorkspaceFoldersParams;
+use lsp_types::DidCloseTextDocumentParams;
+use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::InitializeParams;
+use lsp_types::NumberOrString;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::TextDocumentContentChangeEvent;
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::Url;
+use lsp_types::VersionedTextDocumentIdentifier;
+use lsp_types::WorkspaceFoldersChangeEvent;
+use lsp_types::notification::Cancel;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidChangeWorkspaceFolders;
+use lsp_types::notification::DidCloseTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::Exit;
+use lsp_types::notification::Initialized;
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::Initialize;
+use lsp_types::request::RegisterCapability;
+use lsp_types::request::Request as _;
+use lsp_types::request::Shutdown;
+use lsp_types::request::UnregisterCapability;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+
+use crate::lsp::non_wasm::lsp::apply_change_events;
+
+type ClientId = usize;
+
+enum Event {
+    Connected(ClientId, Sender<Message>),
+    Client(ClientId, Message),
+    Disconnected(ClientId),
+    Server(Message),
+    /// The server has dropped its end of the connection.
+    ServerClosed,
+}
+
+struct Client {
+    sender: Sender<Message>,
+    /// Whether the client has been sent the result of `initialize`, so can be sent anything else.
+    initialized: bool,
+    /// The documents the client has open, with their contents as the client last sent them.
+    open: SmallMap<Url, Document>,
+}
+
+struct Document {
+    version: i32,
+    text: String,
+}
+
+/// A document the server has open.
+struct ServerDocument {
+    /// The client whose contents the server has.
+    client: ClientId,
+    /// The version the server was last sent, which is independent of the clients' versions, so
+    /// it keeps increasing when the server is sent a different client's contents.
+    version: i32,
+}
+
+/// The prefix of the ids of requests made by the multiplexer rather than the server, whose
+/// responses are dropped.
+const OWN_REQUEST_PREFIX: &str = "pyrefly-multiplex-";
+
+struct Multiplexer {
+    server: Sender<Message>,
+    clients: SmallMap<ClientId, Client>,
+    /// The client that initialized the server.
+    first_client: Option<ClientId>,
+    /// The client that most recently sent a request or notification, which requests from the
+    /// server are sent to.
+    active_client: Option<ClientId>,
+    /// The documents open in the server.
+    documents: SmallMap<Url, ServerDocument>,
+    /// The requests sent on to the server, by their new id, with their client, original id and
+    /// method.
+    requests: HashMap<RequestId, (ClientId, RequestId, String)>,
+    next_request: i32,
+    /// The result of `initialize` for the first client, once the server has sent it.
+    initialize_result: Option<serde_json::Value>,
+    /// Clients that asked to `initialize` while the first client was still waiting for the result.
+    waiting_for_initialize: Vec<(ClientId, RequestId, serde_json::Value)>,
+    /// The requests from the server that no client has responded to yet.
+    server_requests: SmallSet<RequestId>,
+    /// The capabilities the server registered, to register with clients that connect later.
+    registrations: Vec<Request>,
+    next_own_request: usize,
+    /// Whether the server has been told to exit.
+    exited: bool,
+}
+
+impl Multiplexer {
+    fn new(server: Sender<Message>) -> Self {
+        Self {
+            server,
+            clients: SmallMap::new(),
+            first_client: None,
+            active_client: None,
+            documents: SmallMap::new(),
+            requests: HashMap::new(),
+            next_request: 0,
+            initialize_result: None,
+            waiting_for_initialize: Vec::new(),
+            server_requests: SmallSet::new(),
+            registrations: Vec::new(),
+            next_own_request: 0,
+            exited: false,
+        }
+    }
+
+    fn send_to_client(&self, client: ClientId, msg: Message) {
+        if let Some(client) = self.clients.get(&client) {
+            // If the client has gone, it will be removed when its reader stops.
+            let _ = client.sender.send(msg);
+        }
+    }
+
+    fn send_to_server(&self, msg: Message) {
+        let _ = self.server.send(msg);
+    }
+
+    fn own_request_id(&mut self) -> RequestId {
+        self.next_own_request += 1;
+        RequestId::from(format!("{OWN_REQUEST_PREFIX}{}", self.next_own_request))
+    }
+
+    /// The contents `client` has of `uri`, if it has it open.
+    fn document(&self, client: ClientId, uri: &Url) -> Option<&Document> {
+        self.clients.get(&client)?.open.get(uri)
+    }
+
+    /// Make sure the server has the contents `client` has of `uri`, if both have it open.
+    fn sync_document(&mut self, client: ClientId, uri: &Url) {
+        let Some(server) = self.documents.get(uri) else {
+            return;
+        };
+        if server.client == client {
+            return;
+        }
+        let Some(text) = self.document(client, uri).map(|x| x.text.clone()) else {
+            return;
+        };
+        let same = self
+            .document(server.client, uri)
+            .is_some_and(|x| x.text == text);
+        let server = self.documents.get_mut(uri).unwrap();
+        server.client = client;
+        if same {
+            return;
+        }
+        server.version += 1;
+        let version = server.version;
+        self.send_to_server(Message::Notification(Notification::new(
+            DidChangeTextDocument::METHOD.to_owned(),
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text,
+                }],
+            },
+        )));
+    }
+
+    /// `client` has closed `uri`, so close it in the server if no other client has it open, or
+    /// else make sure the server has the contents of one that does.
+    fn close_document(&mut self, client: ClientId, uri: Url) {
+        if let Some(c) = self.clients.get_mut(&client) {
+            c.open.shift_remove(&uri);
+        }
+        let Some(server) = self.documents.get(&uri) else {
+            return;
+        };
+        let other = self
+            .clients
+            .iter()
+            .find(|(id, x)| **id != client && x.open.contains_key(&uri))
+            .map(|(id, _)| *id);
+        match other {
+            Some(other) if server.client == client => self.sync_document(other, &uri),
+            Some(_) => {}
+            None => {
+                self.documents.shift_remove(&uri);
+                self.send_to_server(Message::Notification(Notification::new(
+                    DidCloseTextDocument::METHOD.to_owned(),
+                    DidCloseTextDocumentParams {
+                        text_document: TextDocumentIdentifier { uri },
+                    },
+                )));
+            }
+        }
+    }
+
+    fn connected(&mut self, client: ClientId, sender: Sender<Message>) {
+        if self.exited {
+            // Dropping the sender closes the connection.
+            return;
+        }
+        self.clients.insert(
+            client,
+            Client {
+                sender,
+                initialized: false,
+                open: SmallMap::new(),
+            },
+        );
+    }
+
+    /// Send a later client the result of `initialize`, and tell the server about its workspace.
+    fn initialize_later_client(
+        &mut self,
+        client: ClientId,
+        id: RequestId,
+        params: serde_json::Value,
+        result: serde_json::Value,
+    ) {
+        self.send_to_client(client, Message::Response(Response::new_ok(id, result)));
+        if let Some(x) = self.clients.get_mut(&client) {
+            x.initialized = true;
+        }
+        let added = serde_json::from_value::<InitializeParams>(params)
+            .ok()
+            .and_then(|x| x.workspace_folders)
+            .unwrap_or_default();
+        if !added.is_empty() {
+            self.send_to_server(Message::Notification(Notification::new(
+                DidChangeWorkspaceFolders::METHOD.to_owned(),
+                DidChangeWorkspaceFoldersParams {
+                    event: WorkspaceFoldersChangeEvent {
+                        added,
+                        removed: Vec::new(),
+                    },
+                },
+            )));
+        }
+        for registration in self.registrations.clone() {
+            let id = self.own_request_id();
+            self.send_to_client(
+                client,
+                Message::Request(Request::new(id, registration.method, registration.params)),
+            );
+        }
+    }
+
+    fn from_client(&mut self, client: ClientId, msg: Message) {
+        if !matches!(msg, Message::Response(_)) {
+            self.active_client = Some(client);
+        }
+        match msg {
+            Message::Request(x) if x.method == Initialize::METHOD => {
+                if let Some(result) = self.initialize_result.clone() {
+                    self.initialize_later_client(client, x.id, x.params, result);
+                } else if self.first_client.is_some() {
+                    self.waiting_for_initialize.push((client, x.id, x.params));
+                } else {
+                    self.first_client = Some(client);
+                    self.forward_request(client, x);
+                }
+            }
+            Message::Request(x)
+                if x.method == Shutdown::METHOD && self.clients.keys().any(|id| *id != client) =>
+            {
+                // Other clients still need the server, so only this client is shut down.
+                self.send_to_client(
+                    client,
+                    Message::Response(Response::new_ok(x.id, serde_json::Value::Null)),
+                );
+            }
+            Message::Request(x) => self.forward_request(client, x),
+            Message::Response(x) => {
+                if self.server_requests.shift_remove(&x.id) {
+                    self.send_to_server(Message::Response(x));
+                }
+            }
+            Message::Notification(x) => self.client_notification(client, x),
+        }
+    }
+
+    fn forward_request(&mut self, client: ClientId, mut x: Request) {
+        if let Some(uri) = x
+            .params
+            .get("textDocument")
+            .and_then(|x| x.get("uri"))
+            .and_then(|x| x.as_str())
+            .and_then(|x| Url::parse(x).ok())
+        {
+            // Answer the request about the document as the client sees it.
+            self.sync_document(client, &uri);
+        }
+        let id = RequestId::from(self.next_request);
+        self.next_request += 1;
+        self.requests
+            .insert(id.clone(), (client, x.id.clone(), x.method.clone()));
+        x.id = id;
+        self.send_to_server(Message::Request(x));
+    }
+
+    fn client_notification(&mut self, client: ClientId, mut x: Notification) {
+        let method = x.method.as_str();
+        if method == Initialized::METHOD {
+            // Only the server's own client's is expected.
+            if self.first_client != Some(client) {
+                return;
+            }
+        } else if method == Exit::METHOD {
+            // The client is removed once its reader stops, just after `exit`, and the server only
+            // exits with the last client.
+            if self.clients.keys().any(|id| *id != client) {
+                return;
+            }
+            self.exited = true;
+        } else if method == Cancel::METHOD {
+            let Ok(params) = serde_json::from_value::<CancelParams>(x.params.clone()) else {
+                return;
+            };
+            let original = match params.id {
+                NumberOrString::Number(id) => RequestId::from(id),
+                NumberOrString::String(id) => RequestId::from(id),
+            };
+            let Some(id) = self
+                .requests
+                .iter()
+                .find(|(_, (c, id, _))| *c == client && *id == original)
+                .map(|(id, _)| id)
+            else {
+                return;
+            };
+            x.params = serde_json::json!({ "id": id });
+        } else if method == DidOpenTextDocument::METHOD
+            && let Ok(params) =
+                serde_json::from_value::<DidOpenTextDocumentParams>(x.params.clone())
+            && let Some(c) = self.clients.get_mut(&client)
+        {
+            let document = params.text_document;
+            c.open.insert(
+                document.uri.clone(),
+                Document {
+                    version: document.version,
+                    text: document.text,
+                },
+            );
+            if self.documents.contains_key(&document.uri) {
+                // Another client has the document open, so the server only needs its contents.
+                self.sync_document(client, &document.uri);
+                return;
+            }
+            self.documents.insert(
+                document.uri,
+                ServerDocument {
+                    client,
+                    version: document.version,
+                },
+            );
+        } else if method == DidChangeTextDocument::METHOD
+            && let Ok(mut params) =
+                serde_json::from_value::<DidChangeTextDocumentParams>(x.params.clone())
+            && let Some(document) = self
+                .clients
+                .get_mut(&client)
+                .and_then(|c| c.open.get_mut(&params.text_document.uri))
+        {
+            document.text = apply_change_events(&document.text, params.content_changes.clone());
+            document.version = params.text_document.version;
+            let uri = params.text_document.uri.clone();
+            let Some(server) = self.documents.get_mut(&uri) else {
+                return;
+            };
+            if server.client != client {
+                // The server has another client's contents, so is sent all of this client's.
+                self.sync_document(client, &uri);
+                return;
+            }
+            server.version += 1;
+            params.text_document.version = server.version;
+            x.params = serde_json::to_value(params).unwrap();
+        } else if method == DidCloseTextDocument::METHOD
+            && let Ok(params) =
+                serde_json::from_value::<DidCloseTextDocumentParams>(x.params.clone())
+        {
+            self.close_document(client, params.text_document.uri);
+            return;
+        }
+        self.send_to_server(Message::Notification(x));
+    }
+
+    fn disconnected(&mut self, client: ClientId) {
+        let Some(gone) = self.clients.get(&client) else {
+            return;
+        };
+        for uri in gone.open.keys().cloned().collect::<Vec<_>>() {
+            self.close_document(client, uri);
+        }
+        self.clients.shift_remove(&client);
+        self.waiting_for_initialize.retain(|(c, _, _)| *c != client);
+        if self.clients.is_empty() && !self.exited {
+            // The last client has gone, so nothing needs the server any more.
+            self.exited = true;
+            let id = self.own_request_id();
+            self.send_to_server(Message::Request(Request::new(
+                id,
+                Shutdown::METHOD.to_owned(),
+                serde_json::Value::Null,
+            )));
+            self.send_to_server(Message::Notification(Notification::new(
+                Exit::METHOD.to_owned(),
+                serde_json::Value::Null,
+            )));
+        }
+    }
+
+    fn from_server(&mut self, msg: Message) {
+        match msg {
+            Message::Response(mut x) => {
+                let Some((client, id, method)) = self.requests.remove(&x.id) else {
+                    return;
+                };
+                x.id = id;
+                if method == Initialize::METHOD
+                    && let Some(result) = &x.result
+                {
+                    self.initialize_result = Some(result.clone());
+                    if let Some(c) = self.clients.get_mut(&client) {
+                        c.initialized = true;
+                    }
+                    self.send_to_client(client, Message::Response(x));
+                    for (client, id, params) in std::mem::take(&mut self.waiting_for_initialize) {
+                        let result = self.initialize_result.clone().unwrap();
+                        self.initialize_later_client(client, id, params, result);
+                    }
+                    return;
+                }
+                self.send_to_client(client, Message::Response(x));
+            }
+            Message::Notification(x) => {
+                if x.method == PublishDiagnostics::METHOD
+                    && let Ok(params) =
+                        serde_json::from_value::<PublishDiagnosticsParams>(x.params.clone())
+                    && let Some(server) = self.documents.get(&params.uri)
+                {
+                    self.publish_diagnostics(server.client, server.version, params);
+                    return;
+                }
+                for client in self.initialized_clients() {
+                    self.send_to_client(client, Message::Notification(x.clone()));
+                }
+            }
+            Message::Request(x) => {
+                self.server_requests.insert(x.id.clone());
+                let clients = if x.method == RegisterCapability::METHOD
+                    || x.method == UnregisterCapability::METHOD
+                {
+                    if x.method == RegisterCapability::METHOD {
+                        self.registrations.push(x.clone());
+                    }
+                    self.initialized_clients()
+                } else {
+                    self.request_client().into_iter().collect()
+                };
+                for client in clients {
+                    self.send_to_client(client, Message::Request(x.clone()));
+                }
+            }
+        }
+    }
+
+    /// Send diagnostics for an open document to the clients with the contents the server checked,
+    /// i.e. those of `owner` at the server's `version`.
+    fn publish_diagnostics(
+        &self,
+        owner: ClientId,
+        version: i32,
+        mut params: PublishDiagnosticsParams,
+    ) {
+        if params.version.is_some_and(|x| x != version) {
+            // The diagnostics are for contents the server has since been sent changes to.
+            return;
+        }
+        let Some(checked) = self.document(owner, &params.uri).map(|x| x.text.as_str()) else {
+            return;
+        };
+        for (id, c) in self.clients.iter() {
+            if let Some(document) = c.open.get(&params.uri)
+                && document.text == checked
+            {
+                params.version = params.version.map(|_| document.version);
+                self.send_to_client(
+                    *id,
+                    Message::Notification(Notification::new(
+                        PublishDiagnostics::METHOD.to_owned(),
+                        &params,
+                    )),
+                );
+            }
+        }
+    }
+
+    /// The client to send a request from the server to: the most recently active one, or else
+    /// any that can be sent requests.
+    fn request_client(&self) -> Option<ClientId> {
+        let initialized = |id: &ClientId| self.clients.get(id).is_some_and(|c| c.initialized);
+        self.active_client
+            .filter(initialized)
+            .or(self.first_client.filter(initialized))
+            .or_else(|| self.initialized_clients().into_iter().next())
+    }
+
+    fn initialized_clients(&self) -> Vec<ClientId> {
+        self.clients
+            .iter()
+            .filter(|(_, c)| c.initialized)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    fn run(mut self, events: Receiver<Event>) {
+        for event in events {
+            match event {
+                Event::Connected(client, sender) => self.connected(client, sender),
+                Event::Client(client, msg) => self.from_client(client, msg),
+                Event::Disconnected(client) => self.disconnected(client),
+                Event::Server(msg) => self.from_server(msg),
+                Event::ServerClosed => break,
+            }
+        }
+    }
+}
+
+/// Move messages between a client's socket and the multiplexer, until the client exits or the
+/// connection closes.
+fn serve_client(stream: TcpStream, client: ClientId, events: Sender<Event>) -> io::Result<()> {
+    let (sender, receiver) = crossbeam_channel::unbounded::<Message>();
+    let write_stream = stream.try_clone()?;
+    // The writer stops once the multiplexer drops the sender, when the client is removed.
+    thread::spawn(move || -> io::Result<()> {
+        let mut writer = BufWriter::new(write_stream);
+        for msg in receiver {
+            msg.write(&mut writer)?;
+        }
+        Ok(())
+    });
+    if events.send(Event::Connected(client, sender)).is_err() {
+        return Ok(());
+    }
+    let mut reader = BufReader::new(stream);
+    let res = (|| {
+        while let Some(msg) = Message::read(&mut reader)? {
+            let is_exit = matches!(&msg, Message::Notification(x) if x.method == Exit::METHOD);
+            if events.send(Event::Client(client, msg)).is_err() || is_exit {
+                break;
+            }
+        }
+        Ok(())
+    })();
+    let _ = events.send(Event::Disconnected(client));
+    res
+}
+
+/// The thread multiplexing the clients, which should be joined once the server has shut down,
+/// like `lsp_server::IoThreads`. The thread accepting clients is left running.
+pub struct MultiplexThreads {
+    multiplexer: JoinHandle<()>,
+}
+
+impl MultiplexThreads {
+    pub fn join(self) -> io::Result<()> {
+        self.multiplexer
+            .join()
+            .map_err(|_| io::Error::other("multiplexer thread panicked"))
+    }
+}
+
+/// Listen on `addr` for any number of clients, which share the server on the other end of the
+/// returned `Connection`.
+pub fn listen(addr: impl ToSocketAddrs) -> io::Result<(Connection, MultiplexThreads)> {
+    let listener = TcpListener::bind(addr)?;
+    let (events_sender, events) = crossbeam_channel::unbounded();
+    let (server_sender, receiver) = crossbeam_channel::unbounded();
+    let (sender, server_receiver) = crossbeam_channel::unbounded::<Message>();
+
+    let events_from_server = events_sender.clone();
+    thread::spawn(move || {
+        for msg in server_receiver {
+            if events_from_server.send(Event::Server(msg)).is_err() {
+                return;
+            }
+        }
+        let _ = events_from_server.send(Event::ServerClosed);
+    });
+    thread::spawn(move || {
+        for (client, stream) in listener.incoming().enumerate() {
+            match stream {
+                Ok(stream) => {
+                    let events = events_sender.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve_client(stream, client, events) {
+                            eprintln!("client {client} disconnected: {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("failed to accept client: {e}"),
+            }
+        }
+    });
+    let multiplexer = Multiplexer::new(server_sender);
+    let multiplexer = thread::spawn(move || multiplexer.run(events));
+    Ok((
+        Connection { sender, receiver },
+        MultiplexThreads { multiplexer },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(id: i32, method: &str, params: serde_json::Value) -> Message {
+        Message::Request(Request::new(RequestId::from(id), method.to_owned(), params))
+    }
+
+    fn notification(method: &str, params: serde_json::Value) -> Message {
+        Message::Notification(Notification::new(method.to_owned(), params))
+    }
+
+    fn open(text: &str) -> Message {
+        notification(
+            DidOpenTextDocument::METHOD,
+            json!({"textDocument": {"uri": "file:///a.py", "languageId": "python", "version": 1, "text": text}}),
+        )
+    }
+
+    fn change(version: i32, text: &str) -> Message {
+        notification(
+            DidChangeTextDocument::METHOD,
+            json!({"textDocument": {"uri": "file:///a.py", "version": version}, "contentChanges": [{"text": text}]}),
+        )
+    }
+
+    fn close() -> Message {
+        notification(
+            DidCloseTextDocument::METHOD,
+            json!({"textDocument": {"uri": "file:///a.py"}}),
+        )
+    }
+
+    fn hover(id: i32) -> Message {
+        request(
+            id,
+            "textDocument/hover",
+            json!({"textDocument": {"uri": "file:///a.py"}, "position": {"line": 0, "character": 0}}),
+        )
+    }
+
+    fn diagnostics(version: i32) -> Message {
+        notification(
+            PublishDiagnostics::METHOD,
+            json!({"uri": "file:///a.py", "diagnostics": [], "version": version}),
+        )
+    }
+
+    /// The messages sent so far, summarized as strings.
+    fn received(receiver: &Receiver<Message>) -> Vec<String> {
+        receiver
+            .try_iter()
+            .map(|msg| match msg {
+                Message::Request(x) => format!("request {} {}", x.id, x.method),
+                Message::Response(x) => format!("response {}", x.id),
+                Message::Notification(x) => {
+                    let version = x.params["textDocument"]["version"]
+                        .as_i64()
+                        .or_else(|| x.params["version"].as_i64());
+                    let text = x.params["contentChanges"][0]["text"]
+                        .as_str()
+                        .or_else(|| x.params["textDocument"]["text"].as_str());
+                    let mut res = x.method;
+                    if let Some(version) = version {
+                        res.push_str(&format!(" v{version}"));
+                    }
+                    if let Some(text) = text {
+                        res.push_str(&format!(" {text}"));
+                    }
+                    if let Some(id) = x.params["id"].as_i64() {
+                        res.push_str(&format!(" {id}"));
+                    }
+                    res
+                }
+            })
+            .collect()
+    }
+
+    fn multiplexer(clients: usize) -> (Multiplexer, Receiver<Message>, Vec<Receiver<Message>>) {
+        let (server, server_receiver) = crossbeam_channel::unbounded();
+        let mut multiplexer = Multiplexer::new(server);
+        let receivers = (0..clients)
+            .map(|client| {
+                let (sender, receiver) = crossbeam_channel::unbounded();
+                multiplexer.connected(client, sender);
+                receiver
+            })
+            .collect();
+        (multiplexer, server_receiver, receivers)
+    }
+
+    #[test]
+    fn test_renumber_requests() {
+        let (mut m, server, clients) = multiplexer(2);
+        m.from_client(0, hover(1));
+        m.from_client(1, hover(1));
+        assert_eq!(
+            received(&server),
+            vec![
+                "request 0 textDocument/hover",
+                "request 1 textDocument/hover"
+            ]
+        );
+        m.from_server(Message::Response(Response::new_ok(
+            RequestId::from(1),
+            serde_json::Value::Null,
+        )));
+        m.from_server(Message::Response(Response::new_ok(
+            RequestId::from(0),
+            serde_json::Value::Null,
+        )));
+        assert_eq!(received(&clients[0]), vec!["response 1"]);
+        assert_eq!(received(&clients[1]), vec!["response 1"]);
+    }
+
+    #[test]
+    fn test_cancel_request() {
+        let (mut m, server, _clients) = multiplexer(2);
+        m.from_client(0, hover(7));
+        m.from_client(1, hover(7));
+        m.from_client(1, notification(Cancel::METHOD, json!({"id": 7})));
+        // Cancelling a request the server doesn't know about is dropped.
+        m.from_client(0, notification(Cancel::METHOD, json!({"id": 8})));
+        assert_eq!(
+            received(&server),
+            vec![
+                "request 0 textDocument/hover",
+                "request 1 textDocument/hover",
+                "$/cancelRequest 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_close_refcount() {
+        let (mut m, server, _clients) = multiplexer(3);
+        m.from_client(0, open("x"));
+        m.from_client(1, open("x"));
+        m.from_client(2, open("x"));
+        assert_eq!(received(&server), vec!["textDocument/didOpen v1 x"]);
+        m.from_client(0, close());
+        m.disconnected(1);
+        assert_eq!(received(&server), Vec::<String>::new());
+        m.from_client(2, close());
+        assert_eq!(received(&server), vec!["textDocument/didClose"]);
+    }
+
+    #[test]
+    fn test_separate_contents() {
+        let (mut m, server, clients) = multiplexer(2);
+        m.from_client(0, open("x"));
+        m.from_client(1, open("x"));
+        m.from_client(1, change(2, "y"));
+        assert_eq!(
+            received(&server),
+            vec!["textDocument/didOpen v1 x", "textDocument/didChange v2 y"]
+        );
+        // The diagnostics for `y` only go to the client with those contents, with its version.
+        m.from_server(diagnostics(2));
+        assert_eq!(received(&clients[0]), Vec::<String>::new());
+        assert_eq!(
+            received(&clients[1]),
+            vec!["textDocument/publishDiagnostics v2"]
+        );
+        // The server is sent the other client's contents before its request and changes.
+        m.from_client(0, hover(1));
+        m.from_client(0, change(5, "z"));
+        assert_eq!(
+            received(&server),
+            vec![
+                "textDocument/didChange v3 x",
+                "request 0 textDocument/hover",
+                "textDocument/didChange v4 z",
+            ]
+        );
+        // Diagnostics for contents that have since changed are dropped.
+        m.from_server(diagnostics(3));
+        m.from_server(diagnostics(4));
+        assert_eq!(
+            received(&clients[0]),
+            vec!["textDocument/publishDiagnostics v5"]
+        );
+        assert_eq!(received(&clients[1]), Vec::<String>::new());
+        // Once the client whose contents the server has closes the document, the server is sent
+        // the contents of another.
+        m.from_client(0, close());
+        assert_eq!(received(&server), vec!["textDocument/didChange v5 y"]);
+    }
+
+    #[test]
+    fn test_late_client_initialize() {
+        let (mut m, server, clients) = multiplexer(3);
+        m.from_client(
+            0,
+            request(1, Initialize::METHOD, json!({"capabilities": {}})),
+        );
+        m.from_client(
+            1,
+            request(
+                1,
+                Initialize::METHOD,
+                json!({"capabilities": {}, "workspaceFolders": [{"uri": "file:///b", "name": "b"}]}),
+            ),
+        );
+        assert_eq!(received(&server), vec!["request 0 initialize"]);
+        m.from_server(Message::Response(Response::new_ok(
+            RequestId::from(0),
+            json!({"capabilities": {}}),
+        )));
+        assert_eq!(received(&clients[0]), vec!["response 1"]);
+        assert_eq!(received(&clients[1]), vec!["response 1"]);
+        assert_eq!(
+            received(&server),
+            vec!["workspace/didChangeWorkspaceFolders"]
+        );
+        // Only the first client's `initialized` is sent on.
+        m.from_client(0, notification(Initialized::METHOD, json!({})));
+        m.from_client(1, notification(Initialized::METHOD, json!({})));
+        assert_eq!(received(&server), vec!["initialized"]);
+        // Capabilities registered before a client connects are registered with it too.
+        m.from_server(request(
+            1,
+            RegisterCapability::METHOD,
+            json!({"registrations": []}),
+        ));
+        m.from_client(
+            2,
+            request(1, Initialize::METHOD, json!({"capabilities": {}})),
+        );
+        assert_eq!(
+            received(&clients[2]),
+            vec![
+                "response 1",
+                "request \"pyrefly-multiplex-1\" client/registerCapability"
+            ]
+        );
+        assert_eq!(received(&server), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_server_request_routing() {
+        let (mut m, _server, clients) = multiplexer(2);
+        for client in 0..2 {
+            m.from_client(
+                client,
+                request(1, Initialize::METHOD, json!({"capabilities": {}})),
+            );
+        }
+        m.from_server(Message::Response(Response::new_ok(
+            RequestId::from(0),
+            json!({"capabilities": {}}),
+        )));
+        received(&clients[0]);
+        received(&clients[1]);
+        // An edit is only applied by the client that asked for it.
+        m.from_client(1, request(2, "workspace/executeCommand", json!({})));
+        m.from_server(request(1, "workspace/applyEdit", json!({"edit": {}})));
+        assert_eq!(received(&clients[0]), Vec::<String>::new());
+        assert_eq!(received(&clients[1]), vec!["request 1 workspace/applyEdit"]);
+        // Registrations go to every client.
+        m.from_server(request(
+            2,
+            RegisterCapability::METHOD,
+            json!({"registrations": []}),
+        ));
+        assert_eq!(
+            received(&clients[0]),
+            vec!["request 2 client/registerCapability"]
+        );
+        assert_eq!(
+            received(&clients[1]),
+            vec!["request 2 client/registerCapability"]
+        );
+    }
+}
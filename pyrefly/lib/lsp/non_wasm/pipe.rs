@@ -0,0 +1,309 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Talk to the client over a Unix domain socket or a Windows named pipe, rather than stdio.
+//! This is the `pipe` transport of VS Code's language client: the client creates the socket or
+//! pipe, passes its path to the server, and the server connects to it.
+//!
+//! Synchronous reads and writes on the same Windows named pipe block each other, so unlike the
+//! stdio and TCP transports of `lsp_server`, the pipe is driven by an asynchronous runtime.
+
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::Sender;
+use lsp_server::Connection;
+use lsp_server::Message;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+
+/// The threads moving messages between the pipe and the `Connection`, which should be joined
+/// once the server has shut down, like `lsp_server::IoThreads`.
+pub struct PipeThreads {
+    io: JoinHandle<io::Result<()>>,
+    writer: JoinHandle<io::Result<()>>,
+}
+
+impl PipeThreads {
+    pub fn join(self) -> io::Result<()> {
+        let join = |x: JoinHandle<io::Result<()>>, name: &str| match x.join() {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::other(format!("pipe {name} thread panicked"))),
+        };
+        join(self.writer, "writer")?;
+        join(self.io, "io")
+    }
+}
+
+#[cfg(unix)]
+async fn open(path: &Path) -> io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn open(path: &Path) -> io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(path)
+}
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Read one message in the base protocol: headers, a blank line, then `Content-Length` bytes of
+/// JSON. Returns `None` at the end of the stream.
+async fn read_message(reader: &mut (impl AsyncBufRead + Unpin)) -> io::Result<Option<Message>> {
+    let mut size = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            size = Some(value.trim().parse::<usize>().map_err(invalid_data)?);
+        }
+    }
+    let size = size.ok_or_else(|| invalid_data("missing Content-Length header"))?;
+    let mut buf = vec![0; size];
+    reader.read_exact(&mut buf).await?;
+    serde_json::from_slice(&buf).map(Some).map_err(invalid_data)
+}
+
+async fn serve(
+    stream: impl AsyncRead + AsyncWrite,
+    reader_sender: Sender<Message>,
+    mut writer_receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+) -> io::Result<()> {
+    let (read, mut write) = tokio::io::split(stream);
+    // Once reading stops, dropping `reader_sender` tells the server the client has gone.
+    let read = async move {
+        let mut read = BufReader::new(read);
+        while let Some(msg) = read_message(&mut read).await? {
+            let is_exit = matches!(&msg, Message::Notification(x) if x.method == "exit");
+            if reader_sender.send(msg).is_err() || is_exit {
+                break;
+            }
+        }
+        Ok::<_, io::Error>(())
+    };
+    let write = async move {
+        while let Some(bytes) = writer_receiver.recv().await {
+            write.write_all(&bytes).await?;
+            write.flush().await?;
+        }
+        Ok::<_, io::Error>(())
+    };
+    tokio::try_join!(read, write)?;
+    Ok(())
+}
+
+/// Connect to the Unix domain socket or Windows named pipe at `path`, which the client must
+/// already be listening on.
+pub fn connect(path: &Path) -> io::Result<(Connection, PipeThreads)> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()?;
+    let stream = runtime.block_on(open(path))?;
+
+    // The reader must never block, or it would stop the writer running on the same runtime.
+    let (reader_sender, receiver) = crossbeam_channel::unbounded();
+    let (sender, writer_receiver) = crossbeam_channel::unbounded::<Message>();
+    let (bytes_sender, bytes_receiver) = mpsc::unbounded_channel();
+
+    // Messages are sent to a blocking channel, so serialize them on a thread of their own and
+    // hand the bytes to the runtime.
+    let writer = thread::spawn(move || -> io::Result<()> {
+        for msg in writer_receiver {
+            let mut bytes = Vec::new();
+            msg.write(&mut bytes)?;
+            if bytes_sender.send(bytes).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    });
+    let io = thread::spawn(move || runtime.block_on(serve(stream, reader_sender, bytes_receiver)));
+    Ok((Connection { sender, receiver }, PipeThreads { io, writer }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader as StdBufReader;
+    use std::io::Write;
+
+    use lsp_server::Notification;
+    use lsp_server::Request;
+    use lsp_server::RequestId;
+    use lsp_server::Response;
+    use serde_json::json;
+
+    use super::*;
+
+    fn hover() -> Message {
+        Message::Request(Request::new(
+            RequestId::from(1),
+            "textDocument/hover".to_owned(),
+            json!({"textDocument": {"uri": "file:///a.py"}, "position": {"line": 0, "character": 0}}),
+        ))
+    }
+
+    fn exit() -> Message {
+        Message::Notification(Notification::new("exit".to_owned(), json!(null)))
+    }
+
+    fn response() -> Message {
+        Message::Response(Response::new_ok(
+            RequestId::from(1),
+            json!({"contents": "int"}),
+        ))
+    }
+
+    /// The message in the base protocol, as the client would send it.
+    fn frame(msg: &Message) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        msg.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Messages don't implement `PartialEq`, so compare them as JSON.
+    fn json(msg: Option<Message>) -> Option<serde_json::Value> {
+        msg.map(|x| serde_json::to_value(x).unwrap())
+    }
+
+    fn block_on<R>(f: impl Future<Output = R>) -> R {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    #[test]
+    fn test_read_message() {
+        let mut input = frame(&hover());
+        input.extend(frame(&exit()));
+        let mut reader = &input[..];
+        block_on(async {
+            assert_eq!(
+                json(read_message(&mut reader).await.unwrap()),
+                json(Some(hover()))
+            );
+            assert_eq!(
+                json(read_message(&mut reader).await.unwrap()),
+                json(Some(exit()))
+            );
+            assert!(read_message(&mut reader).await.unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_read_message_headers() {
+        // Header names are case insensitive, and other headers are ignored.
+        let body = serde_json::to_vec(&json(Some(exit()))).unwrap();
+        let mut input = format!(
+            "content-length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        input.extend(body);
+        let mut reader = &input[..];
+        let msg = block_on(read_message(&mut reader)).unwrap();
+        assert_eq!(json(msg), json(Some(exit())));
+    }
+
+    #[test]
+    fn test_read_message_missing_header() {
+        let mut reader = &b"Content-Type: application/vscode-jsonrpc\r\n\r\n{}"[..];
+        let err = block_on(read_message(&mut reader)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "missing Content-Length header");
+    }
+
+    #[test]
+    fn test_read_message_truncated() {
+        let input = frame(&hover());
+        let mut reader = &input[..input.len() - 1];
+        let err = block_on(read_message(&mut reader)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_message_partial() {
+        // The message arrives a few bytes at a time, split in the middle of headers and body.
+        let input = frame(&hover());
+        let (mut write, read) = tokio::io::duplex(4);
+        let send = async move {
+            for chunk in input.chunks(3) {
+                write.write_all(chunk).await.unwrap();
+            }
+        };
+        let receive = async move {
+            let mut read = BufReader::new(read);
+            let first = read_message(&mut read).await.unwrap();
+            (first, read_message(&mut read).await.unwrap())
+        };
+        let ((), (first, second)) = block_on(async { tokio::join!(send, receive) });
+        assert_eq!(json(first), json(Some(hover())));
+        assert!(second.is_none());
+    }
+
+    #[test]
+    fn test_serve() {
+        let (client, server) = tokio::io::duplex(64);
+        let (reader_sender, receiver) = crossbeam_channel::unbounded();
+        let (bytes_sender, bytes_receiver) = mpsc::unbounded_channel();
+        bytes_sender.send(frame(&response())).unwrap();
+        drop(bytes_sender);
+        let client = async move {
+            let (read, mut write) = tokio::io::split(client);
+            write.write_all(&frame(&hover())).await.unwrap();
+            write.write_all(&frame(&exit())).await.unwrap();
+            read_message(&mut BufReader::new(read)).await.unwrap()
+        };
+        let (served, received) =
+            block_on(async { tokio::join!(serve(server, reader_sender, bytes_receiver), client) });
+        served.unwrap();
+        assert_eq!(json(received), json(Some(response())));
+        let sent = receiver.try_iter().map(Some).map(json).collect::<Vec<_>>();
+        assert_eq!(sent, vec![json(Some(hover())), json(Some(exit()))]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_connect_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pyrefly.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let (connection, threads) = connect(&path).unwrap();
+        let (mut client, _) = listener.accept().unwrap();
+
+        client.write_all(&frame(&hover())).unwrap();
+        assert_eq!(json(connection.receiver.recv().ok()), json(Some(hover())));
+        connection.sender.send(response()).unwrap();
+        let received = Message::read(&mut StdBufReader::new(&client)).unwrap();
+        assert_eq!(json(received), json(Some(response())));
+
+        client.write_all(&frame(&exit())).unwrap();
+        assert_eq!(json(connection.receiver.recv().ok()), json(Some(exit())));
+        drop(connection);
+        threads.join().unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Render the Markdown that hover and completion documentation are written in as plain text, for
+//! clients that declare they can't display Markdown.
+
+use lsp_types::Documentation;
+use lsp_types::Hover;
+use lsp_types::HoverContents;
+use lsp_types::MarkedString;
+use lsp_types::MarkupContent;
+use lsp_types::MarkupKind;
+
+/// The text a reader would see if `markdown` was rendered: code fences, inline code markers, bold
+/// markers, escapes and headings are dropped and links are replaced by their text, while code
+/// blocks are kept as they are.
+pub fn markdown_to_plaintext(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(line.to_owned());
+            continue;
+        }
+        // Trailing spaces are hard line breaks, and docstrings indent with `&nbsp;`.
+        let line = line.trim_end_matches(' ').replace("&nbsp;", " ");
+        let mut res = String::with_capacity(line.len());
+        push_inline(strip_heading(&line), &mut res);
+        lines.push(res);
+    }
+    lines.join("\n")
+}
+
+fn strip_heading(line: &str) -> &str {
+    let rest = line.trim_start_matches('#');
+    if rest.len() < line.len()
+        && let Some(rest) = rest.strip_prefix(' ')
+    {
+        rest
+    } else {
+        line
+    }
+}
+
+/// Push the text of the inline Markdown `text` onto `res`.
+fn push_inline(text: &str, res: &mut String) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' && rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) {
+            res.push_str(&rest[1..2]);
+            rest = &rest[2..];
+        } else if c == '`' {
+            rest = &rest[1..];
+        } else if let Some(after) = rest.strip_prefix("**") {
+            rest = after;
+        } else if c == '['
+            && let Some((link_text, after)) = split_link(&rest[1..])
+        {
+            push_inline(link_text, res);
+            rest = after;
+        } else {
+            res.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+}
+
+/// Split `text`, which follows the `[` of a link `[text](url)`, into the text of the link and what
+/// follows the link, or `None` if it isn't a link.
+fn split_link(text: &str) -> Option<(&str, &str)> {
+    let close = text.find("](")?;
+    let link_text = &text[..close];
+    if link_text.contains(']') {
+        return None;
+    }
+    let after_url = &text[close + 2..];
+    let end = after_url.find(')')?;
+    Some((link_text, &after_url[end + 1..]))
+}
+
+/// Convert the Markdown contents of `hover` to plain text.
+pub fn hover_to_plaintext(hover: Hover) -> Hover {
+    let value = match hover.contents {
+        HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            ..
+        }) => return hover,
+        HoverContents::Markup(MarkupContent { value, .. }) => markdown_to_plaintext(&value),
+        HoverContents::Scalar(x) => marked_string_to_plaintext(x),
+        HoverContents::Array(xs) => xs
+            .into_iter()
+            .map(marked_string_to_plaintext)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value,
+        }),
+        range: hover.range,
+    }
+}
+
+fn marked_string_to_plaintext(x: MarkedString) -> String {
+    match x {
+        MarkedString::String(x) => markdown_to_plaintext(&x),
+        MarkedString::LanguageString(x) => x.value,
+    }
+}
+
+/// Convert Markdown completion item documentation to plain text.
+pub fn documentation_to_plaintext(documentation: Documentation) -> Documentation {
+    match documentation {
+        Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }) => Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: markdown_to_plaintext(&value),
+        }),
+        x => x,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_plaintext() {
+        assert_eq!(
+            markdown_to_plaintext(
+                "```python\n(function) f: (x: int) -> Box[MyClass]\n```\n\n`lib.f`\n---\nDo the thing.  \n  \n**Args:**  \n- `x` (int): The input.  \n&nbsp;&nbsp;Indented.\n\nGo to [Box](file:///lib.py#L3,7)\\[[MyClass](file:///lib.py#L9,7)\\]"
+            ),
+            "(function) f: (x: int) -> Box[MyClass]\n\nlib.f\n---\nDo the thing.\n\nArgs:\n- x (int): The input.\n  Indented.\n\nGo to Box[MyClass]"
+        );
+        assert_eq!(
+            markdown_to_plaintext("## Notes\nSee list[int] or [the docs](https://example.com)."),
+            "Notes\nSee list[int] or the docs."
+        );
+        assert_eq!(
+            markdown_to_plaintext("```\n**kept** `as is`\n```"),
+            "**kept** `as is`"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Memoized responses to the requests about an open file whose answers only depend on what the
+//! server knows, such as hover, semantic tokens and document symbols. Clients repeat these when
+//! nothing has changed, e.g. asking for the semantic tokens of a file whenever it gets focus, and
+//! the cached response is returned instead of being recomputed.
+
+use std::collections::HashMap;
+
+use lsp_types::request::Request;
+use pyrefly_util::lock::Mutex;
+use serde_json::Value;
+
+/// The most responses we keep. Hovering over a file adds one for every position, so once there
+/// are more we start again rather than grow without bound.
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct QueryKey {
+    method: &'static str,
+    /// The version of the document the request is about.
+    version: i32,
+    /// The parameters of the request, serialized.
+    params: String,
+}
+
+/// The responses to requests, keyed by their method, parameters and the version of their document.
+///
+/// The answers to these requests also depend on other files and the configuration, so the cache
+/// must be cleared whenever anything the server knows changes, not just the document.
+#[derive(Default)]
+pub struct QueryCache {
+    entries: Mutex<HashMap<QueryKey, Value>>,
+}
+
+impl QueryCache {
+    /// The response to the request `R` with `params`, about version `version` of its document,
+    /// computed by `compute` unless a previous request got the same response.
+    pub fn get_or_compute<R: Request>(
+        &self,
+        version: i32,
+        params: &R::Params,
+        compute: impl FnOnce() -> R::Result,
+    ) -> Value {
+        let key = QueryKey {
+            method: R::METHOD,
+            version,
+            params: serde_json::to_string(params).unwrap(),
+        };
+        if let Some(response) = self.entries.lock().get(&key) {
+            return response.clone();
+        }
+        let response = serde_json::to_value(compute()).unwrap();
+        let mut entries = self.entries.lock();
+        if entries.len() >= MAX_ENTRIES {
+            entries.clear();
+        }
+        entries.insert(key, response.clone());
+        response
+    }
+
+    /// Forget every response, as something they may depend on has changed.
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use lsp_types::Hover;
+    use lsp_types::HoverContents;
+    use lsp_types::HoverParams;
+    use lsp_types::MarkedString;
+    use lsp_types::Position;
+    use lsp_types::TextDocumentIdentifier;
+    use lsp_types::TextDocumentPositionParams;
+    use lsp_types::Url;
+    use lsp_types::request::HoverRequest;
+
+    use super::*;
+
+    fn params(line: u32) -> HoverParams {
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::parse("file:///main.py").unwrap(),
+                },
+                position: Position::new(line, 0),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let cache = QueryCache::default();
+        let computed = Cell::new(0);
+        let hover = |version, line| {
+            cache.get_or_compute::<HoverRequest>(version, &params(line), || {
+                computed.set(computed.get() + 1);
+                Some(Hover {
+                    contents: HoverContents::Scalar(MarkedString::String(format!("{line}"))),
+                    range: None,
+                })
+            })
+        };
+        let first = hover(1, 0);
+        assert_eq!(hover(1, 0), first);
+        assert_eq!(computed.get(), 1);
+        // Another position, or another version of the document, is computed again.
+        assert_ne!(hover(1, 1), first);
+        hover(2, 0);
+        assert_eq!(computed.get(), 3);
+        cache.clear();
+        hover(2, 0);
+        assert_eq!(computed.get(), 4);
+    }
+}
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -25,6 +26,11 @@ use lsp_types::DidChangeWorkspaceFoldersParams;
 use lsp_types::DidCloseTextDocumentParams;
 use lsp_types::DidOpenTextDocumentParams;
 use lsp_types::DidSaveTextDocumentParams;
+use lsp_types::Url;
+use lsp_types::request::Completion;
+use lsp_types::request::Request as _;
+use lsp_types::request::SignatureHelpRequest;
+use pyrefly_util::lock::Mutex;
 
 pub enum LspEvent {
     // Part 1: Events that the server should try to handle first.
@@ -48,6 +54,9 @@ pub enum LspEvent {
     DidChangeConfiguration(DidChangeConfigurationParams),
     LspResponse(Response),
     LspRequest(Request),
+    /// A request about a document that was changed after the request was sent, so the server
+    /// should drop it rather than answer it for a version of the document the client no longer has.
+    SupersededRequest(Request),
     Exit,
 }
 
@@ -73,11 +82,21 @@ impl LspEvent {
             | Self::DidChangeConfiguration(_)
             | Self::LspResponse(_)
             | Self::Exit => LspEventKind::Mutation,
-            Self::LspRequest(_) => LspEventKind::Query,
+            Self::LspRequest(_) | Self::SupersededRequest(_) => LspEventKind::Query,
         }
     }
 }
 
+/// Requests where VS Code will use results from previous document versions, so we really don't
+/// want to implicitly cancel them, whether the document changes or anything else does.
+pub const ONLY_ONCE: &[&str] = &[Completion::METHOD, SignatureHelpRequest::METHOD];
+
+/// The document a request is about, if it has one.
+fn request_document(x: &Request) -> Option<Url> {
+    let uri = x.params.get("textDocument")?.get("uri")?;
+    serde_json::from_value(uri.clone()).ok()
+}
+
 #[derive(Clone, Dupe)]
 pub struct LspQueue(Arc<LspQueueInner>);
 
@@ -86,7 +105,13 @@ struct LspQueueInner {
     id: AtomicUsize,
     /// The index of the last event we are aware of that is a mutation. 0 = unknown.
     last_mutation: AtomicUsize,
-    normal: (Sender<(usize, LspEvent)>, Receiver<(usize, LspEvent)>),
+    /// The events that aren't priority events, in the order they will be received. These are kept
+    /// in a queue we can edit, rather than a channel, so that `send` can coalesce changes to a
+    /// document and drop the requests they supersede.
+    normal: Mutex<VecDeque<(usize, LspEvent)>>,
+    /// Receives one message for each event pushed onto `normal`, so `recv` can wait on it
+    /// alongside the priority events.
+    normal_ready: (Sender<()>, Receiver<()>),
     priority: (Sender<(usize, LspEvent)>, Receiver<(usize, LspEvent)>),
 }
 
@@ -95,7 +120,8 @@ impl LspQueue {
         Self(Arc::new(LspQueueInner {
             id: AtomicUsize::new(1),
             last_mutation: AtomicUsize::new(0),
-            normal: crossbeam_channel::unbounded(),
+            normal: Mutex::new(VecDeque::new()),
+            normal_ready: crossbeam_channel::unbounded(),
             priority: crossbeam_channel::unbounded(),
         }))
     }
@@ -103,21 +129,82 @@ impl LspQueue {
     #[allow(clippy::result_large_err)]
     pub fn send(&self, x: LspEvent) -> Result<(), SendError<LspEvent>> {
         let kind = x.kind();
+        if kind == LspEventKind::Priority {
+            let id = self.0.id.fetch_add(1, Ordering::Relaxed);
+            return self
+                .0
+                .priority
+                .0
+                .send((id, x))
+                .map_err(|x| SendError(x.0.1));
+        }
+        let mut normal = self.0.normal.lock();
+        let x = match x {
+            LspEvent::DidChangeTextDocument(params) => {
+                match Self::coalesce_change(&mut normal, params) {
+                    Some(params) => LspEvent::DidChangeTextDocument(params),
+                    // Merged into a change that is already queued.
+                    None => return Ok(()),
+                }
+            }
+            x => x,
+        };
         let id = self.0.id.fetch_add(1, Ordering::Relaxed);
         if kind == LspEventKind::Mutation {
             // This is gently dubious, as we might race condition and it might not really be the last
             // mutation. But it's good enough for now.
             self.0.last_mutation.store(id, Ordering::Relaxed);
         }
-        if kind == LspEventKind::Priority {
-            self.0
-                .priority
-                .0
-                .send((id, x))
-                .map_err(|x| SendError(x.0.1))
-        } else {
-            self.0.normal.0.send((id, x)).map_err(|x| SendError(x.0.1))
+        normal.push_back((id, x));
+        drop(normal);
+        // We hold the receiver too, so this can't fail.
+        let _ = self.0.normal_ready.0.send(());
+        Ok(())
+    }
+
+    /// A change to a document makes the queued requests about that document out of date, so turn
+    /// them into `SupersededRequest`s, except for the `ONLY_ONCE` ones. If the last queued change to the document has only requests
+    /// queued after it, append this change to it, so a burst of edits is handled as one, and return
+    /// `None`. Otherwise, return the change to be queued.
+    fn coalesce_change(
+        normal: &mut VecDeque<(usize, LspEvent)>,
+        mut params: DidChangeTextDocumentParams,
+    ) -> Option<DidChangeTextDocumentParams> {
+        let uri = &params.text_document.uri;
+        let mut only_requests = true;
+        let mut merge_into = None;
+        for (i, (_, x)) in normal.iter_mut().enumerate().rev() {
+            match x {
+                LspEvent::LspRequest(request)
+                    if !ONLY_ONCE.contains(&request.method.as_str())
+                        && request_document(request).as_ref() == Some(uri) =>
+                {
+                    *x = LspEvent::SupersededRequest(request.clone());
+                }
+                LspEvent::LspRequest(_) | LspEvent::SupersededRequest(_) => {}
+                LspEvent::DidChangeTextDocument(previous)
+                    if only_requests && previous.text_document.uri == *uri =>
+                {
+                    merge_into = Some(i);
+                    only_requests = false;
+                }
+                _ => only_requests = false,
+            }
         }
+        match merge_into.map(|i| &mut normal[i].1) {
+            Some(LspEvent::DidChangeTextDocument(previous)) => {
+                previous.text_document.version = params.text_document.version;
+                previous.content_changes.append(&mut params.content_changes);
+                None
+            }
+            _ => Some(params),
+        }
+    }
+
+    /// Whether there is a mutation event in the queue that hasn't been received yet.
+    /// Like `recv`, this might say false when there is one, but never true when there isn't.
+    pub fn has_pending_mutation(&self) -> bool {
+        self.0.last_mutation.load(Ordering::Relaxed) != 0
     }
 
     /// Return a bool indicating whether there is a subsequent mutation event in the queue,
@@ -130,12 +217,20 @@ impl LspQueue {
         // Biased selector will pick the receiver with lower index over higher ones,
         // so we register priority_events_receiver first.
         let priority_receiver_index = event_receiver_selector.recv(&self.0.priority.1);
-        let queued_events_receiver_index = event_receiver_selector.recv(&self.0.normal.1);
+        let queued_events_receiver_index = event_receiver_selector.recv(&self.0.normal_ready.1);
 
         let selected = event_receiver_selector.select();
         let (id, x) = match selected.index() {
             i if i == priority_receiver_index => selected.recv(&self.0.priority.1)?,
-            i if i == queued_events_receiver_index => selected.recv(&self.0.normal.1)?,
+            i if i == queued_events_receiver_index => {
+                selected.recv(&self.0.normal_ready.1)?;
+                // Every message on `normal_ready` is sent after pushing an event.
+                self.0
+                    .normal
+                    .lock()
+                    .pop_front()
+                    .expect("LspQueue event missing")
+            }
             _ => unreachable!(),
         };
         let mut last_mutation = self.0.last_mutation.load(Ordering::Relaxed);
@@ -150,6 +245,8 @@ impl LspQueue {
 pub struct HeavyTask(Box<dyn FnOnce() + Send + Sync + 'static>);
 
 struct HeavyTaskQueueInner {
+    /// The number of tasks queued that haven't finished running.
+    pending: AtomicUsize,
     task_sender: Sender<HeavyTask>,
     task_receiver: Receiver<HeavyTask>,
     stop_sender: Sender<()>,
@@ -165,6 +262,7 @@ impl HeavyTaskQueue {
         let (task_sender, task_receiver) = crossbeam_channel::unbounded();
         let (stop_sender, stop_receiver) = crossbeam_channel::unbounded();
         Self(Arc::new(HeavyTaskQueueInner {
+            pending: AtomicUsize::new(0),
             task_sender,
             task_receiver,
             stop_sender,
@@ -173,6 +271,7 @@ impl HeavyTaskQueue {
     }
 
     pub fn queue_task(&self, f: Box<dyn FnOnce() + Send + Sync + 'static>) {
+        self.0.pending.fetch_add(1, Ordering::Relaxed);
         self.0
             .task_sender
             .send(HeavyTask(f))
@@ -199,12 +298,18 @@ impl HeavyTaskQueue {
                         .recv(&self.0.task_receiver)
                         .expect("Failed to receive heavy task");
                     (task.0)();
+                    self.0.pending.fetch_sub(1, Ordering::Relaxed);
                 }
                 _ => unreachable!(),
             };
         }
     }
 
+    /// The number of tasks queued that haven't finished running, including the one running now.
+    pub fn pending(&self) -> usize {
+        self.0.pending.load(Ordering::Relaxed)
+    }
+
     /// Make `run_until_stopped` exit after finishing the current task.
     pub fn stop(&self) {
         self.0
@@ -213,3 +318,123 @@ impl HeavyTaskQueue {
             .expect("Failed to stop the queue");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::TextDocumentContentChangeEvent;
+    use lsp_types::VersionedTextDocumentIdentifier;
+    use pyrefly_util::prelude::SliceExt;
+    use serde_json::json;
+
+    use super::*;
+
+    fn change(uri: &str, version: i32, text: &str) -> LspEvent {
+        LspEvent::DidChangeTextDocument(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: Url::parse(uri).unwrap(),
+                version,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: text.to_owned(),
+            }],
+        })
+    }
+
+    fn request(id: i32, method: &str, uri: &str) -> LspEvent {
+        LspEvent::LspRequest(Request::new(
+            RequestId::from(id),
+            method.to_owned(),
+            json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 0}}),
+        ))
+    }
+
+    fn hover(id: i32, uri: &str) -> LspEvent {
+        request(id, "textDocument/hover", uri)
+    }
+
+    /// Receive the queued events, summarized as strings.
+    fn recv_all(queue: &LspQueue, n: usize) -> Vec<String> {
+        (0..n)
+            .map(|_| match queue.recv().unwrap().1 {
+                LspEvent::DidChangeTextDocument(x) => format!(
+                    "change {} v{}: {}",
+                    x.text_document.uri.path(),
+                    x.text_document.version,
+                    x.content_changes.map(|x| x.text.as_str()).join(",")
+                ),
+                LspEvent::LspRequest(x) => format!("request {}", x.id),
+                LspEvent::SupersededRequest(x) => format!("superseded {}", x.id),
+                _ => "other".to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_coalesce_changes() {
+        let queue = LspQueue::new();
+        for x in [
+            change("file:///a.py", 1, "x"),
+            hover(1, "file:///a.py"),
+            hover(2, "file:///b.py"),
+            change("file:///a.py", 2, "y"),
+            change("file:///a.py", 3, "z"),
+            hover(3, "file:///a.py"),
+        ] {
+            queue.send(x).unwrap();
+        }
+        assert_eq!(
+            recv_all(&queue, 4),
+            vec![
+                "change /a.py v3: x,y,z",
+                "superseded 1",
+                "request 2",
+                "request 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_changes_only_past_requests() {
+        let queue = LspQueue::new();
+        for x in [
+            change("file:///a.py", 1, "x"),
+            change("file:///b.py", 1, "y"),
+            change("file:///a.py", 2, "z"),
+        ] {
+            queue.send(x).unwrap();
+        }
+        assert_eq!(
+            recv_all(&queue, 3),
+            vec![
+                "change /a.py v1: x",
+                "change /b.py v1: y",
+                "change /a.py v2: z",
+            ]
+        );
+        assert!(!queue.has_pending_mutation());
+    }
+
+    #[test]
+    fn test_coalesce_changes_keeps_only_once_requests() {
+        let queue = LspQueue::new();
+        for x in [
+            request(1, Completion::METHOD, "file:///a.py"),
+            request(2, SignatureHelpRequest::METHOD, "file:///a.py"),
+            hover(3, "file:///a.py"),
+            change("file:///a.py", 1, "x"),
+        ] {
+            queue.send(x).unwrap();
+        }
+        assert_eq!(
+            recv_all(&queue, 4),
+            vec![
+                "request 1",
+                "request 2",
+                "superseded 3",
+                "change /a.py v1: x",
+            ]
+        );
+    }
+}
@@ -7,13 +7,19 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::iter::once;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
 
 use dupe::Dupe;
 use itertools::Itertools;
@@ -23,6 +29,13 @@ use lsp_server::Message;
 use lsp_server::Request;
 use lsp_server::RequestId;
 use lsp_server::Response;
+use lsp_types::CallHierarchyIncomingCall;
+use lsp_types::CallHierarchyIncomingCallsParams;
+use lsp_types::CallHierarchyItem;
+use lsp_types::CallHierarchyOutgoingCall;
+use lsp_types::CallHierarchyOutgoingCallsParams;
+use lsp_types::CallHierarchyPrepareParams;
+use lsp_types::CallHierarchyServerCapability;
 use lsp_types::CodeAction;
 use lsp_types::CodeActionKind;
 use lsp_types::CodeActionOptions;
@@ -30,6 +43,7 @@ use lsp_types::CodeActionOrCommand;
 use lsp_types::CodeActionParams;
 use lsp_types::CodeActionProviderCapability;
 use lsp_types::CodeActionResponse;
+use lsp_types::CompletionItem;
 use lsp_types::CompletionList;
 use lsp_types::CompletionOptions;
 use lsp_types::CompletionParams;
@@ -37,6 +51,9 @@ use lsp_types::CompletionResponse;
 use lsp_types::ConfigurationItem;
 use lsp_types::ConfigurationParams;
 use lsp_types::Diagnostic;
+use lsp_types::DiagnosticOptions;
+use lsp_types::DiagnosticRelatedInformation;
+use lsp_types::DiagnosticServerCapabilities;
 use lsp_types::DiagnosticSeverity;
 use lsp_types::DiagnosticTag;
 use lsp_types::DidChangeConfigurationParams;
@@ -52,6 +69,9 @@ use lsp_types::DocumentDiagnosticParams;
 use lsp_types::DocumentDiagnosticReport;
 use lsp_types::DocumentHighlight;
 use lsp_types::DocumentHighlightParams;
+use lsp_types::DocumentLink;
+use lsp_types::DocumentLinkOptions;
+use lsp_types::DocumentLinkParams;
 use lsp_types::DocumentSymbol;
 use lsp_types::DocumentSymbolParams;
 use lsp_types::DocumentSymbolResponse;
@@ -70,8 +90,13 @@ use lsp_types::HoverProviderCapability;
 use lsp_types::InitializeParams;
 use lsp_types::InlayHint;
 use lsp_types::InlayHintLabel;
+use lsp_types::InlayHintOptions;
 use lsp_types::InlayHintParams;
+use lsp_types::InlayHintServerCapabilities;
+use lsp_types::InlayHintTooltip;
 use lsp_types::Location;
+use lsp_types::MarkupKind;
+use lsp_types::MessageType;
 use lsp_types::NumberOrString;
 use lsp_types::OneOf;
 use lsp_types::Position;
@@ -83,10 +108,14 @@ use lsp_types::ReferenceParams;
 use lsp_types::Registration;
 use lsp_types::RegistrationParams;
 use lsp_types::RelatedFullDocumentDiagnosticReport;
+use lsp_types::RelatedUnchangedDocumentDiagnosticReport;
 use lsp_types::RelativePattern;
 use lsp_types::RenameFilesParams;
 use lsp_types::RenameOptions;
 use lsp_types::RenameParams;
+use lsp_types::SelectionRange;
+use lsp_types::SelectionRangeParams;
+use lsp_types::SelectionRangeProviderCapability;
 use lsp_types::SemanticTokens;
 use lsp_types::SemanticTokensFullOptions;
 use lsp_types::SemanticTokensOptions;
@@ -95,26 +124,39 @@ use lsp_types::SemanticTokensRangeParams;
 use lsp_types::SemanticTokensRangeResult;
 use lsp_types::SemanticTokensResult;
 use lsp_types::SemanticTokensServerCapabilities;
+use lsp_types::ShowMessageParams;
 use lsp_types::SignatureHelp;
 use lsp_types::SignatureHelpOptions;
 use lsp_types::SignatureHelpParams;
 use lsp_types::SymbolInformation;
+use lsp_types::TextDocumentContentChangeEvent;
 use lsp_types::TextDocumentIdentifier;
 use lsp_types::TextDocumentPositionParams;
 use lsp_types::TextDocumentSyncCapability;
 use lsp_types::TextDocumentSyncKind;
 use lsp_types::TextEdit;
 use lsp_types::TypeDefinitionProviderCapability;
+use lsp_types::TypeHierarchyItem;
+use lsp_types::TypeHierarchyPrepareParams;
+use lsp_types::TypeHierarchySubtypesParams;
+use lsp_types::TypeHierarchySupertypesParams;
+use lsp_types::UnchangedDocumentDiagnosticReport;
 use lsp_types::Unregistration;
 use lsp_types::UnregistrationParams;
 use lsp_types::Url;
 use lsp_types::VersionedTextDocumentIdentifier;
 use lsp_types::WatchKind;
 use lsp_types::WorkspaceClientCapabilities;
+use lsp_types::WorkspaceDiagnosticParams;
+use lsp_types::WorkspaceDiagnosticReport;
+use lsp_types::WorkspaceDiagnosticReportResult;
+use lsp_types::WorkspaceDocumentDiagnosticReport;
 use lsp_types::WorkspaceEdit;
 use lsp_types::WorkspaceFoldersServerCapabilities;
+use lsp_types::WorkspaceFullDocumentDiagnosticReport;
 use lsp_types::WorkspaceServerCapabilities;
 use lsp_types::WorkspaceSymbolResponse;
+use lsp_types::WorkspaceUnchangedDocumentDiagnosticReport;
 use lsp_types::notification::Cancel;
 use lsp_types::notification::DidChangeConfiguration;
 use lsp_types::notification::DidChangeTextDocument;
@@ -126,10 +168,15 @@ use lsp_types::notification::DidSaveTextDocument;
 use lsp_types::notification::Exit;
 use lsp_types::notification::Notification as _;
 use lsp_types::notification::PublishDiagnostics;
+use lsp_types::notification::ShowMessage;
+use lsp_types::request::CallHierarchyIncomingCalls;
+use lsp_types::request::CallHierarchyOutgoingCalls;
+use lsp_types::request::CallHierarchyPrepare;
 use lsp_types::request::CodeActionRequest;
 use lsp_types::request::Completion;
 use lsp_types::request::DocumentDiagnosticRequest;
 use lsp_types::request::DocumentHighlightRequest;
+use lsp_types::request::DocumentLinkRequest;
 use lsp_types::request::DocumentSymbolRequest;
 use lsp_types::request::FoldingRangeRequest;
 use lsp_types::request::GotoDefinition;
@@ -138,22 +185,31 @@ use lsp_types::request::GotoTypeDefinitionParams;
 use lsp_types::request::GotoTypeDefinitionResponse;
 use lsp_types::request::HoverRequest;
 use lsp_types::request::InlayHintRequest;
+use lsp_types::request::InlayHintResolveRequest;
 use lsp_types::request::PrepareRenameRequest;
 use lsp_types::request::References;
 use lsp_types::request::RegisterCapability;
 use lsp_types::request::Rename;
 use lsp_types::request::Request as _;
+use lsp_types::request::SelectionRangeRequest;
 use lsp_types::request::SemanticTokensFullRequest;
 use lsp_types::request::SemanticTokensRangeRequest;
 use lsp_types::request::SemanticTokensRefresh;
 use lsp_types::request::SignatureHelpRequest;
+use lsp_types::request::TypeHierarchyPrepare;
+use lsp_types::request::TypeHierarchySubtypes;
+use lsp_types::request::TypeHierarchySupertypes;
 use lsp_types::request::UnregisterCapability;
 use lsp_types::request::WillRenameFiles;
 use lsp_types::request::WorkspaceConfiguration;
+use lsp_types::request::WorkspaceDiagnosticRefresh;
+use lsp_types::request::WorkspaceDiagnosticRequest;
 use lsp_types::request::WorkspaceSymbolRequest;
 use pyrefly_build::handle::Handle;
 use pyrefly_config::config::ConfigSource;
+use pyrefly_config::config::ModuleOrigin;
 use pyrefly_python::PYTHON_EXTENSIONS;
+use pyrefly_python::module::Module;
 use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
@@ -168,12 +224,14 @@ use pyrefly_util::prelude::VecExt;
 use pyrefly_util::task_heap::CancellationHandle;
 use pyrefly_util::task_heap::Cancelled;
 use pyrefly_util::watch_pattern::WatchPattern;
+use ruff_text_size::TextRange;
 use serde::Deserialize;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
+use tracing::trace_span;
 
 use crate::commands::lsp::IndexingMode;
 use crate::config::config::ConfigFile;
@@ -191,23 +249,61 @@ use crate::lsp::non_wasm::module_helpers::make_open_handle;
 use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
 use crate::lsp::non_wasm::module_helpers::to_lsp_location;
 use crate::lsp::non_wasm::module_helpers::to_real_path;
+use crate::lsp::non_wasm::plaintext::documentation_to_plaintext;
+use crate::lsp::non_wasm::plaintext::hover_to_plaintext;
+use crate::lsp::non_wasm::query_cache::QueryCache;
 use crate::lsp::non_wasm::queue::HeavyTaskQueue;
 use crate::lsp::non_wasm::queue::LspEvent;
 use crate::lsp::non_wasm::queue::LspQueue;
+use crate::lsp::non_wasm::queue::ONLY_ONCE;
+use crate::lsp::non_wasm::status::OutgoingRequests;
+use crate::lsp::non_wasm::status::StatusReporter;
 use crate::lsp::non_wasm::transaction_manager::TransactionManager;
 use crate::lsp::non_wasm::workspace::LspAnalysisConfig;
 use crate::lsp::non_wasm::workspace::Workspace;
 use crate::lsp::non_wasm::workspace::Workspaces;
+use crate::lsp::wasm::check_files::CheckFiles;
+use crate::lsp::wasm::check_files::CheckFilesParams;
+use crate::lsp::wasm::check_files::CheckFilesResult;
+use crate::lsp::wasm::check_files::FileDiagnostics;
+use crate::lsp::wasm::check_snippet::CheckSnippet;
+use crate::lsp::wasm::check_snippet::CheckSnippetParams;
+use crate::lsp::wasm::check_snippet::CheckSnippetResult;
 use crate::lsp::wasm::hover::get_hover;
+use crate::lsp::wasm::move_symbol::MoveSymbol;
+use crate::lsp::wasm::move_symbol::MoveSymbolParams;
+use crate::lsp::wasm::move_symbol::move_symbol;
 use crate::lsp::wasm::notebook::NotebookDocumentSyncOptions;
 use crate::lsp::wasm::notebook::NotebookDocumentSyncRegistrationOptions;
 use crate::lsp::wasm::provide_type::ProvideType;
 use crate::lsp::wasm::provide_type::ProvideTypeResponse;
 use crate::lsp::wasm::provide_type::provide_type;
+use crate::lsp::wasm::rename_preview::RenamePreview;
+use crate::lsp::wasm::rename_preview::rename_preview;
+use crate::lsp::wasm::server_status::ServerStatus;
+use crate::lsp::wasm::server_status::ServerStatusResult;
+use crate::lsp::wasm::speculative_check::DiagnosticsDelta;
+use crate::lsp::wasm::speculative_check::SpeculativeCheck;
+use crate::lsp::wasm::speculative_check::SpeculativeCheckParams;
+use crate::lsp::wasm::speculative_check::SpeculativeCheckResult;
+use crate::lsp::wasm::speculative_check::SpeculativeEdit;
+use crate::lsp::wasm::speculative_check::diagnostics_delta;
+use crate::lsp::wasm::status::Status;
+use crate::lsp::wasm::trace_import::TraceImport;
+use crate::lsp::wasm::trace_import::TraceImportParams;
+use crate::lsp::wasm::trace_import::TraceImportResult;
+use crate::lsp::wasm::unreachable_ranges::UnreachableRanges;
+use crate::lsp::wasm::unreachable_ranges::UnreachableRangesParams;
 use crate::lsp::wasm::will_rename_files::will_rename_files;
+use crate::module::import_trace::ImportTrace;
+use crate::state::ide::module_origin;
 use crate::state::lsp::DisplayTypeErrors;
 use crate::state::lsp::FindDefinitionItemWithDocstring;
 use crate::state::lsp::FindPreference;
+use crate::state::lsp::InlayHintConfig;
+use crate::state::lsp::call_hierarchy::CallHierarchyTarget;
+use crate::state::lsp::call_hierarchy::incoming_calls;
+use crate::state::lsp::type_hierarchy::subtypes;
 use crate::state::require::Require;
 use crate::state::semantic_tokens::SemanticTokensLegends;
 use crate::state::semantic_tokens::disabled_ranges_for_module;
@@ -259,7 +355,7 @@ pub trait TspInterface {
 }
 
 /// Until we upgrade lsp-types to 0.96 or newer, we'll need to patch in the notebook document
-/// sync capabilities
+/// sync and type hierarchy capabilities
 #[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerCapabilities {
@@ -272,6 +368,12 @@ pub struct ServerCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notebook_document_sync:
         Option<OneOf<NotebookDocumentSyncOptions, NotebookDocumentSyncRegistrationOptions>>,
+
+    /// The server provides type hierarchy support.
+    ///
+    /// @since 3.17.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_hierarchy_provider: Option<bool>,
 }
 
 #[derive(Clone, Dupe)]
@@ -316,6 +418,8 @@ pub struct Server {
     initialize_params: InitializeParams,
     indexing_mode: IndexingMode,
     workspace_indexing_limit: usize,
+    /// The inlay hints to show in workspaces whose settings don't configure them.
+    default_inlay_hints: InlayHintConfig,
     state: Arc<State>,
     open_files: Arc<RwLock<HashMap<PathBuf, Arc<String>>>>,
     /// A set of configs where we have already indexed all the files within the config.
@@ -327,10 +431,99 @@ pub struct Server {
     indexed_workspaces: Mutex<HashSet<PathBuf>>,
     cancellation_handles: Arc<Mutex<HashMap<RequestId, CancellationHandle>>>,
     workspaces: Arc<Workspaces>,
-    outgoing_request_id: AtomicI32,
-    outgoing_requests: Mutex<HashMap<RequestId, Request>>,
+    outgoing_request_id: Arc<AtomicI32>,
+    outgoing_requests: OutgoingRequests,
+    /// Tells the client what the server is busy with.
+    status: StatusReporter,
     filewatcher_registered: AtomicBool,
     version_info: Mutex<HashMap<PathBuf, i32>>,
+    garbage_collection_stats: Arc<GarbageCollectionStats>,
+    /// The open file the user most recently opened or edited, whose diagnostics are published first.
+    focused_file: Mutex<Option<PathBuf>>,
+    /// The open files the client last asked for semantic tokens or inlay hints, and when. Editors
+    /// only ask for them for the files on screen, so these are the files the user can see.
+    visible_files: Mutex<HashMap<PathBuf, Instant>>,
+    /// When we last published diagnostics for the visible files other than the focused one.
+    visible_published: Mutex<Instant>,
+    /// When we last published diagnostics for the remaining open files.
+    others_published: Mutex<Instant>,
+    /// Invalidations waiting to be applied by the queued recheck, if there is one.
+    pending_invalidations: Arc<Mutex<Vec<Invalidation>>>,
+    /// Whether a config reload is queued and hasn't started yet, so another would be redundant.
+    config_reload_queued: Arc<AtomicBool>,
+    /// The responses to requests about open files, reused until anything changes.
+    query_cache: QueryCache,
+}
+
+type Invalidation = Box<dyn FnOnce(&mut Transaction) + Send + Sync>;
+
+/// The most find-references searches we queue at once. Each searches the whole project, so more
+/// requests are refused rather than queued behind them.
+const MAX_PENDING_FIND_REFERENCES: usize = 4;
+
+/// How long after the client last asked for semantic tokens or inlay hints of a file we still
+/// consider it visible.
+const VISIBLE_FILE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The open files whose diagnostics we publish after those of the focused file, in order.
+///
+/// The server only publishes diagnostics for open files, so files that are merely checked in the
+/// background, such as the dependents of an edited file, don't get a tier of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiagnosticsTier {
+    /// Open files the user can see, e.g. in a split editor.
+    Visible,
+    /// All the other open files.
+    Others,
+}
+
+/// How likely an edit is to change the diagnostics of files other than the one being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    /// Changes within a single line, as produced by typing. Other open files can wait until the
+    /// user pauses.
+    Typing,
+    /// Anything else, e.g. pasting, deleting lines or replacing the whole file.
+    Structural,
+}
+
+impl EditKind {
+    fn classify(changes: &[TextDocumentContentChangeEvent]) -> Self {
+        let typing = changes.iter().all(|change| {
+            change
+                .range
+                .is_some_and(|range| range.start.line == range.end.line)
+                && !change.text.contains('\n')
+        });
+        if typing {
+            EditKind::Typing
+        } else {
+            EditKind::Structural
+        }
+    }
+
+    /// While more edits are queued, the longest we wait after this kind of edit before
+    /// republishing the diagnostics of a tier. Typing rarely affects other files, so they can
+    /// wait until the user pauses, while a structural edit must reach the visible files at once.
+    fn max_deferral(self, tier: DiagnosticsTier) -> Duration {
+        match (self, tier) {
+            (EditKind::Typing, DiagnosticsTier::Visible) => Duration::from_millis(500),
+            (EditKind::Typing, DiagnosticsTier::Others) => Duration::from_secs(2),
+            (EditKind::Structural, DiagnosticsTier::Visible) => Duration::ZERO,
+            (EditKind::Structural, DiagnosticsTier::Others) => Duration::from_millis(500),
+        }
+    }
+}
+
+/// How many epochs a module that isn't reachable from an open file survives garbage collection,
+/// so that quickly reopening a file doesn't have to redo all the work.
+const GARBAGE_COLLECTION_KEEP_RECENT_EPOCHS: u32 = 32;
+
+/// Counters reported by `pyrefly/serverStatus`.
+#[derive(Default)]
+struct GarbageCollectionStats {
+    collections: AtomicUsize,
+    modules_collected: AtomicUsize,
 }
 
 /// At the time when we are ready to handle a new LSP event, it will help if we know the list of
@@ -405,6 +598,26 @@ pub fn dispatch_lsp_events(connection: &Connection, lsp_queue: LspQueue) {
     let _ = lsp_queue.send(LspEvent::Exit);
 }
 
+/// Whether the client pulls diagnostics with `textDocument/diagnostic`, in which case we don't
+/// push them with `textDocument/publishDiagnostics` as well.
+fn supports_pull_diagnostics(initialization_params: &InitializeParams) -> bool {
+    initialization_params
+        .capabilities
+        .text_document
+        .as_ref()
+        .is_some_and(|c| c.diagnostic.is_some())
+}
+
+/// An id for a set of diagnostics, so a client that pulls them again can be told they haven't
+/// changed.
+fn diagnostics_result_id(items: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(items)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
 pub fn capabilities(
     indexing_mode: IndexingMode,
     initialization_params: &InitializeParams,
@@ -425,7 +638,14 @@ pub fn capabilities(
             definition_provider: Some(OneOf::Left(true)),
             type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
             code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
-                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                code_action_kinds: Some(vec![
+                    CodeActionKind::QUICKFIX,
+                    CodeActionKind::REFACTOR_REWRITE,
+                    CodeActionKind::REFACTOR_EXTRACT,
+                    CodeActionKind::REFACTOR_INLINE,
+                    CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                    CodeActionKind::new("source.addTypeAnnotations"),
+                ]),
                 ..Default::default()
             })),
             completion_provider: Some(CompletionOptions {
@@ -449,15 +669,32 @@ pub fn capabilities(
                     }))
                 }
             },
+            // Incoming calls are found with find references.
+            call_hierarchy_provider: match indexing_mode {
+                IndexingMode::None => None,
+                IndexingMode::LazyNonBlockingBackground | IndexingMode::LazyBlocking => {
+                    Some(CallHierarchyServerCapability::Simple(true))
+                }
+            },
             signature_help_provider: Some(SignatureHelpOptions {
                 trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
                 ..Default::default()
             }),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
-            inlay_hint_provider: Some(OneOf::Left(true)),
+            inlay_hint_provider: Some(OneOf::Right(InlayHintServerCapabilities::Options(
+                InlayHintOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                },
+            ))),
             document_symbol_provider: Some(OneOf::Left(true)),
             workspace_symbol_provider: Some(OneOf::Left(true)),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            document_link_provider: Some(DocumentLinkOptions {
+                resolve_provider: Some(false),
+                work_done_progress_options: Default::default(),
+            }),
             semantic_tokens_provider: if augments_syntax_tokens {
                 // We currently only return partial tokens (e.g. no tokens for keywords right now).
                 // If the client doesn't support `augments_syntax_tokens` to fallback baseline
@@ -475,6 +712,16 @@ pub fn capabilities(
             } else {
                 None
             },
+            diagnostic_provider: if supports_pull_diagnostics(initialization_params) {
+                Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                    identifier: Some("pyrefly".to_owned()),
+                    inter_file_dependencies: true,
+                    workspace_diagnostics: true,
+                    ..Default::default()
+                }))
+            } else {
+                None
+            },
             workspace: Some(WorkspaceServerCapabilities {
                 workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                     supported: Some(true),
@@ -482,21 +729,37 @@ pub fn capabilities(
                 }),
                 file_operations: Some(lsp_types::WorkspaceFileOperationsServerCapabilities {
                     will_rename: Some(lsp_types::FileOperationRegistrationOptions {
-                        filters: vec![lsp_types::FileOperationFilter {
-                            pattern: lsp_types::FileOperationPattern {
-                                glob: "**/*.{py,pyi}".to_owned(),
-
-                                matches: Some(lsp_types::FileOperationPatternKind::File),
-                                options: None,
+                        filters: vec![
+                            lsp_types::FileOperationFilter {
+                                pattern: lsp_types::FileOperationPattern {
+                                    glob: "**/*.{py,pyi}".to_owned(),
+
+                                    matches: Some(lsp_types::FileOperationPatternKind::File),
+                                    options: None,
+                                },
+                                scheme: Some("file".to_owned()),
                             },
-                            scheme: Some("file".to_owned()),
-                        }],
+                            lsp_types::FileOperationFilter {
+                                // Renaming a directory may rename a package.
+                                pattern: lsp_types::FileOperationPattern {
+                                    glob: "**".to_owned(),
+                                    matches: Some(lsp_types::FileOperationPatternKind::Folder),
+                                    options: None,
+                                },
+                                scheme: Some("file".to_owned()),
+                            },
+                        ],
                     }),
                     ..Default::default()
                 }),
             }),
             ..Default::default()
         },
+        // Subtypes are found with find references.
+        type_hierarchy_provider: match indexing_mode {
+            IndexingMode::None => None,
+            IndexingMode::LazyNonBlockingBackground | IndexingMode::LazyBlocking => Some(true),
+        },
         ..Default::default()
     }
 }
@@ -513,6 +776,7 @@ pub fn lsp_loop(
     initialization_params: InitializeParams,
     indexing_mode: IndexingMode,
     workspace_indexing_limit: usize,
+    default_inlay_hints: InlayHintConfig,
 ) -> anyhow::Result<()> {
     eprintln!("Reading messages");
     let connection_for_dispatcher = connection.dupe();
@@ -523,6 +787,7 @@ pub fn lsp_loop(
         initialization_params,
         indexing_mode,
         workspace_indexing_limit,
+        default_inlay_hints,
     );
     let lsp_queue2 = lsp_queue.dupe();
     std::thread::spawn(move || {
@@ -595,6 +860,13 @@ impl Server {
         subsequent_mutation: bool,
         event: LspEvent,
     ) -> anyhow::Result<ProcessEvent> {
+        // Anything but a request may change the answers to requests.
+        if !matches!(
+            event,
+            LspEvent::LspRequest(_) | LspEvent::CancelRequest(_) | LspEvent::SupersededRequest(_)
+        ) {
+            self.query_cache.clear();
+        }
         match event {
             LspEvent::Exit => {
                 return Ok(ProcessEvent::Exit);
@@ -653,11 +925,21 @@ impl Server {
                     eprintln!("Response for unknown request: {x:?}");
                 }
             }
+            LspEvent::SupersededRequest(x) => {
+                let message = format!(
+                    "Request {} ({}) is dropped because its document has changed since",
+                    x.method, x.id
+                );
+                eprintln!("{message}");
+                self.send_response(Response::new_err(
+                    x.id,
+                    ErrorCode::ContentModified as i32,
+                    message,
+                ));
+            }
             LspEvent::LspRequest(x) => {
-                // These are messages where VS Code will use results from previous document versions,
-                // we really don't want to implicitly cancel those.
-                const ONLY_ONCE: &[&str] = &[Completion::METHOD, SignatureHelpRequest::METHOD];
-
+                let _span =
+                    trace_span!("request", method = x.method.as_str(), id = %x.id).entered();
                 let in_cancelled_requests = canceled_requests.remove(&x.id);
                 if in_cancelled_requests
                     || (subsequent_mutation && !ONLY_ONCE.contains(&x.method.as_str()))
@@ -773,6 +1055,78 @@ impl Server {
                     {
                         self.references(x.id, ide_transaction_manager, params);
                     }
+                } else if let Some(params) = as_request::<CallHierarchyPrepare>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<CallHierarchyPrepare>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.prepare_call_hierarchy(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<CallHierarchyIncomingCalls>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<CallHierarchyIncomingCalls>(
+                            params, &x.id,
+                        )
+                    {
+                        self.call_hierarchy_incoming_calls(x.id, ide_transaction_manager, params);
+                    }
+                } else if let Some(params) = as_request::<CallHierarchyOutgoingCalls>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<CallHierarchyOutgoingCalls>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.call_hierarchy_outgoing_calls(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<TypeHierarchyPrepare>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<TypeHierarchyPrepare>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.prepare_type_hierarchy(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<TypeHierarchySupertypes>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<TypeHierarchySupertypes>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.type_hierarchy_supertypes(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<TypeHierarchySubtypes>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<TypeHierarchySubtypes>(
+                            params, &x.id,
+                        )
+                    {
+                        self.type_hierarchy_subtypes(x.id, ide_transaction_manager, params);
+                    }
                 } else if let Some(params) = as_request::<PrepareRenameRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<PrepareRenameRequest>(
@@ -793,6 +1147,12 @@ impl Server {
                     {
                         self.rename(x.id, ide_transaction_manager, params);
                     }
+                } else if let Some(params) = as_request::<RenamePreview>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<RenamePreview>(params, &x.id)
+                    {
+                        self.rename_preview(x.id, ide_transaction_manager, params);
+                    }
                 } else if let Some(params) = as_request::<SignatureHelpRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<SignatureHelpRequest>(
@@ -817,9 +1177,16 @@ impl Server {
                         };
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
+                        let uri = params
+                            .text_document_position_params
+                            .text_document
+                            .uri
+                            .clone();
                         self.send_response(new_response(
                             x.id,
-                            Ok(self.hover(&transaction, params).unwrap_or(default_response)),
+                            Ok(self.memoized::<HoverRequest>(&uri, &params.clone(), || {
+                                Some(self.hover(&transaction, params).unwrap_or(default_response))
+                            })),
                         ));
                         ide_transaction_manager.save(transaction);
                     }
@@ -829,6 +1196,7 @@ impl Server {
                             params, &x.id,
                         )
                     {
+                        self.mark_visible(&params.text_document.uri);
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
                         self.send_response(new_response(
@@ -837,6 +1205,20 @@ impl Server {
                         ));
                         ide_transaction_manager.save(transaction);
                     }
+                } else if let Some(params) = as_request::<InlayHintResolveRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<InlayHintResolveRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.inlay_hint_resolve(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
                 } else if let Some(params) = as_request::<SemanticTokensFullRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<SemanticTokensFullRequest>(
@@ -849,11 +1231,20 @@ impl Server {
                         });
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
+                        let uri = params.text_document.uri.clone();
+                        self.mark_visible(&uri);
                         self.send_response(new_response(
                             x.id,
-                            Ok(self
-                                .semantic_tokens_full(&transaction, params)
-                                .unwrap_or(default_response)),
+                            Ok(self.memoized::<SemanticTokensFullRequest>(
+                                &uri,
+                                &params.clone(),
+                                || {
+                                    Some(
+                                        self.semantic_tokens_full(&transaction, params)
+                                            .unwrap_or(default_response),
+                                    )
+                                },
+                            )),
                         ));
                         ide_transaction_manager.save(transaction);
                     }
@@ -869,11 +1260,20 @@ impl Server {
                         });
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
+                        let uri = params.text_document.uri.clone();
+                        self.mark_visible(&uri);
                         self.send_response(new_response(
                             x.id,
-                            Ok(self
-                                .semantic_tokens_ranged(&transaction, params)
-                                .unwrap_or(default_response)),
+                            Ok(self.memoized::<SemanticTokensRangeRequest>(
+                                &uri,
+                                &params.clone(),
+                                || {
+                                    Some(
+                                        self.semantic_tokens_ranged(&transaction, params)
+                                            .unwrap_or(default_response),
+                                    )
+                                },
+                            )),
                         ));
                         ide_transaction_manager.save(transaction);
                     }
@@ -885,11 +1285,18 @@ impl Server {
                     {
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
+                        let uri = params.text_document.uri.clone();
                         self.send_response(new_response(
                             x.id,
-                            Ok(DocumentSymbolResponse::Nested(
-                                self.hierarchical_document_symbols(&transaction, params)
-                                    .unwrap_or_default(),
+                            Ok(self.memoized::<DocumentSymbolRequest>(
+                                &uri,
+                                &params.clone(),
+                                || {
+                                    Some(DocumentSymbolResponse::Nested(
+                                        self.hierarchical_document_symbols(&transaction, params)
+                                            .unwrap_or_default(),
+                                    ))
+                                },
                             )),
                         ));
                         ide_transaction_manager.save(transaction);
@@ -900,6 +1307,9 @@ impl Server {
                             params, &x.id,
                         )
                     {
+                        // Symbols can be searched for before any file is opened, which is
+                        // what otherwise starts indexing the workspace.
+                        self.populate_workspace_files_if_necessary();
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
                         self.send_response(new_response(
@@ -929,6 +1339,21 @@ impl Server {
                         ));
                         ide_transaction_manager.save(transaction);
                     }
+                } else if let Some(params) = as_request::<WorkspaceDiagnosticRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<WorkspaceDiagnosticRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        self.validate_in_memory_and_commit_if_possible(ide_transaction_manager);
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.workspace_diagnostics(&transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
                 } else if let Some(params) = as_request::<ProvideType>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<ProvideType>(params, &x.id)
@@ -941,12 +1366,64 @@ impl Server {
                         ));
                         ide_transaction_manager.save(transaction);
                     }
+                } else if let Some(params) = as_request::<MoveSymbol>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<MoveSymbol>(params, &x.id)
+                    {
+                        let mut transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.move_symbol(&mut transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<CheckFiles>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<CheckFiles>(params, &x.id)
+                    {
+                        let mut transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.check_files(&mut transaction, params)),
+                        ));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<SpeculativeCheck>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<SpeculativeCheck>(
+                            params, &x.id,
+                        )
+                    {
+                        self.send_response(new_response(x.id, Ok(self.speculative_check(params))));
+                    }
+                } else if let Some(params) = as_request::<CheckSnippet>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<CheckSnippet>(params, &x.id)
+                    {
+                        self.send_response(new_response(x.id, Ok(self.check_snippet(params))));
+                    }
+                } else if let Some(params) = as_request::<ServerStatus>(&x) {
+                    if self
+                        .extract_request_params_or_send_err_response::<ServerStatus>(params, &x.id)
+                        .is_some()
+                    {
+                        self.send_response(new_response(x.id, Ok(self.server_status())));
+                    }
+                } else if let Some(params) = as_request::<TraceImport>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<TraceImport>(params, &x.id)
+                    {
+                        self.send_response(new_response(x.id, Ok(self.trace_import(params))));
+                    }
                 } else if let Some(params) = as_request::<WillRenameFiles>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<WillRenameFiles>(
                             params, &x.id,
                         )
                     {
+                        self.index_for_rename();
                         let transaction =
                             ide_transaction_manager.non_committable_transaction(&self.state);
                         let supports_document_changes = self
@@ -981,6 +1458,32 @@ impl Server {
                         self.send_response(new_response(x.id, Ok(result)));
                         ide_transaction_manager.save(transaction);
                     }
+                } else if let Some(params) = as_request::<SelectionRangeRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<SelectionRangeRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        let result = self
+                            .selection_ranges(&transaction, params)
+                            .unwrap_or_default();
+                        self.send_response(new_response(x.id, Ok(result)));
+                        ide_transaction_manager.save(transaction);
+                    }
+                } else if let Some(params) = as_request::<DocumentLinkRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<DocumentLinkRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let transaction =
+                            ide_transaction_manager.non_committable_transaction(&self.state);
+                        let result = self.document_links(&transaction, params);
+                        self.send_response(new_response(x.id, Ok(result)));
+                        ide_transaction_manager.save(transaction);
+                    }
                 } else if &x.method == "pyrefly/textDocument/docstringRanges" {
                     let text_document: TextDocumentIdentifier = serde_json::from_value(x.params)?;
                     let transaction =
@@ -1017,6 +1520,7 @@ impl Server {
         initialize_params: InitializeParams,
         indexing_mode: IndexingMode,
         workspace_indexing_limit: usize,
+        default_inlay_hints: InlayHintConfig,
     ) -> Self {
         let folders = if let Some(capability) = &initialize_params.capabilities.workspace
             && let Some(true) = capability.workspace_folders
@@ -1033,6 +1537,14 @@ impl Server {
         let workspaces = Arc::new(Workspaces::new(Workspace::default(), &folders));
 
         let config_finder = Workspaces::config_finder(&workspaces);
+        let outgoing_request_id = Arc::new(AtomicI32::new(1));
+        let outgoing_requests = Arc::new(Mutex::new(HashMap::new()));
+        let status = StatusReporter::new(
+            connection.sender.clone(),
+            &initialize_params,
+            outgoing_request_id.dupe(),
+            outgoing_requests.dupe(),
+        );
         let s = Self {
             connection: ServerConnection(connection),
             lsp_queue,
@@ -1043,16 +1555,26 @@ impl Server {
             initialize_params,
             indexing_mode,
             workspace_indexing_limit,
+            default_inlay_hints,
             state: Arc::new(State::new(config_finder)),
             open_files: Arc::new(RwLock::new(HashMap::new())),
             indexed_configs: Mutex::new(HashSet::new()),
             indexed_workspaces: Mutex::new(HashSet::new()),
             cancellation_handles: Arc::new(Mutex::new(HashMap::new())),
             workspaces,
-            outgoing_request_id: AtomicI32::new(1),
-            outgoing_requests: Mutex::new(HashMap::new()),
+            outgoing_request_id,
+            outgoing_requests,
+            status,
             filewatcher_registered: AtomicBool::new(false),
             version_info: Mutex::new(HashMap::new()),
+            garbage_collection_stats: Arc::new(GarbageCollectionStats::default()),
+            focused_file: Mutex::new(None),
+            visible_files: Mutex::new(HashMap::new()),
+            visible_published: Mutex::new(Instant::now()),
+            others_published: Mutex::new(Instant::now()),
+            pending_invalidations: Arc::new(Mutex::new(Vec::new())),
+            config_reload_queued: Arc::new(AtomicBool::new(false)),
+            query_cache: QueryCache::default(),
         };
         s.setup_file_watcher_if_necessary();
         s.request_settings_for_all_workspaces();
@@ -1063,6 +1585,14 @@ impl Server {
         self.connection.send(Message::Response(x))
     }
 
+    fn send_notification<T>(&self, params: T::Params)
+    where
+        T: lsp_types::notification::Notification,
+    {
+        self.connection
+            .send(Message::Notification(new_notification::<T>(params)));
+    }
+
     fn send_request<T>(&self, params: T::Params)
     where
         T: lsp_types::request::Request,
@@ -1088,6 +1618,15 @@ impl Server {
             .keys()
             .map(|x| make_open_handle(state, x))
             .collect::<Vec<_>>();
+        Self::set_open_files_memory(open_files, transaction);
+        transaction.run(&handles, Require::Everything);
+        handles
+    }
+
+    fn set_open_files_memory(
+        open_files: &RwLock<HashMap<PathBuf, Arc<String>>>,
+        transaction: &mut Transaction<'_>,
+    ) {
         transaction.set_memory(
             open_files
                 .read()
@@ -1095,8 +1634,6 @@ impl Server {
                 .map(|x| (x.0.clone(), Some(x.1.dupe())))
                 .collect::<Vec<_>>(),
         );
-        transaction.run(&handles, Require::Everything);
-        handles
     }
 
     #[allow(dead_code)]
@@ -1109,10 +1646,12 @@ impl Server {
         false
     }
 
+    /// The diagnostic for an error, if it is in a file we report errors for, according to
+    /// `is_reported`, and the error isn't suppressed by the configuration.
     fn get_diag_if_shown(
         &self,
         e: &Error,
-        open_files: &HashMap<PathBuf, Arc<String>>,
+        is_reported: impl Fn(&Path) -> bool,
     ) -> Option<(PathBuf, Diagnostic)> {
         if let Some(path) = to_real_path(e.path()) {
             // When no file covers this, we'll get the default configured config which includes "everything"
@@ -1121,14 +1660,28 @@ impl Server {
                 .state
                 .config_finder()
                 .python_file(ModuleName::unknown(), e.path());
-            if open_files.contains_key(&path)
+            if is_reported(&path)
                 && config.project_includes.covers(&path)
                 && !config.project_excludes.covers(&path)
                 && self
                     .type_error_display_status(e.path().as_path())
                     .is_enabled()
             {
-                return Some((path.to_path_buf(), e.to_diagnostic()));
+                let mut diagnostic = e.to_diagnostic();
+                let related = e
+                    .related()
+                    .iter()
+                    .filter_map(|(location, message)| {
+                        Some(DiagnosticRelatedInformation {
+                            location: to_lsp_location(location)?,
+                            message: message.clone(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if !related.is_empty() {
+                    diagnostic.related_information = Some(related);
+                }
+                return Some((path.to_path_buf(), diagnostic));
             }
         }
         None
@@ -1144,6 +1697,24 @@ impl Server {
         provide_type(transaction, &handle, params.positions)
     }
 
+    fn move_symbol(
+        &self,
+        transaction: &mut Transaction<'_>,
+        params: MoveSymbolParams,
+    ) -> Option<WorkspaceEdit> {
+        let handle = self.make_handle_if_enabled(&params.text_document.uri, None)?;
+        let target = self.make_handle_if_enabled(&params.target_uri, None)?;
+        // The target may not be imported by anything yet, so make sure it is loaded.
+        transaction.run(&[target.dupe()], Require::Exports);
+        move_symbol(
+            transaction,
+            &handle,
+            &target,
+            params.position,
+            params.leave_reexport,
+        )
+    }
+
     fn type_error_display_status(&self, path: &Path) -> TypeErrorDisplayStatus {
         let handle = make_open_handle(&self.state, path);
         let config = self
@@ -1184,6 +1755,7 @@ impl Server {
         self.validate_in_memory_for_possibly_committable_transaction(
             ide_transaction_manager,
             possibly_committable_transaction,
+            None,
         );
     }
 
@@ -1196,6 +1768,7 @@ impl Server {
         self.validate_in_memory_for_possibly_committable_transaction(
             ide_transaction_manager,
             Err(noncommittable_transaction),
+            None,
         );
     }
 
@@ -1203,34 +1776,89 @@ impl Server {
     /// (i.e., another transaction is already being committed or the state is locked for writing),
     /// we still update diagnostics using a non-committable transaction, which may have slightly stale
     /// data compared to the main state
+    ///
+    /// The focused file is checked and published first, so the file being edited gets its
+    /// diagnostics without waiting on the others, then the other visible files, then the remaining
+    /// open files. If `edit` is set and more edits are already queued, a tier is skipped for now,
+    /// for at most `EditKind::max_deferral`, since the next edit will check it anyway.
     fn validate_in_memory_for_possibly_committable_transaction<'a>(
         &'a self,
         ide_transaction_manager: &mut TransactionManager<'a>,
         mut possibly_committable_transaction: Result<CommittingTransaction<'a>, Transaction<'a>>,
+        edit: Option<EditKind>,
     ) {
+        let _status = self.status.start(Status::Checking {
+            files: self.open_files.read().len(),
+        });
         let transaction = match &mut possibly_committable_transaction {
             Ok(transaction) => transaction.as_mut(),
             Err(transaction) => transaction,
         };
-        let handles =
+        let focused = self
+            .focused_file
+            .lock()
+            .clone()
+            .filter(|x| self.open_files.read().contains_key(x));
+        let visible = {
+            let open_files = self.open_files.read();
+            self.visible_files
+                .lock()
+                .iter()
+                .filter(|(x, seen)| {
+                    seen.elapsed() < VISIBLE_FILE_TIMEOUT
+                        && open_files.contains_key(*x)
+                        && focused.as_ref() != Some(*x)
+                })
+                .map(|(x, _)| x.clone())
+                .collect::<Vec<_>>()
+        };
+        let deferred = |tier: DiagnosticsTier, published: &Mutex<Instant>| {
+            edit.is_some_and(|edit| {
+                self.lsp_queue.has_pending_mutation()
+                    && published.lock().elapsed() < edit.max_deferral(tier)
+            })
+        };
+        let defer_visible = deferred(DiagnosticsTier::Visible, &self.visible_published);
+        let defer_others = deferred(DiagnosticsTier::Others, &self.others_published);
+
+        Self::set_open_files_memory(&self.open_files, transaction);
+        if let Some(focused) = &focused {
+            transaction.run(
+                &[make_open_handle(&self.state, focused)],
+                Require::Everything,
+            );
+            self.publish_open_file_diagnostics(transaction, std::slice::from_ref(focused));
+        }
+        if !defer_visible && !visible.is_empty() {
+            let handles = visible
+                .iter()
+                .map(|x| make_open_handle(&self.state, x))
+                .collect::<Vec<_>>();
+            transaction.run(&handles, Require::Everything);
+            self.publish_open_file_diagnostics(transaction, &visible);
+            *self.visible_published.lock() = Instant::now();
+        }
+        let others = if defer_others {
+            Vec::new()
+        } else {
             Self::validate_in_memory_for_transaction(&self.state, &self.open_files, transaction);
+            // Visible files whose tier was deferred are published along with the rest.
+            self.open_files
+                .read()
+                .keys()
+                .filter(|x| {
+                    focused.as_ref() != Some(*x) && (defer_visible || !visible.contains(*x))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
 
         let publish = |transaction: &Transaction| {
-            let mut diags: SmallMap<PathBuf, Vec<Diagnostic>> = SmallMap::new();
-            let open_files = self.open_files.read();
-            for x in open_files.keys() {
-                diags.insert(x.as_path().to_owned(), Vec::new());
-            }
-            for e in transaction.get_errors(&handles).collect_errors().shown {
-                if let Some((path, diag)) = self.get_diag_if_shown(&e, &open_files) {
-                    diags.entry(path.to_owned()).or_default().push(diag);
-                }
-            }
-            for (path, diagnostics) in diags.iter_mut() {
-                let handle = make_open_handle(&self.state, path);
-                Self::append_unreachable_diagnostics(transaction, &handle, diagnostics);
+            if defer_others {
+                return;
             }
-            self.connection.publish_diagnostics(diags);
+            self.publish_open_file_diagnostics(transaction, &others);
+            *self.others_published.lock() = Instant::now();
             if self
                 .initialize_params
                 .capabilities
@@ -1273,17 +1901,65 @@ impl Server {
         );
     }
 
-    fn invalidate_find_for_configs(&self, invalidated_configs: SmallSet<ArcId<ConfigFile>>) {
-        self.invalidate(|t| t.invalidate_find_for_configs(invalidated_configs));
-    }
-
-    fn populate_project_files_if_necessary(
-        &self,
-        config_to_populate_files: Option<ArcId<ConfigFile>>,
-    ) {
-        if let Some(config) = config_to_populate_files {
-            if config.skip_lsp_config_indexing {
-                return;
+    /// Publish the diagnostics of the open files at `paths`.
+    fn publish_open_file_diagnostics(&self, transaction: &Transaction, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            return;
+        }
+        let handles = paths
+            .iter()
+            .map(|x| make_open_handle(&self.state, x))
+            .collect::<Vec<_>>();
+        let mut diags: SmallMap<PathBuf, Vec<Diagnostic>> = SmallMap::new();
+        let open_files = self.open_files.read();
+        for x in paths {
+            diags.insert(x.clone(), Vec::new());
+        }
+        for e in transaction.get_errors(&handles).collect_errors().shown {
+            if let Some((path, diag)) = self.get_diag_if_shown(&e, |x| open_files.contains_key(x)) {
+                diags.entry(path.to_owned()).or_default().push(diag);
+            }
+        }
+        let send_unreachable_ranges = self.supports_unreachable_ranges();
+        for (path, diagnostics) in diags.iter_mut() {
+            let handle = make_open_handle(&self.state, path);
+            Self::append_unreachable_diagnostics(transaction, &handle, diagnostics);
+            if send_unreachable_ranges && let Ok(uri) = Url::from_file_path(path.absolutize()) {
+                self.send_notification::<UnreachableRanges>(UnreachableRangesParams {
+                    uri,
+                    ranges: Self::unreachable_ranges(transaction, &handle),
+                });
+            }
+        }
+        if supports_pull_diagnostics(&self.initialize_params) {
+            // The client asks for diagnostics itself, so just tell it they may have changed.
+            if self
+                .initialize_params
+                .capabilities
+                .workspace
+                .as_ref()
+                .and_then(|w| w.diagnostic.as_ref())
+                .and_then(|d| d.refresh_support)
+                .unwrap_or(false)
+            {
+                self.send_request::<WorkspaceDiagnosticRefresh>(());
+            }
+        } else {
+            self.connection.publish_diagnostics(diags);
+        }
+    }
+
+    fn invalidate_find_for_configs(&self, invalidated_configs: SmallSet<ArcId<ConfigFile>>) {
+        self.invalidate(|t| t.invalidate_find_for_configs(invalidated_configs));
+    }
+
+    fn populate_project_files_if_necessary(
+        &self,
+        config_to_populate_files: Option<ArcId<ConfigFile>>,
+    ) {
+        if let Some(config) = config_to_populate_files {
+            if config.skip_lsp_config_indexing {
+                return;
             }
             match self.indexing_mode {
                 IndexingMode::None => {}
@@ -1291,8 +1967,11 @@ impl Server {
                     if self.indexed_configs.lock().insert(config.dupe()) {
                         let state = self.state.dupe();
                         let lsp_queue = self.lsp_queue.dupe();
+                        let status = self.status.dupe();
                         self.recheck_queue.queue_task(Box::new(move || {
-                            Self::populate_all_project_files_in_config(config, state, lsp_queue);
+                            Self::populate_all_project_files_in_config(
+                                config, state, lsp_queue, status,
+                            );
                         }));
                     }
                 }
@@ -1302,6 +1981,7 @@ impl Server {
                             config,
                             self.state.dupe(),
                             self.lsp_queue.dupe(),
+                            self.status.dupe(),
                         );
                     }
                 }
@@ -1328,12 +2008,14 @@ impl Server {
                 drop(indexed_workspaces);
                 let state = self.state.dupe();
                 let lsp_queue = self.lsp_queue.dupe();
+                let status = self.status.dupe();
                 self.recheck_queue.queue_task(Box::new(move || {
                     Self::populate_all_workspaces_files(
                         roots_to_populate_files,
                         state,
                         workspace_indexing_limit,
                         lsp_queue,
+                        status,
                     );
                 }));
             }
@@ -1345,6 +2027,7 @@ impl Server {
                     self.state.dupe(),
                     workspace_indexing_limit,
                     self.lsp_queue.dupe(),
+                    self.status.dupe(),
                 );
             }
         }
@@ -1352,14 +2035,32 @@ impl Server {
 
     /// Perform an invalidation of elements on `State` and commit them.
     /// Runs asynchronously. Returns immediately and may wait a while for a committable transaction.
+    /// Invalidations made while a recheck is already queued are applied by that recheck, so a
+    /// burst of them, e.g. from switching branches, only causes one.
     fn invalidate(&self, f: impl FnOnce(&mut Transaction) + Send + Sync + 'static) {
+        let mut pending = self.pending_invalidations.lock();
+        pending.push(Box::new(f));
+        if pending.len() > 1 {
+            return;
+        }
+        drop(pending);
         let state = self.state.dupe();
         let lsp_queue = self.lsp_queue.dupe();
         let cancellation_handles = self.cancellation_handles.dupe();
         let open_files = self.open_files.dupe();
+        let pending_invalidations = self.pending_invalidations.dupe();
+        let status = self.status.dupe();
         self.recheck_queue.queue_task(Box::new(move || {
+            let _span = trace_span!("recheck").entered();
+            let _status = status.start_with_progress(Status::Checking {
+                files: open_files.read().len(),
+            });
             let mut transaction = state.new_committable_transaction(Require::indexing(), None);
-            f(transaction.as_mut());
+            // Take the invalidations only once we have the transaction, as more may have been
+            // made while we waited for it.
+            for f in std::mem::take(&mut *pending_invalidations.lock()) {
+                f(transaction.as_mut());
+            }
 
             Self::validate_in_memory_for_transaction(&state, &open_files, transaction.as_mut());
 
@@ -1387,11 +2088,15 @@ impl Server {
         config: ArcId<ConfigFile>,
         state: Arc<State>,
         lsp_queue: LspQueue,
+        status: StatusReporter,
     ) {
         let unknown = ModuleName::unknown();
 
         eprintln!("Populating all files in the config ({:?}).", config.source);
-        let mut transaction = state.new_committable_transaction(Require::indexing(), None);
+        let _span = trace_span!("index_config", config = ?config.source).entered();
+        let status = status.start_with_progress(Status::Indexing { percentage: 0 });
+        let mut transaction = state
+            .new_committable_transaction(Require::indexing(), Some(status.indexing_progress()));
 
         let project_path_blobs = config.get_filtered_globs(None);
         let paths = project_path_blobs.files().unwrap_or_default();
@@ -1420,12 +2125,16 @@ impl Server {
         state: Arc<State>,
         workspace_indexing_limit: usize,
         lsp_queue: LspQueue,
+        status: StatusReporter,
     ) {
         for workspace_root in workspace_roots {
             eprintln!(
                 "Populating up to {workspace_indexing_limit} files in the workspace ({workspace_root:?}).",
             );
-            let mut transaction = state.new_committable_transaction(Require::indexing(), None);
+            let _span = trace_span!("index_workspace", root = ?workspace_root).entered();
+            let status = status.start_with_progress(Status::Indexing { percentage: 0 });
+            let mut transaction = state
+                .new_committable_transaction(Require::indexing(), Some(status.indexing_progress()));
 
             let includes =
                 ConfigFile::default_project_includes().from_root(workspace_root.as_path());
@@ -1463,6 +2172,7 @@ impl Server {
         subsequent_mutation: bool,
         params: DidOpenTextDocumentParams,
     ) -> anyhow::Result<()> {
+        let _span = trace_span!("did_open", uri = %params.text_document.uri).entered();
         let uri = params.text_document.uri.to_file_path().map_err(|_| {
             anyhow::anyhow!(
                 "Could not convert uri to filepath: {}",
@@ -1482,6 +2192,7 @@ impl Server {
         self.open_files
             .write()
             .insert(uri.clone(), Arc::new(params.text_document.text));
+        *self.focused_file.lock() = Some(uri.clone());
         if !subsequent_mutation {
             // In order to improve perceived startup perf, when a file is opened, we run a
             // non-committing transaction that indexes the file with default require level Exports.
@@ -1513,6 +2224,7 @@ impl Server {
         params: DidChangeTextDocumentParams,
     ) -> anyhow::Result<()> {
         let VersionedTextDocumentIdentifier { uri, version } = params.text_document;
+        let _span = trace_span!("did_change", uri = %uri, version).entered();
         let file_path = uri.to_file_path().unwrap();
 
         let mut version_info = self.version_info.lock();
@@ -1523,6 +2235,8 @@ impl Server {
             ));
         }
         version_info.insert(file_path.clone(), version);
+        let edit = EditKind::classify(&params.content_changes);
+        *self.focused_file.lock() = Some(file_path.clone());
         let mut lock = self.open_files.write();
         let original = lock.get_mut(&file_path).unwrap();
         *original = Arc::new(apply_change_events(
@@ -1535,7 +2249,13 @@ impl Server {
                 "File {} changed, prepare to validate open files.",
                 file_path.display()
             );
-            self.validate_in_memory_and_commit_if_possible(ide_transaction_manager);
+            let possibly_committable_transaction =
+                ide_transaction_manager.get_possibly_committable_transaction(&self.state);
+            self.validate_in_memory_for_possibly_committable_transaction(
+                ide_transaction_manager,
+                possibly_committable_transaction,
+                Some(edit),
+            );
         }
         Ok(())
     }
@@ -1591,6 +2311,7 @@ impl Server {
     fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri.to_file_path().unwrap();
         self.version_info.lock().remove(&uri);
+        self.visible_files.lock().remove(&uri);
         let open_files = self.open_files.dupe();
         open_files.write().remove(&uri);
         self.connection
@@ -1600,15 +2321,25 @@ impl Server {
         let open_files = self.open_files.dupe();
         let sourcedb_queue = self.sourcedb_queue.dupe();
         let invalidated_configs = self.invalidated_configs.dupe();
+        let garbage_collection_stats = self.garbage_collection_stats.dupe();
         self.recheck_queue.queue_task(Box::new(move || {
             // Clear out the memory associated with this file.
             // Not a race condition because we immediately call validate_in_memory to put back the open files as they are now.
             // Having the extra file hanging around doesn't harm anything, but does use extra memory.
             let mut transaction = state.new_committable_transaction(Require::indexing(), None);
             transaction.as_mut().set_memory(vec![(uri, None)]);
-            let _ =
+            let open_handles =
                 Self::validate_in_memory_for_transaction(&state, &open_files, transaction.as_mut());
             state.commit_transaction(transaction);
+            // Closing a file is the point at which the modules only it imported become unreachable.
+            let collected =
+                state.collect_garbage(&open_handles, GARBAGE_COLLECTION_KEEP_RECENT_EPOCHS);
+            garbage_collection_stats
+                .collections
+                .fetch_add(1, Ordering::Relaxed);
+            garbage_collection_stats
+                .modules_collected
+                .fetch_add(collected, Ordering::Relaxed);
             queue_source_db_rebuild_and_recheck(
                 state.dupe(),
                 invalidated_configs,
@@ -1722,12 +2453,16 @@ impl Server {
         params: GotoDefinitionParams,
     ) -> Option<GotoDefinitionResponse> {
         let uri = &params.text_document_position_params.text_document.uri;
-        let handle = self.make_handle_if_enabled(uri, Some(GotoDefinition::METHOD))?;
+        let (handle, lsp_config) = self
+            .make_handle_with_lsp_analysis_config_if_enabled(uri, Some(GotoDefinition::METHOD))?;
+        let preference = lsp_config
+            .and_then(|c| c.definition_preference)
+            .unwrap_or_default();
         let info = transaction.get_module_info(&handle)?;
         let range = info
             .lined_buffer()
             .from_lsp_position(params.text_document_position_params.position);
-        let targets = transaction.goto_definition(&handle, range);
+        let targets = transaction.goto_definition_with_preference(&handle, range, preference);
         let mut lsp_targets = targets
             .iter()
             .filter_map(to_lsp_location)
@@ -1773,7 +2508,7 @@ impl Server {
         params: CompletionParams,
     ) -> anyhow::Result<CompletionResponse> {
         let uri = &params.text_document_position.text_document.uri;
-        let (handle, import_format) = match self
+        let (handle, import_format, dynamic_attributes) = match self
             .make_handle_with_lsp_analysis_config_if_enabled(uri, Some(Completion::METHOD))
         {
             None => {
@@ -1782,7 +2517,13 @@ impl Server {
                     items: Vec::new(),
                 }));
             }
-            Some((x, config)) => (x, config.and_then(|c| c.import_format).unwrap_or_default()),
+            Some((x, config)) => (
+                x,
+                config.and_then(|c| c.import_format).unwrap_or_default(),
+                config
+                    .and_then(|c| c.dynamic_attributes)
+                    .unwrap_or_default(),
+            ),
         };
         let (items, is_incomplete) = transaction
             .get_module_info(&handle)
@@ -1792,9 +2533,19 @@ impl Server {
                     info.lined_buffer()
                         .from_lsp_position(params.text_document_position.position),
                     import_format,
+                    dynamic_attributes,
+                    self.supports_completion_snippets(),
                 )
             })
             .unwrap_or_default();
+        let items = if self.supports_markdown_completion_documentation() {
+            items
+        } else {
+            items.into_map(|item| CompletionItem {
+                documentation: item.documentation.map(documentation_to_plaintext),
+                ..item
+            })
+        };
         Ok(CompletionResponse::List(CompletionList {
             is_incomplete,
             items,
@@ -1814,7 +2565,7 @@ impl Server {
         let import_format = lsp_config.and_then(|c| c.import_format).unwrap_or_default();
         let module_info = transaction.get_module_info(&handle)?;
         let range = module_info.lined_buffer().from_lsp_range(params.range);
-        let code_actions = transaction
+        let mut code_actions = transaction
             .local_quickfix_code_actions(&handle, range, import_format)?
             .into_map(|(title, info, range, insert_text)| {
                 CodeActionOrCommand::CodeAction(CodeAction {
@@ -1833,6 +2584,78 @@ impl Server {
                     ..Default::default()
                 })
             });
+        let refactors = transaction
+            .convert_typed_construct_code_actions(&handle, range, import_format)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| (CodeActionKind::REFACTOR_REWRITE, x))
+            .chain(
+                transaction
+                    .extract_protocol_code_actions(&handle, range)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(
+                        transaction
+                            .extract_variable_code_actions(&handle, range, import_format)
+                            .unwrap_or_default(),
+                    )
+                    .map(|x| (CodeActionKind::REFACTOR_EXTRACT, x)),
+            )
+            .chain(
+                transaction
+                    .inline_variable_code_actions(&handle, range)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| (CodeActionKind::REFACTOR_INLINE, x)),
+            )
+            .chain(
+                transaction
+                    .add_type_annotation_code_actions(&handle, range, import_format)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(
+                        transaction
+                            .implement_members_code_actions(&handle, range)
+                            .unwrap_or_default(),
+                    )
+                    .chain(
+                        transaction
+                            .suppress_error_code_actions(&handle, range)
+                            .unwrap_or_default(),
+                    )
+                    .map(|x| (CodeActionKind::QUICKFIX, x)),
+            )
+            .chain(
+                transaction
+                    .organize_imports_code_actions(&handle)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| (CodeActionKind::SOURCE_ORGANIZE_IMPORTS, x)),
+            )
+            .chain(
+                transaction
+                    .add_type_annotations_code_actions(&handle, import_format)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| (CodeActionKind::new("source.addTypeAnnotations"), x)),
+            );
+        code_actions.extend(refactors.map(|(kind, (title, edits))| {
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(kind),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(
+                        uri.clone(),
+                        edits.into_map(|(info, range, new_text)| TextEdit {
+                            range: info.lined_buffer().to_lsp_range(range),
+                            new_text,
+                        }),
+                    )])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        }));
         Some(code_actions)
     }
 
@@ -1849,10 +2672,10 @@ impl Server {
             .from_lsp_position(params.text_document_position_params.position);
         Some(
             transaction
-                .find_local_references(&handle, position)
-                .into_map(|range| DocumentHighlight {
+                .document_highlights(&handle, position)
+                .into_map(|(range, kind)| DocumentHighlight {
                     range: info.lined_buffer().to_lsp_range(range),
-                    kind: None,
+                    kind: Some(kind),
                 }),
         )
     }
@@ -1866,7 +2689,7 @@ impl Server {
         ide_transaction_manager: &mut TransactionManager<'a>,
         uri: &Url,
         position: Position,
-        map_result: impl FnOnce(Vec<(Url, Vec<Range>)>) -> V + Send + Sync + 'static,
+        map_result: impl FnOnce(Vec<(Module, Vec<TextRange>)>) -> V + Send + Sync + 'static,
     ) {
         let Some(handle) = self.make_handle_if_enabled(uri, Some(References::METHOD)) else {
             return self.send_response(new_response::<Option<V>>(request_id, Ok(None)));
@@ -1899,6 +2722,17 @@ impl Server {
             return self.send_response(new_response::<Option<V>>(request_id, Ok(None)));
         };
         ide_transaction_manager.save(transaction);
+        if self.find_reference_queue.pending() >= MAX_PENDING_FIND_REFERENCES {
+            let message = format!(
+                "Find reference request {request_id} is canceled because too many are pending"
+            );
+            eprintln!("{message}");
+            return self.send_response(Response::new_err(
+                request_id,
+                ErrorCode::RequestCanceled as i32,
+                message,
+            ));
+        }
         let state = self.state.dupe();
         let open_files = self.open_files.dupe();
         let cancellation_handles = self.cancellation_handles.dupe();
@@ -1916,19 +2750,10 @@ impl Server {
                 TextRangeWithModule::new(module, definition_range),
             ) {
                 Ok(global_references) => {
-                    let mut locations = Vec::new();
-                    for (info, ranges) in global_references {
-                        if let Some(uri) = module_info_to_uri(&info) {
-                            locations.push((
-                                uri,
-                                ranges.into_map(|range| info.lined_buffer().to_lsp_range(range)),
-                            ));
-                        };
-                    }
                     cancellation_handles.lock().remove(&request_id);
                     connection.send(Message::Response(new_response(
                         request_id,
-                        Ok(Some(map_result(locations))),
+                        Ok(Some(map_result(global_references))),
                     )));
                 }
                 Err(Cancelled) => {
@@ -1944,6 +2769,20 @@ impl Server {
         }));
     }
 
+    /// Convert the references found by `async_find_references_helper` to LSP locations.
+    fn references_to_lsp(references: Vec<(Module, Vec<TextRange>)>) -> Vec<(Url, Vec<Range>)> {
+        let mut locations = Vec::new();
+        for (info, ranges) in references {
+            if let Some(uri) = module_info_to_uri(&info) {
+                locations.push((
+                    uri,
+                    ranges.into_map(|range| info.lined_buffer().to_lsp_range(range)),
+                ));
+            };
+        }
+        locations
+    }
+
     fn references<'a>(
         &'a self,
         request_id: RequestId,
@@ -1957,7 +2796,7 @@ impl Server {
             params.text_document_position.position,
             move |results| {
                 let mut locations = Vec::new();
-                for (uri, ranges) in results {
+                for (uri, ranges) in Self::references_to_lsp(results) {
                     for range in ranges {
                         locations.push(Location {
                             uri: uri.clone(),
@@ -1970,12 +2809,27 @@ impl Server {
         );
     }
 
+    /// A rename is only complete once the indexer has found every file that could refer to the
+    /// symbol, so make sure it has. Without an index we only see the open files and what they
+    /// import, so warn that references elsewhere won't be renamed.
+    fn index_for_rename(&self) {
+        if self.indexing_mode == IndexingMode::None {
+            self.send_notification::<ShowMessage>(ShowMessageParams {
+                typ: MessageType::WARNING,
+                message: "Indexing is disabled, so rename only updates open files and the files they import".to_owned(),
+            });
+        } else {
+            self.populate_workspace_files_if_necessary();
+        }
+    }
+
     fn rename<'a>(
         &'a self,
         request_id: RequestId,
         ide_transaction_manager: &mut TransactionManager<'a>,
         params: RenameParams,
     ) {
+        self.index_for_rename();
         self.async_find_references_helper(
             request_id,
             ide_transaction_manager,
@@ -1983,7 +2837,7 @@ impl Server {
             params.text_document_position.position,
             move |results| {
                 let mut changes = HashMap::new();
-                for (uri, ranges) in results {
+                for (uri, ranges) in Self::references_to_lsp(results) {
                     changes.insert(
                         uri,
                         ranges.into_map(|range| TextEdit {
@@ -2000,6 +2854,199 @@ impl Server {
         );
     }
 
+    fn rename_preview<'a>(
+        &'a self,
+        request_id: RequestId,
+        ide_transaction_manager: &mut TransactionManager<'a>,
+        params: RenameParams,
+    ) {
+        self.index_for_rename();
+        self.async_find_references_helper(
+            request_id,
+            ide_transaction_manager,
+            &params.text_document_position.text_document.uri,
+            params.text_document_position.position,
+            move |results| rename_preview(results, &params.new_name),
+        );
+    }
+
+    fn call_hierarchy_item(target: &CallHierarchyTarget) -> Option<CallHierarchyItem> {
+        let lined_buffer = target.module.lined_buffer();
+        Some(CallHierarchyItem {
+            name: target.name.clone(),
+            kind: target.kind.to_lsp_symbol_kind(),
+            tags: None,
+            detail: Some(target.module.name().to_string()),
+            uri: module_info_to_uri(&target.module)?,
+            range: lined_buffer.to_lsp_range(target.range),
+            selection_range: lined_buffer.to_lsp_range(target.selection_range),
+            data: None,
+        })
+    }
+
+    fn prepare_call_hierarchy(
+        &self,
+        transaction: &Transaction<'_>,
+        params: CallHierarchyPrepareParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(CallHierarchyPrepare::METHOD))?;
+        let info = transaction.get_module_info(&handle)?;
+        let position = info
+            .lined_buffer()
+            .from_lsp_position(params.text_document_position_params.position);
+        let items = transaction
+            .prepare_call_hierarchy(&handle, position)
+            .iter()
+            .filter_map(Self::call_hierarchy_item)
+            .collect::<Vec<_>>();
+        if items.is_empty() { None } else { Some(items) }
+    }
+
+    fn call_hierarchy_incoming_calls<'a>(
+        &'a self,
+        request_id: RequestId,
+        ide_transaction_manager: &mut TransactionManager<'a>,
+        params: CallHierarchyIncomingCallsParams,
+    ) {
+        if self
+            .make_handle_if_enabled(&params.item.uri, Some(CallHierarchyIncomingCalls::METHOD))
+            .is_none()
+        {
+            return self.send_response(new_response::<Option<Vec<CallHierarchyIncomingCall>>>(
+                request_id,
+                Ok(None),
+            ));
+        }
+        self.async_find_references_helper(
+            request_id,
+            ide_transaction_manager,
+            &params.item.uri,
+            params.item.selection_range.start,
+            move |results| {
+                incoming_calls(results)
+                    .into_iter()
+                    .filter_map(|(target, ranges)| {
+                        let lined_buffer = target.module.lined_buffer();
+                        Some(CallHierarchyIncomingCall {
+                            from_ranges: ranges.into_map(|x| lined_buffer.to_lsp_range(x)),
+                            from: Self::call_hierarchy_item(&target)?,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+    }
+
+    fn call_hierarchy_outgoing_calls(
+        &self,
+        transaction: &Transaction<'_>,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let handle = self
+            .make_handle_if_enabled(&params.item.uri, Some(CallHierarchyOutgoingCalls::METHOD))?;
+        let info = transaction.get_module_info(&handle)?;
+        let position = info
+            .lined_buffer()
+            .from_lsp_position(params.item.selection_range.start);
+        Some(
+            transaction
+                .outgoing_calls(&handle, position)
+                .into_iter()
+                .filter_map(|(target, ranges)| {
+                    Some(CallHierarchyOutgoingCall {
+                        to: Self::call_hierarchy_item(&target)?,
+                        from_ranges: ranges.into_map(|x| info.lined_buffer().to_lsp_range(x)),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn type_hierarchy_item(target: &CallHierarchyTarget) -> Option<TypeHierarchyItem> {
+        let lined_buffer = target.module.lined_buffer();
+        Some(TypeHierarchyItem {
+            name: target.name.clone(),
+            kind: target.kind.to_lsp_symbol_kind(),
+            tags: None,
+            detail: Some(target.module.name().to_string()),
+            uri: module_info_to_uri(&target.module)?,
+            range: lined_buffer.to_lsp_range(target.range),
+            selection_range: lined_buffer.to_lsp_range(target.selection_range),
+            data: None,
+        })
+    }
+
+    fn prepare_type_hierarchy(
+        &self,
+        transaction: &Transaction<'_>,
+        params: TypeHierarchyPrepareParams,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(TypeHierarchyPrepare::METHOD))?;
+        let info = transaction.get_module_info(&handle)?;
+        let position = info
+            .lined_buffer()
+            .from_lsp_position(params.text_document_position_params.position);
+        let items = transaction
+            .prepare_type_hierarchy(&handle, position)
+            .iter()
+            .filter_map(Self::type_hierarchy_item)
+            .collect::<Vec<_>>();
+        if items.is_empty() { None } else { Some(items) }
+    }
+
+    fn type_hierarchy_supertypes(
+        &self,
+        transaction: &Transaction<'_>,
+        params: TypeHierarchySupertypesParams,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let handle =
+            self.make_handle_if_enabled(&params.item.uri, Some(TypeHierarchySupertypes::METHOD))?;
+        let info = transaction.get_module_info(&handle)?;
+        let position = info
+            .lined_buffer()
+            .from_lsp_position(params.item.selection_range.start);
+        Some(
+            transaction
+                .supertypes(&handle, position)
+                .iter()
+                .filter_map(Self::type_hierarchy_item)
+                .collect(),
+        )
+    }
+
+    fn type_hierarchy_subtypes<'a>(
+        &'a self,
+        request_id: RequestId,
+        ide_transaction_manager: &mut TransactionManager<'a>,
+        params: TypeHierarchySubtypesParams,
+    ) {
+        // Subclasses can be anywhere in the project, so we rely on the indexer to find them.
+        if self.indexing_mode == IndexingMode::None
+            || self
+                .make_handle_if_enabled(&params.item.uri, Some(TypeHierarchySubtypes::METHOD))
+                .is_none()
+        {
+            return self.send_response(new_response::<Option<Vec<TypeHierarchyItem>>>(
+                request_id,
+                Ok(None),
+            ));
+        }
+        self.async_find_references_helper(
+            request_id,
+            ide_transaction_manager,
+            &params.item.uri,
+            params.item.selection_range.start,
+            move |results| {
+                subtypes(results)
+                    .iter()
+                    .filter_map(Self::type_hierarchy_item)
+                    .collect::<Vec<_>>()
+            },
+        );
+    }
+
     fn prepare_rename(
         &self,
         transaction: &Transaction<'_>,
@@ -2028,6 +3075,34 @@ impl Server {
         transaction.get_signature_help_at(&handle, position)
     }
 
+    /// Remember that the client can see the file at `uri`, since it asked for something only
+    /// shown on screen, such as semantic tokens or inlay hints.
+    fn mark_visible(&self, uri: &Url) {
+        if let Ok(path) = uri.to_file_path() {
+            self.visible_files.lock().insert(path, Instant::now());
+        }
+    }
+
+    /// The response to the request `R` about `uri`, computed by `compute` unless it is an open
+    /// file and neither it nor anything else has changed since the same request was last made.
+    fn memoized<R: lsp_types::request::Request>(
+        &self,
+        uri: &Url,
+        params: &R::Params,
+        compute: impl FnOnce() -> R::Result,
+    ) -> Value {
+        let version = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| self.version_info.lock().get(&path).copied());
+        match version {
+            Some(version) => self
+                .query_cache
+                .get_or_compute::<R>(version, params, compute),
+            None => serde_json::to_value(compute()).unwrap(),
+        }
+    }
+
     fn hover(&self, transaction: &Transaction<'_>, params: HoverParams) -> Option<Hover> {
         let uri = &params.text_document_position_params.text_document.uri;
         let handle = self.make_handle_if_enabled(uri, Some(HoverRequest::METHOD))?;
@@ -2036,7 +3111,12 @@ impl Server {
             .lined_buffer()
             .from_lsp_position(params.text_document_position_params.position);
 
-        get_hover(transaction, &handle, position)
+        let hover = get_hover(transaction, &handle, position)?;
+        if self.supports_markdown_hover() {
+            Some(hover)
+        } else {
+            Some(hover_to_plaintext(hover))
+        }
     }
 
     fn inlay_hints(
@@ -2049,30 +3129,39 @@ impl Server {
         let (handle, lsp_analysis_config) = self
             .make_handle_with_lsp_analysis_config_if_enabled(uri, Some(InlayHintRequest::METHOD))?;
         let info = transaction.get_module_info(&handle)?;
-        let t = transaction.inlay_hints(
-            &handle,
-            lsp_analysis_config
-                .and_then(|c| c.inlay_hints)
-                .unwrap_or_default(),
-        )?;
+        let config = lsp_analysis_config
+            .and_then(|c| c.inlay_hints)
+            .unwrap_or(self.default_inlay_hints);
+        let t = transaction.inlay_hints(&handle, config)?;
         let res = t
             .into_iter()
             .filter_map(|x| {
                 let position = info.lined_buffer().to_lsp_position(x.0);
                 // The range is half-open, so the end position is exclusive according to the spec.
                 if position >= range.start && position < range.end {
+                    let label = config.label(&x.1);
+                    // Hovering a truncated hint expands it.
+                    let tooltip = (label != x.1).then(|| {
+                        InlayHintTooltip::String(
+                            x.1.strip_prefix(" -> ")
+                                .or_else(|| x.1.strip_prefix(": "))
+                                .unwrap_or(&x.1)
+                                .to_owned(),
+                        )
+                    });
                     Some(InlayHint {
                         position,
-                        label: InlayHintLabel::String(x.1.clone()),
+                        label: InlayHintLabel::String(label),
                         kind: None,
                         text_edits: Some(vec![TextEdit {
                             range: Range::new(position, position),
                             new_text: x.1,
                         }]),
-                        tooltip: None,
+                        tooltip,
                         padding_left: None,
                         padding_right: None,
-                        data: None,
+                        // Resolving the hint adds the imports the annotation needs.
+                        data: Some(serde_json::json!(uri)),
                     })
                 } else {
                     None
@@ -2082,6 +3171,42 @@ impl Server {
         Some(res)
     }
 
+    /// Add the imports needed by the annotation an inlay hint inserts to its text edits.
+    fn inlay_hint_resolve(&self, transaction: &Transaction<'_>, mut hint: InlayHint) -> InlayHint {
+        let Some(uri) = hint
+            .data
+            .as_ref()
+            .and_then(|x| serde_json::from_value::<Url>(x.clone()).ok())
+        else {
+            return hint;
+        };
+        let Some((handle, lsp_analysis_config)) = self
+            .make_handle_with_lsp_analysis_config_if_enabled(&uri, Some(InlayHintRequest::METHOD))
+        else {
+            return hint;
+        };
+        let Some(info) = transaction.get_module_info(&handle) else {
+            return hint;
+        };
+        let import_format = lsp_analysis_config
+            .and_then(|c| c.import_format)
+            .unwrap_or_default();
+        if let Some(text_edits) = &mut hint.text_edits
+            && let Some(annotation) = text_edits.first()
+        {
+            let imports =
+                transaction.inlay_hint_import_edits(&handle, &annotation.new_text, import_format);
+            text_edits.extend(imports.into_iter().map(|(position, new_text)| {
+                let position = info.lined_buffer().to_lsp_position(position);
+                TextEdit {
+                    range: Range::new(position, position),
+                    new_text,
+                }
+            }));
+        }
+        hint
+    }
+
     fn semantic_tokens_full(
         &self,
         transaction: &Transaction<'_>,
@@ -2146,51 +3271,149 @@ impl Server {
         transaction: &Transaction<'_>,
         query: &str,
     ) -> Vec<SymbolInformation> {
+        // Without an index, only the open files are known to be part of the workspace, rather
+        // than their dependencies. Installed packages and generated code are left out, as users
+        // are looking for the code they work on.
+        let is_indexed = self.indexing_mode != IndexingMode::None;
+        let open_files = self.open_files.read();
+        let config_finder = self.state.config_finder();
+        let include = |handle: &Handle| {
+            (is_indexed || open_files.contains_key(handle.path().as_path()))
+                && !matches!(
+                    module_origin(config_finder, handle),
+                    ModuleOrigin::ThirdParty | ModuleOrigin::Generated
+                )
+        };
         transaction
-            .workspace_symbols(query)
+            .workspace_symbols(query, &include)
             .unwrap_or_default()
             .into_iter()
             .filter_map(|(name, kind, location)| {
+                let container_name = location.module.name().to_string();
                 to_lsp_location(&location).map(|location| SymbolInformation {
                     name,
                     kind,
                     location,
                     tags: None,
                     deprecated: None,
-                    container_name: None,
+                    container_name: Some(container_name),
                 })
             })
             .collect()
     }
 
+    fn server_status(&self) -> ServerStatusResult {
+        ServerStatusResult {
+            open_files: self.open_files.read().len(),
+            modules: self.state.transaction().module_count(),
+            garbage_collections: self
+                .garbage_collection_stats
+                .collections
+                .load(Ordering::Relaxed),
+            modules_collected: self
+                .garbage_collection_stats
+                .modules_collected
+                .load(Ordering::Relaxed),
+        }
+    }
+
+    fn trace_import(&self, params: TraceImportParams) -> Option<TraceImportResult> {
+        let handle = self.make_handle_if_enabled(&params.text_document.uri, None)?;
+        let config = self
+            .state
+            .config_finder()
+            .python_file(handle.module(), handle.path());
+        let trace = ImportTrace::new(
+            &config,
+            ModuleName::from_str(&params.module),
+            Some(handle.path()),
+        );
+        Some(TraceImportResult {
+            resolved: trace.result().clone().finding().map(|x| x.to_string()),
+            report: trace.to_string(),
+        })
+    }
+
+    /// Whether the client asked for `pyrefly/textDocument/unreachableRanges` notifications.
+    fn supports_completion_snippets(&self) -> bool {
+        self.initialize_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|x| x.completion.as_ref())
+            .and_then(|x| x.completion_item.as_ref())
+            .and_then(|x| x.snippet_support)
+            .unwrap_or(false)
+    }
+
+    /// Whether the client can display Markdown hover contents. Clients that don't declare which
+    /// formats they support are assumed to support Markdown.
+    fn supports_markdown_hover(&self) -> bool {
+        self.initialize_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|x| x.hover.as_ref())
+            .and_then(|x| x.content_format.as_ref())
+            .is_none_or(|x| x.contains(&MarkupKind::Markdown))
+    }
+
+    /// Whether the client can display Markdown completion item documentation, assumed as for
+    /// [`Self::supports_markdown_hover`].
+    fn supports_markdown_completion_documentation(&self) -> bool {
+        self.initialize_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|x| x.completion.as_ref())
+            .and_then(|x| x.completion_item.as_ref())
+            .and_then(|x| x.documentation_format.as_ref())
+            .is_none_or(|x| x.contains(&MarkupKind::Markdown))
+    }
+
+    fn supports_unreachable_ranges(&self) -> bool {
+        self.initialize_params
+            .capabilities
+            .experimental
+            .as_ref()
+            .and_then(|x| x.get("unreachableRanges"))
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// The ranges of a module that are unreachable under its Python version and platform.
+    fn unreachable_ranges(transaction: &Transaction<'_>, handle: &Handle) -> Vec<Range> {
+        let (Some(ast), Some(module_info)) = (
+            transaction.get_ast(handle),
+            transaction.get_module_info(handle),
+        ) else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        disabled_ranges_for_module(ast.as_ref(), handle.sys_info())
+            .into_iter()
+            .filter(|range| !range.is_empty() && seen.insert(*range))
+            .map(|range| module_info.lined_buffer().to_lsp_range(range))
+            .collect()
+    }
+
     fn append_unreachable_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
         items: &mut Vec<Diagnostic>,
     ) {
-        if let (Some(ast), Some(module_info)) = (
-            transaction.get_ast(handle),
-            transaction.get_module_info(handle),
-        ) {
-            let disabled_ranges = disabled_ranges_for_module(ast.as_ref(), handle.sys_info());
-            let mut seen = HashSet::new();
-            for range in disabled_ranges {
-                if range.is_empty() || !seen.insert(range) {
-                    continue;
-                }
-                let lsp_range = module_info.lined_buffer().to_lsp_range(range);
-                items.push(Diagnostic {
-                    range: lsp_range,
-                    severity: Some(DiagnosticSeverity::HINT),
-                    source: Some("Pyrefly".to_owned()),
-                    message: "This code is unreachable for the current configuration".to_owned(),
-                    code: Some(NumberOrString::String("unreachable-code".to_owned())),
-                    code_description: None,
-                    related_information: None,
-                    tags: Some(vec![DiagnosticTag::UNNECESSARY]),
-                    data: None,
-                });
-            }
+        for lsp_range in Self::unreachable_ranges(transaction, handle) {
+            items.push(Diagnostic {
+                range: lsp_range,
+                severity: Some(DiagnosticSeverity::HINT),
+                source: Some("Pyrefly".to_owned()),
+                message: "This code is unreachable for the current configuration".to_owned(),
+                code: Some(NumberOrString::String("unreachable-code".to_owned())),
+                code_description: None,
+                related_information: None,
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                data: None,
+            });
         }
     }
 
@@ -2251,30 +3474,287 @@ impl Server {
         )
     }
 
-    fn document_diagnostics(
+    fn document_links(
         &self,
         transaction: &Transaction<'_>,
-        params: DocumentDiagnosticParams,
-    ) -> DocumentDiagnosticReport {
-        let handle = make_open_handle(
+        params: DocumentLinkParams,
+    ) -> Option<Vec<DocumentLink>> {
+        let handle = self
+            .make_handle_if_enabled(&params.text_document.uri, Some(DocumentLinkRequest::METHOD))?;
+        let module = transaction.get_module_info(&handle)?;
+        Some(
+            transaction
+                .document_links(&handle)
+                .into_iter()
+                .filter_map(|(range, target)| {
+                    let path = to_real_path(target.path())?;
+                    Some(DocumentLink {
+                        range: module.lined_buffer().to_lsp_range(range),
+                        target: Some(Url::from_file_path(path.absolutize()).ok()?),
+                        tooltip: Some(format!("Open module `{}`", target.module())),
+                        data: None,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn selection_ranges(
+        &self,
+        transaction: &Transaction<'_>,
+        params: SelectionRangeParams,
+    ) -> Option<Vec<SelectionRange>> {
+        let handle = self.make_handle_if_enabled(
+            &params.text_document.uri,
+            Some(SelectionRangeRequest::METHOD),
+        )?;
+        let module = transaction.get_module_info(&handle)?;
+        let positions = params
+            .positions
+            .iter()
+            .map(|x| module.lined_buffer().from_lsp_position(*x))
+            .collect::<Vec<_>>();
+        let ranges = transaction.selection_ranges(&handle, &positions)?;
+        Some(
+            ranges
+                .into_iter()
+                .zip(params.positions)
+                .map(|(ranges, position)| {
+                    // Build the chain from the outermost range in.
+                    ranges
+                        .into_iter()
+                        .rev()
+                        .fold(None, |parent, range| {
+                            Some(SelectionRange {
+                                range: module.lined_buffer().to_lsp_range(range),
+                                parent: parent.map(Box::new),
+                            })
+                        })
+                        .unwrap_or(SelectionRange {
+                            range: Range::new(position, position),
+                            parent: None,
+                        })
+                })
+                .collect(),
+        )
+    }
+
+    /// Check the files, even those that aren't open, and return their diagnostics.
+    fn check_files(
+        &self,
+        transaction: &mut Transaction<'_>,
+        params: CheckFilesParams,
+    ) -> CheckFilesResult {
+        let _status = self.status.start(Status::Checking {
+            files: params.uris.len(),
+        });
+        let open_files = self.open_files.read().clone();
+        let files = params
+            .uris
+            .into_iter()
+            .filter_map(|uri| {
+                let path = uri.to_file_path().ok()?;
+                let handle = if open_files.contains_key(&path) {
+                    make_open_handle(&self.state, &path)
+                } else {
+                    handle_from_module_path(&self.state, ModulePath::filesystem(path.clone()))
+                };
+                Some((uri, path, handle))
+            })
+            .collect::<Vec<_>>();
+        // Files that aren't open may have just been written, before we've heard about it.
+        transaction.invalidate_disk(
+            &files
+                .iter()
+                .filter(|(_, path, _)| !open_files.contains_key(path))
+                .map(|(_, path, _)| path.clone())
+                .collect::<Vec<_>>(),
+        );
+        let handles = files
+            .iter()
+            .map(|(_, _, handle)| handle.dupe())
+            .collect::<Vec<_>>();
+        transaction.run(&handles, Require::Everything);
+        CheckFilesResult {
+            items: files
+                .into_iter()
+                .map(|(uri, path, handle)| {
+                    let mut diagnostics = Vec::new();
+                    for e in transaction.get_errors(once(&handle)).collect_errors().shown {
+                        if let Some((_, diag)) = self.get_diag_if_shown(&e, |x| x == path) {
+                            diagnostics.push(diag);
+                        }
+                    }
+                    Self::append_unreachable_diagnostics(transaction, &handle, &mut diagnostics);
+                    FileDiagnostics { uri, diagnostics }
+                })
+                .collect(),
+        }
+    }
+
+    /// The changes to the diagnostics of the open files if `params.edits` were applied. The
+    /// edited text is checked in a transaction of its own that is thrown away, so neither the
+    /// document nor the diagnostics we publish change.
+    fn speculative_check(&self, params: SpeculativeCheckParams) -> Option<SpeculativeCheckResult> {
+        let edited = params.text_document.uri.to_file_path().ok()?;
+        let text = self.open_files.read().get(&edited)?.dupe();
+        let edit = SpeculativeEdit::new(text, &params.edits)?;
+        let mut paths = self.open_files.read().keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+
+        let mut transaction = self.state.transaction();
+        let handles = Self::validate_in_memory_for_transaction(
             &self.state,
-            &params.text_document.uri.to_file_path().unwrap(),
+            &self.open_files,
+            &mut transaction,
         );
+        let before = paths
+            .iter()
+            .map(|x| self.file_diagnostics(&transaction, x))
+            .collect::<Vec<_>>();
+        transaction.set_memory(vec![(edited.clone(), Some(edit.text()))]);
+        transaction.run(&handles, Require::Everything);
+
+        let mut items = Vec::new();
+        for (path, before) in paths.into_iter().zip(before) {
+            let after = self.file_diagnostics(&transaction, &path);
+            let (added, removed) = if path == edited {
+                diagnostics_delta(before, after, |x| edit.map_range(x))
+            } else {
+                diagnostics_delta(before, after, Some)
+            };
+            if (!added.is_empty() || !removed.is_empty())
+                && let Ok(uri) = Url::from_file_path(path.absolutize())
+            {
+                items.push(DiagnosticsDelta {
+                    uri,
+                    added,
+                    removed,
+                });
+            }
+        }
+        Some(SpeculativeCheckResult { items })
+    }
+
+    /// Whether `params.snippet` type-checks in place of `params.range`, from the diagnostics a
+    /// speculative check of that edit would add to the document.
+    fn check_snippet(&self, params: CheckSnippetParams) -> Option<CheckSnippetResult> {
+        let path = params.text_document.uri.to_file_path().ok()?.absolutize();
+        let result = self.speculative_check(SpeculativeCheckParams {
+            text_document: params.text_document,
+            edits: vec![TextEdit::new(params.range, params.snippet)],
+        })?;
+        let diagnostics = result
+            .items
+            .into_iter()
+            .find(|x| x.uri.to_file_path().ok().as_ref() == Some(&path))
+            .map_or_else(Vec::new, |x| x.added);
+        Some(CheckSnippetResult::new(diagnostics))
+    }
+
+    fn file_diagnostics(&self, transaction: &Transaction<'_>, path: &Path) -> Vec<Diagnostic> {
+        let handle = make_open_handle(&self.state, path);
         let mut items = Vec::new();
         let open_files = &self.open_files.read();
         for e in transaction.get_errors(once(&handle)).collect_errors().shown {
-            if let Some((_, diag)) = self.get_diag_if_shown(&e, open_files) {
+            if let Some((_, diag)) = self.get_diag_if_shown(&e, |x| open_files.contains_key(x)) {
                 items.push(diag);
             }
         }
         Self::append_unreachable_diagnostics(transaction, &handle, &mut items);
-        DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
-            full_document_diagnostic_report: FullDocumentDiagnosticReport {
+        items
+    }
+
+    /// The diagnostics of a file, or `Err` with their result id if it is `previous_result_id`.
+    /// Result ids are only given to clients that declared support for pull diagnostics.
+    fn diagnostics_since(
+        &self,
+        transaction: &Transaction<'_>,
+        path: &Path,
+        previous_result_id: Option<&str>,
+    ) -> Result<FullDocumentDiagnosticReport, UnchangedDocumentDiagnosticReport> {
+        let items = self.file_diagnostics(transaction, path);
+        if !supports_pull_diagnostics(&self.initialize_params) {
+            return Ok(FullDocumentDiagnosticReport {
                 items,
                 result_id: None,
-            },
-            related_documents: None,
-        })
+            });
+        }
+        let result_id = diagnostics_result_id(&items);
+        if previous_result_id == Some(result_id.as_str()) {
+            Err(UnchangedDocumentDiagnosticReport { result_id })
+        } else {
+            Ok(FullDocumentDiagnosticReport {
+                items,
+                result_id: Some(result_id),
+            })
+        }
+    }
+
+    fn document_diagnostics(
+        &self,
+        transaction: &Transaction<'_>,
+        params: DocumentDiagnosticParams,
+    ) -> DocumentDiagnosticReport {
+        let path = params.text_document.uri.to_file_path().unwrap();
+        match self.diagnostics_since(transaction, &path, params.previous_result_id.as_deref()) {
+            Ok(full_document_diagnostic_report) => {
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                    full_document_diagnostic_report,
+                    related_documents: None,
+                })
+            }
+            Err(unchanged_document_diagnostic_report) => {
+                DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                    unchanged_document_diagnostic_report,
+                    related_documents: None,
+                })
+            }
+        }
+    }
+
+    /// The diagnostics of all the open files, which are the only ones we report diagnostics for.
+    fn workspace_diagnostics(
+        &self,
+        transaction: &Transaction<'_>,
+        params: WorkspaceDiagnosticParams,
+    ) -> WorkspaceDiagnosticReportResult {
+        let previous_result_ids = params
+            .previous_result_ids
+            .into_iter()
+            .map(|x| (x.uri, x.value))
+            .collect::<HashMap<_, _>>();
+        let mut paths = self.open_files.read().keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+        let mut items = Vec::new();
+        for path in paths {
+            let Ok(uri) = Url::from_file_path(path.absolutize()) else {
+                continue;
+            };
+            let version = self.version_info.lock().get(&path).map(|x| *x as i64);
+            let previous_result_id = previous_result_ids.get(&uri).map(|x| x.as_str());
+            items.push(
+                match self.diagnostics_since(transaction, &path, previous_result_id) {
+                    Ok(full_document_diagnostic_report) => WorkspaceDocumentDiagnosticReport::Full(
+                        WorkspaceFullDocumentDiagnosticReport {
+                            uri,
+                            version,
+                            full_document_diagnostic_report,
+                        },
+                    ),
+                    Err(unchanged_document_diagnostic_report) => {
+                        WorkspaceDocumentDiagnosticReport::Unchanged(
+                            WorkspaceUnchangedDocumentDiagnosticReport {
+                                uri,
+                                version,
+                                unchanged_document_diagnostic_report,
+                            },
+                        )
+                    }
+                },
+            );
+        }
+        WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })
     }
 
     /// Converts a [`WatchPattern`] into a [`GlobPattern`] that can be used and watched
@@ -2398,13 +3878,24 @@ impl Server {
     }
 
     /// Asynchronously invalidate configuration and then validate in-memory files
-    /// This ensures validate_in_memory() only runs after config invalidation completes
+    /// This ensures validate_in_memory() only runs after config invalidation completes.
+    /// A reload requested while one is queued is handled by that one, so a burst of config
+    /// changes only causes one.
     fn invalidate_config_and_validate_in_memory(&self) {
+        if self.config_reload_queued.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let config_reload_queued = self.config_reload_queued.dupe();
         let state = self.state.dupe();
         let lsp_queue = self.lsp_queue.dupe();
         let cancellation_handles = self.cancellation_handles.dupe();
         let open_files = self.open_files.dupe();
+        let status = self.status.dupe();
         self.recheck_queue.queue_task(Box::new(move || {
+            let _span = trace_span!("reload_config").entered();
+            // Any config change from now on needs a reload of its own.
+            config_reload_queued.store(false, Ordering::Relaxed);
+            let _status = status.start_with_progress(Status::ReloadingConfig);
             let mut transaction = state.new_committable_transaction(Require::indexing(), None);
             transaction.as_mut().invalidate_config();
 
@@ -2485,9 +3976,55 @@ mod tests {
             initialize_params,
             IndexingMode::None,
             0,
+            InlayHintConfig::default(),
         )
     }
 
+    #[test]
+    fn test_classify_edit() {
+        let change = |range: Option<(u32, u32)>, text: &str| TextDocumentContentChangeEvent {
+            range: range.map(|(start, end)| Range {
+                start: Position::new(start, 0),
+                end: Position::new(end, 4),
+            }),
+            range_length: None,
+            text: text.to_owned(),
+        };
+        assert_eq!(
+            EditKind::classify(&[change(Some((1, 1)), "x"), change(Some((3, 3)), "")]),
+            EditKind::Typing
+        );
+        assert_eq!(
+            EditKind::classify(&[change(Some((1, 1)), "x\ny")]),
+            EditKind::Structural
+        );
+        assert_eq!(
+            EditKind::classify(&[change(Some((1, 2)), "")]),
+            EditKind::Structural
+        );
+        assert_eq!(
+            EditKind::classify(&[change(None, "x")]),
+            EditKind::Structural
+        );
+    }
+
+    #[test]
+    fn test_edit_kind_max_deferral() {
+        for tier in [DiagnosticsTier::Visible, DiagnosticsTier::Others] {
+            assert!(EditKind::Structural.max_deferral(tier) < EditKind::Typing.max_deferral(tier));
+        }
+        for edit in [EditKind::Typing, EditKind::Structural] {
+            assert!(
+                edit.max_deferral(DiagnosticsTier::Visible)
+                    < edit.max_deferral(DiagnosticsTier::Others)
+            );
+        }
+        assert_eq!(
+            EditKind::Structural.max_deferral(DiagnosticsTier::Visible),
+            Duration::ZERO
+        );
+    }
+
     #[test]
     fn test_stdlib_paths_for_mac_and_windows_paths() {
         let server = create_test_server();
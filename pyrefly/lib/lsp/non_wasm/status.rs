@@ -0,0 +1,376 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Reports what the server is busy with: as `pyrefly/status` notifications, for clients that opt
+//! in, and as work done progress, for the long running tasks the server starts itself, such as
+//! indexing.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use crossbeam_channel::Sender;
+use dupe::Dupe;
+use lsp_server::Message;
+use lsp_server::Request;
+use lsp_server::RequestId;
+use lsp_types::InitializeParams;
+use lsp_types::NumberOrString;
+use lsp_types::ProgressParams;
+use lsp_types::ProgressParamsValue;
+use lsp_types::WorkDoneProgress;
+use lsp_types::WorkDoneProgressBegin;
+use lsp_types::WorkDoneProgressCreateParams;
+use lsp_types::WorkDoneProgressEnd;
+use lsp_types::WorkDoneProgressReport;
+use lsp_types::notification::Progress;
+use lsp_types::request::Request as _;
+use lsp_types::request::WorkDoneProgressCreate;
+use pyrefly_build::handle::Handle;
+use pyrefly_util::lock::Mutex;
+
+use crate::lsp::non_wasm::lsp::new_notification;
+use crate::lsp::wasm::status::Status;
+use crate::lsp::wasm::status::StatusNotification;
+use crate::lsp::wasm::status::StatusParams;
+use crate::state::load::Load;
+use crate::state::subscriber::Subscriber;
+
+/// The requests the server has sent to the client and is waiting on a response for.
+pub type OutgoingRequests = Arc<Mutex<HashMap<RequestId, Request>>>;
+
+struct Task {
+    id: u64,
+    status: Status,
+    /// The token of the work done progress showing this task, if any.
+    token: Option<NumberOrString>,
+}
+
+struct StatusReporterInner {
+    sender: Sender<Message>,
+    /// Whether the client asked for `pyrefly/status` notifications.
+    send_status: bool,
+    /// Whether the client supports work done progress started by the server.
+    work_done_progress: bool,
+    outgoing_request_id: Arc<AtomicI32>,
+    outgoing_requests: OutgoingRequests,
+    next_task: AtomicU64,
+    /// The tasks in progress, in the order they started. The client is shown the most recent one.
+    tasks: Mutex<Vec<Task>>,
+}
+
+#[derive(Clone, Dupe)]
+pub struct StatusReporter(Arc<StatusReporterInner>);
+
+impl StatusReporter {
+    pub fn new(
+        sender: Sender<Message>,
+        initialize_params: &InitializeParams,
+        outgoing_request_id: Arc<AtomicI32>,
+        outgoing_requests: OutgoingRequests,
+    ) -> Self {
+        let capabilities = &initialize_params.capabilities;
+        Self(Arc::new(StatusReporterInner {
+            sender,
+            send_status: capabilities
+                .experimental
+                .as_ref()
+                .and_then(|x| x.get("status"))
+                .and_then(|x| x.as_bool())
+                .unwrap_or(false),
+            work_done_progress: capabilities
+                .window
+                .as_ref()
+                .and_then(|x| x.work_done_progress)
+                .unwrap_or(false),
+            outgoing_request_id,
+            outgoing_requests,
+            next_task: AtomicU64::new(0),
+            tasks: Mutex::new(Vec::new()),
+        }))
+    }
+
+    fn send(&self, message: Message) {
+        // If the channel is closed, the server is shutting down and nobody is listening.
+        let _ = self.0.sender.send(message);
+    }
+
+    fn send_status(&self, status: &Status) {
+        if self.0.send_status {
+            self.send(Message::Notification(
+                new_notification::<StatusNotification>(StatusParams {
+                    status: status.clone(),
+                    message: status.message(),
+                }),
+            ));
+        }
+    }
+
+    fn send_progress(&self, token: &NumberOrString, progress: WorkDoneProgress) {
+        self.send(Message::Notification(new_notification::<Progress>(
+            ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(progress),
+            },
+        )));
+    }
+
+    /// Ask the client to create a work done progress, returning its token. Like other servers,
+    /// we don't wait for the client to acknowledge it before reporting progress.
+    fn create_progress(&self, id: u64) -> NumberOrString {
+        let token = NumberOrString::String(format!("pyrefly/{id}"));
+        let request_id = RequestId::from(self.0.outgoing_request_id.fetch_add(1, Ordering::SeqCst));
+        let request = Request {
+            id: request_id.clone(),
+            method: WorkDoneProgressCreate::METHOD.to_owned(),
+            params: serde_json::to_value(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .unwrap(),
+        };
+        self.0
+            .outgoing_requests
+            .lock()
+            .insert(request_id, request.clone());
+        self.send(Message::Request(request));
+        token
+    }
+
+    fn start_task(&self, status: Status, progress: bool) -> StatusTask {
+        let id = self.0.next_task.fetch_add(1, Ordering::Relaxed);
+        let token = (progress && self.0.work_done_progress).then(|| self.create_progress(id));
+        if let Some(token) = &token {
+            self.send_progress(
+                token,
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Pyrefly".to_owned(),
+                    cancellable: Some(false),
+                    message: Some(status.message()),
+                    percentage: match status {
+                        Status::Indexing { percentage } => Some(percentage),
+                        _ => None,
+                    },
+                }),
+            );
+        }
+        // Hold the lock while sending, so the statuses of concurrent tasks are sent in order.
+        let mut tasks = self.0.tasks.lock();
+        self.send_status(&status);
+        tasks.push(Task { id, status, token });
+        drop(tasks);
+        StatusTask {
+            reporter: self.dupe(),
+            id,
+        }
+    }
+
+    /// Report `status` until the returned task is dropped.
+    pub fn start(&self, status: Status) -> StatusTask {
+        self.start_task(status, false)
+    }
+
+    /// Like `start`, but also shows the task as work done progress, for tasks that take a while
+    /// and aren't a response to something the user just did.
+    pub fn start_with_progress(&self, status: Status) -> StatusTask {
+        self.start_task(status, true)
+    }
+
+    fn update(&self, id: u64, status: Status) {
+        let mut tasks = self.0.tasks.lock();
+        let is_last = tasks.last().is_some_and(|x| x.id == id);
+        let Some(task) = tasks.iter_mut().find(|x| x.id == id) else {
+            return;
+        };
+        if task.status == status {
+            return;
+        }
+        if let Some(token) = &task.token {
+            self.send_progress(
+                token,
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    cancellable: Some(false),
+                    message: Some(status.message()),
+                    percentage: match status {
+                        Status::Indexing { percentage } => Some(percentage),
+                        _ => None,
+                    },
+                }),
+            );
+        }
+        if is_last {
+            self.send_status(&status);
+        }
+        task.status = status;
+    }
+
+    fn finish(&self, id: u64) {
+        let mut tasks = self.0.tasks.lock();
+        let Some(index) = tasks.iter().position(|x| x.id == id) else {
+            return;
+        };
+        let task = tasks.remove(index);
+        if let Some(token) = &task.token {
+            self.send_progress(
+                token,
+                WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+            );
+        }
+        // If this task was being shown, show what the server is still doing instead.
+        if index == tasks.len() {
+            self.send_status(tasks.last().map_or(&Status::Idle, |x| &x.status));
+        }
+    }
+}
+
+/// Something the server is busy with, which finishes when dropped.
+pub struct StatusTask {
+    reporter: StatusReporter,
+    id: u64,
+}
+
+impl StatusTask {
+    /// A subscriber that reports the percentage of the modules a transaction has started that it
+    /// has finished, for tasks with a `Status::Indexing` status.
+    pub fn indexing_progress(&self) -> Box<dyn Subscriber> {
+        Box::new(IndexingProgress {
+            reporter: self.reporter.dupe(),
+            id: self.id,
+            counts: Mutex::new((0, 0)),
+        })
+    }
+}
+
+impl Drop for StatusTask {
+    fn drop(&mut self) {
+        self.reporter.finish(self.id);
+    }
+}
+
+struct IndexingProgress {
+    reporter: StatusReporter,
+    id: u64,
+    /// The number of modules started and finished.
+    counts: Mutex<(u64, u64)>,
+}
+
+impl IndexingProgress {
+    fn event(&self, f: impl FnOnce(&mut (u64, u64))) {
+        let mut counts = self.counts.lock();
+        f(&mut counts);
+        let (started, finished) = *counts;
+        drop(counts);
+        // Modules are discovered as we go, so the percentage can drop. Only ever report it going
+        // up.
+        let percentage = (finished * 100 / started.max(1)) as u32;
+        let tasks = self.reporter.0.tasks.lock();
+        let current = tasks.iter().find_map(|x| match x.status {
+            Status::Indexing { percentage } if x.id == self.id => Some(percentage),
+            _ => None,
+        });
+        drop(tasks);
+        if current.is_some_and(|x| percentage > x) {
+            self.reporter
+                .update(self.id, Status::Indexing { percentage });
+        }
+    }
+}
+
+impl Subscriber for IndexingProgress {
+    fn start_work(&self, _: &Handle) {
+        self.event(|x| x.0 += 1);
+    }
+
+    fn finish_work(&self, _: &Handle, _: &Arc<Load>) {
+        self.event(|x| x.1 += 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam_channel::Receiver;
+    use lsp_types::ClientCapabilities;
+    use lsp_types::WindowClientCapabilities;
+    use serde_json::json;
+
+    use super::*;
+
+    fn reporter(work_done_progress: bool) -> (StatusReporter, Receiver<Message>) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let initialize_params = InitializeParams {
+            capabilities: ClientCapabilities {
+                experimental: Some(json!({"status": true})),
+                window: Some(WindowClientCapabilities {
+                    work_done_progress: Some(work_done_progress),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let reporter = StatusReporter::new(
+            sender,
+            &initialize_params,
+            Arc::new(AtomicI32::new(1)),
+            Arc::new(Mutex::new(HashMap::new())),
+        );
+        (reporter, receiver)
+    }
+
+    /// The methods and `pyrefly/status` states of the messages sent so far.
+    fn sent(receiver: &Receiver<Message>) -> Vec<String> {
+        receiver
+            .try_iter()
+            .map(|x| match x {
+                Message::Notification(x) if x.method == StatusNotification::METHOD => {
+                    x.params["state"].as_str().unwrap().to_owned()
+                }
+                Message::Notification(x) => x.method,
+                Message::Request(x) => x.method,
+                Message::Response(_) => "response".to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_status_of_nested_tasks() {
+        let (reporter, receiver) = reporter(false);
+        let indexing = reporter.start_with_progress(Status::Indexing { percentage: 0 });
+        let checking = reporter.start(Status::Checking { files: 2 });
+        // Not shown, since checking started later.
+        reporter.update(indexing.id, Status::Indexing { percentage: 50 });
+        assert_eq!(sent(&receiver), vec!["indexing", "checking"]);
+        drop(checking);
+        drop(indexing);
+        assert_eq!(sent(&receiver), vec!["indexing", "idle"]);
+    }
+
+    #[test]
+    fn test_work_done_progress() {
+        let (reporter, receiver) = reporter(true);
+        let checking = reporter.start(Status::Checking { files: 1 });
+        let indexing = reporter.start_with_progress(Status::Indexing { percentage: 0 });
+        reporter.update(indexing.id, Status::Indexing { percentage: 50 });
+        drop(indexing);
+        drop(checking);
+        assert_eq!(
+            sent(&receiver),
+            vec![
+                "checking",
+                "window/workDoneProgress/create",
+                "$/progress",
+                "indexing",
+                "$/progress",
+                "indexing",
+                "$/progress",
+                "checking",
+                "idle",
+            ]
+        );
+    }
+}
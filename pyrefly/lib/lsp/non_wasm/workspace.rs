@@ -30,6 +30,8 @@ use crate::config::finder::ConfigFinder;
 use crate::state::lsp::DisplayTypeErrors;
 use crate::state::lsp::ImportFormat;
 use crate::state::lsp::InlayHintConfig;
+use crate::state::lsp::dynamic_attributes::DynamicAttributes;
+use crate::state::lsp::stub_source::DefinitionPreference;
 
 /// Information about the Python environment provided by this workspace.
 #[derive(Debug, Clone)]
@@ -199,6 +201,12 @@ pub struct DisabledLanguageServices {
     pub document_symbol: bool,
     #[serde(default)]
     pub semantic_tokens: bool,
+    #[serde(default)]
+    pub call_hierarchy: bool,
+    #[serde(default)]
+    pub type_hierarchy: bool,
+    #[serde(default)]
+    pub document_link: bool,
 }
 
 impl DisabledLanguageServices {
@@ -220,6 +228,13 @@ impl DisabledLanguageServices {
             "textDocument/semanticTokens/full" | "textDocument/semanticTokens/range" => {
                 self.semantic_tokens
             }
+            "textDocument/prepareCallHierarchy"
+            | "callHierarchy/incomingCalls"
+            | "callHierarchy/outgoingCalls" => self.call_hierarchy,
+            "textDocument/prepareTypeHierarchy"
+            | "typeHierarchy/supertypes"
+            | "typeHierarchy/subtypes" => self.type_hierarchy,
+            "textDocument/documentLink" => self.document_link,
             _ => false, // Unknown methods are not disabled
         }
     }
@@ -232,6 +247,8 @@ pub struct LspAnalysisConfig {
     #[allow(dead_code)]
     pub diagnostic_mode: Option<DiagnosticMode>,
     pub import_format: Option<ImportFormat>,
+    pub dynamic_attributes: Option<DynamicAttributes>,
+    pub definition_preference: Option<DefinitionPreference>,
     pub inlay_hints: Option<InlayHintConfig>,
     #[serde(default)]
     pub disabled_language_services: Option<DisabledLanguageServices>,
@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method to check a batch of files and get their diagnostics in the response, for
+//! clients that check many files at once, e.g. after saving all of them, and would otherwise have
+//! to wait on a `publishDiagnostics` notification for each.
+
+use lsp_types::Diagnostic;
+use lsp_types::Url;
+use lsp_types::request::Request;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum CheckFiles {}
+
+impl Request for CheckFiles {
+    type Params = CheckFilesParams;
+    type Result = CheckFilesResult;
+    const METHOD: &'static str = "pyrefly/checkFiles";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckFilesParams {
+    /// The files to check. Open files are checked with their contents in the editor, and other
+    /// files with their contents on disk.
+    pub uris: Vec<Url>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckFilesResult {
+    /// The diagnostics of each file, in the order they were requested. Files that aren't checked,
+    /// e.g. because they are excluded from the project, have no diagnostics.
+    pub items: Vec<FileDiagnostics>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiagnostics {
+    pub uri: Url,
+    pub diagnostics: Vec<Diagnostic>,
+}
@@ -0,0 +1,57 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method that checks whether a snippet type-checks in place of a range of a document.
+//! It is a speculative check of a single edit with a simpler answer, for completion ranking and
+//! code generation tools that want to validate candidates before suggesting them.
+
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::Range;
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::request::Request;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum CheckSnippet {}
+
+impl Request for CheckSnippet {
+    type Params = CheckSnippetParams;
+    type Result = Option<CheckSnippetResult>;
+    const METHOD: &'static str = "pyrefly/textDocument/checkSnippet";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckSnippetParams {
+    /// The document to check the snippet in, which must be open.
+    pub text_document: TextDocumentIdentifier,
+    /// The range of the current text the snippet replaces, which is empty to insert it.
+    pub range: Range,
+    pub snippet: String,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckSnippetResult {
+    /// Whether the snippet adds no errors to the document.
+    pub type_checks: bool,
+    /// The diagnostics the snippet would add to the document, with ranges in the edited text.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CheckSnippetResult {
+    pub fn new(diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            type_checks: !diagnostics
+                .iter()
+                .any(|x| x.severity == Some(DiagnosticSeverity::ERROR)),
+            diagnostics,
+        }
+    }
+}
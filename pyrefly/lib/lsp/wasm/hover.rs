@@ -12,12 +12,15 @@ use lsp_types::MarkupKind;
 use lsp_types::Url;
 use pyrefly_build::handle::Handle;
 use pyrefly_python::docstring::Docstring;
+use pyrefly_python::fully_qualified_name::FullyQualifiedName;
 use pyrefly_python::ignore::Ignore;
 use pyrefly_python::ignore::find_comment_start_in_line;
 use pyrefly_python::symbol_kind::SymbolKind;
 use pyrefly_types::types::Type;
 use pyrefly_util::lined_buffer::LineNumber;
+use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
 
 use crate::error::error::Error;
@@ -90,49 +93,102 @@ fn format_suppressed_errors_hover(errors: Vec<Error>) -> Hover {
     }
 }
 
+/// Escape the characters of `text` that Markdown would otherwise interpret.
+#[cfg(not(target_arch = "wasm32"))]
+fn escape_markdown(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// Render the type `display` as Markdown, with the names in `links` (outside of string literals)
+/// linked to their URLs.
+#[cfg(not(target_arch = "wasm32"))]
+fn link_type_names(display: &str, links: &SmallMap<String, String>) -> String {
+    let mut res = String::new();
+    let mut quote = None;
+    let mut chars = display.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if quote.is_none() && (c.is_alphabetic() || c == '_') {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, c)) = chars.peek()
+                && (c.is_alphanumeric() || c == '_' || c == '.')
+            {
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let name = &display[start..end];
+            match links.get(name) {
+                Some(url) => res.push_str(&format!("[{}]({url})", escape_markdown(name))),
+                None => res.push_str(&escape_markdown(name)),
+            }
+        } else {
+            if quote == Some(c) {
+                quote = None;
+            } else if quote.is_none() && (c == '\'' || c == '"') {
+                quote = Some(c);
+            }
+            res.push_str(&escape_markdown(c.encode_utf8(&mut [0; 4])));
+        }
+    }
+    res
+}
+
 pub struct HoverValue {
     pub kind: Option<SymbolKind>,
     pub name: Option<String>,
     pub type_: Type,
     pub docstring: Option<Docstring>,
+    pub fully_qualified_name: Option<FullyQualifiedName>,
 }
 
 impl HoverValue {
+    /// The type as shown in hover, as Markdown with each named type linked to its definition, so
+    /// that e.g. both classes of `dict[MyClass, Result | None]` can be jumped to.
     #[cfg(not(target_arch = "wasm32"))]
     fn format_symbol_def_locations(t: &Type) -> Option<String> {
         let mut tracked_def_locs = SmallSet::new();
         t.universe(&mut |t| tracked_def_locs.extend(t.qname()));
-        let linked_names = tracked_def_locs
-            .into_iter()
-            .filter_map(|qname| {
-                if let Ok(mut url) = Url::from_file_path(qname.module_path().as_path()) {
-                    let start_pos = qname.module().display_range(qname.range()).start;
-                    if let Some(cell) = start_pos.cell() {
-                        url.set_fragment(Some(&format!(
-                            "{},L{},{}",
-                            cell.get(),
-                            start_pos.line_within_cell().get(),
-                            start_pos.column()
-                        )));
-                    } else {
-                        url.set_fragment(Some(&format!(
-                            "L{},{}",
-                            start_pos.line_within_file().get(),
-                            start_pos.column()
-                        )));
-                    }
-                    Some(format!("[{}]({})", qname.id(), url))
+        // Classes whose names clash are shown qualified by their module, so link both forms.
+        let mut links = SmallMap::new();
+        for qname in tracked_def_locs {
+            if let Ok(mut url) = Url::from_file_path(qname.module_path().as_path()) {
+                let start_pos = qname.module().display_range(qname.range()).start;
+                if let Some(cell) = start_pos.cell() {
+                    url.set_fragment(Some(&format!(
+                        "{},L{},{}",
+                        cell.get(),
+                        start_pos.line_within_cell().get(),
+                        start_pos.column()
+                    )));
                 } else {
-                    None
+                    url.set_fragment(Some(&format!(
+                        "L{},{}",
+                        start_pos.line_within_file().get(),
+                        start_pos.column()
+                    )));
                 }
-            })
-            .collect::<Vec<_>>()
-            .join(" | ");
+                links
+                    .entry(format!("{}.{}", qname.module_name(), qname.id()))
+                    .or_insert_with(|| url.to_string());
+                links
+                    .entry(qname.id().to_string())
+                    .or_insert_with(|| url.to_string());
+            }
+        }
 
-        if linked_names.is_empty() {
+        if links.is_empty() {
             None
         } else {
-            Some(format!("\n\nGo to {linked_names}"))
+            Some(format!(
+                "\n\nGo to {}",
+                link_type_names(&t.as_hover_string(), &links)
+            ))
         }
     }
 
@@ -157,6 +213,9 @@ impl HoverValue {
             .name
             .as_ref()
             .map_or("".to_owned(), |s| format!("{s}: "));
+        let fully_qualified_name_formatted = self
+            .fully_qualified_name
+            .map_or("".to_owned(), |x| format!("\n\n`{x}`"));
         let symbol_def_formatted =
             HoverValue::format_symbol_def_locations(&self.type_).unwrap_or("".to_owned());
 
@@ -164,10 +223,11 @@ impl HoverValue {
             contents: HoverContents::Markup(MarkupContent {
                 kind: MarkupKind::Markdown,
                 value: format!(
-                    "```python\n{}{}{}\n```{}{}",
+                    "```python\n{}{}{}\n```{}{}{}",
                     kind_formatted,
                     name_formatted,
                     self.type_.as_hover_string(),
+                    fully_qualified_name_formatted,
                     docstring_formatted,
                     symbol_def_formatted
                 ),
@@ -214,38 +274,44 @@ pub fn get_hover(
 
     // Otherwise, fall through to the existing type hover logic
     let type_ = transaction.get_type_at(handle, position)?;
-    let (kind, name, docstring_range, module) = if let Some(FindDefinitionItemWithDocstring {
-        metadata,
-        definition_range: definition_location,
-        module,
-        docstring_range,
-    }) = transaction
-        .find_definition(
-            handle,
-            position,
-            &FindPreference {
-                prefer_pyi: false,
-                ..Default::default()
-            },
-        )
-        // TODO: handle more than 1 definition
-        .into_iter()
-        .next()
-    {
-        (
-            metadata.symbol_kind(),
-            Some(module.code_at(definition_location).to_owned()),
+    let (kind, name, definition_range, docstring_range, fully_qualified_name, module) =
+        if let Some(FindDefinitionItemWithDocstring {
+            metadata,
+            definition_range: definition_location,
+            module,
             docstring_range,
-            Some(module),
-        )
-    } else {
-        (None, None, None, None)
-    };
+        }) = transaction
+            .find_definition(
+                handle,
+                position,
+                &FindPreference {
+                    prefer_pyi: false,
+                    ..Default::default()
+                },
+            )
+            // TODO: handle more than 1 definition
+            .into_iter()
+            .next()
+        {
+            (
+                metadata.symbol_kind(),
+                Some(module.code_at(definition_location).to_owned()),
+                definition_location,
+                docstring_range,
+                transaction.fully_qualified_name(handle, &module, definition_location),
+                Some(module),
+            )
+        } else {
+            (None, None, TextRange::default(), None, None, None)
+        };
 
-    let docstring = if let (Some(docstring), Some(module)) = (docstring_range, module) {
-        Some(Docstring(docstring, module))
-    } else {
-        None
+    let docstring = match (docstring_range, module) {
+        (Some(docstring), Some(module)) => Some(Docstring(docstring, module)),
+        // Stubs usually leave docstrings out, so look in the source the stub describes.
+        (None, Some(module)) => {
+            transaction.stub_definition_source_docstring(handle, &module, definition_range)
+        }
+        _ => None,
     };
 
     Some(
@@ -254,6 +320,7 @@ pub fn get_hover(
             name,
             type_,
             docstring,
+            fully_qualified_name,
         }
         .format(),
     )
@@ -5,8 +5,26 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod check_files;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod check_snippet;
 pub mod hover;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod move_symbol;
 pub mod notebook;
 pub mod provide_type;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod rename_preview;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server_status;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod speculative_check;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod status;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod trace_import;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod unreachable_ranges;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod will_rename_files;
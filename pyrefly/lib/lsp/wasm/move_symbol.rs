@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method that moves a top-level function or class into another module.
+//! The client picks the target module, and receives a `WorkspaceEdit` to apply.
+
+use std::collections::HashMap;
+
+use lsp_types::Position;
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::WorkspaceEdit;
+use lsp_types::request::Request;
+use pyrefly_build::handle::Handle;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
+use crate::state::state::Transaction;
+
+#[derive(Debug)]
+pub enum MoveSymbol {}
+
+impl Request for MoveSymbol {
+    type Params = MoveSymbolParams;
+    type Result = Option<WorkspaceEdit>;
+    const METHOD: &'static str = "pyrefly/textDocument/moveSymbol";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveSymbolParams {
+    pub text_document: TextDocumentIdentifier,
+    /// A position inside the name of the function or class to move.
+    pub position: Position,
+    /// The module the symbol should be moved into. It must already exist.
+    pub target_uri: Url,
+    /// Keep serving the symbol from the old module, with a `DeprecationWarning`, so its existing
+    /// importers keep working.
+    #[serde(default)]
+    pub leave_reexport: bool,
+}
+
+pub fn move_symbol(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    target: &Handle,
+    position: Position,
+    leave_reexport: bool,
+) -> Option<WorkspaceEdit> {
+    let info = transaction.get_module_info(handle)?;
+    let position = info.lined_buffer().from_lsp_position(position);
+    let edits = transaction.move_symbol(handle, position, target, leave_reexport)?;
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (module, range, new_text) in edits {
+        let uri = module_info_to_uri(&module)?;
+        changes.entry(uri).or_default().push(TextEdit {
+            range: module.lined_buffer().to_lsp_range(range),
+            new_text,
+        });
+    }
+    Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
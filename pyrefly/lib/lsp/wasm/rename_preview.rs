@@ -0,0 +1,190 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method that computes the same edit as `textDocument/rename`, along with the lines
+//! it changes in each file, before and after. Clients without a refactoring preview of their own,
+//! such as command line tools, can show these to confirm a large rename before applying it.
+
+use std::collections::HashMap;
+
+use lsp_types::RenameParams;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::WorkspaceEdit;
+use lsp_types::request::Request;
+use pyrefly_python::module::Module;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
+
+#[derive(Debug)]
+pub enum RenamePreview {}
+
+impl Request for RenamePreview {
+    type Params = RenameParams;
+    type Result = Option<RenamePreviewResult>;
+    const METHOD: &'static str = "pyrefly/textDocument/renamePreview";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePreviewResult {
+    /// The edit to apply, as `textDocument/rename` returns it.
+    pub edit: WorkspaceEdit,
+    /// The changes to each file, ordered by URI.
+    pub files: Vec<RenamePreviewFile>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePreviewFile {
+    pub uri: Url,
+    pub hunks: Vec<RenamePreviewHunk>,
+}
+
+/// A run of consecutive lines the rename changes.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePreviewHunk {
+    /// The first line of the hunk, counting from zero like LSP positions.
+    pub start_line: u32,
+    /// The lines before the rename, without the final newline.
+    pub before: String,
+    /// The lines after the rename, without the final newline.
+    pub after: String,
+}
+
+/// The hunks changed by replacing `ranges` of `module` with `new_name`.
+fn hunks(module: &Module, mut ranges: Vec<TextRange>, new_name: &str) -> Vec<RenamePreviewHunk> {
+    let contents = module.contents().as_str();
+    let line_start = |x: TextSize| contents[..x.to_usize()].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = |x: TextSize| {
+        contents[x.to_usize()..]
+            .find('\n')
+            .map_or(contents.len(), |i| x.to_usize() + i)
+    };
+    ranges.sort_by_key(|x| x.start());
+    ranges.dedup();
+    // The start and end of the lines of each hunk, with the ranges in them.
+    let mut groups: Vec<(usize, usize, Vec<TextRange>)> = Vec::new();
+    for range in ranges {
+        let (start, end) = (line_start(range.start()), line_end(range.end()));
+        match groups.last_mut() {
+            // The range is on the last line of the hunk, or the line after it.
+            Some((_, last_end, last_ranges)) if start <= *last_end + 1 => {
+                *last_end = end.max(*last_end);
+                last_ranges.push(range);
+            }
+            _ => groups.push((start, end, vec![range])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(start, end, ranges)| {
+            let mut after = String::new();
+            let mut pos = start;
+            for range in ranges {
+                after.push_str(&contents[pos..range.start().to_usize()]);
+                after.push_str(new_name);
+                pos = range.end().to_usize();
+            }
+            after.push_str(&contents[pos..end]);
+            RenamePreviewHunk {
+                start_line: module
+                    .lined_buffer()
+                    .to_lsp_position(TextSize::new(start as u32))
+                    .line,
+                before: contents[start..end].to_owned(),
+                after,
+            }
+        })
+        .collect()
+}
+
+/// The rename of the references found for a symbol to `new_name`, with its preview.
+pub fn rename_preview(
+    references: Vec<(Module, Vec<TextRange>)>,
+    new_name: &str,
+) -> RenamePreviewResult {
+    let mut changes = HashMap::new();
+    let mut files = Vec::new();
+    for (module, ranges) in references {
+        let Some(uri) = module_info_to_uri(&module) else {
+            continue;
+        };
+        changes.insert(
+            uri.clone(),
+            ranges
+                .iter()
+                .map(|range| TextEdit {
+                    range: module.lined_buffer().to_lsp_range(*range),
+                    new_text: new_name.to_owned(),
+                })
+                .collect(),
+        );
+        files.push(RenamePreviewFile {
+            uri,
+            hunks: hunks(&module, ranges, new_name),
+        });
+    }
+    files.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+    RenamePreviewResult {
+        edit: WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        },
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+
+    use super::*;
+
+    #[test]
+    fn test_hunks() {
+        let contents = "x = 1\ny = x\n\n\ndef f(x):\n    return x + x\n";
+        let module = Module::new(
+            ModuleName::from_str("test"),
+            ModulePath::filesystem(PathBuf::from("test.py")),
+            Arc::new(contents.to_owned()),
+        );
+        let ranges = ["x = 1", "y = x", "    return x + x"]
+            .iter()
+            .flat_map(|line| {
+                let start = contents.find(line).unwrap();
+                line.match_indices('x')
+                    .map(move |(i, _)| TextRange::at(TextSize::new((start + i) as u32), 1.into()))
+            })
+            .rev()
+            .collect();
+        assert_eq!(
+            hunks(&module, ranges, "value"),
+            vec![
+                RenamePreviewHunk {
+                    start_line: 0,
+                    before: "x = 1\ny = x".to_owned(),
+                    after: "value = 1\ny = value".to_owned(),
+                },
+                RenamePreviewHunk {
+                    start_line: 5,
+                    before: "    return x + x".to_owned(),
+                    after: "    return value + value".to_owned(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,35 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method reporting how much analysis state the server is holding on to, and how much
+//! it has dropped for modules that are no longer reachable from any open file.
+
+use lsp_types::request::Request;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum ServerStatus {}
+
+impl Request for ServerStatus {
+    type Params = ();
+    type Result = ServerStatusResult;
+    const METHOD: &'static str = "pyrefly/serverStatus";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatusResult {
+    /// The number of files open in the editor.
+    pub open_files: usize,
+    /// The number of modules the server currently holds state for.
+    pub modules: usize,
+    /// The number of times unreachable module state has been collected.
+    pub garbage_collections: usize,
+    /// The total number of modules whose state has been dropped by those collections.
+    pub modules_collected: usize,
+}
@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method that checks what a document would be after some edits, without applying
+//! them, and returns the diagnostics the edits would add and remove. Clients can use it to tell
+//! whether a quick fix or a suggested change introduces new errors before offering it.
+
+use std::sync::Arc;
+
+use lsp_types::Diagnostic;
+use lsp_types::Range;
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::request::Request;
+use pyrefly_util::lined_buffer::LinedBuffer;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum SpeculativeCheck {}
+
+impl Request for SpeculativeCheck {
+    type Params = SpeculativeCheckParams;
+    type Result = Option<SpeculativeCheckResult>;
+    const METHOD: &'static str = "pyrefly/textDocument/speculativeCheck";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeculativeCheckParams {
+    /// The document to edit, which must be open.
+    pub text_document: TextDocumentIdentifier,
+    /// The edits to check, with ranges in the current text of the document. They mustn't overlap.
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeculativeCheckResult {
+    /// The changes to the diagnostics of each open file, ordered by URI. Files whose diagnostics
+    /// wouldn't change are left out.
+    pub items: Vec<DiagnosticsDelta>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsDelta {
+    pub uri: Url,
+    /// The diagnostics the edits would add, with ranges in the edited text.
+    pub added: Vec<Diagnostic>,
+    /// The diagnostics the edits would remove, with ranges in the current text.
+    pub removed: Vec<Diagnostic>,
+}
+
+/// Edits to a document, resolved against its current text.
+pub struct SpeculativeEdit {
+    before: LinedBuffer,
+    after: LinedBuffer,
+    /// The ranges of the current text that are replaced, in order, with the length of the text
+    /// that replaces each.
+    edits: Vec<(TextRange, TextSize)>,
+}
+
+impl SpeculativeEdit {
+    /// Apply `edits` to `text`, or `None` if they overlap.
+    pub fn new(text: Arc<String>, edits: &[TextEdit]) -> Option<Self> {
+        let before = LinedBuffer::new(text);
+        let mut edits = edits
+            .iter()
+            .map(|x| (before.from_lsp_range(x.range), x.new_text.as_str()))
+            .collect::<Vec<_>>();
+        edits.sort_by_key(|(range, _)| range.start());
+        if edits.windows(2).any(|x| x[0].0.end() > x[1].0.start()) {
+            return None;
+        }
+        let contents = before.contents().as_str();
+        let mut after = String::new();
+        let mut pos = 0;
+        for (range, new_text) in &edits {
+            after.push_str(&contents[pos..range.start().to_usize()]);
+            after.push_str(new_text);
+            pos = range.end().to_usize();
+        }
+        after.push_str(&contents[pos..]);
+        let edits = edits
+            .into_iter()
+            .map(|(range, new_text)| (range, TextSize::try_from(new_text.len()).unwrap()));
+        Some(Self {
+            after: LinedBuffer::new(Arc::new(after)),
+            edits: edits.collect(),
+            before,
+        })
+    }
+
+    /// The text of the document after the edits.
+    pub fn text(&self) -> Arc<String> {
+        self.after.contents().clone()
+    }
+
+    /// Where `x`, in the current text, ends up after the edits, or `None` if an edit replaces
+    /// the text around it.
+    fn map_offset(&self, x: TextSize) -> Option<TextSize> {
+        let mut res = x;
+        for (range, new_len) in &self.edits {
+            if range.end() <= x {
+                res = res - range.len() + *new_len;
+            } else if range.start() < x {
+                return None;
+            }
+        }
+        Some(res)
+    }
+
+    /// Where `x`, in the current text, ends up after the edits, or `None` if an edit changes
+    /// the text it covers.
+    pub fn map_range(&self, x: Range) -> Option<Range> {
+        let x = self.before.from_lsp_range(x);
+        // Text inserted at the start or end of the range doesn't change the text it covers.
+        if self
+            .edits
+            .iter()
+            .any(|(range, _)| range.start() < x.end() && x.start() < range.end())
+        {
+            return None;
+        }
+        Some(self.after.to_lsp_range(TextRange::new(
+            self.map_offset(x.start())?,
+            self.map_offset(x.end())?,
+        )))
+    }
+}
+
+/// The diagnostics in `after` that aren't in `before`, and those in `before` that aren't in
+/// `after`, where `map_range` moves the range of a diagnostic of `before` to where it would be
+/// in `after`, if it can.
+pub fn diagnostics_delta(
+    before: Vec<Diagnostic>,
+    mut after: Vec<Diagnostic>,
+    map_range: impl Fn(Range) -> Option<Range>,
+) -> (Vec<Diagnostic>, Vec<Diagnostic>) {
+    let mut removed = Vec::new();
+    for diagnostic in before {
+        let moved = map_range(diagnostic.range).map(|range| Diagnostic {
+            range,
+            ..diagnostic.clone()
+        });
+        match moved.and_then(|x| after.iter().position(|y| *y == x)) {
+            Some(i) => {
+                after.remove(i);
+            }
+            None => removed.push(diagnostic),
+        }
+    }
+    (after, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::*;
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+        Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+    }
+
+    fn diagnostic(range: Range, message: &str) -> Diagnostic {
+        Diagnostic {
+            range,
+            message: message.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_speculative_edit() {
+        let text = "x: int = 1\ny: str = x\nz = y\n";
+        let edit = SpeculativeEdit::new(
+            Arc::new(text.to_owned()),
+            &[
+                TextEdit::new(range((1, 3), (1, 6)), "int".to_owned()),
+                TextEdit::new(range((0, 0), (0, 0)), "import os\n".to_owned()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            edit.text().as_str(),
+            "import os\nx: int = 1\ny: int = x\nz = y\n"
+        );
+        // Moved down a line by the import.
+        assert_eq!(
+            edit.map_range(range((1, 9), (1, 10))),
+            Some(range((2, 9), (2, 10)))
+        );
+        // Changed by the edit.
+        assert_eq!(edit.map_range(range((1, 0), (1, 10))), None);
+
+        let overlapping = [
+            TextEdit::new(range((0, 0), (0, 5)), "a".to_owned()),
+            TextEdit::new(range((0, 3), (0, 8)), "b".to_owned()),
+        ];
+        assert!(SpeculativeEdit::new(Arc::new(text.to_owned()), &overlapping).is_none());
+    }
+
+    #[test]
+    fn test_diagnostics_delta() {
+        let before = vec![
+            diagnostic(range((1, 9), (1, 10)), "`int` is not assignable to `str`"),
+            diagnostic(range((2, 0), (2, 1)), "unused"),
+        ];
+        let after = vec![
+            diagnostic(range((3, 0), (3, 1)), "unused"),
+            diagnostic(range((0, 7), (0, 9)), "`os` is unused"),
+        ];
+        let (added, removed) = diagnostics_delta(before, after, |x| {
+            Some(range(
+                (x.start.line + 1, x.start.character),
+                (x.end.line + 1, x.end.character),
+            ))
+        });
+        assert_eq!(
+            added,
+            vec![diagnostic(range((0, 7), (0, 9)), "`os` is unused")]
+        );
+        assert_eq!(
+            removed,
+            vec![diagnostic(
+                range((1, 9), (1, 10)),
+                "`int` is not assignable to `str`"
+            )]
+        );
+    }
+}
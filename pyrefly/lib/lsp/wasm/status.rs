@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP notification telling the client what the server is busy with, so that it can show
+//! a busy indicator, e.g. in the status bar. Clients opt in by setting `status` to `true` in the
+//! `experimental` client capabilities.
+
+use lsp_types::notification::Notification;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum StatusNotification {}
+
+impl Notification for StatusNotification {
+    type Params = StatusParams;
+    const METHOD: &'static str = "pyrefly/status";
+}
+
+/// What the server is doing. Serialized with the kind in `state`, e.g.
+/// `{"state": "checking", "files": 3}`.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum Status {
+    /// Nothing to do.
+    Idle,
+    /// Checking the open files.
+    Checking { files: usize },
+    /// Loading the project, which is needed by find-references, rename and the like.
+    Indexing { percentage: u32 },
+    /// Applying a change to the configuration and rechecking the open files.
+    ReloadingConfig,
+}
+
+impl Status {
+    /// A message describing the status, for clients that show it as is.
+    pub fn message(&self) -> String {
+        match self {
+            Self::Idle => "Ready".to_owned(),
+            Self::Checking { files: 1 } => "Checking 1 file".to_owned(),
+            Self::Checking { files } => format!("Checking {files} files"),
+            Self::Indexing { percentage } => format!("Indexing ({percentage}%)"),
+            Self::ReloadingConfig => "Reloading configuration".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusParams {
+    #[serde(flatten)]
+    pub status: Status,
+    pub message: String,
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP method reporting how an import in a document is resolved, step by step, to help
+//! debug imports that can't be found.
+
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::request::Request;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum TraceImport {}
+
+impl Request for TraceImport {
+    type Params = TraceImportParams;
+    type Result = Option<TraceImportResult>;
+    const METHOD: &'static str = "pyrefly/traceImport";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceImportParams {
+    /// The document the import is resolved from, which determines the config that is used.
+    pub text_document: TextDocumentIdentifier,
+    /// The fully-qualified name of the imported module.
+    pub module: String,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceImportResult {
+    /// The path the import resolves to, if it resolves to a file.
+    pub resolved: Option<String>,
+    /// The report, with one step per line.
+    pub report: String,
+}
@@ -0,0 +1,33 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Custom LSP notification listing the ranges of a file that are unreachable for the configured
+//! Python version and platform, e.g. the body of `if sys.version_info < (3, 0):`, so that the
+//! client can grey them out. Clients opt in by setting `unreachableRanges` to `true` in the
+//! `experimental` client capabilities.
+
+use lsp_types::Range;
+use lsp_types::Url;
+use lsp_types::notification::Notification;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum UnreachableRanges {}
+
+impl Notification for UnreachableRanges {
+    type Params = UnreachableRangesParams;
+    const METHOD: &'static str = "pyrefly/textDocument/unreachableRanges";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreachableRangesParams {
+    pub uri: Url,
+    /// Every unreachable range in the file. An empty list clears earlier ranges.
+    pub ranges: Vec<Range>,
+}
@@ -6,6 +6,7 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use lsp_types::DocumentChangeOperation;
@@ -25,18 +26,31 @@ use pyrefly_util::lined_buffer::LinedBuffer;
 use pyrefly_util::lock::RwLock;
 use rayon::prelude::*;
 use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtImportFrom;
+use ruff_python_ast::name::Name;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_stmt;
 use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
 
 use crate::lsp::non_wasm::module_helpers::handle_from_module_path;
 use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
 use crate::state::state::State;
 use crate::state::state::Transaction;
 
-/// Visitor that looks for imports of an old module name and creates TextEdits to update them
+/// Visitor that looks for imports of an old module name, including nested and relative ones, and
+/// creates TextEdits to update them
 struct RenameUsageVisitor<'a> {
     edits: Vec<TextEdit>,
     old_module_name: &'a ModuleName,
     new_module_name: &'a ModuleName,
+    /// The module being visited, which relative imports are resolved against.
+    module_name: ModuleName,
+    /// The name of the module being visited once renamed, which relative imports are written
+    /// against. It differs from `module_name` if the module is, or is in, the renamed module.
+    renamed_module_name: ModuleName,
+    is_init: bool,
     lined_buffer: &'a LinedBuffer,
 }
 
@@ -44,71 +58,143 @@ impl<'a> RenameUsageVisitor<'a> {
     fn new(
         old_module_name: &'a ModuleName,
         new_module_name: &'a ModuleName,
+        module_name: ModuleName,
+        is_init: bool,
         lined_buffer: &'a LinedBuffer,
     ) -> Self {
-        Self {
+        let mut res = Self {
             edits: Vec::new(),
             old_module_name,
             new_module_name,
+            module_name,
+            renamed_module_name: module_name,
+            is_init,
             lined_buffer,
+        };
+        if let Some(renamed) = res.renamed(module_name) {
+            res.renamed_module_name = renamed;
         }
+        res
     }
 
-    fn visit_stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::Import(import) => {
-                for alias in &import.names {
-                    let imported_module = ModuleName::from_name(&alias.name.id);
-                    if imported_module == *self.old_module_name
-                        || imported_module
-                            .as_str()
-                            .starts_with(&format!("{}.", self.old_module_name.as_str()))
-                    {
-                        // Replace the module name
-                        let new_import_name = if imported_module == *self.old_module_name {
-                            self.new_module_name.as_str().to_owned()
-                        } else {
-                            // Replace the prefix
-                            imported_module.as_str().replace(
-                                self.old_module_name.as_str(),
-                                self.new_module_name.as_str(),
-                            )
-                        };
-
-                        self.edits.push(TextEdit {
-                            range: self.lined_buffer.to_lsp_range(alias.name.range()),
-                            new_text: new_import_name,
-                        });
+    /// The new name of `module`, if it is the renamed module or one of its submodules.
+    fn renamed(&self, module: ModuleName) -> Option<ModuleName> {
+        if module == *self.old_module_name {
+            Some(*self.new_module_name)
+        } else {
+            let rest = module
+                .as_str()
+                .strip_prefix(self.old_module_name.as_str())?;
+            rest.starts_with('.')
+                .then(|| ModuleName::from_string(format!("{}{rest}", self.new_module_name)))
+        }
+    }
+
+    /// `module` relative to the package `level` dots refer to from the renamed module being
+    /// visited, or `None` if it isn't in that package.
+    fn relative_to(&self, level: u32, module: ModuleName) -> Option<String> {
+        let base = self
+            .renamed_module_name
+            .new_maybe_relative(self.is_init, level, None)?;
+        if base.as_str().is_empty() || module == base {
+            Some(module.as_str().to_owned())
+        } else {
+            module
+                .as_str()
+                .strip_prefix(base.as_str())?
+                .strip_prefix('.')
+                .map(|x| x.to_owned())
+        }
+    }
+
+    fn edit(&mut self, range: TextRange, new_text: String) {
+        if self.lined_buffer.code_at(range) != new_text {
+            self.edits.push(TextEdit {
+                range: self.lined_buffer.to_lsp_range(range),
+                new_text,
+            });
+        }
+    }
+
+    /// The range of the dots and module of a relative import, as in `..a.b` in
+    /// `from ..a.b import c`.
+    fn relative_module_range(&self, x: &StmtImportFrom) -> TextRange {
+        let start = x.range.start().to_usize();
+        let dots =
+            TextSize::try_from(start + self.lined_buffer.contents()[start..].find('.').unwrap())
+                .unwrap();
+        match &x.module {
+            Some(module) => TextRange::new(dots, module.range.end()),
+            None => TextRange::at(dots, TextSize::new(x.level)),
+        }
+    }
+
+    fn visit_import_from(&mut self, x: &StmtImportFrom) {
+        let edits = self.edits.len();
+        if let Some(imported_module) = self.module_name.new_maybe_relative(
+            self.is_init,
+            x.level,
+            x.module.as_ref().map(|x| &x.id),
+        ) {
+            let renamed = self.renamed(imported_module);
+            let new_import_name = renamed.unwrap_or(imported_module);
+            if x.level == 0 {
+                if let Some(module) = &x.module
+                    && renamed.is_some()
+                {
+                    self.edit(module.range, new_import_name.to_string());
+                }
+            } else {
+                // Relative imports change if the module they import or the module they are in
+                // moves.
+                match (self.relative_to(x.level, new_import_name), &x.module) {
+                    (Some(relative), Some(module)) => self.edit(module.range, relative),
+                    (Some(_), None) => {}
+                    // The module is no longer under the package the dots refer to, so import it
+                    // by its absolute name.
+                    (None, _) if !new_import_name.as_str().is_empty() => {
+                        self.edit(self.relative_module_range(x), new_import_name.to_string())
                     }
+                    (None, _) => {}
                 }
             }
-            Stmt::ImportFrom(import_from) => {
-                if let Some(module) = &import_from.module {
-                    let imported_module = ModuleName::from_name(&module.id);
-                    if imported_module == *self.old_module_name
-                        || imported_module
-                            .as_str()
-                            .starts_with(&format!("{}.", self.old_module_name.as_str()))
-                    {
-                        // Replace the module name
-                        let new_import_name = if imported_module == *self.old_module_name {
-                            self.new_module_name.as_str().to_owned()
-                        } else {
-                            // Replace the prefix
-                            imported_module.as_str().replace(
-                                self.old_module_name.as_str(),
-                                self.new_module_name.as_str(),
-                            )
-                        };
-
-                        self.edits.push(TextEdit {
-                            range: self.lined_buffer.to_lsp_range(module.range()),
-                            new_text: new_import_name,
-                        });
-                    }
+        }
+        if self.edits.len() != edits {
+            return;
+        }
+        // Otherwise, the renamed module may be one of the names imported, as in
+        // `from pkg import mod`.
+        for alias in &x.names {
+            let name = match &x.module {
+                Some(module) => Name::new(format!("{}.{}", module.id, alias.name.id)),
+                None => alias.name.id.clone(),
+            };
+            if self
+                .module_name
+                .new_maybe_relative(self.is_init, x.level, Some(&name))
+                != Some(*self.old_module_name)
+            {
+                continue;
+            }
+            let new_name = self.new_module_name.components().pop().unwrap();
+            // Keep the name the module is bound to, so its uses don't need updating.
+            let binding = alias.asname.as_ref().unwrap_or(&alias.name).id.as_str();
+            let with_binding = |x: &str| {
+                if x == binding {
+                    x.to_owned()
+                } else {
+                    format!("{x} as {binding}")
                 }
+            };
+            if self.new_module_name.parent() == self.old_module_name.parent() {
+                self.edit(alias.range, with_binding(new_name.as_str()));
+            } else if x.names.len() == 1 {
+                let new_text = match self.new_module_name.parent() {
+                    Some(parent) => format!("from {parent} import {}", with_binding(&new_name)),
+                    None => format!("import {}", with_binding(self.new_module_name.as_str())),
+                };
+                self.edit(x.range, new_text);
             }
-            _ => {}
         }
     }
 
@@ -117,11 +203,31 @@ impl<'a> RenameUsageVisitor<'a> {
     }
 }
 
+impl<'a> SourceOrderVisitor<'a> for RenameUsageVisitor<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    if let Some(new_import_name) =
+                        self.renamed(ModuleName::from_name(&alias.name.id))
+                    {
+                        self.edit(alias.name.range(), new_import_name.to_string());
+                    }
+                }
+            }
+            Stmt::ImportFrom(import_from) => self.visit_import_from(import_from),
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+}
+
 /// Handle workspace/willRenameFiles request to update imports when files are renamed.
 ///
 /// This function:
-/// 1. Converts file paths to module names
-/// 2. Uses get_transitive_rdeps to find all files that depend on the renamed module
+/// 1. Converts file paths to module names, where a directory is renamed as a package
+/// 2. Uses get_transitive_rdeps to find all files that depend on the renamed module or, for a
+///    package, any of its submodules
 /// 3. Uses a visitor pattern to find imports of the old module and creates TextEdits
 /// 4. Returns a WorkspaceEdit with all necessary changes
 ///
@@ -180,14 +286,22 @@ pub fn will_rename_files(
             }
         };
 
-        // Only process Python files
-        if !PYTHON_EXTENSIONS
-            .iter()
-            .any(|ext| old_path.extension().and_then(|e| e.to_str()) == Some(*ext))
+        // Only process Python files and directories, which may be packages
+        let is_package = old_path.is_dir();
+        if !is_package
+            && !PYTHON_EXTENSIONS
+                .iter()
+                .any(|ext| old_path.extension().and_then(|e| e.to_str()) == Some(*ext))
         {
             eprintln!("    Skipping non-Python file");
             continue;
         }
+        // A package is named by its `__init__.py`, whether or not it has one
+        let (old_path, new_path) = if is_package {
+            (old_path.join("__init__.py"), new_path.join("__init__.py"))
+        } else {
+            (old_path, new_path)
+        };
 
         // Important: only use filesystem handle (never use an in-memory handle)
         let module_path = ModulePath::filesystem(old_path.clone());
@@ -225,15 +339,28 @@ pub fn will_rename_files(
             continue;
         }
 
-        // Use get_transitive_rdeps to find all files that depend on this module
-        let rdeps = transaction.get_transitive_rdeps(old_handle.clone());
+        // Renaming a package renames all of its submodules too
+        let mut renamed_handles = vec![old_handle];
+        if is_package && let Some(package_dir) = old_path.parent() {
+            renamed_handles.extend(transaction.handles().into_iter().filter_map(|handle| {
+                let path = handle.path().as_path();
+                (path.starts_with(package_dir) && path != old_path).then(|| {
+                    handle_from_module_path(state, ModulePath::filesystem(path.to_owned()))
+                })
+            }));
+        }
+        // Use get_transitive_rdeps to find all files that depend on the renamed modules
+        let rdeps: HashSet<_> = renamed_handles
+            .into_iter()
+            .flat_map(|handle| transaction.get_transitive_rdeps(handle))
+            .collect();
 
         eprintln!("    Found {} transitive rdeps", rdeps.len());
 
         // Deduplicate rdeps by module path string (get_transitive_rdeps might return duplicates
         // with different variants like FileSystem vs Memory for the same path)
         let unique_rdeps: Vec<_> = {
-            let mut seen = std::collections::HashSet::new();
+            let mut seen = HashSet::new();
             rdeps
                 .into_iter()
                 .filter(|handle| seen.insert(handle.path().as_path().to_owned()))
@@ -250,12 +377,11 @@ pub fn will_rename_files(
                 let mut visitor = RenameUsageVisitor::new(
                     &old_module_name,
                     &new_module_name,
+                    rdep_handle.module(),
+                    rdep_handle.path().is_init(),
                     module_info.lined_buffer(),
                 );
-
-                for stmt in &ast.body {
-                    visitor.visit_stmt(stmt);
-                }
+                visitor.visit_body(&ast.body);
 
                 let edits_for_file = visitor.take_edits();
 
@@ -502,7 +502,10 @@ pub fn find_import_filtered(
             None
         };
 
-    let typeshed_third_party_stub = match from_real_config_file {
+    let manifest = config.loaded_dependency_manifest.as_deref();
+
+    // In hermetic mode, third-party modules only come from the dependency manifest.
+    let typeshed_third_party_stub = match from_real_config_file || manifest.is_some() {
         true => None,
         false => typeshed_third_party_result.clone(),
     };
@@ -548,6 +551,17 @@ pub fn find_import_filtered(
         )
     {
         path
+    } else if let Some(manifest) = manifest
+        && let Some(roots) = manifest.roots(module)
+        && let Some(path) = find_module(
+            module,
+            roots.iter(),
+            &mut namespaces_found,
+            style_filter,
+            None,
+        )
+    {
+        path
     } else if let Some(path) = find_module(
         module,
         config.site_package_path(),
@@ -570,7 +584,15 @@ pub fn find_import_filtered(
         // have the associated stub installed for whatever third party package
         // they are using. At this point we should generate a warning telling them to install
         // the stubs package.
-        if typeshed_third_party_result.is_some() {
+        if let Some(manifest_path) = &config.dependency_manifest
+            && manifest.is_some_and(|manifest| manifest.roots(module).is_none())
+        {
+            return FindingOrError::Error(FindError::not_in_dependency_manifest(
+                module,
+                manifest_path,
+            ));
+        }
+        if manifest.is_none() && typeshed_third_party_result.is_some() {
             let pip_package = format!("{}-stubs", module.components()[0]);
             return FindingOrError::Error(FindError::MissingStubs(module, pip_package.into()));
         }
@@ -612,7 +634,16 @@ pub fn find_import_prefixes(config: &ConfigFile, module: ModuleName) -> Vec<Modu
         results.extend(typeshed_modules);
     }
 
+    if let Some(manifest) = &config.loaded_dependency_manifest {
+        results.extend(
+            find_module_prefixes(module, manifest.modules.values().flatten())
+                .into_iter()
+                .filter(|x| manifest.roots(*x).is_some()),
+        );
+    }
+
     if !config.from_real_config_file()
+        && config.loaded_dependency_manifest.is_none()
         && let Ok(typeshed_third_party) = typeshed_third_party()
     {
         let module_str = module.as_str();
@@ -2053,4 +2084,57 @@ mod tests {
             result_marker
         );
     }
+
+    #[test]
+    fn test_find_import_with_dependency_manifest() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file_with_contents("deps.json", r#"{"modules": {"allowed": ["deps"]}}"#),
+                TestPath::dir(
+                    "deps",
+                    vec![
+                        TestPath::dir("allowed", vec![TestPath::file("__init__.py")]),
+                        TestPath::dir("unlisted", vec![TestPath::file("__init__.py")]),
+                    ],
+                ),
+                TestPath::dir(
+                    "site_package_path",
+                    vec![TestPath::dir(
+                        "installed",
+                        vec![TestPath::file("__init__.py")],
+                    )],
+                ),
+            ],
+        );
+        let mut interpreters = Interpreters::default();
+        interpreters.skip_interpreter_query = true;
+        let mut config = ConfigFile {
+            interpreters,
+            python_environment: PythonEnvironment {
+                site_package_path: Some(vec![root.join("site_package_path")]),
+                ..Default::default()
+            },
+            dependency_manifest: Some(root.join("deps.json")),
+            ..Default::default()
+        };
+        config.configure();
+
+        assert_eq!(
+            find_import(&config, ModuleName::from_str("allowed"), None),
+            FindingOrError::new_finding(ModulePath::filesystem(
+                root.join("deps/allowed/__init__.py")
+            ))
+        );
+        // Neither modules missing from the manifest, nor site packages, can be imported.
+        for module in ["unlisted", "installed"] {
+            let result = find_import(&config, ModuleName::from_str(module), None);
+            assert!(
+                matches!(result, FindingOrError::Error(FindError::NotFound(..))),
+                "Expected `{module}` not to be found, got: {result:?}"
+            );
+        }
+    }
 }
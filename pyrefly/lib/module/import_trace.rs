@@ -0,0 +1,418 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A step-by-step report of how an import is resolved: every search location and candidate file
+//! that is looked at, and why each is used or rejected. This follows the same search order as
+//! [`find_import`], and always reports the result `find_import` actually gives.
+
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+use pyrefly_python::COMPILED_FILE_SUFFIXES;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use ruff_python_ast::name::Name;
+
+use crate::config::config::ConfigFile;
+use crate::module::bundled::BundledStub;
+use crate::module::finder::find_import;
+use crate::module::typeshed::typeshed;
+use crate::module::typeshed_third_party::typeshed_third_party;
+use crate::state::loader::FindingOrError;
+
+/// What a candidate path turned out to be, in increasing order of priority when the same module is
+/// found in more than one root of a search location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CandidateKind {
+    NamespacePackage,
+    CompiledModule,
+    SourceFile,
+    StubFile,
+    RegularPackage,
+}
+
+impl CandidateKind {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::NamespacePackage => "namespace package",
+            Self::CompiledModule => "compiled module (treated as `Any`)",
+            Self::SourceFile => "source file",
+            Self::StubFile => "stub file",
+            Self::RegularPackage => "regular package",
+        }
+    }
+
+    /// Whether the module can contain submodules.
+    fn is_package(self) -> bool {
+        matches!(self, Self::NamespacePackage | Self::RegularPackage)
+    }
+}
+
+/// The paths that could define `part` in `dir`, in the order the finder checks them.
+fn candidates(dir: &Path, part: &Name) -> Vec<(PathBuf, CandidateKind)> {
+    let package = dir.join(part.as_str());
+    let mut res = vec![
+        (package.join("__init__.pyi"), CandidateKind::RegularPackage),
+        (package.join("__init__.py"), CandidateKind::RegularPackage),
+        (dir.join(format!("{part}.pyi")), CandidateKind::StubFile),
+        (dir.join(format!("{part}.py")), CandidateKind::SourceFile),
+    ];
+    res.extend(COMPILED_FILE_SUFFIXES.iter().map(|suffix| {
+        (
+            dir.join(format!("{part}.{suffix}")),
+            CandidateKind::CompiledModule,
+        )
+    }));
+    res.push((package, CandidateKind::NamespacePackage));
+    res
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportTrace {
+    module: ModuleName,
+    origin: Option<ModulePath>,
+    /// The lines of the report, already indented.
+    steps: Vec<String>,
+    result: FindingOrError<ModulePath>,
+}
+
+impl ImportTrace {
+    pub fn new(config: &ConfigFile, module: ModuleName, origin: Option<&ModulePath>) -> Self {
+        let mut trace = Self {
+            module,
+            origin: origin.cloned(),
+            steps: Vec::new(),
+            result: find_import(config, module, origin),
+        };
+        trace.trace(config, origin.map(|x| x.as_path()));
+        trace
+    }
+
+    pub fn result(&self) -> &FindingOrError<ModulePath> {
+        &self.result
+    }
+
+    fn step(&mut self, depth: usize, msg: String) {
+        self.steps.push(format!("{}{msg}", "  ".repeat(depth + 1)));
+    }
+
+    fn trace(&mut self, config: &ConfigFile, origin: Option<&Path>) {
+        let module = self.module;
+        let components = module.components();
+        if components.is_empty() {
+            return;
+        }
+        if module != ModuleName::builtins() && config.replace_imports_with_any(origin, module) {
+            self.step(
+                0,
+                "Matched `replace-imports-with-any`, so the module is `Any` without searching"
+                    .to_owned(),
+            );
+            return;
+        }
+        if let Some(sourcedb) = &config.source_db {
+            match sourcedb.lookup(&module, origin, None) {
+                Some(path) => {
+                    self.step(0, format!("Build system source database: found `{path}`"));
+                    return;
+                }
+                None => self.step(0, "Build system source database: not found".to_owned()),
+            }
+        }
+        if self.location("Search path", config.search_path().collect(), &components) {
+            return;
+        }
+        if let Some(typeshed_path) = &config.typeshed_path {
+            let stdlib = typeshed_path.join("stdlib");
+            if self.location("Custom typeshed", vec![&stdlib], &components) {
+                return;
+            }
+        }
+        match typeshed().map(|ts| ts.find(module)) {
+            Ok(Some(path)) => {
+                self.step(0, format!("Bundled typeshed: found `{path}`"));
+                return;
+            }
+            Ok(None) => self.step(0, "Bundled typeshed: not found".to_owned()),
+            Err(err) => self.step(0, format!("Bundled typeshed: failed to load ({err:#})")),
+        }
+        if config.disable_search_path_heuristics {
+            self.step(
+                0,
+                "Fallback search path: disabled by `disable-search-path-heuristics`".to_owned(),
+            );
+        } else if self.location(
+            "Fallback search path",
+            config.fallback_search_path.iter().collect(),
+            &components,
+        ) {
+            return;
+        }
+        if let Some(manifest) = &config.loaded_dependency_manifest {
+            match manifest.roots(module) {
+                Some(roots) => {
+                    if self.location("Dependency manifest", roots.iter().collect(), &components) {
+                        return;
+                    }
+                }
+                None => self.step(
+                    0,
+                    format!(
+                        "Dependency manifest: `{}` is not listed, so it can't be imported",
+                        components[0]
+                    ),
+                ),
+            }
+            self.step(
+                0,
+                "Bundled third-party stubs: not used, because there is a dependency manifest"
+                    .to_owned(),
+            );
+        } else if config.from_real_config_file() {
+            self.step(
+                0,
+                "Bundled third-party stubs: not used, because there is a config file".to_owned(),
+            );
+        } else {
+            match typeshed_third_party().map(|ts| ts.find(module)) {
+                Ok(Some(path)) => self.step(
+                    0,
+                    format!(
+                        "Bundled third-party stubs: found `{path}`, used unless the site package path has a `-stubs` package"
+                    ),
+                ),
+                Ok(None) => self.step(0, "Bundled third-party stubs: not found".to_owned()),
+                Err(err) => self.step(
+                    0,
+                    format!("Bundled third-party stubs: failed to load ({err:#})"),
+                ),
+            }
+        }
+        if self.location(
+            "Site package path",
+            config.site_package_path().collect(),
+            &components,
+        ) {
+            return;
+        }
+        if config.ignore_missing_imports(origin, module) {
+            self.step(
+                0,
+                "Matched `ignore-missing-imports`, so a missing module is not an error".to_owned(),
+            );
+        }
+    }
+
+    /// Trace the lookup in one kind of search location. Returns `true` if a module was found that
+    /// ends the search, i.e. anything other than a namespace package.
+    fn location(&mut self, location: &str, roots: Vec<&PathBuf>, components: &[Name]) -> bool {
+        if roots.is_empty() {
+            self.step(0, format!("{location}: empty"));
+            return false;
+        }
+        self.step(0, format!("{location}:"));
+        let mut stub_components = components.to_vec();
+        stub_components[0] = Name::new(format!("{}-stubs", components[0]));
+        let stubs = self.best(&roots, &stub_components);
+        let source = self.best(&roots, components);
+        let (msg, found) = match (source, stubs) {
+            (None, None) => ("Not found".to_owned(), false),
+            (None, Some((_, stub))) => (
+                format!(
+                    "Using `{}`, but there is no source package for these stubs, so the import reports `missing-source`",
+                    stub.display()
+                ),
+                true,
+            ),
+            (Some(_), Some((_, stub))) => (
+                format!(
+                    "Using `{}`, because a `-stubs` package takes precedence over the source package",
+                    stub.display()
+                ),
+                true,
+            ),
+            (Some((CandidateKind::NamespacePackage, path)), None) => (
+                format!(
+                    "Found only the namespace package `{}`, which is used if no module or regular package is found in any location",
+                    path.display()
+                ),
+                false,
+            ),
+            (Some((_, path)), None) => (format!("Using `{}`", path.display()), true),
+        };
+        self.step(1, msg);
+        found
+    }
+
+    /// Find the module in the first root that has it, continuing into later roots unless what we
+    /// found can't be beaten by a later root.
+    fn best(
+        &mut self,
+        roots: &[&PathBuf],
+        components: &[Name],
+    ) -> Option<(CandidateKind, PathBuf)> {
+        let name = components
+            .iter()
+            .map(|x| x.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        let mut best: Option<(CandidateKind, PathBuf)> = None;
+        for root in roots {
+            if let Some((kind, path)) = &best
+                && *kind >= CandidateKind::StubFile
+            {
+                self.step(
+                    1,
+                    format!(
+                        "Skipping `{}` for `{name}`, since the {} `{}` takes precedence",
+                        root.display(),
+                        kind.describe(),
+                        path.display()
+                    ),
+                );
+                continue;
+            }
+            self.step(1, format!("Looking for `{name}` in `{}`", root.display()));
+            if let Some(found) = self.walk(root, components)
+                && best.as_ref().is_none_or(|(kind, _)| found.0 > *kind)
+            {
+                best = Some(found);
+            }
+        }
+        best
+    }
+
+    /// Resolve each component of the module in turn, starting from `root`.
+    fn walk(&mut self, root: &Path, components: &[Name]) -> Option<(CandidateKind, PathBuf)> {
+        let mut dir = root.to_path_buf();
+        let mut found: Option<(CandidateKind, PathBuf)> = None;
+        for part in components {
+            if let Some((kind, path)) = &found
+                && !kind.is_package()
+            {
+                self.step(
+                    2,
+                    format!(
+                        "Rejected `{}`: it is a {}, not a package, so it can't contain `{part}`",
+                        path.display(),
+                        kind.describe()
+                    ),
+                );
+                return None;
+            }
+            let hit = candidates(&dir, part).into_iter().find(|(path, kind)| {
+                let exists = if *kind == CandidateKind::NamespacePackage {
+                    path.is_dir()
+                } else {
+                    path.exists()
+                };
+                if !exists {
+                    self.step(2, format!("Missing `{}`", path.display()));
+                }
+                exists
+            });
+            let (path, kind) = hit?;
+            self.step(2, format!("Found {} `{}`", kind.describe(), path.display()));
+            if kind.is_package() {
+                dir = dir.join(part.as_str());
+            }
+            found = Some((kind, path));
+        }
+        found
+    }
+}
+
+impl Display for ImportTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Some(origin) => writeln!(f, "Resolving `{}` imported from `{origin}`", self.module)?,
+            None => writeln!(f, "Resolving `{}`", self.module)?,
+        }
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+        let error = match &self.result {
+            FindingOrError::Finding(finding) => {
+                write!(f, "Resolved to `{}`", finding.finding)?;
+                finding.error.as_ref()
+            }
+            FindingOrError::Error(error) => {
+                write!(f, "Not resolved")?;
+                Some(error)
+            }
+        };
+        if let Some(error) = error {
+            for line in error.display().1 {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_config::environment::environment::PythonEnvironment;
+    use pyrefly_config::environment::interpreters::Interpreters;
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    #[test]
+    fn test_trace_import() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir("search", vec![TestPath::file("foo.py")]),
+                TestPath::dir(
+                    "site",
+                    vec![
+                        TestPath::dir("bar", vec![TestPath::file("__init__.py")]),
+                        TestPath::dir("bar-stubs", vec![TestPath::file("__init__.pyi")]),
+                    ],
+                ),
+            ],
+        );
+        let mut interpreters = Interpreters::default();
+        interpreters.skip_interpreter_query = true;
+        let mut config = ConfigFile {
+            search_path_from_file: vec![root.join("search")],
+            interpreters,
+            python_environment: PythonEnvironment {
+                site_package_path: Some(vec![root.join("site")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        config.configure();
+
+        let trace = ImportTrace::new(&config, ModuleName::from_str("foo.baz"), None);
+        let report = trace.to_string();
+        assert!(report.contains(&format!(
+            "Rejected `{}`: it is a source file, not a package, so it can't contain `baz`",
+            root.join("search/foo.py").display()
+        )));
+        assert!(report.contains("\nNot resolved"));
+
+        let trace = ImportTrace::new(&config, ModuleName::from_str("bar"), None);
+        let stub = root.join("site/bar-stubs/__init__.pyi");
+        assert_eq!(
+            trace.result(),
+            &FindingOrError::new_finding(ModulePath::filesystem(stub.clone()))
+        );
+        let report = trace.to_string();
+        assert!(report.contains(&format!("Missing `{}`", root.join("search/bar").display())));
+        assert!(report.contains(&format!(
+            "Using `{}`, because a `-stubs` package takes precedence over the source package",
+            stub.display()
+        )));
+    }
+}
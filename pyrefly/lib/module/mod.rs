@@ -7,6 +7,7 @@
 
 pub mod bundled;
 pub mod finder;
+pub mod import_trace;
 pub mod parse;
 pub mod typeshed;
 pub mod typeshed_third_party;
@@ -14,6 +14,7 @@ use std::sync::LazyLock;
 
 use anyhow::anyhow;
 use dupe::Dupe;
+use pyrefly_bundled::BundledStubs;
 use pyrefly_bundled::bundled_typeshed;
 use pyrefly_config::error::ErrorDisplayConfig;
 use pyrefly_config::error_kind::ErrorKind;
@@ -29,22 +30,21 @@ use crate::module::bundled::BundledStub;
 #[derive(Debug, Clone)]
 pub struct BundledTypeshedStdlib {
     pub find: SmallMap<ModuleName, PathBuf>,
-    pub load: SmallMap<PathBuf, Arc<String>>,
+    pub load: BundledStubs,
     pub temp_dir: &'static str,
 }
 
 impl BundledStub for BundledTypeshedStdlib {
     fn new() -> anyhow::Result<Self> {
-        let contents = bundled_typeshed()?;
+        let load = bundled_typeshed()?;
         let mut res = Self {
             find: SmallMap::new(),
-            load: SmallMap::new(),
+            load,
             temp_dir: "pyrefly_bundled_typeshed",
         };
-        for (relative_path, contents) in contents {
-            let module_name = ModuleName::from_relative_path(&relative_path)?;
+        for relative_path in res.load.paths() {
+            let module_name = ModuleName::from_relative_path(relative_path)?;
             res.find.insert(module_name, relative_path.clone());
-            res.load.insert(relative_path, Arc::new(contents));
         }
         Ok(res)
     }
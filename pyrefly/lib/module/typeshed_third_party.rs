@@ -12,6 +12,7 @@ use std::sync::LazyLock;
 
 use anyhow::anyhow;
 use dupe::Dupe;
+use pyrefly_bundled::BundledStubs;
 use pyrefly_bundled::bundled_third_party_stubs;
 use pyrefly_config::config::ConfigFile;
 use pyrefly_python::module_name::ModuleName;
@@ -25,22 +26,21 @@ use crate::module::bundled::BundledStub;
 #[derive(Debug, Clone)]
 pub struct BundledTypeshedThirdParty {
     pub find: SmallMap<ModuleName, PathBuf>,
-    pub load: SmallMap<PathBuf, Arc<String>>,
+    pub load: BundledStubs,
     pub temp_dir: &'static str,
 }
 
 impl BundledStub for BundledTypeshedThirdParty {
     fn new() -> anyhow::Result<Self> {
-        let contents = bundled_third_party_stubs()?;
+        let load = bundled_third_party_stubs()?;
         let mut res = Self {
             find: SmallMap::new(),
-            load: SmallMap::new(),
+            load,
             temp_dir: "pyrefly_bundled_typeshed_third_party",
         };
-        for (relative_path, contents) in contents {
-            let module_name = ModuleName::from_relative_path(&relative_path)?;
+        for relative_path in res.load.paths() {
+            let module_name = ModuleName::from_relative_path(relative_path)?;
             res.find.insert(module_name, relative_path.clone());
-            res.load.insert(relative_path, Arc::new(contents));
         }
         Ok(res)
     }
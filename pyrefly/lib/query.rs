@@ -19,6 +19,7 @@ use itertools::Itertools;
 use pyrefly_build::handle::Handle;
 use pyrefly_python::ast::Ast;
 use pyrefly_python::dunder;
+use pyrefly_python::fully_qualified_name::FullyQualifiedName;
 use pyrefly_python::module::Module;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
@@ -70,6 +71,7 @@ use crate::binding::binding::KeyClassSynthesizedFields;
 use crate::binding::binding::KeyDecoratedFunction;
 use crate::binding::bindings::Bindings;
 use crate::config::finder::ConfigFinder;
+use crate::error::error::Error;
 use crate::module::module_info::ModuleInfo;
 use crate::state::lsp::DefinitionMetadata;
 use crate::state::lsp::FindPreference;
@@ -202,14 +204,17 @@ fn python_ast_range_for_expr(
     } else {
         original_range
     };
+    python_ast_range(module_info, expression_range)
+}
 
+fn python_ast_range(module_info: &ModuleInfo, range: TextRange) -> PythonASTRange {
     let start_location = module_info.lined_buffer().line_index().source_location(
-        expression_range.start(),
+        range.start(),
         module_info.lined_buffer().contents(),
         ruff_source_file::PositionEncoding::Utf8,
     );
     let end_location = module_info.lined_buffer().line_index().source_location(
-        expression_range.end(),
+        range.end(),
         module_info.lined_buffer().contents(),
         ruff_source_file::PositionEncoding::Utf8,
     );
@@ -897,6 +902,16 @@ impl<'a> CalleesWithLocation<'a> {
     }
 }
 
+/// An error as a line of text, as `pyrefly check` prints it.
+fn error_line(e: &Error) -> String {
+    // We deliberately don't have a Display for `Error`, to encourage doing the right thing.
+    // But we just hack something up as this code is experimental.
+    let mut s = Cursor::new(Vec::new());
+    e.write_line(&mut s, PathBuf::new().as_path(), false)
+        .unwrap();
+    String::from_utf8_lossy(&s.into_inner()).into_owned()
+}
+
 impl Query {
     pub fn new(config_finder: ConfigFinder) -> Self {
         let state = State::new(config_finder);
@@ -943,14 +958,64 @@ impl Query {
         transaction.as_mut().run(&handles, Require::Everything);
         let errors = transaction.as_mut().get_errors(&handles);
         self.state.commit_transaction(transaction);
-        let project_root = PathBuf::new();
-        errors.collect_errors().shown.map(|e| {
-            // We deliberately don't have a Display for `Error`, to encourage doing the right thing.
-            // But we just hack something up as this code is experimental.
-            let mut s = Cursor::new(Vec::new());
-            e.write_line(&mut s, project_root.as_path(), false).unwrap();
-            String::from_utf8_lossy(&s.into_inner()).into_owned()
-        })
+        errors.collect_errors().shown.map(error_line)
+    }
+
+    /// Check `snippet` in place of `range` of the file at `path`, which must have been added with
+    /// `add_files`, as a completion or code generation tool would to validate a candidate before
+    /// suggesting it. Returns the errors the snippet would add to the file, with ranges in the
+    /// edited text, so the snippet type-checks in that context if there are none.
+    pub fn check_snippet_in_context(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+        range: TextRange,
+        snippet: &str,
+    ) -> Result<Vec<String>, String> {
+        let mut t = self.state.transaction();
+        let module_info = t
+            .get_module_info(&self.make_handle(name, path.dupe()))
+            .ok_or("File not loaded")?;
+        let code = module_info.contents().as_str();
+        let (start, end) = (range.start().to_usize(), range.end().to_usize());
+        if end > code.len() || !code.is_char_boundary(start) || !code.is_char_boundary(end) {
+            return Err(format!("Range {range:?} is not within the file"));
+        }
+        let mut edited = code.to_owned();
+        edited.replace_range(start..end, snippet);
+
+        // Check both versions in memory, so that the errors can be compared.
+        let memory_path = path.as_path().to_path_buf();
+        let h = self.make_handle(name, ModulePath::memory(memory_path.clone()));
+        let mut errors = |code: String| {
+            t.set_memory(vec![(memory_path.clone(), Some(Arc::new(code)))]);
+            t.run(&[h.dupe()], Require::Everything);
+            t.get_errors([&h]).collect_errors().shown
+        };
+        let before = errors(code.to_owned());
+        let after = errors(edited);
+        // Where an error of the current text is in the edited one, unless the snippet replaces it.
+        let new_len = TextSize::try_from(snippet.len()).unwrap();
+        let moved = |x: TextRange| {
+            if x.end() <= range.start() {
+                Some(x)
+            } else if x.start() >= range.end() {
+                Some(x - range.len() + new_len)
+            } else {
+                None
+            }
+        };
+        Ok(after
+            .iter()
+            .filter(|e| {
+                !before.iter().any(|x| {
+                    x.error_kind() == e.error_kind()
+                        && x.msg() == e.msg()
+                        && moved(x.range()) == Some(e.range())
+                })
+            })
+            .map(error_line)
+            .collect())
     }
 
     pub fn get_attributes(
@@ -1026,6 +1091,47 @@ impl Query {
         Some(find_callees.process(location))
     }
 
+    /// The fully qualified names of the classes and functions defined in a module, and of the
+    /// variables defined outside of functions, with the range of the name that defines each.
+    pub fn get_fully_qualified_names(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+    ) -> Option<Vec<(PythonASTRange, FullyQualifiedName)>> {
+        let handle = self.make_handle(name, path);
+        let transaction = self.state.transaction();
+        let ast = transaction.get_ast(&handle)?;
+        let module_info = transaction.get_module_info(&handle)?;
+
+        fn definitions(stmt: &Stmt, in_function: bool, res: &mut Vec<TextRange>) {
+            match stmt {
+                Stmt::ClassDef(x) => res.push(x.name.range),
+                Stmt::FunctionDef(x) => res.push(x.name.range),
+                Stmt::Assign(x) if !in_function => res.extend(
+                    x.targets
+                        .iter()
+                        .filter_map(|x| Some(x.as_name_expr()?.range)),
+                ),
+                Stmt::AnnAssign(x) if !in_function => {
+                    if let Expr::Name(x) = &*x.target {
+                        res.push(x.range)
+                    }
+                }
+                _ => {}
+            }
+            let in_function = in_function || matches!(stmt, Stmt::FunctionDef(_));
+            stmt.recurse(&mut |x| definitions(x, in_function, res));
+        }
+        let mut ranges = Vec::new();
+        ast.body.visit(&mut |x| definitions(x, false, &mut ranges));
+        Some(ranges.into_map(|range| {
+            (
+                python_ast_range(&module_info, range),
+                FullyQualifiedName::of_definition(&module_info, &ast, range),
+            )
+        }))
+    }
+
     pub fn get_types_in_file(
         &self,
         name: ModuleName,
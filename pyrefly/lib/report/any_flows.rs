@@ -0,0 +1,457 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Find where `Any` enters a project, and which public APIs it reaches, to show where adding
+//! annotations would do the most good.
+
+use std::fmt;
+use std::fmt::Display;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use pyrefly_util::display::count;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use serde::Serialize;
+
+use crate::binding::binding::Key;
+use crate::state::state::Transaction;
+use crate::types::types::Type;
+
+/// How `Any` enters the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnySourceKind {
+    /// An import of a module that can't be found, or a name from one with no type information.
+    UntypedImport,
+    /// A parameter or return type without an annotation, which is inferred to contain `Any`.
+    MissingAnnotation,
+    /// A `cast` to `Any`.
+    Cast,
+}
+
+impl Display for AnySourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UntypedImport => write!(f, "untyped import"),
+            Self::MissingAnnotation => write!(f, "missing annotation"),
+            Self::Cast => write!(f, "cast to `Any`"),
+        }
+    }
+}
+
+/// A place where `Any` enters the project, and how far it spreads.
+#[derive(Debug, Serialize)]
+pub struct AnySource {
+    pub kind: AnySourceKind,
+    /// What introduces the `Any`, e.g. an imported name, or `f(x)` for a parameter.
+    pub description: String,
+    pub module: ModuleName,
+    pub path: String,
+    /// The line and column of the source.
+    pub location: String,
+    /// How many times the value is used in its module.
+    pub uses: usize,
+    /// The public functions and methods whose types contain `Any` that the value flows into.
+    pub public_apis: Vec<String>,
+}
+
+/// The sources of `Any` in a project, ranked by how many public APIs they reach, then by how many
+/// times they are used.
+#[derive(Debug, Serialize)]
+pub struct AnyFlows {
+    pub sources: Vec<AnySource>,
+}
+
+impl Display for AnyFlows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sources.is_empty() {
+            return writeln!(f, "No sources of `Any` found");
+        }
+        for (i, source) in self.sources.iter().enumerate() {
+            writeln!(
+                f,
+                "{}. {} `{}` at {}:{}",
+                i + 1,
+                source.kind,
+                source.description,
+                source.path,
+                source.location
+            )?;
+            write!(f, "   used {}", count(source.uses, "time"))?;
+            if source.public_apis.is_empty() {
+                writeln!(f, ", reaches no public APIs")?;
+            } else {
+                writeln!(
+                    f,
+                    ", reaches {}: {}",
+                    count(source.public_apis.len(), "public API"),
+                    source.public_apis.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A source of `Any` found in a module, before working out where it flows.
+struct RawSource {
+    kind: AnySourceKind,
+    description: String,
+    range: TextRange,
+    /// The name the value is bound to, and the range within which uses of it are counted.
+    binding: Option<(Name, TextRange)>,
+}
+
+/// A function or method in the public API of a module, whose type contains `Any`.
+struct PublicApi {
+    name: String,
+    range: TextRange,
+}
+
+fn is_private(x: &str) -> bool {
+    x.starts_with('_')
+}
+
+fn is_dunder(x: &str) -> bool {
+    x.starts_with("__") && x.ends_with("__")
+}
+
+fn contains_any(ty: &Type) -> bool {
+    ty.any(|x| x.is_any())
+}
+
+/// Every read of a name in `stmts`, including from nested scopes.
+fn name_uses(stmts: &[Stmt]) -> Vec<(Name, TextRange)> {
+    fn f(x: &Expr, res: &mut Vec<(Name, TextRange)>) {
+        if let Expr::Name(x) = x
+            && x.ctx == ExprContext::Load
+        {
+            res.push((x.id.clone(), x.range));
+        }
+        x.recurse(&mut |x| f(x, res));
+    }
+    let mut res = Vec::new();
+    for stmt in stmts {
+        stmt.visit(&mut |x| f(x, &mut res));
+    }
+    res
+}
+
+/// Whether `call` is `cast(Any, ...)`, through any spelling of `cast` and `Any`.
+fn is_cast_to_any(x: &Expr) -> bool {
+    let is_named = |x: &Expr, name: &str| match x {
+        Expr::Name(x) => x.id == name,
+        Expr::Attribute(x) => x.attr.id == name,
+        Expr::StringLiteral(x) => x.value.to_str() == name,
+        _ => false,
+    };
+    if let Expr::Call(call) = x
+        && is_named(&call.func, "cast")
+        && let Some(target) = call.arguments.args.first()
+    {
+        is_named(target, "Any")
+    } else {
+        false
+    }
+}
+
+struct ModuleAnyFlows<'a> {
+    transaction: &'a Transaction<'a>,
+    handle: &'a Handle,
+    module_info: &'a Module,
+    sources: Vec<RawSource>,
+    public_apis: Vec<PublicApi>,
+}
+
+impl<'a> ModuleAnyFlows<'a> {
+    fn type_of(&self, key: Key) -> Option<Type> {
+        self.transaction.get_type(self.handle, &key)
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt], scope: TextRange, class: Option<&str>, public: bool) {
+        for stmt in stmts {
+            self.stmt(stmt, scope, class, public);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, scope: TextRange, class: Option<&str>, public: bool) {
+        match stmt {
+            Stmt::Import(x) => {
+                for alias in &x.names {
+                    let module = ModuleName::from_name(&alias.name.id);
+                    if self
+                        .transaction
+                        .import_handle(self.handle, module, None)
+                        .finding()
+                        .is_none()
+                    {
+                        let bound = match &alias.asname {
+                            Some(asname) => asname.id.clone(),
+                            None => module.first_component(),
+                        };
+                        self.sources.push(RawSource {
+                            kind: AnySourceKind::UntypedImport,
+                            description: module.to_string(),
+                            range: alias.range,
+                            binding: Some((bound, scope)),
+                        });
+                    }
+                }
+            }
+            Stmt::ImportFrom(x) => {
+                for alias in &x.names {
+                    let bound = alias.asname.as_ref().unwrap_or(&alias.name);
+                    if &alias.name != "*"
+                        && self
+                            .transaction
+                            .get_type_at(self.handle, bound.range.start())
+                            .is_some_and(|ty| ty.is_any())
+                    {
+                        self.sources.push(RawSource {
+                            kind: AnySourceKind::UntypedImport,
+                            description: bound.id.to_string(),
+                            range: alias.range,
+                            binding: Some((bound.id.clone(), scope)),
+                        });
+                    }
+                }
+            }
+            Stmt::FunctionDef(x) => self.function_def(x, scope, class, public),
+            Stmt::ClassDef(x) => {
+                let public = public && class.is_none() && !is_private(&x.name.id);
+                self.stmts(&x.body, scope, Some(&x.name.id), public);
+            }
+            _ => {
+                // Casts in nested statements are found when we visit those statements.
+                let mut nested = Vec::new();
+                stmt.recurse(&mut |x: &Stmt| nested.push(x.range()));
+                let mut casts = Vec::new();
+                stmt.visit(&mut |x: &Expr| {
+                    fn f(x: &Expr, casts: &mut Vec<TextRange>) {
+                        if is_cast_to_any(x) {
+                            casts.push(x.range());
+                        }
+                        x.recurse(&mut |x| f(x, casts));
+                    }
+                    f(x, &mut casts);
+                });
+                casts.retain(|x| !nested.iter().any(|n| n.contains_range(*x)));
+                let target = match stmt {
+                    Stmt::Assign(x) => match x.targets.as_slice() {
+                        [Expr::Name(target)] => Some(target.id.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                for range in casts {
+                    self.sources.push(RawSource {
+                        kind: AnySourceKind::Cast,
+                        description: self.module_info.code_at(range).to_owned(),
+                        range,
+                        binding: target.clone().map(|x| (x, scope)),
+                    });
+                }
+                stmt.recurse(&mut |x: &Stmt| self.stmt(x, scope, class, public));
+            }
+        }
+    }
+
+    fn function_def(
+        &mut self,
+        x: &StmtFunctionDef,
+        scope: TextRange,
+        class: Option<&str>,
+        public: bool,
+    ) {
+        let name = &x.name.id;
+        let qualified = match class {
+            Some(class) => format!("{class}.{name}"),
+            None => name.to_string(),
+        };
+        let public = public && (!is_private(name) || (class.is_some() && is_dunder(name)));
+        if public
+            && self
+                .type_of(Key::Definition(ShortIdentifier::new(&x.name)))
+                .is_some_and(|ty| contains_any(&ty))
+        {
+            self.public_apis.push(PublicApi {
+                name: format!("{}.{qualified}", self.handle.module()),
+                range: x.range,
+            });
+        }
+
+        let is_staticmethod = x.decorator_list.iter().any(|d| match &d.expression {
+            Expr::Name(x) => x.id == "staticmethod",
+            _ => false,
+        });
+        let receiver = if class.is_some() && !is_staticmethod {
+            x.parameters
+                .iter_non_variadic_params()
+                .next()
+                .map(|x| &x.parameter.name.id)
+        } else {
+            None
+        };
+        for p in x.parameters.iter() {
+            let param = p.name();
+            if p.annotation().is_none()
+                && Some(&param.id) != receiver
+                && self
+                    .type_of(Key::Definition(ShortIdentifier::new(param)))
+                    .is_some_and(|ty| contains_any(&ty))
+            {
+                self.sources.push(RawSource {
+                    kind: AnySourceKind::MissingAnnotation,
+                    description: format!("{qualified}({param})"),
+                    range: param.range,
+                    binding: Some((param.id.clone(), x.range)),
+                });
+            }
+        }
+        if x.returns.is_none()
+            && self
+                .type_of(Key::ReturnType(ShortIdentifier::new(&x.name)))
+                .is_some_and(|ty| contains_any(&ty))
+        {
+            self.sources.push(RawSource {
+                kind: AnySourceKind::MissingAnnotation,
+                description: format!("{qualified}() -> ..."),
+                range: x.name.range,
+                // Methods are called through an instance, so we can't count their uses by name.
+                binding: class.is_none().then(|| (name.clone(), scope)),
+            });
+        }
+        self.stmts(&x.body, x.range, None, false);
+    }
+}
+
+fn module_any_flows(transaction: &Transaction, handle: &Handle, res: &mut Vec<AnySource>) {
+    let (Some(info), Some(ast)) = (
+        transaction.get_module_info(handle),
+        transaction.get_ast(handle),
+    ) else {
+        return;
+    };
+    let module = handle.module();
+    let public = !module.components().iter().any(|x| is_private(x));
+    let mut flows = ModuleAnyFlows {
+        transaction,
+        handle,
+        module_info: &info,
+        sources: Vec::new(),
+        public_apis: Vec::new(),
+    };
+    flows.stmts(&ast.body, ast.range, None, public);
+    let uses = name_uses(&ast.body);
+
+    for source in flows.sources {
+        let uses_of_source = || {
+            uses.iter().filter(|(name, range)| {
+                source
+                    .binding
+                    .as_ref()
+                    .is_some_and(|(x, scope)| x == name && scope.contains_range(*range))
+            })
+        };
+        let public_apis = flows
+            .public_apis
+            .iter()
+            .filter(|api| {
+                api.range.contains_range(source.range)
+                    || uses_of_source().any(|(_, range)| api.range.contains_range(*range))
+            })
+            .map(|api| api.name.clone())
+            .collect();
+        res.push(AnySource {
+            kind: source.kind,
+            description: source.description,
+            module,
+            path: handle.path().to_string(),
+            location: info.display_pos(source.range.start()).to_string(),
+            uses: uses_of_source().count(),
+            public_apis,
+        });
+    }
+}
+
+impl AnyFlows {
+    /// Find the sources of `Any` in the modules of `handles`, which must already have been checked
+    /// with `Require::Everything`.
+    pub fn new(transaction: &Transaction, handles: &[Handle]) -> Self {
+        let mut sources = Vec::new();
+        for handle in handles {
+            module_any_flows(transaction, handle, &mut sources);
+        }
+        // A stable sort, so sources with the same blast radius stay in source order.
+        sources.sort_by_key(|x| std::cmp::Reverse((x.public_apis.len(), x.uses)));
+        Self { sources }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::require::Require;
+    use crate::test::util::mk_multi_file_state;
+
+    #[test]
+    fn test_any_flows() {
+        let code = r#"
+from typing import Any, cast
+from does_not_exist import thing
+
+def fetch(url):
+    return thing(url)
+
+def parse(text: str) -> int:
+    data = cast(Any, text)
+    return data
+
+class Client:
+    def get(self, path: str):
+        return fetch(path)
+
+def _helper(x: int) -> int:
+    return x
+"#;
+        let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Everything, false);
+        let transaction = state.transaction();
+        let flows = AnyFlows::new(&transaction, &[handles["main"].clone()]);
+        let summary = flows
+            .sources
+            .iter()
+            .map(|x| {
+                format!(
+                    "{} `{}`: {} uses, {}",
+                    x.kind,
+                    x.description,
+                    x.uses,
+                    x.public_apis.join(" ")
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                "untyped import `thing`: 1 uses, main.fetch",
+                "missing annotation `fetch(url)`: 1 uses, main.fetch",
+                "missing annotation `fetch() -> ...`: 1 uses, main.fetch main.Client.get",
+                "missing annotation `Client.get() -> ...`: 0 uses, main.Client.get",
+                "cast to `Any` `cast(Any, text)`: 1 uses, ",
+            ]
+        );
+    }
+}
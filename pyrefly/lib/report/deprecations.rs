@@ -0,0 +1,156 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! List the remaining usages of deprecated symbols by the owners of the files they are in, so that
+//! each team knows what it must migrate before the symbols are removed.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use pyrefly_util::display::count;
+use serde::Serialize;
+
+use crate::report::owners::CodeOwners;
+
+/// A usage of a deprecated symbol.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DeprecationUsage {
+    pub path: PathBuf,
+    pub line: usize,
+    /// The fully qualified name of the symbol.
+    pub symbol: String,
+    /// Whether the usage is an error rather than a warning, as it is once the symbol has been
+    /// removed according to the deprecation policy.
+    pub error: bool,
+}
+
+/// The usages of deprecated symbols in the files owned by one owner.
+#[derive(Debug, Serialize)]
+pub struct OwnerDeprecations {
+    /// The owner, or `None` for the files without one.
+    pub owner: Option<String>,
+    pub usages: Vec<DeprecationUsage>,
+}
+
+/// The usages of deprecated symbols in a project by owner, the owners with the most usages first.
+/// A file with several owners counts towards each of them.
+#[derive(Debug, Serialize)]
+pub struct DeprecationsReport {
+    pub owners: Vec<OwnerDeprecations>,
+}
+
+impl DeprecationsReport {
+    pub fn new(
+        code_owners: &CodeOwners,
+        usages: impl IntoIterator<Item = DeprecationUsage>,
+    ) -> Self {
+        let mut by_owner: BTreeMap<Option<String>, Vec<DeprecationUsage>> = BTreeMap::new();
+        for usage in usages {
+            let owners = code_owners.owners_of(&usage.path);
+            if owners.is_empty() {
+                by_owner.entry(None).or_default().push(usage);
+            } else {
+                for owner in owners {
+                    by_owner
+                        .entry(Some(owner.clone()))
+                        .or_default()
+                        .push(usage.clone());
+                }
+            }
+        }
+        let mut owners = by_owner
+            .into_iter()
+            .map(|(owner, mut usages)| {
+                usages.sort();
+                OwnerDeprecations { owner, usages }
+            })
+            .collect::<Vec<_>>();
+        // The sort is stable, so owners with as many usages stay in order of name.
+        owners.sort_by_key(|x| Reverse(x.usages.len()));
+        Self { owners }
+    }
+}
+
+impl Display for DeprecationsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.owners.is_empty() {
+            return writeln!(f, "No usages of deprecated symbols found");
+        }
+        for x in &self.owners {
+            writeln!(
+                f,
+                "{}: {}, {}",
+                x.owner.as_deref().unwrap_or("(no owner)"),
+                count(x.usages.len(), "usage"),
+                count(x.usages.iter().filter(|x| x.error).count(), "error"),
+            )?;
+            for usage in &x.usages {
+                writeln!(
+                    f,
+                    "  {}:{}: `{}`{}",
+                    usage.path.display(),
+                    usage.line,
+                    usage.symbol,
+                    if usage.error { " (error)" } else { "" },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let code_owners = CodeOwners::parse(
+            PathBuf::from("/repo"),
+            r#"
+*                   @org/python
+/app/billing/       @org/payments @alice
+/app/vendored       # No owner.
+"#,
+        )
+        .unwrap();
+        let usage = |path: &str, line, symbol: &str, error| DeprecationUsage {
+            path: PathBuf::from(path),
+            line,
+            symbol: symbol.to_owned(),
+            error,
+        };
+        let report = DeprecationsReport::new(
+            &code_owners,
+            [
+                usage("/repo/main.py", 3, "lib.old", false),
+                usage("/repo/app/billing/tax.py", 7, "lib.Client.get", true),
+                usage("/repo/app/billing/invoice.py", 12, "lib.old", false),
+                usage("/repo/app/vendored/six.py", 1, "lib.old", false),
+            ],
+        );
+        assert_eq!(
+            report.to_string(),
+            r#"
+@alice: 2 usages, 1 error
+  /repo/app/billing/invoice.py:12: `lib.old`
+  /repo/app/billing/tax.py:7: `lib.Client.get` (error)
+@org/payments: 2 usages, 1 error
+  /repo/app/billing/invoice.py:12: `lib.old`
+  /repo/app/billing/tax.py:7: `lib.Client.get` (error)
+(no owner): 1 usage, 0 errors
+  /repo/app/vendored/six.py:1: `lib.old`
+@org/python: 1 usage, 0 errors
+  /repo/main.py:3: `lib.old`
+"#
+            .trim_start()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Per-function metrics of complexity and typing debt, for dashboards that track how well typed a
+//! project is beyond the share of expressions with a known type.
+
+use std::fmt;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
+use pyrefly_util::display::count;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use serde::Serialize;
+
+use crate::alt::answers::Answers;
+use crate::state::state::Transaction;
+
+/// The metrics of one function or method.
+#[derive(Debug, Serialize)]
+pub struct FunctionMetrics {
+    /// The fully qualified name, e.g. `pkg.mod.Class.method`.
+    pub name: String,
+    pub path: String,
+    pub line: usize,
+    /// The number of parameters, other than the `self` or `cls` of a method.
+    pub parameters: usize,
+    /// The number of those parameters without an annotation.
+    pub untyped_parameters: usize,
+    /// The number of expressions in the body whose type is `Any`.
+    pub any_expressions: usize,
+    /// The number of lines in the body with an error suppression.
+    pub suppressions: usize,
+}
+
+/// The metrics of every function and method in a project, in source order. The bodies of nested
+/// functions and classes count towards their own metrics, not those of the enclosing function.
+#[derive(Debug, Serialize)]
+pub struct Metrics {
+    pub functions: Vec<FunctionMetrics>,
+}
+
+impl Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.functions.is_empty() {
+            return writeln!(f, "No functions found");
+        }
+        for x in &self.functions {
+            writeln!(
+                f,
+                "{}:{}: `{}`: {}, {} untyped, {}, {}",
+                x.path,
+                x.line,
+                x.name,
+                count(x.parameters, "parameter"),
+                x.untyped_parameters,
+                count(x.any_expressions, "`Any` expression"),
+                count(x.suppressions, "suppression"),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn is_staticmethod(x: &StmtFunctionDef) -> bool {
+    x.decorator_list.iter().any(|d| match &d.expression {
+        Expr::Name(x) => x.id == "staticmethod",
+        _ => false,
+    })
+}
+
+/// The ranges of the bodies of the functions and classes nested in `stmts`.
+fn nested_bodies(stmts: &[Stmt]) -> Vec<TextRange> {
+    fn f(x: &Stmt, res: &mut Vec<TextRange>) {
+        match x {
+            Stmt::FunctionDef(StmtFunctionDef { body, .. })
+            | Stmt::ClassDef(StmtClassDef { body, .. }) => {
+                if let (Some(first), Some(last)) = (body.first(), body.last()) {
+                    res.push(TextRange::new(first.start(), last.end()));
+                }
+            }
+            _ => x.recurse(&mut |x| f(x, res)),
+        }
+    }
+    let mut res = Vec::new();
+    for stmt in stmts {
+        f(stmt, &mut res);
+    }
+    res
+}
+
+struct ModuleMetrics<'a> {
+    handle: &'a Handle,
+    module_info: &'a Module,
+    answers: Option<Arc<Answers>>,
+    functions: &'a mut Vec<FunctionMetrics>,
+}
+
+impl<'a> ModuleMetrics<'a> {
+    fn stmts(&mut self, stmts: &[Stmt], prefix: &str, in_class: bool) {
+        for stmt in stmts {
+            self.stmt(stmt, prefix, in_class);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, prefix: &str, in_class: bool) {
+        match stmt {
+            Stmt::FunctionDef(x) => self.function_def(x, prefix, in_class),
+            Stmt::ClassDef(x) => {
+                let prefix = format!("{prefix}.{}", x.name.id);
+                self.stmts(&x.body, &prefix, true);
+            }
+            _ => stmt.recurse(&mut |x: &Stmt| self.stmt(x, prefix, in_class)),
+        }
+    }
+
+    fn any_expressions(&self, body: &[Stmt], nested: &[TextRange]) -> usize {
+        fn f(x: &Expr, answers: &Answers, nested: &[TextRange], res: &mut usize) {
+            if nested.iter().any(|n| n.contains_range(x.range())) {
+                return;
+            }
+            if answers
+                .get_type_trace(x.range())
+                .is_some_and(|ty| ty.is_any())
+            {
+                *res += 1;
+            }
+            x.recurse(&mut |x| f(x, answers, nested, res));
+        }
+        let Some(answers) = &self.answers else {
+            return 0;
+        };
+        let mut res = 0;
+        for stmt in body {
+            stmt.visit(&mut |x: &Expr| f(x, answers, nested, &mut res));
+        }
+        res
+    }
+
+    fn suppressions(&self, body: &[Stmt], nested: &[TextRange]) -> usize {
+        let (Some(first), Some(last)) = (body.first(), body.last()) else {
+            return 0;
+        };
+        let lines = |range: TextRange| {
+            let range = self.module_info.display_range(range);
+            range.start.line_within_file()..=range.end.line_within_file()
+        };
+        let nested = nested.iter().map(|x| lines(*x)).collect::<Vec<_>>();
+        let body = lines(TextRange::new(first.start(), last.end()));
+        self.module_info
+            .ignore()
+            .iter()
+            .filter(|(line, _)| body.contains(*line) && !nested.iter().any(|x| x.contains(*line)))
+            .count()
+    }
+
+    fn function_def(&mut self, x: &StmtFunctionDef, prefix: &str, in_class: bool) {
+        let name = format!("{prefix}.{}", x.name.id);
+        let receiver = if in_class && !is_staticmethod(x) {
+            x.parameters
+                .iter_non_variadic_params()
+                .next()
+                .map(|x| &x.parameter.name.id)
+        } else {
+            None
+        };
+        let parameters = x
+            .parameters
+            .iter()
+            .filter(|p| Some(&p.name().id) != receiver)
+            .collect::<Vec<_>>();
+        let nested = nested_bodies(&x.body);
+        self.functions.push(FunctionMetrics {
+            name: name.clone(),
+            path: self.handle.path().to_string(),
+            line: self
+                .module_info
+                .display_range(x.name.range)
+                .start
+                .line_within_file()
+                .get() as usize,
+            parameters: parameters.len(),
+            untyped_parameters: parameters
+                .iter()
+                .filter(|p| p.annotation().is_none())
+                .count(),
+            any_expressions: self.any_expressions(&x.body, &nested),
+            suppressions: self.suppressions(&x.body, &nested),
+        });
+        self.stmts(&x.body, &name, false);
+    }
+}
+
+impl Metrics {
+    /// Compute the metrics of the functions in the modules of `handles`, which must already have
+    /// been checked with `Require::Everything`.
+    pub fn new(transaction: &Transaction, handles: &[Handle]) -> Self {
+        let mut functions = Vec::new();
+        for handle in handles {
+            let (Some(info), Some(ast)) = (
+                transaction.get_module_info(handle),
+                transaction.get_ast(handle),
+            ) else {
+                continue;
+            };
+            ModuleMetrics {
+                handle,
+                module_info: &info,
+                answers: transaction.get_answers(handle),
+                functions: &mut functions,
+            }
+            .stmts(&ast.body, handle.module().as_str(), false);
+        }
+        Self { functions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::require::Require;
+    use crate::test::util::mk_multi_file_state;
+
+    #[test]
+    fn test_metrics() {
+        let code = r#"
+def fetch(url, timeout: float = 1.0):
+    return url.decode(timeout)
+
+class Client:
+    def get(self, path: str) -> str:
+        def helper(x):
+            return x  # type: ignore
+        return path
+
+    @staticmethod
+    def parse(text, *args: int, **kwargs) -> None:
+        if text:
+            print(text)  # pyrefly: ignore
+"#;
+        let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Everything, false);
+        let transaction = state.transaction();
+        let metrics = Metrics::new(&transaction, &[handles["main"].clone()]);
+        let summary = metrics
+            .functions
+            .iter()
+            .map(|x| {
+                format!(
+                    "{} at {}: {} parameters, {} untyped, {} Any, {} suppressions",
+                    x.name,
+                    x.line,
+                    x.parameters,
+                    x.untyped_parameters,
+                    x.any_expressions,
+                    x.suppressions
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                "main.fetch at 2: 2 parameters, 1 untyped, 3 Any, 0 suppressions",
+                "main.Client.get at 6: 1 parameters, 0 untyped, 0 Any, 0 suppressions",
+                "main.Client.get.helper at 7: 1 parameters, 1 untyped, 1 Any, 1 suppressions",
+                "main.Client.parse at 12: 3 parameters, 2 untyped, 2 Any, 1 suppressions",
+            ]
+        );
+    }
+}
@@ -5,8 +5,13 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+pub mod any_flows;
 pub mod binding_memory;
 pub mod debug_info;
+pub mod deprecations;
 pub mod glean;
+pub mod metrics;
+pub mod owners;
 pub mod pysa;
 pub mod trace;
+pub mod why;
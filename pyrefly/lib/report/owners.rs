@@ -0,0 +1,356 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Attribute the errors in a project to the teams that own the files they are in, as given by a
+//! `CODEOWNERS` file, so errors can be driven down team by team rather than file by file.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use pyrefly_util::absolutize::Absolutize as _;
+use pyrefly_util::display::count;
+use pyrefly_util::fs_anyhow;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::config::error_kind::Severity;
+
+/// A rule of a `CODEOWNERS` file: the files matching a pattern, and who owns them.
+#[derive(Debug)]
+struct OwnersRule {
+    pattern: Regex,
+    owners: Vec<String>,
+}
+
+/// Turn a `CODEOWNERS` pattern, which follows the rules of `.gitignore`, into a regex matching
+/// the paths, relative to the root of the repository, that it covers.
+fn pattern_to_regex(pattern: &str) -> anyhow::Result<Regex> {
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    // A pattern with a slash before its end is relative to the root, otherwise it matches at any
+    // depth.
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let mut res = if anchored {
+        "^".to_owned()
+    } else {
+        "^(?:.*/)?".to_owned()
+    };
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    res.push_str("(?:.*/)?");
+                } else {
+                    res.push_str(".*");
+                }
+            }
+            '*' => res.push_str("[^/]*"),
+            '?' => res.push_str("[^/]"),
+            c => res.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // A pattern matching a directory covers everything in it, except that a wildcard in the last
+    // component only matches files directly in the directory, as in `docs/*`.
+    let last = pattern.rsplit('/').next().unwrap_or_default();
+    if dir_only {
+        res.push_str("/.*$");
+    } else if last.contains('*') && !last.contains("**") {
+        res.push('$');
+    } else {
+        res.push_str("(?:/.*)?$");
+    }
+    Ok(Regex::new(&res)?)
+}
+
+/// The rules of a `CODEOWNERS` file, such as:
+///
+/// ```text
+/// *               @org/python
+/// /app/billing/   @org/payments @alice
+/// *_test.py       @org/testing
+/// ```
+///
+/// As on GitHub, the last rule that matches a file decides its owners.
+#[derive(Debug)]
+pub struct CodeOwners {
+    /// The directory the patterns are relative to.
+    root: PathBuf,
+    rules: Vec<OwnersRule>,
+}
+
+impl CodeOwners {
+    /// Read a `CODEOWNERS` file. Its patterns are relative to the root of the repository, which is
+    /// the directory containing the file, or its parent when the file is in `.github` or `docs`.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs_anyhow::read_to_string(path)?;
+        Self::parse(Self::root_of(path), &content)
+            .with_context(|| format!("while parsing CODEOWNERS file `{}`", path.display()))
+    }
+
+    /// The root of the repository containing the `CODEOWNERS` file at `path`, as an absolute path
+    /// so the paths of checked files can be made relative to it.
+    fn root_of(path: &Path) -> PathBuf {
+        let path = path.absolutize();
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        let root = match dir.file_name().and_then(|x| x.to_str()) {
+            Some(".github" | "docs") => dir.parent().unwrap_or(dir),
+            _ => dir,
+        };
+        root.to_path_buf()
+    }
+
+    pub(crate) fn parse(root: PathBuf, content: &str) -> anyhow::Result<Self> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.split_once('#').map_or(line, |(x, _)| x);
+            let mut parts = line.split_whitespace();
+            if let Some(pattern) = parts.next() {
+                rules.push(OwnersRule {
+                    pattern: pattern_to_regex(pattern)?,
+                    owners: parts.map(|x| x.to_owned()).collect(),
+                });
+            }
+        }
+        Ok(Self { root, rules })
+    }
+
+    /// The owners of the file at `path`, which is empty if no rule matches it, if the matching
+    /// rule has no owners, or if the file is outside of the repository.
+    pub fn owners_of(&self, path: &Path) -> &[String] {
+        let path = path.absolutize();
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return &[];
+        };
+        let relative = relative
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|x| x.pattern.is_match(&relative))
+            .map_or(&[], |x| x.owners.as_slice())
+    }
+}
+
+/// The errors and warnings in the files owned by one owner.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OwnerTally {
+    /// The owner, or `None` for the files without one.
+    pub owner: Option<String>,
+    pub errors: usize,
+    pub warnings: usize,
+    /// How many of the owner's files have errors or warnings.
+    pub files: usize,
+}
+
+/// The errors and warnings of a project by owner, the owners with the most errors first. A file
+/// with several owners counts towards each of them.
+#[derive(Debug, Serialize)]
+pub struct OwnersReport {
+    pub owners: Vec<OwnerTally>,
+}
+
+impl OwnersReport {
+    /// Tally the errors, given by the path of their file and their severity.
+    pub fn new<'a>(
+        code_owners: &CodeOwners,
+        errors: impl IntoIterator<Item = (&'a Path, Severity)>,
+    ) -> Self {
+        let mut tallies: BTreeMap<Option<&str>, (OwnerTally, BTreeSet<&Path>)> = BTreeMap::new();
+        for (path, severity) in errors {
+            let owners = code_owners.owners_of(path);
+            let owners = if owners.is_empty() {
+                vec![None]
+            } else {
+                owners.iter().map(|x| Some(x.as_str())).collect()
+            };
+            for owner in owners {
+                let (tally, files) = tallies.entry(owner).or_insert_with(|| {
+                    (
+                        OwnerTally {
+                            owner: owner.map(|x| x.to_owned()),
+                            ..Default::default()
+                        },
+                        BTreeSet::new(),
+                    )
+                });
+                match severity {
+                    Severity::Error => tally.errors += 1,
+                    Severity::Warn => tally.warnings += 1,
+                    Severity::Info | Severity::Ignore => continue,
+                }
+                files.insert(path);
+            }
+        }
+        let mut owners = tallies
+            .into_values()
+            .filter(|(_, files)| !files.is_empty())
+            .map(|(mut tally, files)| {
+                tally.files = files.len();
+                tally
+            })
+            .collect::<Vec<_>>();
+        // The sort is stable, so owners with as many errors stay in order of name.
+        owners.sort_by_key(|x| (Reverse(x.errors), Reverse(x.warnings)));
+        Self { owners }
+    }
+
+    /// The report as CSV, with a header row.
+    pub fn to_csv(&self) -> String {
+        fn field(x: &str) -> String {
+            if x.contains([',', '"', '\n']) {
+                format!("\"{}\"", x.replace('"', "\"\""))
+            } else {
+                x.to_owned()
+            }
+        }
+        let mut res = "owner,errors,warnings,files\n".to_owned();
+        for x in &self.owners {
+            res.push_str(&format!(
+                "{},{},{},{}\n",
+                field(x.owner.as_deref().unwrap_or_default()),
+                x.errors,
+                x.warnings,
+                x.files
+            ));
+        }
+        res
+    }
+}
+
+impl Display for OwnersReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.owners.is_empty() {
+            return writeln!(f, "No errors found");
+        }
+        for x in &self.owners {
+            writeln!(
+                f,
+                "{}: {}, {} in {}",
+                x.owner.as_deref().unwrap_or("(no owner)"),
+                count(x.errors, "error"),
+                count(x.warnings, "warning"),
+                count(x.files, "file"),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_owners() -> CodeOwners {
+        CodeOwners::parse(
+            PathBuf::from("/repo"),
+            r#"
+# Everything defaults to the Python team.
+*                   @org/python
+/app/billing/       @org/payments @alice
+docs/*              @org/docs
+*_test.py           @org/testing
+/app/vendored       # No owner.
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_owners_of() {
+        let code_owners = code_owners();
+        let owners_of = |x: &str| code_owners.owners_of(Path::new(x)).join(" ");
+        assert_eq!(owners_of("/repo/main.py"), "@org/python");
+        assert_eq!(
+            owners_of("/repo/app/billing/invoice.py"),
+            "@org/payments @alice"
+        );
+        assert_eq!(
+            owners_of("/repo/app/billing/invoice_test.py"),
+            "@org/testing"
+        );
+        assert_eq!(owners_of("/repo/docs/conf.py"), "@org/docs");
+        assert_eq!(owners_of("/repo/docs/api/conf.py"), "@org/python");
+        assert_eq!(owners_of("/repo/lib/docs/conf.py"), "@org/python");
+        assert_eq!(owners_of("/repo/app/vendored/six.py"), "");
+        assert_eq!(owners_of("/elsewhere/main.py"), "");
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(CodeOwners::root_of(Path::new(".github/CODEOWNERS")), cwd);
+        assert_eq!(CodeOwners::root_of(Path::new("CODEOWNERS")), cwd);
+        assert_eq!(
+            CodeOwners::root_of(Path::new("repo/docs/CODEOWNERS")),
+            cwd.join("repo")
+        );
+        let code_owners = CodeOwners::parse(
+            CodeOwners::root_of(Path::new(".github/CODEOWNERS")),
+            "/app/ @org/app",
+        )
+        .unwrap();
+        assert_eq!(
+            code_owners.owners_of(&cwd.join("app/main.py")),
+            ["@org/app".to_owned()]
+        );
+        assert_eq!(
+            code_owners.owners_of(Path::new("app/main.py")),
+            ["@org/app".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let code_owners = code_owners();
+        let errors = [
+            ("/repo/main.py", Severity::Error),
+            ("/repo/main.py", Severity::Warn),
+            ("/repo/app/billing/invoice.py", Severity::Error),
+            ("/repo/app/billing/tax.py", Severity::Error),
+            ("/repo/app/vendored/six.py", Severity::Error),
+            ("/repo/app/vendored/six.py", Severity::Info),
+        ];
+        let report = OwnersReport::new(
+            &code_owners,
+            errors.iter().map(|(path, x)| (Path::new(*path), *x)),
+        );
+        assert_eq!(
+            report.to_string(),
+            r#"
+@alice: 2 errors, 0 warnings in 2 files
+@org/payments: 2 errors, 0 warnings in 2 files
+@org/python: 1 error, 1 warning in 1 file
+(no owner): 1 error, 0 warnings in 1 file
+"#
+            .trim_start()
+        );
+        assert_eq!(
+            report.to_csv(),
+            r#"
+owner,errors,warnings,files
+@alice,2,0,2
+@org/payments,2,0,2
+@org/python,1,1,1
+,1,0,1
+"#
+            .trim_start()
+        );
+    }
+}
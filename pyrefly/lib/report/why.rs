@@ -0,0 +1,313 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Explain how the type of a name was computed, by walking back through the bindings that
+//! produced it: the assignment or declaration it came from, each narrowing applied along the way,
+//! the joins of control flow, and how placeholder types were solved.
+
+use std::fmt;
+use std::fmt::Display;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_util::display::DisplayWithCtx;
+use pyrefly_util::display::count;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextSize;
+use serde::Serialize;
+use starlark_map::small_set::SmallSet;
+
+use crate::alt::answers::Answers;
+use crate::binding::binding::Binding;
+use crate::binding::binding::FirstUse;
+use crate::binding::binding::FunctionParameter;
+use crate::binding::binding::Key;
+use crate::binding::binding::KeyAnnotation;
+use crate::binding::bindings::Bindings;
+use crate::binding::parameter_provider::ProvidedParameter;
+use crate::graph::index::Idx;
+use crate::state::state::Transaction;
+
+/// One step in computing a type.
+#[derive(Debug, Serialize)]
+pub struct WhyStep {
+    /// The line and column range the step applies to.
+    pub location: String,
+    pub description: String,
+    /// The type after this step.
+    pub ty: Option<String>,
+}
+
+/// How the type of a name was computed, from where its value came from to the final answer.
+#[derive(Debug, Serialize)]
+pub struct Why {
+    /// The name being explained.
+    pub name: String,
+    pub path: String,
+    pub location: String,
+    pub ty: Option<String>,
+    /// The steps that produced the type, in the order they apply.
+    pub steps: Vec<WhyStep>,
+}
+
+impl Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` at {}:{}", self.name, self.path, self.location)?;
+        match &self.ty {
+            Some(ty) => writeln!(f, " has type `{ty}`")?,
+            None => writeln!(f, " has no known type")?,
+        }
+        if !self.steps.is_empty() {
+            writeln!(f, "because:")?;
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(f, "{}. {}: {}", i + 1, step.location, step.description)?;
+            match &step.ty {
+                Some(ty) => writeln!(f, " => `{ty}`")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Explainer<'a> {
+    bindings: &'a Bindings,
+    answers: &'a Answers,
+    seen: SmallSet<Idx<Key>>,
+    steps: Vec<WhyStep>,
+}
+
+impl<'a> Explainer<'a> {
+    fn code(&self, idx: Idx<Key>) -> &str {
+        self.bindings
+            .module()
+            .code_at(self.bindings.idx_to_key(idx).range())
+    }
+
+    fn annotation(&self, idx: Idx<KeyAnnotation>) -> String {
+        match self.answers.get_idx(idx) {
+            Some(x) => format!("`{}`", x.annotation),
+            None => "an unknown annotation".to_owned(),
+        }
+    }
+
+    /// Add the steps leading to the type of `idx`, upstream bindings first.
+    fn explain(&mut self, idx: Idx<Key>) {
+        if !self.seen.insert(idx) {
+            return;
+        }
+        let bindings = self.bindings;
+        let module = bindings.module();
+        let ty = self.answers.get_type_at(idx);
+        let description = match bindings.get(idx) {
+            Binding::Forward(k) => {
+                // Forwarding doesn't change anything, so isn't worth a step.
+                self.explain(*k);
+                return;
+            }
+            Binding::Narrow(k, op, _) => {
+                self.explain(*k);
+                format!(
+                    "narrowed by `{}` ({})",
+                    self.code(idx),
+                    op.display_with(module)
+                )
+            }
+            Binding::Phi(_, xs) => {
+                for x in xs {
+                    self.explain(*x);
+                }
+                format!(
+                    "joined {} where control flow merges",
+                    count(xs.len(), "branch")
+                )
+            }
+            Binding::LoopPhi(prior, xs) => {
+                self.explain(*prior);
+                for x in xs {
+                    self.explain(*x);
+                }
+                format!(
+                    "joined {} at the top of a loop",
+                    count(xs.len() + 1, "branch")
+                )
+            }
+            Binding::NameAssign(_, Some((_, annot)), expr, _) => {
+                format!(
+                    "assigned `{}`, declared as {}",
+                    module.display(expr),
+                    self.annotation(*annot)
+                )
+            }
+            Binding::NameAssign(_, None, expr, _) => {
+                format!(
+                    "assigned `{}`, inferred from the value",
+                    module.display(expr)
+                )
+            }
+            Binding::CompletedPartialType(k, first_use) => {
+                self.explain(*k);
+                if ty == self.answers.get_type_at(*k) {
+                    // There were no placeholder types to solve.
+                    return;
+                }
+                match first_use {
+                    FirstUse::UsedBy(u) => format!(
+                        "solved placeholder types from the first use, `{}` at {}",
+                        self.code(*u),
+                        module.display_range(bindings.idx_to_key(*u).range())
+                    ),
+                    FirstUse::DoesNotPin => {
+                        "defaulted placeholder types, since the first use doesn't constrain them"
+                            .to_owned()
+                    }
+                    FirstUse::Undetermined => {
+                        "defaulted placeholder types, since the value is never used".to_owned()
+                    }
+                }
+            }
+            Binding::PartialTypeWithUpstreamsCompleted(k, _) => {
+                self.explain(*k);
+                if ty == self.answers.get_type_at(*k) {
+                    return;
+                }
+                "solved placeholder types that came from earlier assignments".to_owned()
+            }
+            Binding::AnnotatedType(annot, _) => {
+                format!("declared as {}", self.annotation(*annot))
+            }
+            Binding::FunctionParameter(FunctionParameter::Annotated(annot)) => {
+                format!("parameter declared as {}", self.annotation(*annot))
+            }
+            Binding::FunctionParameter(FunctionParameter::Unannotated(_, _, Some(provided))) => {
+                match &**provided {
+                    ProvidedParameter::Fixture(_) => {
+                        "parameter given the value of the fixture of the same name".to_owned()
+                    }
+                    ProvidedParameter::ExternalFixture(m, _) => {
+                        format!("parameter given the value of the fixture in `{m}`")
+                    }
+                    ProvidedParameter::Dependency(_) => {
+                        "parameter given the value of its dependency".to_owned()
+                    }
+                    ProvidedParameter::Values(_) => {
+                        "parameter given each of the values listed for it".to_owned()
+                    }
+                    ProvidedParameter::Instance(_) => {
+                        "parameter given an instance of the type declared for it".to_owned()
+                    }
+                    ProvidedParameter::Multiple(_) => {
+                        "parameter given a tuple of the values passed for it".to_owned()
+                    }
+                    ProvidedParameter::Optional(_) => {
+                        "parameter given the value passed for it, or `None`".to_owned()
+                    }
+                }
+            }
+            Binding::FunctionParameter(FunctionParameter::Unannotated(..)) => {
+                "parameter without an annotation, inferred from its default and the function"
+                    .to_owned()
+            }
+            Binding::Import(m, name, _) => format!("imported `{name}` from `{m}`"),
+            Binding::Module(m, _, _) => format!("imported module `{m}`"),
+            Binding::Expr(None, expr) => format!("evaluated `{}`", module.display(expr)),
+            Binding::Expr(Some(annot), expr) => format!(
+                "evaluated `{}`, declared as {}",
+                module.display(expr),
+                self.annotation(*annot)
+            ),
+            binding => format!("computed {}", binding.display_with(bindings)),
+        };
+        self.steps.push(WhyStep {
+            location: module
+                .display_range(bindings.idx_to_key(idx).range())
+                .to_string(),
+            description,
+            ty: ty.map(|x| x.to_string()),
+        });
+    }
+}
+
+impl Why {
+    /// Explain the type of the name at `position` in the module of `handle`, which must already
+    /// have been checked with `Require::Everything`.
+    pub fn new(transaction: &Transaction, handle: &Handle, position: TextSize) -> Option<Self> {
+        let key = transaction.key_at(handle, position)?;
+        let bindings = transaction.get_bindings(handle)?;
+        let answers = transaction.get_answers(handle)?;
+        let idx = bindings.key_to_idx(&key);
+        let mut explainer = Explainer {
+            bindings: &bindings,
+            answers: &answers,
+            seen: SmallSet::new(),
+            steps: Vec::new(),
+        };
+        explainer.explain(idx);
+        let module = bindings.module();
+        Some(Self {
+            name: module.code_at(key.range()).to_owned(),
+            path: handle.path().to_string(),
+            location: module.display_range(key.range()).to_string(),
+            ty: answers.get_type_at(idx).map(|x| x.to_string()),
+            steps: explainer.steps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::require::Require;
+    use crate::test::util::mk_multi_file_state;
+
+    fn why(code: &str, needle: &str) -> Why {
+        let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Everything, false);
+        let transaction = state.transaction();
+        let position = TextSize::try_from(code.rfind(needle).unwrap()).unwrap();
+        Why::new(&transaction, &handles["main"], position).unwrap()
+    }
+
+    #[test]
+    fn test_why_narrow() {
+        let code = r#"
+def f(x: int | None) -> int:
+    if x is None:
+        x = 0
+    return x
+"#;
+        let res = why(code, "x\n");
+        assert_eq!(res.ty.as_deref(), Some("int"));
+        let steps = res
+            .steps
+            .iter()
+            .map(|x| x.description.as_str())
+            .collect::<Vec<_>>();
+        assert!(steps.contains(&"parameter declared as `int | None`"));
+        assert!(steps.contains(&"narrowed by `x is None` (IsNot(None))"));
+        assert!(steps.contains(&"assigned `0`, inferred from the value"));
+        assert_eq!(
+            steps.last(),
+            Some(&"joined 2 branches where control flow merges")
+        );
+    }
+
+    #[test]
+    fn test_why_first_use() {
+        let code = r#"
+xs = []
+xs.append(1)
+ys = xs
+"#;
+        let res = why(code, "xs\n");
+        assert_eq!(res.ty.as_deref(), Some("list[int]"));
+        assert!(res.steps.iter().any(|x| {
+            x.description
+                .starts_with("solved placeholder types from the first use")
+                && x.ty.as_deref() == Some("list[int]")
+        }));
+    }
+}
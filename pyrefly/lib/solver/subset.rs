@@ -420,7 +420,14 @@ impl<'a, Ans: LookupAnswer> Subset<'a, Ans> {
             }
             if matches!(
                 got,
-                Type::Callable(_) | Type::Function(_) | Type::BoundMethod(_)
+                Type::Callable(_)
+                    | Type::Function(_)
+                    | Type::BoundMethod(_)
+                    | Type::Overload(_)
+                    | Type::Forall(box Forall {
+                        body: Forallable::Callable(_) | Forallable::Function(_),
+                        ..
+                    })
             ) && name == dunder::CALL
                 && let Some(want) = self.type_order.instance_as_dunder_call(&protocol)
             {
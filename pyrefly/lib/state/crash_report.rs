@@ -0,0 +1,282 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Blame panics in the checker on the module being checked: report them as an internal error in
+//! that module, and optionally write a bundle with everything needed to reproduce the crash.
+
+use std::backtrace::Backtrace;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::arc_id::ArcId;
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::panic::CrashContext;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::PySourceType;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_expr;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_python_parser::ParseOptions;
+use ruff_python_parser::TokenKind;
+use ruff_python_parser::parse_unchecked;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use serde_json::json;
+use starlark_map::small_map::SmallMap;
+use vec1::vec1;
+
+use crate::config::config::ConfigFile;
+use crate::config::error_kind::ErrorKind;
+use crate::error::collector::ErrorCollector;
+use crate::error::context::ErrorInfo;
+use crate::state::load::Load;
+use crate::state::steps::Step;
+
+/// Where to write crash reports, and whether to anonymize the source in them.
+struct CrashReportSettings {
+    dir: PathBuf,
+    anonymize: bool,
+}
+
+static CRASH_REPORT_SETTINGS: OnceLock<CrashReportSettings> = OnceLock::new();
+
+/// Write a crash report bundle into `dir` if the checker panics.
+pub fn enable_crash_reports(dir: PathBuf, anonymize: bool) {
+    let _ = CRASH_REPORT_SETTINGS.set(CrashReportSettings { dir, anonymize });
+}
+
+/// Record that computing `step` for `module` panicked with `message`.
+pub fn add_crash_error(errors: &ErrorCollector, module: ModuleName, step: Step, message: &str) {
+    errors.add(
+        TextRange::default(),
+        ErrorInfo::Kind(ErrorKind::InternalError),
+        vec1![format!(
+            "Pyrefly crashed while computing {step} for `{module}`: {message}"
+        )],
+    );
+}
+
+/// A step being computed for a module.
+pub struct ModuleCrashContext {
+    pub handle: Handle,
+    pub step: Step,
+    /// The loaded module, if we've got that far.
+    pub load: Option<Arc<Load>>,
+    pub config: ArcId<ConfigFile>,
+}
+
+impl CrashContext for ModuleCrashContext {
+    fn report_panic(&self, message: &str, backtrace: &Backtrace) {
+        // The panic is recorded as an error in the module once we've recovered, so here we only
+        // need to say how to report it.
+        eprintln!(
+            "Pyrefly crashed while computing {} for `{}`, this is always a bug in Pyrefly itself",
+            self.step,
+            self.handle.module(),
+        );
+        match CRASH_REPORT_SETTINGS.get() {
+            Some(settings) => match self.write_bundle(settings, message, backtrace) {
+                Ok(dir) => eprintln!(
+                    "Wrote a crash report to `{}`, please attach it to the bug report",
+                    dir.display()
+                ),
+                Err(error) => eprintln!("Failed to write a crash report: {error:#}"),
+            },
+            None => eprintln!(
+                "Rerun with `--crash-report <DIR>` to write a crash report to attach to the bug report"
+            ),
+        }
+    }
+}
+
+impl ModuleCrashContext {
+    fn write_bundle(
+        &self,
+        settings: &CrashReportSettings,
+        message: &str,
+        backtrace: &Backtrace,
+    ) -> anyhow::Result<PathBuf> {
+        let dir = settings
+            .dir
+            .join(format!("pyrefly-crash-{}", std::process::id()));
+        fs_anyhow::create_dir_all(&dir)?;
+
+        let path = self.handle.path().as_path();
+        // Notebooks are stored as the Python source of their cells, so always use a Python extension.
+        let source_name = if path.extension().is_some_and(|x| x == "pyi") {
+            "source.pyi"
+        } else {
+            "source.py"
+        };
+        if let Some(load) = &self.load {
+            let contents = load.module_info.contents();
+            let contents = if settings.anonymize {
+                anonymize(contents)
+            } else {
+                contents.as_str().to_owned()
+            };
+            fs_anyhow::write(&dir.join(source_name), contents)?;
+        }
+
+        let config =
+            toml::to_string(&*self.config).unwrap_or_else(|_| format!("{:#?}", self.config));
+        fs_anyhow::write(&dir.join("config.toml"), config)?;
+
+        let sys_info = self.handle.sys_info();
+        let report = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "message": message,
+            "module": self.handle.module().as_str(),
+            // The path may identify the user's project, so leave it out if anonymizing.
+            "path": if settings.anonymize {
+                None
+            } else {
+                Some(path.display().to_string())
+            },
+            "source": self.load.is_some().then_some(source_name),
+            "anonymized": settings.anonymize,
+            "step": self.step.to_string(),
+            "python_version": sys_info.version().to_string(),
+            "python_platform": sys_info.platform().to_string(),
+            "backtrace": backtrace.to_string(),
+        });
+        fs_anyhow::write(
+            &dir.join("crash.json"),
+            serde_json::to_string_pretty(&report)?,
+        )?;
+        Ok(dir)
+    }
+}
+
+/// Collects the names the module defines, which are the ones that might reveal what it is about.
+#[derive(Default)]
+struct DefinedNames<'a>(SmallMap<&'a str, String>);
+
+impl<'a> DefinedNames<'a> {
+    fn add(&mut self, name: &'a str) {
+        // Dunder names and `self`/`cls` have meaning to the type checker, so keep them.
+        if (name.starts_with("__") && name.ends_with("__")) || name == "self" || name == "cls" {
+            return;
+        }
+        let n = self.0.len();
+        self.0.entry(name).or_insert_with(|| format!("name{n}"));
+    }
+}
+
+impl<'a> SourceOrderVisitor<'a> for DefinedNames<'a> {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        match x {
+            Stmt::FunctionDef(x) => {
+                self.add(x.name.as_str());
+                for p in x.parameters.iter() {
+                    self.add(p.name().as_str());
+                }
+            }
+            Stmt::ClassDef(x) => self.add(x.name.as_str()),
+            _ => {}
+        }
+        walk_stmt(self, x);
+    }
+
+    fn visit_expr(&mut self, x: &'a Expr) {
+        if let Expr::Name(x) = x
+            && x.ctx == ExprContext::Store
+        {
+            self.add(x.id.as_str());
+        }
+        walk_expr(self, x);
+    }
+}
+
+/// Replace every letter and digit in a string with `x`, along with any escape sequences, which
+/// might otherwise become invalid.
+fn scrub(x: &str) -> String {
+    let mut res = String::with_capacity(x.len());
+    let mut chars = x.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+            res.push('x');
+        } else if c.is_alphanumeric() {
+            res.push('x');
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Remove anything identifying from the source, while keeping its structure, so that it usually
+/// still reproduces a crash: comments and the contents of strings are blanked out, and the names
+/// the module defines are renamed consistently.
+fn anonymize(source: &str) -> String {
+    let parsed = parse_unchecked(source, ParseOptions::from(PySourceType::Python))
+        .try_into_module()
+        .unwrap();
+    let mut names = DefinedNames::default();
+    names.visit_body(&parsed.syntax().body);
+
+    let mut res = String::with_capacity(source.len());
+    let mut last = 0;
+    for token in parsed.tokens().iter() {
+        let range = token.range();
+        let text = &source[range];
+        let replacement = match token.kind() {
+            TokenKind::Comment => Some("#".to_owned()),
+            TokenKind::String => {
+                // Keep the prefix, e.g. `b` or `r`, since it changes the type of the literal.
+                let quote = text.find(['\'', '"']).unwrap_or(0);
+                Some(format!("{}{}", &text[..quote], scrub(&text[quote..])))
+            }
+            TokenKind::FStringMiddle => Some(scrub(text)),
+            TokenKind::Name => names.0.get(text).cloned(),
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            res.push_str(&source[last..range.start().to_usize()]);
+            res.push_str(&replacement);
+            last = range.end().to_usize();
+        }
+    }
+    res.push_str(&source[last..]);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymize() {
+        let source = r#"
+# secret plans
+def launch(target: str, count: int = 3) -> None:
+    codes = b"abc\n123"
+    print(f"launching {target}!", count, codes)
+
+class Rocket:
+    def __init__(self, name):
+        self.name = name
+"#;
+        let expected = r#"
+#
+def name0(name1: str, name2: int = 3) -> None:
+    name3 = b"xxxxxxx"
+    print(f"xxxxxxxxx {name1}!", name2, name3)
+
+class name4:
+    def __init__(self, name5):
+        self.name5 = name5
+"#;
+        assert_eq!(anonymize(source), expected);
+    }
+}
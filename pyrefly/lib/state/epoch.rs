@@ -18,6 +18,11 @@ impl Epoch {
     pub fn next(&mut self) {
         self.0 += 1;
     }
+
+    /// The number of epochs that have passed between `earlier` and `self`.
+    pub fn since(self, earlier: Epoch) -> u32 {
+        self.0.saturating_sub(earlier.0)
+    }
 }
 
 /// Invariant: checked >= computed >= changed
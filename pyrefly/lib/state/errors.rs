@@ -13,6 +13,7 @@ use pyrefly_python::ignore::Ignore;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::arc_id::ArcId;
 use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
 
 use crate::config::config::ConfigFile;
 use crate::error::baseline::BaselineProcessor;
@@ -56,6 +57,22 @@ impl Errors {
         errors
     }
 
+    /// The modules whose errors are treated specially because they are generated code, so we
+    /// shouldn't edit them when suppressing errors.
+    pub fn generated_code_paths(&self) -> SmallSet<&ModulePath> {
+        self.loads
+            .iter()
+            .filter(|(load, config)| {
+                let module_info = &load.module_info;
+                config
+                    .get_error_config(module_info.path().as_path())
+                    .generated_code
+                    .applies_to(module_info.contents())
+            })
+            .map(|(load, _)| load.module_info.path())
+            .collect()
+    }
+
     pub fn collect_ignores(&self) -> SmallMap<&ModulePath, &Ignore> {
         let mut ignore_collection: SmallMap<&ModulePath, &Ignore> = SmallMap::new();
         for (load, _) in &self.loads {
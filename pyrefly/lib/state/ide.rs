@@ -6,7 +6,9 @@
  */
 
 use pyrefly_build::handle::Handle;
+use pyrefly_config::config::ModuleOrigin;
 use pyrefly_config::finder::ConfigFinder;
+use pyrefly_python::ast::Ast;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePathDetails;
 use pyrefly_python::short_identifier::ShortIdentifier;
@@ -14,6 +16,7 @@ use pyrefly_python::symbol_kind::SymbolKind;
 use pyrefly_util::gas::Gas;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
 use ruff_python_ast::helpers::is_docstring_stmt;
 use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
@@ -21,6 +24,7 @@ use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
 use crate::binding::binding::Binding;
+use crate::binding::binding::BindingAnnotation;
 use crate::binding::binding::BindingClass;
 use crate::binding::binding::ClassBinding;
 use crate::binding::binding::Key;
@@ -163,6 +167,21 @@ fn create_intermediate_definition_from(
                     })),
                 };
             }
+            // A `Final` name can't be reassigned, so is a constant whatever its name.
+            Binding::NameAssign(_, Some((_, annotation)), ..)
+                if matches!(
+                    bindings.get(*annotation),
+                    BindingAnnotation::AnnotateExpr(_, x, _) if Ast::is_final_annotation(x)
+                ) =>
+            {
+                return Some(IntermediateDefinition::Local(Export {
+                    location: def_key.range(),
+                    symbol_kind: Some(SymbolKind::Constant),
+                    docstring_range: None,
+                    is_deprecated: false,
+                    special_export: None,
+                }));
+            }
             _ => {
                 return Some(IntermediateDefinition::Local(Export {
                     location: def_key.range(),
@@ -177,14 +196,87 @@ fn create_intermediate_definition_from(
     None
 }
 
-pub fn insert_import_edit(
+/// The blocks imports are grouped into at the top of a module, in the order they appear, as
+/// sorted by isort and ruff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ImportSection {
+    Future,
+    Stdlib,
+    ThirdParty,
+    FirstParty,
+}
+
+impl ImportSection {
+    /// The section an import of `handle` belongs to.
+    pub fn of(config_finder: &ConfigFinder, handle: &Handle) -> Self {
+        if handle.module().as_str() == "__future__" {
+            return Self::Future;
+        }
+        match module_origin(config_finder, handle) {
+            ModuleOrigin::Stdlib => Self::Stdlib,
+            ModuleOrigin::ThirdParty => Self::ThirdParty,
+            ModuleOrigin::FirstParty | ModuleOrigin::Generated => Self::FirstParty,
+        }
+    }
+}
+
+/// Where the module of `handle` comes from, according to its config.
+pub fn module_origin(config_finder: &ConfigFinder, handle: &Handle) -> ModuleOrigin {
+    config_finder
+        .python_file(handle.module(), handle.path())
+        .module_origin(handle.module(), handle.path())
+}
+
+/// The position before the first statement that isn't a docstring.
+pub fn top_of_module_position(ast: &ModModule) -> TextSize {
+    if let Some(first_stmt) = ast.body.iter().find(|stmt| !is_docstring_stmt(stmt)) {
+        first_stmt.range().start()
+    } else {
+        ast.range.end()
+    }
+}
+
+/// The imports at the top of a module, before any other statement, with their sections.
+/// `section_of` gives the section of an import statement, and `None` for other statements.
+pub fn top_imports(
+    ast: &ModModule,
+    section_of: impl Fn(&Stmt) -> Option<ImportSection>,
+) -> Vec<(&Stmt, ImportSection)> {
+    ast.body
+        .iter()
+        .skip_while(|stmt| is_docstring_stmt(stmt))
+        .map_while(|stmt| Some((stmt, section_of(stmt)?)))
+        .collect()
+}
+
+/// Where to insert an import in `section`, so it lands in the block of imports it belongs to:
+/// on the line after the last of `top_imports` in that section or an earlier one, or else at the
+/// top of the module.
+pub fn import_position(
     ast: &ModModule,
+    contents: &str,
+    top_imports: &[(&Stmt, ImportSection)],
+    section: ImportSection,
+) -> TextSize {
+    let Some((stmt, _)) = top_imports.iter().rev().find(|(_, x)| *x <= section) else {
+        return top_of_module_position(ast);
+    };
+    let end = stmt.range().end();
+    match contents[end.to_usize()..].find('\n') {
+        Some(newline) => end + TextSize::new(newline as u32 + 1),
+        // The import is on the last line, with no newline to insert after.
+        None => stmt.range().start(),
+    }
+}
+
+/// The line importing `export_name` from `handle_to_import_from`.
+pub fn insert_import_edit(
     config_finder: &ConfigFinder,
     handle_to_insert_import: Handle,
     handle_to_import_from: Handle,
     export_name: &str,
     import_format: ImportFormat,
-) -> (TextSize, String) {
+) -> String {
     let use_absolute_import = match import_format {
         ImportFormat::Absolute => true,
         ImportFormat::Relative => {
@@ -192,7 +284,6 @@ pub fn insert_import_edit(
         }
     };
     insert_import_edit_with_forced_import_format(
-        ast,
         handle_to_insert_import,
         handle_to_import_from,
         export_name,
@@ -200,33 +291,18 @@ pub fn insert_import_edit(
     )
 }
 
-/// Insert `import <>` import at the top of the file
-pub fn import_regular_import_edit(
-    ast: &ModModule,
-    handle_to_import_from: Handle,
-) -> (TextSize, String) {
-    let position = if let Some(first_stmt) = ast.body.iter().find(|stmt| !is_docstring_stmt(stmt)) {
-        first_stmt.range().start()
-    } else {
-        ast.range.end()
-    };
+/// The line importing `handle_to_import_from` with `import <>`.
+pub fn import_regular_import_edit(handle_to_import_from: Handle) -> String {
     let module_name_to_import = handle_to_import_from.module();
-    let insert_text = format!("import {}\n", module_name_to_import.as_str());
-    (position, insert_text)
+    format!("import {}\n", module_name_to_import.as_str())
 }
 
 pub fn insert_import_edit_with_forced_import_format(
-    ast: &ModModule,
     handle_to_insert_import: Handle,
     handle_to_import_from: Handle,
     export_name: &str,
     use_absolute_import: bool,
-) -> (TextSize, String) {
-    let position = if let Some(first_stmt) = ast.body.iter().find(|stmt| !is_docstring_stmt(stmt)) {
-        first_stmt.range().start()
-    } else {
-        ast.range.end()
-    };
+) -> String {
     let module_name_to_import = if use_absolute_import {
         handle_to_import_from.module()
     } else if let Some(relative_module) = ModuleName::relative_module_name_between(
@@ -237,12 +313,11 @@ pub fn insert_import_edit_with_forced_import_format(
     } else {
         handle_to_import_from.module()
     };
-    let insert_text = format!(
+    format!(
         "from {} import {}\n",
         module_name_to_import.as_str(),
         export_name
-    );
-    (position, insert_text)
+    )
 }
 
 /// Some handles must be imported in absolute style,
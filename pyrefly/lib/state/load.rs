@@ -111,6 +111,8 @@ impl Load {
                     module_info.path()
                 )],
             );
+        } else {
+            check_py_typed(&module_info, &errors);
         }
         Self {
             errors,
@@ -118,3 +120,25 @@ impl Load {
         }
     }
 }
+
+/// Report a public top-level package without a `py.typed` marker, since type checkers will ignore
+/// its annotations once it is installed.
+fn check_py_typed(module_info: &Module, errors: &ErrorCollector) {
+    let name = module_info.name();
+    if let ModulePathDetails::FileSystem(path) = module_info.path().details()
+        && module_info.path().is_init()
+        && !module_info.path().is_interface()
+        && !name.as_str().contains('.')
+        && !name.as_str().starts_with('_')
+        && let Some(dir) = path.parent()
+        && !dir.join("py.typed").exists()
+    {
+        errors.add(
+            TextRange::default(),
+            ErrorInfo::Kind(ErrorKind::MissingPyTyped),
+            vec1![format!(
+                "Package `{name}` has no `py.typed` marker, so type checkers will ignore its annotations once it is installed"
+            )],
+        );
+    }
+}
@@ -6,6 +6,7 @@
  */
 
 use std::fmt::Debug;
+use std::path::Path;
 use std::sync::Arc;
 
 use dupe::Dupe;
@@ -79,6 +80,17 @@ impl FindError {
         FindError::NotFound(module, Arc::new(explanation))
     }
 
+    pub fn not_in_dependency_manifest(module: ModuleName, manifest: &Path) -> Self {
+        Self::NotFound(
+            module,
+            Arc::new(vec1![format!(
+                "`{}` is not listed in the dependency manifest `{}`",
+                module.first_component(),
+                manifest.display()
+            )]),
+        )
+    }
+
     pub fn display(&self) -> (Option<Box<dyn Fn() -> ErrorContext + '_>>, Vec1<String>) {
         match self {
             Self::NotFound(module, err) => (
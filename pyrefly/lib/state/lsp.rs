@@ -5,8 +5,11 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cmp::Reverse;
+use std::iter;
 use std::sync::Arc;
 
+use clap::ValueEnum;
 use dupe::Dupe;
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
@@ -14,6 +17,7 @@ use itertools::Itertools;
 use lsp_types::CompletionItem;
 use lsp_types::CompletionItemKind;
 use lsp_types::CompletionItemTag;
+use lsp_types::DocumentHighlightKind;
 use lsp_types::DocumentSymbol;
 use lsp_types::ParameterInformation;
 use lsp_types::ParameterLabel;
@@ -22,9 +26,11 @@ use lsp_types::SignatureHelp;
 use lsp_types::SignatureInformation;
 use lsp_types::TextEdit;
 use pyrefly_build::handle::Handle;
+use pyrefly_config::config::ModuleOrigin;
 use pyrefly_python::ast::Ast;
 use pyrefly_python::docstring::Docstring;
 use pyrefly_python::dunder;
+use pyrefly_python::fully_qualified_name::FullyQualifiedName;
 use pyrefly_python::keywords::get_keywords;
 use pyrefly_python::module::Module;
 use pyrefly_python::module::TextRangeWithModule;
@@ -41,6 +47,7 @@ use pyrefly_util::task_heap::Cancelled;
 use pyrefly_util::visit::Visit;
 use ruff_python_ast::Alias;
 use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ArgOrKeyword;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ExprAttribute;
 use ruff_python_ast::ExprCall;
@@ -61,6 +68,7 @@ use ruff_text_size::TextSize;
 use serde::Deserialize;
 use starlark_map::ordered_set::OrderedSet;
 use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
 
 use crate::alt::attr::AttrDefinition;
 use crate::alt::attr::AttrInfo;
@@ -71,31 +79,64 @@ use crate::config::error_kind::ErrorKind;
 use crate::export::exports::Export;
 use crate::export::exports::ExportLocation;
 use crate::graph::index::Idx;
+use crate::state::ide::ImportSection;
 use crate::state::ide::IntermediateDefinition;
+use crate::state::ide::import_position;
 use crate::state::ide::import_regular_import_edit;
 use crate::state::ide::insert_import_edit;
 use crate::state::ide::key_to_intermediate_definition;
+use crate::state::ide::module_origin;
+use crate::state::ide::top_imports;
+use crate::state::lsp::document_highlight::receiver_attribute_references;
+use crate::state::lsp::document_highlight::write_ranges;
+use crate::state::lsp::dynamic_attributes::DynamicAttributes;
+use crate::state::lsp::dynamic_attributes::find_dynamic_attributes;
+use crate::state::lsp::quick_fixes::none_guard::none_attribute_fixes;
+use crate::state::lsp::stub_source::DefinitionPreference;
+use crate::state::lsp::stub_source::definition_docstring;
+use crate::state::lsp::stub_source::map_stub_definition;
 use crate::state::require::Require;
 use crate::state::semantic_tokens::SemanticTokenBuilder;
 use crate::state::semantic_tokens::SemanticTokensLegends;
+use crate::state::semantic_tokens::async_function_names;
 use crate::state::semantic_tokens::disabled_ranges_for_module;
 use crate::state::state::CancellableTransaction;
 use crate::state::state::Transaction;
 use crate::types::callable::Param;
 use crate::types::callable::Params;
 use crate::types::module::ModuleType;
+use crate::types::types::Forall;
+use crate::types::types::Forallable;
 use crate::types::types::Type;
 
+pub mod call_hierarchy;
+pub mod document_highlight;
+pub mod document_links;
+pub mod dynamic_attributes;
+pub mod inlay_hint_imports;
+pub mod move_symbol;
+pub mod override_completions;
+pub mod quick_fixes;
+pub mod selection_range;
+pub mod stub_source;
+pub mod type_hierarchy;
+pub mod workspace_symbols;
+
 fn default_true() -> bool {
     true
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+/// Which call arguments get a hint naming their parameter.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, ValueEnum)]
 #[serde(rename_all = "camelCase")]
 pub enum AllOffPartial {
+    /// Every positional argument.
     All,
+    /// No arguments.
     #[default]
     Off,
+    /// Positional arguments that don't already name their parameter, as in `f(x)` or
+    /// `f(self.x)` for a parameter `x`, and aren't for positional-only parameters.
     Partial,
 }
 
@@ -111,6 +152,10 @@ pub struct InlayHintConfig {
     pub pytest_parameters: bool,
     #[serde(default = "default_true")]
     pub variable_types: bool,
+    /// Hints longer than this many characters are truncated, ending with `…`. Hovering the hint
+    /// shows all of it, and inserting it inserts all of it.
+    #[serde(default)]
+    pub max_length: Option<usize>,
 }
 
 impl Default for InlayHintConfig {
@@ -120,6 +165,21 @@ impl Default for InlayHintConfig {
             function_return_types: true,
             pytest_parameters: false,
             variable_types: true,
+            max_length: None,
+        }
+    }
+}
+
+impl InlayHintConfig {
+    /// The label to show for a hint, truncated to `max_length` characters.
+    pub fn label(&self, hint: &str) -> String {
+        match self.max_length {
+            Some(max_length) if hint.chars().count() > max_length => {
+                let mut label: String = hint.chars().take(max_length.saturating_sub(1)).collect();
+                label.push('…');
+                label
+            }
+            _ => hint.to_owned(),
         }
     }
 }
@@ -286,6 +346,28 @@ struct IdentifierWithContext {
     context: IdentifierContext,
 }
 
+/// How an argument of a call is matched against the parameters of the callee.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ArgumentSlot {
+    /// The positional argument at this index.
+    Positional(usize),
+    /// The keyword argument with this name.
+    Keyword(Name),
+    /// An argument like `*args` or `**kwargs`, which may fill any number of parameters.
+    Unpacked,
+}
+
+/// The innermost call whose arguments contain a position, as seen by signature help.
+struct CallAtPosition {
+    callee_range: TextRange,
+    arguments_range: TextRange,
+    /// The arguments other than the one the position is in.
+    arguments: Vec<ArgumentSlot>,
+    /// The argument the position is in, or the next positional argument if it isn't in any and
+    /// there are no keyword arguments yet, in which case the next argument must be one too.
+    active: Option<ArgumentSlot>,
+}
+
 #[derive(PartialEq, Eq)]
 pub enum AnnotationKind {
     #[allow(dead_code)]
@@ -475,7 +557,7 @@ pub struct FindDefinitionItem {
 }
 
 impl<'a> Transaction<'a> {
-    fn get_type(&self, handle: &Handle, key: &Key) -> Option<Type> {
+    pub fn get_type(&self, handle: &Handle, key: &Key) -> Option<Type> {
         let idx = self.get_bindings(handle)?.key_to_idx(key);
         let answers = self.get_answers(handle)?;
         answers.get_type_at(idx)
@@ -703,6 +785,29 @@ impl<'a> Transaction<'a> {
             .cloned()
     }
 
+    /// The key for the name at `position`, either the definition it introduces, or the binding
+    /// it reads.
+    pub fn key_at(&self, handle: &Handle, position: TextSize) -> Option<Key> {
+        if let Some(key) = self.definition_at(handle, position) {
+            return Some(key);
+        }
+        match self.identifier_at(handle, position)? {
+            IdentifierWithContext {
+                identifier: id,
+                context: IdentifierContext::Expr(expr_context),
+            } => {
+                let key = match expr_context {
+                    ExprContext::Store => Key::Definition(ShortIdentifier::new(&id)),
+                    ExprContext::Load | ExprContext::Del | ExprContext::Invalid => {
+                        Key::BoundName(ShortIdentifier::new(&id))
+                    }
+                };
+                self.get_bindings(handle)?.is_valid_key(&key).then_some(key)
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_type_at(&self, handle: &Handle, position: TextSize) -> Option<Type> {
         // TODO(grievejia): Remove the usage of `definition_at()`: it doesn't reliably detect all
         // definitions.
@@ -841,59 +946,120 @@ impl<'a> Transaction<'a> {
         }
     }
 
-    fn visit_finding_signature_range(
-        x: &Expr,
-        find: TextSize,
-        res: &mut Option<(TextRange, TextRange, usize)>,
-    ) {
+    fn visit_finding_signature_range(x: &Expr, find: TextSize, res: &mut Option<CallAtPosition>) {
         if let Expr::Call(call) = x
             && call.arguments.range.contains_inclusive(find)
         {
-            for (i, arg) in call.arguments.args.as_ref().iter().enumerate() {
-                if arg.range().contains_inclusive(find) {
-                    Self::visit_finding_signature_range(arg, find, res);
+            let mut arguments = Vec::new();
+            let mut active = None;
+            let mut positional = 0;
+            for arg in call.arguments.arguments_source_order() {
+                let (slot, value, range) = match arg {
+                    ArgOrKeyword::Arg(value @ Expr::Starred(_)) => {
+                        (ArgumentSlot::Unpacked, value, value.range())
+                    }
+                    ArgOrKeyword::Arg(value) => {
+                        positional += 1;
+                        (
+                            ArgumentSlot::Positional(positional - 1),
+                            value,
+                            value.range(),
+                        )
+                    }
+                    ArgOrKeyword::Keyword(keyword) => (
+                        match &keyword.arg {
+                            Some(name) => ArgumentSlot::Keyword(name.id.clone()),
+                            None => ArgumentSlot::Unpacked,
+                        },
+                        &keyword.value,
+                        keyword.range,
+                    ),
+                };
+                if active.is_none() && range.contains_inclusive(find) {
+                    Self::visit_finding_signature_range(value, find, res);
                     if res.is_some() {
                         return;
                     }
-                    *res = Some((call.func.range(), call.arguments.range, i));
+                    active = Some(slot);
+                } else {
+                    arguments.push(slot);
                 }
             }
-            if res.is_none() {
-                *res = Some((
-                    call.func.range(),
-                    call.arguments.range,
-                    call.arguments.len(),
-                ));
+            if active.is_none() && call.arguments.keywords.is_empty() {
+                active = Some(ArgumentSlot::Positional(positional));
             }
+            *res = Some(CallAtPosition {
+                callee_range: call.func.range(),
+                arguments_range: call.arguments.range,
+                arguments,
+                active,
+            });
         } else {
             x.recurse(&mut |x| Self::visit_finding_signature_range(x, find, res));
         }
     }
 
-    /// Finds the callable(s) (multiple if overloads exist) at position in document, returning them, chosen overload index, and arg index
+    /// The index of the parameter among `params` that `slot` is passed to, if any.
+    fn parameter_for_argument(params: &[Param], slot: &ArgumentSlot) -> Option<usize> {
+        match slot {
+            ArgumentSlot::Positional(i) => params
+                .iter()
+                .positions(|p| matches!(p, Param::PosOnly(..) | Param::Pos(..)))
+                .nth(*i)
+                .or_else(|| params.iter().position(|p| matches!(p, Param::VarArg(..)))),
+            ArgumentSlot::Keyword(name) => params
+                .iter()
+                .position(|p| matches!(p, Param::Pos(x, ..) | Param::KwOnly(x, ..) if x == name))
+                .or_else(|| params.iter().position(|p| matches!(p, Param::Kwargs(..)))),
+            ArgumentSlot::Unpacked => None,
+        }
+    }
+
+    /// Whether every argument of `call`, including the active one, has a parameter of `callable`
+    /// to be passed to. Callables whose parameters aren't known accept anything.
+    fn accepts_arguments(callable: &Type, call: &CallAtPosition) -> bool {
+        let Some(params) = Self::normalize_singleton_function_type_into_params(callable.clone())
+        else {
+            return true;
+        };
+        call.arguments.iter().chain(&call.active).all(|slot| {
+            *slot == ArgumentSlot::Unpacked || Self::parameter_for_argument(&params, slot).is_some()
+        })
+    }
+
+    /// Finds the callable(s) (multiple if overloads exist) at position in document, returning
+    /// them, the index of the one being called, and the argument at position. Among overloads,
+    /// the one the type checker picked is kept if it has a parameter for every argument written
+    /// so far, and otherwise the first one that does is taken, so that e.g. a keyword argument
+    /// only one overload accepts selects it even before the call type checks.
     fn get_callables_from_call(
         &self,
         handle: &Handle,
         position: TextSize,
-    ) -> Option<(Vec<Type>, usize, usize)> {
+    ) -> Option<(Vec<Type>, usize, Option<ArgumentSlot>)> {
         let mod_module = self.get_ast(handle)?;
         let mut res = None;
         mod_module.visit(&mut |x| Self::visit_finding_signature_range(x, position, &mut res));
-        let (callee_range, call_args_range, arg_index) = res?;
+        let call = res?;
         let answers = self.get_answers(handle)?;
         if let Some((overloads, chosen_overload_index)) =
-            answers.get_all_overload_trace(call_args_range)
+            answers.get_all_overload_trace(call.arguments_range)
         {
             let callables = overloads.into_map(|callable| Type::Callable(Box::new(callable)));
-            Some((
-                callables,
-                chosen_overload_index.unwrap_or_default(),
-                arg_index,
-            ))
+            let chosen_overload_index = chosen_overload_index
+                .filter(|i| Self::accepts_arguments(&callables[*i], &call))
+                .or_else(|| {
+                    callables
+                        .iter()
+                        .position(|callable| Self::accepts_arguments(callable, &call))
+                })
+                .or(chosen_overload_index)
+                .unwrap_or_default();
+            Some((callables, chosen_overload_index, call.active))
         } else {
             answers
-                .get_type_trace(callee_range)
-                .map(|t| (vec![t], 0, arg_index))
+                .get_type_trace(call.callee_range)
+                .map(|t| (vec![t], 0, call.active))
         }
     }
 
@@ -903,27 +1069,32 @@ impl<'a> Transaction<'a> {
         position: TextSize,
     ) -> Option<SignatureHelp> {
         self.get_callables_from_call(handle, position).map(
-            |(callables, chosen_overload_index, arg_index)| SignatureHelp {
-                signatures: callables
-                    .into_iter()
-                    .map(|t| Self::create_signature_information(t, arg_index))
-                    .collect_vec(),
-                active_signature: Some(chosen_overload_index as u32),
-                active_parameter: Some(arg_index as u32),
+            |(callables, chosen_overload_index, active_argument)| {
+                let signatures = callables
+                    .into_map(|t| Self::create_signature_information(t, active_argument.as_ref()));
+                let active_parameter = signatures
+                    .get(chosen_overload_index)
+                    .and_then(|x| x.active_parameter);
+                SignatureHelp {
+                    signatures,
+                    active_signature: Some(chosen_overload_index as u32),
+                    active_parameter,
+                }
             },
         )
     }
 
-    fn create_signature_information(type_: Type, arg_index: usize) -> SignatureInformation {
+    fn create_signature_information(
+        type_: Type,
+        active_argument: Option<&ArgumentSlot>,
+    ) -> SignatureInformation {
         let type_ = type_.deterministic_printing();
         let label = type_.as_hover_string();
         let (parameters, active_parameter) =
             if let Some(params) = Self::normalize_singleton_function_type_into_params(type_) {
-                let active_parameter = if arg_index < params.len() {
-                    Some(arg_index as u32)
-                } else {
-                    None
-                };
+                let active_parameter = active_argument
+                    .and_then(|slot| Self::parameter_for_argument(&params, slot))
+                    .map(|i| i as u32);
                 (
                     Some(params.map(|param| ParameterInformation {
                         label: ParameterLabel::Simple(format!("{param}")),
@@ -1405,6 +1576,28 @@ impl<'a> Transaction<'a> {
             .collect()
     }
 
+    /// The fully qualified name of the symbol whose name is defined at `range` of `module`, where
+    /// `handle` is the module we found the definition from. Modules have no name of that form.
+    pub fn fully_qualified_name(
+        &self,
+        handle: &Handle,
+        module: &Module,
+        range: TextRange,
+    ) -> Option<FullyQualifiedName> {
+        if range.is_empty() {
+            return None;
+        }
+        let definition_handle = Handle::new(
+            module.name(),
+            module.path().dupe(),
+            handle.sys_info().dupe(),
+        );
+        let ast = self
+            .get_ast(&definition_handle)
+            .unwrap_or_else(|| Arc::new(Ast::parse(module.contents()).0));
+        Some(FullyQualifiedName::of_definition(module, &ast, range))
+    }
+
     /// Find the definition, metadata and optionally the docstring for the given position.
     pub fn find_definition(
         &self,
@@ -1552,31 +1745,125 @@ impl<'a> Transaction<'a> {
     }
 
     pub fn goto_definition(&self, handle: &Handle, position: TextSize) -> Vec<TextRangeWithModule> {
-        let mut definitions = self.find_definition(
-            handle,
-            position,
-            &FindPreference {
-                prefer_pyi: false,
-                ..Default::default()
-            },
-        );
-        // Add pyi definitions if we haven't found any py definition
-        if definitions.is_empty() {
-            definitions.append(&mut self.find_definition(
+        self.goto_definition_with_preference(handle, position, DefinitionPreference::default())
+    }
+
+    /// Find the definitions of the symbol at `position`. When a definition is in a `.pyi` stub and
+    /// the `.py` source it describes can be found, both are returned, in the order given by
+    /// `preference`.
+    pub fn goto_definition_with_preference(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        preference: DefinitionPreference,
+    ) -> Vec<TextRangeWithModule> {
+        let find = |prefer_pyi| {
+            self.find_definition(
                 handle,
                 position,
-                &FindPreference::default(),
-            ));
+                &FindPreference {
+                    prefer_pyi,
+                    ..Default::default()
+                },
+            )
+        };
+        let prefer_pyi = preference == DefinitionPreference::Stub;
+        let mut definitions = find(prefer_pyi);
+        // Fall back to the other kind of file if we haven't found any definition
+        if definitions.is_empty() {
+            definitions = find(!prefer_pyi);
+        }
+
+        let mut res = Vec::new();
+        for item in definitions {
+            let definition = TextRangeWithModule::new(item.module, item.definition_range);
+            match self.stub_definition_source(handle, &definition) {
+                Some(source) if preference == DefinitionPreference::Source => {
+                    res.push(source);
+                    res.push(definition);
+                }
+                Some(source) => {
+                    res.push(definition);
+                    res.push(source);
+                }
+                None => res.push(definition),
+            }
+        }
+        res
+    }
+
+    /// If `definition` is in a `.pyi` stub, find the same definition in the `.py` source the stub
+    /// describes, if there is one.
+    fn stub_definition_source(
+        &self,
+        handle: &Handle,
+        definition: &TextRangeWithModule,
+    ) -> Option<TextRangeWithModule> {
+        if !definition.module.path().is_interface() {
+            return None;
+        }
+        let source = self
+            .import_handle_prefer_executable(handle, definition.module.name(), None)
+            .finding()?;
+        if source.path().is_interface() {
+            return None;
         }
+        // Computing the exports loads the module, if it isn't loaded already.
+        self.get_exports(&source);
+        let source_info = self.get_module_info(&source)?;
+        let range = map_stub_definition(&definition.module, definition.range, &source_info)?;
+        Some(TextRangeWithModule::new(source_info, range))
+    }
 
-        definitions.into_map(|item| TextRangeWithModule::new(item.module, item.definition_range))
+    /// If the definition at `range` of `module` is in a `.pyi` stub, find its docstring in the
+    /// `.py` source the stub describes, since stubs usually leave docstrings out.
+    pub fn stub_definition_source_docstring(
+        &self,
+        handle: &Handle,
+        module: &Module,
+        range: TextRange,
+    ) -> Option<Docstring> {
+        let source =
+            self.stub_definition_source(handle, &TextRangeWithModule::new(module.dupe(), range))?;
+        let docstring = definition_docstring(&source.module, source.range)?;
+        Some(Docstring(docstring, source.module))
     }
 
+    /// Like go-to-definition, except that a name standing for a type, such as a type alias, jumps
+    /// to the classes that type is made of. An alias of another alias stores the type it resolves
+    /// to, so the whole chain is followed at once.
     pub fn goto_type_definition(
         &self,
         handle: &Handle,
         position: TextSize,
     ) -> Vec<TextRangeWithModule> {
+        let aliased = match self.get_type_at(handle, position) {
+            Some(Type::TypeAlias(ta)) => Some(ta.as_type()),
+            Some(Type::Forall(box Forall {
+                body: Forallable::TypeAlias(ta),
+                ..
+            })) => Some(ta.as_type()),
+            // A variable assigned a class is an implicit alias of it.
+            Some(ty @ Type::ClassDef(_)) => Some(ty),
+            _ => None,
+        };
+        if let Some(aliased) = aliased {
+            let mut res = Vec::new();
+            aliased.universe(&mut |ty| {
+                let qname = match ty {
+                    Type::ClassType(cls) => cls.qname(),
+                    Type::ClassDef(cls) => cls.qname(),
+                    _ => return,
+                };
+                let definition = TextRangeWithModule::new(qname.module().dupe(), qname.range());
+                if !res.contains(&definition) {
+                    res.push(definition);
+                }
+            });
+            if !res.is_empty() {
+                return res;
+            }
+        }
         self.find_definition(handle, position, &FindPreference::default())
             .into_map(|item| TextRangeWithModule::new(item.module, item.definition_range))
     }
@@ -1613,15 +1900,24 @@ impl<'a> Transaction<'a> {
         let ast = self.get_ast(handle)?;
         let errors = self.get_errors(vec![handle]).collect_errors().shown;
         let mut code_actions = Vec::new();
+        if let Some(fix) = self.insert_missing_items_fix(handle, &module_info, &errors, range) {
+            code_actions.push(fix);
+        }
         for error in errors {
             match error.error_kind() {
                 ErrorKind::UnknownName => {
                     let error_range = error.range();
                     if error_range.contains_range(range) {
                         let unknown_name = module_info.code_at(error_range);
-                        for handle_to_import_from in self.search_exports_exact(unknown_name) {
-                            let (position, insert_text) = insert_import_edit(
+                        let top_imports = self.top_imports(handle, &ast);
+                        for handle_to_import_from in self.import_candidates(handle, unknown_name) {
+                            let position = self.import_position(
                                 &ast,
+                                &module_info,
+                                &top_imports,
+                                &handle_to_import_from,
+                            );
+                            let insert_text = insert_import_edit(
                                 self.config_finder(),
                                 handle.dupe(),
                                 handle_to_import_from,
@@ -1640,8 +1936,13 @@ impl<'a> Transaction<'a> {
                             if let Some(module_handle) =
                                 self.import_handle(handle, module_name, None).finding()
                             {
-                                let (position, insert_text) =
-                                    import_regular_import_edit(&ast, module_handle);
+                                let position = self.import_position(
+                                    &ast,
+                                    &module_info,
+                                    &top_imports,
+                                    &module_handle,
+                                );
+                                let insert_text = import_regular_import_edit(module_handle);
                                 let range = TextRange::at(position, TextSize::new(0));
                                 let title = format!("Insert import: `{}`", insert_text.trim());
                                 code_actions.push((title, module_info.dupe(), range, insert_text));
@@ -1649,6 +1950,18 @@ impl<'a> Transaction<'a> {
                         }
                     }
                 }
+                ErrorKind::MissingAttribute
+                    if error.range().contains_range(range)
+                        && error.msg().lines().any(|line| {
+                            line.starts_with("Object of class `NoneType` has no attribute")
+                        }) =>
+                {
+                    for fix in none_attribute_fixes(&module_info, &ast, error.range()) {
+                        if !code_actions.contains(&fix) {
+                            code_actions.push(fix);
+                        }
+                    }
+                }
                 _ => {}
             }
         }
@@ -1688,6 +2001,34 @@ impl<'a> Transaction<'a> {
         .concat()
     }
 
+    /// The occurrences of the symbol at `position` in this file, and whether each one reads or
+    /// writes it. For an attribute of `self`, this includes every `self.x` in the methods of the
+    /// class, even if the type checker can't resolve the attribute.
+    pub fn document_highlights(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+    ) -> Vec<(TextRange, DocumentHighlightKind)> {
+        let mut references = self.find_local_references(handle, position);
+        let Some(ast) = self.get_ast(handle) else {
+            return references.into_map(|range| (range, DocumentHighlightKind::TEXT));
+        };
+        if let Some(attributes) = receiver_attribute_references(&ast, position) {
+            references.extend(attributes);
+            references.sort_by_key(|range| range.start());
+            references.dedup();
+        }
+        let writes = write_ranges(&ast);
+        references.into_map(|range| {
+            let kind = if writes.contains(&range) {
+                DocumentHighlightKind::WRITE
+            } else {
+                DocumentHighlightKind::READ
+            };
+            (range, kind)
+        })
+    }
+
     fn local_references_from_definition(
         &self,
         handle: &Handle,
@@ -1885,13 +2226,17 @@ impl<'a> Transaction<'a> {
             && let Some(callable) = callables.get(overload_idx).cloned()
             && let Some(params) = Self::normalize_singleton_function_type_into_params(callable)
         {
+            let provided = self
+                .get_ast(handle)
+                .map(|module| Self::keyword_arguments_provided(&module, position))
+                .unwrap_or_default();
             for param in params {
                 match param {
                     Param::Pos(name, ty, _)
                     | Param::PosOnly(Some(name), ty, _)
                     | Param::KwOnly(name, ty, _)
                     | Param::VarArg(Some(name), ty) => {
-                        if name.as_str() != "self" {
+                        if name.as_str() != "self" && !provided.contains(&name) {
                             completions.push(CompletionItem {
                                 label: format!("{}=", name.as_str()),
                                 detail: Some(ty.to_string()),
@@ -1906,6 +2251,115 @@ impl<'a> Transaction<'a> {
         }
     }
 
+    /// The keyword arguments already passed to the innermost call around `position`, other than
+    /// the one being edited.
+    fn keyword_arguments_provided(module: &ModModule, position: TextSize) -> SmallSet<Name> {
+        fn f(x: &Expr, position: TextSize, res: &mut SmallSet<Name>) {
+            if let Expr::Call(call) = x
+                && call.arguments.range.contains_inclusive(position)
+            {
+                *res = call
+                    .arguments
+                    .keywords
+                    .iter()
+                    .filter(|keyword| !keyword.range.contains_inclusive(position))
+                    .filter_map(|keyword| keyword.arg.as_ref().map(|arg| arg.id.clone()))
+                    .collect();
+            }
+            x.recurse(&mut |x| f(x, position, res));
+        }
+        let mut res = SmallSet::new();
+        module.visit(&mut |x| f(x, position, &mut res));
+        res
+    }
+
+    /// Offer the keys of a `TypedDict`: those still missing from a dict literal whose expected
+    /// type is a `TypedDict`, or all of them in a subscript of a `TypedDict` value, as in `d["`.
+    fn add_typed_dict_key_completions(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        nodes: &[AnyNodeRef],
+        completions: &mut Vec<CompletionItem>,
+    ) {
+        // Inside a string we complete the key itself, otherwise we include the quotes.
+        let current_key = match nodes.first() {
+            Some(AnyNodeRef::ExprStringLiteral(key)) => Some(key.range),
+            _ => None,
+        };
+        let Some((typed_dict_range, existing)) =
+            nodes.iter().enumerate().find_map(|(i, node)| match node {
+                AnyNodeRef::ExprDict(dict) => Some((
+                    dict.range,
+                    dict.items
+                        .iter()
+                        .filter_map(|item| match &item.key {
+                            Some(Expr::StringLiteral(key)) if Some(key.range) != current_key => {
+                                Some(key.value.to_str())
+                            }
+                            _ => None,
+                        })
+                        .collect::<SmallSet<_>>(),
+                )),
+                // The key is either the string being written, or nothing yet, as in `d[]`.
+                AnyNodeRef::ExprSubscript(subscript)
+                    if current_key == Some(subscript.slice.range())
+                        || (i == 0 && subscript.slice.range().contains_inclusive(position)) =>
+                {
+                    Some((subscript.value.range(), SmallSet::new()))
+                }
+                _ => None,
+            })
+        else {
+            return;
+        };
+        let Some(Type::TypedDict(typed_dict)) = self.get_type_trace(handle, typed_dict_range)
+        else {
+            return;
+        };
+        let Some((fields, docstrings)) = self.ad_hoc_solve(handle, |solver| {
+            let fields = solver.typed_dict_fields(&typed_dict);
+            // A key is documented where it is declared, which may be in a base TypedDict.
+            let class = typed_dict.class_object();
+            let mro = solver.get_mro_for_class(class);
+            let docstrings = fields
+                .keys()
+                .filter_map(|name| {
+                    let docstring = iter::once(class)
+                        .chain(mro.ancestors_no_object().iter().map(|x| x.class_object()))
+                        .find_map(|x| {
+                            Some(Docstring(x.field_docstring_range(name)?, x.module().dupe()))
+                        })?;
+                    Some((name.clone(), docstring))
+                })
+                .collect::<SmallMap<_, _>>();
+            (fields, docstrings)
+        }) else {
+            return;
+        };
+        for (name, field) in fields {
+            if existing.contains(name.as_str()) {
+                continue;
+            }
+            completions.push(CompletionItem {
+                label: if current_key.is_some() {
+                    name.to_string()
+                } else {
+                    format!("\"{name}\"")
+                },
+                detail: Some(field.ty.to_string()),
+                kind: Some(CompletionItemKind::FIELD),
+                documentation: docstrings.get(&name).map(|docstring| {
+                    lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+                        kind: lsp_types::MarkupKind::Markdown,
+                        value: docstring.resolve().trim().to_owned(),
+                    })
+                }),
+                ..Default::default()
+            });
+        }
+    }
+
     fn add_builtins_autoimport_completions(
         &self,
         handle: &Handle,
@@ -1944,6 +2398,85 @@ impl<'a> Transaction<'a> {
             }
         }
     }
+    /// The section an import of `module` from the module of `handle` belongs to.
+    fn import_section(&self, handle: &Handle, module: ModuleName) -> ImportSection {
+        match self.import_handle(handle, module, None).finding() {
+            Some(module_handle) => ImportSection::of(self.config_finder(), &module_handle),
+            // Like isort, treat modules we can't find as third party.
+            None => ImportSection::ThirdParty,
+        }
+    }
+
+    /// The imports at the top of the module of `handle`, with the sections they belong to.
+    fn top_imports<'b>(
+        &self,
+        handle: &Handle,
+        ast: &'b ModModule,
+    ) -> Vec<(&'b Stmt, ImportSection)> {
+        top_imports(ast, |x| match x {
+            Stmt::Import(x) => {
+                Some(self.import_section(handle, ModuleName::from_name(&x.names.first()?.name.id)))
+            }
+            Stmt::ImportFrom(x) if x.level > 0 => Some(ImportSection::FirstParty),
+            Stmt::ImportFrom(x) => {
+                Some(self.import_section(handle, ModuleName::from_name(&x.module.as_ref()?.id)))
+            }
+            _ => None,
+        })
+    }
+
+    /// Where to insert an import of `handle_to_import_from`, in the block of imports it belongs
+    /// to.
+    fn import_position(
+        &self,
+        ast: &ModModule,
+        module_info: &Module,
+        top_imports: &[(&Stmt, ImportSection)],
+        handle_to_import_from: &Handle,
+    ) -> TextSize {
+        import_position(
+            ast,
+            module_info.contents(),
+            top_imports,
+            ImportSection::of(self.config_finder(), handle_to_import_from),
+        )
+    }
+
+    /// The modules exporting `name` that `handle` could import it from, closest first: those
+    /// sharing the most of the package of `handle`, then those from the project or the standard
+    /// library before those from installed packages.
+    fn import_candidates(&self, handle: &Handle, name: &str) -> Vec<Handle> {
+        let package = handle.module().components();
+        let mut candidates = self.search_exports_exact(name);
+        candidates.sort_by_cached_key(|x| {
+            let shared = x
+                .module()
+                .components()
+                .iter()
+                .zip(&package)
+                .take_while(|(a, b)| a == b)
+                .count();
+            (
+                Reverse(shared),
+                module_origin(self.config_finder(), x),
+                x.module().as_str().to_owned(),
+            )
+        });
+        candidates
+    }
+
+    /// The sort text of completions importing from `handle`, which rank below the names already
+    /// in scope. Those from the project and the standard library come first, then those from
+    /// installed packages, then those from generated code.
+    fn autoimport_sort_text(&self, handle: &Handle) -> String {
+        match module_origin(self.config_finder(), handle) {
+            ModuleOrigin::FirstParty | ModuleOrigin::Stdlib => "4",
+            ModuleOrigin::ThirdParty => "5",
+            ModuleOrigin::Generated => "6",
+        }
+        .to_owned()
+    }
+
     fn add_autoimport_completions(
         &self,
         handle: &Handle,
@@ -1959,6 +2492,7 @@ impl<'a> Transaction<'a> {
             && let Some(ast) = self.get_ast(handle)
             && let Some(module_info) = self.get_module_info(handle)
         {
+            let top_imports = self.top_imports(handle, &ast);
             for (handle_to_import_from, name, export) in
                 self.search_exports_fuzzy(identifier.as_str())
             {
@@ -1968,9 +2502,15 @@ impl<'a> Transaction<'a> {
                 {
                     continue;
                 }
+                let sort_text = self.autoimport_sort_text(&handle_to_import_from);
                 let (insert_text, additional_text_edits) = {
-                    let (position, insert_text) = insert_import_edit(
+                    let position = self.import_position(
                         &ast,
+                        &module_info,
+                        &top_imports,
+                        &handle_to_import_from,
+                    );
+                    let insert_text = insert_import_edit(
                         self.config_finder(),
                         handle.dupe(),
                         handle_to_import_from,
@@ -1994,6 +2534,7 @@ impl<'a> Transaction<'a> {
                             Some(k.to_lsp_completion_item_kind())
                         }),
                     additional_text_edits,
+                    sort_text: Some(sort_text),
                     tags: if export.is_deprecated {
                         Some(vec![CompletionItemTag::DEPRECATED])
                     } else {
@@ -2010,9 +2551,11 @@ impl<'a> Transaction<'a> {
                 let module_name_str = module_name.as_str();
                 if let Some(module_handle) = self.import_handle(handle, module_name, None).finding()
                 {
+                    let sort_text = self.autoimport_sort_text(&module_handle);
                     let (insert_text, additional_text_edits) = {
-                        let (position, insert_text) =
-                            import_regular_import_edit(&ast, module_handle);
+                        let position =
+                            self.import_position(&ast, &module_info, &top_imports, &module_handle);
+                        let insert_text = import_regular_import_edit(module_handle);
                         let import_text_edit = TextEdit {
                             range: module_info
                                 .lined_buffer()
@@ -2026,6 +2569,7 @@ impl<'a> Transaction<'a> {
                         detail: insert_text,
                         kind: Some(CompletionItemKind::MODULE),
                         additional_text_edits,
+                        sort_text: Some(sort_text),
                         ..Default::default()
                     });
                 }
@@ -2163,18 +2707,53 @@ impl<'a> Transaction<'a> {
         ))
     }
 
+    /// Completion details elide deeply nested types. When the detail of an item was elided, its
+    /// documentation, which the client shows once the item is selected, starts with the full type.
+    fn expand_elided_completion_detail(
+        ty: Option<&Type>,
+        detail: Option<&str>,
+        documentation: Option<lsp_types::Documentation>,
+    ) -> Option<lsp_types::Documentation> {
+        let Some(ty) = ty else {
+            return documentation;
+        };
+        let full = ty.as_hover_string();
+        if detail == Some(full.as_str()) {
+            return documentation;
+        }
+        let mut value = format!("```python\n{full}\n```");
+        match documentation {
+            Some(lsp_types::Documentation::String(docstring))
+            | Some(lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+                value: docstring,
+                ..
+            })) => {
+                value.push_str("\n---\n");
+                value.push_str(&docstring);
+            }
+            None => {}
+        }
+        Some(lsp_types::Documentation::MarkupContent(
+            lsp_types::MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value,
+            },
+        ))
+    }
+
     fn add_literal_completions(
         &self,
         handle: &Handle,
         position: TextSize,
         completions: &mut Vec<CompletionItem>,
     ) {
-        if let Some((callables, chosen_overload_index, arg_index)) =
+        if let Some((callables, chosen_overload_index, Some(active_argument))) =
             self.get_callables_from_call(handle, position)
             && let Some(callable) = callables.get(chosen_overload_index)
             && let Some(params) =
                 Self::normalize_singleton_function_type_into_params(callable.clone())
-            && let Some(param) = params.get(arg_index)
+            && let Some(i) = Self::parameter_for_argument(&params, &active_argument)
+            && let Some(param) = params.get(i)
         {
             Self::add_literal_completions_from_type(param.as_type(), completions);
         }
@@ -2208,7 +2787,7 @@ impl<'a> Transaction<'a> {
         position: TextSize,
         import_format: ImportFormat,
     ) -> Vec<CompletionItem> {
-        self.completion_with_incomplete(handle, position, import_format)
+        self.completion_with_incomplete(handle, position, import_format, Default::default(), false)
             .0
     }
 
@@ -2218,9 +2797,16 @@ impl<'a> Transaction<'a> {
         handle: &Handle,
         position: TextSize,
         import_format: ImportFormat,
+        dynamic_attributes: DynamicAttributes,
+        supports_snippets: bool,
     ) -> (Vec<CompletionItem>, bool) {
-        let (mut results, is_incomplete) =
-            self.completion_sorted_opt_with_incomplete(handle, position, import_format);
+        let (mut results, is_incomplete) = self.completion_sorted_opt_with_incomplete(
+            handle,
+            position,
+            import_format,
+            dynamic_attributes,
+            supports_snippets,
+        );
         results.sort_by(|item1, item2| {
             item1
                 .sort_text
@@ -2237,6 +2823,8 @@ impl<'a> Transaction<'a> {
         handle: &Handle,
         position: TextSize,
         import_format: ImportFormat,
+        dynamic_attributes: DynamicAttributes,
+        supports_snippets: bool,
     ) -> (Vec<CompletionItem>, bool) {
         let mut result = Vec::new();
         let mut is_incomplete = false;
@@ -2303,7 +2891,7 @@ impl<'a> Transaction<'a> {
                 {
                     self.ad_hoc_solve(handle, |solver| {
                         solver
-                            .completions(base_type, None, true)
+                            .completions(base_type.clone(), None, true)
                             .iter()
                             .for_each(|x| {
                                 let kind = match x.ty {
@@ -2312,8 +2900,12 @@ impl<'a> Transaction<'a> {
                                     _ => Some(CompletionItemKind::FIELD),
                                 };
                                 let ty = &x.ty;
-                                let detail = ty.clone().map(|t| t.as_hover_string());
-                                let documentation = self.get_docstring_for_attribute(handle, x);
+                                let detail = ty.as_ref().map(|t| t.as_completion_detail_string());
+                                let documentation = Self::expand_elided_completion_detail(
+                                    ty.as_ref(),
+                                    detail.as_deref(),
+                                    self.get_docstring_for_attribute(handle, x),
+                                );
                                 result.push(CompletionItem {
                                     label: x.name.as_str().to_owned(),
                                     detail,
@@ -2332,9 +2924,41 @@ impl<'a> Transaction<'a> {
                                     ..Default::default()
                                 });
                             });
+                        let cls = match &base_type {
+                            Type::ClassType(cls) | Type::SelfType(cls) => Some(cls.class_object()),
+                            Type::ClassDef(cls) => Some(cls),
+                            _ => None,
+                        };
+                        if let Some(cls) = cls {
+                            let mro = solver.get_mro_for_class(cls);
+                            for cls in iter::once(cls)
+                                .chain(mro.ancestors_no_object().iter().map(|x| x.class_object()))
+                            {
+                                for name in find_dynamic_attributes(
+                                    cls.module(),
+                                    cls.range(),
+                                    dynamic_attributes,
+                                ) {
+                                    if result.iter().any(|x| x.label == name.as_str()) {
+                                        continue;
+                                    }
+                                    result.push(CompletionItem {
+                                        label: name.as_str().to_owned(),
+                                        kind: Some(CompletionItemKind::FIELD),
+                                        ..Default::default()
+                                    });
+                                }
+                            }
+                        }
                     });
                 }
             }
+            Some(IdentifierWithContext {
+                identifier,
+                context: IdentifierContext::MethodDef { .. },
+            }) => {
+                self.add_override_completions(handle, &identifier, supports_snippets, &mut result);
+            }
             Some(IdentifierWithContext { identifier, .. }) => {
                 self.add_kwargs_completions(handle, position, &mut result);
                 self.add_keyword_completions(handle, &mut result);
@@ -2371,6 +2995,7 @@ impl<'a> Transaction<'a> {
                         self.add_builtins_autoimport_completions(handle, None, &mut result);
                     }
                     self.add_literal_completions(handle, position, &mut result);
+                    self.add_typed_dict_key_completions(handle, position, &nodes, &mut result);
                     // in foo(x=<>, y=2<>), the first containing node is AnyNodeRef::Arguments(_)
                     // in foo(<>), the first containing node is AnyNodeRef::ExprCall
                     if let Some(first) = nodes.first()
@@ -2383,7 +3008,8 @@ impl<'a> Transaction<'a> {
         }
         for item in &mut result {
             let sort_text = if item.additional_text_edits.is_some() {
-                "4"
+                // Auto-imports may be ranked by where they import from.
+                item.sort_text.as_deref().unwrap_or("4")
             } else if item.label.starts_with("__") {
                 "3"
             } else if item.label.as_str().starts_with("_") {
@@ -2688,7 +3314,13 @@ impl<'a> Transaction<'a> {
                                     {
                                         ty = return_ty;
                                     }
-                                    res.push((fun.def.parameters.range.end(), format!(" -> {ty}")));
+                                    res.push((
+                                        fun.def.parameters.range.end(),
+                                        format!(
+                                            " -> {}",
+                                            ty.as_inlay_hint_string(handle.sys_info().version())
+                                        ),
+                                    ));
                                 }
                             }
                             _ => {}
@@ -2717,7 +3349,8 @@ impl<'a> Transaction<'a> {
                     if let Some(e) = e
                         && is_interesting(e, &ty, class_name)
                     {
-                        let ty = format!(": {ty}");
+                        let ty =
+                            format!(": {}", ty.as_inlay_hint_string(handle.sys_info().version()));
                         res.push((key.range().end(), ty));
                     }
                 }
@@ -2726,7 +3359,10 @@ impl<'a> Transaction<'a> {
         }
 
         if inlay_hint_config.call_argument_names != AllOffPartial::Off {
-            res.extend(self.add_inlay_hints_for_positional_function_args(handle));
+            res.extend(self.add_inlay_hints_for_positional_function_args(
+                handle,
+                inlay_hint_config.call_argument_names == AllOffPartial::Partial,
+            ));
         }
 
         Some(res)
@@ -2745,9 +3381,12 @@ impl<'a> Transaction<'a> {
         function_calls
     }
 
+    /// Hints naming the parameters of positional arguments. If `partial`, arguments that already
+    /// name their parameter, and those of positional-only parameters, are skipped.
     fn add_inlay_hints_for_positional_function_args(
         &self,
         handle: &Handle,
+        partial: bool,
     ) -> Vec<(TextSize, String)> {
         let mut param_hints: Vec<(TextSize, String)> = Vec::new();
 
@@ -2779,14 +3418,21 @@ impl<'a> Transaction<'a> {
                                 .iter()
                                 .any(|kw| kw.value.range() == arg.range());
 
+                            let names_param = |name: &Name| match arg {
+                                Expr::Name(x) => x.id == *name,
+                                Expr::Attribute(x) => x.attr.id == *name,
+                                _ => false,
+                            };
                             if !is_keyword_arg
                                 && let Some(
-                                    Param::Pos(name, _, _)
+                                    param @ (Param::Pos(name, _, _)
                                     | Param::PosOnly(Some(name), _, _)
-                                    | Param::KwOnly(name, _, _),
+                                    | Param::KwOnly(name, _, _)),
                                 ) = params.get(arg_idx)
                                 && name.as_str() != "self"
                                 && name.as_str() != "cls"
+                                && !(partial
+                                    && (matches!(param, Param::PosOnly(..)) || names_param(name)))
                             {
                                 param_hints
                                     .push((arg.range().start(), format!("{}= ", name.as_str())));
@@ -2811,6 +3457,7 @@ impl<'a> Transaction<'a> {
         let ast = self.get_ast(handle)?;
         let legends = SemanticTokensLegends::new();
         let disabled_ranges = disabled_ranges_for_module(ast.as_ref(), handle.sys_info());
+        let async_functions = async_function_names(&ast);
         let mut builder = SemanticTokenBuilder::new(limit_range, disabled_ranges);
         for NamedBinding {
             definition_handle,
@@ -2819,11 +3466,22 @@ impl<'a> Transaction<'a> {
         } in self.named_bindings(handle, &bindings)
         {
             if let Export {
+                location,
                 symbol_kind: Some(symbol_kind),
                 ..
             } = definition_export
             {
-                builder.process_key(&key, definition_handle.module(), symbol_kind)
+                // The standard library, or its most used modules if typeshed isn't bundled.
+                let is_default_library = matches!(
+                    definition_handle.path().details(),
+                    ModulePathDetails::BundledTypeshed(_)
+                ) || matches!(
+                    definition_handle.module().as_str(),
+                    "builtins" | "typing" | "typing_extensions"
+                );
+                // We only know which functions are async in this module.
+                let is_async = definition_handle == *handle && async_functions.contains(&location);
+                builder.process_key(&key, symbol_kind, is_default_library, is_async)
             }
         }
         builder.process_ast(&ast, &|range| self.get_type_trace(handle, range));
@@ -2841,10 +3499,11 @@ impl<'a> Transaction<'a> {
             stmt: &'a Stmt,
             symbols: &'a mut Vec<DocumentSymbol>,
             module_info: &Module,
+            ast: &ModModule,
         ) {
             let mut recursed_symbols = Vec::new();
             stmt.recurse(&mut |stmt| {
-                recurse_stmt_adding_symbols(stmt, &mut recursed_symbols, module_info)
+                recurse_stmt_adding_symbols(stmt, &mut recursed_symbols, module_info, ast)
             });
 
             match stmt {
@@ -2858,7 +3517,14 @@ impl<'a> Transaction<'a> {
                     };
                     symbols.push(DocumentSymbol {
                         name,
-                        detail: None,
+                        detail: Some(
+                            FullyQualifiedName::of_definition(
+                                module_info,
+                                ast,
+                                stmt_function_def.name.range,
+                            )
+                            .to_string(),
+                        ),
                         kind: lsp_types::SymbolKind::FUNCTION,
                         tags: None,
                         deprecated: None,
@@ -2881,7 +3547,14 @@ impl<'a> Transaction<'a> {
                     };
                     symbols.push(DocumentSymbol {
                         name,
-                        detail: None,
+                        detail: Some(
+                            FullyQualifiedName::of_definition(
+                                module_info,
+                                ast,
+                                stmt_class_def.name.range,
+                            )
+                            .to_string(),
+                        ),
                         kind: lsp_types::SymbolKind::CLASS,
                         tags: None,
                         deprecated: None,
@@ -2939,30 +3612,7 @@ impl<'a> Transaction<'a> {
         }
         let mut result = Vec::new();
         ast.body
-            .visit(&mut |stmt| recurse_stmt_adding_symbols(stmt, &mut result, &module_info));
-        Some(result)
-    }
-
-    pub fn workspace_symbols(
-        &self,
-        query: &str,
-    ) -> Option<Vec<(String, lsp_types::SymbolKind, TextRangeWithModule)>> {
-        if query.len() < MIN_CHARACTERS_TYPED_AUTOIMPORT {
-            return None;
-        }
-        let mut result = Vec::new();
-        for (handle, name, export) in self.search_exports_fuzzy(query) {
-            if let Some(module) = self.get_module_info(&handle) {
-                let kind = export
-                    .symbol_kind
-                    .map_or(lsp_types::SymbolKind::VARIABLE, |k| k.to_lsp_symbol_kind());
-                let location = TextRangeWithModule {
-                    module,
-                    range: export.location,
-                };
-                result.push((name, kind, location));
-            }
-        }
+            .visit(&mut |stmt| recurse_stmt_adding_symbols(stmt, &mut result, &module_info, &ast));
         Some(result)
     }
 }
@@ -0,0 +1,246 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Call hierarchy: the functions a function calls, and the functions that call it. Calls are
+//! found syntactically, then resolved with go-to-definition (outgoing) or find-references
+//! (incoming), so only calls of a name or an attribute are understood.
+
+use std::sync::Arc;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::symbol_kind::SymbolKind;
+use pyrefly_python::sys_info::SysInfo;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Identifier;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_expr;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
+
+use crate::state::lsp::FindDefinitionItemWithDocstring;
+use crate::state::lsp::FindPreference;
+use crate::state::state::Transaction;
+
+/// A function, method, class or module that is a node of a call hierarchy.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyTarget {
+    pub module: Module,
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The whole definition.
+    pub range: TextRange,
+    /// The name of the definition, or the start of the module.
+    pub selection_range: TextRange,
+}
+
+impl CallHierarchyTarget {
+    fn new(module: &Module, kind: SymbolKind, name: &Identifier, range: TextRange) -> Self {
+        Self {
+            module: module.dupe(),
+            name: name.id.to_string(),
+            kind,
+            range,
+            selection_range: name.range,
+        }
+    }
+
+    /// Calls at the top level of a module are made by the module itself.
+    fn module(module: &Module) -> Self {
+        Self {
+            module: module.dupe(),
+            name: module.name().to_string(),
+            kind: SymbolKind::Module,
+            range: TextRange::up_to(TextSize::of(module.contents().as_str())),
+            selection_range: TextRange::default(),
+        }
+    }
+}
+
+/// Every function and class defined in a module, parents before the definitions nested in them.
+fn definitions(module: &Module, ast: &ModModule) -> Vec<CallHierarchyTarget> {
+    fn f(module: &Module, x: &Stmt, in_class: bool, res: &mut Vec<CallHierarchyTarget>) {
+        match x {
+            Stmt::FunctionDef(x) => {
+                let kind = if in_class {
+                    SymbolKind::Method
+                } else {
+                    SymbolKind::Function
+                };
+                res.push(CallHierarchyTarget::new(module, kind, &x.name, x.range));
+                for x in &x.body {
+                    f(module, x, false, res);
+                }
+            }
+            Stmt::ClassDef(x) => {
+                res.push(CallHierarchyTarget::new(
+                    module,
+                    SymbolKind::Class,
+                    &x.name,
+                    x.range,
+                ));
+                for x in &x.body {
+                    f(module, x, true, res);
+                }
+            }
+            _ => x.recurse(&mut |x| f(module, x, in_class, res)),
+        }
+    }
+    let mut res = Vec::new();
+    for x in &ast.body {
+        f(module, x, false, &mut res);
+    }
+    res
+}
+
+/// The innermost function or class containing `range`, or else the module.
+fn enclosing_target(module: &Module, ast: &ModModule, range: TextRange) -> CallHierarchyTarget {
+    definitions(module, ast)
+        .into_iter()
+        .rfind(|x| x.range.contains_range(range))
+        .unwrap_or_else(|| CallHierarchyTarget::module(module))
+}
+
+/// The ranges of the names called: `f` in `f()` and `m` in `x.m()`.
+struct Callees {
+    /// Whether to include the calls made in nested functions and classes.
+    nested: bool,
+    res: Vec<TextRange>,
+}
+
+impl<'a> SourceOrderVisitor<'a> for Callees {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        if self.nested || !matches!(x, Stmt::FunctionDef(_) | Stmt::ClassDef(_)) {
+            walk_stmt(self, x);
+        }
+    }
+
+    fn visit_expr(&mut self, x: &'a Expr) {
+        if let Expr::Call(call) = x {
+            match &*call.func {
+                Expr::Name(func) => self.res.push(func.range),
+                Expr::Attribute(func) => self.res.push(func.attr.range),
+                _ => {}
+            }
+        }
+        walk_expr(self, x);
+    }
+}
+
+/// Group the calls made by or to each target, in the order the targets are first seen.
+fn group_calls(
+    calls: impl IntoIterator<Item = (CallHierarchyTarget, TextRange)>,
+) -> Vec<(CallHierarchyTarget, Vec<TextRange>)> {
+    let mut res: SmallMap<(ModulePath, TextRange), (CallHierarchyTarget, Vec<TextRange>)> =
+        SmallMap::new();
+    for (target, range) in calls {
+        res.entry((target.module.path().dupe(), target.selection_range))
+            .or_insert_with(|| (target, Vec::new()))
+            .1
+            .push(range);
+    }
+    res.into_values().collect()
+}
+
+/// The callers of a function, given the references to it found by find-references. Returns each
+/// caller along with the ranges of its calls, in the module of the caller.
+pub fn incoming_calls(
+    references: Vec<(Module, Vec<TextRange>)>,
+) -> Vec<(CallHierarchyTarget, Vec<TextRange>)> {
+    let mut calls = Vec::new();
+    for (module, ranges) in references {
+        let ast = Ast::parse(module.contents()).0;
+        let mut callees = Callees {
+            nested: true,
+            res: Vec::new(),
+        };
+        callees.visit_body(&ast.body);
+        for range in ranges {
+            if callees.res.contains(&range) {
+                calls.push((enclosing_target(&module, &ast, range), range));
+            }
+        }
+    }
+    group_calls(calls)
+}
+
+impl<'a> Transaction<'a> {
+    fn call_hierarchy_ast(&self, sys_info: &SysInfo, module: &Module) -> Arc<ModModule> {
+        let handle = Handle::new(module.name(), module.path().dupe(), sys_info.dupe());
+        self.get_ast(&handle)
+            .unwrap_or_else(|| Ast::parse(module.contents()).0.into())
+    }
+
+    /// The functions and classes defined by the symbol at `position`.
+    pub fn prepare_call_hierarchy(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+    ) -> Vec<CallHierarchyTarget> {
+        let preference = FindPreference {
+            prefer_pyi: false,
+            ..Default::default()
+        };
+        self.find_definition(handle, position, &preference)
+            .into_iter()
+            .filter_map(
+                |FindDefinitionItemWithDocstring {
+                     module,
+                     definition_range,
+                     ..
+                 }| {
+                    let ast = self.call_hierarchy_ast(handle.sys_info(), &module);
+                    definitions(&module, &ast)
+                        .into_iter()
+                        .find(|x| x.selection_range == definition_range)
+                },
+            )
+            .collect()
+    }
+
+    /// The functions and classes called by the function or class whose name is at `position`,
+    /// along with the ranges of the calls.
+    pub fn outgoing_calls(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+    ) -> Vec<(CallHierarchyTarget, Vec<TextRange>)> {
+        let Some(ast) = self.get_ast(handle) else {
+            return Vec::new();
+        };
+        let mut callees = Callees {
+            nested: false,
+            res: Vec::new(),
+        };
+        match Ast::locate_node(&ast, position)
+            .into_iter()
+            .find_map(|x| match x {
+                AnyNodeRef::StmtFunctionDef(x) => Some((x.name.range, &x.body)),
+                AnyNodeRef::StmtClassDef(x) => Some((x.name.range, &x.body)),
+                _ => None,
+            }) {
+            Some((name, body)) if name.contains_inclusive(position) => callees.visit_body(body),
+            _ => return Vec::new(),
+        }
+        let mut calls = Vec::new();
+        for range in callees.res {
+            for target in self.prepare_call_hierarchy(handle, range.start()) {
+                calls.push((target, range));
+            }
+        }
+        group_calls(calls)
+    }
+}
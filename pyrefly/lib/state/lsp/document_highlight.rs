@@ -0,0 +1,221 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Syntactic helpers for document highlight: which occurrences of a name write to it, and the
+//! occurrences of an attribute of `self` across the methods of a class.
+
+use pyrefly_python::ast::Ast;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Pattern;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+fn pattern_writes(x: &Pattern, res: &mut SmallSet<TextRange>) {
+    match x {
+        Pattern::MatchAs(x) => res.extend(x.name.as_ref().map(|x| x.range)),
+        Pattern::MatchStar(x) => res.extend(x.name.as_ref().map(|x| x.range)),
+        Pattern::MatchMapping(x) => res.extend(x.rest.as_ref().map(|x| x.range)),
+        _ => {}
+    }
+    x.recurse(&mut |x| pattern_writes(x, res));
+}
+
+fn stmt_writes(x: &Stmt, res: &mut SmallSet<TextRange>) {
+    match x {
+        Stmt::FunctionDef(x) => {
+            res.insert(x.name.range);
+            for p in x.parameters.iter() {
+                res.insert(p.name().range);
+            }
+        }
+        Stmt::ClassDef(x) => {
+            res.insert(x.name.range);
+        }
+        Stmt::Import(x) => {
+            for alias in &x.names {
+                res.insert(alias.asname.as_ref().unwrap_or(&alias.name).range);
+            }
+        }
+        Stmt::ImportFrom(x) => {
+            for alias in &x.names {
+                res.insert(alias.asname.as_ref().unwrap_or(&alias.name).range);
+            }
+        }
+        Stmt::Try(x) => {
+            for handler in &x.handlers {
+                let ExceptHandler::ExceptHandler(handler) = handler;
+                res.extend(handler.name.as_ref().map(|x| x.range));
+            }
+        }
+        Stmt::Match(x) => {
+            for case in &x.cases {
+                pattern_writes(&case.pattern, res);
+            }
+        }
+        _ => {}
+    }
+    x.recurse(&mut |x| stmt_writes(x, res));
+}
+
+fn expr_writes(x: &Expr, res: &mut SmallSet<TextRange>) {
+    let is_write = |ctx: ExprContext| matches!(ctx, ExprContext::Store | ExprContext::Del);
+    match x {
+        Expr::Name(x) if is_write(x.ctx) => {
+            res.insert(x.range);
+        }
+        Expr::Attribute(x) if is_write(x.ctx) => {
+            res.insert(x.attr.range);
+        }
+        Expr::Lambda(x) => {
+            for p in x.parameters.iter().flat_map(|x| x.iter()) {
+                res.insert(p.name().range);
+            }
+        }
+        Expr::ListComp(x) => {
+            for x in &x.generators {
+                Ast::expr_lvalue(&x.target, &mut |x| {
+                    res.insert(x.range);
+                });
+            }
+        }
+        Expr::SetComp(x) => {
+            for x in &x.generators {
+                Ast::expr_lvalue(&x.target, &mut |x| {
+                    res.insert(x.range);
+                });
+            }
+        }
+        Expr::DictComp(x) => {
+            for x in &x.generators {
+                Ast::expr_lvalue(&x.target, &mut |x| {
+                    res.insert(x.range);
+                });
+            }
+        }
+        Expr::Generator(x) => {
+            for x in &x.generators {
+                Ast::expr_lvalue(&x.target, &mut |x| {
+                    res.insert(x.range);
+                });
+            }
+        }
+        _ => {}
+    }
+    x.recurse(&mut |x| expr_writes(x, res));
+}
+
+/// The ranges of every name in the module that is written to, rather than read: definitions,
+/// assignment targets, parameters, imports and the like. For attributes, the range is that of the
+/// attribute name, e.g. `x` in `self.x = 1`.
+pub fn write_ranges(ast: &ModModule) -> SmallSet<TextRange> {
+    let mut res = SmallSet::new();
+    for x in &ast.body {
+        stmt_writes(x, &mut res);
+    }
+    ast.visit(&mut |x: &Expr| expr_writes(x, &mut res));
+    res
+}
+
+/// The methods of a class, along with the name of their receiver, usually `self` or `cls`.
+/// Static methods have no receiver, so are skipped.
+fn methods_with_receiver(class: &StmtClassDef) -> Vec<(&StmtFunctionDef, &Name)> {
+    class
+        .body
+        .iter()
+        .filter_map(|x| {
+            let x = x.as_function_def_stmt()?;
+            let is_staticmethod = x.decorator_list.iter().any(|d| match &d.expression {
+                Expr::Name(d) => d.id == "staticmethod",
+                _ => false,
+            });
+            if is_staticmethod {
+                return None;
+            }
+            let receiver = x.parameters.iter_non_variadic_params().next()?;
+            Some((x, &receiver.parameter.name.id))
+        })
+        .collect()
+}
+
+/// Every `receiver.<attr>` in the body of `method`, as the range of the attribute name.
+fn receiver_attributes<'a>(
+    method: &'a StmtFunctionDef,
+    receiver: &Name,
+) -> Vec<(&'a Name, TextRange)> {
+    fn f<'a>(x: &'a Expr, receiver: &Name, res: &mut Vec<(&'a Name, TextRange)>) {
+        if let Expr::Attribute(x) = x
+            && let Expr::Name(value) = &*x.value
+            && &value.id == receiver
+        {
+            res.push((&x.attr.id, x.attr.range));
+        }
+        x.recurse(&mut |x| f(x, receiver, res));
+    }
+    let mut res = Vec::new();
+    method.body.visit(&mut |x: &Expr| f(x, receiver, &mut res));
+    res
+}
+
+fn all_classes(ast: &ModModule) -> Vec<&StmtClassDef> {
+    fn f<'a>(x: &'a Stmt, res: &mut Vec<&'a StmtClassDef>) {
+        if let Stmt::ClassDef(x) = x {
+            res.push(x);
+        }
+        x.recurse(&mut |x| f(x, res));
+    }
+    let mut res = Vec::new();
+    for x in &ast.body {
+        f(x, &mut res);
+    }
+    res
+}
+
+/// If `position` is on `x` in `self.x`, where `self` is the receiver of a method, find the range
+/// of `x` in every `self.x` in the methods of the same class, whatever the receiver is called in
+/// each method. This works even if the attribute is never declared, so the type checker can't
+/// resolve it.
+pub fn receiver_attribute_references(
+    ast: &ModModule,
+    position: TextSize,
+) -> Option<Vec<TextRange>> {
+    // If classes are nested, the innermost method containing the position is the one we want.
+    let (class, name) = all_classes(ast)
+        .into_iter()
+        .flat_map(|class| {
+            methods_with_receiver(class)
+                .into_iter()
+                .filter(|(method, _)| method.range.contains(position))
+                .map(move |(method, receiver)| (class, method, receiver))
+        })
+        .min_by_key(|(_, method, _)| method.range.len())
+        .and_then(|(class, method, receiver)| {
+            let (name, _) = receiver_attributes(method, receiver)
+                .into_iter()
+                .find(|(_, range)| range.contains_inclusive(position))?;
+            Some((class, name))
+        })?;
+    let mut res = Vec::new();
+    for (method, receiver) in methods_with_receiver(class) {
+        res.extend(
+            receiver_attributes(method, receiver)
+                .into_iter()
+                .filter(|(x, _)| *x == name)
+                .map(|(_, range)| range),
+        );
+    }
+    Some(res)
+}
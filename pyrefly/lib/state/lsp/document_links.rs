@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Document links for the module names in import statements. Each part of a dotted name links to
+//! the module it names, so in `import a.b`, `a` links to `a` and `b` links to `a.b`. Unlike
+//! go-to-definition, this only resolves imports, so doesn't need the module to be checked.
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePathDetails;
+use ruff_python_ast::Identifier;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::name::Name;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::state::Transaction;
+
+/// The range of each part of a dotted name, with the name up to and including that part.
+fn dotted_name_parts(name: &Identifier) -> Vec<(TextRange, &str)> {
+    let id = name.id.as_str();
+    // With whitespace around the dots, as in `a . b`, the parts aren't where the name says.
+    if TextSize::of(id) != name.range.len() {
+        return vec![(name.range, id)];
+    }
+    let mut start = 0;
+    id.match_indices('.')
+        .map(|(i, _)| i)
+        .chain([id.len()])
+        .map(|end| {
+            let range = TextRange::new(
+                name.range.start() + TextSize::new(start as u32),
+                name.range.start() + TextSize::new(end as u32),
+            );
+            start = end + 1;
+            (range, &id[..end])
+        })
+        .collect()
+}
+
+/// The module names in the import statements of a module, including nested ones.
+struct ImportedModules {
+    module: ModuleName,
+    is_init: bool,
+    names: Vec<(TextRange, ModuleName)>,
+}
+
+impl<'a> SourceOrderVisitor<'a> for ImportedModules {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        match x {
+            Stmt::Import(x) => {
+                for alias in &x.names {
+                    for (range, name) in dotted_name_parts(&alias.name) {
+                        self.names.push((range, ModuleName::from_str(name)));
+                    }
+                }
+            }
+            Stmt::ImportFrom(x) => {
+                // With no module, as in `from . import a`, there is no name to link.
+                if let Some(module) = &x.module {
+                    for (range, name) in dotted_name_parts(module) {
+                        if let Some(name) = self.module.new_maybe_relative(
+                            self.is_init,
+                            x.level,
+                            Some(&Name::new(name)),
+                        ) {
+                            self.names.push((range, name));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_stmt(self, x);
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// The module names in the imports of a module, with the modules they resolve to, preferring
+    /// stubs. Names that don't resolve to a file, including namespace packages, are skipped.
+    pub fn document_links(&self, handle: &Handle) -> Vec<(TextRange, Handle)> {
+        let Some(ast) = self.get_ast(handle) else {
+            return Vec::new();
+        };
+        let mut imports = ImportedModules {
+            module: handle.module(),
+            is_init: handle.path().is_init(),
+            names: Vec::new(),
+        };
+        imports.visit_body(&ast.body);
+        imports
+            .names
+            .into_iter()
+            .filter_map(|(range, name)| {
+                let target = self.import_handle(handle, name, None).finding()?;
+                (!matches!(target.path().details(), ModulePathDetails::Namespace(_)))
+                    .then_some((range, target))
+            })
+            .collect()
+    }
+}
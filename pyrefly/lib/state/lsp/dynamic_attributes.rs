@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Find attributes that a class defines dynamically, so they can be offered as completions even
+//! though the type checker doesn't know about them.
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::TextRange;
+use serde::Deserialize;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+
+/// How far to trust dynamic patterns when looking for attributes to complete.
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DynamicAttributes {
+    /// Only complete attributes the type checker knows about.
+    Off,
+    /// Also complete attributes set with a literal name, such as `setattr(self, "x", 1)` in a
+    /// method, or `locals()["x"] = 1` in the class body.
+    #[default]
+    Literal,
+    /// Also complete attributes set in a loop over literal names, such as
+    /// `for name in ("x", "y"): setattr(self, name, None)`.
+    Loops,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// The names in a tuple, list or set made up entirely of string literals.
+fn literal_names(x: &Expr) -> Option<Vec<Name>> {
+    let elts = match x {
+        Expr::Tuple(x) => &x.elts,
+        Expr::List(x) => &x.elts,
+        Expr::Set(x) => &x.elts,
+        _ => return None,
+    };
+    elts.iter()
+        .map(|x| match x {
+            Expr::StringLiteral(x) => Some(Name::new(x.value.to_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+struct Collector<'a> {
+    mode: DynamicAttributes,
+    /// Loop variables that range over literal names.
+    loop_vars: SmallMap<&'a Name, Vec<Name>>,
+    res: SmallSet<Name>,
+}
+
+impl<'a> Collector<'a> {
+    /// Record the attribute named by `x`, which is either a string literal or a loop variable.
+    fn add_name(&mut self, x: &Expr) {
+        match x {
+            Expr::StringLiteral(x) => {
+                self.res.insert(Name::new(x.value.to_str()));
+            }
+            Expr::Name(x) => {
+                if let Some(names) = self.loop_vars.get(&x.id) {
+                    for name in names {
+                        self.res.insert(name.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `setattr(receiver, name, value)` inside a method.
+    fn setattr_call(&mut self, receiver: &Name, x: &Expr) {
+        if let Expr::Call(call) = x
+            && call.func.as_name_expr().is_some_and(|f| f.id == "setattr")
+            && let [obj, name, _] = &*call.arguments.args
+            && obj.as_name_expr().is_some_and(|obj| &obj.id == receiver)
+        {
+            self.add_name(name);
+        }
+    }
+
+    /// `locals()[name] = value` or `vars()[name] = value` in the class body.
+    fn namespace_assign(&mut self, x: &Expr) {
+        if let Expr::Subscript(x) = x
+            && let Expr::Call(call) = &*x.value
+            && call.arguments.args.is_empty()
+            && call.arguments.keywords.is_empty()
+            && call
+                .func
+                .as_name_expr()
+                .is_some_and(|f| f.id == "locals" || f.id == "vars")
+        {
+            self.add_name(&x.slice);
+        }
+    }
+
+    /// Walk `stmt`, where `receiver` is the first parameter of the enclosing method, or `None` in
+    /// the class body.
+    fn stmt(&mut self, stmt: &'a Stmt, receiver: Option<&Name>) {
+        match stmt {
+            Stmt::FunctionDef(_) | Stmt::ClassDef(_) => {}
+            Stmt::Expr(x) => {
+                if let Some(receiver) = receiver {
+                    self.setattr_call(receiver, &x.value);
+                }
+            }
+            Stmt::Assign(x) if receiver.is_none() => {
+                for target in &x.targets {
+                    self.namespace_assign(target);
+                }
+            }
+            Stmt::For(x) if self.mode == DynamicAttributes::Loops => {
+                let loop_var = match (&*x.target, literal_names(&x.iter)) {
+                    (Expr::Name(var), Some(names)) => Some((&var.id, names)),
+                    _ => None,
+                };
+                let Some((var, names)) = loop_var else {
+                    stmt.recurse(&mut |x| self.stmt(x, receiver));
+                    return;
+                };
+                let previous = self.loop_vars.insert(var, names);
+                for stmt in &x.body {
+                    self.stmt(stmt, receiver);
+                }
+                if let Some(previous) = previous {
+                    self.loop_vars.insert(var, previous);
+                } else {
+                    self.loop_vars.shift_remove(var);
+                }
+                for stmt in &x.orelse {
+                    self.stmt(stmt, receiver);
+                }
+            }
+            _ => stmt.recurse(&mut |x| self.stmt(x, receiver)),
+        }
+    }
+}
+
+fn find_class<'a>(body: &'a [Stmt], range: TextRange) -> Option<&'a StmtClassDef> {
+    let mut res = None;
+    for stmt in body {
+        stmt.visit(&mut |x: &'a Stmt| {
+            if res.is_none()
+                && let Stmt::ClassDef(x) = x
+                && x.name.range == range
+            {
+                res = Some(x);
+            }
+        });
+    }
+    res
+}
+
+/// The attributes that the class whose name is at `class_range` in `module` sets dynamically,
+/// trusting the patterns allowed by `mode`.
+pub fn find_dynamic_attributes(
+    module: &Module,
+    class_range: TextRange,
+    mode: DynamicAttributes,
+) -> SmallSet<Name> {
+    if mode == DynamicAttributes::Off || module.path().is_interface() {
+        return SmallSet::new();
+    }
+    let ast = Ast::parse(module.contents()).0;
+    let Some(cls) = find_class(&ast.body, class_range) else {
+        return SmallSet::new();
+    };
+    let mut collector = Collector {
+        mode,
+        loop_vars: SmallMap::new(),
+        res: SmallSet::new(),
+    };
+    for stmt in &cls.body {
+        match stmt {
+            Stmt::FunctionDef(x) => {
+                let receiver = x
+                    .parameters
+                    .posonlyargs
+                    .iter()
+                    .chain(&x.parameters.args)
+                    .next()
+                    .map(|x| &x.parameter.name.id);
+                if let Some(receiver) = receiver {
+                    for stmt in &x.body {
+                        collector.stmt(stmt, Some(receiver));
+                    }
+                }
+            }
+            _ => collector.stmt(stmt, None),
+        }
+    }
+    collector
+        .res
+        .into_iter()
+        .filter(|name| is_identifier(name))
+        .collect()
+}
@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! The imports a type inlay hint needs once it is inserted as an annotation, such as `Literal`
+//! for `-> Literal[0]`. Finding them means searching the exports of every module, so it is done
+//! when the client resolves a hint, rather than for every hint shown.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::name::Name;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+use crate::state::ide::insert_import_edit;
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::is_bound_at_top_level;
+use crate::state::state::Transaction;
+
+fn names_in(x: &Expr, res: &mut SmallSet<Name>) {
+    if let Expr::Name(x) = x {
+        res.insert(x.id.clone());
+    }
+    x.recurse(&mut |x| names_in(x, res));
+}
+
+impl<'a> Transaction<'a> {
+    /// The imports to add along with the annotation inserted by the inlay hint `hint`, for the
+    /// names it uses that the module neither defines nor imports. A name exported by several
+    /// modules is imported from `typing` if that is one of them, and otherwise left alone, since
+    /// we can't tell which one the hint means.
+    pub fn inlay_hint_import_edits(
+        &self,
+        handle: &Handle,
+        hint: &str,
+        import_format: ImportFormat,
+    ) -> Vec<(TextSize, String)> {
+        // Only type hints are annotations. Parameter name hints don't need anything imported.
+        let Some(annotation) = hint
+            .strip_prefix(" -> ")
+            .or_else(|| hint.strip_prefix(": "))
+        else {
+            return Vec::new();
+        };
+        let (Some(module_info), Some(ast), Ok(annotation)) = (
+            self.get_module_info(handle),
+            self.get_ast(handle),
+            Ast::parse_expr(annotation, TextSize::new(0)),
+        ) else {
+            return Vec::new();
+        };
+        let mut names = SmallSet::new();
+        names_in(&annotation, &mut names);
+        let builtins = self
+            .import_handle(handle, ModuleName::builtins(), None)
+            .finding()
+            .map(|x| self.get_exports(&x));
+        let top_imports = self.top_imports(handle, &ast);
+        let mut edits = Vec::new();
+        for name in names {
+            if is_bound_at_top_level(&ast, &name)
+                || builtins.as_ref().is_some_and(|x| x.contains_key(&name))
+            {
+                continue;
+            }
+            let mut candidates = self.search_exports_exact(&name);
+            candidates.retain(|x| x.module() != handle.module());
+            let handle_to_import_from = match candidates
+                .iter()
+                .find(|x| x.module() == ModuleName::typing())
+            {
+                Some(typing) => typing.dupe(),
+                None if candidates.len() == 1 => candidates.pop().unwrap(),
+                None => continue,
+            };
+            edits.push((
+                self.import_position(&ast, &module_info, &top_imports, &handle_to_import_from),
+                insert_import_edit(
+                    self.config_finder(),
+                    handle.dupe(),
+                    handle_to_import_from,
+                    &name,
+                    import_format,
+                ),
+            ));
+        }
+        edits
+    }
+}
@@ -0,0 +1,388 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashSet;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Alias;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtImportFrom;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::import_insertion_point;
+use crate::state::lsp::quick_fixes::is_bound_at_top_level;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+
+fn collect_loaded_names(x: &Expr, names: &mut SmallSet<Name>) {
+    if let Expr::Name(name) = x
+        && name.ctx == ExprContext::Load
+    {
+        names.insert(name.id.clone());
+    }
+    x.recurse(&mut |x| collect_loaded_names(x, names));
+}
+
+/// Names bound by a top-level statement that other code could import.
+fn defined_names(stmt: &Stmt) -> Vec<&Name> {
+    match stmt {
+        Stmt::FunctionDef(x) => vec![&x.name.id],
+        Stmt::ClassDef(x) => vec![&x.name.id],
+        Stmt::Assign(x) => x
+            .targets
+            .iter()
+            .filter_map(|t| t.as_name_expr())
+            .map(|n| &n.id)
+            .collect(),
+        Stmt::AnnAssign(x) => x.target.as_name_expr().map(|n| &n.id).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn alias_text(alias: &Alias) -> String {
+    match &alias.asname {
+        Some(asname) => format!("{} as {}", alias.name.id, asname.id),
+        None => alias.name.id.to_string(),
+    }
+}
+
+/// Extend `range` to cover whole lines, plus any blank lines that follow, so that deleting it
+/// doesn't leave a hole in the file. At the end of the file, the blank lines before it go too.
+fn removal_range(contents: &str, range: TextRange) -> TextRange {
+    let mut start = line_start(contents, range.start().to_usize());
+    let mut end = range.end().to_usize();
+    while end < contents.len() {
+        let line_end = contents[end..]
+            .find('\n')
+            .map_or(contents.len(), |i| end + i + 1);
+        if end != range.end().to_usize() && !contents[end..line_end].trim().is_empty() {
+            break;
+        }
+        end = line_end;
+    }
+    if end == contents.len() {
+        while start > 0 {
+            let previous = line_start(contents, start - 1);
+            if !contents[previous..start].trim().is_empty() {
+                break;
+            }
+            start = previous;
+        }
+    }
+    TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32))
+}
+
+fn resolve_import_from(module: &Module, x: &StmtImportFrom) -> Option<ModuleName> {
+    module.name().new_maybe_relative(
+        module.path().is_init(),
+        x.level,
+        x.module.as_ref().map(|m| &m.id),
+    )
+}
+
+/// The import statements the moved code needs in its new home, derived from the names it loads.
+fn dependency_imports(
+    source: &Module,
+    source_ast: &ModModule,
+    moved: &Stmt,
+    symbol: &Name,
+) -> Vec<String> {
+    let mut used = SmallSet::new();
+    moved.visit(&mut |x| collect_loaded_names(x, &mut used));
+    let mut imports = Vec::new();
+    for stmt in &source_ast.body {
+        if stmt.range() == moved.range() {
+            continue;
+        }
+        match stmt {
+            Stmt::Import(x) => {
+                for alias in &x.names {
+                    let bound = match &alias.asname {
+                        Some(asname) => asname.id.clone(),
+                        None => ModuleName::from_name(&alias.name.id).first_component(),
+                    };
+                    if used.contains(&bound) {
+                        imports.push(format!("import {}", alias_text(alias)));
+                    }
+                }
+            }
+            Stmt::ImportFrom(x) => {
+                let Some(module) = resolve_import_from(source, x) else {
+                    continue;
+                };
+                for alias in &x.names {
+                    let bound = alias.asname.as_ref().unwrap_or(&alias.name);
+                    if used.contains(&bound.id) {
+                        imports.push(format!("from {module} import {}", alias_text(alias)));
+                    }
+                }
+            }
+            _ => {
+                for name in defined_names(stmt) {
+                    if name != symbol && used.contains(name) {
+                        imports.push(format!("from {} import {name}", source.name()));
+                    }
+                }
+            }
+        }
+    }
+    imports
+}
+
+/// Whether `module` imports the module `name` at its top level.
+fn imports_module(module: &Module, ast: &ModModule, name: ModuleName) -> bool {
+    ast.body.iter().any(|stmt| match stmt {
+        Stmt::Import(x) => x
+            .names
+            .iter()
+            .any(|alias| ModuleName::from_name(&alias.name.id) == name),
+        Stmt::ImportFrom(x) => resolve_import_from(module, x) == Some(name),
+        _ => false,
+    })
+}
+
+/// A module `__getattr__` that still serves `symbol` from the module it was moved out of, by
+/// importing it from `target` when first asked for, with a `DeprecationWarning`. Importing lazily
+/// keeps the old module from importing the new one when it is loaded.
+fn deprecation_shim(source: ModuleName, target: ModuleName, symbol: &Name) -> String {
+    format!(
+        r#"def __getattr__(name: str) -> object:
+    if name == "{symbol}":
+        import warnings
+
+        from {target} import {symbol}
+
+        warnings.warn(
+            "`{source}.{symbol}` has moved, import it from `{target}` instead",
+            DeprecationWarning,
+            stacklevel=2,
+        )
+        return {symbol}
+    raise AttributeError(f"module {{__name__!r}} has no attribute {{name!r}}")
+"#
+    )
+}
+
+fn visit_import_froms(stmt: &Stmt, f: &mut impl FnMut(&StmtImportFrom)) {
+    if let Stmt::ImportFrom(x) = stmt {
+        f(x);
+    }
+    stmt.recurse(&mut |x| visit_import_froms(x, f));
+}
+
+/// Rewrite every `from source import symbol` in `module` to import from `target` instead.
+/// If `module` is the target itself, the import is dropped instead.
+fn rewrite_importers(
+    module: &Module,
+    source: ModuleName,
+    target: ModuleName,
+    symbol: &Name,
+    edits: &mut Vec<RefactorEdit>,
+) {
+    let ast = Ast::parse(module.contents()).0;
+    let contents = module.contents();
+    let mut rewrite = |x: &StmtImportFrom| {
+        if resolve_import_from(module, x) != Some(source) {
+            return;
+        }
+        let (moved, rest): (Vec<_>, Vec<_>) = x.names.iter().partition(|a| &a.name.id == symbol);
+        if moved.is_empty() {
+            return;
+        }
+        let indent = &contents
+            [line_start(contents, x.range().start().to_usize())..x.range().start().to_usize()];
+        let mut lines = Vec::new();
+        if !rest.is_empty() {
+            lines.push(format!(
+                "from {}{} import {}",
+                ".".repeat(x.level as usize),
+                x.module.as_ref().map_or("", |m| m.id.as_str()),
+                rest.iter()
+                    .map(|a| alias_text(a))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if module.name() != target {
+            for alias in moved {
+                lines.push(format!("from {target} import {}", alias_text(alias)));
+            }
+        }
+        if lines.is_empty() {
+            edits.push((
+                module.dupe(),
+                removal_range(contents, x.range()),
+                String::new(),
+            ));
+        } else {
+            edits.push((module.dupe(), x.range(), lines.join(&format!("\n{indent}"))));
+        }
+    };
+    for stmt in &ast.body {
+        visit_import_froms(stmt, &mut rewrite);
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Move the top-level function or class whose name is at `position` from `handle` into
+    /// `target`, appending the definition to the end of the target module.
+    ///
+    /// Imports needed by the moved code are added to the target, and `from ... import` statements
+    /// that refer to the symbol through the old module are rewritten to use the new one.
+    /// Qualified accesses such as `module.symbol` are left untouched. If `leave_reexport` is set,
+    /// the old module gets a `__getattr__` that keeps serving the symbol to existing callers, with
+    /// a `DeprecationWarning`.
+    ///
+    /// Returns `None` if there is no movable symbol at `position`, if the target module already
+    /// defines a symbol of the same name, or if the move would leave the two modules importing
+    /// each other, i.e. the old module still uses the symbol while the target imports from the
+    /// old module. With `leave_reexport`, it also returns `None` if the old module still uses the
+    /// symbol or already has a `__getattr__`, since the shim then couldn't warn.
+    pub fn move_symbol(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        target: &Handle,
+        leave_reexport: bool,
+    ) -> Option<Vec<RefactorEdit>> {
+        let source_info = self.get_module_info(handle)?;
+        let target_info = self.get_module_info(target)?;
+        if source_info.name() == target_info.name() {
+            return None;
+        }
+        let source_ast = Ast::parse(source_info.contents()).0;
+        let target_ast = Ast::parse(target_info.contents()).0;
+        let moved = source_ast.body.iter().find(|stmt| match stmt {
+            Stmt::FunctionDef(x) => x.name.range().contains_inclusive(position),
+            Stmt::ClassDef(x) => x.name.range().contains_inclusive(position),
+            _ => false,
+        })?;
+        let symbol = defined_names(moved).into_iter().next()?.clone();
+        if target_ast
+            .body
+            .iter()
+            .any(|stmt| defined_names(stmt).contains(&&symbol))
+        {
+            return None;
+        }
+
+        let mut edits = Vec::new();
+        let source_contents = source_info.contents();
+        let target_contents = target_info.contents();
+        let removed = removal_range(source_contents, moved.range());
+        // The range of a definition includes its decorators.
+        let moved_text = source_info.code_at(moved.range());
+
+        // 1. Add the definition, and whatever it depends on, to the target.
+        let existing_lines = target_contents.lines().collect::<HashSet<_>>();
+        let imports = dependency_imports(&source_info, &source_ast, moved, &symbol)
+            .into_iter()
+            .filter(|line| !existing_lines.contains(line.as_str()))
+            .collect::<Vec<_>>();
+        if !imports.is_empty() {
+            let mut text = imports.join("\n");
+            text.push('\n');
+            edits.push((
+                target_info.dupe(),
+                TextRange::at(import_insertion_point(&target_ast), TextSize::new(0)),
+                text,
+            ));
+        }
+        let separator = if target_contents.trim().is_empty() {
+            ""
+        } else if target_contents.ends_with('\n') {
+            "\n\n"
+        } else {
+            "\n\n\n"
+        };
+        edits.push((
+            target_info.dupe(),
+            TextRange::at(
+                TextSize::new(target_contents.len() as u32),
+                TextSize::new(0),
+            ),
+            format!("{separator}{moved_text}\n"),
+        ));
+
+        // 2. Remove the definition from the source, importing it back if it is still needed.
+        let mut still_used = SmallSet::new();
+        for stmt in &source_ast.body {
+            if stmt.range() != moved.range() {
+                stmt.visit(&mut |x| collect_loaded_names(x, &mut still_used));
+            }
+        }
+        let still_used = still_used.contains(&symbol);
+        if leave_reexport {
+            if still_used || is_bound_at_top_level(&source_ast, "__getattr__") {
+                return None;
+            }
+            let shim = deprecation_shim(source_info.name(), target_info.name(), &symbol);
+            let text = if removed.end().to_usize() == source_contents.len() {
+                let separator = if removed.start() == TextSize::new(0) {
+                    ""
+                } else {
+                    "\n\n"
+                };
+                format!("{separator}{shim}")
+            } else {
+                format!("{shim}\n\n")
+            };
+            edits.push((source_info.dupe(), removed, text));
+        } else {
+            if still_used {
+                let source_prefix = format!("from {} import ", source_info.name());
+                if imports.iter().any(|x| x.starts_with(&source_prefix))
+                    || imports_module(&target_info, &target_ast, source_info.name())
+                {
+                    return None;
+                }
+                edits.push((
+                    source_info.dupe(),
+                    TextRange::at(import_insertion_point(&source_ast), TextSize::new(0)),
+                    format!("from {} import {symbol}\n", target_info.name()),
+                ));
+            }
+            edits.push((source_info.dupe(), removed, String::new()));
+        }
+
+        // 3. Point every other importer of the symbol at the new module.
+        for rdep in self.transitive_importers(handle) {
+            if rdep.path().as_path() == handle.path().as_path() {
+                continue;
+            }
+            let module = if rdep.path().as_path() == target.path().as_path() {
+                target_info.dupe()
+            } else {
+                match self.get_module_info(&rdep) {
+                    Some(module) => module,
+                    None => continue,
+                }
+            };
+            rewrite_importers(
+                &module,
+                source_info.name(),
+                target_info.name(),
+                &symbol,
+                &mut edits,
+            );
+        }
+        Some(edits)
+    }
+}
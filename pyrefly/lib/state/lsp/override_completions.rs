@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Completions for the name of a method being defined in a class body, offering the methods of
+//! the base classes with their signatures, so overriding a method doesn't mean copying it.
+
+use lsp_types::CompletionItem;
+use lsp_types::CompletionItemKind;
+use lsp_types::InsertTextFormat;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Identifier;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+
+use crate::binding::binding::Key;
+use crate::state::state::Transaction;
+use crate::types::types::Type;
+
+/// Decorators that don't change how a method is called, so an override can leave them out.
+fn is_plain_method_decorator(x: &Expr) -> bool {
+    let name = match x {
+        Expr::Name(x) => &x.id,
+        Expr::Attribute(x) => &x.attr.id,
+        _ => return false,
+    };
+    matches!(name.as_str(), "abstractmethod" | "override")
+}
+
+/// Escape `$`, `}` and `\`, which are special in snippets.
+fn escape_snippet(x: &str) -> String {
+    let mut res = String::with_capacity(x.len());
+    for c in x.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// The signature of the method whose name is at `range`, from its name to its return annotation,
+/// as in `f(self, x: int) -> str`. Overloaded methods, properties and other methods whose
+/// decorators change how they are called have no signature an override could copy.
+fn method_signature(module: &Module, ast: &ModModule, range: TextRange) -> Option<String> {
+    let fun = Ast::locate_node(ast, range.start())
+        .into_iter()
+        .find_map(|x| match x {
+            AnyNodeRef::StmtFunctionDef(x) if x.name.range == range => Some(x),
+            _ => None,
+        })?;
+    if !fun
+        .decorator_list
+        .iter()
+        .all(|x| is_plain_method_decorator(&x.expression))
+    {
+        return None;
+    }
+    let end = fun
+        .returns
+        .as_ref()
+        .map_or(fun.parameters.range().end(), |x| x.range().end());
+    Some(
+        module
+            .code_at(TextRange::new(range.start(), end))
+            .to_owned(),
+    )
+}
+
+impl<'a> Transaction<'a> {
+    /// Add a completion for each method of the base classes of the class `method_name` is being
+    /// defined in, which completes the name with the signature of the method. Clients that
+    /// support snippets also get a placeholder body.
+    pub(crate) fn add_override_completions(
+        &self,
+        handle: &Handle,
+        method_name: &Identifier,
+        supports_snippets: bool,
+        completions: &mut Vec<CompletionItem>,
+    ) {
+        let (Some(module_info), Some(ast), Some(bindings)) = (
+            self.get_module_info(handle),
+            self.get_ast(handle),
+            self.get_bindings(handle),
+        ) else {
+            return;
+        };
+        // If the parameters are already written, completing them again would duplicate them.
+        if module_info.contents().as_str()[method_name.range.end().to_usize()..]
+            .trim_start()
+            .starts_with('(')
+        {
+            return;
+        }
+        let Some(class_def) = Ast::locate_node(&ast, method_name.range.start())
+            .into_iter()
+            .find_map(|x| match x {
+                AnyNodeRef::StmtClassDef(x) => Some(x),
+                _ => None,
+            })
+        else {
+            return;
+        };
+        let key = Key::Definition(ShortIdentifier::new(&class_def.name));
+        if !bindings.is_valid_key(&key) {
+            return;
+        }
+        let Some(Type::ClassDef(cls)) = self.get_type(handle, &key) else {
+            return;
+        };
+        let Some(bases) = self.ad_hoc_solve(handle, |solver| {
+            solver
+                .get_mro_for_class(&cls)
+                .ancestors_no_object()
+                .to_vec()
+        }) else {
+            return;
+        };
+        // The names defined by the class, or by a base class earlier in the MRO, which hides the
+        // methods of the same name of later bases.
+        let mut seen: SmallSet<Name> = cls
+            .fields()
+            .filter(|x| **x != method_name.id)
+            .cloned()
+            .collect();
+        let mut asts = SmallMap::new();
+        for base in bases {
+            let base = base.class_object();
+            let module = base.module();
+            for name in base.fields() {
+                // Name-mangled methods can't be overridden.
+                let is_mangled = name.starts_with("__") && !name.ends_with("__");
+                if is_mangled || !seen.insert(name.clone()) {
+                    continue;
+                }
+                let Some(range) = base.field_decl_range(name) else {
+                    continue;
+                };
+                let ast = asts
+                    .entry(module.name())
+                    .or_insert_with(|| Ast::parse(module.contents()).0);
+                let Some(signature) = method_signature(module, ast, range) else {
+                    continue;
+                };
+                let (insert_text, insert_text_format) = if supports_snippets {
+                    (
+                        format!("{}:\n    ${{0:...}}", escape_snippet(&signature)),
+                        InsertTextFormat::SNIPPET,
+                    )
+                } else {
+                    (format!("{signature}:"), InsertTextFormat::PLAIN_TEXT)
+                };
+                completions.push(CompletionItem {
+                    label: name.to_string(),
+                    kind: Some(CompletionItemKind::METHOD),
+                    detail: Some(format!("Override {}.{name}", base.name())),
+                    insert_text: Some(insert_text),
+                    insert_text_format: Some(insert_text_format),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Insert the inferred type of an unannotated variable, parameter or function return as its
+//! annotation, along with the imports the annotation needs, either at the cursor or for every
+//! function of a module.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+use crate::binding::binding::Key;
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::state::Transaction;
+use crate::types::simplify::unions;
+use crate::types::types::Type;
+
+impl<'a> Transaction<'a> {
+    /// The annotation of the inferred type of `key`, as ` -> T` for the return type of a function
+    /// and `: T` otherwise, or `None` if nothing more precise than `Any` can be written.
+    fn inferred_annotation(&self, handle: &Handle, key: &Key, is_async: bool) -> Option<String> {
+        let is_return = matches!(key, Key::ReturnType(_));
+        let mut ty = self.get_type(handle, key)?;
+        if is_return {
+            // The return type of an `async` function is the coroutine calling it makes.
+            if is_async
+                && let Some(Some((_, _, return_ty))) =
+                    self.ad_hoc_solve(handle, |solver| solver.unwrap_coroutine(&ty))
+            {
+                ty = return_ty;
+            }
+        } else {
+            // An unannotated parameter is `Any` as well as the type of its default, and a variable
+            // may be assigned other values of the same type as the literal it starts with.
+            if let Type::Union(xs) = ty {
+                ty = unions(xs.into_iter().filter(|x| !x.is_any()).collect());
+            }
+            ty = ty.promote_literals(&self.get_stdlib(handle));
+        }
+        if ty.is_any() || ty.is_error() || ty.is_never() {
+            return None;
+        }
+        let annotation = ty.as_inlay_hint_string(handle.sys_info().version());
+        Some(if is_return {
+            format!(" -> {annotation}")
+        } else {
+            format!(": {annotation}")
+        })
+    }
+
+    /// A code action annotating the name at `range` with its inferred type: the target of an
+    /// assignment to a single name, a parameter with a default, or a function without a return
+    /// annotation.
+    pub fn add_type_annotation_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+        import_format: ImportFormat,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let bindings = self.get_bindings(handle)?;
+        let covers = |x: TextRange| x.contains_range(range);
+        let nodes = Ast::locate_node(&ast, range.start());
+        let (key, position, is_async) = nodes.iter().find_map(|node| match node {
+            AnyNodeRef::StmtAssign(x) => match &*x.targets {
+                [Expr::Name(target)] if covers(target.range) => Some((
+                    Key::Definition(ShortIdentifier::expr_name(target)),
+                    target.range.end(),
+                    false,
+                )),
+                _ => None,
+            },
+            AnyNodeRef::ParameterWithDefault(x)
+                if x.default.is_some()
+                    && x.parameter.annotation.is_none()
+                    && covers(x.parameter.name.range) =>
+            {
+                Some((
+                    Key::Definition(ShortIdentifier::new(&x.parameter.name)),
+                    x.parameter.name.range.end(),
+                    false,
+                ))
+            }
+            AnyNodeRef::StmtFunctionDef(x) if x.returns.is_none() && covers(x.name.range) => {
+                Some((
+                    Key::ReturnType(ShortIdentifier::new(&x.name)),
+                    x.parameters.range.end(),
+                    x.is_async,
+                ))
+            }
+            _ => None,
+        })?;
+        if !bindings.is_valid_key(&key) {
+            return None;
+        }
+        let text = self.inferred_annotation(handle, &key, is_async)?;
+        let mut edits = vec![(module_info.dupe(), TextRange::empty(position), text.clone())];
+        edits.extend(
+            self.inlay_hint_import_edits(handle, &text, import_format)
+                .into_iter()
+                .map(|(position, import)| {
+                    (
+                        module_info.dupe(),
+                        TextRange::at(position, TextSize::new(0)),
+                        import,
+                    )
+                }),
+        );
+        Some(vec![("Add type annotation".to_owned(), edits)])
+    }
+
+    /// A source action annotating every function of the module of `handle` with its inferred
+    /// types, where the annotations are missing: the parameters with defaults and the returns.
+    /// Functions with only `...` as a body, such as overloads and protocol methods, are skipped.
+    pub fn add_type_annotations_code_actions(
+        &self,
+        handle: &Handle,
+        import_format: ImportFormat,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        if module_info.path().is_interface() {
+            return None;
+        }
+        let ast = self.get_ast(handle)?;
+        let bindings = self.get_bindings(handle)?;
+
+        fn functions<'b>(x: &'b Stmt, res: &mut Vec<&'b StmtFunctionDef>) {
+            if let Stmt::FunctionDef(x) = x {
+                res.push(x);
+            }
+            x.recurse(&mut |x| functions(x, res));
+        }
+        let mut defs = Vec::new();
+        for stmt in &ast.body {
+            functions(stmt, &mut defs);
+        }
+
+        let mut annotations = Vec::new();
+        for def in defs {
+            if let [Stmt::Expr(x)] = &*def.body
+                && x.value.is_ellipsis_literal_expr()
+            {
+                continue;
+            }
+            for x in def.parameters.iter_non_variadic_params() {
+                if x.default.is_some() && x.parameter.annotation.is_none() {
+                    annotations.push((
+                        Key::Definition(ShortIdentifier::new(&x.parameter.name)),
+                        x.parameter.name.range.end(),
+                        false,
+                    ));
+                }
+            }
+            if def.returns.is_none() {
+                annotations.push((
+                    Key::ReturnType(ShortIdentifier::new(&def.name)),
+                    def.parameters.range.end(),
+                    def.is_async,
+                ));
+            }
+        }
+
+        let mut edits = Vec::new();
+        let mut imports = SmallSet::new();
+        for (key, position, is_async) in annotations {
+            if !bindings.is_valid_key(&key) {
+                continue;
+            }
+            let Some(text) = self.inferred_annotation(handle, &key, is_async) else {
+                continue;
+            };
+            imports.extend(self.inlay_hint_import_edits(handle, &text, import_format));
+            edits.push((module_info.dupe(), TextRange::empty(position), text));
+        }
+        if edits.is_empty() {
+            return None;
+        }
+        edits.extend(imports.into_iter().map(|(position, import)| {
+            (
+                module_info.dupe(),
+                TextRange::at(position, TextSize::new(0)),
+                import,
+            )
+        }));
+        Some(vec![(
+            "Add type annotations to all functions".to_owned(),
+            edits,
+        )])
+    }
+}
@@ -0,0 +1,624 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::dunder;
+use pyrefly_python::keywords::get_keywords;
+use pyrefly_python::module::Module;
+use pyrefly_python::qname::QName;
+use pyrefly_util::prelude::VecExt;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprName;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::StmtReturn;
+use ruff_python_ast::helpers::is_docstring_stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::import_edit;
+use crate::state::lsp::quick_fixes::is_bound_at_top_level;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+use crate::types::types::Type;
+
+fn is_named_tuple_base(base: &Expr) -> bool {
+    match base {
+        Expr::Name(x) => x.id == "NamedTuple",
+        Expr::Attribute(x) => x.attr.id == "NamedTuple",
+        _ => false,
+    }
+}
+
+/// The attributes of a `NamedTuple` that a dataclass doesn't have.
+const NAMED_TUPLE_ATTRIBUTES: &[&str] =
+    &["_asdict", "_field_defaults", "_fields", "_make", "_replace"];
+
+/// Whether `x` uses the value of an expression for which `is_cls` holds as a tuple: indexing,
+/// unpacking or iterating over it, or using the attributes only a `NamedTuple` has.
+fn expr_uses_as_tuple(x: &Expr, is_cls: &dyn Fn(&Expr) -> bool) -> bool {
+    let generators = match x {
+        Expr::Subscript(x) => return is_cls(&x.value),
+        Expr::Starred(x) => return is_cls(&x.value),
+        Expr::Attribute(x) => {
+            return NAMED_TUPLE_ATTRIBUTES.contains(&x.attr.id.as_str()) && is_cls(&x.value);
+        }
+        Expr::Call(x) => {
+            let takes_tuple = x
+                .func
+                .as_name_expr()
+                .is_some_and(|f| matches!(f.id.as_str(), "iter" | "len" | "list" | "tuple"));
+            return takes_tuple && x.arguments.args.first().is_some_and(is_cls);
+        }
+        Expr::ListComp(x) => &x.generators,
+        Expr::SetComp(x) => &x.generators,
+        Expr::DictComp(x) => &x.generators,
+        Expr::Generator(x) => &x.generators,
+        _ => return false,
+    };
+    generators.iter().any(|x| is_cls(&x.iter))
+}
+
+/// Whether `x`, or an expression in it, uses the value of an expression for which `is_cls`
+/// holds as a tuple.
+fn any_expr_uses_as_tuple(x: &Expr, is_cls: &dyn Fn(&Expr) -> bool) -> bool {
+    let mut found = expr_uses_as_tuple(x, is_cls);
+    x.recurse(&mut |x| found = found || any_expr_uses_as_tuple(x, is_cls));
+    found
+}
+
+/// Whether `x`, or a statement or expression in it, uses the value of an expression for which
+/// `is_cls` holds as a tuple.
+fn stmt_uses_as_tuple(x: &Stmt, is_cls: &dyn Fn(&Expr) -> bool) -> bool {
+    let mut found = match x {
+        Stmt::Assign(x) => {
+            x.targets
+                .iter()
+                .any(|t| matches!(t, Expr::Tuple(_) | Expr::List(_)))
+                && is_cls(&x.value)
+        }
+        Stmt::For(x) => is_cls(&x.iter),
+        _ => false,
+    };
+    x.visit(&mut |x: &Expr| found = found || any_expr_uses_as_tuple(x, is_cls));
+    x.recurse(&mut |x: &Stmt| found = found || stmt_uses_as_tuple(x, is_cls));
+    found
+}
+
+/// Whether `x` uses the name of `name` anywhere other than at `name` itself.
+fn is_name_used(x: &Expr, name: &ExprName) -> bool {
+    let mut used = matches!(x, Expr::Name(x) if x.id == name.id && x.range != name.range);
+    x.recurse(&mut |x| used = used || is_name_used(x, name));
+    used
+}
+
+/// The edit removing the import of `NamedTuple` once `base` no longer uses it, unless something
+/// else still does.
+fn remove_named_tuple_import(
+    module_info: &Module,
+    ast: &ModModule,
+    base: &Expr,
+) -> Option<RefactorEdit> {
+    let Expr::Name(base) = base else {
+        return None;
+    };
+    let mut used = false;
+    ast.visit(&mut |x: &Expr| used = used || is_name_used(x, base));
+    if used {
+        return None;
+    }
+    let (import, i) = ast.body.iter().find_map(|stmt| {
+        let Stmt::ImportFrom(import) = stmt else {
+            return None;
+        };
+        let i = import
+            .names
+            .iter()
+            .position(|x| x.asname.as_ref().unwrap_or(&x.name).id == base.id)?;
+        Some((import, i))
+    })?;
+    let names = &import.names;
+    let range = if names.len() == 1 {
+        // Remove the whole statement, along with its line.
+        let contents = module_info.contents();
+        let start = line_start(contents, import.range.start().to_usize());
+        let end = import.range.end().to_usize();
+        let end = if contents[end..].starts_with('\n') {
+            end + 1
+        } else {
+            end
+        };
+        TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32))
+    } else if i + 1 < names.len() {
+        TextRange::new(names[i].range.start(), names[i + 1].range.start())
+    } else {
+        TextRange::new(names[i - 1].range.end(), names[i].range.end())
+    };
+    Some((module_info.dupe(), range, String::new()))
+}
+
+/// A field of the dataclass replacing an `__init__` that only assigns its parameters to
+/// attributes of `self`.
+struct InitField<'a> {
+    /// The parameter the field is assigned from, which names the keyword argument callers pass.
+    param: &'a str,
+    /// The attribute the parameter is assigned to, which names the field and the keyword
+    /// argument of the generated `__init__`.
+    field: &'a str,
+    annotation: &'a Expr,
+    default: Option<&'a Expr>,
+}
+
+/// The fields of a dataclass with the same constructor as `init`, in the order of its
+/// parameters. Only an `__init__` whose parameters are all annotated, plain (neither positional-
+/// nor keyword-only, nor variadic) and assigned as they are to distinct attributes of `self` can
+/// be replaced, along with nothing else.
+fn init_fields(init: &StmtFunctionDef) -> Option<Vec<InitField<'_>>> {
+    let parameters = &init.parameters;
+    if !init.decorator_list.is_empty()
+        || init.is_async
+        || !parameters.posonlyargs.is_empty()
+        || parameters.vararg.is_some()
+        || !parameters.kwonlyargs.is_empty()
+        || parameters.kwarg.is_some()
+    {
+        return None;
+    }
+    let (this, params) = parameters.args.split_first()?;
+    if this.default.is_some() {
+        return None;
+    }
+    let this = this.parameter.name.id.as_str();
+    let mut fields = Vec::with_capacity(params.len());
+    for param in params {
+        // The values `dataclass` refuses as defaults, since they would be shared between instances.
+        if let Some(
+            Expr::List(_)
+            | Expr::Dict(_)
+            | Expr::Set(_)
+            | Expr::ListComp(_)
+            | Expr::DictComp(_)
+            | Expr::SetComp(_),
+        ) = param.default.as_deref()
+        {
+            return None;
+        }
+        fields.push((param, None));
+    }
+    for stmt in &init.body {
+        if is_docstring_stmt(stmt) {
+            continue;
+        }
+        let Stmt::Assign(assign) = stmt else {
+            return None;
+        };
+        let ([Expr::Attribute(target)], Expr::Name(value)) = (&*assign.targets, &*assign.value)
+        else {
+            return None;
+        };
+        if !target.value.as_name_expr().is_some_and(|x| x.id == this)
+            || fields
+                .iter()
+                .any(|(_, field)| *field == Some(target.attr.id.as_str()))
+        {
+            return None;
+        }
+        let (_, field) = fields
+            .iter_mut()
+            .find(|(param, _)| param.parameter.name.id == value.id)?;
+        if field.is_some() {
+            return None;
+        }
+        *field = Some(target.attr.id.as_str());
+    }
+    fields
+        .into_iter()
+        .map(|(param, field)| {
+            Some(InitField {
+                param: param.parameter.name.id.as_str(),
+                field: field?,
+                annotation: param.parameter.annotation.as_deref()?,
+                default: param.default.as_deref(),
+            })
+        })
+        .collect()
+}
+
+/// All the calls in `x`, including `x` itself.
+fn calls_in<'a>(x: &'a Expr, res: &mut Vec<&'a ExprCall>) {
+    if let Expr::Call(x) = x {
+        res.push(x);
+    }
+    x.recurse(&mut |x| calls_in(x, res));
+}
+
+/// All the `return` statements belonging to a function body, skipping nested scopes.
+fn collect_returns<'a>(stmt: &'a Stmt, returns: &mut Vec<&'a StmtReturn>) {
+    match stmt {
+        Stmt::FunctionDef(_) | Stmt::ClassDef(_) => {}
+        Stmt::Return(x) => returns.push(x),
+        _ => stmt.recurse(&mut |x| collect_returns(x, returns)),
+    }
+}
+
+fn is_dict_annotation(annotation: &Expr) -> bool {
+    let base = match annotation {
+        Expr::Subscript(x) => &*x.value,
+        x => x,
+    };
+    match base {
+        Expr::Name(x) => x.id == "dict" || x.id == "Dict",
+        Expr::Attribute(x) => x.attr.id == "Dict",
+        _ => false,
+    }
+}
+
+/// `get_user_info` becomes `GetUserInfoResult`.
+fn result_type_name(function_name: &str) -> String {
+    let mut name = function_name
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+    name.push_str("Result");
+    name
+}
+
+impl<'a> Transaction<'a> {
+    /// Code actions that rewrite the class or function named at `range` into a more precisely
+    /// typed construct: a plain class or `NamedTuple` into a dataclass, or a function returning
+    /// string-keyed dict literals into one returning a `TypedDict`.
+    pub fn convert_typed_construct_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+        import_format: ImportFormat,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let mut actions = Vec::new();
+        for stmt in &ast.body {
+            match stmt {
+                Stmt::ClassDef(cls) if cls.name.range().contains_inclusive(range.start()) => {
+                    if let Some(edits) = self.class_to_dataclass(handle, &module_info, &ast, cls) {
+                        actions.push(("Convert to dataclass".to_owned(), edits));
+                    }
+                }
+                Stmt::FunctionDef(fun) if fun.name.range().contains_inclusive(range.start()) => {
+                    if let Some((name, edits)) = self.dict_function_to_typed_dict(
+                        handle,
+                        &module_info,
+                        &ast,
+                        fun,
+                        import_format,
+                    ) {
+                        actions.push((format!("Convert return type to TypedDict `{name}`"), edits));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(actions)
+    }
+
+    /// Add `@dataclass` to a plain class whose fields all have defaults (so existing `C()` calls
+    /// keep working), or turn a `NamedTuple` into a frozen dataclass. The generated `__init__`
+    /// takes the fields in the same order as the `NamedTuple` constructor, but a dataclass is not a
+    /// tuple, so a `NamedTuple` is only converted if no module depending on it uses it as one.
+    ///
+    /// A plain class may instead have an `__init__` that only assigns its parameters to `self`,
+    /// which is replaced by the fields it assigns. Where a parameter and its attribute are named
+    /// differently, the keyword arguments of the calls to the class are renamed to match the
+    /// generated `__init__`.
+    fn class_to_dataclass(
+        &self,
+        handle: &Handle,
+        module_info: &Module,
+        ast: &ModModule,
+        cls: &StmtClassDef,
+    ) -> Option<Vec<RefactorEdit>> {
+        if !cls.decorator_list.is_empty() {
+            return None;
+        }
+        let named_tuple_base = match &cls.arguments {
+            None => None,
+            Some(arguments) if !arguments.keywords.is_empty() => return None,
+            Some(arguments) => match &*arguments.args {
+                [] => None,
+                [base] if is_named_tuple_base(base) => Some(base),
+                _ => return None,
+            },
+        };
+        let mut has_field = false;
+        let mut init = None;
+        for stmt in &cls.body {
+            match stmt {
+                Stmt::AnnAssign(x) if x.target.is_name_expr() => {
+                    if named_tuple_base.is_none() && x.value.is_none() {
+                        return None;
+                    }
+                    has_field = true;
+                }
+                Stmt::FunctionDef(x) if x.name.id == dunder::INIT => {
+                    if named_tuple_base.is_some() || init.is_some() {
+                        return None;
+                    }
+                    init = Some(x);
+                }
+                Stmt::FunctionDef(_) | Stmt::Assign(_) | Stmt::Pass(_) => {}
+                _ if is_docstring_stmt(stmt) => {}
+                _ => return None,
+            }
+        }
+        let mut edits = Vec::new();
+        if let Some(init) = init {
+            // The fields would come before those declared in the class, changing the order of
+            // the generated `__init__`.
+            if has_field {
+                return None;
+            }
+            let fields = init_fields(init)?;
+            if fields.is_empty() {
+                return None;
+            }
+            let contents = module_info.contents();
+            let start = line_start(contents, init.range().start().to_usize());
+            let indent = &contents[start..init.range().start().to_usize()];
+            let declarations = fields
+                .iter()
+                .map(|field| {
+                    let annotation = module_info.code_at(field.annotation.range());
+                    match field.default {
+                        Some(default) => format!(
+                            "{}: {annotation} = {}",
+                            field.field,
+                            module_info.code_at(default.range())
+                        ),
+                        None => format!("{}: {annotation}", field.field),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(&format!("\n{indent}"));
+            edits.push((module_info.dupe(), init.range(), declarations));
+            edits.extend(self.rename_constructor_keywords(handle, cls, &fields)?);
+            has_field = true;
+        }
+        if !has_field || (named_tuple_base.is_some() && self.is_used_as_tuple(handle, cls)) {
+            return None;
+        }
+        edits.extend(import_edit(module_info, ast, "dataclasses", &["dataclass"]));
+        let decorator = if named_tuple_base.is_some() {
+            "@dataclass(frozen=True)\n"
+        } else {
+            "@dataclass\n"
+        };
+        edits.push((
+            module_info.dupe(),
+            TextRange::at(cls.range().start(), TextSize::new(0)),
+            decorator.to_owned(),
+        ));
+        if let Some(base) = named_tuple_base
+            && let Some(arguments) = &cls.arguments
+        {
+            edits.push((module_info.dupe(), arguments.range(), String::new()));
+            edits.extend(remove_named_tuple_import(module_info, ast, base));
+        }
+        Some(edits)
+    }
+
+    /// Whether `x`, in the module `importer`, is the class `cls` of the module of `handle`.
+    fn is_class(&self, handle: &Handle, cls: &StmtClassDef, importer: &Handle, x: &Expr) -> bool {
+        matches!(
+            self.get_type_trace(importer, x.range()),
+            Some(Type::ClassDef(x))
+                if x.qname().module_name() == handle.module()
+                    && x.qname().range() == cls.name.range()
+        )
+    }
+
+    /// The edits renaming the keyword arguments of the calls to `cls`, in the module of `handle`
+    /// or one depending on it, from the parameters of its `__init__` to the `fields` they are
+    /// assigned to. `None` if there are calls we can't update: those passing `**kwargs`, or
+    /// calling `__init__` directly, such as `super().__init__(...)` in a subclass.
+    fn rename_constructor_keywords(
+        &self,
+        handle: &Handle,
+        cls: &StmtClassDef,
+        fields: &[InitField],
+    ) -> Option<Vec<RefactorEdit>> {
+        if fields.iter().all(|x| x.param == x.field) {
+            return Some(Vec::new());
+        }
+        let mut edits = Vec::new();
+        for importer in self.transitive_importers(handle) {
+            let (Some(module_info), Some(ast)) =
+                (self.get_module_info(&importer), self.get_ast(&importer))
+            else {
+                continue;
+            };
+            let mut calls = Vec::new();
+            ast.visit(&mut |x: &Expr| calls_in(x, &mut calls));
+            for call in calls {
+                if let Expr::Attribute(x) = &*call.func
+                    && x.attr.id == dunder::INIT
+                {
+                    return None;
+                }
+                if !self.is_class(handle, cls, &importer, &call.func) {
+                    continue;
+                }
+                for keyword in &call.arguments.keywords {
+                    let Some(arg) = &keyword.arg else {
+                        return None;
+                    };
+                    if let Some(field) = fields.iter().find(|x| arg.id == x.param)
+                        && field.param != field.field
+                    {
+                        edits.push((module_info.dupe(), arg.range(), field.field.to_owned()));
+                    }
+                }
+            }
+        }
+        Some(edits)
+    }
+
+    /// Whether the module of `handle`, or one depending on it, uses an instance of the class
+    /// `cls`, or the class itself, as a tuple.
+    fn is_used_as_tuple(&self, handle: &Handle, cls: &StmtClassDef) -> bool {
+        let is_cls = |qname: &QName| {
+            qname.module_name() == handle.module() && qname.range() == cls.name.range()
+        };
+        self.transitive_importers(handle)
+            .into_iter()
+            .any(|importer| {
+                let Some(ast) = self.get_ast(&importer) else {
+                    return false;
+                };
+                let is_cls = |x: &Expr| match self.get_type_trace(&importer, x.range()) {
+                    Some(Type::ClassType(x)) => is_cls(x.qname()),
+                    Some(Type::ClassDef(x)) => is_cls(x.qname()),
+                    _ => false,
+                };
+                ast.body.iter().any(|x| stmt_uses_as_tuple(x, &is_cls))
+            })
+    }
+
+    /// Generate a `TypedDict` describing the dict literals a function returns, and use it as the
+    /// return annotation. Only offered when every `return` is a dict literal with the same set of
+    /// distinct string keys, all of which are valid identifiers.
+    fn dict_function_to_typed_dict(
+        &self,
+        handle: &Handle,
+        module_info: &Module,
+        ast: &ModModule,
+        fun: &StmtFunctionDef,
+        import_format: ImportFormat,
+    ) -> Option<(String, Vec<RefactorEdit>)> {
+        if let Some(returns) = &fun.returns
+            && !is_dict_annotation(returns)
+        {
+            return None;
+        }
+        let mut returns = Vec::new();
+        for stmt in &fun.body {
+            collect_returns(stmt, &mut returns);
+        }
+        if returns.is_empty() {
+            return None;
+        }
+        let version = handle.sys_info().version();
+        let keywords = get_keywords(version);
+        let stdlib = self.get_stdlib(handle);
+        // Keys in the order of the first `return`, each with the types it is assigned.
+        let mut fields: Vec<(&str, SmallSet<String>)> = Vec::new();
+        for (i, ret) in returns.iter().enumerate() {
+            let Some(Expr::Dict(dict)) = ret.value.as_deref() else {
+                return None;
+            };
+            let mut keys = SmallSet::new();
+            for item in &dict.items {
+                let Some(Expr::StringLiteral(key)) = &item.key else {
+                    return None;
+                };
+                let key = key.value.to_str();
+                let mut chars = key.chars();
+                if !chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                    || !chars.all(|c| c.is_alphanumeric() || c == '_')
+                    || keywords.iter().any(|k| *k == key)
+                    || !keys.insert(key)
+                {
+                    return None;
+                }
+                let ty = self
+                    .get_type_trace(handle, item.value.range())?
+                    .promote_literals(&stdlib)
+                    .as_inlay_hint_string(version);
+                if i == 0 {
+                    fields.push((key, SmallSet::new()));
+                }
+                let (_, types) = fields.iter_mut().find(|(k, _)| *k == key)?;
+                types.insert(ty);
+            }
+            if keys.len() != fields.len() || fields.iter().any(|(k, _)| !keys.contains(k)) {
+                return None;
+            }
+        }
+
+        let name = result_type_name(&fun.name.id);
+        if is_bound_at_top_level(ast, &name) {
+            return None;
+        }
+        let fields = fields.into_map(|(key, types)| {
+            (
+                key,
+                types
+                    .iter()
+                    .map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            )
+        });
+        // Some types, such as those of functions, can't be written as annotations.
+        if fields
+            .iter()
+            .any(|(_, ty)| Ast::parse_expr(ty, TextSize::new(0)).is_err())
+        {
+            return None;
+        }
+        let mut class_text = format!("class {name}(TypedDict):\n");
+        for (key, ty) in &fields {
+            class_text.push_str(&format!("    {key}: {ty}\n"));
+        }
+        class_text.push_str("\n\n");
+
+        let mut edits = Vec::new();
+        edits.extend(import_edit(module_info, ast, "typing", &["TypedDict"]));
+        // Fields may share the names they need imported, which should only be imported once.
+        let imports = fields
+            .iter()
+            .flat_map(|(_, ty)| {
+                self.inlay_hint_import_edits(handle, &format!(": {ty}"), import_format)
+            })
+            .collect::<SmallSet<_>>();
+        edits.extend(imports.into_iter().map(|(position, import)| {
+            (
+                module_info.dupe(),
+                TextRange::at(position, TextSize::new(0)),
+                import,
+            )
+        }));
+        edits.push((
+            module_info.dupe(),
+            TextRange::at(fun.range().start(), TextSize::new(0)),
+            class_text,
+        ));
+        match &fun.returns {
+            Some(returns) => edits.push((module_info.dupe(), returns.range(), name.clone())),
+            None => edits.push((
+                module_info.dupe(),
+                TextRange::at(fun.parameters.range().end(), TextSize::new(0)),
+                format!(" -> {name}"),
+            )),
+        }
+        Some((name, edits))
+    }
+}
@@ -0,0 +1,318 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Extract the interface of a class, its public attributes and method signatures, into a
+//! `Protocol` or an abstract base class, so code can depend on the interface rather than the
+//! class.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::dunder;
+use pyrefly_python::module::Module;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Operator;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::import_edit;
+use crate::state::lsp::quick_fixes::is_bound_at_top_level;
+use crate::state::state::Transaction;
+
+/// A method of the extracted interface: the decorators it keeps and its `def` line, up to the
+/// colon.
+pub(super) struct Method<'a> {
+    pub(super) decorators: Vec<&'a str>,
+    pub(super) header: &'a str,
+}
+
+/// The public attributes and methods of a class, with the annotations of the attributes.
+#[derive(Default)]
+struct Members<'a> {
+    attributes: Vec<(String, String)>,
+    methods: Vec<Method<'a>>,
+}
+
+impl<'a> Members<'a> {
+    fn add_attribute(&mut self, name: &str, annotation: String) {
+        if !name.starts_with('_') && !self.attributes.iter().any(|(x, _)| x == name) {
+            self.attributes.push((name.to_owned(), annotation));
+        }
+    }
+
+    /// The class text of the interface, with `@abstractmethod` on every method if `is_abstract`.
+    fn to_class(&self, name: &str, base: &str, is_abstract: bool) -> String {
+        let mut text = format!("class {name}({base}):\n");
+        for (attribute, annotation) in &self.attributes {
+            text.push_str(&format!("    {attribute}: {annotation}\n"));
+        }
+        for method in &self.methods {
+            for decorator in &method.decorators {
+                text.push_str(&format!("    @{decorator}\n"));
+            }
+            if is_abstract {
+                text.push_str("    @abstractmethod\n");
+            }
+            text.push_str(&format!("    {}: ...\n", method.header));
+        }
+        text.push_str("\n\n");
+        text
+    }
+}
+
+/// Decorators that change how a method is called or accessed, so are part of its interface.
+fn is_interface_decorator(x: &Expr) -> bool {
+    match x {
+        Expr::Name(x) => matches!(
+            x.id.as_str(),
+            "property" | "staticmethod" | "classmethod" | "overload"
+        ),
+        Expr::Attribute(x) => matches!(x.attr.id.as_str(), "setter" | "deleter" | "overload"),
+        _ => false,
+    }
+}
+
+pub(super) fn method<'a>(module_info: &'a Module, fun: &StmtFunctionDef) -> Method<'a> {
+    let decorators = fun
+        .decorator_list
+        .iter()
+        .filter(|x| is_interface_decorator(&x.expression))
+        .map(|x| module_info.code_at(x.expression.range()))
+        .collect();
+    // The `def`, or `async def`, follows the decorators.
+    let start = fun
+        .decorator_list
+        .last()
+        .map_or(fun.range().start(), |x| x.range().end());
+    let end = fun
+        .returns
+        .as_ref()
+        .map_or(fun.parameters.range().end(), |x| x.range().end());
+    Method {
+        decorators,
+        header: module_info.code_at(TextRange::new(start, end)).trim_start(),
+    }
+}
+
+/// The ranges of `name` in parameter annotations of functions outside of `skip`, either as the
+/// whole annotation or as a member of a union.
+fn parameter_annotations_naming(
+    stmt: &Stmt,
+    name: &str,
+    skip: TextRange,
+    res: &mut Vec<TextRange>,
+) {
+    fn in_union(x: &Expr, name: &str, res: &mut Vec<TextRange>) {
+        match x {
+            Expr::Name(x) if x.id == name => res.push(x.range),
+            Expr::BinOp(x) if x.op == Operator::BitOr => {
+                in_union(&x.left, name, res);
+                in_union(&x.right, name, res);
+            }
+            _ => {}
+        }
+    }
+    if stmt.range() == skip {
+        return;
+    }
+    if let Stmt::FunctionDef(fun) = stmt {
+        for p in fun.parameters.iter() {
+            if let Some(annotation) = p.annotation() {
+                in_union(annotation, name, res);
+            }
+        }
+    }
+    stmt.recurse(&mut |x| parameter_annotations_naming(x, name, skip, res));
+}
+
+/// The attributes `__init__` assigns to `self`, with their annotations, or else their inferred
+/// types.
+fn self_attributes(
+    module_info: &Module,
+    init: &StmtFunctionDef,
+    inferred: &impl Fn(&Expr) -> Option<String>,
+    members: &mut Members,
+) {
+    let Some(this) = init
+        .parameters
+        .posonlyargs
+        .iter()
+        .chain(&init.parameters.args)
+        .next()
+    else {
+        return;
+    };
+    let this = &this.parameter.name.id;
+    let attribute = |x: &Expr| {
+        let x = x.as_attribute_expr()?;
+        matches!(&*x.value, Expr::Name(base) if &base.id == this).then(|| x.attr.id.clone())
+    };
+    for stmt in &init.body {
+        match stmt {
+            Stmt::AnnAssign(x) => {
+                if let Some(name) = attribute(&x.target) {
+                    members
+                        .add_attribute(&name, module_info.code_at(x.annotation.range()).to_owned());
+                }
+            }
+            Stmt::Assign(x) => {
+                if let [target] = &*x.targets
+                    && let Some(name) = attribute(target)
+                    && let Some(ty) = inferred(&x.value)
+                {
+                    members.add_attribute(&name, ty);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Code actions that extract the interface of the class named at `range` into a `Protocol`,
+    /// optionally used in place of the class in parameter annotations, or into an abstract base
+    /// class the class then inherits from.
+    pub fn extract_protocol_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let cls = ast.body.iter().find_map(|x| match x {
+            Stmt::ClassDef(x) if x.name.range().contains_inclusive(range.start()) => Some(x),
+            _ => None,
+        })?;
+        // The members of a generic class would refer to type parameters the interface doesn't
+        // have.
+        if cls.type_params.is_some() || cls.bases().iter().any(|x| x.is_subscript_expr()) {
+            return None;
+        }
+        let members = self.members(handle, &module_info, cls);
+        if members.attributes.is_empty() && members.methods.is_empty() {
+            return None;
+        }
+        let insert_at = TextRange::at(cls.range().start(), TextSize::new(0));
+        let mut actions = Vec::new();
+
+        let protocol = format!("{}Protocol", cls.name.id);
+        if !is_bound_at_top_level(&ast, &protocol) {
+            let mut edits = Vec::new();
+            edits.extend(import_edit(&module_info, &ast, "typing", &["Protocol"]));
+            edits.push((
+                module_info.dupe(),
+                insert_at,
+                members.to_class(&protocol, "Protocol", false),
+            ));
+            actions.push((format!("Extract protocol `{protocol}`"), edits.clone()));
+            let mut annotations = Vec::new();
+            for stmt in &ast.body {
+                parameter_annotations_naming(stmt, &cls.name.id, cls.range(), &mut annotations);
+            }
+            if !annotations.is_empty() {
+                edits.extend(
+                    annotations
+                        .into_iter()
+                        .map(|x| (module_info.dupe(), x, protocol.clone())),
+                );
+                actions.push((
+                    format!("Extract protocol `{protocol}` and use it in parameter annotations"),
+                    edits,
+                ));
+            }
+        }
+
+        let abstract_class = format!("Abstract{}", cls.name.id);
+        if !is_bound_at_top_level(&ast, &abstract_class) {
+            let mut edits = Vec::new();
+            edits.extend(import_edit(
+                &module_info,
+                &ast,
+                "abc",
+                &["ABC", "abstractmethod"],
+            ));
+            edits.push((
+                module_info.dupe(),
+                insert_at,
+                members.to_class(&abstract_class, "ABC", true),
+            ));
+            edits.push(match &cls.arguments {
+                None => (
+                    module_info.dupe(),
+                    TextRange::at(cls.name.range().end(), TextSize::new(0)),
+                    format!("({abstract_class})"),
+                ),
+                Some(arguments) => {
+                    let close = arguments.range().end() - TextSize::new(1);
+                    let text = if arguments.args.is_empty() && arguments.keywords.is_empty() {
+                        abstract_class.clone()
+                    } else {
+                        format!(", {abstract_class}")
+                    };
+                    (
+                        module_info.dupe(),
+                        TextRange::at(close, TextSize::new(0)),
+                        text,
+                    )
+                }
+            });
+            actions.push((
+                format!("Extract abstract base class `{abstract_class}`"),
+                edits,
+            ));
+        }
+        Some(actions)
+    }
+
+    /// The public members of a class: annotated or assigned class attributes, attributes
+    /// assigned to `self` in `__init__`, and methods.
+    fn members<'m>(
+        &self,
+        handle: &Handle,
+        module_info: &'m Module,
+        cls: &StmtClassDef,
+    ) -> Members<'m> {
+        let stdlib = self.get_stdlib(handle);
+        let inferred = |x: &Expr| {
+            let ty = self.get_type_trace(handle, x.range())?;
+            (!ty.is_any()).then(|| ty.promote_literals(&stdlib).to_string())
+        };
+        let mut members = Members::default();
+        for stmt in &cls.body {
+            match stmt {
+                Stmt::AnnAssign(x) => {
+                    if let Expr::Name(name) = &*x.target {
+                        members.add_attribute(
+                            &name.id,
+                            module_info.code_at(x.annotation.range()).to_owned(),
+                        );
+                    }
+                }
+                Stmt::Assign(x) => {
+                    if let [Expr::Name(name)] = &*x.targets
+                        && let Some(ty) = inferred(&x.value)
+                    {
+                        members.add_attribute(&name.id, ty);
+                    }
+                }
+                Stmt::FunctionDef(fun) if fun.name.id == dunder::INIT => {
+                    self_attributes(module_info, fun, &inferred, &mut members);
+                }
+                Stmt::FunctionDef(fun) if !fun.name.id.starts_with('_') => {
+                    members.methods.push(method(module_info, fun));
+                }
+                _ => {}
+            }
+        }
+        members
+    }
+}
@@ -0,0 +1,373 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Extract the selected expression into a local variable, annotated with its inferred type and
+//! assigned just before the statement that uses it, or replace every occurrence of the
+//! expression in the enclosing scope with the variable.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::keywords::get_keywords;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::comparable::ComparableExpr;
+use ruff_python_ast::name::Name;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_expr;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+
+/// The blocks of statements nested in `stmt`, not counting the bodies of functions and classes,
+/// which run at another time.
+pub(super) fn blocks(stmt: &Stmt) -> Vec<&[Stmt]> {
+    match stmt {
+        Stmt::For(x) => vec![&*x.body, &*x.orelse],
+        Stmt::While(x) => vec![&*x.body, &*x.orelse],
+        Stmt::If(x) => std::iter::once(&*x.body)
+            .chain(x.elif_else_clauses.iter().map(|x| &*x.body))
+            .collect(),
+        Stmt::With(x) => vec![&*x.body],
+        Stmt::Match(x) => x.cases.iter().map(|x| &*x.body).collect(),
+        Stmt::Try(x) => std::iter::once(&*x.body)
+            .chain(x.handlers.iter().map(|x| match x {
+                ExceptHandler::ExceptHandler(x) => &*x.body,
+            }))
+            .chain([&*x.orelse, &*x.finalbody])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub(super) fn in_block(block: &[Stmt], range: TextRange) -> bool {
+    block.iter().any(|x| x.range().contains_range(range))
+}
+
+/// The body of the innermost function or class containing `range`, or else `body`.
+fn scope_body(body: &[Stmt], range: TextRange) -> &[Stmt] {
+    fn nested(block: &[Stmt], range: TextRange) -> Option<&[Stmt]> {
+        let stmt = block.iter().find(|x| x.range().contains_range(range))?;
+        match stmt {
+            Stmt::FunctionDef(x) if in_block(&x.body, range) => Some(scope_body(&x.body, range)),
+            Stmt::ClassDef(x) if in_block(&x.body, range) => Some(scope_body(&x.body, range)),
+            _ => blocks(stmt).into_iter().find_map(|x| nested(x, range)),
+        }
+    }
+    nested(body, range).unwrap_or(body)
+}
+
+/// The statement before which a variable used at all of `ranges` can be assigned: the first
+/// statement containing one of them, in the innermost block containing all of them.
+fn insertion_point<'a>(block: &'a [Stmt], ranges: &[TextRange]) -> Option<&'a Stmt> {
+    let stmt = block
+        .iter()
+        .find(|x| ranges.iter().any(|r| x.range().contains_range(*r)))?;
+    match blocks(stmt)
+        .into_iter()
+        .find(|block| ranges.iter().all(|r| in_block(block, *r)))
+    {
+        Some(block) => insertion_point(block, ranges),
+        None => Some(stmt),
+    }
+}
+
+/// Whether `range`, in the header of `stmt` rather than one of its blocks, is evaluated once
+/// whenever `stmt` runs, so can be computed by a statement before it.
+fn is_evaluated_once(stmt: &Stmt, range: TextRange) -> bool {
+    if blocks(stmt).iter().any(|x| in_block(x, range)) {
+        return true;
+    }
+    match stmt {
+        // The test of a `while` is evaluated on every iteration, and those of `elif` clauses and
+        // `except` clauses, and the guards of `case` clauses, only sometimes.
+        Stmt::While(_) | Stmt::Try(_) => false,
+        Stmt::If(x) => x.test.range().contains_range(range),
+        Stmt::Match(x) => x.subject.range().contains_range(range),
+        // Annotations are types, not values.
+        Stmt::AnnAssign(x) => !x.annotation.range().contains_range(range),
+        Stmt::FunctionDef(x) => {
+            !x.returns
+                .as_ref()
+                .is_some_and(|x| x.range().contains_range(range))
+                && !x
+                    .parameters
+                    .iter()
+                    .filter_map(|x| x.annotation())
+                    .any(|x| x.range().contains_range(range))
+        }
+        // The bases of a generic class may use its type parameters.
+        Stmt::ClassDef(x) => x.type_params.is_none(),
+        Stmt::TypeAlias(_) => false,
+        _ => true,
+    }
+}
+
+pub(super) fn is_load(x: &Expr) -> bool {
+    let ctx = match x {
+        Expr::Attribute(x) => x.ctx,
+        Expr::Subscript(x) => x.ctx,
+        Expr::Starred(x) => x.ctx,
+        Expr::Name(x) => x.ctx,
+        Expr::List(x) => x.ctx,
+        Expr::Tuple(x) => x.ctx,
+        _ => ExprContext::Load,
+    };
+    ctx == ExprContext::Load
+}
+
+/// The expressions of a scope equal to the extracted one, and where names are bound, skipping
+/// nested scopes.
+struct Occurrences<'a> {
+    expr: ComparableExpr<'a>,
+    ranges: Vec<TextRange>,
+    bindings: Vec<(&'a Name, TextSize)>,
+}
+
+impl<'a> SourceOrderVisitor<'a> for Occurrences<'a> {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        match x {
+            Stmt::FunctionDef(x) => self.bindings.push((&x.name.id, x.name.range.start())),
+            Stmt::ClassDef(x) => self.bindings.push((&x.name.id, x.name.range.start())),
+            _ => walk_stmt(self, x),
+        }
+    }
+
+    fn visit_expr(&mut self, x: &'a Expr) {
+        match x {
+            Expr::Name(name) if !is_load(x) => self.bindings.push((&name.id, name.range.start())),
+            _ if is_load(x) && ComparableExpr::from(x) == self.expr => {
+                self.ranges.push(x.range());
+            }
+            Expr::Lambda(_)
+            | Expr::ListComp(_)
+            | Expr::SetComp(_)
+            | Expr::DictComp(_)
+            | Expr::Generator(_) => {}
+            _ => walk_expr(self, x),
+        }
+    }
+}
+
+fn find_expr<'a>(x: &'a Expr, range: TextRange, res: &mut Option<&'a Expr>) {
+    if x.range() == range {
+        *res = Some(x);
+    } else if x.range().contains_range(range) {
+        x.recurse(&mut |x| find_expr(x, range, res));
+    }
+}
+
+pub(super) fn names_in<'a>(x: &'a Expr, res: &mut Vec<&'a Name>) {
+    if let Expr::Name(x) = x {
+        res.push(&x.id);
+    }
+    x.recurse(&mut |x| names_in(x, res));
+}
+
+/// `Point` becomes `point`, and `HTTPServer` becomes `http_server`.
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut res = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase()
+            && i > 0
+            && (!chars[i - 1].is_uppercase() || chars.get(i + 1).is_some_and(|x| x.is_lowercase()))
+        {
+            res.push('_');
+        }
+        res.extend(c.to_lowercase());
+    }
+    res
+}
+
+/// A name for a variable holding `x`: the instance of a class, as in `point = Point()`, what a
+/// getter gets, as in `user = get_user()`, or an attribute, as in `name = user.name`.
+fn variable_name(x: &Expr) -> String {
+    let name = match x {
+        Expr::Call(call) => match &*call.func {
+            Expr::Name(x) if x.id.starts_with(char::is_uppercase) => to_snake_case(&x.id),
+            Expr::Attribute(x) if x.attr.id.starts_with(char::is_uppercase) => {
+                to_snake_case(&x.attr.id)
+            }
+            Expr::Name(x) => x.id.strip_prefix("get_").unwrap_or_default().to_owned(),
+            Expr::Attribute(x) => x
+                .attr
+                .id
+                .strip_prefix("get_")
+                .unwrap_or_default()
+                .to_owned(),
+            _ => String::new(),
+        },
+        Expr::Attribute(x) => x.attr.id.trim_start_matches('_').to_owned(),
+        _ => String::new(),
+    };
+    if name.is_empty() || name.starts_with(|x: char| x.is_ascii_digit()) {
+        "value".to_owned()
+    } else {
+        name
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Code actions that extract the expression selected by `range` into a variable, either only
+    /// where it is selected or everywhere it occurs in the enclosing function, class or module.
+    pub fn extract_variable_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+        import_format: ImportFormat,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let contents = module_info.contents().as_str();
+        let selected = module_info.code_at(range);
+        let range = TextRange::at(
+            range.start() + TextSize::of(&selected[..selected.len() - selected.trim_start().len()]),
+            TextSize::of(selected.trim()),
+        );
+        if range.is_empty() {
+            return None;
+        }
+        let scope = scope_body(&ast.body, range);
+        let mut expr = None;
+        for stmt in scope {
+            stmt.visit(&mut |x: &Expr| find_expr(x, range, &mut expr));
+        }
+        let expr = expr?;
+        // Extracting a name gains nothing, and other expressions that aren't values can't be
+        // assigned.
+        if matches!(expr, Expr::Name(_) | Expr::Starred(_) | Expr::Slice(_)) || !is_load(expr) {
+            return None;
+        }
+        let stmt = insertion_point(scope, &[range])?;
+        if !is_evaluated_once(stmt, range)
+            || matches!(stmt, Stmt::Expr(x) if x.value.range() == range)
+        {
+            return None;
+        }
+
+        let mut occurrences = Occurrences {
+            expr: ComparableExpr::from(expr),
+            ranges: Vec::new(),
+            bindings: Vec::new(),
+        };
+        occurrences.visit_body(scope);
+        // The selection is in a nested scope, such as a lambda, whose variables we can't use.
+        if !occurrences.ranges.contains(&range) {
+            return None;
+        }
+        let mut names = Vec::new();
+        names_in(expr, &mut names);
+        // Whether the names `expr` uses keep their values from the start of `stmt` to `end`, so
+        // occurrences up to `end` can be computed before `stmt`.
+        let unchanged_until = |stmt: &Stmt, end: TextSize| {
+            !occurrences
+                .bindings
+                .iter()
+                .any(|(name, x)| stmt.range().start() <= *x && *x < end && names.contains(name))
+        };
+
+        let keywords = get_keywords(handle.sys_info().version());
+        let base = variable_name(expr);
+        let is_taken = |x: &str| {
+            keywords.iter().any(|k| *k == x)
+                || contents
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|word| word == x)
+        };
+        let name = std::iter::once(base.clone())
+            .chain((2..).map(|i| format!("{base}{i}")))
+            .find(|x| !is_taken(x))
+            .unwrap();
+
+        let stdlib = self.get_stdlib(handle);
+        let annotation = self
+            .get_type_trace(handle, range)
+            .filter(|x| !x.is_any())
+            .map(|x| {
+                x.promote_literals(&stdlib)
+                    .as_inlay_hint_string(handle.sys_info().version())
+            })
+            // Some types, such as those of functions, can't be written as annotations.
+            .filter(|x| Ast::parse_expr(x, TextSize::new(0)).is_ok())
+            .map(|x| format!(": {x}"))
+            .unwrap_or_default();
+        let mut imports = self
+            .inlay_hint_import_edits(handle, &annotation, import_format)
+            .into_iter()
+            .map(|(position, text)| {
+                (
+                    module_info.dupe(),
+                    TextRange::at(position, TextSize::new(0)),
+                    text,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut value = module_info.code_at(range).to_owned();
+        // Inside brackets, an expression can span lines, but a statement can't.
+        if value.contains('\n') {
+            value = format!("({value})");
+        }
+        let assignment = |stmt: &Stmt| {
+            let start = stmt.range().start();
+            let indent = &contents[line_start(contents, start.to_usize())..start.to_usize()];
+            let text = if indent.trim().is_empty() {
+                format!("{name}{annotation} = {value}\n{indent}")
+            } else {
+                // The statement follows another on the same line, after a semicolon.
+                format!("{name}{annotation} = {value}; ")
+            };
+            (
+                module_info.dupe(),
+                TextRange::at(start, TextSize::new(0)),
+                text,
+            )
+        };
+
+        let mut actions = Vec::new();
+        let mut edits = imports.clone();
+        edits.push(assignment(stmt));
+        edits.push((module_info.dupe(), range, name.clone()));
+        actions.push((format!("Extract variable `{name}`"), edits));
+
+        // Leave the occurrences that can't be computed before the statement they are in.
+        let ranges = occurrences
+            .ranges
+            .iter()
+            .copied()
+            .filter(|x| insertion_point(scope, &[*x]).is_some_and(|s| is_evaluated_once(s, *x)))
+            .collect::<Vec<_>>();
+        if ranges.len() > 1
+            && let Some(stmt) = insertion_point(scope, &ranges)
+            && unchanged_until(stmt, ranges.last().unwrap().start())
+        {
+            imports.push(assignment(stmt));
+            imports.extend(
+                ranges
+                    .iter()
+                    .map(|x| (module_info.dupe(), *x, name.clone())),
+            );
+            actions.push((
+                format!(
+                    "Extract variable `{name}` and replace all {} occurrences",
+                    ranges.len()
+                ),
+                imports,
+            ));
+        }
+        Some(actions)
+    }
+}
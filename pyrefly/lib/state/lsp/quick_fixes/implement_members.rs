@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Implement the members a class inherits without an implementation: the abstract methods of its
+//! abstract base classes, and the methods and attributes of the protocols it subclasses that
+//! have no default.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
+
+use crate::binding::binding::Key;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::extract_protocol::method;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+use crate::types::class::Class;
+use crate::types::types::Type;
+
+/// Whether `body` has no implementation, being only a docstring, `...` or `pass`.
+fn is_stub_body(body: &[Stmt]) -> bool {
+    body.iter().all(|x| match x {
+        Stmt::Pass(_) => true,
+        Stmt::Expr(x) => matches!(&*x.value, Expr::EllipsisLiteral(_) | Expr::StringLiteral(_)),
+        _ => false,
+    })
+}
+
+/// The implementation of the member whose name is at `range` in `module`, for a class body
+/// indented by `indent`: a method raising `NotImplementedError` with the same signature, or an
+/// attribute with the same annotation. If `only_stubs`, members with a default are skipped.
+fn implementation(
+    module: &Module,
+    ast: &ModModule,
+    range: TextRange,
+    indent: &str,
+    only_stubs: bool,
+) -> Option<String> {
+    Ast::locate_node(ast, range.start())
+        .into_iter()
+        .find_map(|x| match x {
+            AnyNodeRef::StmtFunctionDef(fun) if fun.name.range == range => {
+                if only_stubs && !is_stub_body(&fun.body) {
+                    return None;
+                }
+                let method = method(module, fun);
+                let mut text = String::new();
+                for decorator in method.decorators {
+                    if decorator != "overload" {
+                        text.push_str(&format!("{indent}@{decorator}\n"));
+                    }
+                }
+                text.push_str(&format!(
+                    "{indent}{}:\n{indent}    raise NotImplementedError\n",
+                    method.header
+                ));
+                Some(text)
+            }
+            AnyNodeRef::StmtAnnAssign(x)
+                if let Expr::Name(target) = &*x.target
+                    && target.range == range =>
+            {
+                if only_stubs && x.value.is_some() {
+                    return None;
+                }
+                Some(format!(
+                    "{indent}{}: {}\n",
+                    target.id,
+                    module.code_at(x.annotation.range())
+                ))
+            }
+            _ => None,
+        })
+}
+
+impl<'a> Transaction<'a> {
+    /// A code action for the class named at `range` that adds every abstract method it inherits
+    /// without overriding, and every member without a default of the protocols it subclasses, at
+    /// the end of its body.
+    pub fn implement_members_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let bindings = self.get_bindings(handle)?;
+        let class_def = ast.body.iter().find_map(|x| match x {
+            Stmt::ClassDef(x) if x.name.range().contains_inclusive(range.start()) => Some(x),
+            _ => None,
+        })?;
+        let key = Key::Definition(ShortIdentifier::new(&class_def.name));
+        if !bindings.is_valid_key(&key) {
+            return None;
+        }
+        let Some(Type::ClassDef(cls)) = self.get_type(handle, &key) else {
+            return None;
+        };
+        // Each missing member, with the class it comes from and whether it is only missing if it
+        // has no default there, as for the members of protocols.
+        let missing: Vec<(Name, Class, bool)> = self.ad_hoc_solve(handle, |solver| {
+            if solver.get_metadata_for_class(&cls).is_protocol() {
+                return Vec::new();
+            }
+            let mro = solver.get_mro_for_class(&cls);
+            let ancestors = mro.ancestors_no_object();
+            let defining = |name: &Name| {
+                ancestors
+                    .iter()
+                    .map(|x| x.class_object())
+                    .find(|x| x.contains(name))
+            };
+            let mut missing = Vec::new();
+            for name in solver
+                .get_abstract_members_for_class(&cls)
+                .unimplemented_abstract_methods()
+            {
+                if let Some(base) = defining(name) {
+                    missing.push((name.clone(), base.dupe(), false));
+                }
+            }
+            for ancestor in ancestors {
+                let metadata = solver.get_metadata_for_class(ancestor.class_object());
+                let Some(protocol) = metadata.protocol_metadata() else {
+                    continue;
+                };
+                for name in &protocol.members {
+                    if cls.contains(name) || missing.iter().any(|(x, _, _)| x == name) {
+                        continue;
+                    }
+                    if let Some(base) = defining(name)
+                        && solver.get_metadata_for_class(base).is_protocol()
+                    {
+                        missing.push((name.clone(), base.dupe(), true));
+                    }
+                }
+            }
+            missing
+        })?;
+
+        // The members go after the last statement of the body, indented like the first.
+        let contents = module_info.contents().as_str();
+        let first = class_def.body.first()?.range().start().to_usize();
+        let indent = &contents[line_start(contents, first)..first];
+        if !indent.trim().is_empty() {
+            return None;
+        }
+        let mut asts = SmallMap::new();
+        let members = missing
+            .iter()
+            .filter_map(|(name, base, only_stubs)| {
+                let range = base.field_decl_range(name)?;
+                let module = base.module();
+                let ast = asts
+                    .entry(module.name())
+                    .or_insert_with(|| Ast::parse(module.contents()).0);
+                implementation(module, ast, range, indent, *only_stubs)
+            })
+            .collect::<Vec<_>>();
+        if members.is_empty() {
+            return None;
+        }
+        let last = class_def.body.last()?.range().end().to_usize();
+        let (position, mut text) = match contents[last..].find('\n') {
+            Some(i) => (last + i + 1, String::new()),
+            None => (contents.len(), "\n".to_owned()),
+        };
+        for member in members {
+            text.push('\n');
+            text.push_str(&member);
+        }
+        Some(vec![(
+            "Implement missing members".to_owned(),
+            vec![(
+                module_info.dupe(),
+                TextRange::empty(TextSize::try_from(position).unwrap()),
+                text,
+            )],
+        )])
+    }
+}
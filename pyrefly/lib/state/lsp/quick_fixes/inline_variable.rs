@@ -0,0 +1,291 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Inline a local variable that is assigned exactly once, replacing each of its uses with the
+//! assigned value and removing the assignment.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprName;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_expr;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::extract_variable::blocks;
+use crate::state::lsp::quick_fixes::extract_variable::in_block;
+use crate::state::lsp::quick_fixes::extract_variable::is_load;
+use crate::state::lsp::quick_fixes::extract_variable::names_in;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+
+/// The innermost function whose own body, rather than that of a nested class, contains `range`.
+fn enclosing_function(body: &[Stmt], range: TextRange) -> Option<&StmtFunctionDef> {
+    let stmt = body.iter().find(|x| x.range().contains_range(range))?;
+    match stmt {
+        Stmt::FunctionDef(x) if in_block(&x.body, range) => {
+            enclosing_function(&x.body, range).or(Some(x))
+        }
+        Stmt::ClassDef(x) if in_block(&x.body, range) => enclosing_function(&x.body, range),
+        _ => blocks(stmt)
+            .into_iter()
+            .find_map(|x| enclosing_function(x, range)),
+    }
+}
+
+/// The block of `body` that `stmt` is directly in.
+fn containing_block<'a>(body: &'a [Stmt], stmt: &Stmt) -> Option<&'a [Stmt]> {
+    if body.iter().any(|x| x.range() == stmt.range()) {
+        return Some(body);
+    }
+    let outer = body
+        .iter()
+        .find(|x| x.range().contains_range(stmt.range()))?;
+    blocks(outer)
+        .into_iter()
+        .find_map(|x| containing_block(x, stmt))
+}
+
+/// The statement of `block`, or of a block nested in it, that assigns a value to one of
+/// `references` and nothing else, along with the name it assigns and the value.
+fn find_assignment<'a>(
+    block: &'a [Stmt],
+    references: &[TextRange],
+) -> Option<(&'a Stmt, &'a ExprName, &'a Expr)> {
+    block.iter().find_map(|stmt| {
+        let (target, value) = match stmt {
+            Stmt::Assign(x) if let [target] = &*x.targets => (target, &*x.value),
+            Stmt::AnnAssign(x) if let Some(value) = &x.value => (&*x.target, &**value),
+            _ => {
+                return blocks(stmt)
+                    .into_iter()
+                    .find_map(|x| find_assignment(x, references));
+            }
+        };
+        match target {
+            Expr::Name(target) if references.contains(&target.range) => Some((stmt, target, value)),
+            _ => None,
+        }
+    })
+}
+
+/// Where names are bound in a scope, skipping nested scopes, and the expressions that are in a
+/// position where any expression can be written without parentheses.
+#[derive(Default)]
+struct ScopeNames<'a> {
+    bindings: Vec<(&'a Name, TextSize)>,
+    unbracketed: Vec<TextRange>,
+}
+
+impl<'a> SourceOrderVisitor<'a> for ScopeNames<'a> {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        match x {
+            Stmt::FunctionDef(x) => self.bindings.push((&x.name.id, x.name.range.start())),
+            Stmt::ClassDef(x) => self.bindings.push((&x.name.id, x.name.range.start())),
+            Stmt::Import(x) => self.bindings.extend(
+                x.names
+                    .iter()
+                    .map(|x| (&x.asname.as_ref().unwrap_or(&x.name).id, x.range.start())),
+            ),
+            Stmt::ImportFrom(x) => self.bindings.extend(
+                x.names
+                    .iter()
+                    .map(|x| (&x.asname.as_ref().unwrap_or(&x.name).id, x.range.start())),
+            ),
+            // The name refers to a variable of another scope.
+            Stmt::Global(x) => self
+                .bindings
+                .extend(x.names.iter().map(|x| (&x.id, x.range.start()))),
+            Stmt::Nonlocal(x) => self
+                .bindings
+                .extend(x.names.iter().map(|x| (&x.id, x.range.start()))),
+            _ => {
+                match x {
+                    Stmt::Assign(x) => self.unbracketed.push(x.value.range()),
+                    Stmt::AnnAssign(x) => {
+                        self.unbracketed.extend(x.value.as_ref().map(|x| x.range()))
+                    }
+                    Stmt::Return(x) => self.unbracketed.extend(x.value.as_ref().map(|x| x.range())),
+                    Stmt::Expr(x) => self.unbracketed.push(x.value.range()),
+                    Stmt::Try(x) => {
+                        for handler in &x.handlers {
+                            let ExceptHandler::ExceptHandler(handler) = handler;
+                            if let Some(name) = &handler.name {
+                                self.bindings.push((&name.id, name.range.start()));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                walk_stmt(self, x)
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, x: &'a Expr) {
+        match x {
+            Expr::Name(name) if !is_load(x) => self.bindings.push((&name.id, name.range.start())),
+            Expr::Lambda(_)
+            | Expr::ListComp(_)
+            | Expr::SetComp(_)
+            | Expr::DictComp(_)
+            | Expr::Generator(_) => {}
+            _ => {
+                match x {
+                    Expr::Call(x) => {
+                        self.unbracketed
+                            .extend(x.arguments.args.iter().map(|x| x.range()));
+                        self.unbracketed
+                            .extend(x.arguments.keywords.iter().map(|x| x.value.range()));
+                    }
+                    Expr::List(x) => self.unbracketed.extend(x.elts.iter().map(|x| x.range())),
+                    Expr::Set(x) => self.unbracketed.extend(x.elts.iter().map(|x| x.range())),
+                    Expr::Tuple(x) if x.parenthesized => {
+                        self.unbracketed.extend(x.elts.iter().map(|x| x.range()))
+                    }
+                    Expr::Dict(x) => self.unbracketed.extend(
+                        x.items
+                            .iter()
+                            .flat_map(|x| x.key.iter().chain([&x.value]))
+                            .map(|x| x.range()),
+                    ),
+                    _ => {}
+                }
+                walk_expr(self, x)
+            }
+        }
+    }
+}
+
+/// Whether `x` binds tighter than any operator, so can replace a name anywhere.
+fn is_atomic(x: &Expr) -> bool {
+    match x {
+        Expr::Name(_)
+        | Expr::Attribute(_)
+        | Expr::Call(_)
+        | Expr::Subscript(_)
+        | Expr::NumberLiteral(_)
+        | Expr::StringLiteral(_)
+        | Expr::BytesLiteral(_)
+        | Expr::FString(_)
+        | Expr::BooleanLiteral(_)
+        | Expr::NoneLiteral(_)
+        | Expr::EllipsisLiteral(_)
+        | Expr::List(_)
+        | Expr::Set(_)
+        | Expr::Dict(_)
+        | Expr::ListComp(_)
+        | Expr::SetComp(_)
+        | Expr::DictComp(_) => true,
+        Expr::Tuple(x) => x.parenthesized,
+        Expr::Generator(x) => x.parenthesized,
+        _ => false,
+    }
+}
+
+/// Whether `x` needs parentheses even where any other expression can be written without them.
+fn needs_parentheses(x: &Expr) -> bool {
+    match x {
+        Expr::Tuple(x) => !x.parenthesized,
+        Expr::Named(_) | Expr::Yield(_) | Expr::YieldFrom(_) | Expr::Starred(_) => true,
+        _ => false,
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// A code action that inlines the local variable at `range`, either where it is assigned or
+    /// where it is used, if it is assigned exactly once, by a statement that comes before all of
+    /// its uses in the same block.
+    pub fn inline_variable_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let contents = module_info.contents().as_str();
+        let references = self.find_local_references(handle, range.start());
+        let function = enclosing_function(&ast.body, *references.first()?)?;
+        let scope = &*function.body;
+        let mut names = ScopeNames::default();
+        names.visit_body(scope);
+
+        // Find the assignment among the references, which must be the only binding of the name.
+        let (stmt, target, value) = find_assignment(scope, &references)?;
+        if names
+            .bindings
+            .iter()
+            .any(|(name, x)| **name == target.id && *x != target.range.start())
+        {
+            return None;
+        }
+        let uses = references
+            .iter()
+            .copied()
+            .filter(|x| *x != target.range)
+            .collect::<Vec<_>>();
+        let last = uses.iter().map(|x| x.end()).max()?;
+        // The uses must all come after the assignment, in the rest of its block, and see the same
+        // values of the names it uses.
+        let block = containing_block(scope, stmt)?;
+        let rest = &block[block.iter().position(|x| x.range() == stmt.range())? + 1..];
+        if !uses.iter().all(|x| in_block(rest, *x)) {
+            return None;
+        }
+        let mut used = Vec::new();
+        names_in(value, &mut used);
+        if names
+            .bindings
+            .iter()
+            .any(|(name, x)| stmt.range().end() <= *x && *x < last && used.contains(name))
+        {
+            return None;
+        }
+
+        // Remove the whole lines of the assignment, which mustn't share them with other statements.
+        let start = line_start(contents, stmt.range().start().to_usize());
+        let end = contents[stmt.range().end().to_usize()..]
+            .find('\n')
+            .map_or(contents.len(), |i| stmt.range().end().to_usize() + i + 1);
+        let after = &contents[stmt.range().end().to_usize()..end];
+        if !contents[start..stmt.range().start().to_usize()]
+            .trim()
+            .is_empty()
+            || !(after.trim().is_empty() || after.trim_start().starts_with('#'))
+        {
+            return None;
+        }
+        let mut edits = vec![(
+            module_info.dupe(),
+            TextRange::new(
+                TextSize::try_from(start).unwrap(),
+                TextSize::try_from(end).unwrap(),
+            ),
+            String::new(),
+        )];
+        let text = module_info.code_at(value.range());
+        for x in uses {
+            let text = if needs_parentheses(value)
+                || (!is_atomic(value) && !names.unbracketed.contains(&x))
+            {
+                format!("({text})")
+            } else {
+                text.to_owned()
+            };
+            edits.push((module_info.dupe(), x, text));
+        }
+        Some(vec![(format!("Inline variable `{}`", target.id), edits)])
+    }
+}
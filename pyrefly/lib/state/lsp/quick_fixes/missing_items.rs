@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use itertools::Itertools;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
+
+use crate::config::error_kind::ErrorKind;
+use crate::error::error::Error;
+use crate::state::state::Transaction;
+use crate::types::callable::Param;
+use crate::types::callable::Params;
+use crate::types::literal::Lit;
+use crate::types::tuple::Tuple;
+use crate::types::types::Type;
+
+/// Something missing from a TypedDict literal or a call, which the error reports at the closing
+/// bracket, where it needs to be added.
+enum MissingItem<'a> {
+    Key(&'a str),
+    Argument(&'a str),
+}
+
+impl<'a> MissingItem<'a> {
+    fn from_error(error: &'a Error) -> Option<Self> {
+        // The name is the first thing in backticks in the message.
+        let name = |prefix: &str| {
+            error
+                .msg_header()
+                .strip_prefix(prefix)?
+                .split_once('`')
+                .map(|x| x.0)
+        };
+        match error.error_kind() {
+            ErrorKind::BadTypedDictKey => name("Missing required key `").map(Self::Key),
+            ErrorKind::MissingArgument => name("Missing argument `").map(Self::Argument),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'a str {
+        match self {
+            Self::Key(x) | Self::Argument(x) => x,
+        }
+    }
+
+    /// The item with a placeholder value of type `ty`, if known.
+    fn placeholder(&self, ty: Option<&Type>) -> String {
+        let value = ty.map_or_else(|| "...".to_owned(), placeholder_value);
+        match self {
+            Self::Key(key) => format!(
+                "\"{}\": {value}",
+                key.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            Self::Argument(name) => format!("{name}={value}"),
+        }
+    }
+}
+
+/// A value of type `ty`: the empty value of a builtin type, the only value of a literal, or
+/// `None` if that is allowed. Otherwise, `...`.
+fn placeholder_value(ty: &Type) -> String {
+    match ty {
+        Type::None => "None".to_owned(),
+        Type::Literal(lit @ (Lit::Str(_) | Lit::Int(_) | Lit::Bool(_))) => {
+            lit.to_string_escaped(false)
+        }
+        Type::Union(xs) if xs.iter().any(|x| matches!(x, Type::None)) => "None".to_owned(),
+        Type::ClassType(cls) => [
+            ("bool", "False"),
+            ("int", "0"),
+            ("float", "0.0"),
+            ("complex", "0j"),
+            ("str", "\"\""),
+            ("bytes", "b\"\""),
+            ("list", "[]"),
+            ("dict", "{}"),
+            ("set", "set()"),
+        ]
+        .iter()
+        .find(|(name, _)| cls.is_builtin(name))
+        .map_or("...", |(_, value)| *value)
+        .to_owned(),
+        Type::Tuple(Tuple::Unbounded(_)) => "()".to_owned(),
+        _ => "...".to_owned(),
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// The declared types of the keys of the TypedDict literal, or of the parameters of the
+    /// function or class called, whose closing bracket is at `closing`.
+    fn declared_item_types(
+        &self,
+        handle: &Handle,
+        closing: TextRange,
+        is_key: bool,
+    ) -> Option<SmallMap<Name, Type>> {
+        let ast = self.get_ast(handle)?;
+        let nodes = Ast::locate_node(&ast, closing.start());
+        if is_key {
+            let dict = nodes.iter().find_map(|x| match x {
+                AnyNodeRef::ExprDict(x) if x.range.end() == closing.end() => Some(x),
+                _ => None,
+            })?;
+            let Some(Type::TypedDict(typed_dict)) = self.get_type_trace(handle, dict.range) else {
+                return None;
+            };
+            let fields =
+                self.ad_hoc_solve(handle, |solver| solver.typed_dict_fields(&typed_dict))?;
+            Some(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| (name, field.ty))
+                    .collect(),
+            )
+        } else {
+            let call = nodes.iter().find_map(|x| match x {
+                AnyNodeRef::ExprCall(x) if x.arguments.range.end() == closing.end() => Some(x),
+                _ => None,
+            })?;
+            let callee = match self.get_type_trace(handle, call.func.range())? {
+                Type::ClassDef(cls) => self.ad_hoc_solve(handle, |solver| {
+                    solver.constructor_to_callable(
+                        &solver.promote_nontypeddict_silently_to_classtype(&cls),
+                    )
+                })?,
+                ty => ty,
+            };
+            let Params::List(params) = callee.to_callable()?.params else {
+                return None;
+            };
+            Some(
+                params
+                    .into_items()
+                    .into_iter()
+                    .filter_map(|x| match x {
+                        Param::Pos(name, ty, _) | Param::KwOnly(name, ty, _) => Some((name, ty)),
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    /// Insert every key missing from the TypedDict literal, or every argument missing from the
+    /// call, that an error at `range` reports, with a placeholder value of its declared type.
+    pub fn insert_missing_items_fix(
+        &self,
+        handle: &Handle,
+        module_info: &Module,
+        errors: &[Error],
+        range: TextRange,
+    ) -> Option<(String, Module, TextRange, String)> {
+        let (error_range, first) = errors.iter().find_map(|error| {
+            if error.range().contains_range(range) {
+                Some((error.range(), MissingItem::from_error(error)?))
+            } else {
+                None
+            }
+        })?;
+        let is_key = matches!(first, MissingItem::Key(_));
+        let closing = if is_key { "}" } else { ")" };
+        if module_info.code_at(error_range) != closing {
+            return None;
+        }
+        let items = errors
+            .iter()
+            .filter(|error| error.range() == error_range)
+            .filter_map(MissingItem::from_error)
+            .filter(|item| matches!(item, MissingItem::Key(_)) == is_key)
+            .collect::<Vec<_>>();
+
+        let contents = module_info.contents();
+        let before = contents[..error_range.start().to_usize()].trim_end();
+        let separator = match before.chars().last() {
+            Some('(' | '{') => "",
+            Some(',') => " ",
+            _ => ", ",
+        };
+        let title = format!(
+            "Insert missing {}: {}",
+            if is_key { "required keys" } else { "arguments" },
+            items
+                .iter()
+                .map(|item| format!("`{}`", item.name()))
+                .join(", ")
+        );
+        let types = self
+            .declared_item_types(handle, error_range, is_key)
+            .unwrap_or_default();
+        let insert_text = format!(
+            "{separator}{}",
+            items
+                .iter()
+                .map(|item| item.placeholder(types.get(item.name())))
+                .join(", ")
+        );
+        Some((
+            title,
+            module_info.dupe(),
+            TextRange::empty(TextSize::new(before.len() as u32)),
+            insert_text,
+        ))
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashSet;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModulePathDetails;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::helpers::is_docstring_stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::state::Transaction;
+
+pub mod add_type_annotation;
+pub mod convert_typed_construct;
+pub mod extract_protocol;
+pub mod extract_variable;
+pub mod implement_members;
+pub mod inline_variable;
+pub mod missing_items;
+pub mod none_guard;
+pub mod organize_imports;
+pub mod suppress;
+
+/// A single text edit produced by a refactoring: replace `TextRange` in `Module` with the `String`.
+pub type RefactorEdit = (Module, TextRange, String);
+
+/// Where new imports go: before the first statement that isn't the module docstring.
+/// This matches the placement used by the import quick fixes.
+pub(crate) fn import_insertion_point(ast: &ModModule) -> TextSize {
+    if let Some(first_stmt) = ast.body.iter().find(|stmt| !is_docstring_stmt(stmt)) {
+        first_stmt.range().start()
+    } else {
+        ast.range.end()
+    }
+}
+
+/// Whether `name` is bound at the top level of `ast`, either by a definition or an import.
+pub(crate) fn is_bound_at_top_level(ast: &ModModule, name: &str) -> bool {
+    ast.body.iter().any(|stmt| match stmt {
+        Stmt::FunctionDef(x) => x.name.id == name,
+        Stmt::ClassDef(x) => x.name.id == name,
+        Stmt::Assign(x) => x
+            .targets
+            .iter()
+            .any(|t| t.as_name_expr().is_some_and(|t| t.id == name)),
+        Stmt::AnnAssign(x) => x.target.as_name_expr().is_some_and(|t| t.id == name),
+        Stmt::ImportFrom(x) => x
+            .names
+            .iter()
+            .any(|a| a.asname.as_ref().unwrap_or(&a.name).id == name),
+        _ => false,
+    })
+}
+
+/// An edit adding `from module import names`, for those of `names` that aren't already available.
+fn import_edit(
+    module_info: &Module,
+    ast: &ModModule,
+    module: &str,
+    names: &[&str],
+) -> Option<RefactorEdit> {
+    let names = names
+        .iter()
+        .filter(|name| !is_bound_at_top_level(ast, name))
+        .copied()
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return None;
+    }
+    Some((
+        module_info.dupe(),
+        TextRange::at(import_insertion_point(ast), TextSize::new(0)),
+        format!("from {module} import {}\n", names.join(", ")),
+    ))
+}
+
+pub(crate) fn line_start(contents: &str, offset: usize) -> usize {
+    contents[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+impl<'a> Transaction<'a> {
+    /// The modules depending on the module of `handle`, directly or not, including itself, with
+    /// one handle per path. In-memory files have no rdeps, so those of the filesystem counterpart
+    /// are included too.
+    pub(crate) fn transitive_importers(&self, handle: &Handle) -> Vec<Handle> {
+        let mut rdeps = self.get_transitive_rdeps(handle.dupe());
+        if let ModulePathDetails::Memory(path) = handle.path().details() {
+            rdeps.extend(self.get_transitive_rdeps(Handle::new(
+                handle.module(),
+                ModulePath::filesystem((**path).clone()),
+                handle.sys_info().dupe(),
+            )));
+        }
+        let mut seen = HashSet::new();
+        rdeps
+            .into_iter()
+            .filter(|rdep| seen.insert(rdep.path().as_path().to_owned()))
+            .collect()
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Operator;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::quick_fixes::line_start;
+
+/// The innermost statement containing `range`, along with the innermost function enclosing that
+/// statement, if any.
+fn enclosing_stmt<'a>(
+    body: &'a [Stmt],
+    range: TextRange,
+    function: Option<&'a StmtFunctionDef>,
+) -> Option<(&'a Stmt, Option<&'a StmtFunctionDef>)> {
+    let stmt = body.iter().find(|x| x.range().contains_range(range))?;
+    let inner_function = match stmt {
+        Stmt::FunctionDef(x) => Some(x),
+        _ => function,
+    };
+    let mut inner = None;
+    stmt.recurse(&mut |x: &'a Stmt| {
+        if inner.is_none() {
+            inner = enclosing_stmt(std::slice::from_ref(x), range, inner_function);
+        }
+    });
+    Some(inner.unwrap_or((stmt, function)))
+}
+
+/// Whether the expression at `range` is evaluated as soon as `stmt` starts, so that a check placed
+/// right before the statement applies to it.
+fn evaluated_on_entry(stmt: &Stmt, range: TextRange) -> bool {
+    match stmt {
+        Stmt::If(x) => x.test.range().contains_range(range),
+        Stmt::For(x) => x.iter.range().contains_range(range),
+        Stmt::With(x) => x
+            .items
+            .iter()
+            .any(|item| item.context_expr.range().contains_range(range)),
+        Stmt::While(_)
+        | Stmt::Try(_)
+        | Stmt::Match(_)
+        | Stmt::FunctionDef(_)
+        | Stmt::ClassDef(_) => false,
+        _ => true,
+    }
+}
+
+/// Expressions whose `None`-ness can be narrowed by an `is not None` check, such as `x` or `x.y`.
+fn is_narrowable(x: &Expr) -> bool {
+    match x {
+        Expr::Name(_) => true,
+        Expr::Attribute(x) => is_narrowable(&x.value),
+        _ => false,
+    }
+}
+
+fn is_none(x: &Expr) -> bool {
+    matches!(x, Expr::NoneLiteral(_))
+}
+
+fn union_members<'a>(x: &'a Expr, res: &mut Vec<&'a Expr>) {
+    match x {
+        Expr::BinOp(x) if x.op == Operator::BitOr => {
+            union_members(&x.left, res);
+            union_members(&x.right, res);
+        }
+        _ => res.push(x),
+    }
+}
+
+/// The text of `annotation` with `None` removed, if it is an `X | None` or `Optional[X]` annotation.
+fn strip_none(module_info: &Module, annotation: &Expr) -> Option<String> {
+    match annotation {
+        Expr::Subscript(x)
+            if match &*x.value {
+                Expr::Name(x) => x.id == "Optional",
+                Expr::Attribute(x) => x.attr.id == "Optional",
+                _ => false,
+            } =>
+        {
+            Some(module_info.code_at(x.slice.range()).to_owned())
+        }
+        Expr::BinOp(_) => {
+            let mut members = Vec::new();
+            union_members(annotation, &mut members);
+            let rest = members
+                .iter()
+                .filter(|x| !is_none(x))
+                .map(|x| module_info.code_at(x.range()))
+                .collect::<Vec<_>>();
+            if rest.is_empty() || rest.len() == members.len() {
+                None
+            } else {
+                Some(rest.join(" | "))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The annotation of `name`, if it is a parameter of `function` or an annotated variable in
+/// `body`, and it isn't given a `None` value there (which would no longer type check).
+fn find_annotation<'a>(
+    body: &'a [Stmt],
+    function: Option<&'a StmtFunctionDef>,
+    name: &Name,
+) -> Option<&'a Expr> {
+    if let Some(function) = function
+        && let Some(param) = function
+            .parameters
+            .iter_non_variadic_params()
+            .find(|x| &x.parameter.name.id == name)
+    {
+        if param.default.as_deref().is_some_and(is_none) {
+            return None;
+        }
+        return param.parameter.annotation.as_deref();
+    }
+    fn f<'a>(stmt: &'a Stmt, name: &Name, res: &mut Option<&'a Expr>) {
+        match stmt {
+            Stmt::FunctionDef(_) | Stmt::ClassDef(_) => {}
+            Stmt::AnnAssign(x)
+                if res.is_none()
+                    && x.target.as_name_expr().is_some_and(|x| &x.id == name)
+                    && !x.value.as_deref().is_some_and(is_none) =>
+            {
+                *res = Some(&x.annotation);
+            }
+            _ => stmt.recurse(&mut |x| f(x, name, res)),
+        }
+    }
+    let mut res = None;
+    for stmt in function.map_or(body, |x| &x.body) {
+        f(stmt, name, &mut res);
+    }
+    res
+}
+
+/// Fixes for accessing an attribute on a value that might be `None`, where `error_range` is the
+/// range of the attribute access: guard the statement with `if x is not None:`, assert that `x` is
+/// not `None`, or remove `None` from the annotation of `x`.
+pub fn none_attribute_fixes(
+    module_info: &Module,
+    ast: &ModModule,
+    error_range: TextRange,
+) -> Vec<(String, Module, TextRange, String)> {
+    let mut fixes = Vec::new();
+    let Some(attribute) = Ast::locate_node(ast, error_range.start())
+        .into_iter()
+        .find_map(|node| match node {
+            AnyNodeRef::ExprAttribute(x) if x.range.contains_range(error_range) => Some(x),
+            _ => None,
+        })
+    else {
+        return fixes;
+    };
+    let base = &*attribute.value;
+    if !is_narrowable(base) {
+        return fixes;
+    }
+    let base_text = module_info.code_at(base.range());
+    let Some((stmt, function)) = enclosing_stmt(&ast.body, error_range, None) else {
+        return fixes;
+    };
+    let contents = module_info.contents();
+    let start = line_start(contents, stmt.range().start().to_usize());
+    let indent = &contents[start..stmt.range().start().to_usize()];
+    if evaluated_on_entry(stmt, error_range) && indent.trim().is_empty() {
+        let stmt_text = &contents[start..stmt.range().end().to_usize()];
+        let guarded = stmt_text
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_owned()
+                } else {
+                    format!("    {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        fixes.push((
+            format!("Add `if {base_text} is not None:` guard"),
+            module_info.dupe(),
+            TextRange::new(TextSize::new(start as u32), stmt.range().end()),
+            format!("{indent}if {base_text} is not None:\n{guarded}"),
+        ));
+        fixes.push((
+            format!("Add `assert {base_text} is not None`"),
+            module_info.dupe(),
+            TextRange::at(TextSize::new(start as u32), TextSize::new(0)),
+            format!("{indent}assert {base_text} is not None\n"),
+        ));
+    }
+    if let Expr::Name(name) = base
+        && let Some(annotation) = find_annotation(&ast.body, function, &name.id)
+        && let Some(new_annotation) = strip_none(module_info, annotation)
+    {
+        fixes.push((
+            format!("Change annotation of `{base_text}` to `{new_annotation}`"),
+            module_info.dupe(),
+            annotation.range(),
+            new_annotation,
+        ));
+    }
+    fixes
+}
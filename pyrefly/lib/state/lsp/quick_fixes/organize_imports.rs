@@ -0,0 +1,239 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Organize the imports at the top of a module the way isort and ruff do: remove those that are
+//! unused, merge the `from` imports of the same module, and sort them into blocks by where the
+//! modules come from.
+
+use std::cmp::Reverse;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Alias;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+
+use crate::state::ide::ImportSection;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::lsp::quick_fixes::line_start;
+use crate::state::state::Transaction;
+
+/// The longest line a merged `from` import is written on before it is wrapped, as in ruff.
+const LINE_LENGTH: usize = 88;
+
+/// The names read anywhere in `ast`, including from string annotations and `__all__`. A string
+/// counts if it parses as an expression, which may keep an unused import, but never removes a used
+/// one.
+fn used_names(ast: &ModModule) -> SmallSet<Name> {
+    fn f(x: &Expr, used: &mut SmallSet<Name>) {
+        match x {
+            Expr::Name(x) if x.ctx != ExprContext::Store => {
+                used.insert(x.id.clone());
+            }
+            Expr::StringLiteral(x)
+                if let Some(x) = x.as_single_part_string()
+                    && let Ok(x) = Ast::parse_type_literal(x) =>
+            {
+                f(&x, used);
+            }
+            _ => {}
+        }
+        x.recurse(&mut |x| f(x, used));
+    }
+    let mut used = SmallSet::new();
+    for stmt in &ast.body {
+        stmt.visit(&mut |x| f(x, &mut used));
+    }
+    used
+}
+
+/// `name`, or `name as alias`.
+fn alias(x: &Alias) -> String {
+    match &x.asname {
+        Some(asname) => format!("{} as {}", x.name.id, asname.id),
+        None => x.name.id.to_string(),
+    }
+}
+
+/// The order of names in a `from` import: constants, then classes, then everything else.
+fn member_key(name: &str) -> (u8, String, String) {
+    let kind = if name.len() > 1 && !name.chars().any(|c| c.is_lowercase()) {
+        0
+    } else if name.starts_with(|c: char| c.is_uppercase()) {
+        1
+    } else {
+        2
+    };
+    (kind, name.to_lowercase(), name.to_owned())
+}
+
+/// `from module import names`, wrapped with one name per line if it doesn't fit on one.
+fn from_import(module: &str, names: &[String]) -> String {
+    let line = format!("from {module} import {}\n", names.join(", "));
+    if line.len() <= LINE_LENGTH + 1 {
+        return line;
+    }
+    let mut text = format!("from {module} import (\n");
+    for name in names {
+        text.push_str(&format!("    {name},\n"));
+    }
+    text.push_str(")\n");
+    text
+}
+
+impl<'a> Transaction<'a> {
+    /// A source action organizing the imports at the top of the module of `handle`: unused
+    /// imports are removed, `from` imports of the same module merged, and the rest sorted into
+    /// blocks of `__future__`, standard library, third party and first party imports. Each block
+    /// has the `import` statements before the `from` imports, both sorted by module.
+    ///
+    /// Imports in `__init__.py` files and stubs may be re-exports, so are only sorted. Nothing is
+    /// offered if the imports are already organized, or have comments among them that would be
+    /// lost.
+    pub fn organize_imports_code_actions(
+        &self,
+        handle: &Handle,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let top_imports = self.top_imports(handle, &ast);
+        let contents = module_info.contents().as_str();
+        let start = line_start(contents, top_imports.first()?.0.range().start().to_usize());
+        let mut end = top_imports.last()?.0.range().end().to_usize();
+        if contents[start..end].contains('#') {
+            return None;
+        }
+
+        let path = module_info.path();
+        let keep_unused = path.is_init() || path.is_interface();
+        let used = used_names(&ast);
+        let is_used = |bound: &Name, x: &Alias| {
+            keep_unused
+                || used.contains(bound)
+                // `import x as x` and `from m import x as x` are explicit re-exports.
+                || x.asname.as_ref().is_some_and(|asname| asname.id == x.name.id)
+        };
+        let mut imports: SmallMap<ImportSection, SmallSet<String>> = SmallMap::new();
+        let mut from_imports: SmallMap<(ImportSection, u32, String), SmallSet<String>> =
+            SmallMap::new();
+        for (stmt, _) in &top_imports {
+            match stmt {
+                Stmt::Import(x) => {
+                    for name in &x.names {
+                        // `import a.b` binds `a`.
+                        let bound = match &name.asname {
+                            Some(asname) => asname.id.clone(),
+                            None => Name::new(name.name.id.split('.').next().unwrap_or_default()),
+                        };
+                        if is_used(&bound, name) {
+                            let section =
+                                self.import_section(handle, ModuleName::from_name(&name.name.id));
+                            imports.entry(section).or_default().insert(alias(name));
+                        }
+                    }
+                }
+                Stmt::ImportFrom(x) => {
+                    let module = x.module.as_ref().map_or("", |x| x.id.as_str());
+                    let section = if x.level > 0 {
+                        ImportSection::FirstParty
+                    } else {
+                        self.import_section(handle, ModuleName::from_str(module))
+                    };
+                    let names = from_imports
+                        .entry((section, x.level, module.to_owned()))
+                        .or_default();
+                    for name in &x.names {
+                        let bound = &name.asname.as_ref().unwrap_or(&name.name).id;
+                        if section == ImportSection::Future
+                            || name.name.id == "*"
+                            || is_used(bound, name)
+                        {
+                            names.insert(alias(name));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut sections = imports
+            .keys()
+            .chain(from_imports.keys().map(|(section, _, _)| section))
+            .copied()
+            .collect::<Vec<_>>();
+        sections.sort();
+        sections.dedup();
+        let mut blocks = Vec::new();
+        for section in sections {
+            let mut block = String::new();
+            let mut modules = imports
+                .get(&section)
+                .map(|x| x.iter().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            modules.sort_by_cached_key(|x| (x.to_lowercase(), x.clone()));
+            for module in modules {
+                block.push_str(&format!("import {module}\n"));
+            }
+            // Absolute imports come before relative ones, which go from the furthest to the
+            // closest.
+            let mut froms = from_imports
+                .iter()
+                .filter(|((x, _, _), names)| *x == section && !names.is_empty())
+                .collect::<Vec<_>>();
+            froms.sort_by_cached_key(|((_, level, module), _)| {
+                (
+                    *level > 0,
+                    Reverse(*level),
+                    module.to_lowercase(),
+                    module.clone(),
+                )
+            });
+            for ((_, level, module), names) in froms {
+                let mut names = names.iter().cloned().collect::<Vec<_>>();
+                names.sort_by_cached_key(|x| member_key(x));
+                let module = format!("{}{module}", ".".repeat(*level as usize));
+                block.push_str(&from_import(&module, &names));
+            }
+            if !block.is_empty() {
+                blocks.push(block);
+            }
+        }
+        let mut text = blocks.join("\n");
+        if text.is_empty() {
+            // Remove the blank lines that separated the imports from the rest of the module.
+            end = contents.len() - contents[end..].trim_start_matches(['\r', '\n']).len();
+        } else {
+            // The edit stops at the end of the last import, before its newline.
+            text.pop();
+        }
+        if contents[start..end] == text {
+            return None;
+        }
+        Some(vec![(
+            "Organize imports".to_owned(),
+            vec![(
+                module_info.dupe(),
+                TextRange::new(
+                    TextSize::try_from(start).unwrap(),
+                    TextSize::try_from(end).unwrap(),
+                ),
+                text,
+            )],
+        )])
+    }
+}
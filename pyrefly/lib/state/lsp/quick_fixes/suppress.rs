@@ -0,0 +1,234 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Suppress the errors on a line with a `# pyrefly: ignore` comment, or those of the whole file
+//! with `# pyrefly: ignore-errors`, and remove the ignore comments that no longer suppress
+//! anything.
+
+use std::sync::LazyLock;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::ignore::find_comment_start_in_line;
+use pyrefly_python::module::Module;
+use pyrefly_util::lined_buffer::LineNumber;
+use regex::Regex;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use starlark_map::small_set::SmallSet;
+
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::state::Transaction;
+
+/// A `# pyrefly: ignore` comment, capturing the codes between its brackets, if any.
+static IGNORE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#\s*pyrefly:\s*ignore(?:\s*\[([^\]]*)\])?").unwrap());
+
+/// The start and end of `line` in the contents of `module`, excluding its line break.
+fn line_bounds(module: &Module, line: LineNumber) -> (usize, usize) {
+    let contents = module.contents().as_str();
+    let start = module.lined_buffer().line_start(line).to_usize();
+    let end = contents[start..]
+        .find('\n')
+        .map_or(contents.len(), |i| start + i);
+    (start, end - contents[start..end].ends_with('\r') as usize)
+}
+
+/// The `# pyrefly: ignore` comment in the line `text`, as its range within the line and the range
+/// of the codes between its brackets.
+fn ignore_comment(text: &str) -> Option<(TextRange, Option<TextRange>)> {
+    let comment = find_comment_start_in_line(text)?;
+    let captures = IGNORE_REGEX.captures(&text[comment..])?;
+    let range = |x: regex::Match| {
+        TextRange::new(
+            TextSize::try_from(comment + x.start()).unwrap(),
+            TextSize::try_from(comment + x.end()).unwrap(),
+        )
+    };
+    Some((range(captures.get(0)?), captures.get(1).map(range)))
+}
+
+/// Whether a comment can't go at `offset`, the end of a line, because the line continues into
+/// the next one, either inside a string or after a backslash.
+fn continues_line(ast: &ModModule, contents: &str, offset: usize) -> bool {
+    contents[..offset].trim_end().ends_with('\\')
+        || Ast::locate_node(ast, TextSize::try_from(offset).unwrap())
+            .into_iter()
+            .any(|x| {
+                matches!(
+                    x,
+                    AnyNodeRef::ExprStringLiteral(_)
+                        | AnyNodeRef::ExprBytesLiteral(_)
+                        | AnyNodeRef::ExprFString(_)
+                        | AnyNodeRef::ExprTString(_)
+                ) && x.range().end().to_usize() > offset
+            })
+}
+
+/// The edit suppressing errors of `kind` on `line`: the code is added to the ignore comment
+/// already there, or a new comment is added at the end of the line, or on its own line above if
+/// the line continues into the next.
+fn ignore_edit(module: &Module, ast: &ModModule, line: LineNumber, kind: &str) -> RefactorEdit {
+    let contents = module.contents().as_str();
+    let (start, end) = line_bounds(module, line);
+    let text = &contents[start..end];
+    if let Some((_, Some(codes))) = ignore_comment(text) {
+        let existing = &text[codes];
+        let position = start + codes.start().to_usize() + existing.trim_end().len();
+        let text = if existing.trim().is_empty() {
+            kind.to_owned()
+        } else {
+            format!(", {kind}")
+        };
+        return (
+            module.dupe(),
+            TextRange::empty(TextSize::try_from(position).unwrap()),
+            text,
+        );
+    }
+    let code_end = start + text.trim_end().len();
+    if continues_line(ast, contents, code_end) {
+        let indent = &text[..text.len() - text.trim_start().len()];
+        (
+            module.dupe(),
+            TextRange::empty(TextSize::try_from(start).unwrap()),
+            format!("{indent}# pyrefly: ignore[{kind}]\n"),
+        )
+    } else {
+        (
+            module.dupe(),
+            TextRange::new(
+                TextSize::try_from(code_end).unwrap(),
+                TextSize::try_from(end).unwrap(),
+            ),
+            format!("  # pyrefly: ignore[{kind}]"),
+        )
+    }
+}
+
+/// The edit removing the ignore comment of `line`, along with the line if nothing else is on it.
+fn remove_ignore_edit(module: &Module, line: LineNumber) -> Option<RefactorEdit> {
+    let contents = module.contents().as_str();
+    let (start, end) = line_bounds(module, line);
+    let text = &contents[start..end];
+    let (comment, _) = ignore_comment(text)?;
+    let before = text[..comment.start().to_usize()].trim_end();
+    let after = &text[comment.end().to_usize()..];
+    let range = if before.is_empty() && after.trim().is_empty() {
+        let next = contents[end..]
+            .find('\n')
+            .map_or(contents.len(), |i| end + i + 1);
+        TextRange::new(
+            TextSize::try_from(start).unwrap(),
+            TextSize::try_from(next).unwrap(),
+        )
+    } else if after.trim().is_empty() {
+        TextRange::new(
+            TextSize::try_from(start + before.len()).unwrap(),
+            TextSize::try_from(end).unwrap(),
+        )
+    } else {
+        // Keep any comment that follows, such as `# noqa`.
+        TextRange::new(
+            TextSize::try_from(start).unwrap() + comment.start(),
+            TextSize::try_from(start + text.len() - after.trim_start().len()).unwrap(),
+        )
+    };
+    Some((module.dupe(), range, String::new()))
+}
+
+impl<'a> Transaction<'a> {
+    /// Quick fixes for the errors at `range` that suppress them with a `# pyrefly: ignore`
+    /// comment on their line, or with `# pyrefly: ignore-errors` for the whole file, along with
+    /// one removing a `# pyrefly: ignore` comment at `range` that doesn't suppress any error.
+    pub fn suppress_error_code_actions(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+    ) -> Option<Vec<(String, Vec<RefactorEdit>)>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let contents = module_info.contents().as_str();
+        let errors = self.get_errors(vec![handle]).collect_errors();
+        let mut actions = Vec::new();
+
+        let mut seen = SmallSet::new();
+        for error in &errors.shown {
+            if error.range().intersect(range).is_none() {
+                continue;
+            }
+            let line = error.display_range().start.line_within_file();
+            let kind = error.error_kind().to_name();
+            if seen.insert((line, kind)) {
+                actions.push((
+                    format!("Suppress `{kind}` with `# pyrefly: ignore`"),
+                    vec![ignore_edit(&module_info, &ast, line, kind)],
+                ));
+            }
+        }
+        if !seen.is_empty() {
+            // The directive has to be among the comments at the top of the file, which may start
+            // with a shebang.
+            let position = if contents.starts_with("#!") {
+                contents.find('\n').map_or(contents.len(), |i| i + 1)
+            } else {
+                0
+            };
+            actions.push((
+                "Suppress all errors in this file with `# pyrefly: ignore-errors`".to_owned(),
+                vec![(
+                    module_info.dupe(),
+                    TextRange::empty(TextSize::try_from(position).unwrap()),
+                    "# pyrefly: ignore-errors\n".to_owned(),
+                )],
+            ));
+        }
+
+        let ignore = module_info.ignore();
+        for line in ignore.get_pyrefly_ignores(false) {
+            let used = errors.suppressed.iter().any(|e| {
+                let display = e.display_range();
+                ignore.is_ignored_by_suppression_line(
+                    line,
+                    display.start.line_within_file(),
+                    display.end.line_within_file(),
+                    e.error_kind().to_name(),
+                    false,
+                )
+            });
+            if used {
+                continue;
+            }
+            // A comment on a line of its own applies to the next line.
+            let comment_line = [Some(line), line.decrement()]
+                .into_iter()
+                .flatten()
+                .find(|x| {
+                    let (start, end) = line_bounds(&module_info, *x);
+                    ignore_comment(&contents[start..end]).is_some()
+                });
+            let Some(comment_line) = comment_line else {
+                continue;
+            };
+            let (start, end) = line_bounds(&module_info, comment_line);
+            let bounds = TextRange::new(
+                TextSize::try_from(start).unwrap(),
+                TextSize::try_from(end).unwrap(),
+            );
+            if bounds.intersect(range).is_some()
+                && let Some(edit) = remove_ignore_edit(&module_info, comment_line)
+            {
+                actions.push(("Remove unused `# pyrefly: ignore`".to_owned(), vec![edit]));
+            }
+        }
+        Some(actions)
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Selection ranges, for expanding the selection one syntactic level at a time: from a name, to
+//! the expressions containing it, to its statement, the block of statements it is in, the
+//! statement owning that block, and so on up to the whole module.
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::state::Transaction;
+
+/// The blocks of statements directly owned by a node.
+fn blocks<'a>(x: AnyNodeRef<'a>) -> Vec<&'a [Stmt]> {
+    match x {
+        AnyNodeRef::ModModule(x) => vec![&x.body],
+        AnyNodeRef::StmtFunctionDef(x) => vec![&x.body],
+        AnyNodeRef::StmtClassDef(x) => vec![&x.body],
+        AnyNodeRef::StmtIf(x) => vec![&x.body],
+        AnyNodeRef::ElifElseClause(x) => vec![&x.body],
+        AnyNodeRef::StmtFor(x) => vec![&x.body, &x.orelse],
+        AnyNodeRef::StmtWhile(x) => vec![&x.body, &x.orelse],
+        AnyNodeRef::StmtWith(x) => vec![&x.body],
+        AnyNodeRef::StmtTry(x) => vec![&x.body, &x.orelse, &x.finalbody],
+        AnyNodeRef::ExceptHandlerExceptHandler(x) => vec![&x.body],
+        AnyNodeRef::MatchCase(x) => vec![&x.body],
+        _ => Vec::new(),
+    }
+}
+
+/// The range of the block of statements of `parent` that contains `range`, if any.
+fn block_range(parent: AnyNodeRef, range: TextRange) -> Option<TextRange> {
+    blocks(parent).into_iter().find_map(|block| {
+        let block = block.first()?.range().cover(block.last()?.range());
+        block.contains_range(range).then_some(block)
+    })
+}
+
+/// The ranges to select at `position`, from the innermost outwards, each strictly containing the
+/// previous one.
+fn selection_ranges(ast: &ModModule, len: TextSize, position: TextSize) -> Vec<TextRange> {
+    let nodes = Ast::locate_node(ast, position);
+    let mut res: Vec<TextRange> = Vec::new();
+    let mut push = |range: TextRange| {
+        if res
+            .last()
+            .is_none_or(|last| range != *last && range.contains_range(*last))
+        {
+            res.push(range);
+        }
+    };
+    // The name of a definition isn't a node of its own.
+    match nodes.first() {
+        Some(AnyNodeRef::StmtFunctionDef(x)) if x.name.range.contains_inclusive(position) => {
+            push(x.name.range)
+        }
+        Some(AnyNodeRef::StmtClassDef(x)) if x.name.range.contains_inclusive(position) => {
+            push(x.name.range)
+        }
+        _ => {}
+    }
+    for (i, node) in nodes.iter().enumerate() {
+        if !matches!(node, AnyNodeRef::ModModule(_)) {
+            push(node.range());
+        }
+        if node.is_statement()
+            && let Some(parent) = nodes.get(i + 1)
+            && let Some(block) = block_range(*parent, node.range())
+        {
+            push(block);
+        }
+    }
+    // The whole module, including any blank lines and comments around its statements.
+    push(TextRange::up_to(len));
+    res
+}
+
+impl<'a> Transaction<'a> {
+    /// For each position, the ranges to select there, from the innermost outwards.
+    pub fn selection_ranges(
+        &self,
+        handle: &Handle,
+        positions: &[TextSize],
+    ) -> Option<Vec<Vec<TextRange>>> {
+        let ast = self.get_ast(handle)?;
+        let len = TextSize::of(self.get_module_info(handle)?.contents().as_str());
+        Some(
+            positions
+                .iter()
+                .map(|position| selection_ranges(&ast, len, *position))
+                .collect(),
+        )
+    }
+}
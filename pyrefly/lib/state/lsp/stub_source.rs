@@ -0,0 +1,284 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Map a definition in a `.pyi` stub to the same definition in the `.py` source it describes, so
+//! go-to-definition can offer both, and hover can show the docstring the stub leaves out.
+
+use std::slice;
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::docstring::Docstring;
+use pyrefly_python::module::Module;
+use ruff_python_ast::ExceptHandler;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use serde::Deserialize;
+
+/// Which definition go-to-definition should return first when a symbol has both a stub and a
+/// source implementation. The other is always returned as a secondary result.
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DefinitionPreference {
+    /// Go to the `.py` source first.
+    #[default]
+    Source,
+    /// Go to the `.pyi` stub first.
+    Stub,
+}
+
+/// The names defined directly in a statement, with their ranges.
+fn defined_names(x: &Stmt) -> Vec<(&Name, TextRange)> {
+    let mut res = Vec::new();
+    match x {
+        Stmt::FunctionDef(x) => res.push((&x.name.id, x.name.range)),
+        Stmt::ClassDef(x) => res.push((&x.name.id, x.name.range)),
+        Stmt::Assign(x) => {
+            for target in &x.targets {
+                Ast::expr_lvalue(target, &mut |x| res.push((&x.id, x.range)));
+            }
+        }
+        Stmt::AnnAssign(x) => {
+            if let Expr::Name(x) = &*x.target {
+                res.push((&x.id, x.range));
+            }
+        }
+        Stmt::TypeAlias(x) => {
+            if let Expr::Name(x) = &*x.name {
+                res.push((&x.id, x.range));
+            }
+        }
+        _ => {}
+    }
+    res
+}
+
+/// The statements nested in `x` that are still in the same scope, e.g. the branches of an `if`.
+fn same_scope_bodies(x: &Stmt) -> Vec<&[Stmt]> {
+    match x {
+        Stmt::If(x) => Ast::if_branches(x).map(|(_, body)| body).collect(),
+        Stmt::Try(x) => {
+            let mut res = vec![
+                x.body.as_slice(),
+                x.orelse.as_slice(),
+                x.finalbody.as_slice(),
+            ];
+            for handler in &x.handlers {
+                let ExceptHandler::ExceptHandler(handler) = handler;
+                res.push(&handler.body);
+            }
+            res
+        }
+        Stmt::With(x) => vec![x.body.as_slice()],
+        _ => Vec::new(),
+    }
+}
+
+/// The path of class and function names leading to the definition whose name is at `range`,
+/// e.g. `["Foo", "method"]`.
+fn definition_path(body: &[Stmt], range: TextRange) -> Option<Vec<Name>> {
+    for stmt in body {
+        if !stmt.range().contains_range(range) {
+            continue;
+        }
+        if let Some((name, _)) = defined_names(stmt).into_iter().find(|x| x.1 == range) {
+            return Some(vec![name.clone()]);
+        }
+        let (name, nested) = match stmt {
+            Stmt::ClassDef(x) => (&x.name.id, &x.body),
+            Stmt::FunctionDef(x) => (&x.name.id, &x.body),
+            _ => {
+                return same_scope_bodies(stmt)
+                    .into_iter()
+                    .find_map(|body| definition_path(body, range));
+            }
+        };
+        let mut path = definition_path(nested, range)?;
+        path.insert(0, name.clone());
+        return Some(path);
+    }
+    None
+}
+
+/// Find the range of the name of the definition at `path`. If a name is defined more than once,
+/// e.g. in both branches of an `if`, use the first.
+fn find_definition_path(body: &[Stmt], path: &[Name]) -> Option<TextRange> {
+    let (first, rest) = path.split_first()?;
+    for stmt in body {
+        if let Some((_, range)) = defined_names(stmt).into_iter().find(|x| x.0 == first) {
+            if rest.is_empty() {
+                return Some(range);
+            }
+            match stmt {
+                Stmt::ClassDef(x) => return find_definition_path(&x.body, rest),
+                Stmt::FunctionDef(x) => return find_definition_path(&x.body, rest),
+                _ => return None,
+            }
+        }
+        if let Some(range) = same_scope_bodies(stmt)
+            .into_iter()
+            .find_map(|body| find_definition_path(body, path))
+        {
+            return Some(range);
+        }
+    }
+    None
+}
+
+/// Given the range of a definition in `stub`, find the range of the same definition in `source`.
+/// A default range refers to the module itself, so maps to the start of the source.
+pub fn map_stub_definition(stub: &Module, range: TextRange, source: &Module) -> Option<TextRange> {
+    if range == TextRange::default() {
+        return Some(range);
+    }
+    let (stub_ast, _, _) = Ast::parse(stub.contents());
+    let path = definition_path(&stub_ast.body, range)?;
+    let (source_ast, _, _) = Ast::parse(source.contents());
+    find_definition_path(&source_ast.body, &path)
+}
+
+/// Find the docstring of the definition whose name is at `range`: the first statement of a class
+/// or function body, or the string following an assignment.
+fn find_docstring(body: &[Stmt], range: TextRange) -> Option<TextRange> {
+    for (i, stmt) in body.iter().enumerate() {
+        if !stmt.range().contains_range(range) {
+            continue;
+        }
+        match stmt {
+            Stmt::ClassDef(x) if x.name.range == range => {
+                return Docstring::range_from_stmts(&x.body);
+            }
+            Stmt::FunctionDef(x) if x.name.range == range => {
+                return Docstring::range_from_stmts(&x.body);
+            }
+            Stmt::ClassDef(x) => return find_docstring(&x.body, range),
+            Stmt::FunctionDef(x) => return find_docstring(&x.body, range),
+            _ => {}
+        }
+        if defined_names(stmt).into_iter().any(|x| x.1 == range) {
+            return Docstring::range_from_stmts(slice::from_ref(body.get(i + 1)?));
+        }
+        return same_scope_bodies(stmt)
+            .into_iter()
+            .find_map(|body| find_docstring(body, range));
+    }
+    None
+}
+
+/// The range of the docstring of the definition whose name is at `range` in `module`. A default
+/// range refers to the module itself.
+pub fn definition_docstring(module: &Module, range: TextRange) -> Option<TextRange> {
+    let (ast, _, _) = Ast::parse(module.contents());
+    if range == TextRange::default() {
+        return Docstring::range_from_stmts(&ast.body);
+    }
+    find_docstring(&ast.body, range)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+    use ruff_text_size::TextSize;
+
+    use super::*;
+
+    fn module(path: &str, contents: &str) -> Module {
+        Module::new(
+            ModuleName::from_str("lib"),
+            ModulePath::filesystem(PathBuf::from(path)),
+            Arc::new(contents.to_owned()),
+        )
+    }
+
+    #[test]
+    fn test_map_stub_definition() {
+        let stub = module(
+            "lib.pyi",
+            r#"
+import sys
+x: int
+class Foo:
+    if sys.version_info >= (3, 12):
+        def method(self) -> None: ...
+"#,
+        );
+        let source = module(
+            "lib.py",
+            r#"
+import sys
+
+class Foo:
+    def helper(self):
+        def method(): ...
+
+    def method(self):
+        pass
+
+x = 1
+"#,
+        );
+        let at = |module: &Module, needle: &str| {
+            let start = TextSize::try_from(module.contents().rfind(needle).unwrap()).unwrap();
+            TextRange::at(start, TextSize::of(needle))
+        };
+        assert_eq!(
+            map_stub_definition(&stub, at(&stub, "method"), &source),
+            Some(at(&source, "method"))
+        );
+        assert_eq!(
+            map_stub_definition(&stub, at(&stub, "Foo"), &source),
+            Some(at(&source, "Foo"))
+        );
+        assert_eq!(
+            map_stub_definition(&stub, at(&stub, "x"), &source),
+            Some(at(&source, "x"))
+        );
+        assert_eq!(map_stub_definition(&stub, at(&stub, "None"), &source), None);
+    }
+
+    #[test]
+    fn test_definition_docstring() {
+        let source = module(
+            "lib.py",
+            r#""""The module."""
+class Foo:
+    """The class."""
+    def method(self):
+        """The method."""
+    x = 1
+    """The attribute."""
+    y = 2
+"#,
+        );
+        let at = |needle: &str| {
+            let start = TextSize::try_from(source.contents().find(needle).unwrap()).unwrap();
+            TextRange::at(start, TextSize::of(needle))
+        };
+        let docstring =
+            |name| definition_docstring(&source, name).map(|x| source.code_at(x).to_owned());
+        assert_eq!(
+            docstring(TextRange::default()).as_deref(),
+            Some(r#""""The module.""""#)
+        );
+        assert_eq!(docstring(at("Foo")).as_deref(), Some(r#""""The class.""""#));
+        assert_eq!(
+            docstring(at("method")).as_deref(),
+            Some(r#""""The method.""""#)
+        );
+        assert_eq!(
+            docstring(at("x")).as_deref(),
+            Some(r#""""The attribute.""""#)
+        );
+        assert_eq!(docstring(at("y")), None);
+    }
+}
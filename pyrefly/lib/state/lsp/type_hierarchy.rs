@@ -0,0 +1,111 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Type hierarchy: the base classes of a class, and the classes that inherit from it. Like the
+//! call hierarchy, bases are resolved with go-to-definition (supertypes) and subclasses are found
+//! with find-references (subtypes), so only bases written as a name or an attribute, possibly
+//! subscripted, are understood.
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::symbol_kind::SymbolKind;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::visitor::source_order::SourceOrderVisitor;
+use ruff_python_ast::visitor::source_order::walk_stmt;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::call_hierarchy::CallHierarchyTarget;
+use crate::state::state::Transaction;
+
+/// The range of the name a base class refers to: `B` in `class A(B)`, `B[int]` or `m.B`.
+fn base_name(x: &Expr) -> Option<TextRange> {
+    match x {
+        Expr::Name(x) => Some(x.range),
+        Expr::Attribute(x) => Some(x.attr.range),
+        Expr::Subscript(x) => base_name(&x.value),
+        _ => None,
+    }
+}
+
+fn base_names(x: &StmtClassDef) -> impl Iterator<Item = TextRange> {
+    x.bases().iter().filter_map(base_name)
+}
+
+/// Every class defined in a module, including nested ones.
+#[derive(Default)]
+struct Classes<'a>(Vec<&'a StmtClassDef>);
+
+impl<'a> SourceOrderVisitor<'a> for Classes<'a> {
+    fn visit_stmt(&mut self, x: &'a Stmt) {
+        if let Stmt::ClassDef(x) = x {
+            self.0.push(x);
+        }
+        walk_stmt(self, x);
+    }
+}
+
+/// The subclasses of a class, given the references to it found by find-references.
+pub fn subtypes(references: Vec<(Module, Vec<TextRange>)>) -> Vec<CallHierarchyTarget> {
+    let mut res = Vec::new();
+    for (module, ranges) in references {
+        let ast = Ast::parse(module.contents()).0;
+        let mut classes = Classes::default();
+        classes.visit_body(&ast.body);
+        for x in classes.0 {
+            if base_names(x).any(|x| ranges.contains(&x)) {
+                res.push(CallHierarchyTarget {
+                    module: module.dupe(),
+                    name: x.name.id.to_string(),
+                    kind: SymbolKind::Class,
+                    range: x.range,
+                    selection_range: x.name.range,
+                });
+            }
+        }
+    }
+    res
+}
+
+impl<'a> Transaction<'a> {
+    /// The classes defined by the symbol at `position`.
+    pub fn prepare_type_hierarchy(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+    ) -> Vec<CallHierarchyTarget> {
+        self.prepare_call_hierarchy(handle, position)
+            .into_iter()
+            .filter(|x| matches!(x.kind, SymbolKind::Class))
+            .collect()
+    }
+
+    /// The base classes of the class whose name is at `position`, in the order they are listed.
+    pub fn supertypes(&self, handle: &Handle, position: TextSize) -> Vec<CallHierarchyTarget> {
+        let Some(ast) = self.get_ast(handle) else {
+            return Vec::new();
+        };
+        let Some(class) = Ast::locate_node(&ast, position)
+            .into_iter()
+            .find_map(|x| match x {
+                AnyNodeRef::StmtClassDef(x) => Some(x),
+                _ => None,
+            })
+            .filter(|x| x.name.range.contains_inclusive(position))
+        else {
+            return Vec::new();
+        };
+        base_names(class)
+            .flat_map(|x| self.prepare_type_hierarchy(handle, x.start()))
+            .collect()
+    }
+}
@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Search for the symbols defined at the top level of any module the state knows about, which is
+//! every module of the project once it has been indexed, ranked by how well they match a query.
+
+use std::cmp::Reverse;
+
+use dupe::Dupe;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::TextRangeWithModule;
+
+use crate::export::exports::ExportLocation;
+use crate::state::lsp::MIN_CHARACTERS_TYPED_AUTOIMPORT;
+use crate::state::state::Transaction;
+
+/// The most symbols a search returns, since a short query can match most of a project.
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+/// The words of an identifier, split at underscores and at the start of each hump of a camel case
+/// name, so `get_type_checker` and `getTypeChecker` are both `get`, `Type` and `Checker`.
+fn words(name: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = None;
+    let mut previous_is_lower = false;
+    for (i, c) in name.char_indices() {
+        if c == '_' {
+            if let Some(start) = start.take() {
+                res.push(&name[start..i]);
+            }
+            previous_is_lower = false;
+            continue;
+        }
+        if c.is_uppercase()
+            && previous_is_lower
+            && let Some(start) = start.replace(i)
+        {
+            res.push(&name[start..i]);
+        }
+        start.get_or_insert(i);
+        previous_is_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if let Some(start) = start {
+        res.push(&name[start..]);
+    }
+    res
+}
+
+/// Whether `query` is made of prefixes of `words`, in order and ignoring case, so `gtc`, `getTC`
+/// and `TypeCheck` all match the words of `get_type_checker`.
+fn matches_word_prefixes(words: &[&str], query: &str) -> bool {
+    let Some((word, rest)) = words.split_first() else {
+        return query.is_empty();
+    };
+    if query.is_empty() {
+        return true;
+    }
+    let prefixes = word
+        .chars()
+        .zip(query.char_indices())
+        .take_while(|(a, (_, b))| a.eq_ignore_ascii_case(b))
+        .map(|(_, (i, b))| i + b.len_utf8())
+        .collect::<Vec<_>>();
+    // Prefer taking as much of the query from this word as possible.
+    prefixes
+        .into_iter()
+        .rev()
+        .any(|end| matches_word_prefixes(rest, &query[end..]))
+        || matches_word_prefixes(rest, query)
+}
+
+/// How well `name` matches `query`, higher being better, or `None` if it doesn't. Exact matches
+/// come first, then prefixes, then matches of the starts of the words of the name, and then any
+/// other fuzzy match, each ignoring case.
+fn match_score(matcher: &SkimMatcherV2, name: &str, query: &str) -> Option<(u8, i64)> {
+    let fuzzy = matcher.fuzzy_match(name, query);
+    let tier = if name == query {
+        4
+    } else if name.eq_ignore_ascii_case(query) {
+        3
+    } else if name.len() >= query.len()
+        && name.is_char_boundary(query.len())
+        && name[..query.len()].eq_ignore_ascii_case(query)
+    {
+        2
+    } else if matches_word_prefixes(&words(name), query) {
+        1
+    } else if fuzzy.is_some() {
+        0
+    } else {
+        return None;
+    };
+    Some((tier, fuzzy.unwrap_or_default()))
+}
+
+impl<'a> Transaction<'a> {
+    /// The top level symbols of the modules accepted by `include` that match `query`, best match
+    /// first, and at most `MAX_WORKSPACE_SYMBOLS` of them.
+    pub fn workspace_symbols(
+        &self,
+        query: &str,
+        include: &(dyn Fn(&Handle) -> bool + Sync),
+    ) -> Option<Vec<(String, lsp_types::SymbolKind, TextRangeWithModule)>> {
+        if query.len() < MIN_CHARACTERS_TYPED_AUTOIMPORT {
+            return None;
+        }
+        let mut matches = self.search_exports_helper(|handle, exports| {
+            if !include(handle) {
+                return Vec::new();
+            }
+            let matcher = SkimMatcherV2::default().ignore_case();
+            let mut results = Vec::new();
+            for (name, location) in exports.iter() {
+                if let ExportLocation::ThisModule(export) = location
+                    && let Some(score) = match_score(&matcher, name.as_str(), query)
+                {
+                    results.push((score, handle.dupe(), name.to_string(), export.clone()));
+                }
+            }
+            results
+        });
+        // Break ties by name and module, so the results don't depend on which thread found them.
+        matches.sort_by_cached_key(|(score, handle, name, _)| {
+            (
+                Reverse(*score),
+                name.len(),
+                name.clone(),
+                handle.module().as_str().to_owned(),
+            )
+        });
+        let mut result = Vec::new();
+        for (_, handle, name, export) in matches.into_iter().take(MAX_WORKSPACE_SYMBOLS) {
+            if let Some(module) = self.get_module_info(&handle) {
+                let kind = export
+                    .symbol_kind
+                    .map_or(lsp_types::SymbolKind::VARIABLE, |k| k.to_lsp_symbol_kind());
+                let location = TextRangeWithModule {
+                    module,
+                    range: export.location,
+                };
+                result.push((name, kind, location));
+            }
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("get_type_checker"), vec!["get", "type", "checker"]);
+        assert_eq!(words("getTypeChecker"), vec!["get", "Type", "Checker"]);
+        assert_eq!(words("_TypeChecker2"), vec!["Type", "Checker2"]);
+        assert_eq!(words("HTTPServer"), vec!["HTTPServer"]);
+    }
+
+    #[test]
+    fn test_match_score() {
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let tier = |name, query| match_score(&matcher, name, query).map(|(tier, _)| tier);
+        assert_eq!(tier("TypeChecker", "TypeChecker"), Some(4));
+        assert_eq!(tier("TypeChecker", "typechecker"), Some(3));
+        assert_eq!(tier("TypeChecker", "typech"), Some(2));
+        assert_eq!(tier("getTypeChecker", "gtc"), Some(1));
+        assert_eq!(tier("get_type_checker", "getTC"), Some(1));
+        assert_eq!(tier("get_type_checker", "TypeCheck"), Some(1));
+        assert_eq!(tier("get_type_checker", "yche"), Some(0));
+        assert_eq!(tier("get_type_checker", "xyz"), None);
+    }
+}
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+pub mod crash_report;
 pub mod dirty;
 pub mod epoch;
 pub mod errors;
@@ -13,12 +13,12 @@ use lsp_types::SemanticTokenType;
 use lsp_types::SemanticTokensLegend;
 use pyrefly_python::ast::Ast;
 use pyrefly_python::module::Module;
-use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::symbol_kind::SymbolKind;
 use pyrefly_python::sys_info::SysInfo;
 use pyrefly_types::types::Type;
 use pyrefly_util::visit::Visit as _;
 use ruff_python_ast::Arguments;
+use ruff_python_ast::Decorator;
 use ruff_python_ast::ExceptHandler;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ModModule;
@@ -27,6 +27,7 @@ use ruff_python_ast::StmtImport;
 use ruff_python_ast::StmtImportFrom;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
+use starlark_map::small_set::SmallSet;
 
 use crate::binding::binding::Key;
 
@@ -166,6 +167,8 @@ pub struct SemanticTokenBuilder {
     tokens: Vec<SemanticTokenWithFullRange>,
     limit_range: Option<TextRange>,
     disabled_ranges: Vec<TextRange>,
+    /// The names of decorators, which are decorators whatever else they are.
+    decorator_ranges: SmallSet<TextRange>,
 }
 
 impl SemanticTokenBuilder {
@@ -179,6 +182,7 @@ impl SemanticTokenBuilder {
             tokens: Vec::new(),
             limit_range,
             disabled_ranges,
+            decorator_ranges: SmallSet::new(),
         }
     }
 
@@ -203,27 +207,44 @@ impl SemanticTokenBuilder {
             .any(|disabled| disabled.contains_range(range))
     }
 
+    /// Add a token for `key`, whose definition is of `symbol_kind`. `is_default_library` is set
+    /// for symbols defined by the standard library, and `is_async` for `async def` functions.
     pub fn process_key(
         &mut self,
         key: &Key,
-        definition_module: ModuleName,
         symbol_kind: SymbolKind,
+        is_default_library: bool,
+        is_async: bool,
     ) {
         let reference_range = key.range();
         let (token_type, mut token_modifiers) =
             symbol_kind.to_lsp_semantic_token_type_with_modifiers();
-        let is_default_library = {
-            let module_name_str = definition_module.as_str();
-            module_name_str == "builtins"
-                || module_name_str == "typing"
-                || module_name_str == "typing_extensions"
-        };
         if is_default_library {
             token_modifiers.push(SemanticTokenModifier::DEFAULT_LIBRARY);
         }
+        if is_async {
+            token_modifiers.push(SemanticTokenModifier::ASYNC);
+        }
         self.push_if_in_range(reference_range, token_type, token_modifiers);
     }
 
+    fn process_decorators(&mut self, decorators: &[Decorator]) {
+        for decorator in decorators {
+            let mut x = &decorator.expression;
+            // For `@foo(...)`, the decorator is the result of calling `foo`.
+            while let Expr::Call(call) = x {
+                x = &call.func;
+            }
+            let range = match x {
+                Expr::Name(name) => name.range,
+                Expr::Attribute(attr) => attr.attr.range,
+                _ => continue,
+            };
+            self.decorator_ranges.insert(range);
+            self.push_if_in_range(range, SemanticTokenType::DECORATOR, Vec::new());
+        }
+    }
+
     fn process_arguments(&mut self, args: &Arguments) {
         for keyword in &args.keywords {
             if let Some(arg) = &keyword.arg {
@@ -263,6 +284,7 @@ impl SemanticTokenBuilder {
     fn process_stmt(&mut self, x: &Stmt) {
         match x {
             Stmt::ClassDef(class_def) => {
+                self.process_decorators(&class_def.decorator_list);
                 if self.is_disabled(class_def.range) {
                     self.push_if_in_range(
                         class_def.name.range,
@@ -273,11 +295,16 @@ impl SemanticTokenBuilder {
                 x.recurse(&mut |x| self.process_stmt(x));
             }
             Stmt::FunctionDef(function_def) => {
+                self.process_decorators(&function_def.decorator_list);
                 if self.is_disabled(function_def.range) {
                     self.push_if_in_range(
                         function_def.name.range,
                         SemanticTokenType::FUNCTION,
-                        Vec::new(),
+                        if function_def.is_async {
+                            vec![SemanticTokenModifier::ASYNC]
+                        } else {
+                            Vec::new()
+                        },
                     );
                 }
                 x.recurse(&mut |x| self.process_stmt(x));
@@ -347,6 +374,12 @@ impl SemanticTokenBuilder {
 
     pub fn all_tokens_sorted(self) -> Vec<SemanticTokenWithFullRange> {
         let mut tokens = self.tokens;
+        for token in &mut tokens {
+            // Keep the modifiers of what the decorator refers to, such as `defaultLibrary`.
+            if self.decorator_ranges.contains(&token.range) {
+                token.token_type = SemanticTokenType::DECORATOR;
+            }
+        }
         tokens.sort_by(|a, b| a.range.start().cmp(&b.range.start()));
         tokens
     }
@@ -432,3 +465,20 @@ pub(crate) fn disabled_ranges_for_module(ast: &ModModule, sys_info: &SysInfo) ->
     collect_disabled_ranges_from_block(&ast.body, sys_info, true, &mut ranges);
     ranges
 }
+
+/// The ranges of the names of the `async def` functions in a module.
+pub(crate) fn async_function_names(ast: &ModModule) -> SmallSet<TextRange> {
+    fn f(x: &Stmt, res: &mut SmallSet<TextRange>) {
+        if let Stmt::FunctionDef(x) = x
+            && x.is_async
+        {
+            res.insert(x.name.range);
+        }
+        x.recurse(&mut |x| f(x, res));
+    }
+    let mut res = SmallSet::new();
+    for x in &ast.body {
+        f(x, &mut res);
+    }
+    res
+}
@@ -43,12 +43,15 @@ use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::module_path::ModulePathDetails;
 use pyrefly_python::sys_info::SysInfo;
 use pyrefly_util::arc_id::ArcId;
+use pyrefly_util::deterministic::SeededState;
+use pyrefly_util::deterministic::batch_size;
 use pyrefly_util::events::CategorizedEvents;
 use pyrefly_util::fs_anyhow;
 use pyrefly_util::lock::Mutex;
 use pyrefly_util::lock::RwLock;
 use pyrefly_util::locked_map::LockedMap;
 use pyrefly_util::no_hash::BuildNoHash;
+use pyrefly_util::panic::with_crash_context;
 use pyrefly_util::small_set1::SmallSet1;
 use pyrefly_util::task_heap::CancellationHandle;
 use pyrefly_util::task_heap::Cancelled;
@@ -71,6 +74,7 @@ use starlark_map::small_set::SmallSet;
 use tracing::debug;
 use tracing::info;
 use tracing::trace;
+use tracing::trace_span;
 use vec1::vec1;
 
 use crate::alt::answers::AnswerEntry;
@@ -92,9 +96,12 @@ use crate::binding::bindings::BindingTable;
 use crate::binding::bindings::Bindings;
 use crate::binding::table::TableKeyed;
 use crate::config::config::ConfigFile;
+use crate::config::config::ModuleOrigin;
 use crate::config::error_kind::ErrorKind;
+use crate::config::error_kind::Severity;
 use crate::config::finder::ConfigError;
 use crate::config::finder::ConfigFinder;
+use crate::config::import_boundaries::ImportBoundaries;
 use crate::error::collector::ErrorCollector;
 use crate::error::context::ErrorInfo;
 use crate::export::exports::Export;
@@ -105,6 +112,7 @@ use crate::module::bundled::BundledStub;
 use crate::module::finder::find_import_prefixes;
 use crate::module::typeshed::BundledTypeshedStdlib;
 use crate::solver::solver::VarRecurser;
+use crate::state::crash_report::ModuleCrashContext;
 use crate::state::dirty::Dirty;
 use crate::state::epoch::Epoch;
 use crate::state::epoch::Epochs;
@@ -137,7 +145,7 @@ struct ModuleData {
     /// The dependencies of this module.
     /// Most modules exist in exactly one place, but it can be possible to load the same module multiple times with different paths.
     deps: HashMap<ModuleName, SmallSet1<Handle>, BuildNoHash>,
-    rdeps: HashSet<Handle>,
+    rdeps: HashSet<Handle, SeededState>,
 }
 
 #[derive(Debug)]
@@ -154,7 +162,7 @@ struct ModuleDataMut {
     /// The reverse dependencies of this module. This is used to invalidate on change.
     /// Note that if we are only running once, e.g. on the command line, this isn't valuable.
     /// But we create it anyway for simplicity, since it doesn't seem to add much overhead.
-    rdeps: Mutex<HashSet<Handle>>,
+    rdeps: Mutex<HashSet<Handle, SeededState>>,
 }
 
 /// The fields of `ModuleDataMut` that are stored together as they might be mutated.
@@ -227,7 +235,7 @@ impl ModuleDataMut {
 /// A subset of State that contains readable information for various systems (e.g. IDE, error reporting, etc).
 struct StateData {
     stdlib: SmallMap<SysInfo, Arc<Stdlib>>,
-    modules: HashMap<Handle, ModuleData>,
+    modules: HashMap<Handle, ModuleData, SeededState>,
     loaders: SmallMap<ArcId<ConfigFile>, Arc<LoaderFindCache>>,
     /// The contents for ModulePath::memory values
     memory: MemoryFiles,
@@ -458,7 +466,7 @@ impl<'a> Transaction<'a> {
             .collect()
     }
 
-    fn search_exports_helper<V: Send + Sync>(
+    pub(crate) fn search_exports_helper<V: Send + Sync>(
         &self,
         searcher: impl Fn(&Handle, Arc<SmallMap<Name, ExportLocation>>) -> Vec<V> + Sync,
     ) -> Vec<V> {
@@ -475,7 +483,12 @@ impl<'a> Transaction<'a> {
         // add up for a large codebase. Therefore, we will parallelize the work. The work is
         // distributed in the task heap above.
         // To avoid too much lock contention, we chunk the work into size of 1000 modules.
-        for chunk in &self.data.updated_modules.iter_unordered().chunks(1000) {
+        for chunk in &self
+            .data
+            .updated_modules
+            .iter_unordered()
+            .chunks(batch_size(1000))
+        {
             tasks.push((), chunk.collect_vec(), false);
         }
         self.data.state.threads.spawn_many(|| {
@@ -848,9 +861,24 @@ impl<'a> Transaction<'a> {
                 drop(to_drop);
             }
 
+            // A span per step is only worth its cost when exporting traces.
+            #[cfg(feature = "otlp")]
+            let _span = trace_span!(
+                "step",
+                module = %module_data.handle.module(),
+                path = %module_data.handle.path(),
+                step = %todo,
+            )
+            .entered();
             let stdlib = self.get_stdlib(&module_data.handle);
             let config = module_data.config.read();
-            let set = compute(&Context {
+            let crash_context = ModuleCrashContext {
+                handle: module_data.handle.dupe(),
+                step: todo,
+                load: exclusive.steps.load.dupe(),
+                config: config.dupe(),
+            };
+            let ctx = Context {
                 require,
                 module: module_data.handle.module(),
                 path: module_data.handle.path(),
@@ -863,7 +891,27 @@ impl<'a> Transaction<'a> {
                     .untyped_def_behavior(module_data.handle.path().as_path()),
                 infer_with_first_use: config
                     .infer_with_first_use(module_data.handle.path().as_path()),
-            });
+                propagate_constants: config
+                    .propagate_constants(module_data.handle.path().as_path()),
+                reexport: config.reexport(module_data.handle.path().as_path()),
+                embedded_code: config.embedded_code(module_data.handle.path().as_path()),
+                parameter_providers: config
+                    .parameter_providers(module_data.handle.path().as_path()),
+                base_class_factories: config
+                    .base_class_factories(module_data.handle.path().as_path()),
+                stub: self.implementation_stub(&module_data.handle, &config),
+                infer_parameters_from_defaults: self
+                    .infers_untyped_package_signatures(&module_data.handle, &config),
+                check_unmanaged_resources: config
+                    .errors(module_data.handle.path().as_path())
+                    .severity(ErrorKind::UnmanagedResource)
+                    != Severity::Ignore,
+                import_boundaries: Self::import_boundaries(&module_data.handle, &config),
+            };
+            let set = match with_crash_context(Box::new(crash_context), || compute(&ctx)) {
+                Ok(set) => set,
+                Err(message) => todo.recover(&exclusive.steps, &ctx, &message),
+            };
             {
                 let mut changed = false;
                 let mut to_drop = None;
@@ -1204,6 +1252,82 @@ impl<'a> Transaction<'a> {
             .dupe()
     }
 
+    /// The `.pyi` stub next to the first-party `.py` implementation of `handle`, which other
+    /// modules import instead of the implementation, so the implementation is checked against it,
+    /// if the `inconsistent-stub` check is enabled.
+    fn implementation_stub(&self, handle: &Handle, config: &ArcId<ConfigFile>) -> Option<Module> {
+        let path = handle.path();
+        if path.is_interface()
+            || config.module_origin(handle.module(), path) != ModuleOrigin::FirstParty
+            || config
+                .errors(path.as_path())
+                .severity(ErrorKind::InconsistentStub)
+                == Severity::Ignore
+        {
+            return None;
+        }
+        let stub = self
+            .get_cached_loader(config)
+            .find_import(handle.module(), Some(path))
+            .finding()?;
+        if !stub.is_interface() || stub.as_path().with_extension("py") != path.as_path() {
+            return None;
+        }
+        // Load the stub, so errors can point at its declarations.
+        let stub = self.get_module(&Handle::new(
+            handle.module(),
+            stub,
+            handle.sys_info().dupe(),
+        ));
+        self.demand(&stub, Step::Load);
+        let load = stub.state.read().steps.load.dupe()?;
+        Some(load.module_info.dupe())
+    }
+
+    /// Whether `handle` is the `.py` source of an installed package without a `py.typed` marker,
+    /// whose signatures `infer-untyped-package-signatures` asks us to infer from defaults.
+    fn infers_untyped_package_signatures(
+        &self,
+        handle: &Handle,
+        config: &ArcId<ConfigFile>,
+    ) -> bool {
+        let path = handle.path();
+        if path.is_interface()
+            || !config.infer_untyped_package_signatures(path.as_path())
+            || config.module_origin(handle.module(), path) != ModuleOrigin::ThirdParty
+        {
+            return false;
+        }
+        let Some(root) = config
+            .site_package_path()
+            .find(|x| path.as_path().starts_with(x))
+        else {
+            return false;
+        };
+        !path
+            .as_path()
+            .ancestors()
+            .skip(1)
+            .take_while(|x| *x != root.as_path())
+            .any(|x| x.join("py.typed").exists())
+    }
+
+    /// The import boundaries to check the imports of `handle` against, which only apply to the
+    /// project's own code.
+    fn import_boundaries<'c>(
+        handle: &Handle,
+        config: &'c ConfigFile,
+    ) -> Option<&'c ImportBoundaries> {
+        let boundaries = &config.import_boundaries;
+        if boundaries.is_empty()
+            || config.module_origin(handle.module(), handle.path()) != ModuleOrigin::FirstParty
+        {
+            None
+        } else {
+            Some(boundaries)
+        }
+    }
+
     pub fn get_stdlib(&self, handle: &Handle) -> Arc<Stdlib> {
         if self.data.stdlib.len() == 1 {
             // Since we know our one must exist, we can shortcut
@@ -1302,6 +1426,7 @@ impl<'a> Transaction<'a> {
 
     fn run_internal(&mut self, handles: &[Handle], require: Require) -> Result<(), Cancelled> {
         let run_number = self.data.state.run_count.fetch_add(1, Ordering::SeqCst);
+        let _span = trace_span!("run", run_number, handles = handles.len()).entered();
 
         // We first compute all the modules that are either new or have changed.
         // Then we repeatedly compute all the modules who depend on modules that changed.
@@ -1576,6 +1701,19 @@ impl<'a> Transaction<'a> {
                 lookup: &self.lookup(m.dupe()),
                 untyped_def_behavior: config.untyped_def_behavior(m.handle.path().as_path()),
                 infer_with_first_use: config.infer_with_first_use(m.handle.path().as_path()),
+                propagate_constants: config.propagate_constants(m.handle.path().as_path()),
+                reexport: config.reexport(m.handle.path().as_path()),
+                embedded_code: config.embedded_code(m.handle.path().as_path()),
+                parameter_providers: config.parameter_providers(m.handle.path().as_path()),
+                base_class_factories: config.base_class_factories(m.handle.path().as_path()),
+                stub: self.implementation_stub(&m.handle, &config),
+                infer_parameters_from_defaults: self
+                    .infers_untyped_package_signatures(&m.handle, &config),
+                check_unmanaged_resources: config
+                    .errors(m.handle.path().as_path())
+                    .severity(ErrorKind::UnmanagedResource)
+                    != Severity::Ignore,
+                import_boundaries: Self::import_boundaries(&m.handle, &config),
             };
             let mut step = Step::Load; // Start at AST (Load.next)
             alt.load = lock.steps.load.dupe();
@@ -1638,6 +1776,7 @@ fn collect_folding_ranges(
 ) -> Vec<(TextRange, Option<FoldingRangeKind>)> {
     use ruff_python_ast::ExceptHandler;
     use ruff_text_size::Ranged;
+    use ruff_text_size::TextSize;
 
     fn range_without_decorators(
         range: TextRange,
@@ -1652,16 +1791,65 @@ fn collect_folding_ranges(
         })
     }
 
+    /// Whether a line is a `# region` or `# endregion` marker comment, as used by VS Code.
+    fn is_marker(line: &str, marker: &str) -> bool {
+        line.trim_start()
+            .strip_prefix('#')
+            .and_then(|x| x.trim_start().strip_prefix(marker))
+            .is_some_and(|x| !x.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+    }
+
+    /// The ranges between matching `# region` and `# endregion` comments, which may be nested.
+    fn region_ranges(contents: &str) -> Vec<TextRange> {
+        let mut ranges = Vec::new();
+        let mut starts = Vec::new();
+        let mut offset = TextSize::default();
+        for line in contents.split_inclusive('\n') {
+            let text = line.trim_end();
+            let indent = line.len() - line.trim_start().len();
+            let start = offset + TextSize::try_from(indent).unwrap();
+            if is_marker(text, "region") {
+                starts.push(start);
+            } else if is_marker(text, "endregion")
+                && let Some(region_start) = starts.pop()
+            {
+                ranges.push(TextRange::new(region_start, offset + TextSize::of(text)));
+            }
+            offset += TextSize::of(line);
+        }
+        ranges
+    }
+
     struct FoldingRangeCollector<'a> {
         ranges: Vec<(TextRange, Option<FoldingRangeKind>)>,
         module: &'a Module,
     }
 
+    impl FoldingRangeCollector<'_> {
+        /// Fold each run of consecutive imports that spans more than one line, including a single
+        /// import split over several lines.
+        fn push_imports(&mut self, body: &[Stmt]) {
+            let is_import = |x: &Stmt| matches!(x, Stmt::Import(_) | Stmt::ImportFrom(_));
+            for run in body.chunk_by(|x, y| is_import(x) == is_import(y)) {
+                if let (Some(first), Some(last)) = (run.first(), run.last())
+                    && is_import(first)
+                {
+                    let range = first.range().cover(last.range());
+                    let lsp_range = self.module.lined_buffer().to_lsp_range(range);
+                    if lsp_range.start.line != lsp_range.end.line {
+                        self.ranges.push((range, Some(FoldingRangeKind::Imports)));
+                    }
+                }
+            }
+        }
+    }
+
     impl Visitor<'_> for FoldingRangeCollector<'_> {
         fn visit_body(&mut self, body: &[Stmt]) {
             if let Some(range) = Docstring::range_from_stmts(body) {
                 self.ranges.push((range, Some(FoldingRangeKind::Comment)));
             }
+            self.push_imports(body);
             walk_body(self, body);
         }
 
@@ -1758,11 +1946,18 @@ fn collect_folding_ranges(
             .ranges
             .push((range, Some(FoldingRangeKind::Comment)));
     }
+    collector.push_imports(body);
 
     for stmt in body {
         Visitor::visit_stmt(&mut collector, stmt);
     }
 
+    for range in region_ranges(module.contents()) {
+        collector
+            .ranges
+            .push((range, Some(FoldingRangeKind::Region)));
+    }
+
     collector.ranges
 }
 
@@ -2066,6 +2261,47 @@ impl State {
         drop(committing_transaction_guard)
     }
 
+    /// Drop the state of modules that can't be reached by following imports from `roots`, and
+    /// which haven't been checked in the last `keep_recent` epochs. Modules that are indexed for
+    /// IDE features, and the bundled typeshed (which the cached stdlib refers to), are always kept.
+    /// Returns the number of modules that were dropped.
+    pub fn collect_garbage(&self, roots: &[Handle], keep_recent: u32) -> usize {
+        let _committing_transaction_guard = self.committing_transaction_lock.lock();
+        let mut state = self.state.write();
+        let now = state.now;
+        let mut todo = roots
+            .iter()
+            .filter(|x| state.modules.contains_key(x))
+            .cloned()
+            .collect::<Vec<_>>();
+        for (handle, module) in state.modules.iter() {
+            if matches!(
+                handle.path().details(),
+                ModulePathDetails::BundledTypeshed(_)
+            ) || matches!(module.state.require, Require::Indexing(_))
+                || now.since(module.state.epochs.checked) <= keep_recent
+            {
+                todo.push(handle.dupe());
+            }
+        }
+        // Keep everything the kept modules depend on, so no module we keep has a dangling dependency.
+        let mut keep = HashSet::new();
+        while let Some(handle) = todo.pop() {
+            if !keep.insert(handle.dupe()) {
+                continue;
+            }
+            if let Some(module) = state.modules.get(&handle) {
+                todo.extend(module.deps.values().flatten().cloned());
+            }
+        }
+        let before = state.modules.len();
+        state.modules.retain(|handle, _| keep.contains(handle));
+        for module in state.modules.values_mut() {
+            module.rdeps.retain(|x| keep.contains(x));
+        }
+        before - state.modules.len()
+    }
+
     pub fn run(
         &self,
         handles: &[Handle],
@@ -5,12 +5,14 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use dupe::Dupe;
 use enum_iterator::Sequence;
 use parse_display::Display;
 use paste::paste;
+use pyrefly_python::module::Module;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::sys_info::SysInfo;
@@ -21,17 +23,27 @@ use crate::alt::answers::Answers;
 use crate::alt::answers::LookupAnswer;
 use crate::alt::answers::Solutions;
 use crate::binding::bindings::Bindings;
+use crate::config::base::EmbeddedCodeKind;
+use crate::config::base::ParameterProviderKind;
+use crate::config::base::ReexportMode;
 use crate::config::base::UntypedDefBehavior;
+use crate::config::import_boundaries::ImportBoundaries;
 use crate::error::style::ErrorStyle;
 use crate::export::exports::Exports;
 use crate::export::exports::LookupExport;
 use crate::module::parse::module_parse;
 use crate::solver::solver::Solver;
+use crate::state::crash_report::add_crash_error;
+use crate::state::load::CodeOrNotebook;
 use crate::state::load::Load;
 use crate::state::memory::MemoryFilesLookup;
 use crate::state::require::Require;
 use crate::types::stdlib::Stdlib;
 
+/// A module whose exports step panics, to test recovering from a crash.
+#[cfg(test)]
+pub const TEST_PANIC_MODULE: &str = "pyrefly_test_panic";
+
 pub struct Context<'a, Lookup> {
     pub require: Require,
     pub module: ModuleName,
@@ -43,6 +55,20 @@ pub struct Context<'a, Lookup> {
     pub lookup: &'a Lookup,
     pub untyped_def_behavior: UntypedDefBehavior,
     pub infer_with_first_use: bool,
+    pub propagate_constants: bool,
+    pub reexport: ReexportMode,
+    pub embedded_code: &'a [EmbeddedCodeKind],
+    pub parameter_providers: &'a [ParameterProviderKind],
+    pub base_class_factories: &'a BTreeMap<String, String>,
+    /// The `.pyi` stub that other modules see instead of this `.py` implementation, if any.
+    pub stub: Option<Module>,
+    /// Whether unannotated parameters take the type of their default value, because this is an
+    /// untyped installed package and `infer-untyped-package-signatures` is enabled.
+    pub infer_parameters_from_defaults: bool,
+    /// Whether the `unmanaged-resource` check is enabled, so it is worth running.
+    pub check_unmanaged_resources: bool,
+    /// The import boundaries to check the imports against, if this module is part of the project.
+    pub import_boundaries: Option<&'a ImportBoundaries>,
 }
 
 #[derive(Debug, Default, Dupe, Clone)]
@@ -128,18 +154,84 @@ impl Step {
         }
     }
 
-    #[inline(never)]
-    fn step_load<Lookup>(ctx: &Context<Lookup>) -> Arc<Load> {
-        let error_style = if ctx.require.compute_errors() {
+    /// Recover from computing this step panicking with `message`, by recording the panic as an
+    /// error in the module and computing the step as though the module were empty. Anything
+    /// computed from the module's contents is replaced too, so the steps stay consistent with
+    /// each other, and importers of the module see it as empty, rather than panicking again.
+    pub fn recover<Lookup: LookupExport + LookupAnswer>(
+        self,
+        steps: &Steps,
+        ctx: &Context<Lookup>,
+        message: &str,
+    ) -> Box<dyn FnOnce(&mut Steps)> {
+        let load = match &steps.load {
+            Some(load) if self != Step::Load => load.dupe(),
+            _ => Arc::new(Load::load_from_data(
+                ctx.module,
+                ctx.path.dupe(),
+                Self::error_style(ctx),
+                CodeOrNotebook::Code(Arc::new(String::new())),
+                None,
+            )),
+        };
+        add_crash_error(&load.errors, ctx.module, self, message);
+        let ast = (self >= Step::Ast)
+            .then(|| Arc::new(module_parse("", ctx.sys_info.version(), &load.errors)));
+        let exports =
+            (self >= Step::Exports).then(|| Exports::new(&[], &load.module_info, ctx.sys_info));
+        let answers = match (&ast, &exports) {
+            (Some(ast), Some(exports)) if self >= Step::Answers => Some(Self::step_answers(
+                ctx,
+                load.dupe(),
+                ast.dupe(),
+                exports.dupe(),
+            )),
+            _ => None,
+        };
+        let solutions = match &answers {
+            Some(answers) if self >= Step::Solutions => {
+                Some(Self::step_solutions(ctx, load.dupe(), answers.dupe()))
+            }
+            _ => None,
+        };
+        Box::new(move |steps: &mut Steps| {
+            if self == Step::Load {
+                steps.load = Some(load);
+            }
+            if let Some(ast) = ast
+                && (self <= Step::Exports || steps.ast.is_some())
+            {
+                // Once we have answers we may have dropped the AST, and shouldn't bring it back.
+                steps.ast = Some(ast);
+            }
+            if exports.is_some() {
+                steps.exports = exports;
+            }
+            if answers.is_some() {
+                steps.answers = answers;
+            }
+            if solutions.is_some() {
+                steps.solutions = solutions;
+            }
+            steps.last_step = Some(self);
+        })
+    }
+
+    fn error_style<Lookup>(ctx: &Context<Lookup>) -> ErrorStyle {
+        if ctx.require.compute_errors() {
             ErrorStyle::Delayed
         } else {
             ErrorStyle::Never
-        };
+        }
+    }
+
+    #[inline(never)]
+    fn step_load<Lookup>(ctx: &Context<Lookup>) -> Arc<Load> {
         let (code_or_notebook, self_error) = Load::load_from_path(ctx.path, ctx.memory);
         Arc::new(Load::load_from_data(
             ctx.module,
             ctx.path.dupe(),
-            error_style,
+            Self::error_style(ctx),
             code_or_notebook,
             self_error,
         ))
@@ -160,6 +252,10 @@ impl Step {
         load: Arc<Load>,
         ast: Arc<ModModule>,
     ) -> Exports {
+        #[cfg(test)]
+        if ctx.module.as_str() == TEST_PANIC_MODULE {
+            panic!("Computing the exports of `{TEST_PANIC_MODULE}` always panics");
+        }
         Exports::new(&ast.body, &load.module_info, ctx.sys_info)
     }
 
@@ -184,6 +280,15 @@ impl Step {
             ctx.uniques,
             enable_trace,
             ctx.untyped_def_behavior,
+            ctx.propagate_constants,
+            ctx.reexport,
+            ctx.embedded_code,
+            ctx.parameter_providers,
+            ctx.base_class_factories,
+            ctx.stub.as_ref(),
+            ctx.infer_parameters_from_defaults,
+            ctx.check_unmanaged_resources,
+            ctx.import_boundaries,
         );
         let answers = Answers::new(&bindings, solver, enable_index, enable_trace);
         Arc::new((bindings, Arc::new(answers)))
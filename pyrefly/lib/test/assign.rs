@@ -297,6 +297,23 @@ def f(x: list[str]):
     "#,
 );
 
+testcase!(
+    test_unpack_in_iteration,
+    r#"
+from typing import assert_type
+def f(d: dict[str, int], xs: list[int], ys: list[str], t: tuple[int, str, bytes]):
+    for k, v in d.items():
+        assert_type(k, str)
+        assert_type(v, int)
+    for x, y in zip(xs, ys, strict=True):
+        assert_type(x, int)
+        assert_type(y, str)
+    a, *rest = t
+    assert_type(a, int)
+    assert_type(rest, list[str | bytes])
+    "#,
+);
+
 testcase!(
     test_splat_error,
     r#"
@@ -279,7 +279,6 @@ def foo(x: Callable[[int], str], c: C, c2: C2, c3: C3):
 );
 
 testcase!(
-    bug = "classmethod bound object w/o targs is default-instantiated, solves T to Any",
     test_bound_classmethod_explicit_targs,
     r#"
 from typing import assert_type
@@ -292,7 +291,7 @@ class A[T]:
         return cls(x)
 
 assert_type(A[int].m(0), A[int])
-assert_type(A.m(0), A[int]) # TODO # E: assert_type(A[Any], A[int]) failed
+assert_type(A.m(0), A[int])
 
 def test_typevar_bounds[T: A[int]](x: type[T]):
     assert_type(x.m(0), A[int])
@@ -1739,3 +1738,90 @@ def f(g: Callable[P, T], ts: tuple[*Ts], *args: P.args, **kwargs: P.kwargs):
     ty(Ts).__name__
 "#,
 );
+
+testcase!(
+    test_slots_undeclared_attribute,
+    r#"
+class A:
+    __slots__ = ("x",)
+
+    def __init__(self) -> None:
+        self.x = 1
+        self.y = 2  # E: Attribute `y` is not declared in `__slots__` of `A`
+
+class B:
+    __slots__ = ("x", "__dict__")
+
+    def __init__(self) -> None:
+        self.x = 1
+        self.y = 2
+
+class Base:
+    __slots__ = ("y",)
+
+class C(Base):
+    __slots__ = ("x",)
+
+    def __init__(self) -> None:
+        self.x = 1
+        self.y = 2
+    "#,
+);
+
+testcase!(
+    test_slots_conflict_with_class_variable,
+    r#"
+class A:
+    __slots__ = ("x", "y")
+    x: int = 0  # E: `x` in `__slots__` conflicts with a class variable of the same name
+    y: int
+    "#,
+);
+
+testcase!(
+    test_slots_dict_from_base,
+    r#"
+class Base:
+    pass
+
+class A(Base):
+    __slots__ = ("__dict__",)  # E: `__dict__` in `__slots__` of `A` conflicts with base class `Base`
+
+class B(Base):
+    __slots__ = ("x",)
+
+    def __init__(self) -> None:
+        self.y = 1
+    "#,
+);
+
+testcase!(
+    test_possibly_missing_attribute,
+    TestEnv::new().enable_possibly_missing_attribute_error(),
+    r#"
+class A:
+    def __init__(self, flag: bool) -> None:
+        if flag:
+            self.x = 1  # E: Attribute `x` is only assigned on some paths through `__init__`
+            self.y = 1
+        else:
+            self.y = 2
+        try:
+            self.z = 1  # E: Attribute `z` is only assigned on some paths through `__init__`
+        except Exception:
+            return
+
+class B:
+    w: int = 0
+
+    def __init__(self, flag: bool) -> None:
+        if flag:
+            self.w = 1
+            self.v = 1
+        else:
+            raise ValueError()
+
+    def reset(self) -> None:
+        self.u = 1
+    "#,
+);
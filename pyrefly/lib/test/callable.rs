@@ -797,6 +797,35 @@ def test(cls: C):
 "#,
 );
 
+testcase!(
+    test_callable_class_generic_and_overloaded_call,
+    r#"
+from typing import Callable, Protocol, overload
+class Identity:
+    def __call__[T](self, x: T) -> T:
+        return x
+class Parse:
+    @overload
+    def __call__(self, x: str) -> int: ...
+    @overload
+    def __call__(self, x: bytes) -> float: ...
+    def __call__(self, x: str | bytes) -> int | float:
+        return 0
+class Box[T]:
+    def __call__(self) -> T: ...
+class Handler(Protocol):
+    def __call__(self, x: str) -> int: ...
+
+handlers: dict[str, Callable[[str], int]] = {"parse": Parse()}
+f: Callable[[int], int] = Identity()
+g: Callable[[str], str] = Identity()
+h: Handler = Parse()
+i: Callable[[], int] = Box[int]()
+j: Callable[[int], int] = Parse()  # E: `Parse` is not assignable to `(int) -> int`
+k: Callable[[], str] = Box[int]()  # E: `Box[int]` is not assignable to `() -> str`
+"#,
+);
+
 testcase!(
     test_callable_class_functools_partial,
     r#"
@@ -6,8 +6,10 @@
  */
 
 use pyrefly_python::sys_info::PythonVersion;
+use ruff_text_size::Ranged;
 
 use crate::test::util::TestEnv;
+use crate::test::util::mk_state;
 use crate::testcase;
 
 testcase!(
@@ -236,3 +238,31 @@ from typing import Any
 Any()  # E: `Any` can not be instantiated
     "#,
 );
+
+#[test]
+fn test_arity_error_points_at_definition() {
+    let (handle, state) = mk_state(
+        r#"
+def f(x: int) -> None: ...
+f(1, 2)
+f()
+"#,
+    );
+    let errors = state
+        .transaction()
+        .get_errors([&handle])
+        .collect_errors()
+        .shown;
+    assert_eq!(errors.len(), 2);
+    for error in &errors {
+        let [(definition, msg)] = error.related() else {
+            panic!("Expected one related location for: {}", error.msg());
+        };
+        assert_eq!(definition.module.code_at(definition.range), "f");
+        assert_eq!(msg, "`f` is defined here with signature `(x: int) -> None`");
+    }
+    // Each error points at the offending argument, or the closing parenthesis of the call.
+    let module = errors[0].module();
+    assert_eq!(module.code_at(errors[0].range()), "2");
+    assert_eq!(module.code_at(errors[1].range()), ")");
+}
@@ -93,7 +93,10 @@ testcase!(
     r#"
 def f(x: bool) -> bool: ...
 
-class A(foo=f(15)):  # E: Argument `Literal[15]` is not assignable to parameter `x` with type `bool`
+class Base:
+    def __init_subclass__(cls, **kwargs) -> None: ...
+
+class A(Base, foo=f(15)):  # E: Argument `Literal[15]` is not assignable to parameter `x` with type `bool`
     pass
 "#,
 );
@@ -133,7 +136,7 @@ class A(B0, B1):  # E:  Class `A` has metaclass `M0` which is not a subclass of
 testcase!(
     test_duplicate_class_keyword,
     r#"
-class A(foo="x" + 5, foo=True):  # E: Parse error: Duplicate keyword argument "foo"  # E: `+` is not supported between `Literal['x']` and `Literal[5]`
+class A(foo="x" + 5, foo=True):  # E: Parse error: Duplicate keyword argument "foo"  # E: `+` is not supported between `Literal['x']` and `Literal[5]`  # E: Unexpected keyword argument `foo`
     pass
 "#,
 );
@@ -153,3 +156,46 @@ f(C1)
 f(C2[int])
     "#,
 );
+
+testcase!(
+    test_init_subclass_keywords,
+    r#"
+class Base:
+    def __init_subclass__(cls, *, flag: bool = False) -> None: ...
+class A(Base, flag=True): ...
+class B(Base, flag=1):  # E: Argument `Literal[1]` is not assignable to parameter `flag` with type `bool`
+    ...
+class C(Base, other=True):  # E: Unexpected keyword argument `other`
+    ...
+class D(other=True):  # E: Unexpected keyword argument `other`
+    ...
+"#,
+);
+
+testcase!(
+    test_init_subclass_required_argument,
+    r#"
+class Base:
+    def __init_subclass__(cls, name: str) -> None: ...
+class A(Base, name="a"): ...
+class B(Base): ...  # E: Missing argument `name`
+"#,
+);
+
+testcase!(
+    test_init_subclass_of_class_itself,
+    r#"
+# A class's own `__init_subclass__` is only called for its subclasses.
+class A(flag=True):  # E: Unexpected keyword argument `flag`
+    def __init_subclass__(cls, flag: bool) -> None: ...
+class B(A, flag=True): ...
+"#,
+);
+
+testcase!(
+    test_init_subclass_keywords_with_metaclass,
+    r#"
+class Meta(type): ...
+class A(metaclass=Meta, anything=1): ...
+"#,
+);
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use crate::test::util::TestEnv;
 use crate::testcase;
 
 testcase!(
@@ -262,6 +263,70 @@ assert_type(B.x, Any)
     "#,
 );
 
+testcase!(
+    test_base_class_factory_returning_type,
+    r#"
+from typing import assert_type
+class Base:
+    x: int = 0
+def make_base(flag: bool) -> type[Base]:
+    return Base
+class Foo(make_base(True)):
+    pass
+assert_type(Foo().x, int)
+Foo().y  # E: Object of class `Foo` has no attribute `y`
+b: Base = Foo()
+class Bar(make_base("no")):  # E: Argument `Literal['no']` is not assignable to parameter `flag` with type `bool` in function `make_base`
+    pass
+    "#,
+);
+
+testcase!(
+    test_generic_base_class_factory,
+    r#"
+from typing import TypeVar
+T = TypeVar("T")
+def mixin(base: type[T]) -> type[T]:
+    return base
+class A:
+    pass
+class B(mixin(A)):  # E: Invalid expression form for base class: `mixin(A)`
+    pass
+    "#,
+);
+
+fn env_base_class_factory() -> TestEnv {
+    let mut t = TestEnv::new().with_base_class_factory("orm.make_model", "orm.Model");
+    t.add(
+        "orm",
+        r#"
+from typing import Any
+class Model:
+    def save(self) -> None: ...
+def make_model(config: dict[str, Any]) -> type: ...
+"#,
+    );
+    t
+}
+
+testcase!(
+    test_base_class_factory_from_config,
+    env_base_class_factory(),
+    r#"
+from typing import assert_type
+import orm
+from orm import make_model
+class User(make_model({"table": "users"})):
+    pass
+class Post(orm.make_model({})):
+    pass
+assert_type(User().save(), None)
+assert_type(Post().save(), None)
+User().delete()  # E: Object of class `User` has no attribute `delete`
+m: orm.Model = User()
+    "#,
+);
+
 testcase!(
     test_multiple_inheritance_incompatible_field,
     r#"
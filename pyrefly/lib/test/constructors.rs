@@ -560,3 +560,100 @@ class A:
 A(0) # E: `A.__new__` is deprecated # E: `Literal[0]` is not assignable to parameter `x` with type `str`
     "#,
 );
+
+testcase!(
+    test_infer_targs_from_inherited_init,
+    r#"
+from typing import assert_type
+
+class Base[T]:
+    def __init__(self, x: T) -> None:
+        self.x = x
+
+class Child[T](Base[T]):
+    pass
+
+class Wrapper[T](Base[list[T]]):
+    pass
+
+assert_type(Child(1), Child[int])
+assert_type(Wrapper([""]), Wrapper[str])
+    "#,
+);
+
+testcase!(
+    test_infer_targs_from_inherited_new,
+    r#"
+from typing import Self, assert_type
+
+class Base[T]:
+    def __new__(cls, x: T) -> Self:
+        return super().__new__(cls)
+
+class Child[T](Base[T]):
+    def __new__(cls, x: T) -> Self:
+        return super().__new__(cls, x)
+
+assert_type(Child(1), Child[int])
+    "#,
+);
+
+testcase!(
+    test_construct_self_in_classmethod,
+    r#"
+from typing import Self, assert_type
+
+class A[T]:
+    def __init__(self, x: T) -> None:
+        self.x = x
+
+    @classmethod
+    def same(cls, x: T) -> Self:
+        y = cls(x)
+        assert_type(y, Self)
+        return y
+
+    @classmethod
+    def of_int(cls, x: int) -> "A[int]":
+        y = cls(x)
+        assert_type(y, A[int])
+        return y
+
+    def copy(self) -> Self:
+        return type(self)(self.x)
+    "#,
+);
+
+testcase!(
+    test_construct_self_in_generic_classmethod_factory,
+    r#"
+from typing import Self, assert_type
+
+class Model[T]:
+    def __init__(self) -> None:
+        self.items: list[T] = []
+
+    @classmethod
+    def empty(cls) -> Self:
+        x = cls()
+        assert_type(x, Self)
+        return x
+
+    @classmethod
+    def parse(cls: type[Self], data: str) -> Self:
+        return cls()
+
+    @classmethod
+    def build(cls):
+        return cls()
+
+class User(Model[int]):
+    pass
+
+assert_type(User.empty(), User)
+assert_type(User.parse(""), User)
+assert_type(User.build(), User)
+assert_type(User().parse(""), User)
+assert_type(Model[str].empty(), Model[str])
+    "#,
+);
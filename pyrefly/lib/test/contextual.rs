@@ -319,6 +319,29 @@ g: Callable[[int, int], None] = lambda x: None # E: `(x: int) -> None` is not as
 "#,
 );
 
+testcase!(
+    test_context_lambda_overload,
+    r#"
+from typing import assert_type
+class A:
+    name: str
+def f(xs: list[A]) -> None:
+    assert_type(sorted(xs, key=lambda x: x.name), list[A])
+    sorted(xs, key=lambda x: x.nmae)  # E: Object of class `A` has no attribute `nmae`
+    max(xs, key=lambda x: assert_type(x, A).name)
+"#,
+);
+
+testcase!(
+    test_context_lambda_in_containers,
+    r#"
+from typing import Callable
+callbacks: list[Callable[[int], str]] = [lambda i: str(i + 1), lambda i: i.upper()]  # E: Object of class `int` has no attribute `upper`
+adders: list[Callable[[int], int]] = [lambda x: x + i for i in range(3)]
+by_name: dict[str, Callable[[int], int]] = {str(i): lambda x: x.bit_length() for i in range(3)}
+"#,
+);
+
 testcase!(
     test_context_lambda_generic,
     r#"
@@ -336,3 +336,18 @@ class A:
         return self.d
     "#,
 );
+
+testcase!(
+    test_set_name,
+    r#"
+class Named:
+    def __set_name__(self, owner: type["A"], name: str) -> None: ...
+class Unnamed:
+    def __set_name__(self, owner: type, name: int) -> None: ...
+class A:
+    x = Named()
+class B:
+    x = Named()  # E: Argument `type[B]` is not assignable to parameter `owner` with type `type[A]`
+    y = Unnamed()  # E: Argument `Literal['y']` is not assignable to parameter `name` with type `int`
+    "#,
+);
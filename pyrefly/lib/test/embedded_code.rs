@@ -0,0 +1,61 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::config::base::EmbeddedCodeKind;
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+fn env_eval() -> TestEnv {
+    TestEnv::new().enable_embedded_code(EmbeddedCodeKind::Eval)
+}
+
+testcase!(
+    test_eval,
+    env_eval(),
+    r#"
+x: int = 1
+eval("x + 1")
+eval("x + 'a'")  # E: `+` is not supported between `int` and `Literal['a']`
+eval("undefined_name")  # E: Could not find name `undefined_name`
+"#,
+);
+
+testcase!(
+    test_eval_in_function,
+    env_eval(),
+    r#"
+def f(y: str) -> None:
+    eval("y.upper()")
+    eval("y.missing()")  # E: Object of class `str` has no attribute `missing`
+"#,
+);
+
+testcase!(
+    test_eval_not_checked,
+    env_eval(),
+    r#"
+x: int = 1
+eval("x + 'a'", {})  # Globals are given, so the scope isn't known.
+eval("x + \x27a\x27")  # Escapes can't be mapped back to the source.
+eval(f"x + 'a'")
+"#,
+);
+
+testcase!(
+    test_eval_parse_error,
+    env_eval(),
+    r#"
+eval("1 +")  # E: Could not parse embedded code
+"#,
+);
+
+testcase!(
+    test_eval_disabled,
+    r#"
+eval("undefined_name")
+"#,
+);
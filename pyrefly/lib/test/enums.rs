@@ -638,3 +638,46 @@ assert_type(E3.X.value, str)
 assert_type(E4.X.value, tuple[int])
     "#,
 );
+
+testcase!(
+    test_non_exhaustive_enum_dict,
+    TestEnv::new().enable_non_exhaustive_enum_dict_error(),
+    r#"
+from enum import Enum
+from typing import Callable
+
+class State(Enum):
+    IDLE = 1
+    RUNNING = 2
+    DONE = 3
+
+def handle() -> None: ...
+
+complete: dict[State, Callable[[], None]] = {
+    State.IDLE: handle,
+    State.RUNNING: handle,
+    State.DONE: handle,
+}
+partial: dict[State, Callable[[], None]] = {  # E: Dict literal keyed by enum `State` is missing entries for `State.RUNNING`, `State.DONE`
+    State.IDLE: handle,
+}
+def f(state: State, rest: dict[State, int]) -> None:
+    # We can't tell which members these cover.
+    x: dict[State, int] = {state: 1}
+    y: dict[State, int] = {State.IDLE: 1, **rest}
+    z: dict[State, int] = {}
+"#,
+);
+
+testcase!(
+    test_non_exhaustive_enum_dict_off_by_default,
+    r#"
+from enum import Enum
+
+class Color(Enum):
+    RED = 1
+    BLUE = 2
+
+names: dict[Color, str] = {Color.RED: "red"}
+"#,
+);
@@ -909,6 +909,7 @@ if foo:  # E: Function object `foo` used as condition
 while foo:  # E: Function object `foo` used as condition
     ...
 [x for x in range(42) if foo]  # E: Function object `foo` used as condition
+{x for x in range(42) if foo}  # E: Function object `foo` used as condition
     "#,
 );
 
@@ -923,6 +924,7 @@ if Foo:  # E: Class name `Foo` used as condition
 while Foo:  # E: Class name `Foo` used as condition
     ...
 [x for x in range(42) if Foo]  # E: Class name `Foo` used as condition
+{x for x in range(42) if Foo}  # E: Class name `Foo` used as condition
     "#,
 );
 
@@ -10,6 +10,7 @@ use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::fs_anyhow;
 
+use crate::config::import_boundaries::ImportBoundaries;
 use crate::test::util::TestEnv;
 use crate::testcase;
 
@@ -1076,3 +1077,93 @@ testcase!(
 x: X = X()
 "#,
 );
+
+fn env_reexport() -> TestEnv {
+    let mut t = TestEnv::new().enable_explicit_reexport();
+    t.add("base", "x: int = 1\ny: int = 2\nz: int = 3");
+    t.add(
+        "lib",
+        r#"
+import base
+from base import x
+from base import y as y
+from base import z as zz
+w: int = 4
+"#,
+    );
+    t.add("lib_all", "from base import x, y\n__all__ = ['x']");
+    t.add_with_path(
+        "pkg",
+        "pkg/__init__.py",
+        "from . import sub\nfrom .sub import v",
+    );
+    t.add_with_path("pkg.sub", "pkg/sub.py", "v: int = 1");
+    t
+}
+
+testcase!(
+    test_explicit_reexport,
+    env_reexport(),
+    r#"
+from lib import base  # E: `base` is imported into `lib` but not re-exported
+from lib import x  # E: `x` is imported into `lib` but not re-exported
+from lib import y, w
+from lib import zz  # E: `zz` is imported into `lib` but not re-exported
+from lib_all import x as x2
+from lib_all import y as y2  # E: `y` is imported into `lib_all` but not re-exported
+from pkg import sub
+from pkg import v  # E: `v` is imported into `pkg` but not re-exported
+"#,
+);
+
+fn env_implicit_reexport() -> TestEnv {
+    let mut t = TestEnv::new();
+    t.add("base", "x: int = 1");
+    t.add("lib", "from base import x");
+    t
+}
+
+testcase!(
+    test_implicit_reexport,
+    env_implicit_reexport(),
+    r#"
+from lib import x
+"#,
+);
+
+fn env_import_boundaries() -> TestEnv {
+    let mut t = TestEnv::new().with_import_boundaries(
+        ImportBoundaries::new(
+            vec!["app".to_owned(), "main".to_owned(), "lib".to_owned()],
+            &["*.internal"],
+        )
+        .unwrap(),
+    );
+    t.add_with_path("app", "app/__init__.py", "x: int = 1");
+    t.add_with_path("lib", "lib/__init__.py", "x: int = 1");
+    t.add_with_path(
+        "lib.db",
+        "lib/db/__init__.py",
+        "from lib.db.internal.pool import connect",
+    );
+    t.add_with_path("lib.db.internal", "lib/db/internal/__init__.py", "");
+    t.add_with_path(
+        "lib.db.internal.pool",
+        "lib/db/internal/pool.py",
+        "def connect() -> None: ...",
+    );
+    t
+}
+
+testcase!(
+    test_import_boundaries,
+    env_import_boundaries(),
+    r#"
+import app  # E: `main` may not import `app`, because layer `main` is below layer `app`
+import lib
+from lib import x
+from lib.db import connect
+from lib.db.internal.pool import connect as connect2  # E: `main` may not import `lib.db.internal.pool`, because `lib.db.internal` is private to `lib.db`
+from lib.db import internal  # E: `main` may not import `lib.db.internal`, because `lib.db.internal` is private to `lib.db`
+"#,
+);
@@ -0,0 +1,87 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+testcase!(
+    test_library_mode_off_by_default,
+    r#"
+def f(x):
+    return x
+"#,
+);
+
+testcase!(
+    test_missing_public_annotation,
+    TestEnv::new().enable_library_mode_errors(),
+    r#"
+def f(x, y: int):  # E: Parameter `x` of public function `f` is missing an annotation  # E: Public function `f` is missing a return annotation
+    return y
+
+def _private(x):
+    return x
+
+def outer() -> None:
+    def inner(x):
+        pass
+
+class A:
+    def __init__(self, x: int):
+        self.x = x
+
+    def method(self, y) -> None:  # E: Parameter `y` of public function `method` is missing an annotation
+        pass
+
+    @staticmethod
+    def static(x: int):  # E: Public function `static` is missing a return annotation
+        return x
+
+    def _helper(self, y):
+        pass
+
+class _B:
+    def method(self, y):
+        pass
+"#,
+);
+
+fn env_private_module() -> TestEnv {
+    let mut t = TestEnv::new().enable_library_mode_errors();
+    t.add_with_path("lib", "lib/__init__.py", "from lib._impl import Exported");
+    t.add_with_path(
+        "lib._impl",
+        "lib/_impl.py",
+        r#"
+class Exported: ...
+class Hidden: ...
+"#,
+    );
+    t
+}
+
+testcase!(
+    test_private_type_in_public_api,
+    env_private_module(),
+    r#"
+from lib._impl import Exported, Hidden
+
+class _Internal: ...
+
+def f(x: Exported) -> Exported:
+    return x
+
+def g(x: Hidden) -> None:  # E: Public function `g` mentions `Hidden` from private module `lib._impl`, which isn't re-exported
+    pass
+
+def h(x: _Internal) -> list[_Internal]:  # E: Public function `h` mentions internal class `_Internal`
+    return [x]
+
+def _private(x: Hidden) -> _Internal:
+    return _Internal()
+"#,
+);
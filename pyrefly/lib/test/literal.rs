@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use crate::test::util::TestEnv;
 use crate::testcase;
 
 testcase!(
@@ -324,3 +325,55 @@ result5 = x.replace(old, non_lit)
 assert_type(result5, str)
 "#,
 );
+
+testcase!(
+    test_literal_table,
+    r#"
+from typing import Final, Literal, assert_type
+STATUS_CODES: Final = {"ok": 200, "missing": 404}
+SIZES: Final = (1, 2, 3)
+Status = Literal[tuple(STATUS_CODES)]
+def lookup(status: Status) -> int:
+    return STATUS_CODES[status]
+lookup("ok")
+lookup("teapot")  # E: Argument `Literal['teapot']` is not assignable to parameter `status`
+def size(x: Literal[tuple(SIZES), 4]):
+    assert_type(x, Literal[1, 2, 3, 4])
+"#,
+);
+
+fn env_codes() -> TestEnv {
+    TestEnv::one(
+        "codes",
+        r#"
+from typing import Final
+CODES: Final = {"ok": 200, "missing": 404}
+"#,
+    )
+}
+
+testcase!(
+    test_literal_table_imported,
+    env_codes(),
+    r#"
+from typing import Literal, assert_type
+from codes import CODES
+def f(code: Literal[tuple(CODES)]):
+    assert_type(code, Literal["ok", "missing"])
+"#,
+);
+
+testcase!(
+    test_literal_table_invalid,
+    r#"
+from typing import Final, Literal
+NOT_FINAL = {"ok": 200}
+NOT_LITERAL: Final = {str(1): 200}
+CODES: Final = ("ok",)
+def f(x: Literal[tuple(NOT_FINAL)]): ...  # E: Invalid literal expression
+def g(x: Literal[tuple(NOT_LITERAL)]): ...  # E: Invalid literal expression
+def h():
+    CODES = ("missing",)
+    def inner(x: Literal[tuple(CODES)]): ...  # E: Invalid literal expression
+"#,
+);
@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::call_hierarchy::CallHierarchyTarget;
+use crate::state::lsp::call_hierarchy::incoming_calls;
+use crate::state::state::State;
+use crate::test::util::code_frame_of_source_at_range;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn describe(target: &CallHierarchyTarget) -> String {
+    format!(
+        "{} ({:?}) in {}",
+        target.name,
+        target.kind,
+        target.module.name()
+    )
+}
+
+fn describe_calls(calls: Vec<(CallHierarchyTarget, Vec<TextRange>)>, source: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    for (target, ranges) in calls {
+        res.push(describe(&target));
+        for range in ranges {
+            res.push(code_frame_of_source_at_range(source, range));
+        }
+    }
+    res
+}
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let mut report = Vec::new();
+    for target in transaction.prepare_call_hierarchy(handle, position) {
+        report.push(format!("Item: {}", describe(&target)));
+    }
+    report.push("Outgoing:".to_owned());
+    report.extend(describe_calls(
+        transaction.outgoing_calls(handle, position),
+        module_info.contents(),
+    ));
+    report.push("Incoming:".to_owned());
+    // All the callers are in the same module in these tests.
+    let references = transaction.find_local_references(handle, position);
+    report.extend(describe_calls(
+        incoming_calls(vec![(module_info.dupe(), references)]),
+        module_info.contents(),
+    ));
+    report.join("\n")
+}
+
+#[test]
+fn function_calls_test() {
+    let code = r#"
+def helper():
+    pass
+
+def caller():
+#    ^
+    helper()
+    helper()
+
+class Greeter:
+    def greet(self):
+        return caller()
+
+caller()
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+5 | def caller():
+         ^
+Item: caller (Function) in main
+Outgoing:
+helper (Function) in main
+7 |     helper()
+        ^^^^^^
+8 |     helper()
+        ^^^^^^
+Incoming:
+greet (Method) in main
+12 |         return caller()
+                    ^^^^^^
+main (Module) in main
+14 | caller()
+     ^^^^^^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn imported_and_nested_calls_test() {
+    let lib = r#"
+def f():
+    pass
+"#;
+    let code = r#"
+from lib import f
+
+def g():
+#   ^
+    f()
+    def nested():
+        g()
+"#;
+    let report =
+        get_batched_lsp_operations_report(&[("lib", lib), ("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# lib.py
+
+# main.py
+4 | def g():
+        ^
+Item: g (Function) in main
+Outgoing:
+f (Function) in lib
+6 |     f()
+        ^
+Incoming:
+nested (Function) in main
+8 |         g()
+            ^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
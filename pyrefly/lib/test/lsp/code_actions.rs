@@ -5,6 +5,9 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cmp::Reverse;
+
+use itertools::Itertools;
 use pretty_assertions::assert_eq;
 use pyrefly_build::handle::Handle;
 use ruff_text_size::TextRange;
@@ -12,8 +15,12 @@ use ruff_text_size::TextSize;
 
 use crate::module::module_info::ModuleInfo;
 use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::RefactorEdit;
+use crate::state::require::Require;
 use crate::state::state::State;
+use crate::state::state::Transaction;
 use crate::test::util::get_batched_lsp_operations_report_allow_error;
+use crate::test::util::mk_multi_file_state;
 
 fn apply_patch(info: &ModuleInfo, range: TextRange, patch: String) -> (String, String) {
     let before = info.contents().as_str().to_owned();
@@ -100,6 +107,38 @@ my_export
     );
 }
 
+#[test]
+fn insertion_test_closest_module_first() {
+    let (handles, state) = mk_multi_file_state(
+        &[
+            ("geometry", "def area() -> int: ...\n"),
+            ("shapes.geometry", "def area() -> int: ...\n"),
+            ("shapes.square", "area\n"),
+        ],
+        Require::indexing(),
+        false,
+    );
+    let titles = state
+        .transaction()
+        .local_quickfix_code_actions(
+            &handles["shapes.square"],
+            TextRange::empty(TextSize::new(0)),
+            ImportFormat::Absolute,
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(title, _, _, _)| title)
+        .collect::<Vec<_>>();
+    // The module in the same package comes first.
+    assert_eq!(
+        titles,
+        vec![
+            "Insert import: `from shapes.geometry import area`",
+            "Insert import: `from geometry import area`",
+        ]
+    );
+}
+
 #[test]
 fn insertion_test_module_import() {
     let report = get_batched_lsp_operations_report_allow_error(
@@ -175,7 +214,7 @@ fn insertion_test_existing_imports() {
         ],
         get_test_report,
     );
-    // Insert before all imports. This might not adhere to existing import sorting code style.
+    // `a` is a first party module, so goes after the standard library imports.
     assert_eq!(
         r#"
 # a.py
@@ -191,8 +230,8 @@ from typing import List
 my_export
 # ^
 ## After:
-from a import my_export
 from typing import List
+from a import my_export
 my_export
 # ^
 "#
@@ -227,8 +266,8 @@ from a import another_thing
 my_export
 # ^
 ## After:
-from a import my_export
 from a import another_thing
+from a import my_export
 my_export
 # ^
 "#
@@ -236,3 +275,1236 @@ my_export
         report.trim()
     );
 }
+
+#[test]
+fn insertion_test_import_sections() {
+    let report = get_batched_lsp_operations_report_allow_error(
+        &[
+            ("a", "my_export = 3\n"),
+            (
+                "b",
+                "\"\"\"Docstring.\"\"\"\nfrom __future__ import annotations\n\nimport os\n\nmy_export\n# ^",
+            ),
+        ],
+        get_test_report,
+    );
+    // The import goes in the block it belongs to, after the `__future__` and standard library
+    // imports.
+    assert_eq!(
+        r#"
+# a.py
+
+# b.py
+6 | my_export
+      ^
+Code Actions Results:
+# Title: Insert import: `from a import my_export`
+
+## Before:
+"""Docstring."""
+from __future__ import annotations
+
+import os
+
+my_export
+# ^
+## After:
+"""Docstring."""
+from __future__ import annotations
+
+import os
+from a import my_export
+
+my_export
+# ^
+"#
+        .trim(),
+        report.trim()
+    );
+}
+
+/// Apply the refactoring code actions offered on `name` in `code`, returning the title of each
+/// action together with the resulting code.
+fn apply_refactors(code: &str, name: &str) -> Vec<(String, String)> {
+    apply_code_actions(code, name, |transaction, handle, range| {
+        transaction.convert_typed_construct_code_actions(handle, range, ImportFormat::Absolute)
+    })
+}
+
+fn apply_code_actions(
+    code: &str,
+    name: &str,
+    code_actions: impl Fn(&Transaction, &Handle, TextRange) -> Option<Vec<(String, Vec<RefactorEdit>)>>,
+) -> Vec<(String, String)> {
+    let position = TextSize::new(code.find(name).unwrap() as u32);
+    apply_code_actions_to_range(code, TextRange::new(position, position), code_actions)
+}
+
+fn apply_code_actions_to_range(
+    code: &str,
+    range: TextRange,
+    code_actions: impl Fn(&Transaction, &Handle, TextRange) -> Option<Vec<(String, Vec<RefactorEdit>)>>,
+) -> Vec<(String, String)> {
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::indexing(), false);
+    let handle = &handles["main"];
+    let transaction = state.transaction();
+    code_actions(&transaction, handle, range)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(title, edits)| {
+            let mut after = code.to_owned();
+            // Apply back to front, so earlier offsets stay valid.
+            for (_, range, text) in edits
+                .iter()
+                .rev()
+                .sorted_by_key(|(_, range, _)| Reverse(range.start()))
+            {
+                after.replace_range(range.start().to_usize()..range.end().to_usize(), text);
+            }
+            (title, after)
+        })
+        .collect()
+}
+
+#[test]
+fn convert_class_to_dataclass() {
+    let code = r#"class Point:
+    x: int = 0
+    y: int = 0
+"#;
+    assert_eq!(
+        apply_refactors(code, "Point"),
+        vec![(
+            "Convert to dataclass".to_owned(),
+            r#"from dataclasses import dataclass
+@dataclass
+class Point:
+    x: int = 0
+    y: int = 0
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_named_tuple_to_dataclass() {
+    let code = r#"from typing import NamedTuple
+class Point(NamedTuple):
+    x: int
+    y: int
+"#;
+    assert_eq!(
+        apply_refactors(code, "Point"),
+        vec![(
+            "Convert to dataclass".to_owned(),
+            r#"from dataclasses import dataclass
+@dataclass(frozen=True)
+class Point:
+    x: int
+    y: int
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_named_tuple_to_dataclass_keeps_other_imports() {
+    let code = r#"from typing import Any, NamedTuple
+class Point(NamedTuple):
+    x: Any
+    y: Any
+p = Point(1, 2)
+print(p.x)
+"#;
+    assert_eq!(
+        apply_refactors(code, "Point"),
+        vec![(
+            "Convert to dataclass".to_owned(),
+            r#"from dataclasses import dataclass
+from typing import Any
+@dataclass(frozen=True)
+class Point:
+    x: Any
+    y: Any
+p = Point(1, 2)
+print(p.x)
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_named_tuple_used_as_tuple_not_offered() {
+    for usage in [
+        "p[0]",
+        "x, y = p",
+        "for v in p: pass",
+        "f(*p)",
+        "len(p)",
+        "[v for v in p]",
+        "p._replace(x=1)",
+        "p._asdict()",
+        "Point._make([1, 2])",
+    ] {
+        let code = format!(
+            "from typing import NamedTuple\nclass Point(NamedTuple):\n    x: int\n    y: int\ndef f(*args: int) -> None: ...\np = Point(1, 2)\n{usage}\n"
+        );
+        assert_eq!(apply_refactors(&code, "Point"), Vec::new(), "{usage}");
+    }
+}
+
+#[test]
+fn convert_dict_function_to_typed_dict() {
+    let code = r#"def get_user():
+    return {"name": "x", "age": 1}
+"#;
+    assert_eq!(
+        apply_refactors(code, "get_user"),
+        vec![(
+            "Convert return type to TypedDict `GetUserResult`".to_owned(),
+            r#"from typing import TypedDict
+class GetUserResult(TypedDict):
+    name: str
+    age: int
+
+
+def get_user() -> GetUserResult:
+    return {"name": "x", "age": 1}
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_dict_function_to_typed_dict_imports_field_types() {
+    let code = r#"import fractions
+def get_price():
+    return {"amount": fractions.Fraction(1), "tax": fractions.Fraction(0)}
+"#;
+    assert_eq!(
+        apply_refactors(code, "get_price"),
+        vec![(
+            "Convert return type to TypedDict `GetPriceResult`".to_owned(),
+            r#"from typing import TypedDict
+import fractions
+from fractions import Fraction
+class GetPriceResult(TypedDict):
+    amount: Fraction
+    tax: Fraction
+
+
+def get_price() -> GetPriceResult:
+    return {"amount": fractions.Fraction(1), "tax": fractions.Fraction(0)}
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_class_with_init_to_dataclass() {
+    let code = r#"class Point:
+    def __init__(self, x_pos: int, y: int = 0) -> None:
+        self.x = x_pos
+        self.y = y
+
+    def norm(self) -> int:
+        return self.x + self.y
+p = Point(x_pos=1)
+q = Point(2, y=3)
+"#;
+    assert_eq!(
+        apply_refactors(code, "Point"),
+        vec![(
+            "Convert to dataclass".to_owned(),
+            r#"from dataclasses import dataclass
+@dataclass
+class Point:
+    x: int
+    y: int = 0
+
+    def norm(self) -> int:
+        return self.x + self.y
+p = Point(x=1)
+q = Point(2, y=3)
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn convert_class_with_init_not_offered() {
+    let init =
+        "class Point:\n    def __init__(self, x_pos: int) -> None:\n        self.x = x_pos\n";
+    for usage in [
+        // We can't rename the keywords passed as a dict.
+        "Point(**{'x_pos': 1})",
+        // Nor those passed to `__init__` by a subclass.
+        "class Point3(Point):\n    def __init__(self, x_pos: int) -> None:\n        super().__init__(x_pos=x_pos)",
+    ] {
+        let code = format!("{init}{usage}\n");
+        assert_eq!(apply_refactors(&code, "Point"), Vec::new(), "{usage}");
+    }
+    for init in [
+        // Not every parameter becomes a field.
+        "def __init__(self, x: int, y: int) -> None:\n        self.x = x",
+        // The field isn't assigned as it is passed.
+        "def __init__(self, x: int) -> None:\n        self.x = x + 1",
+        // The field has no annotation to declare it with.
+        "def __init__(self, x) -> None:\n        self.x = x",
+        // Every instance would share the same default.
+        "def __init__(self, x: list[int] = []) -> None:\n        self.x = x",
+        // Keyword-only parameters would become positional.
+        "def __init__(self, *, x: int) -> None:\n        self.x = x",
+    ] {
+        let code = format!("class Point:\n    {init}\n");
+        assert_eq!(apply_refactors(&code, "Point"), Vec::new(), "{init}");
+    }
+}
+
+#[test]
+fn convert_typed_construct_not_offered() {
+    // Without defaults, existing `Point()` calls would stop working.
+    assert_eq!(
+        apply_refactors("class Point:\n    x: int\n", "Point"),
+        Vec::new()
+    );
+    // Keys that aren't string literals can't become TypedDict fields.
+    assert_eq!(
+        apply_refactors("def f(k: str):\n    return {k: 1}\n", "f"),
+        Vec::new()
+    );
+    // The same number of keys, all known, but not the same keys.
+    assert_eq!(
+        apply_refactors(
+            "def f(b: bool):\n    if b:\n        return {\"a\": 1, \"b\": 2}\n    return {\"a\": 1, \"a\": 2}\n",
+            "f"
+        ),
+        Vec::new()
+    );
+}
+
+#[test]
+fn extract_protocol() {
+    let code = r#"class Greeter:
+    greeting: str = "hi"
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+        self._count = 0
+
+    def greet(self, loud: bool = False) -> str:
+        return self.greeting + self.name
+
+    @property
+    def size(self) -> int:
+        return 1
+
+    def _helper(self) -> None:
+        pass
+
+
+def welcome(greeter: Greeter) -> str:
+    return greeter.greet()
+"#;
+    let protocol = r#"from typing import Protocol
+class GreeterProtocol(Protocol):
+    greeting: str
+    name: str
+    def greet(self, loud: bool = False) -> str: ...
+    @property
+    def size(self) -> int: ...
+
+
+"#;
+    let abstract_class = r#"from abc import ABC, abstractmethod
+class AbstractGreeter(ABC):
+    greeting: str
+    name: str
+    @abstractmethod
+    def greet(self, loud: bool = False) -> str: ...
+    @property
+    @abstractmethod
+    def size(self) -> int: ...
+
+
+"#;
+    assert_eq!(
+        apply_code_actions(code, "Greeter", |transaction, handle, range| {
+            transaction.extract_protocol_code_actions(handle, range)
+        }),
+        vec![
+            (
+                "Extract protocol `GreeterProtocol`".to_owned(),
+                format!("{protocol}{code}"),
+            ),
+            (
+                "Extract protocol `GreeterProtocol` and use it in parameter annotations".to_owned(),
+                format!(
+                    "{protocol}{}",
+                    code.replace("greeter: Greeter", "greeter: GreeterProtocol")
+                ),
+            ),
+            (
+                "Extract abstract base class `AbstractGreeter`".to_owned(),
+                format!(
+                    "{abstract_class}{}",
+                    code.replace("class Greeter:", "class Greeter(AbstractGreeter):")
+                ),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn extract_protocol_not_offered() {
+    let extract = |code: &str| {
+        apply_code_actions(code, "C", |transaction, handle, range| {
+            transaction.extract_protocol_code_actions(handle, range)
+        })
+    };
+    // Nothing public to extract.
+    assert_eq!(
+        extract("class C:\n    def _f(self) -> None: ...\n"),
+        Vec::new()
+    );
+    // The members of a generic class may refer to its type parameters.
+    assert_eq!(
+        extract("class C[T]:\n    def f(self, x: T) -> T: ...\n"),
+        Vec::new()
+    );
+}
+
+/// Apply the extract variable code actions offered when the first occurrence of `selection` in
+/// `code` is selected.
+fn apply_extract_variable(code: &str, selection: &str) -> Vec<(String, String)> {
+    let range = TextRange::at(
+        TextSize::new(code.find(selection).unwrap() as u32),
+        TextSize::of(selection),
+    );
+    apply_code_actions_to_range(code, range, |transaction, handle, range| {
+        transaction.extract_variable_code_actions(handle, range, ImportFormat::Absolute)
+    })
+}
+
+#[test]
+fn extract_variable() {
+    let code = r#"def area(width: int, height: int) -> int:
+    total = width * height + 1
+    return (width * height) * 2
+"#;
+    assert_eq!(
+        apply_extract_variable(code, "width * height"),
+        vec![
+            (
+                "Extract variable `value`".to_owned(),
+                r#"def area(width: int, height: int) -> int:
+    value: int = width * height
+    total = value + 1
+    return (width * height) * 2
+"#
+                .to_owned()
+            ),
+            (
+                "Extract variable `value` and replace all 2 occurrences".to_owned(),
+                r#"def area(width: int, height: int) -> int:
+    value: int = width * height
+    total = value + 1
+    return (value) * 2
+"#
+                .to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn extract_variable_in_block() {
+    let code = r#"def shout(words: list[str]) -> None:
+    for word in words:
+        print(word.upper())
+        print(word.upper())
+"#;
+    assert_eq!(
+        apply_extract_variable(code, "word.upper()"),
+        vec![
+            (
+                "Extract variable `value`".to_owned(),
+                r#"def shout(words: list[str]) -> None:
+    for word in words:
+        value: str = word.upper()
+        print(value)
+        print(word.upper())
+"#
+                .to_owned()
+            ),
+            (
+                "Extract variable `value` and replace all 2 occurrences".to_owned(),
+                r#"def shout(words: list[str]) -> None:
+    for word in words:
+        value: str = word.upper()
+        print(value)
+        print(value)
+"#
+                .to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn extract_variable_name() {
+    let code = r#"class Config:
+    name: str = ""
+
+
+def load() -> Config:
+    return Config()
+
+
+print(load().name)
+"#;
+    assert_eq!(
+        apply_extract_variable(code, "Config()"),
+        vec![(
+            "Extract variable `config`".to_owned(),
+            code.replace(
+                "    return Config()",
+                "    config: Config = Config()\n    return config"
+            )
+        )]
+    );
+    // `name` is already used, so the variable gets a number.
+    assert_eq!(
+        apply_extract_variable(code, "load().name"),
+        vec![(
+            "Extract variable `name2`".to_owned(),
+            code.replace(
+                "print(load().name)",
+                "name2: str = load().name\nprint(name2)"
+            )
+        )]
+    );
+}
+
+#[test]
+fn extract_variable_not_offered() {
+    let code = r#"def f(n: int) -> None:
+    while n - 1 > 0:
+        n = n - 1
+    g = lambda k: k + 1
+"#;
+    // The test of a `while` is evaluated on every iteration.
+    assert_eq!(apply_extract_variable(code, "n - 1"), Vec::new());
+    // The parameter of a lambda isn't available outside of it.
+    assert_eq!(apply_extract_variable(code, "k + 1"), Vec::new());
+}
+
+#[test]
+fn extract_variable_all_occurrences_not_offered() {
+    // `n` changes between the occurrences.
+    let code = r#"def f(n: int) -> int:
+    a = n + 1
+    n = 2
+    return n + 1
+"#;
+    assert_eq!(
+        apply_extract_variable(code, "n + 1")
+            .into_iter()
+            .map(|(title, _)| title)
+            .collect::<Vec<_>>(),
+        vec!["Extract variable `value`".to_owned()]
+    );
+}
+
+/// Apply the inline variable code actions offered at the first occurrence of `needle` in `code`.
+fn apply_inline_variable(code: &str, needle: &str) -> Vec<(String, String)> {
+    let range = TextRange::empty(TextSize::new(code.find(needle).unwrap() as u32));
+    apply_code_actions_to_range(code, range, |transaction, handle, range| {
+        transaction.inline_variable_code_actions(handle, range)
+    })
+}
+
+#[test]
+fn inline_variable() {
+    let code = r#"def area(width: int, height: int) -> int:
+    size = width * height
+    print(size)
+    return size * 2
+"#;
+    let expected = vec![(
+        "Inline variable `size`".to_owned(),
+        r#"def area(width: int, height: int) -> int:
+    print(width * height)
+    return (width * height) * 2
+"#
+        .to_owned(),
+    )];
+    // Offered both where the variable is assigned and where it is used.
+    assert_eq!(apply_inline_variable(code, "size ="), expected);
+    assert_eq!(apply_inline_variable(code, "size)"), expected);
+}
+
+#[test]
+fn inline_variable_in_block() {
+    let code = r#"def shout(words: list[str]) -> None:
+    for word in words:
+        loud: str = word.upper()
+        print(loud, loud)
+"#;
+    assert_eq!(
+        apply_inline_variable(code, "loud"),
+        vec![(
+            "Inline variable `loud`".to_owned(),
+            r#"def shout(words: list[str]) -> None:
+    for word in words:
+        print(word.upper(), word.upper())
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn inline_variable_not_offered() {
+    // Assigned twice.
+    let code = r#"def f(n: int) -> int:
+    x = n + 1
+    x = x + 1
+    return x
+"#;
+    assert_eq!(apply_inline_variable(code, "x ="), Vec::new());
+    // `n` changes before the use.
+    let code = r#"def f(n: int) -> int:
+    x = n + 1
+    n = 2
+    return x
+"#;
+    assert_eq!(apply_inline_variable(code, "x ="), Vec::new());
+    // Used in a block the assignment might not run before.
+    let code = r#"def f(n: int) -> int:
+    if n > 0:
+        x = n
+    else:
+        x = 0
+    return x
+"#;
+    assert_eq!(apply_inline_variable(code, "x = n"), Vec::new());
+    // Shares its line with another statement.
+    let code = r#"def f(n: int) -> int:
+    x = n; print(n)
+    return x
+"#;
+    assert_eq!(apply_inline_variable(code, "x ="), Vec::new());
+}
+
+fn apply_add_type_annotation(code: &str, needle: &str) -> Vec<(String, String)> {
+    apply_code_actions(code, needle, |transaction, handle, range| {
+        transaction.add_type_annotation_code_actions(handle, range, ImportFormat::Absolute)
+    })
+}
+
+#[test]
+fn add_type_annotation() {
+    let code = r#"def count(n=0):
+    return n + 1
+
+
+x = count()
+"#;
+    let add = |needle, from, to| {
+        assert_eq!(
+            apply_add_type_annotation(code, needle),
+            vec![("Add type annotation".to_owned(), code.replace(from, to))]
+        );
+    };
+    add("x =", "x = count()", "x: int = count()");
+    add("n=0", "(n=0)", "(n: int=0)");
+    add("count(n", "count(n=0):", "count(n=0) -> int:");
+}
+
+#[test]
+fn add_type_annotation_import() {
+    let code = r#"async def name():
+    return "pyrefly"
+"#;
+    assert_eq!(
+        apply_add_type_annotation(code, "name"),
+        vec![(
+            "Add type annotation".to_owned(),
+            r#"from typing import Literal
+async def name() -> Literal['pyrefly']:
+    return "pyrefly"
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn add_type_annotation_not_offered() {
+    let code = r#"def f(n: int = 0) -> int:
+    return n
+
+
+a, b = 1, 2
+"#;
+    assert_eq!(apply_add_type_annotation(code, "n:"), Vec::new());
+    assert_eq!(apply_add_type_annotation(code, "f("), Vec::new());
+    assert_eq!(apply_add_type_annotation(code, "a,"), Vec::new());
+}
+
+fn apply_add_type_annotations(code: &str) -> Vec<(String, String)> {
+    apply_code_actions_to_range(code, TextRange::default(), |transaction, handle, _| {
+        transaction.add_type_annotations_code_actions(handle, ImportFormat::Absolute)
+    })
+}
+
+#[test]
+fn add_type_annotations() {
+    let code = r#"def count(n=0):
+    return n + 1
+
+
+class Greeter:
+    def greet(self, name="world"):
+        return "Hello, " + name
+
+    async def wait(self):
+        return None
+
+
+def stub(): ...
+"#;
+    assert_eq!(
+        apply_add_type_annotations(code),
+        vec![(
+            "Add type annotations to all functions".to_owned(),
+            r#"def count(n: int=0) -> int:
+    return n + 1
+
+
+class Greeter:
+    def greet(self, name: str="world") -> str:
+        return "Hello, " + name
+
+    async def wait(self) -> None:
+        return None
+
+
+def stub(): ...
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn add_type_annotations_import() {
+    let code = r#"def first():
+    return "a"
+
+
+def second():
+    return "a"
+"#;
+    assert_eq!(
+        apply_add_type_annotations(code),
+        vec![(
+            "Add type annotations to all functions".to_owned(),
+            r#"from typing import Literal
+def first() -> Literal['a']:
+    return "a"
+
+
+def second() -> Literal['a']:
+    return "a"
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn add_type_annotations_not_offered() {
+    let code = r#"def f(n: int = 0) -> int:
+    return n
+
+
+def g(x):
+    return x
+"#;
+    assert_eq!(apply_add_type_annotations(code), Vec::new());
+}
+
+const SHAPES: &str = r#"from abc import ABC, abstractmethod
+from typing import Protocol
+
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> float: ...
+
+    @property
+    @abstractmethod
+    def name(self) -> str: ...
+
+    def describe(self) -> str:
+        return self.name
+
+
+class Drawable(Protocol):
+    color: str
+
+    def draw(self, scale: float = 1.0) -> None: ...
+
+    def redraw(self) -> None:
+        self.draw()
+"#;
+
+fn apply_implement_members(code: &str, needle: &str) -> Vec<(String, String)> {
+    apply_code_actions(code, needle, |transaction, handle, range| {
+        transaction.implement_members_code_actions(handle, range)
+    })
+}
+
+#[test]
+fn implement_members() {
+    let code = format!(
+        r#"{SHAPES}
+
+class Square(Shape, Drawable):
+    side: float = 1.0
+"#
+    );
+    assert_eq!(
+        apply_implement_members(&code, "Square"),
+        vec![(
+            "Implement missing members".to_owned(),
+            format!(
+                r#"{code}
+    def area(self) -> float:
+        raise NotImplementedError
+
+    @property
+    def name(self) -> str:
+        raise NotImplementedError
+
+    color: str
+
+    def draw(self, scale: float = 1.0) -> None:
+        raise NotImplementedError
+"#
+            )
+        )]
+    );
+}
+
+#[test]
+fn implement_members_not_offered() {
+    let code = format!(
+        r#"{SHAPES}
+
+class Circle(Shape):
+    def area(self) -> float:
+        return 3.14
+
+    @property
+    def name(self) -> str:
+        return "circle"
+"#
+    );
+    // Abstract classes, protocols and classes implementing everything have nothing to implement.
+    assert_eq!(apply_implement_members(&code, "Shape"), Vec::new());
+    assert_eq!(apply_implement_members(&code, "Drawable"), Vec::new());
+    assert_eq!(apply_implement_members(&code, "Circle"), Vec::new());
+}
+
+/// Apply each quick fix offered at the first occurrence of `needle` in `code`, returning the title
+/// of each fix together with the resulting code.
+fn apply_quickfixes(code: &str, needle: &str) -> Vec<(String, String)> {
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::indexing(), false);
+    let position = TextSize::new(code.find(needle).unwrap() as u32);
+    state
+        .transaction()
+        .local_quickfix_code_actions(
+            &handles["main"],
+            TextRange::new(position, position),
+            ImportFormat::Absolute,
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(title, info, range, patch)| (title, apply_patch(&info, range, patch).1))
+        .collect()
+}
+
+#[test]
+fn optional_attribute_fixes() {
+    let code = r#"class C:
+    x: int = 0
+
+def f(c: C | None) -> None:
+    print(c.x)
+"#;
+    assert_eq!(
+        apply_quickfixes(code, "c.x"),
+        vec![
+            (
+                "Add `assert c is not None`".to_owned(),
+                r#"class C:
+    x: int = 0
+
+def f(c: C | None) -> None:
+    assert c is not None
+    print(c.x)
+"#
+                .to_owned()
+            ),
+            (
+                "Add `if c is not None:` guard".to_owned(),
+                r#"class C:
+    x: int = 0
+
+def f(c: C | None) -> None:
+    if c is not None:
+        print(c.x)
+"#
+                .to_owned()
+            ),
+            (
+                "Change annotation of `c` to `C`".to_owned(),
+                r#"class C:
+    x: int = 0
+
+def f(c: C) -> None:
+    print(c.x)
+"#
+                .to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn optional_attribute_fixes_attribute_chain() {
+    let code = r#"from typing import Optional
+class C:
+    x: int = 0
+class D:
+    c: Optional[C] = None
+def f(d: D) -> None:
+    if d.c.x:
+        pass
+"#;
+    assert_eq!(
+        apply_quickfixes(code, "d.c.x"),
+        vec![
+            (
+                "Add `assert d.c is not None`".to_owned(),
+                r#"from typing import Optional
+class C:
+    x: int = 0
+class D:
+    c: Optional[C] = None
+def f(d: D) -> None:
+    assert d.c is not None
+    if d.c.x:
+        pass
+"#
+                .to_owned()
+            ),
+            (
+                "Add `if d.c is not None:` guard".to_owned(),
+                r#"from typing import Optional
+class C:
+    x: int = 0
+class D:
+    c: Optional[C] = None
+def f(d: D) -> None:
+    if d.c is not None:
+        if d.c.x:
+            pass
+"#
+                .to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn optional_attribute_fixes_not_offered() {
+    // Removing `None` from the annotation would make the default invalid, and the loop condition
+    // is evaluated more than once.
+    let code = r#"class C:
+    x: int = 0
+def f(c: C | None = None) -> None:
+    while c.x:
+        pass
+"#;
+    assert_eq!(apply_quickfixes(code, "c.x"), Vec::new());
+}
+
+#[test]
+fn insert_missing_typed_dict_keys() {
+    let code = r#"from typing import TypedDict
+class Movie(TypedDict):
+    name: str
+    year: int
+    rating: float
+m: Movie = {"name": "Alien"}
+"#;
+    assert_eq!(
+        apply_quickfixes(code, "}"),
+        vec![(
+            "Insert missing required keys: `year`, `rating`".to_owned(),
+            r#"from typing import TypedDict
+class Movie(TypedDict):
+    name: str
+    year: int
+    rating: float
+m: Movie = {"name": "Alien", "year": 0, "rating": 0.0}
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn insert_missing_typed_dict_keys_placeholders() {
+    let code = r#"from typing import Literal, TypedDict
+class Config(TypedDict):
+    mode: Literal["fast"]
+    path: str | None
+    items: list[int]
+    callback: object
+c: Config = {}
+"#;
+    assert_eq!(
+        apply_quickfixes(code, "}"),
+        vec![(
+            "Insert missing required keys: `mode`, `path`, `items`, `callback`".to_owned(),
+            r#"from typing import Literal, TypedDict
+class Config(TypedDict):
+    mode: Literal["fast"]
+    path: str | None
+    items: list[int]
+    callback: object
+c: Config = {"mode": "fast", "path": None, "items": [], "callback": ...}
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn insert_missing_dataclass_arguments() {
+    let code = r#"from dataclasses import dataclass
+@dataclass
+class Point:
+    x: int
+    y: int
+p = Point(
+    x=1,
+)
+"#;
+    assert_eq!(
+        apply_quickfixes(code, ")"),
+        vec![(
+            "Insert missing arguments: `y`".to_owned(),
+            r#"from dataclasses import dataclass
+@dataclass
+class Point:
+    x: int
+    y: int
+p = Point(
+    x=1, y=0
+)
+"#
+            .to_owned()
+        )]
+    );
+}
+
+fn apply_organize_imports(code: &str) -> Vec<(String, String)> {
+    apply_code_actions_to_range(code, TextRange::default(), |transaction, handle, _| {
+        transaction.organize_imports_code_actions(handle)
+    })
+}
+
+#[test]
+fn organize_imports() {
+    let code = r#"from __future__ import annotations
+import sys
+from typing import TYPE_CHECKING
+import requests
+from .shapes import Square
+import os, json
+from typing import Any, cast
+from .shapes import Circle
+from ..util import helper
+
+def f(x: "Any") -> None:
+    os.getcwd()
+    print(requests, Square, Circle, helper, cast, sys)
+"#;
+    assert_eq!(
+        apply_organize_imports(code),
+        vec![(
+            "Organize imports".to_owned(),
+            r#"from __future__ import annotations
+
+import os
+import sys
+from typing import Any, cast
+
+import requests
+
+from ..util import helper
+from .shapes import Circle, Square
+
+def f(x: "Any") -> None:
+    os.getcwd()
+    print(requests, Square, Circle, helper, cast, sys)
+"#
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn organize_imports_remove_all() {
+    let code = r#"import os
+from typing import Any
+
+x = 1
+"#;
+    assert_eq!(
+        apply_organize_imports(code),
+        vec![("Organize imports".to_owned(), "x = 1\n".to_owned())]
+    );
+}
+
+#[test]
+fn organize_imports_not_offered() {
+    // The imports are already organized.
+    let organized = r#"import os
+from typing import Any
+
+x: Any = os.sep
+"#;
+    assert_eq!(apply_organize_imports(organized), Vec::new());
+    // Reordering the imports would lose the comment.
+    let commented = r#"import sys
+# Needed for the separator.
+import os
+
+x = os.sep
+"#;
+    assert_eq!(apply_organize_imports(commented), Vec::new());
+}
+
+fn apply_suppress_error(code: &str, needle: &str) -> Vec<(String, String)> {
+    apply_code_actions(code, needle, |transaction, handle, range| {
+        transaction.suppress_error_code_actions(handle, range)
+    })
+}
+
+#[test]
+fn suppress_error() {
+    let code = r#"x: int = "a"
+y: int = 1
+"#;
+    assert_eq!(
+        apply_suppress_error(code, "\"a\""),
+        vec![
+            (
+                "Suppress `bad-assignment` with `# pyrefly: ignore`".to_owned(),
+                r#"x: int = "a"  # pyrefly: ignore[bad-assignment]
+y: int = 1
+"#
+                .to_owned()
+            ),
+            (
+                "Suppress all errors in this file with `# pyrefly: ignore-errors`".to_owned(),
+                r#"# pyrefly: ignore-errors
+x: int = "a"
+y: int = 1
+"#
+                .to_owned()
+            ),
+        ]
+    );
+    assert_eq!(apply_suppress_error(code, "y: int"), Vec::new());
+}
+
+#[test]
+fn suppress_error_existing_ignore() {
+    // The code is added to the ignore already on the line, which doesn't suppress anything yet.
+    let code = r#"x: int = "a"  # pyrefly: ignore[bad-return]
+"#;
+    assert_eq!(
+        apply_suppress_error(code, "\"a\""),
+        vec![
+            (
+                "Suppress `bad-assignment` with `# pyrefly: ignore`".to_owned(),
+                r#"x: int = "a"  # pyrefly: ignore[bad-return, bad-assignment]
+"#
+                .to_owned()
+            ),
+            (
+                "Suppress all errors in this file with `# pyrefly: ignore-errors`".to_owned(),
+                r#"# pyrefly: ignore-errors
+x: int = "a"  # pyrefly: ignore[bad-return]
+"#
+                .to_owned()
+            ),
+            (
+                "Remove unused `# pyrefly: ignore`".to_owned(),
+                r#"x: int = "a"
+"#
+                .to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn suppress_error_multiline_string() {
+    let code = r#"x: int = """
+a
+"""
+"#;
+    assert_eq!(
+        apply_suppress_error(code, "\"\"\"")[0].1,
+        r#"# pyrefly: ignore[bad-assignment]
+x: int = """
+a
+"""
+"#
+    );
+}
+
+#[test]
+fn remove_unused_ignore() {
+    let code = r#"# pyrefly: ignore[bad-assignment]
+x: int = 1
+y: int = "a"  # pyrefly: ignore
+"#;
+    assert_eq!(
+        apply_suppress_error(code, "pyrefly"),
+        vec![(
+            "Remove unused `# pyrefly: ignore`".to_owned(),
+            r#"x: int = 1
+y: int = "a"  # pyrefly: ignore
+"#
+            .to_owned()
+        )]
+    );
+    // The ignore on the last line is in use.
+    assert_eq!(apply_suppress_error(code, "y: int"), Vec::new());
+}
@@ -12,9 +12,12 @@ use pyrefly_build::handle::Handle;
 use ruff_text_size::TextSize;
 
 use crate::state::lsp::ImportFormat;
+use crate::state::lsp::dynamic_attributes::DynamicAttributes;
+use crate::state::require::Require;
 use crate::state::state::State;
 use crate::test::util::get_batched_lsp_operations_report;
 use crate::test::util::get_batched_lsp_operations_report_allow_error;
+use crate::test::util::mk_multi_file_state;
 
 #[derive(Default)]
 struct ResultsFilter {
@@ -126,6 +129,35 @@ Completion Results:
     );
 }
 
+#[test]
+fn dot_complete_elided_detail_test() {
+    let code = r#"
+class Foo:
+    x: list[list[list[list[list[int]]]]]
+    y: list[int]
+foo = Foo()
+foo.
+#   ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+6 | foo.
+        ^
+Completion Results:
+- (Field) x: list[list[list[list[...]]]]
+```python
+list[list[list[list[list[int]]]]]
+```
+- (Field) y: list[int]
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 #[test]
 fn dot_complete_with_deprecated_method() {
     let code = r#"
@@ -678,6 +710,108 @@ Completion Results:
     );
 }
 
+#[test]
+fn kwargs_completion_skips_provided_keywords() {
+    let code = r#"
+def foo(x: int, y: str): ...
+foo(x=1, )
+#        ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+3 | foo(x=1, )
+             ^
+Completion Results:
+- (Variable) y=: str
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn typed_dict_missing_key_completion() {
+    let code = r#"
+from typing import TypedDict
+class Movie(TypedDict):
+    name: str
+    year: int
+m: Movie = {"name": "x", }
+#                        ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+6 | m: Movie = {"name": "x", }
+                            ^
+Completion Results:
+- (Field) "year": int
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn typed_dict_subscript_key_completion() {
+    let code = r#"
+from typing import TypedDict
+class Movie(TypedDict):
+    name: str
+    year: int
+def f(m: Movie):
+    m[""]
+#      ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+7 |     m[""]
+           ^
+Completion Results:
+- (Field) name: str
+- (Field) year: int
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn typed_dict_key_completion_with_docstring() {
+    let code = r#"
+from typing import TypedDict
+class Base(TypedDict):
+    name: str
+    """The title of the movie."""
+class Movie(Base):
+    year: int
+m: Movie = {"year": 1979, }
+#                         ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+8 | m: Movie = {"year": 1979, }
+                              ^
+Completion Results:
+- (Field) "name": str
+The title of the movie.
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 #[test]
 fn kwargs_completion_method() {
     let code = r#"
@@ -2173,6 +2307,33 @@ This has documentation.
     );
 }
 
+#[test]
+fn dot_complete_with_attribute_docstring() {
+    let code = r#"
+class Foo:
+    x: int = 0
+    """The x coordinate."""
+
+f = Foo()
+f.
+# ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert_eq!(
+        r#"
+# main.py
+7 | f.
+      ^
+Completion Results:
+- (Field) x: int
+The x coordinate.
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 // Regression test for https://github.com/facebook/pyrefly/issues/1257
 // Because the base type for completion is passed to Type::for_display,
 // which converts all unsolved Var to Var::ZERO, we were running into an
@@ -2205,3 +2366,146 @@ Completion Results:
         report.trim(),
     );
 }
+
+/// The labels of the completions offered at the end of `code`, which should end in an attribute
+/// access, when trusting dynamic attributes as far as `dynamic_attributes` allows. Private names
+/// are left out.
+fn dynamic_attribute_completions(code: &str, dynamic_attributes: DynamicAttributes) -> Vec<String> {
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::indexing(), false);
+    let position = TextSize::new(code.trim_end().len() as u32);
+    state
+        .transaction()
+        .completion_with_incomplete(
+            &handles["main"],
+            position,
+            ImportFormat::Absolute,
+            dynamic_attributes,
+            false,
+        )
+        .0
+        .into_iter()
+        .map(|x| x.label)
+        .filter(|x| !x.starts_with('_'))
+        .collect()
+}
+
+#[test]
+fn dot_complete_dynamic_attributes() {
+    let code = r#"
+class Foo:
+    x: int = 0
+    locals()["y"] = 1
+    for _name in ("a", "b"):
+        locals()[_name] = None
+    def __init__(self) -> None:
+        setattr(self, "z", 1)
+        setattr(self, "not an identifier", 1)
+        for name in ["c", "d"]:
+            setattr(self, name, None)
+class Bar(Foo):
+    pass
+Bar().
+"#;
+    assert_eq!(
+        dynamic_attribute_completions(code, DynamicAttributes::Off),
+        vec!["x"]
+    );
+    assert_eq!(
+        dynamic_attribute_completions(code, DynamicAttributes::Literal),
+        vec!["x", "y", "z"]
+    );
+    assert_eq!(
+        dynamic_attribute_completions(code, DynamicAttributes::Loops),
+        vec!["a", "b", "c", "d", "x", "y", "z"]
+    );
+}
+
+/// The labels and insert texts of the completions at `position` in `code`.
+fn override_completions(
+    code: &str,
+    position: usize,
+    supports_snippets: bool,
+) -> Vec<(String, Option<String>)> {
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::indexing(), false);
+    state
+        .transaction()
+        .completion_with_incomplete(
+            &handles["main"],
+            TextSize::new(position as u32),
+            ImportFormat::Absolute,
+            DynamicAttributes::default(),
+            supports_snippets,
+        )
+        .0
+        .into_iter()
+        .map(|x| (x.label, x.insert_text))
+        .collect()
+}
+
+#[test]
+fn override_method_completion() {
+    let code = r#"
+from abc import ABC, abstractmethod
+
+class Base(ABC):
+    @abstractmethod
+    def greet(self, name: str, loud: bool = False) -> str: ...
+    def close(self) -> None: ...
+    @property
+    def size(self) -> int: ...
+    def __private(self) -> None: ...
+
+class Middle(Base):
+    def greet(self, name: str, loud: bool = True) -> str:
+        return name
+    async def fetch(self, url):
+        pass
+
+class Impl(Middle):
+    def close(self) -> None: ...
+    def g
+"#;
+    assert_eq!(
+        override_completions(code, code.trim_end().len(), false),
+        vec![
+            ("fetch".to_owned(), Some("fetch(self, url):".to_owned())),
+            (
+                "greet".to_owned(),
+                Some("greet(self, name: str, loud: bool = True) -> str:".to_owned())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn override_method_completion_snippet() {
+    let code = r#"
+class Base:
+    def price(self, currency: str = "$") -> dict[str, int]: ...
+
+class Impl(Base):
+    def pr
+"#;
+    assert_eq!(
+        override_completions(code, code.trim_end().len(), true),
+        vec![(
+            "price".to_owned(),
+            Some(
+                "price(self, currency: str = \"\\$\") -> dict[str, int]:\n    ${0:...}".to_owned()
+            )
+        )]
+    );
+}
+
+#[test]
+fn override_method_completion_with_parameters() {
+    let code = r#"
+class Base:
+    def greet(self) -> str: ...
+
+class Impl(Base):
+    def gr(self) -> None: ...
+"#;
+    let position = code.find("gr(self)").unwrap() + 2;
+    assert_eq!(override_completions(code, position, false), Vec::new());
+}
@@ -1593,3 +1593,76 @@ Definition Result:
         report.trim(),
     );
 }
+
+fn get_type_definition_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let defs = state.transaction().goto_type_definition(handle, position);
+    if !defs.is_empty() {
+        defs.into_iter()
+            .map(
+                |TextRangeWithModule {
+                     module: module_info,
+                     range,
+                 }| {
+                    format!(
+                        "Type Definition Result:\n{}",
+                        code_frame_of_source_at_range(module_info.contents(), range)
+                    )
+                },
+            )
+            .join("\n")
+    } else {
+        "Type Definition Result: None".to_owned()
+    }
+}
+
+#[test]
+fn type_definition_through_alias_chain_test() {
+    let code = r#"
+from typing import TypeAlias
+
+class A: pass
+class B: pass
+AB: TypeAlias = A | B
+Alias: TypeAlias = AB
+Implicit = A
+type Scoped = Alias | None
+
+x: Alias
+#  ^
+y: Implicit
+#  ^
+z: Scoped
+#  ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_type_definition_report);
+    assert_eq!(
+        r#"
+# main.py
+11 | x: Alias
+        ^
+Type Definition Result:
+4 | class A: pass
+          ^
+Type Definition Result:
+5 | class B: pass
+          ^
+
+13 | y: Implicit
+        ^
+Type Definition Result:
+4 | class A: pass
+          ^
+
+15 | z: Scoped
+        ^
+Type Definition Result:
+4 | class A: pass
+          ^
+Type Definition Result:
+5 | class B: pass
+          ^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
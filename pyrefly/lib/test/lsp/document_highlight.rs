@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use itertools::Itertools;
+use lsp_types::DocumentHighlightKind;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::state::state::State;
+use crate::test::util::code_frame_of_source_at_range;
+use crate::test::util::get_batched_lsp_operations_report;
+use crate::test::util::get_batched_lsp_operations_report_allow_error;
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let transaction = state.transaction();
+    let highlights = transaction.document_highlights(handle, position);
+    let module_info = transaction.get_module_info(handle).unwrap();
+    format!(
+        "Highlights:\n{}",
+        highlights
+            .into_iter()
+            .map(|(range, kind)| {
+                let kind = if kind == DocumentHighlightKind::WRITE {
+                    "write"
+                } else {
+                    "read"
+                };
+                format!(
+                    "{} {kind}",
+                    code_frame_of_source_at_range(module_info.contents(), range)
+                )
+            })
+            .join("\n")
+    )
+}
+
+#[test]
+fn read_and_write_test() {
+    let code = r#"
+foo = 3
+# ^
+foo + 4 + foo
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+2 | foo = 3
+      ^
+Highlights:
+2 | foo = 3
+    ^^^ write
+4 | foo + 4 + foo
+    ^^^ read
+4 | foo + 4 + foo
+              ^^^ read
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn self_attribute_across_methods_test() {
+    let code = r#"
+class Counter:
+    def __init__(self):
+        self.count = 0
+
+    def increment(self):
+        self.count += 1
+#            ^
+        return self.count
+
+    def reset(this):
+        this.count = 0
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+7 |         self.count += 1
+                 ^
+Highlights:
+4 |         self.count = 0
+                 ^^^^^ write
+7 |         self.count += 1
+                 ^^^^^ write
+9 |         return self.count
+                        ^^^^^ read
+12 |         this.count = 0
+                  ^^^^^ write
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn undeclared_self_attribute_test() {
+    let code = r#"
+class C:
+    def f(self):
+        return self.missing
+#                   ^
+
+    def g(self):
+        print(self.missing)
+"#;
+    let report = get_batched_lsp_operations_report_allow_error(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+4 |         return self.missing
+                        ^
+Highlights:
+4 |         return self.missing
+                        ^^^^^^^ read
+8 |         print(self.missing)
+                       ^^^^^^^ read
+"#
+        .trim(),
+        report.trim(),
+    );
+}
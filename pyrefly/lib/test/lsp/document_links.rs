@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+
+use crate::state::state::State;
+use crate::test::util::get_batched_lsp_operations_report_no_cursor;
+
+fn get_document_links_report(state: &State, handle: &Handle) -> String {
+    let transaction = state.transaction();
+    let Some(module) = transaction.get_module_info(handle) else {
+        return String::new();
+    };
+    transaction
+        .document_links(handle)
+        .into_iter()
+        .map(|(range, target)| {
+            let position = module.lined_buffer().to_lsp_range(range).start;
+            format!(
+                "{}:{} `{}` -> {} ({})",
+                position.line + 1,
+                position.character + 1,
+                module.code_at(range),
+                target.module(),
+                target.path().as_path().display(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn document_links_for_imports_test() {
+    let code = r#"
+import pkg.sub
+from .sub import f
+from . import sub
+
+def g() -> None:
+    import pkg
+"#;
+    let report = get_batched_lsp_operations_report_no_cursor(
+        &[
+            ("pkg", ""),
+            ("pkg.sub", "def f() -> None: ..."),
+            ("pkg.main", code),
+        ],
+        get_document_links_report,
+    );
+    assert_eq!(
+        r#"# pkg.py
+
+
+
+
+# pkg.sub.py
+
+
+
+
+# pkg.main.py
+
+2:8 `pkg` -> pkg (pkg.py)
+2:12 `sub` -> pkg.sub (pkg/sub.py)
+3:7 `sub` -> pkg.sub (pkg/sub.py)
+6:12 `pkg` -> pkg (pkg.py)
+
+"#
+        .trim(),
+        report.trim(),
+    );
+}
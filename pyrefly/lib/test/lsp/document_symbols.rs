@@ -39,6 +39,7 @@ def function2(param1, param2):
 [
   {
     "name": "function1",
+    "detail": "main:function1",
     "kind": 12,
     "range": {
       "start": {
@@ -89,6 +90,7 @@ def function2(param1, param2):
   },
   {
     "name": "function2",
+    "detail": "main:function2",
     "kind": 12,
     "range": {
       "start": {
@@ -165,6 +167,7 @@ class MyClass:
 [
   {
     "name": "MyClass",
+    "detail": "main:MyClass",
     "kind": 5,
     "range": {
       "start": {
@@ -189,6 +192,7 @@ class MyClass:
     "children": [
       {
         "name": "__init__",
+        "detail": "main:MyClass.__init__",
         "kind": 12,
         "range": {
           "start": {
@@ -214,6 +218,7 @@ class MyClass:
       },
       {
         "name": "method1",
+        "detail": "main:MyClass.method1",
         "kind": 12,
         "range": {
           "start": {
@@ -239,6 +244,7 @@ class MyClass:
       },
       {
         "name": "method2",
+        "detail": "main:MyClass.method2",
         "kind": 12,
         "range": {
           "start": {
@@ -323,6 +329,7 @@ result = y.method()
   },
   {
     "name": "helper_function",
+    "detail": "main:helper_function",
     "kind": 12,
     "range": {
       "start": {
@@ -348,6 +355,7 @@ result = y.method()
   },
   {
     "name": "MyClass",
+    "detail": "main:MyClass",
     "kind": 5,
     "range": {
       "start": {
@@ -396,6 +404,7 @@ result = y.method()
       },
       {
         "name": "method",
+        "detail": "main:MyClass.method",
         "kind": 12,
         "range": {
           "start": {
@@ -516,6 +525,7 @@ while True:
 [
   {
     "name": "Foo",
+    "detail": "main:Foo",
     "kind": 5,
     "range": {
       "start": {
@@ -616,6 +626,7 @@ result = y.method()
   },
   {
     "name": "helper_function",
+    "detail": "main:helper_function",
     "kind": 12,
     "range": {
       "start": {
@@ -641,6 +652,7 @@ result = y.method()
   },
   {
     "name": "MyClass",
+    "detail": "main:MyClass",
     "kind": 5,
     "range": {
       "start": {
@@ -689,6 +701,7 @@ result = y.method()
       },
       {
         "name": "method",
+        "detail": "main:MyClass.method",
         "kind": 12,
         "range": {
           "start": {
@@ -877,6 +890,7 @@ items: List[str] = ["a", "b", "c"]
   },
   {
     "name": "helper_function",
+    "detail": "main:helper_function",
     "kind": 12,
     "range": {
       "start": {
@@ -902,6 +916,7 @@ items: List[str] = ["a", "b", "c"]
   },
   {
     "name": "MyClass",
+    "detail": "main:MyClass",
     "kind": 5,
     "range": {
       "start": {
@@ -976,6 +991,7 @@ items: List[str] = ["a", "b", "c"]
       },
       {
         "name": "method",
+        "detail": "main:MyClass.method",
         "kind": 12,
         "range": {
           "start": {
@@ -729,3 +729,97 @@ nested = {
         report.trim(),
     );
 }
+
+#[test]
+fn folding_ranges_for_imports() {
+    let code = r#"
+import os
+import sys
+from typing import (
+    Any,
+    List,
+)
+
+x = 1
+
+def f():
+    import json
+    from os import path
+    return json, path
+"#;
+
+    let report =
+        get_batched_lsp_operations_report_no_cursor(&[("main", code)], get_folding_ranges_report);
+
+    assert_eq!(
+        r#"# main.py
+
+[
+  {
+    "start_line": 1,
+    "end_line": 6,
+    "kind": "imports"
+  },
+  {
+    "start_line": 10,
+    "end_line": 13
+  },
+  {
+    "start_line": 11,
+    "end_line": 12,
+    "kind": "imports"
+  }
+]"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn folding_ranges_for_regions() {
+    let code = r#"
+# region Setup
+x = 1
+#region nested
+y = 2
+# endregion
+# endregion
+
+# regional = 3
+def f():
+    # region inner
+    pass
+    # endregion
+"#;
+
+    let report =
+        get_batched_lsp_operations_report_no_cursor(&[("main", code)], get_folding_ranges_report);
+
+    assert_eq!(
+        r#"# main.py
+
+[
+  {
+    "start_line": 1,
+    "end_line": 6,
+    "kind": "region"
+  },
+  {
+    "start_line": 3,
+    "end_line": 5,
+    "kind": "region"
+  },
+  {
+    "start_line": 9,
+    "end_line": 11
+  },
+  {
+    "start_line": 10,
+    "end_line": 12,
+    "kind": "region"
+  }
+]"#
+        .trim(),
+        report.trim(),
+    );
+}
@@ -48,11 +48,15 @@ xyz = [foo.meth]
 (attribute) meth: def meth(self: Foo) -> None
 ```
 
+`main:Foo.meth`
+
 9 | xyz = [foo.meth]
      ^
 ```python
 (variable) xyz: list[(self: Foo) -> None]
 ```
+
+`main:xyz`
 "#
         .trim(),
         report.trim(),
@@ -84,6 +88,8 @@ from lib import foo_renamed
 (function) foo: def foo() -> None
 ```
 
+`lib2:foo`
+
 
 # lib.py
 
@@ -94,6 +100,33 @@ from lib import foo_renamed
     );
 }
 
+#[test]
+fn fully_qualified_name_test() {
+    let code = r#"
+class Outer:
+    class Inner:
+        def method(self) -> None:
+            def helper() -> None: ...
+            helper()
+#           ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+6 |             helper()
+                ^
+```python
+(function) helper: def helper() -> None
+```
+
+`main:Outer.Inner.method.<locals>.helper`
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 #[test]
 fn hover_over_inline_ignore_comment() {
     let code = r#"
@@ -210,6 +210,78 @@ Docstring Result: `Test docstring`
     );
 }
 
+#[test]
+fn attribute_variable_test() {
+    let code = r#"
+class Foo:
+    x: int = 0
+    """Test docstring"""
+print(Foo().x)
+#           ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], test_report_factory(code));
+    assert_eq!(
+        r#"
+# main.py
+5 | print(Foo().x)
+                ^
+Docstring Result: `Test docstring`
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn attribute_annotated_doc_test() {
+    let code = r#"
+from typing import Annotated
+from typing_extensions import Doc
+class Foo:
+    x: Annotated[int, Doc("Test docstring")]
+    y: Annotated[int, "not documentation"]
+print(Foo().x, Foo().y)
+#           ^        ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], test_report_factory(code));
+    assert_eq!(
+        r#"
+# main.py
+7 | print(Foo().x, Foo().y)
+                ^
+Docstring Result: `Test docstring`
+
+7 | print(Foo().x, Foo().y)
+                         ^
+Docstring Result: None
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn attribute_field_description_test() {
+    let code = r#"
+def Field(*, description: str) -> int: ...
+class Foo:
+    x: int = Field(description="Test docstring")
+print(Foo().x)
+#           ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], test_report_factory(code));
+    assert_eq!(
+        r#"
+# main.py
+5 | print(Foo().x)
+                ^
+Docstring Result: `Test docstring`
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 #[test]
 fn cross_module_function_test() {
     let lib = r#"
@@ -207,3 +207,36 @@ obj.method(5, "world")
         .trim()
     );
 }
+
+#[test]
+fn test_parameter_name_hints_partial() {
+    let code = r#"
+def f(x: int, y: int, /, z: int) -> None:
+    pass
+
+class C:
+    z: int = 0
+
+def g(x: int, c: C) -> None:
+    f(x, 1, x)
+    f(1, 2, c.z)
+"#;
+    assert_eq!(
+        r#"
+# main.py
+9 |     f(x, 1, x)
+                ^ inlay-hint: `z= `
+"#
+        .trim(),
+        generate_inlay_hint_report(
+            code,
+            InlayHintConfig {
+                call_argument_names: AllOffPartial::Partial,
+                variable_types: false,
+                function_return_types: false,
+                ..Default::default()
+            }
+        )
+        .trim()
+    );
+}
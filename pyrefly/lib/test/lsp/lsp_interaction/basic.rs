@@ -37,7 +37,7 @@ fn test_initialize_basic() {
                 "definitionProvider": true,
                 "typeDefinitionProvider": true,
                 "codeActionProvider": {
-                    "codeActionKinds": ["quickfix"]
+                    "codeActionKinds": ["quickfix", "refactor.rewrite", "refactor.extract", "refactor.inline", "source.organizeImports", "source.addTypeAnnotations"]
                 },
                 "completionProvider": {
                     "triggerCharacters": ["."]
@@ -47,9 +47,15 @@ fn test_initialize_basic() {
                     "triggerCharacters": ["(", ","]
                 },
                 "hoverProvider": true,
-                "inlayHintProvider": true,
+                "inlayHintProvider": {
+                    "resolveProvider": true
+                },
                 "documentSymbolProvider": true,
                 "foldingRangeProvider":true,
+                "selectionRangeProvider": true,
+                "documentLinkProvider": {
+                    "resolveProvider": false
+                },
                 "workspaceSymbolProvider": true,
                 "workspace": {
                     "workspaceFolders": {
@@ -65,6 +71,13 @@ fn test_initialize_basic() {
                                         "matches": "file"
                                     },
                                     "scheme": "file"
+                                },
+                                {
+                                    "pattern": {
+                                        "glob": "**",
+                                        "matches": "folder"
+                                    },
+                                    "scheme": "file"
                                 }
                             ]
                         }
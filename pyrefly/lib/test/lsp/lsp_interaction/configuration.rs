@@ -398,9 +398,9 @@ fn test_disable_specific_language_services_via_analysis_config() {
         result: Some(serde_json::json!({
             "contents": {
                 "kind":"markdown",
-                "value":"```python\n(class) Bar: type[Bar]\n```\n\nGo to [Bar](".to_owned()
+                "value":"```python\n(class) Bar: type[Bar]\n```\n\n`bar:Bar`\n\nGo to type\\[[Bar](".to_owned()
                     + Url::from_file_path(this_test_root.join("bar.py")).unwrap().as_str()
-                    + "#L7,7)"
+                    + "#L7,7)\\]"
             }
         })),
         error: None,
@@ -548,9 +548,9 @@ fn test_disable_type_errors_language_services_still_work() {
         result: Some(serde_json::json!({
             "contents": {
                 "kind":"markdown",
-                "value":"```python\n(class) Bar: type[Bar]\n```\n\nGo to [Bar](".to_owned()
+                "value":"```python\n(class) Bar: type[Bar]\n```\n\n`bar:Bar`\n\nGo to type\\[[Bar](".to_owned()
                     + Url::from_file_path(root_path.join("bar.py")).unwrap().as_str()
-                    + "#L7,7)"
+                    + "#L7,7)\\]"
             }
         })),
         error: None,
@@ -170,7 +170,7 @@ fn definition_in_builtins() {
     );
 }
 
-// todo(kylei): definition of an attribute of a pyi should still point to py
+// the attribute resolves into the pyi, so we map it back to the py and offer both
 #[test]
 fn definition_on_attr_of_pyi() {
     let root = get_test_files_root();
@@ -184,7 +184,35 @@ fn definition_on_attr_of_pyi() {
     interaction.server.definition(file, 7, 4);
     interaction
         .client
-        .expect_definition_response_from_root("attributes_of_py/lib.pyi", 6, 8, 6, 9);
+        .expect_definition_response_array_from_root(&[
+            ("attributes_of_py/lib.py", 7, 8, 7, 9),
+            ("attributes_of_py/lib.pyi", 6, 8, 6, 9),
+        ]);
+    interaction.shutdown();
+}
+
+#[test]
+fn definition_on_attr_of_pyi_prefer_stub() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(serde_json::json!([{
+            "analysis": {
+                "definitionPreference": "stub",
+            }
+        }]))),
+        ..Default::default()
+    });
+    let file = "attributes_of_py/src.py";
+    interaction.server.did_open(file);
+    interaction.server.definition(file, 7, 4);
+    interaction
+        .client
+        .expect_definition_response_array_from_root(&[
+            ("attributes_of_py/lib.pyi", 6, 8, 6, 9),
+            ("attributes_of_py/lib.py", 7, 8, 7, 9),
+        ]);
     interaction.shutdown();
 }
 
@@ -5,13 +5,72 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
 use lsp_server::RequestId;
 use lsp_server::Response;
+use lsp_types::Url;
 
 use crate::test::lsp::lsp_interaction::object_model::InitializeSettings;
 use crate::test::lsp::lsp_interaction::object_model::LspInteraction;
+use crate::test::lsp::lsp_interaction::object_model::ValidationResult;
 use crate::test::lsp::lsp_interaction::util::get_test_files_root;
 
+/// The file a `publishDiagnostics` notification is for, if `msg` is one.
+fn published_file(msg: &Message) -> Option<PathBuf> {
+    match msg {
+        Message::Notification(Notification { method, params })
+            if method == "textDocument/publishDiagnostics" =>
+        {
+            let path = Url::parse(params["uri"].as_str()?)
+                .ok()?
+                .to_file_path()
+                .ok()?;
+            Some(path.canonicalize().unwrap_or(path))
+        }
+        _ => None,
+    }
+}
+
+/// Expect diagnostics to be published for each of `files`, in that order, and for none of the
+/// other `open_files` in between.
+fn expect_published_in_order(interaction: &LspInteraction, files: &[&Path], open_files: &[&Path]) {
+    for file in files {
+        interaction.client.expect_message_helper(
+            |msg| match published_file(msg) {
+                Some(x) if x == *file => ValidationResult::Pass,
+                Some(x) if open_files.contains(&x.as_path()) => ValidationResult::Fail,
+                _ => ValidationResult::Skip,
+            },
+            &format!("diagnostics of {} next", file.display()),
+        );
+    }
+}
+
+/// Wait until diagnostics have been published for all of `files`, in any order.
+fn expect_published(interaction: &LspInteraction, files: &[&Path]) {
+    let pending = RefCell::new(files.iter().copied().collect::<HashSet<_>>());
+    interaction.client.expect_message_helper(
+        |msg| {
+            if let Some(x) = published_file(msg) {
+                pending.borrow_mut().remove(x.as_path());
+            }
+            if pending.borrow().is_empty() {
+                ValidationResult::Pass
+            } else {
+                ValidationResult::Skip
+            }
+        },
+        "diagnostics of all the files",
+    );
+}
+
 #[test]
 fn test_cycle_class() {
     let test_files_root = get_test_files_root();
@@ -69,6 +128,18 @@ fn test_unexpected_keyword_range() {
                         "end": {"character": 8, "line": 10},
                         "start": {"character": 5, "line": 10}
                     },
+                    "relatedInformation": [
+                        {
+                            "location": {
+                                "range": {
+                                    "end": {"character": 8, "line": 6},
+                                    "start": {"character": 4, "line": 6}
+                                },
+                                "uri": Url::from_file_path(test_files_root.path().join("unexpected_keyword.py")).unwrap().to_string()
+                            },
+                            "message": "`test` is defined here with signature `() -> None`"
+                        }
+                    ],
                     "severity": 1,
                     "source": "Pyrefly"
                 }
@@ -212,6 +283,43 @@ fn test_unreachable_branch_diagnostic() {
     interaction.shutdown();
 }
 
+#[test]
+fn test_unreachable_ranges_notification() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        capabilities: Some(serde_json::json!({
+            "experimental": {"unreachableRanges": true}
+        })),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("unreachable_branch.py");
+
+    interaction.client.expect_message_helper(
+        |msg| match msg {
+            Message::Notification(Notification { method, params })
+                if method == "pyrefly/textDocument/unreachableRanges" =>
+            {
+                assert_eq!(
+                    params.get("ranges"),
+                    Some(&serde_json::json!([{
+                        "start": {"line": 6, "character": 4},
+                        "end": {"line": 6, "character": 12}
+                    }]))
+                );
+                ValidationResult::Pass
+            }
+            _ => ValidationResult::Skip,
+        },
+        "unreachableRanges notification for unreachable_branch.py",
+    );
+
+    interaction.shutdown();
+}
+
 #[cfg(unix)]
 #[test]
 fn test_publish_diagnostics_preserves_symlink_uri() {
@@ -239,3 +347,390 @@ fn test_publish_diagnostics_preserves_symlink_uri() {
 
     interaction.shutdown();
 }
+
+#[test]
+fn test_pull_diagnostics_capability() {
+    let mut interaction = LspInteraction::new();
+    interaction
+        .server
+        .send_initialize(
+            interaction
+                .server
+                .get_initialize_params(&InitializeSettings {
+                    capabilities: Some(serde_json::json!({"textDocument": {"diagnostic": {}}})),
+                    ..Default::default()
+                }),
+        );
+    interaction.client.expect_response_with(
+        |response| {
+            response
+                .result
+                .as_ref()
+                .and_then(|x| x.get("capabilities"))
+                .and_then(|x| x.get("diagnosticProvider"))
+                == Some(&serde_json::json!({
+                    "identifier": "pyrefly",
+                    "interFileDependencies": true,
+                    "workspaceDiagnostics": true
+                }))
+        },
+        "initialize response advertising pull diagnostics",
+    );
+    interaction.server.send_initialized();
+    interaction.shutdown();
+}
+
+#[test]
+fn test_pull_diagnostics_unchanged() {
+    let test_files_root = get_test_files_root();
+    let uri = Url::from_file_path(test_files_root.path().join("cycle_class/foo.py")).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        capabilities: Some(serde_json::json!({"textDocument": {"diagnostic": {}}})),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("cycle_class/foo.py");
+    interaction.server.diagnostic("cycle_class/foo.py");
+
+    let result_id = RefCell::new(String::new());
+    interaction.client.expect_response_with(
+        |response| {
+            let Some(result) = &response.result else {
+                return false;
+            };
+            assert_eq!(result.get("kind"), Some(&serde_json::json!("full")));
+            assert_eq!(result.get("items"), Some(&serde_json::json!([])));
+            *result_id.borrow_mut() = result["resultId"].as_str().unwrap().to_owned();
+            response.id == RequestId::from(2)
+        },
+        "full diagnostic report with a result id",
+    );
+    let result_id = result_id.into_inner();
+
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(3),
+        method: "textDocument/diagnostic".to_owned(),
+        params: serde_json::json!({
+            "textDocument": {"uri": uri.to_string()},
+            "previousResultId": result_id,
+        }),
+    }));
+    interaction.client.expect_response(Response {
+        id: RequestId::from(3),
+        result: Some(serde_json::json!({"kind": "unchanged", "resultId": result_id})),
+        error: None,
+    });
+
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(4),
+        method: "workspace/diagnostic".to_owned(),
+        params: serde_json::json!({
+            "previousResultIds": [{"uri": uri.to_string(), "value": result_id}],
+        }),
+    }));
+    interaction.client.expect_response(Response {
+        id: RequestId::from(4),
+        result: Some(serde_json::json!({"items": [{
+            "kind": "unchanged",
+            "resultId": result_id,
+            "uri": uri.to_string(),
+            "version": 1
+        }]})),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_check_files() {
+    let test_files_root = get_test_files_root();
+    let type_errors = Url::from_file_path(test_files_root.path().join("type_errors.py")).unwrap();
+    let cycle_class =
+        Url::from_file_path(test_files_root.path().join("cycle_class/foo.py")).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+        )),
+        ..Default::default()
+    });
+
+    // Neither file is open, so they are checked with their contents on disk.
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(2),
+        method: "pyrefly/checkFiles".to_owned(),
+        params: serde_json::json!({
+            "uris": [type_errors.to_string(), cycle_class.to_string()],
+        }),
+    }));
+    interaction.client.expect_response_with(
+        |response| {
+            if response.id != RequestId::from(2) {
+                return false;
+            }
+            let items = &response.result.as_ref().unwrap()["items"];
+            assert_eq!(items[0]["uri"], serde_json::json!(type_errors.to_string()));
+            assert_eq!(
+                items[0]["diagnostics"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x["code"].as_str().unwrap())
+                    .collect::<Vec<_>>(),
+                vec!["unsupported-operation"]
+            );
+            assert_eq!(
+                items[1],
+                serde_json::json!({"uri": cycle_class.to_string(), "diagnostics": []})
+            );
+            true
+        },
+        "diagnostics of both files",
+    );
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_speculative_check() {
+    let test_files_root = get_test_files_root();
+    let type_errors = Url::from_file_path(test_files_root.path().join("type_errors.py")).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+        )),
+        ..Default::default()
+    });
+    interaction.server.did_open("type_errors.py");
+
+    // Fix the error on the last line, and add a new one above it.
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(2),
+        method: "pyrefly/textDocument/speculativeCheck".to_owned(),
+        params: serde_json::json!({
+            "textDocument": {"uri": type_errors.to_string()},
+            "edits": [
+                {
+                    "range": {"start": {"line": 5, "character": 4}, "end": {"line": 5, "character": 6}},
+                    "newText": "1",
+                },
+                {
+                    "range": {"start": {"line": 5, "character": 0}, "end": {"line": 5, "character": 0}},
+                    "newText": "x: str = 1\n",
+                },
+            ],
+        }),
+    }));
+    interaction.client.expect_response_with(
+        |response| {
+            if response.id != RequestId::from(2) {
+                return false;
+            }
+            let items = response.result.as_ref().unwrap()["items"]
+                .as_array()
+                .unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0]["uri"], serde_json::json!(type_errors.to_string()));
+            let codes = |key: &str| {
+                items[0][key]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x["code"].as_str().unwrap().to_owned())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(codes("added"), vec!["bad-assignment"]);
+            assert_eq!(codes("removed"), vec!["unsupported-operation"]);
+            true
+        },
+        "diagnostics added and removed by the edits",
+    );
+
+    // The document itself is unchanged.
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(3),
+        method: "textDocument/diagnostic".to_owned(),
+        params: serde_json::json!({"textDocument": {"uri": type_errors.to_string()}}),
+    }));
+    interaction.client.expect_response_with(
+        |response| {
+            if response.id != RequestId::from(3) {
+                return false;
+            }
+            let items = response.result.as_ref().unwrap()["items"]
+                .as_array()
+                .unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0]["code"], serde_json::json!("unsupported-operation"));
+            true
+        },
+        "diagnostics of the unchanged document",
+    );
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_check_snippet() {
+    let test_files_root = get_test_files_root();
+    let type_errors = Url::from_file_path(test_files_root.path().join("type_errors.py")).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+        )),
+        ..Default::default()
+    });
+    interaction.server.did_open("type_errors.py");
+
+    let check = |id: i32, range: serde_json::Value, snippet: &str| {
+        Message::Request(Request {
+            id: RequestId::from(id),
+            method: "pyrefly/textDocument/checkSnippet".to_owned(),
+            params: serde_json::json!({
+                "textDocument": {"uri": type_errors.to_string()},
+                "range": range,
+                "snippet": snippet,
+            }),
+        })
+    };
+
+    // Replacing the string of `1 + ""` with an int fixes the existing error, and adds none.
+    interaction.server.send_message(check(
+        2,
+        serde_json::json!({"start": {"line": 5, "character": 4}, "end": {"line": 5, "character": 6}}),
+        "1",
+    ));
+    interaction.client.expect_response_with(
+        |response| {
+            if response.id != RequestId::from(2) {
+                return false;
+            }
+            let result = response.result.as_ref().unwrap();
+            assert_eq!(result["typeChecks"], serde_json::json!(true));
+            assert_eq!(result["diagnostics"], serde_json::json!([]));
+            true
+        },
+        "a snippet that type-checks",
+    );
+
+    interaction.server.send_message(check(
+        3,
+        serde_json::json!({"start": {"line": 5, "character": 0}, "end": {"line": 5, "character": 0}}),
+        "x: str = 1\n",
+    ));
+    interaction.client.expect_response_with(
+        |response| {
+            if response.id != RequestId::from(3) {
+                return false;
+            }
+            let result = response.result.as_ref().unwrap();
+            assert_eq!(result["typeChecks"], serde_json::json!(false));
+            let diagnostics = result["diagnostics"].as_array().unwrap();
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0]["code"], serde_json::json!("bad-assignment"));
+            assert_eq!(
+                diagnostics[0]["range"]["start"]["line"],
+                serde_json::json!(5)
+            );
+            true
+        },
+        "a snippet that doesn't type-check",
+    );
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_focused_file_diagnostics_published_first() {
+    let test_files_root = get_test_files_root();
+    let root = test_files_root.path().canonicalize().unwrap();
+    let type_errors = root.join("type_errors.py");
+    let unexpected_keyword = root.join("unexpected_keyword.py");
+    let open_files = [type_errors.as_path(), unexpected_keyword.as_path()];
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+        )),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("type_errors.py");
+    expect_published_in_order(&interaction, &[&type_errors], &[]);
+    interaction.server.did_open("unexpected_keyword.py");
+    expect_published_in_order(
+        &interaction,
+        &[&unexpected_keyword, &type_errors],
+        &open_files,
+    );
+
+    // The edited file comes first, although it was opened last.
+    interaction
+        .server
+        .did_change("unexpected_keyword.py", "test = 1\n");
+    expect_published_in_order(
+        &interaction,
+        &[&unexpected_keyword, &type_errors],
+        &open_files,
+    );
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_visible_file_diagnostics_published_before_others() {
+    let test_files_root = get_test_files_root();
+    let root = test_files_root.path().canonicalize().unwrap();
+    let type_errors = root.join("type_errors.py");
+    let unexpected_keyword = root.join("unexpected_keyword.py");
+    let unreachable_branch = root.join("unreachable_branch.py");
+    let open_files = [
+        type_errors.as_path(),
+        unexpected_keyword.as_path(),
+        unreachable_branch.as_path(),
+    ];
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+        )),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("type_errors.py");
+    interaction.server.did_open("unexpected_keyword.py");
+    interaction.server.did_open("unreachable_branch.py");
+    expect_published_in_order(&interaction, &[&unreachable_branch], &[]);
+    expect_published(&interaction, &[&type_errors, &unexpected_keyword]);
+
+    // Asking for inlay hints shows the client has the file on screen.
+    interaction
+        .server
+        .inlay_hint("unexpected_keyword.py", 0, 0, 100, 0);
+    let id = interaction.server.current_request_id();
+    interaction
+        .client
+        .expect_response_with(|response| response.id == id, "inlay hints");
+
+    interaction
+        .server
+        .did_change("unreachable_branch.py", "x = 1\n");
+    expect_published_in_order(
+        &interaction,
+        &[&unreachable_branch, &unexpected_keyword, &type_errors],
+        &open_files,
+    );
+
+    interaction.shutdown();
+}
@@ -30,7 +30,7 @@ fn test_hover_basic() {
         result: Some(serde_json::json!({
             "contents": {
                 "kind": "markdown",
-                "value": "```python\n(variable) foo: Literal[3]\n```",
+                "value": "```python\n(variable) foo: Literal[3]\n```\n\n`bar:Bar.foo`",
             }
         })),
         error: None,
@@ -57,9 +57,96 @@ fn test_hover_import() {
         result: Some(serde_json::json!({
             "contents": {
                 "kind": "markdown",
-                "value": "```python\n(class) Bar: type[Bar]\n```\n\nGo to [Bar](".to_owned()
+                "value": "```python\n(class) Bar: type[Bar]\n```\n\n`bar:Bar`\n\nGo to type\\[[Bar](".to_owned()
                     + Url::from_file_path(root.path().join("basic/bar.py")).unwrap().as_str()
-                    + "#L7,7)",
+                    + "#L7,7)\\]",
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_hover_plaintext() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().join("basic"));
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        capabilities: Some(serde_json::json!({
+            "textDocument": {"hover": {"contentFormat": ["plaintext"]}}
+        })),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("foo.py");
+    interaction.server.hover("foo.py", 6, 16);
+
+    interaction.client.expect_response(Response {
+        id: interaction.server.current_request_id(),
+        result: Some(serde_json::json!({
+            "contents": {
+                "kind": "plaintext",
+                "value": "(class) Bar: type[Bar]\n\nbar:Bar\n\nGo to type[Bar]",
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_hover_links_each_named_type() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().join("type_links"));
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("main.py");
+    interaction.server.hover("main.py", 11, 0);
+
+    let lib = Url::from_file_path(root.path().join("type_links/lib.py")).unwrap();
+    interaction.client.expect_response(Response {
+        id: interaction.server.current_request_id(),
+        result: Some(serde_json::json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!(
+                    "```python\n(variable) x: Box[MyClass | Result] | None\n```\n\n`main:x`\n\nGo to [Box]({lib}#L11,7)\\[[MyClass]({lib}#L14,7) | [Result]({lib}#L17,7)\\] | None"
+                ),
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_hover_docstring_from_source_of_stub() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().join("docstring_of_py"));
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("src.py");
+    interaction.server.hover("src.py", 7, 4);
+
+    interaction.client.expect_response(Response {
+        id: interaction.server.current_request_id(),
+        result: Some(serde_json::json!({
+            "contents": {
+                "kind": "markdown",
+                "value": "```python\n(attribute) x: def x() -> None\n```\n\n`lib:Foo.x`\n---\nDo nothing.  \n  \n**Returns**  \n- `None`: Nothing at all.",
             }
         })),
         error: None,
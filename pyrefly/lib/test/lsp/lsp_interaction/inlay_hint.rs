@@ -6,6 +6,7 @@
  */
 
 use lsp_server::Response;
+use lsp_types::Url;
 
 use crate::test::lsp::lsp_interaction::object_model::InitializeSettings;
 use crate::test::lsp::lsp_interaction::object_model::LspInteraction;
@@ -14,6 +15,9 @@ use crate::test::lsp::lsp_interaction::util::get_test_files_root;
 #[test]
 fn test_inlay_hint_default_config() {
     let root = get_test_files_root();
+    let uri = Url::from_file_path(root.path().join("inlay_hint_test.py"))
+        .unwrap()
+        .to_string();
     let mut interaction = LspInteraction::new();
     interaction.set_root(root.path().to_path_buf());
     interaction.initialize(InitializeSettings {
@@ -35,7 +39,8 @@ fn test_inlay_hint_default_config() {
                 "textEdits":[{
                     "newText":" -> tuple[Literal[1], Literal[2]]",
                     "range":{"end":{"character":21,"line":6},"start":{"character":21,"line":6}}
-                }]
+                }],
+                "data": uri
             },
             {
                 "label":": tuple[Literal[1], Literal[2]]",
@@ -43,7 +48,8 @@ fn test_inlay_hint_default_config() {
                 "textEdits":[{
                     "newText":": tuple[Literal[1], Literal[2]]",
                     "range":{"end":{"character":6,"line":11},"start":{"character":6,"line":11}}
-                }]
+                }],
+                "data": uri
             },
             {
                 "label":" -> Literal[0]",
@@ -51,7 +57,8 @@ fn test_inlay_hint_default_config() {
                 "textEdits":[{
                     "newText":" -> Literal[0]",
                     "range":{"end":{"character":15,"line":14},"start":{"character":15,"line":14}}
-                }]
+                }],
+                "data": uri
             }
         ])),
         error: None,
@@ -97,6 +104,9 @@ fn test_inlay_hint_disable_all() {
 #[test]
 fn test_inlay_hint_disable_variables() {
     let root = get_test_files_root();
+    let uri = Url::from_file_path(root.path().join("inlay_hint_test.py"))
+        .unwrap()
+        .to_string();
     let mut interaction = LspInteraction::new();
     interaction.set_root(root.path().to_path_buf());
     interaction.initialize(InitializeSettings {
@@ -124,7 +134,8 @@ fn test_inlay_hint_disable_variables() {
             "textEdits":[{
                 "newText":" -> tuple[Literal[1], Literal[2]]",
                 "range":{"end":{"character":21,"line":6},"start":{"character":21,"line":6}}
-            }]
+            }],
+            "data": uri
         },
         {
             "label":" -> Literal[0]",
@@ -132,7 +143,62 @@ fn test_inlay_hint_disable_variables() {
             "textEdits":[{
                 "newText":" -> Literal[0]",
                 "range":{"end":{"character":15,"line":14},"start":{"character":15,"line":14}}
-            }]
+            }],
+            "data": uri
+        }])),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_inlay_hint_max_length() {
+    let root = get_test_files_root();
+    let uri = Url::from_file_path(root.path().join("inlay_hint_test.py"))
+        .unwrap()
+        .to_string();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(Some(serde_json::json!([{
+            "analysis": {
+                "inlayHints": {
+                    "variableTypes": false,
+                    "maxLength": 10
+                },
+            }
+        }]))),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("inlay_hint_test.py");
+
+    interaction
+        .server
+        .inlay_hint("inlay_hint_test.py", 0, 0, 100, 0);
+
+    interaction.client.expect_response(Response {
+        id: interaction.server.current_request_id(),
+        result: Some(serde_json::json!([{
+            "label":" -> tuple…",
+            "tooltip":"tuple[Literal[1], Literal[2]]",
+            "position":{"character":21,"line":6},
+            "textEdits":[{
+                "newText":" -> tuple[Literal[1], Literal[2]]",
+                "range":{"end":{"character":21,"line":6},"start":{"character":21,"line":6}}
+            }],
+            "data": uri
+        },
+        {
+            "label":" -> Liter…",
+            "tooltip":"Literal[0]",
+            "position":{"character":15,"line":14},
+            "textEdits":[{
+                "newText":" -> Literal[0]",
+                "range":{"end":{"character":15,"line":14},"start":{"character":15,"line":14}}
+            }],
+            "data": uri
         }])),
         error: None,
     });
@@ -143,6 +209,9 @@ fn test_inlay_hint_disable_variables() {
 #[test]
 fn test_inlay_hint_disable_returns() {
     let root = get_test_files_root();
+    let uri = Url::from_file_path(root.path().join("inlay_hint_test.py"))
+        .unwrap()
+        .to_string();
     let mut interaction = LspInteraction::new();
     interaction.set_root(root.path().to_path_buf());
     interaction.initialize(InitializeSettings {
@@ -170,10 +239,56 @@ fn test_inlay_hint_disable_returns() {
             "textEdits":[{
                 "newText":": tuple[Literal[1], Literal[2]]",
                 "range":{"end":{"character":6,"line":11},"start":{"character":6,"line":11}}
-            }]
+            }],
+            "data": uri
         }])),
         error: None,
     });
 
     interaction.shutdown();
 }
+
+#[test]
+fn test_inlay_hint_resolve_adds_imports() {
+    let root = get_test_files_root();
+    let uri = Url::from_file_path(root.path().join("inlay_hint_test.py"))
+        .unwrap()
+        .to_string();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction.initialize(InitializeSettings {
+        configuration: Some(None),
+        ..Default::default()
+    });
+
+    interaction.server.did_open("inlay_hint_test.py");
+    interaction.server.inlay_hint_resolve(serde_json::json!({
+        "label":" -> Literal[0]",
+        "position":{"character":15,"line":14},
+        "textEdits":[{
+            "newText":" -> Literal[0]",
+            "range":{"end":{"character":15,"line":14},"start":{"character":15,"line":14}}
+        }],
+        "data": uri
+    }));
+
+    interaction.client.expect_response(Response {
+        id: interaction.server.current_request_id(),
+        result: Some(serde_json::json!({
+            "label":" -> Literal[0]",
+            "position":{"character":15,"line":14},
+            "textEdits":[{
+                "newText":" -> Literal[0]",
+                "range":{"end":{"character":15,"line":14},"start":{"character":15,"line":14}}
+            },
+            {
+                "newText":"from typing import Literal\n",
+                "range":{"end":{"character":0,"line":6},"start":{"character":0,"line":6}}
+            }],
+            "data": uri
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
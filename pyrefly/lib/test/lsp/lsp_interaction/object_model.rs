@@ -35,6 +35,7 @@ use serde_json::Value;
 use crate::commands::lsp::IndexingMode;
 use crate::commands::lsp::LspArgs;
 use crate::commands::lsp::run_lsp;
+use crate::state::lsp::AllOffPartial;
 use crate::test::util::init_test;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -327,6 +328,15 @@ impl TestServer {
         }));
     }
 
+    pub fn inlay_hint_resolve(&mut self, hint: serde_json::Value) {
+        let id = self.next_request_id();
+        self.send_message(Message::Request(Request {
+            id,
+            method: "inlayHint/resolve".to_owned(),
+            params: hint,
+        }));
+    }
+
     pub fn send_configuration_response(&self, id: i32, result: serde_json::Value) {
         self.send_message(Message::Response(Response {
             id: RequestId::from(id),
@@ -685,6 +695,31 @@ impl TestClient {
         })
     }
 
+    /// Expect a definition response with several locations, each given as
+    /// `(file, line_start, char_start, line_end, char_end)`.
+    pub fn expect_definition_response_array_from_root(
+        &self,
+        definitions: &[(&'static str, u32, u32, u32, u32)],
+    ) {
+        let locations = definitions
+            .iter()
+            .map(|(file, line_start, char_start, line_end, char_end)| {
+                serde_json::json!({
+                    "uri": Url::from_file_path(self.get_root_or_panic().join(file)).unwrap().to_string(),
+                    "range": {
+                        "start": {"line": line_start, "character": char_start},
+                        "end": {"line": line_end, "character": char_end}
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        self.expect_response(Response {
+            id: RequestId::from(*self.request_idx.lock().unwrap()),
+            result: Some(serde_json::Value::Array(locations)),
+            error: None,
+        })
+    }
+
     pub fn expect_response_with<F>(&self, validator: F, description: &str)
     where
         F: Fn(&Response) -> bool,
@@ -858,6 +893,15 @@ impl LspInteraction {
         let args = LspArgs {
             indexing_mode,
             workspace_indexing_limit: 50,
+            port: None,
+            host: "127.0.0.1".to_owned(),
+            connect: false,
+            multi_client: false,
+            pipe: None,
+            inlay_hint_variable_types: true,
+            inlay_hint_function_return_types: true,
+            inlay_hint_call_argument_names: AllOffPartial::Off,
+            inlay_hint_max_length: None,
         };
         let connection = Connection {
             sender: language_client_sender,
@@ -6,13 +6,16 @@
  */
 
 use lsp_server::Message;
+use lsp_server::Notification;
 use lsp_server::Request;
 use lsp_server::RequestId;
 use lsp_server::Response;
 use lsp_types::Url;
 
+use crate::commands::lsp::IndexingMode;
 use crate::test::lsp::lsp_interaction::object_model::InitializeSettings;
 use crate::test::lsp::lsp_interaction::object_model::LspInteraction;
+use crate::test::lsp::lsp_interaction::object_model::ValidationResult;
 use crate::test::lsp::lsp_interaction::util::get_test_files_root;
 
 #[test]
@@ -147,3 +150,120 @@ fn test_rename() {
 
     interaction.shutdown();
 }
+
+fn rename_request(path: &std::path::Path, line: u32, character: u32, new_name: &str) -> Message {
+    Message::Request(Request {
+        id: RequestId::from(2),
+        method: "textDocument/rename".to_owned(),
+        params: serde_json::json!({
+            "textDocument": {
+                "uri": Url::from_file_path(path).unwrap().to_string()
+            },
+            "position": {
+                "line": line,
+                "character": character
+            },
+            "newName": new_name
+        }),
+    })
+}
+
+#[test]
+fn test_rename_files_that_are_not_open() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("basic");
+    let scope_uri = Url::from_file_path(&root_path).unwrap();
+    let mut interaction = LspInteraction::new_with_indexing_mode(IndexingMode::LazyBlocking);
+    interaction.set_root(root_path.clone());
+    interaction.initialize(InitializeSettings {
+        workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+        ..Default::default()
+    });
+
+    let bar = root_path.join("bar.py");
+    let foo = root_path.join("foo.py");
+    let foo_relative = root_path.join("foo_relative.py");
+
+    interaction.server.did_open("bar.py");
+    interaction
+        .server
+        .send_message(rename_request(&bar, 10, 1, "Baz"));
+
+    let edit = |line, start, end| {
+        serde_json::json!({
+            "newText": "Baz",
+            "range": {"start": {"line": line, "character": start}, "end": {"line": line, "character": end}}
+        })
+    };
+    interaction.client.expect_response(Response {
+        id: RequestId::from(2),
+        result: Some(serde_json::json!({
+            "changes": {
+                Url::from_file_path(&foo).unwrap().to_string():
+                    [edit(6, 16, 19), edit(8, 0, 3), edit(9, 4, 7)],
+                Url::from_file_path(&foo_relative).unwrap().to_string():
+                    [edit(6, 17, 20), edit(8, 0, 3), edit(9, 4, 7)],
+                Url::from_file_path(&bar).unwrap().to_string():
+                    [edit(6, 6, 9), edit(10, 0, 3)],
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_rename_without_indexing_warns() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("basic");
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction.initialize(InitializeSettings::default());
+
+    let bar = root_path.join("bar.py");
+
+    interaction.server.did_open("bar.py");
+    interaction
+        .server
+        .send_message(rename_request(&bar, 10, 1, "Baz"));
+
+    interaction.client.expect_message_helper(
+        |msg| match msg {
+            Message::Notification(Notification { method, params })
+                if method == "window/showMessage" =>
+            {
+                assert_eq!(
+                    params,
+                    &serde_json::json!({
+                        "type": 2,
+                        "message": "Indexing is disabled, so rename only updates open files and the files they import",
+                    })
+                );
+                ValidationResult::Pass
+            }
+            _ => ValidationResult::Skip,
+        },
+        "showMessage warning that rename is incomplete",
+    );
+    interaction.client.expect_response(Response {
+        id: RequestId::from(2),
+        result: Some(serde_json::json!({
+            "changes": {
+                Url::from_file_path(&bar).unwrap().to_string(): [
+                    {
+                        "newText": "Baz",
+                        "range": {"start": {"line": 6, "character": 6}, "end": {"line": 6, "character": 9}}
+                    },
+                    {
+                        "newText": "Baz",
+                        "range": {"start": {"line": 10, "character": 0}, "end": {"line": 10, "character": 3}}
+                    },
+                ]
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
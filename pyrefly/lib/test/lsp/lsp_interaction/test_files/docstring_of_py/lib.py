@@ -0,0 +1,13 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under the MIT license found in the
+# LICENSE file in the root directory of this source tree.
+
+
+class Foo:
+    def x() -> None:
+        """Do nothing.
+
+        Returns:
+            None: Nothing at all.
+        """
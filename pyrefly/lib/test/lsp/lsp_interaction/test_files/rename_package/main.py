@@ -0,0 +1,12 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under the MIT license found in the
+# LICENSE file in the root directory of this source tree.
+
+import pkg.utils
+from pkg import utils
+from pkg.core import helper
+
+
+def f() -> None:
+    from pkg.utils import helper
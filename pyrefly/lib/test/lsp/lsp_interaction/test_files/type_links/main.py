@@ -0,0 +1,12 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under the MIT license found in the
+# LICENSE file in the root directory of this source tree.
+
+from lib import Box, MyClass, Result
+
+
+def make() -> Box[MyClass | Result] | None: ...
+
+
+x = make()
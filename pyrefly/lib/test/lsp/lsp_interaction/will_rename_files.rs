@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::path::Path;
+
 use lsp_server::RequestId;
 use lsp_server::Response;
 use lsp_types::Url;
@@ -319,7 +321,8 @@ fn test_will_rename_files_without_config_with_workspace_folder() {
     // Send will_rename_files request to rename bar.py to baz.py
     interaction.server.will_rename_files(bar, "baz.py");
 
-    // Expect a response with edits to update imports in foo.py and foo_relative.py using "changes" format
+    // Expect a response with edits to update imports in foo.py and foo_relative.py using "changes" format,
+    // keeping `bar` bound in `from . import bar`
     interaction.client.expect_response(Response {
         id: RequestId::from(2),
         result: Some(serde_json::json!({
@@ -341,6 +344,13 @@ fn test_will_rename_files_without_config_with_workspace_folder() {
                     }
                 ],
                 Url::from_file_path(root_path.join("foo_relative.py")).unwrap().to_string(): [
+                    {
+                        "newText": "baz as bar",
+                        "range": {
+                            "start": {"line": 5, "character": 14},
+                            "end": {"line": 5, "character": 17}
+                        }
+                    },
                     {
                         "newText": "baz",
                         "range": {
@@ -456,3 +466,82 @@ fn test_will_rename_files_document_changes() {
 
     interaction.shutdown();
 }
+
+fn rename_package_interaction(root_path: &Path) -> LspInteraction {
+    let scope_uri = Url::from_file_path(root_path).unwrap();
+    let mut interaction = LspInteraction::new_with_indexing_mode(IndexingMode::LazyBlocking);
+    interaction.set_root(root_path.to_path_buf());
+    interaction.initialize(InitializeSettings {
+        workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+        ..Default::default()
+    });
+    interaction.server.did_open("main.py");
+    interaction
+}
+
+fn edit(new_text: &str, line: u32, start: u32, end: u32) -> serde_json::Value {
+    serde_json::json!({
+        "newText": new_text,
+        "range": {
+            "start": {"line": line, "character": start},
+            "end": {"line": line, "character": end}
+        }
+    })
+}
+
+#[test]
+fn test_will_rename_files_renames_package() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("rename_package");
+    let mut interaction = rename_package_interaction(&root_path);
+
+    interaction.server.will_rename_files("pkg", "renamed");
+
+    // The relative import in pkg/core.py still works, since pkg/utils.py moves with it
+    interaction.client.expect_response(Response {
+        id: RequestId::from(2),
+        result: Some(serde_json::json!({
+            "changes": {
+                Url::from_file_path(root_path.join("main.py")).unwrap().to_string(): [
+                    edit("renamed.utils", 5, 7, 16),
+                    edit("renamed", 6, 5, 8),
+                    edit("renamed.core", 7, 5, 13),
+                    edit("renamed.utils", 11, 9, 18),
+                ],
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_will_rename_files_moves_module_out_of_package() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("rename_package");
+    let mut interaction = rename_package_interaction(&root_path);
+
+    interaction
+        .server
+        .will_rename_files("pkg/utils.py", "helpers.py");
+
+    interaction.client.expect_response(Response {
+        id: RequestId::from(2),
+        result: Some(serde_json::json!({
+            "changes": {
+                Url::from_file_path(root_path.join("main.py")).unwrap().to_string(): [
+                    edit("helpers", 5, 7, 16),
+                    edit("import helpers as utils", 6, 0, 21),
+                    edit("helpers", 11, 9, 18),
+                ],
+                Url::from_file_path(root_path.join("pkg/core.py")).unwrap().to_string(): [
+                    edit("helpers", 5, 5, 11),
+                ],
+            }
+        })),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
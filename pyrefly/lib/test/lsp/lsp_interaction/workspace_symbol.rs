@@ -52,7 +52,54 @@ fn test_workspace_symbol() {
                     },
                     "uri": Url::from_file_path(root_path.join("autoimport_provider.py")).unwrap().to_string()
                 },
-                "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search"
+                "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search",
+                "containerName": "autoimport_provider"
+            }
+        ])),
+        error: None,
+    });
+
+    interaction.shutdown();
+}
+
+#[test]
+fn test_workspace_symbol_word_initials_without_open_files() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction.initialize(InitializeSettings {
+        workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+        configuration: Some(Some(
+            serde_json::json!([{ "indexing_mode": "lazy_blocking"}]),
+        )),
+        ..Default::default()
+    });
+
+    // The first letter of each word of the function name, found in the index of the workspace.
+    interaction.server.send_message(Message::Request(Request {
+        id: RequestId::from(2),
+        method: "workspace/symbol".to_owned(),
+        params: serde_json::json!({
+            "query": "tiavlfnswcdtawfs"
+        }),
+    }));
+
+    interaction.client.expect_response(Response {
+        id: RequestId::from(2),
+        result: Some(serde_json::json!([
+            {
+                "kind": 12,
+                "location": {
+                    "range": {
+                        "start": {"line": 6, "character": 4},
+                        "end": {"line": 6, "character": 99}
+                    },
+                    "uri": Url::from_file_path(root_path.join("autoimport_provider.py")).unwrap().to_string()
+                },
+                "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search",
+                "containerName": "autoimport_provider"
             }
         ])),
         error: None,
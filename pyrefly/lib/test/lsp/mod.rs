@@ -7,9 +7,12 @@
 
 #![cfg(test)]
 
+mod call_hierarchy;
 mod code_actions;
 mod completion;
 mod definition;
+mod document_highlight;
+mod document_links;
 mod document_symbols;
 mod folding_ranges;
 mod hover;
@@ -18,5 +21,8 @@ mod hover_type;
 mod inlay_hint;
 mod local_find_refs;
 mod lsp_interaction;
+mod move_symbol;
+mod selection_range;
 mod semantic_tokens;
 mod signature_help;
+mod type_hierarchy;
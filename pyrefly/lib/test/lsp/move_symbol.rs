@@ -0,0 +1,225 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::BTreeMap;
+
+use pretty_assertions::assert_eq;
+use ruff_text_size::TextSize;
+
+use crate::state::require::Require;
+use crate::test::util::mk_multi_file_state;
+
+/// Move `symbol` from module `from` to module `to`, and return the contents of every edited
+/// module after the edits are applied.
+fn move_symbol(
+    files: &[(&'static str, &str)],
+    from: &str,
+    symbol: &str,
+    to: &str,
+    leave_reexport: bool,
+) -> Option<BTreeMap<String, String>> {
+    let (handles, state) = mk_multi_file_state(files, Require::indexing(), false);
+    let transaction = state.transaction();
+    let source = files.iter().find(|(name, _)| *name == from).unwrap().1;
+    let position = TextSize::new(source.find(symbol).unwrap() as u32);
+    let edits = transaction.move_symbol(&handles[from], position, &handles[to], leave_reexport)?;
+    let mut results = BTreeMap::new();
+    for (i, (module, range, text)) in edits.iter().enumerate() {
+        results
+            .entry(module.name().as_str().to_owned())
+            .or_insert_with(|| (module.contents().as_str().to_owned(), Vec::new()))
+            .1
+            .push((range.start(), i, range, text));
+    }
+    Some(
+        results
+            .into_iter()
+            .map(|(name, (mut contents, mut edits))| {
+                // Apply back to front, so earlier offsets stay valid.
+                edits.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+                for (_, _, range, text) in edits {
+                    contents.replace_range(range.start().to_usize()..range.end().to_usize(), text);
+                }
+                (name, contents)
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn test_move_function() {
+    let a = r#"import os
+
+
+def helper() -> int:
+    return 1
+
+
+def moved() -> str:
+    return os.sep * helper()
+"#;
+    let results = move_symbol(
+        &[
+            ("a", a),
+            ("b", "x = 1\n"),
+            ("c", "from a import moved\n\nmoved()\n"),
+        ],
+        "a",
+        "moved",
+        "b",
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        results["a"],
+        r#"import os
+
+
+def helper() -> int:
+    return 1
+"#
+    );
+    assert_eq!(
+        results["b"],
+        r#"import os
+from a import helper
+x = 1
+
+
+def moved() -> str:
+    return os.sep * helper()
+"#
+    );
+    assert_eq!(results["c"], "from b import moved\n\nmoved()\n");
+}
+
+#[test]
+fn test_move_class_still_used() {
+    let a = r#"class C:
+    pass
+
+
+C()
+"#;
+    let results = move_symbol(&[("a", a), ("b", "")], "a", "C", "b", false).unwrap();
+    assert_eq!(results["a"], "from b import C\nC()\n");
+    assert_eq!(results["b"], "class C:\n    pass\n");
+}
+
+#[test]
+fn test_move_leave_reexport() {
+    let a = r#"def other():
+    pass
+
+
+def moved():
+    pass
+"#;
+    let results = move_symbol(
+        &[("a", a), ("b", ""), ("c", "from a import other, moved\n")],
+        "a",
+        "moved",
+        "b",
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        results["a"],
+        r#"def other():
+    pass
+
+
+def __getattr__(name: str) -> object:
+    if name == "moved":
+        import warnings
+
+        from b import moved
+
+        warnings.warn(
+            "`a.moved` has moved, import it from `b` instead",
+            DeprecationWarning,
+            stacklevel=2,
+        )
+        return moved
+    raise AttributeError(f"module {__name__!r} has no attribute {name!r}")
+"#
+    );
+    assert_eq!(results["b"], "def moved():\n    pass\n");
+    assert_eq!(results["c"], "from a import other\nfrom b import moved\n");
+}
+
+#[test]
+fn test_move_into_importer() {
+    let results = move_symbol(
+        &[
+            ("a", "def moved():\n    pass\n"),
+            ("b", "from a import moved\n\nmoved()\n"),
+        ],
+        "a",
+        "moved",
+        "b",
+        false,
+    )
+    .unwrap();
+    assert_eq!(results["a"], "");
+    assert_eq!(results["b"], "moved()\n\n\ndef moved():\n    pass\n");
+}
+
+#[test]
+fn test_move_name_clash() {
+    assert!(
+        move_symbol(
+            &[("a", "def moved(): ...\n"), ("b", "def moved(): ...\n")],
+            "a",
+            "moved",
+            "b",
+            false,
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_move_leave_reexport_still_used() {
+    assert!(
+        move_symbol(
+            &[("a", "def moved(): ...\n\nmoved()\n"), ("b", "")],
+            "a",
+            "moved",
+            "b",
+            true,
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_move_circular_import() {
+    let a = r#"def helper() -> int:
+    return 1
+
+
+def moved() -> int:
+    return helper()
+
+
+moved()
+"#;
+    // `b` would import `helper` from `a`, while `a` imports `moved` back from `b`.
+    assert!(move_symbol(&[("a", a), ("b", "")], "a", "moved", "b", false).is_none());
+    // Likewise if `b` already imports from `a`.
+    assert!(
+        move_symbol(
+            &[("a", "def moved(): ...\n\nmoved()\n"), ("b", "import a\n"),],
+            "a",
+            "moved",
+            "b",
+            false,
+        )
+        .is_none()
+    );
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::state::state::State;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let mut report = Vec::new();
+    for range in transaction
+        .selection_ranges(handle, &[position])
+        .unwrap()
+        .concat()
+    {
+        let lsp_range = module_info.lined_buffer().to_lsp_range(range);
+        let text = module_info.code_at(range);
+        let mut line = format!(
+            "{}:{}-{}:{}",
+            lsp_range.start.line,
+            lsp_range.start.character,
+            lsp_range.end.line,
+            lsp_range.end.character
+        );
+        if !text.contains('\n') {
+            line.push_str(&format!(" `{text}`"));
+        }
+        report.push(line);
+    }
+    report.join("\n")
+}
+
+#[test]
+fn expression_to_module_test() {
+    let code = r#"
+def f(x):
+    if x:
+        y = g(x + 1)
+#             ^
+        return y
+    return 0
+
+def g(x: int) -> int:
+    return x
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+4 |         y = g(x + 1)
+                  ^
+3:14-3:15 `x`
+3:14-3:19 `x + 1`
+3:13-3:20 `(x + 1)`
+3:12-3:20 `g(x + 1)`
+3:8-3:20 `y = g(x + 1)`
+3:8-5:16
+2:4-5:16
+2:4-6:12
+1:0-6:12
+1:0-9:12
+0:0-10:0
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn definition_name_test() {
+    let code = r#"
+class C:
+    def method(self):
+#           ^
+        pass
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 |     def method(self):
+                ^
+2:8-2:14 `method`
+2:4-4:12
+1:0-4:12
+0:0-5:0
+"#
+        .trim(),
+        report.trim(),
+    );
+}
@@ -78,7 +78,7 @@ line: 1, column: 5, length: 4, text: json
 token-type: namespace
 
 line: 1, column: 17, length: 7, text: decoder
-token-type: namespace
+token-type: namespace, token-modifiers: [defaultLibrary]
 "#,
     );
 }
@@ -192,10 +192,10 @@ else:
         r#"
 # main.py
 line: 1, column: 7, length: 3, text: sys
-token-type: namespace
+token-type: namespace, token-modifiers: [defaultLibrary]
 
 line: 3, column: 3, length: 3, text: sys
-token-type: namespace
+token-type: namespace, token-modifiers: [defaultLibrary]
 
 line: 3, column: 7, length: 12, text: version_info
 token-type: property
@@ -497,6 +497,169 @@ token-type: parameter
     );
 }
 
+#[test]
+fn type_param_class_and_alias_test() {
+    let code = r#"
+class Box[T]:
+    def get(self) -> T: ...
+
+type Pair[K] = tuple[K, K]
+"#;
+    assert_full_semantic_tokens(
+        &[("main", code)],
+        r#"
+# main.py
+line: 1, column: 6, length: 3, text: Box
+token-type: class
+
+line: 1, column: 10, length: 1, text: T
+token-type: typeParameter
+
+line: 2, column: 8, length: 3, text: get
+token-type: function
+
+line: 2, column: 12, length: 4, text: self
+token-type: parameter
+
+line: 2, column: 21, length: 1, text: T
+token-type: typeParameter
+
+line: 4, column: 5, length: 4, text: Pair
+token-type: interface
+
+line: 4, column: 10, length: 1, text: K
+token-type: typeParameter
+
+line: 4, column: 15, length: 5, text: tuple
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 4, column: 21, length: 1, text: K
+token-type: typeParameter
+
+line: 4, column: 24, length: 1, text: K
+token-type: typeParameter
+"#,
+    );
+}
+
+#[test]
+fn final_test() {
+    let code = r#"
+from typing import Final
+
+limit: Final = 10
+scale: Final[float] = 1.5
+
+def f() -> float:
+    local: Final = 2
+    return limit * scale * local
+"#;
+    assert_full_semantic_tokens(
+        &[("main", code)],
+        r#"
+# main.py
+line: 1, column: 5, length: 6, text: typing
+token-type: namespace
+
+line: 1, column: 19, length: 5, text: Final
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 3, column: 0, length: 5, text: limit
+token-type: variable, token-modifiers: [readonly]
+
+line: 3, column: 7, length: 5, text: Final
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 4, column: 0, length: 5, text: scale
+token-type: variable, token-modifiers: [readonly]
+
+line: 4, column: 7, length: 5, text: Final
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 4, column: 13, length: 5, text: float
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 6, column: 4, length: 1, text: f
+token-type: function
+
+line: 6, column: 11, length: 5, text: float
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 7, column: 4, length: 5, text: local
+token-type: variable, token-modifiers: [readonly]
+
+line: 7, column: 11, length: 5, text: Final
+token-type: class, token-modifiers: [defaultLibrary]
+
+line: 8, column: 11, length: 5, text: limit
+token-type: variable, token-modifiers: [readonly]
+
+line: 8, column: 19, length: 5, text: scale
+token-type: variable, token-modifiers: [readonly]
+
+line: 8, column: 27, length: 5, text: local
+token-type: variable, token-modifiers: [readonly]
+"#,
+    );
+}
+
+#[test]
+fn decorator_and_async_test() {
+    let code = r#"
+import functools
+from typing import final
+
+@final
+class C:
+    @functools.lru_cache(maxsize=None)
+    def f(self) -> None: ...
+
+async def g() -> None:
+    await g()
+"#;
+    assert_full_semantic_tokens(
+        &[("main", code)],
+        r#"
+# main.py
+line: 1, column: 7, length: 9, text: functools
+token-type: namespace, token-modifiers: [defaultLibrary]
+
+line: 2, column: 5, length: 6, text: typing
+token-type: namespace
+
+line: 2, column: 19, length: 5, text: final
+token-type: function, token-modifiers: [defaultLibrary]
+
+line: 4, column: 1, length: 5, text: final
+token-type: decorator, token-modifiers: [defaultLibrary]
+
+line: 5, column: 6, length: 1, text: C
+token-type: class
+
+line: 6, column: 5, length: 9, text: functools
+token-type: namespace, token-modifiers: [defaultLibrary]
+
+line: 6, column: 15, length: 9, text: lru_cache
+token-type: decorator
+
+line: 6, column: 25, length: 7, text: maxsize
+token-type: parameter
+
+line: 7, column: 8, length: 1, text: f
+token-type: function
+
+line: 7, column: 10, length: 4, text: self
+token-type: parameter
+
+line: 9, column: 10, length: 1, text: g
+token-type: function, token-modifiers: [async]
+
+line: 10, column: 10, length: 1, text: g
+token-type: function, token-modifiers: [async]
+"#,
+    );
+}
+
 #[test]
 fn control_flow_merge_test_try() {
     let code = r#"
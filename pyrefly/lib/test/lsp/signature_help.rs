@@ -281,7 +281,7 @@ Signature Help Result: active=0
 
 15 | overloaded_func(1, )
                        ^
-Signature Help Result: active=0
+Signature Help Result: active=1
 - (a: str) -> bool, parameters=[a: str]
 - (
     a: int,
@@ -344,7 +344,7 @@ Signature Help Result: active=0
 
 17 | foo.overloaded_meth(1, )
                             ^
-Signature Help Result: active=0
+Signature Help Result: active=1
 - (
     self: Foo,
     a: str
@@ -372,3 +372,138 @@ Signature Help Result: active=1
         report.trim(),
     );
 }
+
+#[test]
+fn keyword_argument_test() {
+    let code = r#"
+def f(a: str, b: int, *, c: bool, **kwargs: int) -> None: ...
+
+f("", c=True)
+#      ^
+f(c=True, b=3, )
+#           ^
+f(b=3, a="", )
+#            ^
+f("", d=1)
+#      ^
+f("", 3, True)
+#        ^
+"#;
+    let report = get_batched_lsp_operations_report_allow_error(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+4 | f("", c=True)
+           ^
+Signature Help Result: active=0
+- def f(
+    a: str,
+    b: int,
+    *,
+    c: bool,
+    **kwargs: int
+) -> None, parameters=[a: str, b: int, c: bool, **kwargs: int], active parameter = 2
+
+6 | f(c=True, b=3, )
+                ^
+Signature Help Result: active=0
+- def f(
+    a: str,
+    b: int,
+    *,
+    c: bool,
+    **kwargs: int
+) -> None, parameters=[a: str, b: int, c: bool, **kwargs: int], active parameter = 1
+
+8 | f(b=3, a="", )
+                 ^
+Signature Help Result: active=0
+- def f(
+    a: str,
+    b: int,
+    *,
+    c: bool,
+    **kwargs: int
+) -> None, parameters=[a: str, b: int, c: bool, **kwargs: int]
+
+10 | f("", d=1)
+            ^
+Signature Help Result: active=0
+- def f(
+    a: str,
+    b: int,
+    *,
+    c: bool,
+    **kwargs: int
+) -> None, parameters=[a: str, b: int, c: bool, **kwargs: int], active parameter = 3
+
+12 | f("", 3, True)
+              ^
+Signature Help Result: active=0
+- def f(
+    a: str,
+    b: int,
+    *,
+    c: bool,
+    **kwargs: int
+) -> None, parameters=[a: str, b: int, c: bool, **kwargs: int]
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn overloaded_function_keyword_argument_test() {
+    let code = r#"
+from typing import overload
+
+
+@overload
+def fetch(url: str, *, stream: bool) -> bytes: ...
+@overload
+def fetch(url: str, *, encoding: str) -> str: ...
+def fetch(url: str, **kwargs) -> bytes | str: ...
+
+
+fetch("", encoding=)
+#                  ^
+fetch("", stream=True)
+#                 ^
+"#;
+    let report = get_batched_lsp_operations_report_allow_error(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+12 | fetch("", encoding=)
+                        ^
+Signature Help Result: active=1
+- (
+    url: str,
+    *,
+    stream: bool
+) -> bytes, parameters=[url: str, stream: bool]
+- (
+    url: str,
+    *,
+    encoding: str
+) -> str, parameters=[url: str, encoding: str], active parameter = 1
+
+14 | fetch("", stream=True)
+                       ^
+Signature Help Result: active=0
+- (
+    url: str,
+    *,
+    stream: bool
+) -> bytes, parameters=[url: str, stream: bool], active parameter = 1
+- (
+    url: str,
+    *,
+    encoding: str
+) -> str, parameters=[url: str, encoding: str]
+"#
+        .trim(),
+        report.trim(),
+    );
+}
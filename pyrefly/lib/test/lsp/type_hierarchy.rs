@@ -0,0 +1,112 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::call_hierarchy::CallHierarchyTarget;
+use crate::state::lsp::type_hierarchy::subtypes;
+use crate::state::state::State;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn describe(target: &CallHierarchyTarget) -> String {
+    format!("{} in {}", target.name, target.module.name())
+}
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let mut report = Vec::new();
+    for target in transaction.prepare_type_hierarchy(handle, position) {
+        report.push(format!("Item: {}", describe(&target)));
+    }
+    report.push("Supertypes:".to_owned());
+    for target in transaction.supertypes(handle, position) {
+        report.push(describe(&target));
+    }
+    report.push("Subtypes:".to_owned());
+    // All the subclasses are in the same module in these tests.
+    let references = transaction.find_local_references(handle, position);
+    for target in subtypes(vec![(module_info.dupe(), references)]) {
+        report.push(describe(&target));
+    }
+    report.join("\n")
+}
+
+#[test]
+fn class_hierarchy_test() {
+    // `Generic` isn't a class in typeshed, so it isn't a supertype.
+    let code = r#"
+from typing import Generic, TypeVar
+T = TypeVar("T")
+
+class Base:
+    pass
+
+class Middle(Base, Generic[T]):
+#     ^
+    pass
+
+class Leaf(Middle[int]):
+    pass
+
+def f():
+    class Local(Middle):
+        pass
+    return Middle()
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+8 | class Middle(Base, Generic[T]):
+          ^
+Item: Middle in main
+Supertypes:
+Base in main
+Subtypes:
+Leaf in main
+Local in main
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn imported_base_test() {
+    let lib = r#"
+class A:
+    pass
+"#;
+    let code = r#"
+import lib
+
+class B(lib.A):
+#     ^
+    pass
+"#;
+    let report =
+        get_batched_lsp_operations_report(&[("lib", lib), ("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# lib.py
+
+# main.py
+4 | class B(lib.A):
+          ^
+Item: B in main
+Supertypes:
+A in lib
+Subtypes:
+"#
+        .trim(),
+        report.trim(),
+    );
+}
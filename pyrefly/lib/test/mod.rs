@@ -27,6 +27,7 @@ mod delayed_inference;
 mod descriptors;
 mod dict;
 mod django;
+mod embedded_code;
 mod enums;
 mod flow_branching;
 mod flow_looping;
@@ -37,6 +38,7 @@ mod generic_sub;
 mod imports;
 mod incremental;
 mod inference;
+mod library_mode;
 mod literal;
 mod lsp;
 mod mro;
@@ -46,17 +48,22 @@ mod natural;
 mod new_type;
 mod operators;
 mod overload;
+mod parameter_provider;
 mod paramspec;
 mod pattern_match;
 mod perf;
 mod protocol;
 mod pydantic;
 mod pysa;
+mod pytest;
+mod redefinition;
 mod redundant_cast;
 mod returns;
 mod scope;
+mod shadowing;
 mod simple;
 mod state;
+mod stub_consistency;
 mod subscript_narrow;
 mod suppression;
 mod sys_info;
@@ -67,6 +74,7 @@ mod type_var_tuple;
 mod typed_dict;
 mod typing_self;
 mod untyped_def_behaviors;
+mod unused_parameter;
 pub mod util;
 mod var_resolution;
 mod variance_inference;
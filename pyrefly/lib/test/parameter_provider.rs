@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Tests of the parameter providers for frameworks other than pytest, which is tested in
+//! `pytest.rs`.
+
+use crate::config::base::ParameterProviderKind;
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+fn env_fastapi() -> TestEnv {
+    let mut t = TestEnv::new().enable_parameter_provider(ParameterProviderKind::Fastapi);
+    t.add_with_path(
+        "fastapi",
+        "fastapi/__init__.pyi",
+        r#"
+from typing import Any, Callable
+def Depends(dependency: Callable[..., Any] | None = None, *, use_cache: bool = True) -> Any: ...
+def Security(dependency: Callable[..., Any] | None = None, *, scopes: list[str] | None = None) -> Any: ...
+"#,
+    );
+    t
+}
+
+fn env_click() -> TestEnv {
+    let mut t = TestEnv::new().enable_parameter_provider(ParameterProviderKind::Click);
+    t.add_with_path(
+        "click",
+        "click/__init__.pyi",
+        r#"
+from typing import Any, Callable, TypeVar
+_F = TypeVar("_F", bound=Callable[..., Any])
+def command(name: str | None = None) -> Callable[[_F], _F]: ...
+def group(name: str | None = None) -> Callable[[_F], _F]: ...
+def option(*param_decls: str, **attrs: Any) -> Callable[[_F], _F]: ...
+def argument(*param_decls: str, **attrs: Any) -> Callable[[_F], _F]: ...
+"#,
+    );
+    t
+}
+
+testcase!(
+    test_fastapi_depends,
+    env_fastapi(),
+    r#"
+from typing import Any, Iterator, assert_type
+import fastapi
+from fastapi import Depends, Security
+
+class Session: ...
+
+def get_session() -> Session:
+    return Session()
+
+def get_user_id() -> Iterator[int]:
+    yield 1
+
+async def get_token() -> str:
+    return ""
+
+class Settings: ...
+
+def read(
+    session = Depends(get_session),
+    user_id = fastapi.Depends(dependency=get_user_id),
+    token = Security(get_token),
+    settings = Depends(Settings),
+    other = Depends(),
+) -> None:
+    assert_type(session, Session)
+    assert_type(user_id, int)
+    assert_type(token, str)
+    assert_type(settings, Settings)
+    assert_type(other, Any)
+"#,
+);
+
+testcase!(
+    test_fastapi_disabled,
+    TestEnv::new(),
+    r#"
+from typing import Any, assert_type
+
+def Depends(f: object) -> Any: ...
+def get_session() -> int: ...
+
+def read(session = Depends(get_session)) -> None:
+    assert_type(session, Any)
+"#,
+);
+
+testcase!(
+    test_click_options,
+    env_click(),
+    r#"
+from pathlib import Path
+from typing import assert_type
+import click
+
+@click.command()
+@click.argument("src")
+@click.argument("paths", nargs=-1, type=Path)
+@click.option("-n", "--count", default=1)
+@click.option("--name")
+@click.option("--user-id", type=int, required=True)
+@click.option("--verbose", "-v", is_flag=True)
+@click.option("--shout/--no-shout")
+@click.option("-q", "quiet_level", count=True)
+@click.option("--tag", multiple=True)
+def main(src, paths, count, name, user_id, verbose, shout, quiet_level, tag):
+    assert_type(src, str)
+    assert_type(paths, tuple[Path, ...])
+    assert_type(count, int)
+    assert_type(name, str | None)
+    assert_type(user_id, int)
+    assert_type(verbose, bool)
+    assert_type(shout, bool)
+    assert_type(quiet_level, int)
+    assert_type(tag, tuple[str, ...])
+"#,
+);
+
+testcase!(
+    test_click_relaxed_annotations,
+    env_click().enable_implicit_any_error(),
+    r#"
+import click
+
+@click.command()
+@click.option("--count", default=1)
+def main(count):
+    return count
+
+def helper(x):  # E: `helper` is missing a return annotation # E: `helper` is missing an annotation for parameter `x`
+    return x
+"#,
+);
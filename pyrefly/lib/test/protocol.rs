@@ -629,3 +629,32 @@ class C:
 x: P = C() # OK
     "#,
 );
+
+testcase!(
+    test_callback_protocol_overloaded_and_generic_functions,
+    r#"
+from typing import Protocol, overload
+class P(Protocol):
+    def __call__(self, x: int) -> int: ...
+@overload
+def good(x: int) -> int: ...
+@overload
+def good(x: str) -> str: ...
+def good(x: int | str) -> int | str:
+    return x
+@overload
+def bad(x: str) -> str: ...
+@overload
+def bad(x: bytes) -> bytes: ...
+def bad(x: str | bytes) -> str | bytes:
+    return x
+def identity[T](x: T) -> T:
+    return x
+def to_list[T](x: T) -> list[T]:
+    return [x]
+p: P = good
+p: P = bad  # E: is not assignable to `P`
+p: P = identity
+p: P = to_list  # E: is not assignable to `P`
+    "#,
+);
@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Tests of pytest test files, which are recognized by their file names, so can't be written
+//! with `testcase!`.
+
+use std::path::PathBuf;
+
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_util::prelude::SliceExt;
+
+use crate::binding::parameter_provider::pytest::is_test_file;
+use crate::test::util::TestEnv;
+use crate::test::util::init_test;
+
+fn env_pytest() -> TestEnv {
+    let mut t = TestEnv::new();
+    t.add_with_path(
+        "pytest",
+        "pytest.pyi",
+        r#"
+from typing import Any, Callable, TypeVar, overload
+_F = TypeVar("_F", bound=Callable[..., Any])
+@overload
+def fixture(fixture_function: _F) -> _F: ...
+@overload
+def fixture(*, name: str | None = None, autouse: bool = False) -> Callable[[_F], _F]: ...
+def param(*values: object, id: str | None = None) -> Any: ...
+class _Mark:
+    def parametrize(self, argnames: Any, argvalues: Any, indirect: bool = False) -> Callable[[_F], _F]: ...
+mark: _Mark
+"#,
+    );
+    t
+}
+
+/// Check the given modules, each written to `<name>.py`, against their `# E:` comments.
+fn check_files(mut env: TestEnv, files: &[(&str, &str)]) -> anyhow::Result<()> {
+    init_test();
+    for (name, code) in files {
+        env.add_with_path(name, &format!("{name}.py"), code);
+    }
+    let (state, handle) = env.to_state();
+    let handles = files.map(|(name, _)| handle(name));
+    state
+        .transaction()
+        .get_errors(&handles)
+        .check_against_expectations()
+}
+
+#[test]
+fn test_is_test_file() {
+    let is_test = |path: &str| is_test_file(&ModulePath::memory(PathBuf::from(path)));
+    assert!(is_test("tests/test_math.py"));
+    assert!(is_test("math_test.py"));
+    assert!(is_test("tests/conftest.py"));
+    assert!(!is_test("math.py"));
+    assert!(!is_test("testing.py"));
+    assert!(!is_test("test_math.pyi"));
+}
+
+#[test]
+fn test_fixture_types() -> anyhow::Result<()> {
+    check_files(
+        env_pytest(),
+        &[(
+            "test_fixtures",
+            r#"
+from typing import assert_type
+import pytest
+
+@pytest.fixture
+def number():
+    return len("abc")
+
+@pytest.fixture
+def text():
+    yield "abc".upper()
+
+@pytest.fixture
+async def remote():
+    return len("abc")
+
+@pytest.fixture(name="numbers")
+def numbers_fixture(number):
+    return [number]
+
+def test_fixtures(number, text, remote, numbers):
+    assert_type(number, int)
+    assert_type(text, str)
+    assert_type(remote, int)
+    assert_type(numbers, list[int])
+    text.missing  # E: Object of class `str` has no attribute `missing`
+
+class TestClass:
+    def test_method(self, number):
+        assert_type(number, int)
+
+def helper(number):
+    # Not a test, so pytest doesn't pass it the fixture.
+    assert_type(number, int)  # E: assert_type
+"#,
+        )],
+    )
+}
+
+#[test]
+fn test_conftest_fixture() -> anyhow::Result<()> {
+    check_files(
+        env_pytest(),
+        &[
+            (
+                "conftest",
+                r#"
+from typing import Iterator
+import pytest
+
+@pytest.fixture
+def database() -> Iterator[dict[str, int]]:
+    yield {}
+"#,
+            ),
+            (
+                "test_database",
+                r#"
+from typing import assert_type
+
+def test_database(database):
+    assert_type(database, dict[str, int])
+"#,
+            ),
+        ],
+    )
+}
+
+#[test]
+fn test_parametrize() -> anyhow::Result<()> {
+    check_files(
+        env_pytest(),
+        &[(
+            "test_parametrize",
+            r#"
+from typing import assert_type
+import pytest
+
+@pytest.mark.parametrize("x", [1, 2, 3])
+def test_one(x):
+    assert_type(x, int)
+
+@pytest.mark.parametrize("x, y", [(1, "a"), pytest.param(2, "b", id="two")])
+def test_two(x, y):
+    assert_type(x, int)
+    assert_type(y, str)
+
+@pytest.mark.parametrize(("x", "y"), [(1, "a"), (None, "b")])
+def test_union(x, y):
+    assert_type(x, int | None)
+    x + 1  # E: `+` is not supported
+"#,
+        )],
+    )
+}
+
+#[test]
+fn test_relaxed_annotations() -> anyhow::Result<()> {
+    check_files(
+        env_pytest()
+            .enable_library_mode_errors()
+            .enable_implicit_any_error()
+            .enable_unused_parameter_error(),
+        &[(
+            "test_annotations",
+            r#"
+import pytest
+
+@pytest.fixture
+def number():
+    return len("abc")
+
+@pytest.fixture
+def setup():
+    yield
+
+def test_uses_fixtures(number, setup):
+    assert number > 0
+
+def test_unknown(unknown):  # E: `test_unknown` is missing an annotation for parameter `unknown` # E: Parameter `unknown` is never used
+    pass
+
+def helper(x: int):  # E: Public function `helper` is missing a return annotation
+    return x
+"#,
+        )],
+    )
+}
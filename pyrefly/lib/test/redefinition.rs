@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+testcase!(
+    test_redefinition_off_by_default,
+    r#"
+def f() -> int:
+    return 1
+
+f = 1
+"#,
+);
+
+testcase!(
+    test_redefinition,
+    TestEnv::new().enable_redefinition_error(),
+    r#"
+def f() -> int:
+    return 1
+
+f = 1  # E: `f` is redefined as a variable before the function it replaces is used
+
+class A: ...
+class A: ...  # E: `A` is redefined as a class before the class it replaces is used
+
+def g() -> None: ...
+g()
+def g() -> None: ...
+
+def h(x: int) -> int:
+    return x
+h = h
+
+x = 1
+def x() -> None: ...
+
+def cond() -> bool:
+    return True
+
+if cond():
+    def k() -> None: ...
+def k() -> None: ...
+"#,
+);
+
+testcase!(
+    test_redefinition_intended,
+    TestEnv::new().enable_redefinition_error(),
+    r#"
+from typing import overload
+
+@overload
+def o(x: int) -> int: ...
+@overload
+def o(x: str) -> str: ...
+def o(x: int | str) -> int | str:
+    return x
+
+class B:
+    @property
+    def p(self) -> int:
+        return 1
+    @p.setter
+    def p(self, v: int) -> None:
+        pass
+"#,
+);
+
+testcase!(
+    test_redefinition_in_function,
+    TestEnv::new().enable_redefinition_error(),
+    r#"
+def outer() -> None:
+    def inner() -> None: ...
+    inner = None  # E: `inner` is redefined as a variable before the function it replaces is used
+"#,
+);
+
+testcase!(
+    test_conditional_redefinition,
+    TestEnv::new().enable_conditional_redefinition_error(),
+    r#"
+def cond() -> bool:
+    return True
+
+if cond():
+    def f() -> None: ...
+else:
+    def f() -> None: ...
+
+if cond():
+    class G: ...
+else:
+    def G() -> None: ...  # E: `G` is defined as a function here, but as a class in another branch
+
+if cond():
+    def k() -> None: ...
+def k() -> None: ...  # E: `k` is redefined as a function before the function it replaces is used
+
+class A: ...
+class A: ...
+"#,
+);
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use crate::test::util::TestEnv;
 use crate::testcase;
 
 testcase!(
@@ -286,3 +287,52 @@ def f(x: str): pass
 return f(1) # E: Invalid `return` outside of a function # E: `Literal[1]` is not assignable to parameter `x` with type `str`
 "#,
 );
+
+testcase!(
+    test_missing_return_paths,
+    r#"
+import sys
+def f(x: int) -> int:  # E: paths are missing an explicit `return`\nExecution can reach the end of the function after line 9\nExecution can reach the end of the function after line 11
+    if x == 0:
+        return 0
+    elif x == 1:
+        sys.exit(1)
+    elif x == 2:
+        print(x)
+    else:
+        x += 1
+def g(b: bool) -> int:  # E: paths are missing an explicit `return`\nExecution can reach the end of the function after line 14
+    if b:
+        return 1
+"#,
+);
+
+testcase!(
+    test_generator_with_non_generator_annotation,
+    r#"
+def gen() -> list[int]:  # E: Generator function should return `Generator`\nThe function is a generator because of the `yield` on line 4
+    x = 1
+    yield x
+"#,
+);
+
+testcase!(
+    test_inconsistent_return,
+    TestEnv::new().enable_inconsistent_return_error(),
+    r#"
+def f(x: int):
+    if x > 0:
+        return x
+    return  # E: Bare `return` in a function that returns a value elsewhere, use `return None`
+
+def g(x: int):
+    if x > 0:
+        return
+    return None
+
+def h(x: int):
+    if x > 0:
+        return
+    yield x
+"#,
+);
@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+testcase!(
+    test_shadowed_name_off_by_default,
+    r#"
+def f() -> None:
+    list = 1
+"#,
+);
+
+testcase!(
+    test_shadowed_builtin,
+    TestEnv::new().enable_shadowed_name_error(),
+    r#"
+def f(items: list[int]) -> int:
+    sum = 0  # E: `sum` shadows a builtin
+    for i in items:
+        sum += i
+    return sum
+
+def g(list: int) -> int:
+    return list
+"#,
+);
+
+testcase!(
+    test_shadowed_enclosing_name,
+    TestEnv::new().enable_shadowed_name_error(),
+    r#"
+import os
+
+counter = 0
+
+def f() -> None:
+    os = 1  # E: `os` shadows a name from an enclosing scope
+    x = 1
+    def inner() -> None:
+        x = 2  # E: `x` shadows a name from an enclosing scope
+        print(x)
+    print(os, x)
+    inner()
+
+def g() -> None:
+    global counter
+    counter = 1
+
+class C:
+    y = 1
+    def m(self) -> int:
+        y = 2
+        return y
+"#,
+);
+
+testcase!(
+    test_shadowed_module_name_not_builtin,
+    TestEnv::new().enable_shadowed_name_error(),
+    r#"
+from json import *
+
+def sum(xs: list[int]) -> int:
+    return 0
+
+def f() -> None:
+    loads = 1  # E: `loads` shadows a name from an enclosing scope
+    sum = 0  # E: `sum` shadows a name from an enclosing scope
+    print(loads, sum)
+"#,
+);
+
+testcase!(
+    test_shadowed_import,
+    TestEnv::new().enable_shadowed_name_error(),
+    r#"
+from json import loads
+import os.path
+
+try:
+    from json import dumps
+except ImportError:
+    dumps = None
+
+loads = None  # E: `loads` shadows an import of the same name
+os = None  # E: `os` shadows an import of the same name
+
+def f() -> None:
+    from json import JSONDecoder
+    JSONDecoder = None  # E: `JSONDecoder` shadows an import of the same name
+"#,
+);
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use pyrefly_python::sys_info::PythonVersion;
+
 use crate::test::util::TestEnv;
 use crate::test::util::testcase_for_macro;
 use crate::testcase;
@@ -405,6 +407,40 @@ x = f"abc{f(1)}def"  # E: Argument `Literal[1]` is not assignable to parameter `
 "#,
 );
 
+testcase!(
+    test_tstring,
+    TestEnv::new_with_version(PythonVersion::new(3, 14, 0)),
+    r#"
+from string.templatelib import Template
+from typing import assert_type
+
+def f(x: str) -> str:
+    return x
+
+name = "world"
+assert_type(t"hello {name}", Template)
+t"abc{f(1)}def"  # E: Argument `Literal[1]` is not assignable to parameter `x` with type `str`
+"#,
+);
+
+testcase!(
+    test_tstring_before_3_14,
+    TestEnv::new_with_version(PythonVersion::new(3, 13, 0)),
+    r#"
+x = t"hello"  # E: Cannot use t-strings on Python 3.13
+"#,
+);
+
+testcase!(
+    test_nested_quote_fstring,
+    TestEnv::new_with_version(PythonVersion::new(3, 12, 0)),
+    r#"
+from typing import assert_type
+d = {"key": "value"}
+assert_type(f"{d["key"]}", str)
+"#,
+);
+
 testcase!(
     test_ternary_expression,
     r#"
@@ -762,6 +798,22 @@ def test(
 "#,
 );
 
+testcase!(
+    test_class_object_variable_in_annotations,
+    r#"
+def get_class() -> type[int]:
+    return int
+Cls = get_class()
+def f(
+    a: Cls,  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be used as a type
+    b: list[Cls],  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be used as a type
+) -> Cls:  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be used as a type
+    return 1
+class C(Cls):
+    x: "Cls"  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be used as a type
+"#,
+);
+
 testcase!(
     test_invalid_type_arguments,
     r#"
@@ -1759,13 +1811,15 @@ def f(condition: bool):
 testcase!(
     test_class_getitem_magic_dunder,
     r#"
-from typing import assert_type
+from typing import Self, assert_type
 
 class Foo:
     def __class_getitem__(cls, item: int) -> str:
+        assert_type(cls, type[Self])
         return str(item)
 
 assert_type(Foo[0], str)
+Foo[""]  # E: Argument `Literal['']` is not assignable to parameter `item` with type `int`
 "#,
 );
 
@@ -30,6 +30,7 @@ use crate::error::error::print_errors;
 use crate::module::finder::find_import;
 use crate::state::require::Require;
 use crate::state::state::State;
+use crate::state::steps::TEST_PANIC_MODULE;
 use crate::test::util::TestEnv;
 
 #[test]
@@ -279,3 +280,44 @@ fn test_sequential_committable_transactions() {
     let lock = counter.lock();
     assert_eq!(10, *lock);
 }
+
+#[test]
+fn test_collect_garbage() {
+    let mut t = TestEnv::new();
+    t.add("bar", "x = 1");
+    t.add("foo", "import bar");
+    t.add("baz", "y = 1");
+    let (state, handle) = t.to_state();
+    let foo = handle("foo");
+
+    // Everything was just checked, so it is all recent enough to keep.
+    assert_eq!(state.collect_garbage(std::slice::from_ref(&foo), 0), 0);
+
+    // Checking `foo` again moves to a new epoch, leaving `baz` unreachable and stale.
+    state.run(&[foo.dupe()], Require::Everything, Require::Exports, None);
+    assert_eq!(state.collect_garbage(std::slice::from_ref(&foo), 1), 0);
+    assert_eq!(state.collect_garbage(std::slice::from_ref(&foo), 0), 1);
+    let transaction = state.transaction();
+    assert!(transaction.get_module_info(&foo).is_some());
+    assert!(transaction.get_module_info(&handle("bar")).is_some());
+    assert!(transaction.get_module_info(&handle("baz")).is_none());
+}
+
+#[test]
+fn test_recover_from_panic() {
+    let mut t = TestEnv::new();
+    t.add(
+        TEST_PANIC_MODULE,
+        "x: int = 1  # E: Pyrefly crashed while computing Exports for `pyrefly_test_panic`",
+    );
+    t.add(
+        "main",
+        "from pyrefly_test_panic import x  # E: Could not import `x` from `pyrefly_test_panic`",
+    );
+    let (state, handle) = t.to_state();
+    state
+        .transaction()
+        .get_errors(&[handle(TEST_PANIC_MODULE), handle("main")])
+        .check_against_expectations()
+        .unwrap();
+}
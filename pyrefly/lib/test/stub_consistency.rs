@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+fn env_shapes() -> TestEnv {
+    let mut env = TestEnv::new().enable_inconsistent_stub_error();
+    env.add_with_path(
+        "shapes",
+        "shapes.pyi",
+        r#"
+from typing import Sequence
+class Shape:
+    def area(self) -> float: ...
+def make(size: int) -> Shape: ...
+def scale(shape: Shape, factor: float) -> Shape: ...
+def total(shapes: Sequence[Shape]) -> float: ...
+def describe(shape: Shape) -> str: ...
+version: str
+"#,
+    );
+    env.add_with_path(
+        "shapes",
+        "shapes.py",
+        r#"from typing import Iterable  # E: `describe` is declared in the stub but not defined in the implementation
+class Shape:
+    def area(self) -> float:
+        return 0.0
+def make(size: int) -> Shape:
+    return Shape()
+def scale(shape: Shape) -> Shape:  # E: `scale` has type `(shape: Shape) -> Shape` in the implementation, which is inconsistent with `(shape: Shape, factor: float) -> Shape` in the stub
+    return shape
+def total(shapes: Iterable[Shape]) -> float:
+    return sum(x.area() for x in shapes)
+version: int = 1  # E: `version` has type `int` in the implementation, which is inconsistent with `str` in the stub
+"#,
+    );
+    env
+}
+
+testcase!(
+    test_implementation_consistent_with_stub,
+    env_shapes(),
+    r#"
+from typing import assert_type
+from shapes import make, version, Shape
+assert_type(make(1), Shape)
+assert_type(version, str)
+"#,
+);
+
+fn env_version() -> TestEnv {
+    let mut env = TestEnv::new();
+    env.add_with_path("shapes", "shapes.pyi", "version: str");
+    env.add_with_path("shapes", "shapes.py", "version: int = 1");
+    env
+}
+
+testcase!(
+    test_implementation_consistent_with_stub_off_by_default,
+    env_version(),
+    r#"
+from typing import assert_type
+from shapes import version
+assert_type(version, str)
+"#,
+);
+
+fn env_overloads() -> TestEnv {
+    let mut env = TestEnv::new().enable_inconsistent_stub_error();
+    env.add_with_path(
+        "convert",
+        "convert.pyi",
+        r#"
+from typing import overload
+@overload
+def parse(x: str) -> int: ...
+@overload
+def parse(x: bytes) -> float: ...
+@overload
+def render(x: int) -> str: ...
+@overload
+def render(x: float) -> str: ...
+"#,
+    );
+    env.add_with_path(
+        "convert",
+        "convert.py",
+        r#"
+def parse(x: str | bytes) -> int | float:
+    return 0
+def render(x: int) -> str:  # E: `render` has type `(x: int) -> str` in the implementation, which doesn't satisfy the overload `(x: float) -> str` in the stub
+    return ""
+"#,
+    );
+    env
+}
+
+testcase!(
+    test_implementation_satisfies_stub_overloads,
+    env_overloads(),
+    r#"
+from typing import assert_type
+from convert import parse
+assert_type(parse(""), int)
+assert_type(parse(b""), float)
+"#,
+);
@@ -176,6 +176,62 @@ assert_type(y, Literal[''])
 "#,
 );
 
+fn env_settings() -> TestEnv {
+    let mut env = TestEnv::new();
+    env.add(
+        "settings",
+        r#"
+DEBUG = False
+BACKEND = "postgres"
+MAX_RETRIES: int = 3
+"#,
+    );
+    env
+}
+
+testcase!(
+    test_propagate_constants,
+    env_settings().enable_propagate_constants(),
+    r#"
+from typing import assert_type
+import settings
+from settings import BACKEND
+
+if settings.DEBUG:
+    X = str
+else:
+    X = int
+assert_type(X(), int)
+
+if BACKEND == "sqlite":
+    Y = str
+elif BACKEND == "postgres" and settings.MAX_RETRIES > 0:
+    Y = bytes
+else:
+    Y = int
+assert_type(Y(), bytes)
+
+if not settings.DEBUG:
+    Z = str
+assert_type(Z(), str)
+"#,
+);
+
+testcase!(
+    test_propagate_constants_disabled,
+    env_settings(),
+    r#"
+from typing import assert_type
+import settings
+
+if settings.DEBUG:
+    X = str
+else:
+    X = int
+assert_type(X(), str | int)
+"#,
+);
+
 testcase!(
     test_python_3_14,
     TestEnv::new_with_version(PythonVersion::new(3, 14, 0)),
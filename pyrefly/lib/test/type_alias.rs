@@ -610,6 +610,44 @@ bar: type[object] = get_class()
 "#,
 );
 
+testcase!(
+    test_variable_of_class_type_in_annotation,
+    r#"
+from typing import cast
+def get_class() -> type[int]:
+    return int
+
+Cls = get_class()
+x: Cls = 1  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be used as a type
+y = cast(Cls, 1)
+"#,
+);
+
+testcase!(
+    test_variable_of_class_type_in_type_alias,
+    r#"
+from typing import TypeAlias
+def get_class() -> type[int]:
+    return int
+
+Cls = get_class()
+X: TypeAlias = Cls  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be aliased
+type Y = Cls  # E: Variable `Cls` of type `type[int]` is not a type alias and cannot be aliased
+Z = Cls
+"#,
+);
+
+testcase!(
+    test_type_statement_redeclared,
+    r#"
+type A = int
+type A = str  # E: Type alias `A` is already declared
+class C:
+    type B = int
+    type B = int  # E: Type alias `B` is already declared
+"#,
+);
+
 testcase!(
     test_union_with_implicit_generic_alias,
     r#"
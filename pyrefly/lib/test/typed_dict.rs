@@ -85,6 +85,22 @@ foo({"x": 1, "y": 2})
     "#,
 );
 
+testcase!(
+    test_typed_dict_literal_multiline_ranges,
+    r#"
+from typing import TypedDict
+class Coord(TypedDict):
+    x: int
+    y: int
+    z: int
+
+c: Coord = {
+    "x": 1,
+    "y": "foo",  # E: `Literal['foo']` is not assignable to TypedDict key `y` with type `int`
+}  # E: Missing required key `z` for TypedDict `Coord`
+    "#,
+);
+
 testcase!(
     test_typed_dict_callable,
     r#"
@@ -323,7 +339,7 @@ def f(td: TD):
 testcase!(
     test_typed_dict_pop_2,
     r#"
-from typing import TypedDict, NotRequired, assert_type, Any
+from typing import TypedDict, NotRequired, assert_type
 
 class TDRequired(TypedDict):
     a: int
@@ -341,11 +357,11 @@ td_r: TDRequired = {"a": 10, "b": "hi"}
 td_o: TDOptional = {"x": 42}
 td_m: TDMixed = {"a": 1, "x": 99}
 
-v1 = td_r.pop("a") # E:
-assert_type(v1, object)
+v1 = td_r.pop("a") # E: Key `a` in TypedDict `TDRequired` may not be deleted
+assert_type(v1, int)
 
-v2 = td_r.pop("a", 3.14) # E:
-assert_type(v2, object)
+v2 = td_r.pop("a", 3.14) # E: Key `a` in TypedDict `TDRequired` may not be deleted
+assert_type(v2, int)
 
 v3 = td_o.pop("x")
 assert_type(v3, int)
@@ -356,8 +372,8 @@ assert_type(v4, int)
 v5 = td_o.pop("x", "fallback")
 assert_type(v5, int | str)
 
-v6 = td_m.pop("a") # E:
-assert_type(v6, Any)
+v6 = td_m.pop("a") # E: Key `a` in TypedDict `TDMixed` may not be deleted
+assert_type(v6, int)
 
 v7 = td_m.pop("x")
 assert_type(v7, int)
@@ -494,7 +510,7 @@ x |= y
 
 assert_type((x["a"]), int | str)
 
-x |= {} # E: Augmented assignment produces a value of type `dict[str, object]`, which is not assignable to `TypedDict[TD]`
+x |= {}
 
 x: TD = {"a": 1, "b": 2}
 x.__ior__(y)
@@ -503,6 +519,50 @@ x.__ior__({}) # E:  Missing required key `a` for TypedDict `TD` # E: Missing req
     "#,
 );
 
+testcase!(
+    test_typed_dict_merge_dict_literal,
+    r#"
+from typing import TypedDict, assert_type
+
+class TD(TypedDict):
+    a: int
+    b: str
+
+x: TD = {"a": 1, "b": "2"}
+assert_type(x | {"a": 2}, TD)
+assert_type(x | {}, TD)
+x |= {"b": "3"}
+assert_type(x, TD)
+x | {"a": "oops"}  # E: `Literal['oops']` is not assignable to TypedDict key `a` with type `int`
+x |= {"c": 1}  # E: Key `c` is not defined in TypedDict `TD`
+    "#,
+);
+
+testcase!(
+    test_typed_dict_merge_partial,
+    r#"
+from typing import TypedDict, assert_type
+
+class TD(TypedDict):
+    a: int
+    b: str
+
+class Update(TypedDict, total=False, closed=True):
+    a: int
+
+class BadUpdate(TypedDict, total=False, closed=True):
+    a: str
+
+def f(x: TD, update: Update, bad: BadUpdate):
+    assert_type(x | update, TD)
+    assert_type(x | {**update, "b": "2"}, TD)
+    x |= update
+    assert_type(x, TD)
+    x | {**bad}  # E: is not assignable to `Partial[TD]`
+    x | bad  # E: No matching overload found
+    "#,
+);
+
 testcase!(
     test_typed_dict_dunder_ror,
     r#"
@@ -813,6 +873,7 @@ def f(c: C, k1: str, k2: int):
     assert_type(c.get(k1), object | None)
     assert_type(c.get(k1, 0), int | object)
     c.get(k2)  # E: No matching overload
+    c.get("z")  # E: TypedDict `C` does not have key `z`
     "#,
 );
 
@@ -932,9 +993,9 @@ class D(TypedDict):
     x: int
     y: ReadOnly[str]
 def f(c: C, d: D):
-    c.setdefault("x", 0)  # E: `Literal['x']` is not assignable to parameter `k` with type `Never`
+    c.setdefault("x", 0)  # E: Key `x` in TypedDict `C` is read-only
     d.setdefault("x", 0)
-    d.setdefault("y", "oops")  # E: No matching overload
+    d.setdefault("y", "oops")  # E: Key `y` in TypedDict `D` is read-only
     "#,
 );
 
@@ -1700,7 +1761,7 @@ from typing import NotRequired, ReadOnly, TypedDict
 class A(TypedDict):
     x: NotRequired[ReadOnly[int]]
 def f(a: A):
-    a.pop('x')  # E: `Literal['x']` is not assignable to parameter `k` with type `Never`
+    a.pop('x')  # E: Key `x` in TypedDict `A` may not be deleted
     "#,
 );
 
@@ -1904,3 +1965,16 @@ t1: ClosedTarget = {**open}  # E: open TypedDict with unknown extra items
 t2: ExtraItemsTarget = {**open}  # E: open TypedDict with unknown extra items
     "#,
 );
+
+testcase!(
+    test_typed_dict_literal_optional_hint,
+    r#"
+from typing import TypedDict
+class TD(TypedDict):
+    x: int
+    y: str
+def f(td: TD | None) -> None: ...
+f({"x": 1, "y": 2})  # E: `Literal[2]` is not assignable to TypedDict key `y` with type `str`
+x: TD | None = {"x": 1, "y": "", "z": ""}  # E: Key `z` is not defined in TypedDict `TD`
+    "#,
+);
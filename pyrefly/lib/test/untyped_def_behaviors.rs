@@ -273,3 +273,35 @@ class C:
 assert_type(C(42).x, Any)
 "#,
 );
+
+fn env_untyped_package() -> TestEnv {
+    let mut env = TestEnv::new_with_site_package_path("site-packages");
+    env.add_with_path(
+        "lib",
+        "site-packages/lib.py",
+        r#"
+def scale(x, factor=2, name=None):
+    return x * factor
+"#,
+    );
+    env
+}
+
+testcase!(
+    test_untyped_package_parameters_with_defaults,
+    env_untyped_package(),
+    r#"
+from lib import scale
+scale(1, factor="2", name="a")
+"#,
+);
+
+testcase!(
+    test_infer_untyped_package_signatures,
+    env_untyped_package().enable_infer_untyped_package_signatures(),
+    r#"
+from lib import scale
+scale(1, factor="2")  # E: Argument `Literal['2']` is not assignable to parameter `factor` with type `int`
+scale(1, name="a")
+"#,
+);
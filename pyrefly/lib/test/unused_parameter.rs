@@ -0,0 +1,80 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::test::util::TestEnv;
+use crate::testcase;
+
+testcase!(
+    test_unused_parameter_off_by_default,
+    r#"
+def f(x: int) -> None:
+    pass
+"#,
+);
+
+testcase!(
+    test_unused_parameter,
+    TestEnv::new().enable_unused_parameter_error(),
+    r#"
+def f(x: int, y: int, _z: int) -> int:  # E: Parameter `y` is never used
+    return x
+
+def g(x: int, *args: int, **kwargs: int) -> int:  # E: Parameter `args` is never used  # E: Parameter `kwargs` is never used
+    x = 1
+    return x
+
+def stub(x: int) -> int: ...
+"#,
+);
+
+testcase!(
+    test_unused_parameter_nested_use,
+    TestEnv::new().enable_unused_parameter_error(),
+    r#"
+def f(x: int) -> int:
+    def inner() -> int:
+        return x
+    return inner()
+
+def g(x: int) -> dict[str, object]:
+    return locals()
+"#,
+);
+
+testcase!(
+    test_unused_parameter_methods,
+    TestEnv::new().enable_unused_parameter_error(),
+    r#"
+from abc import ABC, abstractmethod
+
+class A(ABC):
+    def method(self, x: int) -> None:  # E: Parameter `x` is never used
+        pass
+
+    @classmethod
+    def create(cls) -> None:
+        pass
+
+    @staticmethod
+    def helper(x: int) -> None:  # E: Parameter `x` is never used
+        pass
+
+    @abstractmethod
+    def abstract(self, x: int) -> None:
+        pass
+
+    def __exit__(self, exc_type: object, exc: object, tb: object) -> None:
+        pass
+
+class B(A):
+    def method(self, x: int) -> None:
+        pass
+
+    def abstract(self, x: int) -> None:
+        pass
+"#,
+);
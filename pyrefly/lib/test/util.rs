@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
@@ -42,9 +43,13 @@ use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
 use crate::binding::binding::KeyExport;
+use crate::config::base::EmbeddedCodeKind;
+use crate::config::base::ParameterProviderKind;
+use crate::config::base::ReexportMode;
 use crate::config::base::UntypedDefBehavior;
 use crate::config::config::ConfigFile;
 use crate::config::finder::ConfigFinder;
+use crate::config::import_boundaries::ImportBoundaries;
 use crate::error::error::print_errors;
 use crate::module::finder::find_import;
 use crate::state::errors::Errors;
@@ -102,11 +107,28 @@ pub struct TestEnv {
     version: PythonVersion,
     untyped_def_behavior: UntypedDefBehavior,
     infer_with_first_use: bool,
+    propagate_constants: bool,
+    reexport: ReexportMode,
+    embedded_code: Vec<EmbeddedCodeKind>,
+    parameter_providers: Vec<ParameterProviderKind>,
+    base_class_factories: BTreeMap<String, String>,
+    infer_untyped_package_signatures: bool,
+    import_boundaries: ImportBoundaries,
     site_package_path: Vec<PathBuf>,
     implicitly_defined_attribute_error: bool,
     implicit_any_error: bool,
     implicit_abstract_class_error: bool,
     open_unpacking_error: bool,
+    unused_parameter_error: bool,
+    shadowed_name_error: bool,
+    inconsistent_return_error: bool,
+    inconsistent_stub_error: bool,
+    unmanaged_resource_error: bool,
+    possibly_missing_attribute_error: bool,
+    non_exhaustive_enum_dict_error: bool,
+    redefinition_error: bool,
+    conditional_redefinition_error: bool,
+    library_mode_errors: bool,
     default_require_level: Require,
 }
 
@@ -119,11 +141,28 @@ impl TestEnv {
             version: PythonVersion::default(),
             untyped_def_behavior: UntypedDefBehavior::default(),
             infer_with_first_use: true,
+            propagate_constants: false,
+            reexport: ReexportMode::default(),
+            embedded_code: Vec::new(),
+            parameter_providers: vec![ParameterProviderKind::Pytest],
+            base_class_factories: BTreeMap::new(),
+            infer_untyped_package_signatures: false,
+            import_boundaries: ImportBoundaries::default(),
             site_package_path: Vec::new(),
             implicitly_defined_attribute_error: false,
             implicit_any_error: false,
             implicit_abstract_class_error: false,
             open_unpacking_error: false,
+            unused_parameter_error: false,
+            shadowed_name_error: false,
+            inconsistent_return_error: false,
+            inconsistent_stub_error: false,
+            unmanaged_resource_error: false,
+            possibly_missing_attribute_error: false,
+            non_exhaustive_enum_dict_error: false,
+            redefinition_error: false,
+            conditional_redefinition_error: false,
+            library_mode_errors: false,
             default_require_level: Require::Exports,
         }
     }
@@ -157,6 +196,42 @@ impl TestEnv {
         self
     }
 
+    pub fn enable_propagate_constants(mut self) -> Self {
+        self.propagate_constants = true;
+        self
+    }
+
+    pub fn enable_explicit_reexport(mut self) -> Self {
+        self.reexport = ReexportMode::Explicit;
+        self
+    }
+
+    pub fn enable_embedded_code(mut self, kind: EmbeddedCodeKind) -> Self {
+        self.embedded_code.push(kind);
+        self
+    }
+
+    pub fn enable_parameter_provider(mut self, kind: ParameterProviderKind) -> Self {
+        self.parameter_providers.push(kind);
+        self
+    }
+
+    pub fn with_base_class_factory(mut self, factory: &str, base: &str) -> Self {
+        self.base_class_factories
+            .insert(factory.to_owned(), base.to_owned());
+        self
+    }
+
+    pub fn enable_infer_untyped_package_signatures(mut self) -> Self {
+        self.infer_untyped_package_signatures = true;
+        self
+    }
+
+    pub fn with_import_boundaries(mut self, import_boundaries: ImportBoundaries) -> Self {
+        self.import_boundaries = import_boundaries;
+        self
+    }
+
     pub fn enable_implicit_any_error(mut self) -> Self {
         self.implicit_any_error = true;
         self
@@ -172,6 +247,56 @@ impl TestEnv {
         self
     }
 
+    pub fn enable_unused_parameter_error(mut self) -> Self {
+        self.unused_parameter_error = true;
+        self
+    }
+
+    pub fn enable_shadowed_name_error(mut self) -> Self {
+        self.shadowed_name_error = true;
+        self
+    }
+
+    pub fn enable_inconsistent_return_error(mut self) -> Self {
+        self.inconsistent_return_error = true;
+        self
+    }
+
+    pub fn enable_inconsistent_stub_error(mut self) -> Self {
+        self.inconsistent_stub_error = true;
+        self
+    }
+
+    pub fn enable_unmanaged_resource_error(mut self) -> Self {
+        self.unmanaged_resource_error = true;
+        self
+    }
+
+    pub fn enable_possibly_missing_attribute_error(mut self) -> Self {
+        self.possibly_missing_attribute_error = true;
+        self
+    }
+
+    pub fn enable_non_exhaustive_enum_dict_error(mut self) -> Self {
+        self.non_exhaustive_enum_dict_error = true;
+        self
+    }
+
+    pub fn enable_redefinition_error(mut self) -> Self {
+        self.redefinition_error = true;
+        self
+    }
+
+    pub fn enable_conditional_redefinition_error(mut self) -> Self {
+        self.conditional_redefinition_error = true;
+        self
+    }
+
+    pub fn enable_library_mode_errors(mut self) -> Self {
+        self.library_mode_errors = true;
+        self
+    }
+
     pub fn with_default_require_level(mut self, level: Require) -> Self {
         self.default_require_level = level;
         self
@@ -240,6 +365,13 @@ impl TestEnv {
         config.python_environment.site_package_path = Some(self.site_package_path.clone());
         config.root.untyped_def_behavior = Some(self.untyped_def_behavior);
         config.root.infer_with_first_use = Some(self.infer_with_first_use);
+        config.root.propagate_constants = Some(self.propagate_constants);
+        config.root.reexport = Some(self.reexport);
+        config.root.embedded_code = Some(self.embedded_code.clone());
+        config.root.parameter_providers = Some(self.parameter_providers.clone());
+        config.root.base_class_factories = Some(self.base_class_factories.clone());
+        config.root.infer_untyped_package_signatures = Some(self.infer_untyped_package_signatures);
+        config.import_boundaries = self.import_boundaries.clone();
         if config.root.errors.is_none() {
             config.root.errors = Some(ErrorDisplayConfig::new(HashMap::new()));
         };
@@ -256,6 +388,38 @@ impl TestEnv {
         if self.open_unpacking_error {
             errors.set_error_severity(ErrorKind::OpenUnpacking, Severity::Error);
         }
+        if self.unused_parameter_error {
+            errors.set_error_severity(ErrorKind::UnusedParameter, Severity::Error);
+        }
+        if self.shadowed_name_error {
+            errors.set_error_severity(ErrorKind::ShadowedName, Severity::Error);
+        }
+        if self.inconsistent_return_error {
+            errors.set_error_severity(ErrorKind::InconsistentReturn, Severity::Error);
+        }
+        if self.inconsistent_stub_error {
+            errors.set_error_severity(ErrorKind::InconsistentStub, Severity::Error);
+        }
+        if self.unmanaged_resource_error {
+            errors.set_error_severity(ErrorKind::UnmanagedResource, Severity::Error);
+        }
+        if self.possibly_missing_attribute_error {
+            errors.set_error_severity(ErrorKind::PossiblyMissingAttribute, Severity::Error);
+        }
+        if self.non_exhaustive_enum_dict_error {
+            errors.set_error_severity(ErrorKind::NonExhaustiveEnumDict, Severity::Error);
+        }
+        if self.redefinition_error {
+            errors.set_error_severity(ErrorKind::Redefinition, Severity::Error);
+        }
+        if self.conditional_redefinition_error {
+            errors.set_error_severity(ErrorKind::ConditionalRedefinition, Severity::Error);
+        }
+        if self.library_mode_errors {
+            for kind in ErrorKind::LIBRARY_MODE {
+                errors.set_error_severity(kind, Severity::Error);
+            }
+        }
         let mut sourcedb = MapDatabase::new(config.get_sys_info());
         for (name, path, _) in self.modules.iter() {
             sourcedb.insert(*name, path.dupe());
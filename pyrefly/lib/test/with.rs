@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use crate::test::util::TestEnv;
 use crate::testcase;
 
 testcase!(
@@ -109,7 +110,7 @@ from typing import assert_type
 class Foo:
     def __enter__(self) -> int:
         ...
-    def __exit__(self) -> None:
+    def __exit__(self) -> None:  # E: `__exit__` should accept three positional arguments besides `self`
         ...
 
 with Foo() as foo:  # E: Expected 0 positional arguments, got 3
@@ -159,7 +160,7 @@ testcase!(
     r#"
 from types import TracebackType
 class Foo:
-    def __aenter__(self) -> int:
+    def __aenter__(self) -> int:  # E: `__aenter__` should return an awaitable
         ...
     async def __aexit__(
         self,
@@ -183,7 +184,7 @@ from types import TracebackType
 class Foo:
     async def __aenter__(self) -> int:
         ...
-    def __aexit__(
+    def __aexit__(  # E: `__aexit__` should return an awaitable
         self,
         exc_type: type[BaseException] | None,
         exc_value: BaseException | None,
@@ -323,3 +324,35 @@ with Foo() as foo:
     assert_type(foo, int)
     "#,
 );
+
+testcase!(
+    test_context_manager_method_signatures,
+    r#"
+from types import TracebackType
+from typing import Any
+class Good:
+    def __enter__(self, verbose: bool = False) -> None: ...
+    def __exit__(self, *args: Any) -> None: ...
+class Bad:
+    def __enter__(self, x: int) -> None: ...  # E: `__enter__` should accept no arguments besides `self`
+    def __exit__(self, exc_type: type[BaseException] | None) -> None: ...  # E: `__exit__` should accept three positional arguments
+class BadAsync:
+    async def __enter__(self) -> None: ...  # E: `__enter__` should not be async, since `with` does not await it, did you mean `__aenter__`?
+    async def __aexit__(self, a: Any, b: Any, c: Any, *, d: int) -> None: ...  # E: `__aexit__` should accept three positional arguments
+"#,
+);
+
+testcase!(
+    test_unmanaged_resource,
+    TestEnv::new().enable_unmanaged_resource_error(),
+    r#"
+import threading
+text = open("data.txt").read()  # E: Result of `open()` is never closed or released
+threading.Lock().acquire()  # E: Result of `threading.Lock()` is never closed or released
+first = open("data.txt").readline().strip().upper()  # E: Result of `open()` is never closed or released
+with open("data.txt") as f:
+    text = f.read()
+f = open("data.txt")
+f.close()
+"#,
+);
@@ -80,6 +80,11 @@ let docsSidebar = [
         id: 'pydantic',
         label: 'Pydantic Support',
     },
+    {
+        type: 'doc' as const,
+        id: 'pytest',
+        label: 'pytest Support',
+    },
     {
         type: 'doc' as const,
         id: 'autotype',